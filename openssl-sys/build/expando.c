@@ -35,6 +35,10 @@ RUST_CONF_OPENSSL_NO_CMS
 RUST_CONF_OPENSSL_NO_COMP
 #endif
 
+#ifdef OPENSSL_NO_COMP_ALG
+RUST_CONF_OPENSSL_NO_COMP_ALG
+#endif
+
 #ifdef OPENSSL_NO_EC
 RUST_CONF_OPENSSL_NO_EC
 #endif
@@ -63,6 +67,10 @@ RUST_CONF_OPENSSL_NO_OCSP
 RUST_CONF_OPENSSL_NO_PSK
 #endif
 
+#ifdef OPENSSL_NO_QUIC
+RUST_CONF_OPENSSL_NO_QUIC
+#endif
+
 #ifdef OPENSSL_NO_RFC3779
 RUST_CONF_OPENSSL_NO_RFC3779
 #endif
@@ -83,6 +91,10 @@ RUST_CONF_OPENSSL_NO_SRP
 RUST_CONF_OPENSSL_NO_SSL3_METHOD
 #endif
 
+#ifdef OPENSSL_NO_SSL_TRACE
+RUST_CONF_OPENSSL_NO_SSL_TRACE
+#endif
+
 #ifdef OPENSSL_NO_TLSEXT
 RUST_CONF_OPENSSL_NO_TLSEXT
 #endif
@@ -0,0 +1,61 @@
+use libc::*;
+use std::ptr;
+
+use *;
+
+cfg_if! {
+    if #[cfg(ossl300)] {
+        pub const OSSL_CMP_IR: c_int = 0;
+        pub const OSSL_CMP_CR: c_int = 2;
+        pub const OSSL_CMP_P10CR: c_int = 4;
+        pub const OSSL_CMP_KUR: c_int = 7;
+
+        pub const OSSL_CMP_PKISTATUS_accepted: c_int = 0;
+        pub const OSSL_CMP_PKISTATUS_grantedWithMods: c_int = 1;
+        pub const OSSL_CMP_PKISTATUS_rejection: c_int = 2;
+        pub const OSSL_CMP_PKISTATUS_waiting: c_int = 3;
+        pub const OSSL_CMP_PKISTATUS_revocationWarning: c_int = 4;
+        pub const OSSL_CMP_PKISTATUS_revocationNotification: c_int = 5;
+        pub const OSSL_CMP_PKISTATUS_keyUpdateWarning: c_int = 6;
+
+        pub const OSSL_CMP_OPT_LOG_VERBOSITY: c_int = 0;
+        pub const OSSL_CMP_OPT_KEEP_ALIVE: c_int = 10;
+        pub const OSSL_CMP_OPT_MSG_TIMEOUT: c_int = 11;
+        pub const OSSL_CMP_OPT_TOTAL_TIMEOUT: c_int = 12;
+        pub const OSSL_CMP_OPT_VALIDITY_DAYS: c_int = 20;
+        pub const OSSL_CMP_OPT_SUBJECTALTNAME_NODEFAULT: c_int = 21;
+        pub const OSSL_CMP_OPT_SUBJECTALTNAME_CRITICAL: c_int = 22;
+        pub const OSSL_CMP_OPT_POLICIES_CRITICAL: c_int = 23;
+        pub const OSSL_CMP_OPT_POPO_METHOD: c_int = 24;
+        pub const OSSL_CMP_OPT_IMPLICIT_CONFIRM: c_int = 25;
+        pub const OSSL_CMP_OPT_DISABLE_CONFIRM: c_int = 26;
+        pub const OSSL_CMP_OPT_REVOCATION_REASON: c_int = 27;
+        pub const OSSL_CMP_OPT_UNPROTECTED_SEND: c_int = 30;
+        pub const OSSL_CMP_OPT_UNPROTECTED_ERRORS: c_int = 31;
+        pub const OSSL_CMP_OPT_OWF_ALGNID: c_int = 32;
+        pub const OSSL_CMP_OPT_MAC_ALGNID: c_int = 33;
+        pub const OSSL_CMP_OPT_DIGEST_ALGNID: c_int = 34;
+        pub const OSSL_CMP_OPT_IGNORE_KEYUSAGE: c_int = 35;
+        pub const OSSL_CMP_OPT_PERMIT_TA_IN_EXTRACERTS_FOR_IR: c_int = 36;
+    }
+}
+
+#[cfg(ossl300)]
+pub unsafe fn OSSL_CMP_exec_IR_ses(ctx: *mut OSSL_CMP_CTX) -> *mut X509 {
+    OSSL_CMP_exec_certreq(ctx, OSSL_CMP_IR, ptr::null())
+}
+
+#[cfg(ossl300)]
+pub unsafe fn OSSL_CMP_exec_CR_ses(ctx: *mut OSSL_CMP_CTX) -> *mut X509 {
+    OSSL_CMP_exec_certreq(ctx, OSSL_CMP_CR, ptr::null())
+}
+
+#[cfg(ossl300)]
+pub unsafe fn OSSL_CMP_exec_P10CR_ses(ctx: *mut OSSL_CMP_CTX) -> *mut X509 {
+    OSSL_CMP_exec_certreq(ctx, OSSL_CMP_P10CR, ptr::null())
+}
+
+#[cfg(ossl300)]
+pub unsafe fn OSSL_CMP_exec_KUR_ses(ctx: *mut OSSL_CMP_CTX) -> *mut X509 {
+    OSSL_CMP_exec_certreq(ctx, OSSL_CMP_KUR, ptr::null())
+}
@@ -0,0 +1,13 @@
+use libc::*;
+
+use *;
+
+cfg_if! {
+    if #[cfg(ossl300)] {
+        pub const OSSL_CRMF_POPO_NONE: c_int = -1;
+        pub const OSSL_CRMF_POPO_RAVERIFIED: c_int = 0;
+        pub const OSSL_CRMF_POPO_SIGNATURE: c_int = 1;
+        pub const OSSL_CRMF_POPO_KEYENC: c_int = 2;
+        pub const OSSL_CRMF_POPO_KEYAGREE: c_int = 3;
+    }
+}
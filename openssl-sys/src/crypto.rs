@@ -109,6 +109,8 @@ cfg_if! {
     if #[cfg(ossl110)] {
         pub const CRYPTO_EX_INDEX_SSL: c_int = 0;
         pub const CRYPTO_EX_INDEX_SSL_CTX: c_int = 1;
+        pub const CRYPTO_EX_INDEX_EC_KEY: c_int = 8;
+        pub const CRYPTO_EX_INDEX_RSA: c_int = 9;
     } else if #[cfg(libressl)] {
         pub const CRYPTO_EX_INDEX_SSL: c_int = 1;
         pub const CRYPTO_EX_INDEX_SSL_CTX: c_int = 2;
@@ -132,3 +134,22 @@ cfg_if! {
 }
 
 pub const CRYPTO_LOCK: c_int = 1;
+
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_NO_LOAD_CRYPTO_STRINGS: u64 = 0x00000001;
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_LOAD_CRYPTO_STRINGS: u64 = 0x00000002;
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_ADD_ALL_CIPHERS: u64 = 0x00000004;
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_ADD_ALL_DIGESTS: u64 = 0x00000008;
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_NO_ADD_ALL_CIPHERS: u64 = 0x00000010;
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_NO_ADD_ALL_DIGESTS: u64 = 0x00000020;
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_LOAD_CONFIG: u64 = 0x00000040;
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_NO_LOAD_CONFIG: u64 = 0x00000080;
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_ASYNC: u64 = 0x00000100;
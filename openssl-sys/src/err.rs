@@ -5,7 +5,9 @@ pub const ERR_TXT_STRING: c_int = 0x02;
 
 pub const ERR_LIB_SYS: c_int = 2;
 pub const ERR_LIB_PEM: c_int = 9;
+pub const ERR_LIB_X509: c_int = 11;
 pub const ERR_LIB_ASN1: c_int = 13;
+pub const ERR_LIB_SSL: c_int = 20;
 
 cfg_if! {
     if #[cfg(ossl300)] {
@@ -24,6 +24,7 @@ extern "C" {
     pub fn ASN1_BIT_STRING_free(x: *mut ASN1_BIT_STRING);
 
     pub fn ASN1_STRING_free(x: *mut ASN1_STRING);
+    pub fn ASN1_STRING_dup(x: *const ASN1_STRING) -> *mut ASN1_STRING;
     pub fn ASN1_STRING_length(x: *const ASN1_STRING) -> c_int;
 
     pub fn ASN1_GENERALIZEDTIME_free(tm: *mut ASN1_GENERALIZEDTIME);
@@ -46,6 +47,11 @@ extern "C" {
     pub fn BN_to_ASN1_INTEGER(bn: *const BIGNUM, ai: *mut ASN1_INTEGER) -> *mut ASN1_INTEGER;
     pub fn ASN1_INTEGER_to_BN(ai: *const ASN1_INTEGER, bn: *mut BIGNUM) -> *mut BIGNUM;
 
+    pub fn ASN1_ENUMERATED_new() -> *mut ASN1_ENUMERATED;
+    pub fn ASN1_ENUMERATED_free(a: *mut ASN1_ENUMERATED);
+    pub fn ASN1_ENUMERATED_get(a: *const ASN1_ENUMERATED) -> c_long;
+    pub fn ASN1_ENUMERATED_set(a: *mut ASN1_ENUMERATED, v: c_long) -> c_int;
+
     pub fn ASN1_TIME_set_string(s: *mut ASN1_TIME, str: *const c_char) -> c_int;
     #[cfg(ossl111)]
     pub fn ASN1_TIME_set_string_X509(s: *mut ASN1_TIME, str: *const c_char) -> c_int;
@@ -53,6 +59,37 @@ extern "C" {
 
 const_ptr_api! {
     extern "C" {
+        pub fn i2d_ASN1_OBJECT(a: #[const_ptr_if(ossl110)] ASN1_OBJECT, pp: *mut *mut c_uchar) -> c_int;
+        pub fn ASN1_TIME_to_generalizedtime(t: #[const_ptr_if(ossl110)] ASN1_TIME, out: *mut *mut ASN1_GENERALIZEDTIME) -> *mut ASN1_GENERALIZEDTIME;
+        pub fn i2d_ASN1_TYPE(a: #[const_ptr_if(ossl300)] ASN1_TYPE, pp: *mut *mut c_uchar) -> c_int;
         pub fn ASN1_STRING_to_UTF8(out: *mut *mut c_uchar, s: #[const_ptr_if(any(ossl110, libressl280))] ASN1_STRING) -> c_int;
     }
 }
+
+extern "C" {
+    pub fn ASN1_get_object(
+        pp: *mut *const c_uchar,
+        plength: *mut c_long,
+        ptag: *mut c_int,
+        pclass: *mut c_int,
+        omax: c_long,
+    ) -> c_int;
+    pub fn ASN1_put_object(
+        pp: *mut *mut c_uchar,
+        constructed: c_int,
+        length: c_int,
+        tag: c_int,
+        xclass: c_int,
+    );
+    pub fn ASN1_object_size(constructed: c_int, length: c_int, tag: c_int) -> c_int;
+
+    pub fn ASN1_TYPE_get(a: *const ASN1_TYPE) -> c_int;
+
+    pub fn ASN1_STRING_set(s: *mut ASN1_STRING, data: *const c_void, len: c_int) -> c_int;
+
+    pub fn d2i_ASN1_OBJECT(
+        a: *mut *mut ASN1_OBJECT,
+        pp: *mut *const c_uchar,
+        length: c_long,
+    ) -> *mut ASN1_OBJECT;
+}
@@ -48,6 +48,11 @@ extern "C" {
     pub fn BIO_read(b: *mut BIO, buf: *mut c_void, len: c_int) -> c_int;
     pub fn BIO_ctrl(b: *mut BIO, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long;
     pub fn BIO_free_all(b: *mut BIO);
+    pub fn BIO_push(b: *mut BIO, append: *mut BIO) -> *mut BIO;
+    #[cfg(ossl110)]
+    pub fn BIO_number_read(bio: *mut BIO) -> u64;
+    #[cfg(ossl110)]
+    pub fn BIO_number_written(bio: *mut BIO) -> u64;
 }
 
 const_ptr_api! {
@@ -60,6 +65,11 @@ const_ptr_api! {
 extern "C" {
     pub fn BIO_new_socket(sock: c_int, close_flag: c_int) -> *mut BIO;
 
+    #[cfg(ossl110)]
+    pub fn BIO_ADDR_new() -> *mut BIO_ADDR;
+    #[cfg(ossl110)]
+    pub fn BIO_ADDR_free(addr: *mut BIO_ADDR);
+
     #[cfg(any(ossl110, libressl273))]
     pub fn BIO_meth_new(type_: c_int, name: *const c_char) -> *mut BIO_METHOD;
     #[cfg(any(ossl110, libressl273))]
@@ -0,0 +1,67 @@
+use libc::*;
+use *;
+
+pub enum OSSL_CMP_CTX {}
+
+#[cfg(ossl300)]
+extern "C" {
+    pub fn OSSL_CMP_CTX_new(libctx: *mut OSSL_LIB_CTX, propq: *const c_char) -> *mut OSSL_CMP_CTX;
+    pub fn OSSL_CMP_CTX_free(ctx: *mut OSSL_CMP_CTX);
+    pub fn OSSL_CMP_CTX_reinit(ctx: *mut OSSL_CMP_CTX) -> c_int;
+    pub fn OSSL_CMP_CTX_set_option(ctx: *mut OSSL_CMP_CTX, opt: c_int, val: c_int) -> c_int;
+    pub fn OSSL_CMP_CTX_get_option(ctx: *const OSSL_CMP_CTX, opt: c_int) -> c_int;
+
+    pub fn OSSL_CMP_CTX_set1_serverPath(ctx: *mut OSSL_CMP_CTX, path: *const c_char) -> c_int;
+    pub fn OSSL_CMP_CTX_set1_server(ctx: *mut OSSL_CMP_CTX, address: *const c_char) -> c_int;
+    pub fn OSSL_CMP_CTX_set_serverPort(ctx: *mut OSSL_CMP_CTX, port: c_int) -> c_int;
+    pub fn OSSL_CMP_CTX_set1_proxy(ctx: *mut OSSL_CMP_CTX, name: *const c_char) -> c_int;
+    pub fn OSSL_CMP_CTX_set1_no_proxy(ctx: *mut OSSL_CMP_CTX, names: *const c_char) -> c_int;
+
+    pub fn OSSL_CMP_CTX_set1_srvCert(ctx: *mut OSSL_CMP_CTX, cert: *mut X509) -> c_int;
+    pub fn OSSL_CMP_CTX_set1_expected_sender(
+        ctx: *mut OSSL_CMP_CTX,
+        name: *const X509_NAME,
+    ) -> c_int;
+    pub fn OSSL_CMP_CTX_set0_trustedStore(ctx: *mut OSSL_CMP_CTX, store: *mut X509_STORE) -> c_int;
+    pub fn OSSL_CMP_CTX_set1_untrusted(ctx: *mut OSSL_CMP_CTX, certs: *mut stack_st_X509) -> c_int;
+
+    pub fn OSSL_CMP_CTX_set1_cert(ctx: *mut OSSL_CMP_CTX, cert: *mut X509) -> c_int;
+    pub fn OSSL_CMP_CTX_set1_pkey(ctx: *mut OSSL_CMP_CTX, pkey: *mut EVP_PKEY) -> c_int;
+    pub fn OSSL_CMP_CTX_set1_referenceValue(
+        ctx: *mut OSSL_CMP_CTX,
+        ref_: *const c_uchar,
+        len: c_int,
+    ) -> c_int;
+    pub fn OSSL_CMP_CTX_set1_secretValue(
+        ctx: *mut OSSL_CMP_CTX,
+        sec: *const c_uchar,
+        len: c_int,
+    ) -> c_int;
+
+    pub fn OSSL_CMP_CTX_set0_newPkey(
+        ctx: *mut OSSL_CMP_CTX,
+        priv_: c_int,
+        pkey: *mut EVP_PKEY,
+    ) -> c_int;
+    pub fn OSSL_CMP_CTX_set1_subjectName(ctx: *mut OSSL_CMP_CTX, name: *const X509_NAME) -> c_int;
+    pub fn OSSL_CMP_CTX_set0_reqExtensions(
+        ctx: *mut OSSL_CMP_CTX,
+        exts: *mut stack_st_X509_EXTENSION,
+    ) -> c_int;
+    pub fn OSSL_CMP_CTX_set1_oldCert(ctx: *mut OSSL_CMP_CTX, cert: *mut X509) -> c_int;
+    pub fn OSSL_CMP_CTX_set1_p10CSR(ctx: *mut OSSL_CMP_CTX, csr: *const X509_REQ) -> c_int;
+
+    pub fn OSSL_CMP_CTX_get_status(ctx: *const OSSL_CMP_CTX) -> c_int;
+    pub fn OSSL_CMP_CTX_get_failInfoCode(ctx: *const OSSL_CMP_CTX) -> c_int;
+    pub fn OSSL_CMP_CTX_get0_newCert(ctx: *const OSSL_CMP_CTX) -> *mut X509;
+    pub fn OSSL_CMP_CTX_get1_newChain(ctx: *const OSSL_CMP_CTX) -> *mut stack_st_X509;
+    pub fn OSSL_CMP_CTX_get1_caPubs(ctx: *const OSSL_CMP_CTX) -> *mut stack_st_X509;
+    pub fn OSSL_CMP_CTX_get1_extraCertsIn(ctx: *const OSSL_CMP_CTX) -> *mut stack_st_X509;
+
+    pub fn OSSL_CMP_exec_certreq(
+        ctx: *mut OSSL_CMP_CTX,
+        req_type: c_int,
+        crm: *const OSSL_CRMF_MSG,
+    ) -> *mut X509;
+    pub fn OSSL_CMP_exec_RR_ses(ctx: *mut OSSL_CMP_CTX) -> c_int;
+}
@@ -2,6 +2,9 @@ use libc::*;
 use *;
 
 pub enum CMS_ContentInfo {}
+pub enum CMS_SignerInfo {}
+
+stack!(stack_st_CMS_SignerInfo);
 
 extern "C" {
     #[cfg(ossl101)]
@@ -52,4 +55,39 @@ extern "C" {
         out: *mut ::BIO,
         flags: c_uint,
     ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_add1_signer(
+        cms: *mut ::CMS_ContentInfo,
+        signer: *mut ::X509,
+        pk: *mut ::EVP_PKEY,
+        md: *const ::EVP_MD,
+        flags: c_uint,
+    ) -> *mut ::CMS_SignerInfo;
+
+    #[cfg(ossl101)]
+    pub fn CMS_final(
+        cms: *mut ::CMS_ContentInfo,
+        data: *mut ::BIO,
+        dcont: *mut ::BIO,
+        flags: c_uint,
+    ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_get0_SignerInfos(cms: *mut ::CMS_ContentInfo) -> *mut stack_st_CMS_SignerInfo;
+
+    #[cfg(ossl101)]
+    pub fn CMS_add_smimecap(si: *mut ::CMS_SignerInfo, algs: *mut stack_st_X509_ALGOR) -> c_int;
+}
+
+const_ptr_api! {
+    extern "C" {
+        #[cfg(ossl101)]
+        pub fn CMS_signed_get0_data_by_OBJ(
+            si: #[const_ptr_if(ossl300)] ::CMS_SignerInfo,
+            oid: *const ::ASN1_OBJECT,
+            lastpos: c_int,
+            type_: c_int,
+        ) -> *mut c_void;
+    }
 }
@@ -1,5 +1,15 @@
+use libc::*;
 use *;
 
+#[repr(C)]
+pub struct CONF_VALUE {
+    pub section: *mut c_char,
+    pub name: *mut c_char,
+    pub value: *mut c_char,
+}
+
+stack!(stack_st_CONF_VALUE);
+
 extern "C" {
     pub fn NCONF_new(meth: *mut CONF_METHOD) -> *mut CONF;
     pub fn NCONF_default() -> *mut CONF_METHOD;
@@ -0,0 +1,71 @@
+use libc::*;
+use *;
+
+pub enum OSSL_CRMF_MSG {}
+pub enum OSSL_CRMF_CERTTEMPLATE {}
+
+stack!(stack_st_OSSL_CRMF_MSG);
+
+#[cfg(ossl300)]
+extern "C" {
+    pub fn OSSL_CRMF_MSG_new() -> *mut OSSL_CRMF_MSG;
+    pub fn OSSL_CRMF_MSG_free(a: *mut OSSL_CRMF_MSG);
+    pub fn d2i_OSSL_CRMF_MSG(
+        a: *mut *mut OSSL_CRMF_MSG,
+        in_: *mut *const c_uchar,
+        len: c_long,
+    ) -> *mut OSSL_CRMF_MSG;
+    pub fn i2d_OSSL_CRMF_MSG(a: *const OSSL_CRMF_MSG, out: *mut *mut c_uchar) -> c_int;
+    pub fn d2i_OSSL_CRMF_MSGS(
+        a: *mut *mut stack_st_OSSL_CRMF_MSG,
+        in_: *mut *const c_uchar,
+        len: c_long,
+    ) -> *mut stack_st_OSSL_CRMF_MSG;
+    pub fn i2d_OSSL_CRMF_MSGS(a: *const stack_st_OSSL_CRMF_MSG, out: *mut *mut c_uchar) -> c_int;
+
+    pub fn OSSL_CRMF_MSG_set_certReqId(crm: *mut OSSL_CRMF_MSG, rid: c_int) -> c_int;
+    pub fn OSSL_CRMF_MSG_get_certReqId(crm: *const OSSL_CRMF_MSG) -> c_int;
+    pub fn OSSL_CRMF_MSG_set0_validity(
+        crm: *mut OSSL_CRMF_MSG,
+        notBefore: *mut ASN1_TIME,
+        notAfter: *mut ASN1_TIME,
+    ) -> c_int;
+    pub fn OSSL_CRMF_MSG_push0_extension(
+        crm: *mut OSSL_CRMF_MSG,
+        ext: *mut X509_EXTENSION,
+    ) -> c_int;
+    pub fn OSSL_CRMF_MSG_get0_tmpl(crm: *const OSSL_CRMF_MSG) -> *mut OSSL_CRMF_CERTTEMPLATE;
+
+    pub fn OSSL_CRMF_CERTTEMPLATE_fill(
+        tmpl: *mut OSSL_CRMF_CERTTEMPLATE,
+        pubkey: *mut EVP_PKEY,
+        subject: *const X509_NAME,
+        issuer: *const X509_NAME,
+        serial: *const ASN1_INTEGER,
+    ) -> c_int;
+    pub fn OSSL_CRMF_CERTTEMPLATE_get0_subject(
+        tmpl: *const OSSL_CRMF_CERTTEMPLATE,
+    ) -> *const X509_NAME;
+    pub fn OSSL_CRMF_CERTTEMPLATE_get0_issuer(
+        tmpl: *const OSSL_CRMF_CERTTEMPLATE,
+    ) -> *const X509_NAME;
+    pub fn OSSL_CRMF_CERTTEMPLATE_get0_serialNumber(
+        tmpl: *const OSSL_CRMF_CERTTEMPLATE,
+    ) -> *const ASN1_INTEGER;
+
+    pub fn OSSL_CRMF_MSG_create_popo(
+        meth: c_int,
+        crm: *mut OSSL_CRMF_MSG,
+        pkey: *mut EVP_PKEY,
+        digest: *const EVP_MD,
+        libctx: *mut OSSL_LIB_CTX,
+        propq: *const c_char,
+    ) -> c_int;
+    pub fn OSSL_CRMF_MSGS_verify_popo(
+        reqs: *const stack_st_OSSL_CRMF_MSG,
+        rid: c_int,
+        acceptRAVerified: c_int,
+        libctx: *mut OSSL_LIB_CTX,
+        propq: *const c_char,
+    ) -> c_int;
+}
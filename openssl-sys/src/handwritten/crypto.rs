@@ -83,3 +83,25 @@ extern "C" {
     #[cfg(ossl300)]
     pub fn OSSL_LIB_CTX_free(libcts: *mut OSSL_LIB_CTX);
 }
+
+extern "C" {
+    #[cfg(ossl110)]
+    pub fn OPENSSL_init_crypto(opts: u64, settings: *const OPENSSL_INIT_SETTINGS) -> c_int;
+    #[cfg(ossl110)]
+    pub fn OPENSSL_cleanup();
+
+    #[cfg(ossl110)]
+    pub fn OPENSSL_INIT_new() -> *mut OPENSSL_INIT_SETTINGS;
+    #[cfg(ossl110)]
+    pub fn OPENSSL_INIT_free(settings: *mut OPENSSL_INIT_SETTINGS);
+    #[cfg(ossl110)]
+    pub fn OPENSSL_INIT_set_config_appname(
+        settings: *mut OPENSSL_INIT_SETTINGS,
+        config_appname: *const c_char,
+    ) -> c_int;
+    #[cfg(ossl111b)]
+    pub fn OPENSSL_INIT_set_config_filename(
+        settings: *mut OPENSSL_INIT_SETTINGS,
+        config_filename: *const c_char,
+    ) -> c_int;
+}
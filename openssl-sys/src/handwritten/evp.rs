@@ -47,6 +47,8 @@ cfg_if! {
         extern "C" {
             pub fn EVP_MD_CTX_new() -> *mut EVP_MD_CTX;
             pub fn EVP_MD_CTX_free(ctx: *mut EVP_MD_CTX);
+            pub fn EVP_MD_CTX_reset(ctx: *mut EVP_MD_CTX) -> c_int;
+            pub fn EVP_CIPHER_CTX_reset(ctx: *mut EVP_CIPHER_CTX) -> c_int;
         }
     } else {
         extern "C" {
@@ -511,6 +513,17 @@ const_ptr_api! {
     }
 }
 
+extern "C" {
+    #[cfg(ossl300)]
+    pub fn EVP_PKCS82PKEY_ex(
+        p8: *const PKCS8_PRIV_KEY_INFO,
+        libctx: *mut OSSL_LIB_CTX,
+        propq: *const c_char,
+    ) -> *mut EVP_PKEY;
+    #[cfg(ossl300)]
+    pub fn EVP_PKEY2PKCS8(pkey: *const EVP_PKEY) -> *mut PKCS8_PRIV_KEY_INFO;
+}
+
 cfg_if! {
     if #[cfg(any(ossl111))] {
         extern "C" {
@@ -0,0 +1,66 @@
+use libc::*;
+use *;
+
+pub enum OSSL_HTTP_REQ_CTX {}
+
+pub type OSSL_HTTP_bio_cb_t = Option<
+    unsafe extern "C" fn(
+        bio: *mut BIO,
+        arg: *mut c_void,
+        connect: c_int,
+        detail: c_int,
+    ) -> *mut BIO,
+>;
+
+#[cfg(ossl300)]
+extern "C" {
+    pub fn OSSL_HTTP_parse_url(
+        url: *const c_char,
+        pssl: *mut c_int,
+        puser: *mut *mut c_char,
+        phost: *mut *mut c_char,
+        pport: *mut *mut c_char,
+        pport_num: *mut c_int,
+        ppath: *mut *mut c_char,
+        pquery: *mut *mut c_char,
+        pfrag: *mut *mut c_char,
+    ) -> c_int;
+    pub fn OSSL_HTTP_get(
+        url: *const c_char,
+        proxy: *const c_char,
+        no_proxy: *const c_char,
+        bio: *mut BIO,
+        rbio: *mut BIO,
+        bio_update_fn: OSSL_HTTP_bio_cb_t,
+        arg: *mut c_void,
+        buf_size: c_int,
+        headers: *const stack_st_CONF_VALUE,
+        expected_content_type: *const c_char,
+        expect_asn1: c_int,
+        max_resp_len: size_t,
+        timeout: c_int,
+    ) -> *mut BIO;
+    pub fn OSSL_HTTP_transfer(
+        prctx: *mut *mut OSSL_HTTP_REQ_CTX,
+        server: *const c_char,
+        port: *const c_char,
+        path: *const c_char,
+        use_ssl: c_int,
+        proxy: *const c_char,
+        no_proxy: *const c_char,
+        bio: *mut BIO,
+        rbio: *mut BIO,
+        bio_update_fn: OSSL_HTTP_bio_cb_t,
+        arg: *mut c_void,
+        buf_size: c_int,
+        headers: *const stack_st_CONF_VALUE,
+        content_type: *const c_char,
+        req: *mut BIO,
+        expected_content_type: *const c_char,
+        expect_asn1: c_int,
+        max_resp_len: size_t,
+        timeout: c_int,
+        keep_alive: c_int,
+    ) -> *mut BIO;
+    pub fn OSSL_HTTP_close(rctx: *mut OSSL_HTTP_REQ_CTX, ok: c_int) -> c_int;
+}
@@ -2,8 +2,10 @@ pub use handwritten::aes::*;
 pub use handwritten::asn1::*;
 pub use handwritten::bio::*;
 pub use handwritten::bn::*;
+pub use handwritten::cmp::*;
 pub use handwritten::cms::*;
 pub use handwritten::conf::*;
+pub use handwritten::crmf::*;
 pub use handwritten::crypto::*;
 pub use handwritten::dh::*;
 pub use handwritten::dsa::*;
@@ -11,6 +13,7 @@ pub use handwritten::ec::*;
 pub use handwritten::err::*;
 pub use handwritten::evp::*;
 pub use handwritten::hmac::*;
+pub use handwritten::http::*;
 pub use handwritten::kdf::*;
 pub use handwritten::object::*;
 pub use handwritten::ocsp::*;
@@ -22,12 +25,15 @@ pub use handwritten::rand::*;
 pub use handwritten::rsa::*;
 pub use handwritten::safestack::*;
 pub use handwritten::sha::*;
+pub use handwritten::srp::*;
 pub use handwritten::srtp::*;
 pub use handwritten::ssl::*;
 pub use handwritten::stack::*;
 pub use handwritten::tls1::*;
+pub use handwritten::ts::*;
 pub use handwritten::types::*;
 pub use handwritten::x509::*;
+pub use handwritten::x509_acert::*;
 pub use handwritten::x509_vfy::*;
 pub use handwritten::x509v3::*;
 
@@ -35,8 +41,10 @@ mod aes;
 mod asn1;
 mod bio;
 mod bn;
+mod cmp;
 mod cms;
 mod conf;
+mod crmf;
 mod crypto;
 mod dh;
 mod dsa;
@@ -44,6 +52,7 @@ mod ec;
 mod err;
 mod evp;
 mod hmac;
+mod http;
 mod kdf;
 mod object;
 mod ocsp;
@@ -55,11 +64,14 @@ mod rand;
 mod rsa;
 mod safestack;
 mod sha;
+mod srp;
 mod srtp;
 mod ssl;
 mod stack;
 mod tls1;
+mod ts;
 mod types;
 mod x509;
+mod x509_acert;
 mod x509_vfy;
 mod x509v3;
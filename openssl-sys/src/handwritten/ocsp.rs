@@ -16,6 +16,13 @@ const_ptr_api! {
             subject: #[const_ptr_if(any(ossl110, libressl281))] X509,
             issuer: #[const_ptr_if(any(ossl110, libressl281))] X509,
         ) -> *mut OCSP_CERTID;
+
+        pub fn OCSP_cert_id_new(
+            dgst: *const EVP_MD,
+            issuerName: #[const_ptr_if(any(ossl110, libressl281))] X509_NAME,
+            issuerKey: #[const_ptr_if(any(ossl110, libressl281))] ASN1_BIT_STRING,
+            serialNumber: #[const_ptr_if(any(ossl110, libressl281))] ASN1_INTEGER,
+        ) -> *mut OCSP_CERTID;
     }
 }
 
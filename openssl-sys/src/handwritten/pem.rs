@@ -146,6 +146,13 @@ extern "C" {
         user_data: *mut c_void,
     ) -> *mut DH;
     pub fn PEM_write_bio_DHparams(bio: *mut BIO, x: *const DH) -> c_int;
+    pub fn PEM_read_bio(
+        bio: *mut BIO,
+        name: *mut *mut c_char,
+        header: *mut *mut c_char,
+        data: *mut *mut c_uchar,
+        len: *mut c_long,
+    ) -> c_int;
     pub fn PEM_read_bio_PrivateKey(
         bio: *mut BIO,
         out: *mut *mut EVP_PKEY,
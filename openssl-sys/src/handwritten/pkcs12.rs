@@ -51,3 +51,25 @@ const_ptr_api! {
         pub fn i2d_PKCS12_bio(b: *mut BIO, a: #[const_ptr_if(ossl300)] PKCS12) -> c_int;
     }
 }
+
+extern "C" {
+    #[cfg(ossl300)]
+    pub fn PKCS12_create_ex(
+        pass: *const c_char,
+        name: *const c_char,
+        pkey: *mut EVP_PKEY,
+        cert: *mut X509,
+        ca: *mut stack_st_X509,
+        nid_key: c_int,
+        nid_cert: c_int,
+        iter: c_int,
+        mac_iter: c_int,
+        keytype: c_int,
+        ctx: *mut OSSL_LIB_CTX,
+        propq: *const c_char,
+    ) -> *mut PKCS12;
+
+    #[cfg(ossl300)]
+    pub fn PKCS12_init_ex(mode: c_int, ctx: *mut OSSL_LIB_CTX, propq: *const c_char)
+        -> *mut PKCS12;
+}
@@ -1,12 +1,51 @@
 use libc::*;
 use *;
 
-pub enum PKCS7_SIGNED {}
+#[repr(C)]
+pub struct PKCS7_SIGNED {
+    pub version: *mut ASN1_INTEGER,
+    pub md_algs: *mut stack_st_X509_ALGOR,
+    pub cert: *mut stack_st_X509,
+    pub crl: *mut stack_st_X509_CRL,
+    pub signer_info: *mut stack_st_PKCS7_SIGNER_INFO,
+    pub contents: *mut PKCS7,
+}
 pub enum PKCS7_ENVELOPE {}
 pub enum PKCS7_SIGN_ENVELOPE {}
 pub enum PKCS7_DIGEST {}
 pub enum PKCS7_ENCRYPT {}
-pub enum PKCS7 {}
+
+#[repr(C)]
+pub struct PKCS7 {
+    pub asn1: *mut c_uchar,
+    pub length: c_long,
+    pub state: c_int,
+    pub detached: c_int,
+    pub type_: *mut ASN1_OBJECT,
+    pub d: PKCS7_data,
+    #[cfg(ossl300)]
+    pub ctx: PKCS7_CTX,
+}
+#[repr(C)]
+pub union PKCS7_data {
+    pub ptr: *mut c_char,
+    pub data: *mut ASN1_OCTET_STRING,
+    pub sign: *mut PKCS7_SIGNED,
+    pub enveloped: *mut PKCS7_ENVELOPE,
+    pub signed_and_enveloped: *mut PKCS7_SIGN_ENVELOPE,
+    pub digest: *mut PKCS7_DIGEST,
+    pub encrypted: *mut PKCS7_ENCRYPT,
+    pub other: *mut ASN1_TYPE,
+}
+#[cfg(ossl300)]
+#[repr(C)]
+pub struct PKCS7_CTX {
+    pub libctx: *mut OSSL_LIB_CTX,
+    pub propq: *mut c_char,
+}
+pub enum PKCS7_SIGNER_INFO {}
+
+stack!(stack_st_PKCS7_SIGNER_INFO);
 
 extern "C" {
     pub fn d2i_PKCS7(a: *mut *mut PKCS7, pp: *mut *const c_uchar, length: c_long) -> *mut PKCS7;
@@ -67,4 +106,40 @@ extern "C" {
     ) -> c_int;
 
     pub fn SMIME_read_PKCS7(bio: *mut BIO, bcont: *mut *mut BIO) -> *mut PKCS7;
+
+    pub fn PKCS7_sign_add_signer(
+        p7: *mut PKCS7,
+        signcert: *mut X509,
+        pkey: *mut EVP_PKEY,
+        md: *const EVP_MD,
+        flags: c_int,
+    ) -> *mut PKCS7_SIGNER_INFO;
+
+    pub fn PKCS7_final(p7: *mut PKCS7, data: *mut BIO, flags: c_int) -> c_int;
+
+    pub fn PKCS7_get_signer_info(p7: *mut PKCS7) -> *mut stack_st_PKCS7_SIGNER_INFO;
+
+    pub fn PKCS7_SIGNER_INFO_free(si: *mut PKCS7_SIGNER_INFO);
+
+    pub fn PKCS7_add_attrib_smimecap(
+        si: *mut PKCS7_SIGNER_INFO,
+        cap: *mut stack_st_X509_ALGOR,
+    ) -> c_int;
+
+    pub fn PKCS7_get_smimecap(si: *mut PKCS7_SIGNER_INFO) -> *mut stack_st_X509_ALGOR;
+
+    pub fn PKCS7_simple_smimecap(sk: *mut stack_st_X509_ALGOR, nid: c_int, arg: c_int) -> c_int;
+
+    pub fn PKCS7_add_signed_attribute(
+        p7si: *mut PKCS7_SIGNER_INFO,
+        nid: c_int,
+        attrtype: c_int,
+        data: *mut c_void,
+    ) -> c_int;
+}
+
+const_ptr_api! {
+    extern "C" {
+        pub fn PKCS7_get_signed_attribute(si: #[const_ptr_if(ossl300)] PKCS7_SIGNER_INFO, nid: c_int) -> *mut ASN1_TYPE;
+    }
 }
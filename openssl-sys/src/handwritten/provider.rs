@@ -12,4 +12,6 @@ extern "C" {
     ) -> *mut OSSL_PROVIDER;
     #[cfg(ossl300)]
     pub fn OSSL_PROVIDER_unload(prov: *mut OSSL_PROVIDER) -> c_int;
+    #[cfg(ossl300)]
+    pub fn OSSL_PROVIDER_available(ctx: *mut OSSL_LIB_CTX, name: *const c_char) -> c_int;
 }
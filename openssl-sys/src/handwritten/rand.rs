@@ -8,5 +8,7 @@ extern "C" {
 
     pub fn RAND_status() -> c_int;
 
+    pub fn RAND_poll() -> c_int;
+
     pub fn RAND_add(buf: *const c_void, num: c_int, randomness: c_double);
 }
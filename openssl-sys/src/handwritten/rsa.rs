@@ -127,3 +127,51 @@ extern "C" {
         rsa_len: c_int,
     ) -> c_int;
 }
+
+extern "C" {
+    pub fn RSA_padding_add_PKCS1_type_1(
+        to: *mut c_uchar,
+        tlen: c_int,
+        f: *const c_uchar,
+        fl: c_int,
+    ) -> c_int;
+
+    #[cfg(ossl110)]
+    pub fn RSA_PKCS1_OpenSSL() -> *const RSA_METHOD;
+    #[cfg(ossl110)]
+    pub fn RSA_set_method(rsa: *mut RSA, meth: *const RSA_METHOD) -> c_int;
+    #[cfg(ossl110)]
+    pub fn RSA_meth_dup(meth: *const RSA_METHOD) -> *mut RSA_METHOD;
+    #[cfg(ossl110)]
+    pub fn RSA_meth_set_priv_enc(
+        rsa: *mut RSA_METHOD,
+        priv_enc: Option<
+            unsafe extern "C" fn(
+                flen: c_int,
+                from: *const c_uchar,
+                to: *mut c_uchar,
+                rsa: *mut RSA,
+                padding: c_int,
+            ) -> c_int,
+        >,
+    ) -> c_int;
+    #[cfg(ossl110)]
+    pub fn RSA_meth_set_sign(
+        rsa: *mut RSA_METHOD,
+        sign: Option<
+            unsafe extern "C" fn(
+                type_: c_int,
+                m: *const c_uchar,
+                m_length: c_uint,
+                sigret: *mut c_uchar,
+                siglen: *mut c_uint,
+                rsa: *const RSA,
+            ) -> c_int,
+        >,
+    ) -> c_int;
+
+    #[cfg(ossl110)]
+    pub fn RSA_set_ex_data(r: *mut RSA, idx: c_int, arg: *mut c_void) -> c_int;
+    #[cfg(ossl110)]
+    pub fn RSA_get_ex_data(r: *const RSA, idx: c_int) -> *mut c_void;
+}
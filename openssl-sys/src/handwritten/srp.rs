@@ -0,0 +1,23 @@
+use libc::*;
+use *;
+
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+#[repr(C)]
+pub struct SRP_gN {
+    pub id: *mut c_char,
+    pub g: *const BIGNUM,
+    pub N: *const BIGNUM,
+}
+
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+extern "C" {
+    pub fn SRP_get_default_gN(id: *const c_char) -> *mut SRP_gN;
+    pub fn SRP_create_verifier_BN(
+        user: *const c_char,
+        pass: *const c_char,
+        salt: *mut *mut BIGNUM,
+        verifier: *mut *mut BIGNUM,
+        N: *const BIGNUM,
+        g: *const BIGNUM,
+    ) -> c_int;
+}
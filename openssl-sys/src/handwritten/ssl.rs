@@ -200,12 +200,18 @@ cfg_if! {
             pub fn SSL_CTX_get_options(ctx: *const SSL_CTX) -> u64;
             pub fn SSL_CTX_set_options(ctx: *mut SSL_CTX, op: u64) -> u64;
             pub fn SSL_CTX_clear_options(ctx: *mut SSL_CTX, op: u64) -> u64;
+            pub fn SSL_get_options(ssl: *const SSL) -> u64;
+            pub fn SSL_set_options(ssl: *mut SSL, op: u64) -> u64;
+            pub fn SSL_clear_options(ssl: *mut SSL, op: u64) -> u64;
         }
     } else if #[cfg(ossl110)] {
         extern "C" {
             pub fn SSL_CTX_get_options(ctx: *const SSL_CTX) -> c_ulong;
             pub fn SSL_CTX_set_options(ctx: *mut SSL_CTX, op: c_ulong) -> c_ulong;
             pub fn SSL_CTX_clear_options(ctx: *mut SSL_CTX, op: c_ulong) -> c_ulong;
+            pub fn SSL_get_options(ssl: *const SSL) -> c_ulong;
+            pub fn SSL_set_options(ssl: *mut SSL, op: c_ulong) -> c_ulong;
+            pub fn SSL_clear_options(ssl: *mut SSL, op: c_ulong) -> c_ulong;
         }
     }
 }
@@ -384,6 +390,106 @@ extern "C" {
             extern "C" fn(*mut SSL, *const c_char, *mut c_uchar, c_uint) -> c_uint,
         >,
     );
+    pub fn SSL_CTX_use_psk_identity_hint(ctx: *mut SSL_CTX, hint: *const c_char) -> c_int;
+    pub fn SSL_use_psk_identity_hint(ssl: *mut SSL, hint: *const c_char) -> c_int;
+    pub fn SSL_get_psk_identity_hint(ssl: *const SSL) -> *const c_char;
+    pub fn SSL_get_psk_identity(ssl: *const SSL) -> *const c_char;
+}
+
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+extern "C" {
+    pub fn SSL_CTX_set_srp_username(ctx: *mut SSL_CTX, name: *mut c_char) -> c_int;
+    pub fn SSL_CTX_set_srp_password(ctx: *mut SSL_CTX, password: *mut c_char) -> c_int;
+    pub fn SSL_CTX_set_srp_strength(ctx: *mut SSL_CTX, strength: c_int) -> c_int;
+    pub fn SSL_CTX_set_srp_username_callback(
+        ctx: *mut SSL_CTX,
+        cb: Option<unsafe extern "C" fn(*mut SSL, *mut c_int, *mut c_void) -> c_int>,
+    ) -> c_int;
+    pub fn SSL_set_srp_server_param(
+        s: *mut SSL,
+        N: *const BIGNUM,
+        g: *const BIGNUM,
+        sa: *mut BIGNUM,
+        v: *mut BIGNUM,
+        info: *mut c_char,
+    ) -> c_int;
+    pub fn SSL_get_srp_username(s: *mut SSL) -> *mut c_char;
+}
+
+#[cfg(ossl111)]
+pub type SSL_psk_use_session_cb_func = Option<
+    unsafe extern "C" fn(
+        ssl: *mut SSL,
+        md: *const EVP_MD,
+        id: *mut *const c_uchar,
+        idlen: *mut size_t,
+        sess: *mut *mut SSL_SESSION,
+    ) -> c_int,
+>;
+#[cfg(ossl111)]
+pub type SSL_psk_find_session_cb_func = Option<
+    unsafe extern "C" fn(
+        ssl: *mut SSL,
+        identity: *const c_uchar,
+        identity_len: size_t,
+        sess: *mut *mut SSL_SESSION,
+    ) -> c_int,
+>;
+
+extern "C" {
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_psk_use_session_callback(ctx: *mut SSL_CTX, cb: SSL_psk_use_session_cb_func);
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_psk_find_session_callback(
+        ctx: *mut SSL_CTX,
+        cb: SSL_psk_find_session_cb_func,
+    );
+}
+
+extern "C" {
+    #[cfg(ossl320)]
+    pub fn SSL_CTX_set1_cert_comp_preference(
+        ctx: *mut SSL_CTX,
+        algs: *mut c_int,
+        len: size_t,
+    ) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_set1_cert_comp_preference(ssl: *mut SSL, algs: *mut c_int, len: size_t) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_CTX_compress_certs(ctx: *mut SSL_CTX, alg: c_int) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_compress_certs(ssl: *mut SSL, alg: c_int) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_CTX_get1_compressed_cert(
+        ctx: *mut SSL_CTX,
+        alg: c_int,
+        data: *mut *mut c_uchar,
+        orig_len: *mut size_t,
+    ) -> size_t;
+}
+
+#[cfg(all(ossl320, not(osslconf = "OPENSSL_NO_QUIC")))]
+extern "C" {
+    pub fn OSSL_QUIC_client_method() -> *const SSL_METHOD;
+    pub fn OSSL_QUIC_client_thread_method() -> *const SSL_METHOD;
+}
+
+#[cfg(ossl320)]
+extern "C" {
+    pub fn SSL_handle_events(s: *mut SSL) -> c_int;
+    pub fn SSL_get_event_timeout(s: *mut SSL, tv: *mut timeval, is_infinite: *mut c_int) -> c_int;
+    pub fn SSL_get_blocking_mode(s: *mut SSL) -> c_int;
+    pub fn SSL_set_blocking_mode(s: *mut SSL, blocking: c_int) -> c_int;
+    pub fn SSL_set1_initial_peer_addr(s: *mut SSL, peer_addr: *const BIO_ADDR) -> c_int;
+    pub fn SSL_get0_connection(s: *mut SSL) -> *mut SSL;
+    pub fn SSL_is_connection(s: *mut SSL) -> c_int;
+    pub fn SSL_get_stream_type(s: *mut SSL) -> c_int;
+    pub fn SSL_get_stream_id(s: *mut SSL) -> u64;
+    pub fn SSL_stream_conclude(s: *mut SSL, flags: u64) -> c_int;
+    pub fn SSL_new_stream(s: *mut SSL, flags: u64) -> *mut SSL;
+    pub fn SSL_accept_stream(s: *mut SSL, flags: u64) -> *mut SSL;
+    pub fn SSL_get_accept_stream_queue_len(s: *mut SSL) -> size_t;
+    pub fn SSL_set_default_stream_mode(s: *mut SSL, mode: u32) -> c_int;
 }
 
 extern "C" {
@@ -403,6 +509,200 @@ extern "C" {
     pub fn SSL_extension_supported(ext_type: c_uint) -> c_int;
 }
 
+extern "C" {
+    pub fn SSL_CTX_set_msg_callback(
+        ctx: *mut SSL_CTX,
+        cb: Option<
+            unsafe extern "C" fn(
+                write_p: c_int,
+                version: c_int,
+                content_type: c_int,
+                buf: *const c_void,
+                len: size_t,
+                ssl: *mut SSL,
+                arg: *mut c_void,
+            ),
+        >,
+    );
+    pub fn SSL_set_msg_callback(
+        ssl: *mut SSL,
+        cb: Option<
+            unsafe extern "C" fn(
+                write_p: c_int,
+                version: c_int,
+                content_type: c_int,
+                buf: *const c_void,
+                len: size_t,
+                ssl: *mut SSL,
+                arg: *mut c_void,
+            ),
+        >,
+    );
+
+    pub fn SSL_CTX_set_info_callback(
+        ctx: *mut SSL_CTX,
+        cb: Option<unsafe extern "C" fn(ssl: *const SSL, type_: c_int, val: c_int)>,
+    );
+    pub fn SSL_set_info_callback(
+        ssl: *mut SSL,
+        cb: Option<unsafe extern "C" fn(ssl: *const SSL, type_: c_int, val: c_int)>,
+    );
+    pub fn SSL_alert_type_string_long(value: c_int) -> *const c_char;
+    pub fn SSL_alert_desc_string_long(value: c_int) -> *const c_char;
+}
+
+extern "C" {
+    #[cfg(ossl110)]
+    pub fn SSL_CTX_set_security_level(ctx: *mut SSL_CTX, level: c_int);
+    #[cfg(ossl110)]
+    pub fn SSL_CTX_get_security_level(ctx: *const SSL_CTX) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_set_security_level(ssl: *mut SSL, level: c_int);
+    #[cfg(ossl110)]
+    pub fn SSL_get_security_level(ssl: *const SSL) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_CTX_set_security_callback(
+        ctx: *mut SSL_CTX,
+        cb: Option<
+            unsafe extern "C" fn(
+                ssl: *const SSL,
+                ctx: *const SSL_CTX,
+                op: c_int,
+                bits: c_int,
+                nid: c_int,
+                other: *mut c_void,
+                ex: *mut c_void,
+            ) -> c_int,
+        >,
+    );
+    #[cfg(ossl110)]
+    pub fn SSL_set_security_callback(
+        ssl: *mut SSL,
+        cb: Option<
+            unsafe extern "C" fn(
+                ssl: *const SSL,
+                ctx: *const SSL_CTX,
+                op: c_int,
+                bits: c_int,
+                nid: c_int,
+                other: *mut c_void,
+                ex: *mut c_void,
+            ) -> c_int,
+        >,
+    );
+
+    #[cfg(all(ossl102, not(osslconf = "OPENSSL_NO_SSL_TRACE")))]
+    pub fn SSL_trace(
+        write_p: c_int,
+        version: c_int,
+        content_type: c_int,
+        buf: *const c_void,
+        len: size_t,
+        ssl: *mut SSL,
+        arg: *mut c_void,
+    );
+}
+
+#[cfg(ossl110)]
+extern "C" {
+    pub fn SSL_CTX_dane_enable(ctx: *mut SSL_CTX) -> c_int;
+    pub fn SSL_CTX_dane_mtype_set(
+        ctx: *mut SSL_CTX,
+        md: *const EVP_MD,
+        mtype: u8,
+        ord: u8,
+    ) -> c_int;
+    pub fn SSL_CTX_dane_set_flags(ctx: *mut SSL_CTX, flags: c_ulong) -> c_ulong;
+    pub fn SSL_CTX_dane_clear_flags(ctx: *mut SSL_CTX, flags: c_ulong) -> c_ulong;
+    pub fn SSL_dane_enable(s: *mut SSL, basedomain: *const c_char) -> c_int;
+    pub fn SSL_dane_tlsa_add(
+        s: *mut SSL,
+        usage: u8,
+        selector: u8,
+        mtype: u8,
+        data: *const c_uchar,
+        dlen: size_t,
+    ) -> c_int;
+    pub fn SSL_dane_set_flags(ssl: *mut SSL, flags: c_ulong) -> c_ulong;
+    pub fn SSL_dane_clear_flags(ssl: *mut SSL, flags: c_ulong) -> c_ulong;
+    pub fn SSL_get0_dane_authority(
+        s: *mut SSL,
+        mcert: *mut *mut X509,
+        mspki: *mut *mut EVP_PKEY,
+    ) -> c_int;
+    pub fn SSL_get0_dane_tlsa(
+        s: *mut SSL,
+        usage: *mut u8,
+        selector: *mut u8,
+        mtype: *mut u8,
+        data: *mut *const c_uchar,
+        dlen: *mut size_t,
+    ) -> c_int;
+}
+
+extern "C" {
+    #[cfg(ossl102)]
+    pub fn SSL_get_shared_sigalgs(
+        s: *mut SSL,
+        idx: c_int,
+        psign: *mut c_int,
+        phash: *mut c_int,
+        psignhash: *mut c_int,
+        rsig: *mut c_uchar,
+        rhash: *mut c_uchar,
+    ) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_get_peer_signature_type_nid(s: *const SSL, pnid: *mut c_int) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_get_signature_type_nid(s: *const SSL, pnid: *mut c_int) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_get0_group_name(s: *mut SSL) -> *const c_char;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_has_ticket(s: *const SSL_SESSION) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_num_tickets(ctx: *mut SSL_CTX, num_tickets: size_t) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_get_num_tickets(ctx: *const SSL_CTX) -> size_t;
+    #[cfg(ossl111)]
+    pub fn SSL_set_num_tickets(s: *mut SSL, num_tickets: size_t) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_get_num_tickets(s: *const SSL) -> size_t;
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_session_ticket_cb(
+        ctx: *mut SSL_CTX,
+        gen_cb: SSL_CTX_generate_session_ticket_fn,
+        dec_cb: SSL_CTX_decrypt_session_ticket_fn,
+        arg: *mut c_void,
+    ) -> c_int;
+
+    #[cfg(ossl320)]
+    pub fn SSL_CTX_set1_client_cert_type(
+        ctx: *mut SSL_CTX,
+        val: *const c_uchar,
+        len: size_t,
+    ) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_CTX_set1_server_cert_type(
+        ctx: *mut SSL_CTX,
+        val: *const c_uchar,
+        len: size_t,
+    ) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_set1_client_cert_type(s: *mut SSL, val: *const c_uchar, len: size_t) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_set1_server_cert_type(s: *mut SSL, val: *const c_uchar, len: size_t) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_get_negotiated_client_cert_type(s: *const SSL) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_get_negotiated_server_cert_type(s: *const SSL) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_add_expected_rpk(s: *mut SSL, rpk: *mut EVP_PKEY) -> c_int;
+    #[cfg(ossl320)]
+    pub fn SSL_get0_peer_rpk(s: *const SSL) -> *mut EVP_PKEY;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_get_max_fragment_length(sess: *const SSL_SESSION) -> u8;
+}
+
 #[cfg(ossl111)]
 pub type SSL_CTX_keylog_cb_func =
     Option<unsafe extern "C" fn(ssl: *const SSL, line: *const c_char)>;
@@ -419,6 +719,16 @@ extern "C" {
     pub fn SSL_set_max_early_data(ctx: *mut SSL, max_early_data: u32) -> c_int;
     #[cfg(ossl111)]
     pub fn SSL_get_max_early_data(ctx: *const SSL) -> u32;
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_recv_max_early_data(ctx: *mut SSL_CTX, recv_max_early_data: u32) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_get_recv_max_early_data(ctx: *const SSL_CTX) -> u32;
+    #[cfg(ossl111)]
+    pub fn SSL_set_recv_max_early_data(ctx: *mut SSL, recv_max_early_data: u32) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_get_recv_max_early_data(ctx: *const SSL) -> u32;
+    #[cfg(ossl111)]
+    pub fn SSL_get_early_data_status(ssl: *const SSL) -> c_int;
 
     pub fn SSL_get_finished(s: *const SSL, buf: *mut c_void, count: size_t) -> size_t;
     pub fn SSL_get_peer_finished(s: *const SSL, buf: *mut c_void, count: size_t) -> size_t;
@@ -477,8 +787,28 @@ extern "C" {
     pub fn SSL_CIPHER_standard_name(cipher: *const SSL_CIPHER) -> *const c_char;
     #[cfg(ossl111)]
     pub fn OPENSSL_cipher_name(rfc_name: *const c_char) -> *const c_char;
+    #[cfg(ossl111)]
+    pub fn SSL_CIPHER_find(ssl: *mut SSL, ptr: *const c_uchar) -> *const SSL_CIPHER;
+    #[cfg(ossl111)]
+    pub fn SSL_CIPHER_get_protocol_id(c: *const SSL_CIPHER) -> u16;
+    #[cfg(ossl110)]
+    pub fn SSL_CIPHER_get_id(c: *const SSL_CIPHER) -> u32;
+    #[cfg(ossl110)]
+    pub fn SSL_CIPHER_get_kx_nid(c: *const SSL_CIPHER) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_CIPHER_get_auth_nid(c: *const SSL_CIPHER) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_CIPHER_is_aead(c: *const SSL_CIPHER) -> c_int;
+
+    pub fn SSL_get_ciphers(ssl: *const SSL) -> *mut stack_st_SSL_CIPHER;
+    #[cfg(ossl110)]
+    pub fn SSL_CTX_get_ciphers(ctx: *const SSL_CTX) -> *mut stack_st_SSL_CIPHER;
+    #[cfg(ossl110)]
+    pub fn SSL_get1_supported_ciphers(ssl: *mut SSL) -> *mut stack_st_SSL_CIPHER;
 
     pub fn SSL_pending(ssl: *const SSL) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_has_pending(s: *const SSL) -> c_int;
     pub fn SSL_set_bio(ssl: *mut SSL, rbio: *mut BIO, wbio: *mut BIO);
     pub fn SSL_get_rbio(ssl: *const SSL) -> *mut BIO;
     pub fn SSL_get_wbio(ssl: *const SSL) -> *mut BIO;
@@ -494,6 +824,24 @@ extern "C" {
     );
     pub fn SSL_CTX_use_PrivateKey(ctx: *mut SSL_CTX, key: *mut EVP_PKEY) -> c_int;
     pub fn SSL_CTX_use_certificate(ctx: *mut SSL_CTX, cert: *mut X509) -> c_int;
+    pub fn SSL_use_PrivateKey(ssl: *mut SSL, key: *mut EVP_PKEY) -> c_int;
+    pub fn SSL_use_certificate(ssl: *mut SSL, cert: *mut X509) -> c_int;
+    pub fn SSL_CTX_set_client_cert_cb(
+        ctx: *mut SSL_CTX,
+        client_cert_cb: Option<
+            unsafe extern "C" fn(
+                ssl: *mut SSL,
+                x509: *mut *mut X509,
+                pkey: *mut *mut EVP_PKEY,
+            ) -> c_int,
+        >,
+    );
+    #[cfg(ossl102)]
+    pub fn SSL_CTX_set_cert_cb(
+        ctx: *mut SSL_CTX,
+        cb: Option<unsafe extern "C" fn(ssl: *mut SSL, arg: *mut c_void) -> c_int>,
+        arg: *mut c_void,
+    );
 
     pub fn SSL_CTX_use_PrivateKey_file(
         ctx: *mut SSL_CTX,
@@ -520,15 +868,47 @@ extern "C" {
     pub fn SSL_SESSION_get_timeout(s: *const SSL_SESSION) -> c_long;
     #[cfg(ossl110)]
     pub fn SSL_SESSION_get_protocol_version(s: *const SSL_SESSION) -> c_int;
+    #[cfg(any(ossl110, libressl270))]
+    pub fn SSL_SESSION_get0_cipher(s: *const SSL_SESSION) -> *const SSL_CIPHER;
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_get_ticket_lifetime_hint(s: *const SSL_SESSION) -> c_ulong;
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_get0_ticket(
+        s: *const SSL_SESSION,
+        tick: *mut *const c_uchar,
+        len: *mut size_t,
+    );
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_is_resumable(s: *const SSL_SESSION) -> c_int;
 
     #[cfg(ossl111)]
     pub fn SSL_SESSION_set_max_early_data(ctx: *mut SSL_SESSION, max_early_data: u32) -> c_int;
     #[cfg(ossl111)]
     pub fn SSL_SESSION_get_max_early_data(ctx: *const SSL_SESSION) -> u32;
 
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set1_master_key(
+        sess: *mut SSL_SESSION,
+        in_: *const c_uchar,
+        len: size_t,
+    ) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set_cipher(s: *mut SSL_SESSION, cipher: *const SSL_CIPHER) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set_protocol_version(s: *mut SSL_SESSION, version: c_int) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set1_hostname(s: *mut SSL_SESSION, hostname: *const c_char) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set1_alpn_selected(
+        s: *mut SSL_SESSION,
+        alpn: *const c_uchar,
+        len: size_t,
+    ) -> c_int;
+
     pub fn SSL_SESSION_get_id(s: *const SSL_SESSION, len: *mut c_uint) -> *const c_uchar;
     #[cfg(any(ossl110, libressl273))]
     pub fn SSL_SESSION_up_ref(ses: *mut SSL_SESSION) -> c_int;
+    pub fn SSL_SESSION_new() -> *mut SSL_SESSION;
     pub fn SSL_SESSION_free(s: *mut SSL_SESSION);
 }
 const_ptr_api! {
@@ -560,10 +940,24 @@ extern "C" {
     );
     pub fn SSL_CTX_set_verify_depth(ctx: *mut SSL_CTX, depth: c_int);
 
+    pub fn SSL_CTX_set_cert_verify_callback(
+        ctx: *mut SSL_CTX,
+        cb: Option<extern "C" fn(*mut X509_STORE_CTX, *mut c_void) -> c_int>,
+        arg: *mut c_void,
+    );
+
     #[cfg(ossl111)]
     pub fn SSL_CTX_set_post_handshake_auth(ctx: *mut SSL_CTX, val: c_int);
+    #[cfg(ossl111)]
+    pub fn SSL_set_post_handshake_auth(ssl: *mut SSL, val: c_int);
+    #[cfg(ossl111)]
+    pub fn SSL_verify_client_post_handshake(ssl: *mut SSL) -> c_int;
 
     pub fn SSL_CTX_check_private_key(ctx: *const SSL_CTX) -> c_int;
+    pub fn SSL_check_private_key(ssl: *const SSL) -> c_int;
+
+    #[cfg(ossl110)]
+    pub fn DTLSv1_listen(s: *mut SSL, client: *mut BIO_ADDR) -> c_int;
 
     pub fn SSL_CTX_set_session_id_context(
         ssl: *mut SSL_CTX,
@@ -620,10 +1014,19 @@ extern "C" {
     ) -> c_int;
 
     pub fn SSL_free(ssl: *mut SSL);
+    pub fn SSL_clear(ssl: *mut SSL) -> c_int;
     pub fn SSL_accept(ssl: *mut SSL) -> c_int;
     #[cfg(ossl111)]
     pub fn SSL_stateless(s: *mut SSL) -> c_int;
     pub fn SSL_connect(ssl: *mut SSL) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_key_update(s: *mut SSL, updatetype: c_int) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_get_key_update_type(s: *const SSL) -> c_int;
+    pub fn SSL_renegotiate(s: *mut SSL) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_renegotiate_abbreviated(s: *mut SSL) -> c_int;
+    pub fn SSL_renegotiate_pending(s: *const SSL) -> c_int;
     pub fn SSL_read(ssl: *mut SSL, buf: *mut c_void, num: c_int) -> c_int;
     pub fn SSL_peek(ssl: *mut SSL, buf: *mut c_void, num: c_int) -> c_int;
     #[cfg(ossl111)]
@@ -635,8 +1038,63 @@ extern "C" {
     ) -> c_int;
 }
 
+#[cfg(ossl111)]
+pub type SSL_CTX_generate_session_ticket_fn =
+    Option<unsafe extern "C" fn(s: *mut SSL, arg: *mut c_void) -> c_int>;
+#[cfg(ossl111)]
+pub type SSL_CTX_decrypt_session_ticket_fn = Option<
+    unsafe extern "C" fn(
+        s: *mut SSL,
+        ss: *mut SSL_SESSION,
+        keyname: *const c_uchar,
+        keyname_length: size_t,
+        status: c_int,
+        arg: *mut c_void,
+    ) -> c_int,
+>;
+
+#[cfg(ossl300)]
+pub type SSL_async_callback_fn =
+    Option<unsafe extern "C" fn(s: *mut SSL, arg: *mut c_void) -> c_int>;
+extern "C" {
+    #[cfg(ossl110)]
+    pub fn SSL_waiting_for_async(s: *mut SSL) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_get_all_async_fds(
+        s: *mut SSL,
+        fds: *mut OSSL_ASYNC_FD,
+        numfds: *mut size_t,
+    ) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_get_changed_async_fds(
+        s: *mut SSL,
+        addfd: *mut OSSL_ASYNC_FD,
+        numaddfds: *mut size_t,
+        delfd: *mut OSSL_ASYNC_FD,
+        numdelfds: *mut size_t,
+    ) -> c_int;
+    #[cfg(ossl300)]
+    pub fn SSL_CTX_set_async_callback(ctx: *mut SSL_CTX, callback: SSL_async_callback_fn) -> c_int;
+    #[cfg(ossl300)]
+    pub fn SSL_CTX_set_async_callback_arg(ctx: *mut SSL_CTX, arg: *mut c_void) -> c_int;
+    #[cfg(ossl300)]
+    pub fn SSL_set_async_callback(s: *mut SSL, callback: SSL_async_callback_fn) -> c_int;
+    #[cfg(ossl300)]
+    pub fn SSL_set_async_callback_arg(s: *mut SSL, arg: *mut c_void) -> c_int;
+    #[cfg(ossl300)]
+    pub fn SSL_get_async_status(s: *mut SSL, status: *mut c_int) -> c_int;
+}
+
 extern "C" {
     pub fn SSL_write(ssl: *mut SSL, buf: *const c_void, num: c_int) -> c_int;
+    #[cfg(ossl330)]
+    pub fn SSL_write_ex2(
+        s: *mut SSL,
+        buf: *const c_void,
+        num: size_t,
+        flags: u64,
+        written: *mut size_t,
+    ) -> c_int;
     #[cfg(ossl111)]
     pub fn SSL_write_early_data(
         s: *mut SSL,
@@ -716,6 +1174,12 @@ const_ptr_api! {
     }
 }
 
+const_ptr_api! {
+    extern "C" {
+        pub fn BIO_f_ssl() -> #[const_ptr_if(any(ossl110, libressl280))] BIO_METHOD;
+    }
+}
+
 extern "C" {
     pub fn SSL_set_connect_state(s: *mut SSL);
     pub fn SSL_set_accept_state(s: *mut SSL);
@@ -745,6 +1209,10 @@ extern "C" {
 
     pub fn SSL_set_shutdown(ss: *mut SSL, mode: c_int);
     pub fn SSL_get_shutdown(ssl: *const SSL) -> c_int;
+    pub fn SSL_CTX_set_quiet_shutdown(ctx: *mut SSL_CTX, mode: c_int);
+    pub fn SSL_CTX_get_quiet_shutdown(ctx: *const SSL_CTX) -> c_int;
+    pub fn SSL_set_quiet_shutdown(ssl: *mut SSL, mode: c_int);
+    pub fn SSL_get_quiet_shutdown(ssl: *const SSL) -> c_int;
     pub fn SSL_version(ssl: *const SSL) -> c_int;
     pub fn SSL_get_session(s: *const SSL) -> *mut SSL_SESSION;
     pub fn SSL_get_SSL_CTX(ssl: *const SSL) -> *mut SSL_CTX;
@@ -4,6 +4,11 @@ use *;
 extern "C" {
     pub fn SSL_get_servername(ssl: *const SSL, name_type: c_int) -> *const c_char;
 
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_tlsext_max_fragment_length(ctx: *mut SSL_CTX, mode: u8) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_set_tlsext_max_fragment_length(ssl: *mut SSL, mode: u8) -> c_int;
+
     pub fn SSL_export_keying_material(
         s: *mut SSL,
         out: *mut c_uchar,
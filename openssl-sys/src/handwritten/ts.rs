@@ -0,0 +1,112 @@
+use libc::*;
+use *;
+
+pub enum TS_MSG_IMPRINT {}
+pub enum TS_REQ {}
+pub enum TS_STATUS_INFO {}
+pub enum TS_TST_INFO {}
+pub enum TS_RESP {}
+pub enum TS_RESP_CTX {}
+pub enum TS_VERIFY_CTX {}
+
+#[cfg(ossl110)]
+pub type TS_serial_cb =
+    Option<unsafe extern "C" fn(ctx: *mut TS_RESP_CTX, data: *mut c_void) -> *mut ASN1_INTEGER>;
+
+#[cfg(ossl110)]
+extern "C" {
+    pub fn TS_MSG_IMPRINT_new() -> *mut TS_MSG_IMPRINT;
+    pub fn TS_MSG_IMPRINT_free(a: *mut TS_MSG_IMPRINT);
+    pub fn TS_MSG_IMPRINT_set_algo(a: *mut TS_MSG_IMPRINT, alg: *mut X509_ALGOR) -> c_int;
+    pub fn TS_MSG_IMPRINT_get_algo(a: *mut TS_MSG_IMPRINT) -> *mut X509_ALGOR;
+    pub fn TS_MSG_IMPRINT_set_msg(a: *mut TS_MSG_IMPRINT, d: *mut c_uchar, len: c_int) -> c_int;
+    pub fn TS_MSG_IMPRINT_get_msg(a: *mut TS_MSG_IMPRINT) -> *mut ASN1_OCTET_STRING;
+
+    pub fn TS_REQ_new() -> *mut TS_REQ;
+    pub fn TS_REQ_free(a: *mut TS_REQ);
+    pub fn d2i_TS_REQ(a: *mut *mut TS_REQ, pp: *mut *const c_uchar, length: c_long) -> *mut TS_REQ;
+    pub fn i2d_TS_REQ(a: *const TS_REQ, pp: *mut *mut c_uchar) -> c_int;
+    pub fn TS_REQ_set_version(a: *mut TS_REQ, version: c_long) -> c_int;
+    pub fn TS_REQ_get_version(a: *const TS_REQ) -> c_long;
+    pub fn TS_REQ_set_msg_imprint(a: *mut TS_REQ, msg_imprint: *mut TS_MSG_IMPRINT) -> c_int;
+    pub fn TS_REQ_get_msg_imprint(a: *mut TS_REQ) -> *mut TS_MSG_IMPRINT;
+    pub fn TS_REQ_set_policy_id(a: *mut TS_REQ, policy: *const ASN1_OBJECT) -> c_int;
+    pub fn TS_REQ_get_policy_id(a: *mut TS_REQ) -> *mut ASN1_OBJECT;
+    pub fn TS_REQ_set_nonce(a: *mut TS_REQ, nonce: *const ASN1_INTEGER) -> c_int;
+    pub fn TS_REQ_get_nonce(a: *const TS_REQ) -> *const ASN1_INTEGER;
+    pub fn TS_REQ_set_cert_req(a: *mut TS_REQ, cert_req: c_int) -> c_int;
+    pub fn TS_REQ_get_cert_req(a: *const TS_REQ) -> c_int;
+
+    pub fn TS_STATUS_INFO_free(a: *mut TS_STATUS_INFO);
+    pub fn TS_STATUS_INFO_get0_status(a: *const TS_STATUS_INFO) -> *const ASN1_INTEGER;
+
+    pub fn TS_TST_INFO_free(a: *mut TS_TST_INFO);
+    pub fn TS_TST_INFO_get_version(a: *const TS_TST_INFO) -> c_long;
+    pub fn TS_TST_INFO_get_policy_id(a: *mut TS_TST_INFO) -> *mut ASN1_OBJECT;
+    pub fn TS_TST_INFO_get_msg_imprint(a: *mut TS_TST_INFO) -> *mut TS_MSG_IMPRINT;
+    pub fn TS_TST_INFO_get_serial(a: *const TS_TST_INFO) -> *const ASN1_INTEGER;
+    pub fn TS_TST_INFO_get_time(a: *const TS_TST_INFO) -> *const ASN1_GENERALIZEDTIME;
+    pub fn TS_TST_INFO_get_nonce(a: *const TS_TST_INFO) -> *const ASN1_INTEGER;
+
+    pub fn TS_RESP_new() -> *mut TS_RESP;
+    pub fn TS_RESP_free(a: *mut TS_RESP);
+    pub fn d2i_TS_RESP(
+        a: *mut *mut TS_RESP,
+        pp: *mut *const c_uchar,
+        length: c_long,
+    ) -> *mut TS_RESP;
+    pub fn i2d_TS_RESP(a: *const TS_RESP, pp: *mut *mut c_uchar) -> c_int;
+    pub fn TS_RESP_get_status_info(a: *mut TS_RESP) -> *mut TS_STATUS_INFO;
+    pub fn TS_RESP_get_token(a: *mut TS_RESP) -> *mut PKCS7;
+    pub fn TS_RESP_get_tst_info(a: *mut TS_RESP) -> *mut TS_TST_INFO;
+
+    pub fn TS_RESP_CTX_new() -> *mut TS_RESP_CTX;
+    pub fn TS_RESP_CTX_free(ctx: *mut TS_RESP_CTX);
+    pub fn TS_RESP_CTX_set_signer_cert(ctx: *mut TS_RESP_CTX, signer: *mut X509) -> c_int;
+    pub fn TS_RESP_CTX_set_signer_key(ctx: *mut TS_RESP_CTX, key: *mut EVP_PKEY) -> c_int;
+    pub fn TS_RESP_CTX_set_signer_digest(
+        ctx: *mut TS_RESP_CTX,
+        signer_digest: *const EVP_MD,
+    ) -> c_int;
+    pub fn TS_RESP_CTX_set_def_policy(
+        ctx: *mut TS_RESP_CTX,
+        def_policy: *const ASN1_OBJECT,
+    ) -> c_int;
+    pub fn TS_RESP_CTX_set_certs(ctx: *mut TS_RESP_CTX, certs: *mut stack_st_X509) -> c_int;
+    pub fn TS_RESP_CTX_add_policy(ctx: *mut TS_RESP_CTX, policy: *const ASN1_OBJECT) -> c_int;
+    pub fn TS_RESP_CTX_add_md(ctx: *mut TS_RESP_CTX, md: *const EVP_MD) -> c_int;
+    pub fn TS_RESP_CTX_set_accuracy(
+        ctx: *mut TS_RESP_CTX,
+        secs: c_int,
+        millis: c_int,
+        micros: c_int,
+    ) -> c_int;
+    pub fn TS_RESP_CTX_add_flags(ctx: *mut TS_RESP_CTX, flags: c_int);
+    pub fn TS_RESP_CTX_set_serial_cb(ctx: *mut TS_RESP_CTX, cb: TS_serial_cb, data: *mut c_void);
+    pub fn TS_RESP_create_response(ctx: *mut TS_RESP_CTX, req_bio: *mut BIO) -> *mut TS_RESP;
+
+    pub fn TS_VERIFY_CTX_new() -> *mut TS_VERIFY_CTX;
+    pub fn TS_VERIFY_CTX_free(ctx: *mut TS_VERIFY_CTX);
+    pub fn TS_VERIFY_CTX_set_flags(ctx: *mut TS_VERIFY_CTX, f: c_int) -> c_int;
+    pub fn TS_VERIFY_CTX_add_flags(ctx: *mut TS_VERIFY_CTX, f: c_int) -> c_int;
+    pub fn TS_VERIFY_CTX_set_imprint(
+        ctx: *mut TS_VERIFY_CTX,
+        hexstr: *mut c_uchar,
+        len: c_long,
+    ) -> *mut c_uchar;
+    pub fn TS_VERIFY_CTX_set_store(ctx: *mut TS_VERIFY_CTX, s: *mut X509_STORE) -> *mut X509_STORE;
+    #[cfg(ossl300)]
+    pub fn TS_VERIFY_CTX_set_certs(
+        ctx: *mut TS_VERIFY_CTX,
+        certs: *mut stack_st_X509,
+    ) -> *mut stack_st_X509;
+    #[cfg(not(ossl300))]
+    pub fn TS_VERIFY_CTS_set_certs(
+        ctx: *mut TS_VERIFY_CTX,
+        certs: *mut stack_st_X509,
+    ) -> *mut stack_st_X509;
+    pub fn TS_REQ_to_TS_VERIFY_CTX(req: *mut TS_REQ, ctx: *mut TS_VERIFY_CTX)
+        -> *mut TS_VERIFY_CTX;
+    pub fn TS_RESP_verify_response(ctx: *mut TS_VERIFY_CTX, response: *mut TS_RESP) -> c_int;
+    pub fn TS_RESP_verify_token(ctx: *mut TS_VERIFY_CTX, token: *mut PKCS7) -> c_int;
+}
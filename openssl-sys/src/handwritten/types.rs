@@ -4,6 +4,7 @@ use libc::*;
 use *;
 
 pub enum ASN1_INTEGER {}
+pub enum ASN1_ENUMERATED {}
 pub enum ASN1_GENERALIZEDTIME {}
 pub enum ASN1_STRING {}
 pub enum ASN1_BIT_STRING {}
@@ -39,6 +40,8 @@ cfg_if! {
         }
     }
 }
+#[cfg(ossl110)]
+pub enum BIO_ADDR {}
 cfg_if! {
     if #[cfg(ossl110)] {
         pub enum BIGNUM {}
@@ -251,6 +254,8 @@ cfg_if! {
 pub enum RSA_METHOD {}
 
 pub enum EC_KEY {}
+#[cfg(ossl110)]
+pub enum EC_KEY_METHOD {}
 
 cfg_if! {
     if #[cfg(any(ossl110, libressl280))] {
@@ -367,6 +372,10 @@ cfg_if! {
 
 pub enum X509_STORE_CTX {}
 
+pub enum X509_POLICY_TREE {}
+pub enum X509_POLICY_LEVEL {}
+pub enum X509_POLICY_NODE {}
+
 cfg_if! {
     if #[cfg(any(ossl110, libressl280))] {
         pub enum X509_VERIFY_PARAM {}
@@ -9,14 +9,20 @@ pub struct X509_VAL {
 
 pub enum X509_NAME_ENTRY {}
 
+stack!(stack_st_X509_NAME_ENTRY);
+
 stack!(stack_st_X509_NAME);
 
 pub enum X509_EXTENSION {}
 
 stack!(stack_st_X509_EXTENSION);
 
+pub enum X509_ATTRIBUTE {}
+
 stack!(stack_st_X509_ATTRIBUTE);
 
+stack!(stack_st_X509_ALGOR);
+
 cfg_if! {
     if #[cfg(ossl110)] {
         pub enum X509_REQ_INFO {}
@@ -185,6 +191,7 @@ extern "C" {
 
 const_ptr_api! {
     extern "C" {
+        pub fn i2d_X509_ALGOR(a: #[const_ptr_if(ossl300)] X509_ALGOR, pp: *mut *mut c_uchar) -> c_int;
         #[cfg(ossl102)]
         pub fn X509_ALGOR_get0(
             paobj: *mut #[const_ptr_if(ossl110)] ASN1_OBJECT,
@@ -200,7 +207,21 @@ extern "C" {
 
     pub fn X509_to_X509_REQ(x: *mut X509, pkey: *mut EVP_PKEY, md: *const EVP_MD) -> *mut X509_REQ;
 
+    pub fn X509_ALGOR_new() -> *mut X509_ALGOR;
     pub fn X509_ALGOR_free(x: *mut X509_ALGOR);
+    pub fn X509_ALGOR_set_md(alg: *mut X509_ALGOR, md: *const EVP_MD);
+    #[cfg(ossl111)]
+    pub fn X509_ALGOR_copy(dest: *mut X509_ALGOR, src: *const X509_ALGOR) -> c_int;
+    pub fn d2i_X509_ALGOR(
+        a: *mut *mut X509_ALGOR,
+        pp: *mut *const c_uchar,
+        length: c_long,
+    ) -> *mut X509_ALGOR;
+    pub fn d2i_X509_ALGORS(
+        a: *mut *mut stack_st_X509_ALGOR,
+        pp: *mut *const c_uchar,
+        length: c_long,
+    ) -> *mut stack_st_X509_ALGOR;
 
     pub fn X509_REVOKED_new() -> *mut X509_REVOKED;
     pub fn X509_REVOKED_free(x: *mut X509_REVOKED);
@@ -263,8 +284,19 @@ const_ptr_api! {
 extern "C" {
     #[cfg(ossl102)]
     pub fn X509_get_signature_nid(x: *const X509) -> c_int;
+    #[cfg(ossl110)]
+    pub fn X509_get0_tbs_sigalg(x: *const X509) -> *const X509_ALGOR;
+    #[cfg(ossl110)]
+    pub fn i2d_re_X509_tbs(x: *mut X509, pp: *mut *mut c_uchar) -> c_int;
+    #[cfg(ossl110)]
+    pub fn i2d_re_X509_REQ_tbs(req: *mut X509_REQ, pp: *mut *mut c_uchar) -> c_int;
 
     pub fn X509_EXTENSION_free(ext: *mut X509_EXTENSION);
+    pub fn d2i_X509_EXTENSION(
+        a: *mut *mut X509_EXTENSION,
+        pp: *mut *const c_uchar,
+        length: c_long,
+    ) -> *mut X509_EXTENSION;
 
     pub fn X509_NAME_ENTRY_free(x: *mut X509_NAME_ENTRY);
 
@@ -282,6 +314,8 @@ const_ptr_api! {
 extern "C" {
     pub fn d2i_X509(a: *mut *mut X509, pp: *mut *const c_uchar, length: c_long) -> *mut X509;
     pub fn d2i_X509_bio(b: *mut BIO, a: *mut *mut X509) -> *mut X509;
+    #[cfg(ossl300)]
+    pub fn X509_new_ex(libctx: *mut OSSL_LIB_CTX, propq: *const c_char) -> *mut X509;
 
     pub fn X509_get_pubkey(x: *mut X509) -> *mut EVP_PKEY;
 
@@ -291,6 +325,14 @@ extern "C" {
     pub fn X509_set_serialNumber(x: *mut X509, sn: *mut ASN1_INTEGER) -> c_int;
     pub fn X509_get_serialNumber(x: *mut X509) -> *mut ASN1_INTEGER;
 }
+const_ptr_api! {
+    extern "C" {
+        pub fn X509_check_private_key(
+            x509: #[const_ptr_if(ossl110)] X509,
+            pkey: #[const_ptr_if(ossl110)] EVP_PKEY,
+        ) -> c_int;
+    }
+}
 const_ptr_api! {
     extern "C" {
         pub fn X509_set_issuer_name(x: *mut X509, name: #[const_ptr_if(ossl300)] X509_NAME) -> c_int;
@@ -304,6 +346,7 @@ const_ptr_api! {
         pub fn X509_get_issuer_name(x: #[const_ptr_if(any(ossl110, libressl280))] ::X509) -> *mut ::X509_NAME;
         pub fn X509_set_subject_name(x: *mut X509, name: #[const_ptr_if(ossl300)] X509_NAME) -> c_int;
         pub fn X509_get_subject_name(x: #[const_ptr_if(any(ossl110, libressl280))] ::X509) -> *mut ::X509_NAME;
+        pub fn X509_get0_pubkey_bitstr(x: #[const_ptr_if(any(ossl110, libressl280))] ::X509) -> *mut ::ASN1_BIT_STRING;
     }
 }
 cfg_if! {
@@ -332,6 +375,12 @@ const_ptr_api! {
     }
 }
 extern "C" {
+    #[cfg(ossl110)]
+    pub fn X509_REQ_get0_signature(
+        req: *const X509_REQ,
+        psig: *mut *const ASN1_BIT_STRING,
+        palg: *mut *const X509_ALGOR,
+    );
     pub fn X509_REQ_set_pubkey(req: *mut X509_REQ, pkey: *mut EVP_PKEY) -> c_int;
     pub fn X509_REQ_get_pubkey(req: *mut X509_REQ) -> *mut EVP_PKEY;
     pub fn X509_REQ_get_extensions(req: *mut X509_REQ) -> *mut stack_st_X509_EXTENSION;
@@ -342,6 +391,49 @@ const_ptr_api! {
             -> c_int;
     }
 }
+extern "C" {
+    pub fn X509_REQ_get_attr_count(req: *const X509_REQ) -> c_int;
+    pub fn X509_REQ_get_attr_by_NID(req: *const X509_REQ, nid: c_int, lastpos: c_int) -> c_int;
+    pub fn X509_REQ_get_attr(req: *const X509_REQ, loc: c_int) -> *mut X509_ATTRIBUTE;
+    pub fn X509_REQ_delete_attr(req: *mut X509_REQ, loc: c_int) -> *mut X509_ATTRIBUTE;
+    pub fn X509_REQ_add1_attr(req: *mut X509_REQ, attr: *mut X509_ATTRIBUTE) -> c_int;
+    pub fn X509_REQ_add1_attr_by_NID(
+        req: *mut X509_REQ,
+        nid: c_int,
+        type_: c_int,
+        bytes: *const c_uchar,
+        len: c_int,
+    ) -> c_int;
+
+    pub fn X509_ATTRIBUTE_free(attr: *mut X509_ATTRIBUTE);
+    pub fn X509_ATTRIBUTE_create_by_OBJ(
+        attr: *mut *mut X509_ATTRIBUTE,
+        obj: *const ASN1_OBJECT,
+        atrtype: c_int,
+        data: *const c_void,
+        len: c_int,
+    ) -> *mut X509_ATTRIBUTE;
+    pub fn d2i_X509_ATTRIBUTE(
+        a: *mut *mut X509_ATTRIBUTE,
+        pp: *mut *const c_uchar,
+        length: c_long,
+    ) -> *mut X509_ATTRIBUTE;
+    pub fn X509_ATTRIBUTE_count(attr: *const X509_ATTRIBUTE) -> c_int;
+    pub fn X509_ATTRIBUTE_get0_object(attr: *mut X509_ATTRIBUTE) -> *mut ASN1_OBJECT;
+    pub fn X509_ATTRIBUTE_get0_type(attr: *mut X509_ATTRIBUTE, idx: c_int) -> *mut ASN1_TYPE;
+    pub fn X509_ATTRIBUTE_get0_data(
+        attr: *mut X509_ATTRIBUTE,
+        idx: c_int,
+        atrtype: c_int,
+        data: *mut c_void,
+    ) -> *mut c_void;
+}
+const_ptr_api! {
+    extern "C" {
+        pub fn i2d_X509_EXTENSION(ext: #[const_ptr_if(ossl300)] X509_EXTENSION, buf: *mut *mut u8) -> c_int;
+        pub fn i2d_X509_ATTRIBUTE(a: #[const_ptr_if(ossl300)] X509_ATTRIBUTE, buf: *mut *mut u8) -> c_int;
+    }
+}
 extern "C" {
     pub fn X509_set_pubkey(x: *mut X509, pkey: *mut EVP_PKEY) -> c_int;
     pub fn X509_REQ_verify(req: *mut X509_REQ, pkey: *mut EVP_PKEY) -> c_int;
@@ -447,7 +539,17 @@ const_ptr_api! {
         pub fn X509_NAME_ENTRY_get_data(ne: #[const_ptr_if(any(ossl110, libressl280))] X509_NAME_ENTRY) -> *mut ASN1_STRING;
     }
 }
+const_ptr_api! {
+    extern "C" {
+        pub fn X509_NAME_dup(n: #[const_ptr_if(ossl300)] X509_NAME) -> *mut X509_NAME;
+        pub fn X509_NAME_print_ex(out: *mut BIO, nm: #[const_ptr_if(any(ossl110, libressl280))] X509_NAME, indent: c_int, flags: c_ulong) -> c_int;
+        pub fn X509_NAME_oneline(a: #[const_ptr_if(any(ossl110, libressl280))] X509_NAME, buf: *mut c_char, size: c_int) -> *mut c_char;
+    }
+}
 extern "C" {
+    pub fn X509_NAME_delete_entry(n: *mut X509_NAME, loc: c_int) -> *mut X509_NAME_ENTRY;
+    #[cfg(any(ossl110, libressl270))]
+    pub fn X509_NAME_ENTRY_set(ne: *const X509_NAME_ENTRY) -> c_int;
     pub fn X509_NAME_add_entry_by_txt(
         x: *mut X509_NAME,
         field: *const c_char,
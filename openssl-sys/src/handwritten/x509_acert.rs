@@ -0,0 +1,73 @@
+use libc::*;
+use *;
+
+pub enum X509_ACERT {}
+pub enum OSSL_ISSUER_SERIAL {}
+
+#[cfg(ossl340)]
+extern "C" {
+    pub fn X509_ACERT_new() -> *mut X509_ACERT;
+    pub fn X509_ACERT_free(a: *mut X509_ACERT);
+    pub fn X509_ACERT_dup(a: *const X509_ACERT) -> *mut X509_ACERT;
+    pub fn d2i_X509_ACERT(
+        a: *mut *mut X509_ACERT,
+        pp: *mut *const c_uchar,
+        length: c_long,
+    ) -> *mut X509_ACERT;
+    pub fn i2d_X509_ACERT(a: *const X509_ACERT, pp: *mut *mut c_uchar) -> c_int;
+    pub fn PEM_read_bio_X509_ACERT(
+        bp: *mut BIO,
+        x: *mut *mut X509_ACERT,
+        cb: pem_password_cb,
+        u: *mut c_void,
+    ) -> *mut X509_ACERT;
+    pub fn PEM_write_bio_X509_ACERT(bp: *mut BIO, x: *const X509_ACERT) -> c_int;
+
+    pub fn X509_ACERT_sign(x: *mut X509_ACERT, pkey: *mut EVP_PKEY, md: *const EVP_MD) -> c_int;
+    pub fn X509_ACERT_verify(a: *mut X509_ACERT, r: *mut EVP_PKEY) -> c_int;
+
+    pub fn X509_ACERT_get_version(x: *const X509_ACERT) -> c_long;
+    pub fn X509_ACERT_set_version(x: *mut X509_ACERT, version: c_long) -> c_int;
+    pub fn X509_ACERT_get0_signature(
+        x: *const X509_ACERT,
+        psig: *mut *const ASN1_BIT_STRING,
+        palg: *mut *const X509_ALGOR,
+    );
+    pub fn X509_ACERT_get0_holder_entityName(x: *const X509_ACERT) -> *const stack_st_GENERAL_NAME;
+    pub fn X509_ACERT_get0_holder_baseCertId(x: *const X509_ACERT) -> *const OSSL_ISSUER_SERIAL;
+    pub fn X509_ACERT_set0_holder_entityName(x: *mut X509_ACERT, name: *mut stack_st_GENERAL_NAME);
+    pub fn X509_ACERT_set0_holder_baseCertId(x: *mut X509_ACERT, isss: *mut OSSL_ISSUER_SERIAL);
+    pub fn X509_ACERT_get0_issuerName(x: *const X509_ACERT) -> *const X509_NAME;
+    pub fn X509_ACERT_set1_issuerName(x: *mut X509_ACERT, name: *const X509_NAME) -> c_int;
+    pub fn X509_ACERT_get0_serialNumber(x: *const X509_ACERT) -> *const ASN1_INTEGER;
+    pub fn X509_ACERT_set1_serialNumber(x: *mut X509_ACERT, serial: *const ASN1_INTEGER) -> c_int;
+    pub fn X509_ACERT_get0_notBefore(x: *const X509_ACERT) -> *const ASN1_TIME;
+    pub fn X509_ACERT_get0_notAfter(x: *const X509_ACERT) -> *const ASN1_TIME;
+    pub fn X509_ACERT_set1_notBefore(
+        x: *mut X509_ACERT,
+        time: *const ASN1_GENERALIZEDTIME,
+    ) -> c_int;
+    pub fn X509_ACERT_set1_notAfter(x: *mut X509_ACERT, time: *const ASN1_GENERALIZEDTIME)
+        -> c_int;
+
+    pub fn X509_ACERT_get0_info_sigalg(x: *const X509_ACERT) -> *const X509_ALGOR;
+    pub fn X509_ACERT_get_signature_nid(x: *const X509_ACERT) -> c_int;
+    pub fn X509_ACERT_get0_extensions(x: *const X509_ACERT) -> *const stack_st_X509_EXTENSION;
+
+    pub fn X509_ACERT_get_attr_count(x: *const X509_ACERT) -> c_int;
+    pub fn X509_ACERT_get_attr(x: *const X509_ACERT, loc: c_int) -> *mut X509_ATTRIBUTE;
+    pub fn X509_ACERT_add1_attr(x: *mut X509_ACERT, attr: *mut X509_ATTRIBUTE) -> c_int;
+
+    pub fn OSSL_ISSUER_SERIAL_new() -> *mut OSSL_ISSUER_SERIAL;
+    pub fn OSSL_ISSUER_SERIAL_free(a: *mut OSSL_ISSUER_SERIAL);
+    pub fn OSSL_ISSUER_SERIAL_get0_issuer(isss: *const OSSL_ISSUER_SERIAL) -> *const X509_NAME;
+    pub fn OSSL_ISSUER_SERIAL_get0_serial(isss: *const OSSL_ISSUER_SERIAL) -> *const ASN1_INTEGER;
+    pub fn OSSL_ISSUER_SERIAL_set1_issuer(
+        isss: *mut OSSL_ISSUER_SERIAL,
+        issuer: *const X509_NAME,
+    ) -> c_int;
+    pub fn OSSL_ISSUER_SERIAL_set1_serial(
+        isss: *mut OSSL_ISSUER_SERIAL,
+        serial: *const ASN1_INTEGER,
+    ) -> c_int;
+}
@@ -12,6 +12,7 @@ extern "C" {
 extern "C" {
     pub fn X509_LOOKUP_free(ctx: *mut X509_LOOKUP);
     pub fn X509_LOOKUP_hash_dir() -> *mut X509_LOOKUP_METHOD;
+    pub fn X509_LOOKUP_file() -> *mut X509_LOOKUP_METHOD;
     pub fn X509_LOOKUP_ctrl(
         ctx: *mut X509_LOOKUP,
         cmd: c_int,
@@ -21,6 +22,11 @@ extern "C" {
     ) -> c_int;
 }
 
+extern "C" {
+    pub fn X509_load_cert_file(ctx: *mut X509_LOOKUP, file: *const c_char, _type: c_int) -> c_int;
+    pub fn X509_load_crl_file(ctx: *mut X509_LOOKUP, file: *const c_char, _type: c_int) -> c_int;
+}
+
 extern "C" {
     pub fn X509_STORE_new() -> *mut X509_STORE;
     pub fn X509_STORE_free(store: *mut X509_STORE);
@@ -37,6 +43,7 @@ extern "C" {
     pub fn X509_STORE_CTX_cleanup(ctx: *mut X509_STORE_CTX);
 
     pub fn X509_STORE_add_cert(store: *mut X509_STORE, x: *mut X509) -> c_int;
+    pub fn X509_STORE_add_crl(store: *mut X509_STORE, x: *mut X509_CRL) -> c_int;
 
     pub fn X509_STORE_add_lookup(
         store: *mut X509_STORE,
@@ -58,6 +65,53 @@ const_ptr_api! {
 extern "C" {
     pub fn X509_STORE_CTX_set_error(ctx: *mut X509_STORE_CTX, error: c_int);
 }
+cfg_if! {
+    if #[cfg(ossl110)] {
+        const_ptr_api! {
+            extern "C" {
+                pub fn X509_STORE_CTX_get0_cert(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> *mut X509;
+                pub fn X509_STORE_CTX_get0_untrusted(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> *mut stack_st_X509;
+            }
+        }
+        extern "C" {
+            pub fn X509_STORE_CTX_set0_untrusted(ctx: *mut X509_STORE_CTX, sk: *mut stack_st_X509);
+        }
+    }
+}
+
+#[cfg(ossl110)]
+stack!(stack_st_X509_POLICY_NODE);
+
+cfg_if! {
+    if #[cfg(ossl110)] {
+        const_ptr_api! {
+            extern "C" {
+                pub fn X509_STORE_CTX_get0_policy_tree(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> *mut X509_POLICY_TREE;
+                pub fn X509_STORE_CTX_get_explicit_policy(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> c_int;
+            }
+        }
+        extern "C" {
+            pub fn X509_policy_tree_level_count(tree: *const X509_POLICY_TREE) -> c_int;
+            pub fn X509_policy_tree_get0_level(
+                tree: *const X509_POLICY_TREE,
+                i: c_int,
+            ) -> *mut X509_POLICY_LEVEL;
+            pub fn X509_policy_tree_get0_policies(
+                tree: *const X509_POLICY_TREE,
+            ) -> *mut stack_st_X509_POLICY_NODE;
+            pub fn X509_policy_tree_get0_user_policies(
+                tree: *const X509_POLICY_TREE,
+            ) -> *mut stack_st_X509_POLICY_NODE;
+            pub fn X509_policy_level_node_count(level: *mut X509_POLICY_LEVEL) -> c_int;
+            pub fn X509_policy_level_get0_node(
+                level: *const X509_POLICY_LEVEL,
+                i: c_int,
+            ) -> *mut X509_POLICY_NODE;
+            pub fn X509_policy_node_get0_policy(node: *const X509_POLICY_NODE) -> *const ASN1_OBJECT;
+            pub fn X509_policy_node_get0_parent(node: *const X509_POLICY_NODE) -> *const X509_POLICY_NODE;
+        }
+    }
+}
 cfg_if! {
     if #[cfg(ossl110)] {
         const_ptr_api! {
@@ -96,6 +150,12 @@ extern "C" {
         namelen: size_t,
     ) -> c_int;
     #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_add1_host(
+        param: *mut X509_VERIFY_PARAM,
+        name: *const c_char,
+        namelen: size_t,
+    ) -> c_int;
+    #[cfg(any(ossl102, libressl261))]
     pub fn X509_VERIFY_PARAM_set_hostflags(param: *mut X509_VERIFY_PARAM, flags: c_uint);
     #[cfg(any(ossl102, libressl261))]
     pub fn X509_VERIFY_PARAM_set1_ip(
@@ -103,4 +163,33 @@ extern "C" {
         ip: *const c_uchar,
         iplen: size_t,
     ) -> c_int;
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_set1_email(
+        param: *mut X509_VERIFY_PARAM,
+        email: *const c_char,
+        emaillen: size_t,
+    ) -> c_int;
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_set_time(param: *mut X509_VERIFY_PARAM, t: time_t);
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_set_depth(param: *mut X509_VERIFY_PARAM, depth: c_int);
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_set_purpose(param: *mut X509_VERIFY_PARAM, purpose: c_int) -> c_int;
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_add0_policy(
+        param: *mut X509_VERIFY_PARAM,
+        policy: *mut ASN1_OBJECT,
+    ) -> c_int;
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_set1_policies(
+        param: *mut X509_VERIFY_PARAM,
+        policies: *mut stack_st_ASN1_OBJECT,
+    ) -> c_int;
+}
+
+const_ptr_api! {
+    extern "C" {
+        #[cfg(any(ossl102, libressl261))]
+        pub fn X509_STORE_CTX_get0_param(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> *mut X509_VERIFY_PARAM;
+    }
 }
@@ -4,7 +4,9 @@ use *;
 pub enum CONF_METHOD {}
 
 extern "C" {
+    pub fn GENERAL_NAME_new() -> *mut GENERAL_NAME;
     pub fn GENERAL_NAME_free(name: *mut GENERAL_NAME);
+    pub fn GENERAL_NAME_set0_value(a: *mut GENERAL_NAME, type_: c_int, value: *mut c_void);
 }
 
 #[repr(C)]
@@ -30,6 +32,96 @@ extern "C" {
     pub fn AUTHORITY_KEYID_free(akid: *mut AUTHORITY_KEYID);
 }
 
+#[repr(C)]
+pub struct DIST_POINT_NAME {
+    pub type_: c_int,
+    pub name: DIST_POINT_NAME_st_anon_union,
+    pub dpname: *mut X509_NAME,
+}
+
+#[repr(C)]
+pub union DIST_POINT_NAME_st_anon_union {
+    pub fullname: *mut stack_st_GENERAL_NAME,
+    pub relativename: *mut stack_st_X509_NAME_ENTRY,
+}
+
+#[repr(C)]
+pub struct DIST_POINT {
+    pub distpoint: *mut DIST_POINT_NAME,
+    pub reasons: *mut ASN1_BIT_STRING,
+    pub CRLissuer: *mut stack_st_GENERAL_NAME,
+    pub dp_reasons: c_int,
+}
+
+stack!(stack_st_DIST_POINT);
+
+extern "C" {
+    pub fn DIST_POINT_free(dp: *mut DIST_POINT);
+    pub fn DIST_POINT_NAME_free(dpn: *mut DIST_POINT_NAME);
+}
+
+#[repr(C)]
+pub struct ISSUING_DIST_POINT {
+    pub distpoint: *mut DIST_POINT_NAME,
+    pub onlyuser: c_int,
+    pub onlyCA: c_int,
+    pub onlysomereasons: *mut ASN1_BIT_STRING,
+    pub indirectCRL: c_int,
+    pub onlyattr: c_int,
+}
+
+extern "C" {
+    pub fn ISSUING_DIST_POINT_free(idp: *mut ISSUING_DIST_POINT);
+}
+
+#[repr(C)]
+pub struct POLICY_CONSTRAINTS {
+    pub requireExplicitPolicy: *mut ASN1_INTEGER,
+    pub inhibitPolicyMapping: *mut ASN1_INTEGER,
+}
+
+extern "C" {
+    pub fn POLICY_CONSTRAINTS_free(pc: *mut POLICY_CONSTRAINTS);
+}
+
+#[repr(C)]
+pub struct BASIC_CONSTRAINTS {
+    pub ca: c_int,
+    pub pathlen: *mut ASN1_INTEGER,
+}
+
+extern "C" {
+    pub fn BASIC_CONSTRAINTS_free(bc: *mut BASIC_CONSTRAINTS);
+}
+
+#[repr(C)]
+pub union POLICYQUALINFO_st_anon_union {
+    pub cpsuri: *mut ASN1_STRING,
+    pub usernotice: *mut c_void,
+    pub other: *mut ASN1_TYPE,
+}
+
+#[repr(C)]
+pub struct POLICYQUALINFO {
+    pub pqualid: *mut ASN1_OBJECT,
+    pub d: POLICYQUALINFO_st_anon_union,
+}
+
+stack!(stack_st_POLICYQUALINFO);
+
+#[repr(C)]
+pub struct POLICYINFO {
+    pub policyid: *mut ASN1_OBJECT,
+    pub qualifiers: *mut stack_st_POLICYQUALINFO,
+}
+
+stack!(stack_st_POLICYINFO);
+
+extern "C" {
+    pub fn POLICYQUALINFO_free(a: *mut POLICYQUALINFO);
+    pub fn POLICYINFO_free(a: *mut POLICYINFO);
+}
+
 const_ptr_api! {
     extern "C" {
         pub fn X509V3_EXT_nconf_nid(
@@ -53,6 +145,13 @@ extern "C" {
 
     pub fn X509V3_set_nconf(ctx: *mut X509V3_CTX, conf: *mut CONF);
 
+    pub fn X509V3_add_value(
+        name: *const c_char,
+        value: *const c_char,
+        extlist: *mut *mut stack_st_CONF_VALUE,
+    ) -> c_int;
+    pub fn X509V3_conf_free(val: *mut CONF_VALUE);
+
     pub fn X509V3_set_ctx(
         ctx: *mut X509V3_CTX,
         issuer: *mut X509,
@@ -65,6 +164,26 @@ extern "C" {
     pub fn X509_get1_ocsp(x: *mut X509) -> *mut stack_st_OPENSSL_STRING;
 }
 
+#[cfg(any(ossl102, libressl261))]
+extern "C" {
+    pub fn X509_check_host(
+        x: *mut X509,
+        chk: *const c_char,
+        chklen: size_t,
+        flags: c_uint,
+        peername: *mut *mut c_char,
+    ) -> c_int;
+    pub fn X509_check_email(
+        x: *mut X509,
+        chk: *const c_char,
+        chklen: size_t,
+        flags: c_uint,
+    ) -> c_int;
+    pub fn X509_check_ip(x: *mut X509, chk: *const c_uchar, chklen: size_t, flags: c_uint)
+        -> c_int;
+    pub fn X509_check_ip_asc(x: *mut X509, ipasc: *const c_char, flags: c_uint) -> c_int;
+}
+
 const_ptr_api! {
     extern "C" {
         pub fn X509V3_get_d2i(
@@ -23,7 +23,9 @@ pub use aes::*;
 pub use asn1::*;
 pub use bio::*;
 pub use bn::*;
+pub use cmp::*;
 pub use cms::*;
+pub use crmf::*;
 pub use crypto::*;
 pub use dtls1::*;
 pub use ec::*;
@@ -41,6 +43,7 @@ pub use srtp::*;
 pub use ssl::*;
 pub use ssl3::*;
 pub use tls1::*;
+pub use ts::*;
 pub use types::*;
 pub use x509::*;
 pub use x509_vfy::*;
@@ -53,7 +56,9 @@ mod aes;
 mod asn1;
 mod bio;
 mod bn;
+mod cmp;
 mod cms;
+mod crmf;
 mod crypto;
 mod dtls1;
 mod ec;
@@ -71,6 +76,7 @@ mod srtp;
 mod ssl;
 mod ssl3;
 mod tls1;
+mod ts;
 mod types;
 mod x509;
 mod x509_vfy;
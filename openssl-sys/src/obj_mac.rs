@@ -918,6 +918,44 @@ pub const NID_X25519: c_int = 1034;
 pub const NID_X448: c_int = 1035;
 #[cfg(ossl110)]
 pub const NID_hkdf: c_int = 1036;
+#[cfg(ossl110)]
+pub const NID_kx_rsa: c_int = 1037;
+#[cfg(ossl110)]
+pub const NID_kx_ecdhe: c_int = 1038;
+#[cfg(ossl110)]
+pub const NID_kx_dhe: c_int = 1039;
+#[cfg(ossl110)]
+pub const NID_kx_ecdhe_psk: c_int = 1040;
+#[cfg(ossl110)]
+pub const NID_kx_dhe_psk: c_int = 1041;
+#[cfg(ossl110)]
+pub const NID_kx_rsa_psk: c_int = 1042;
+#[cfg(ossl110)]
+pub const NID_kx_psk: c_int = 1043;
+#[cfg(ossl110)]
+pub const NID_kx_srp: c_int = 1044;
+#[cfg(ossl110)]
+pub const NID_kx_gost: c_int = 1045;
+#[cfg(ossl110)]
+pub const NID_auth_rsa: c_int = 1046;
+#[cfg(ossl110)]
+pub const NID_auth_ecdsa: c_int = 1047;
+#[cfg(ossl110)]
+pub const NID_auth_psk: c_int = 1048;
+#[cfg(ossl110)]
+pub const NID_auth_dss: c_int = 1049;
+#[cfg(ossl110)]
+pub const NID_auth_gost01: c_int = 1050;
+#[cfg(ossl110)]
+pub const NID_auth_gost12: c_int = 1051;
+#[cfg(ossl110)]
+pub const NID_auth_srp: c_int = 1052;
+#[cfg(ossl110)]
+pub const NID_auth_null: c_int = 1053;
+#[cfg(ossl111)]
+pub const NID_kx_any: c_int = 1063;
+#[cfg(ossl111)]
+pub const NID_auth_any: c_int = 1064;
 #[cfg(ossl111)]
 pub const NID_ED25519: c_int = 1087;
 #[cfg(ossl111)]
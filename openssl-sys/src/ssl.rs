@@ -22,6 +22,8 @@ pub const SSL_RECEIVED_SHUTDOWN: c_int = 2;
 pub const SSL_FILETYPE_PEM: c_int = X509_FILETYPE_PEM;
 pub const SSL_FILETYPE_ASN1: c_int = X509_FILETYPE_ASN1;
 
+pub const SSL_R_BAD_LENGTH: c_int = 271;
+
 #[cfg(ossl111)]
 pub const SSL_EXT_TLS_ONLY: c_uint = 0x0001;
 /* This extension is only allowed in DTLS */
@@ -83,6 +85,12 @@ cfg_if! {
 #[cfg(ossl101)]
 pub const SSL_OP_SAFARI_ECDHE_ECDSA_BUG: ssl_op_type!() = 0x00000040;
 
+#[cfg(ossl300)]
+pub const SSL_OP_ALLOW_CLIENT_RENEGOTIATION: ssl_op_type!() = 0x00000100;
+
+#[cfg(ossl111)]
+pub const SSL_OP_ALLOW_NO_DHE_KEX: ssl_op_type!() = 0x00000400;
+
 pub const SSL_OP_DONT_INSERT_EMPTY_FRAGMENTS: ssl_op_type!() = 0x00000800;
 
 pub const SSL_OP_NO_QUERY_MTU: ssl_op_type!() = 0x00001000;
@@ -145,6 +153,11 @@ pub const SSL_OP_NO_TLSv1_3: ssl_op_type!() = 0x20000000;
 #[cfg(ossl110h)]
 pub const SSL_OP_NO_RENEGOTIATION: ssl_op_type!() = 0x40000000;
 
+#[cfg(ossl320)]
+pub const SSL_OP_NO_TX_CERTIFICATE_COMPRESSION: ssl_op_type!() = 1 << 32;
+#[cfg(ossl320)]
+pub const SSL_OP_NO_RX_CERTIFICATE_COMPRESSION: ssl_op_type!() = 1 << 33;
+
 cfg_if! {
     if #[cfg(ossl111)] {
         pub const SSL_OP_NO_SSL_MASK: ssl_op_type!() = SSL_OP_NO_SSLv2
@@ -228,6 +241,9 @@ cfg_if! {
     }
 }
 
+#[cfg(ossl110)]
+pub const DANE_FLAG_NO_DANE_EE_NAMECHECKS: c_ulong = 1 << 0;
+
 pub const SSL_MODE_ENABLE_PARTIAL_WRITE: c_long = 0x1;
 pub const SSL_MODE_ACCEPT_MOVING_WRITE_BUFFER: c_long = 0x2;
 pub const SSL_MODE_AUTO_RETRY: c_long = 0x4;
@@ -239,14 +255,72 @@ pub const SSL_MODE_SEND_CLIENTHELLO_TIME: c_long = 0x20;
 pub const SSL_MODE_SEND_SERVERHELLO_TIME: c_long = 0x40;
 #[cfg(ossl101)]
 pub const SSL_MODE_SEND_FALLBACK_SCSV: c_long = 0x80;
+#[cfg(ossl110)]
+pub const SSL_MODE_ASYNC: c_long = 0x100;
+
+#[cfg(ossl330)]
+pub const SSL_WRITE_FLAG_CONCLUDE: u64 = 1;
+
+#[cfg(ossl320)]
+pub const SSL_STREAM_TYPE_NONE: c_int = 0;
+#[cfg(ossl320)]
+pub const SSL_STREAM_TYPE_READ: c_int = 1;
+#[cfg(ossl320)]
+pub const SSL_STREAM_TYPE_WRITE: c_int = 2;
+#[cfg(ossl320)]
+pub const SSL_STREAM_TYPE_BIDI: c_int = SSL_STREAM_TYPE_READ | SSL_STREAM_TYPE_WRITE;
+
+#[cfg(ossl320)]
+pub const SSL_STREAM_FLAG_UNI: u64 = 1 << 0;
+#[cfg(ossl320)]
+pub const SSL_STREAM_FLAG_NO_BLOCK: u64 = 1 << 1;
+#[cfg(ossl320)]
+pub const SSL_STREAM_FLAG_ADVANCE: u64 = 1 << 2;
+
+#[cfg(ossl320)]
+pub const SSL_ACCEPT_STREAM_NO_BLOCK: u64 = 1 << 0;
+
+#[cfg(ossl320)]
+pub const SSL_DEFAULT_STREAM_MODE_NONE: u32 = 0;
+#[cfg(ossl320)]
+pub const SSL_DEFAULT_STREAM_MODE_AUTO_BIDI: u32 = 1;
+#[cfg(ossl320)]
+pub const SSL_DEFAULT_STREAM_MODE_AUTO_UNI: u32 = 2;
+
+#[cfg(all(ossl110, unix))]
+pub type OSSL_ASYNC_FD = c_int;
+#[cfg(all(ossl110, windows))]
+pub type OSSL_ASYNC_FD = *mut c_void;
+
+#[cfg(ossl300)]
+pub const ASYNC_STATUS_UNSUPPORTED: c_int = 0;
+#[cfg(ossl300)]
+pub const ASYNC_STATUS_ERR: c_int = 1;
+#[cfg(ossl300)]
+pub const ASYNC_STATUS_OK: c_int = 2;
+#[cfg(ossl300)]
+pub const ASYNC_STATUS_EAGAIN: c_int = 3;
 
 pub unsafe fn SSL_CTX_set_mode(ctx: *mut SSL_CTX, op: c_long) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_MODE, op, ptr::null_mut())
 }
 
+pub const BIO_C_SET_SSL: c_int = 109;
+
+pub unsafe fn BIO_set_ssl(b: *mut BIO, ssl: *mut SSL, c: c_int) -> c_long {
+    BIO_ctrl(b, BIO_C_SET_SSL, c as c_long, ssl as *mut c_void)
+}
+
 #[cfg(ossl111)]
 pub const SSL_COOKIE_LENGTH: c_int = 4096;
 
+#[cfg(ossl111)]
+pub const SSL_KEY_UPDATE_NONE: c_int = -1;
+#[cfg(ossl111)]
+pub const SSL_KEY_UPDATE_NOT_REQUESTED: c_int = 0;
+#[cfg(ossl111)]
+pub const SSL_KEY_UPDATE_REQUESTED: c_int = 1;
+
 cfg_if! {
     if #[cfg(not(ossl110))] {
         pub unsafe fn SSL_CTX_get_options(ctx: *const SSL_CTX) -> c_ulong {
@@ -270,6 +344,18 @@ cfg_if! {
                 ptr::null_mut(),
             ) as c_ulong
         }
+
+        pub unsafe fn SSL_get_options(ssl: *const SSL) -> c_ulong {
+            SSL_ctrl(ssl as *mut _, SSL_CTRL_OPTIONS, 0, ptr::null_mut()) as c_ulong
+        }
+
+        pub unsafe fn SSL_set_options(ssl: *mut SSL, op: c_ulong) -> c_ulong {
+            SSL_ctrl(ssl, SSL_CTRL_OPTIONS, op as c_long, ptr::null_mut()) as c_ulong
+        }
+
+        pub unsafe fn SSL_clear_options(ssl: *mut SSL, op: c_ulong) -> c_ulong {
+            SSL_ctrl(ssl, SSL_CTRL_CLEAR_OPTIONS, op as c_long, ptr::null_mut()) as c_ulong
+        }
     }
 }
 
@@ -277,6 +363,32 @@ pub unsafe fn SSL_set_mtu(ssl: *mut SSL, mtu: c_long) -> c_long {
     SSL_ctrl(ssl, SSL_CTRL_SET_MTU, mtu, ptr::null_mut())
 }
 
+pub const SSL_MAX_CERT_LIST_DEFAULT: c_long = 1024 * 100;
+
+pub unsafe fn SSL_CTX_get_max_cert_list(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_GET_MAX_CERT_LIST, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_set_max_cert_list(ctx: *mut SSL_CTX, m: c_long) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SET_MAX_CERT_LIST, m, ptr::null_mut())
+}
+
+pub unsafe fn SSL_get_max_cert_list(ssl: *mut SSL) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_MAX_CERT_LIST, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_set_max_cert_list(ssl: *mut SSL, m: c_long) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_SET_MAX_CERT_LIST, m, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_set_max_send_fragment(ctx: *mut SSL_CTX, m: c_long) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SET_MAX_SEND_FRAGMENT, m, ptr::null_mut())
+}
+
+pub unsafe fn SSL_set_max_send_fragment(ssl: *mut SSL, m: c_long) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_SET_MAX_SEND_FRAGMENT, m, ptr::null_mut())
+}
+
 #[cfg(ossl110)]
 pub unsafe fn SSL_get_extms_support(ssl: *mut SSL) -> c_long {
     SSL_ctrl(ssl, SSL_CTRL_GET_EXTMS_SUPPORT, 0, ptr::null_mut())
@@ -296,9 +408,118 @@ pub const OPENSSL_NPN_UNSUPPORTED: c_int = 0;
 pub const OPENSSL_NPN_NEGOTIATED: c_int = 1;
 pub const OPENSSL_NPN_NO_OVERLAP: c_int = 2;
 
+pub const SSL_AD_CLOSE_NOTIFY: c_int = SSL3_AD_CLOSE_NOTIFY;
+pub const SSL_AD_UNEXPECTED_MESSAGE: c_int = SSL3_AD_UNEXPECTED_MESSAGE;
+pub const SSL_AD_BAD_RECORD_MAC: c_int = SSL3_AD_BAD_RECORD_MAC;
+pub const SSL_AD_DECRYPTION_FAILED: c_int = TLS1_AD_DECRYPTION_FAILED;
+pub const SSL_AD_RECORD_OVERFLOW: c_int = TLS1_AD_RECORD_OVERFLOW;
+pub const SSL_AD_DECOMPRESSION_FAILURE: c_int = SSL3_AD_DECOMPRESSION_FAILURE;
+pub const SSL_AD_HANDSHAKE_FAILURE: c_int = SSL3_AD_HANDSHAKE_FAILURE;
+pub const SSL_AD_NO_CERTIFICATE: c_int = SSL3_AD_NO_CERTIFICATE;
+pub const SSL_AD_BAD_CERTIFICATE: c_int = SSL3_AD_BAD_CERTIFICATE;
+pub const SSL_AD_UNSUPPORTED_CERTIFICATE: c_int = SSL3_AD_UNSUPPORTED_CERTIFICATE;
+pub const SSL_AD_CERTIFICATE_REVOKED: c_int = SSL3_AD_CERTIFICATE_REVOKED;
+pub const SSL_AD_CERTIFICATE_EXPIRED: c_int = SSL3_AD_CERTIFICATE_EXPIRED;
+pub const SSL_AD_CERTIFICATE_UNKNOWN: c_int = SSL3_AD_CERTIFICATE_UNKNOWN;
 pub const SSL_AD_ILLEGAL_PARAMETER: c_int = SSL3_AD_ILLEGAL_PARAMETER;
+pub const SSL_AD_UNKNOWN_CA: c_int = TLS1_AD_UNKNOWN_CA;
+pub const SSL_AD_ACCESS_DENIED: c_int = TLS1_AD_ACCESS_DENIED;
 pub const SSL_AD_DECODE_ERROR: c_int = TLS1_AD_DECODE_ERROR;
+pub const SSL_AD_DECRYPT_ERROR: c_int = TLS1_AD_DECRYPT_ERROR;
+pub const SSL_AD_EXPORT_RESTRICTION: c_int = TLS1_AD_EXPORT_RESTRICTION;
+pub const SSL_AD_PROTOCOL_VERSION: c_int = TLS1_AD_PROTOCOL_VERSION;
+pub const SSL_AD_INSUFFICIENT_SECURITY: c_int = TLS1_AD_INSUFFICIENT_SECURITY;
+pub const SSL_AD_INTERNAL_ERROR: c_int = TLS1_AD_INTERNAL_ERROR;
+pub const SSL_AD_INAPPROPRIATE_FALLBACK: c_int = TLS1_AD_INAPPROPRIATE_FALLBACK;
+pub const SSL_AD_USER_CANCELLED: c_int = TLS1_AD_USER_CANCELLED;
+pub const SSL_AD_NO_RENEGOTIATION: c_int = TLS1_AD_NO_RENEGOTIATION;
+pub const SSL_AD_MISSING_EXTENSION: c_int = TLS13_AD_MISSING_EXTENSION;
+pub const SSL_AD_UNSUPPORTED_EXTENSION: c_int = TLS1_AD_UNSUPPORTED_EXTENSION;
+pub const SSL_AD_CERTIFICATE_UNOBTAINABLE: c_int = TLS1_AD_CERTIFICATE_UNOBTAINABLE;
 pub const SSL_AD_UNRECOGNIZED_NAME: c_int = TLS1_AD_UNRECOGNIZED_NAME;
+pub const SSL_AD_BAD_CERTIFICATE_STATUS_RESPONSE: c_int = TLS1_AD_BAD_CERTIFICATE_STATUS_RESPONSE;
+pub const SSL_AD_BAD_CERTIFICATE_HASH_VALUE: c_int = TLS1_AD_BAD_CERTIFICATE_HASH_VALUE;
+pub const SSL_AD_UNKNOWN_PSK_IDENTITY: c_int = TLS1_AD_UNKNOWN_PSK_IDENTITY;
+pub const SSL_AD_CERTIFICATE_REQUIRED: c_int = TLS13_AD_CERTIFICATE_REQUIRED;
+pub const SSL_AD_NO_APPLICATION_PROTOCOL: c_int = TLS1_AD_NO_APPLICATION_PROTOCOL;
+
+pub const SSL_ST_CONNECT: c_int = 0x1000;
+pub const SSL_ST_ACCEPT: c_int = 0x2000;
+pub const SSL_ST_MASK: c_int = 0x0FFF;
+
+pub const SSL_CB_LOOP: c_int = 0x01;
+pub const SSL_CB_EXIT: c_int = 0x02;
+pub const SSL_CB_READ: c_int = 0x04;
+pub const SSL_CB_WRITE: c_int = 0x08;
+pub const SSL_CB_ALERT: c_int = 0x4000;
+pub const SSL_CB_READ_ALERT: c_int = SSL_CB_ALERT | SSL_CB_READ;
+pub const SSL_CB_WRITE_ALERT: c_int = SSL_CB_ALERT | SSL_CB_WRITE;
+pub const SSL_CB_ACCEPT_LOOP: c_int = SSL_ST_ACCEPT | SSL_CB_LOOP;
+pub const SSL_CB_ACCEPT_EXIT: c_int = SSL_ST_ACCEPT | SSL_CB_EXIT;
+pub const SSL_CB_CONNECT_LOOP: c_int = SSL_ST_CONNECT | SSL_CB_LOOP;
+pub const SSL_CB_CONNECT_EXIT: c_int = SSL_ST_CONNECT | SSL_CB_EXIT;
+pub const SSL_CB_HANDSHAKE_START: c_int = 0x10;
+pub const SSL_CB_HANDSHAKE_DONE: c_int = 0x20;
+
+#[cfg(ossl110)]
+pub const SSL_SECOP_OTHER_TYPE: c_int = 0xffff0000u32 as c_int;
+#[cfg(ossl110)]
+pub const SSL_SECOP_OTHER_NONE: c_int = 0;
+#[cfg(ossl110)]
+pub const SSL_SECOP_OTHER_CIPHER: c_int = 1 << 16;
+#[cfg(ossl110)]
+pub const SSL_SECOP_OTHER_CURVE: c_int = 2 << 16;
+#[cfg(ossl110)]
+pub const SSL_SECOP_OTHER_DH: c_int = 3 << 16;
+#[cfg(ossl110)]
+pub const SSL_SECOP_OTHER_PKEY: c_int = 4 << 16;
+#[cfg(ossl110)]
+pub const SSL_SECOP_OTHER_SIGALG: c_int = 5 << 16;
+#[cfg(ossl110)]
+pub const SSL_SECOP_OTHER_CERT: c_int = 6 << 16;
+#[cfg(ossl110)]
+pub const SSL_SECOP_PEER: c_int = 0x1000;
+#[cfg(ossl110)]
+pub const SSL_SECOP_CIPHER_SUPPORTED: c_int = 1 | SSL_SECOP_OTHER_CIPHER;
+#[cfg(ossl110)]
+pub const SSL_SECOP_CIPHER_SHARED: c_int = 2 | SSL_SECOP_OTHER_CIPHER;
+#[cfg(ossl110)]
+pub const SSL_SECOP_CIPHER_CHECK: c_int = 3 | SSL_SECOP_OTHER_CIPHER;
+#[cfg(ossl110)]
+pub const SSL_SECOP_CURVE_SUPPORTED: c_int = 4 | SSL_SECOP_OTHER_CURVE;
+#[cfg(ossl110)]
+pub const SSL_SECOP_CURVE_SHARED: c_int = 5 | SSL_SECOP_OTHER_CURVE;
+#[cfg(ossl110)]
+pub const SSL_SECOP_CURVE_CHECK: c_int = 6 | SSL_SECOP_OTHER_CURVE;
+#[cfg(ossl110)]
+pub const SSL_SECOP_TMP_DH: c_int = 7 | SSL_SECOP_OTHER_PKEY;
+#[cfg(ossl110)]
+pub const SSL_SECOP_VERSION: c_int = 9 | SSL_SECOP_OTHER_NONE;
+#[cfg(ossl110)]
+pub const SSL_SECOP_TICKET: c_int = 10 | SSL_SECOP_OTHER_NONE;
+#[cfg(ossl110)]
+pub const SSL_SECOP_SIGALG_SUPPORTED: c_int = 11 | SSL_SECOP_OTHER_SIGALG;
+#[cfg(ossl110)]
+pub const SSL_SECOP_SIGALG_SHARED: c_int = 12 | SSL_SECOP_OTHER_SIGALG;
+#[cfg(ossl110)]
+pub const SSL_SECOP_SIGALG_CHECK: c_int = 13 | SSL_SECOP_OTHER_SIGALG;
+#[cfg(ossl110)]
+pub const SSL_SECOP_SIGALG_MASK: c_int = 14 | SSL_SECOP_OTHER_SIGALG;
+#[cfg(ossl110)]
+pub const SSL_SECOP_COMPRESSION: c_int = 15 | SSL_SECOP_OTHER_NONE;
+#[cfg(ossl110)]
+pub const SSL_SECOP_EE_KEY: c_int = 16 | SSL_SECOP_OTHER_CERT;
+#[cfg(ossl110)]
+pub const SSL_SECOP_CA_KEY: c_int = 17 | SSL_SECOP_OTHER_CERT;
+#[cfg(ossl110)]
+pub const SSL_SECOP_CA_MD: c_int = 18 | SSL_SECOP_OTHER_CERT;
+#[cfg(ossl110)]
+pub const SSL_SECOP_PEER_EE_KEY: c_int = SSL_SECOP_EE_KEY | SSL_SECOP_PEER;
+#[cfg(ossl110)]
+pub const SSL_SECOP_PEER_CA_KEY: c_int = SSL_SECOP_CA_KEY | SSL_SECOP_PEER;
+#[cfg(ossl110)]
+pub const SSL_SECOP_PEER_CA_MD: c_int = SSL_SECOP_CA_MD | SSL_SECOP_PEER;
+
 pub const SSL_ERROR_NONE: c_int = 0;
 pub const SSL_ERROR_SSL: c_int = 1;
 pub const SSL_ERROR_SYSCALL: c_int = 5;
@@ -308,11 +529,20 @@ pub const SSL_ERROR_WANT_READ: c_int = 2;
 pub const SSL_ERROR_WANT_WRITE: c_int = 3;
 pub const SSL_ERROR_WANT_X509_LOOKUP: c_int = 4;
 pub const SSL_ERROR_ZERO_RETURN: c_int = 6;
+#[cfg(ossl110)]
+pub const SSL_ERROR_WANT_ASYNC: c_int = 9;
+#[cfg(ossl110)]
+pub const SSL_ERROR_WANT_ASYNC_JOB: c_int = 10;
 #[cfg(ossl111)]
 pub const SSL_ERROR_WANT_CLIENT_HELLO_CB: c_int = 11;
+#[cfg(ossl300)]
+pub const SSL_ERROR_WANT_RETRY_VERIFY: c_int = 12;
 pub const SSL_VERIFY_NONE: c_int = 0;
 pub const SSL_VERIFY_PEER: c_int = 1;
 pub const SSL_VERIFY_FAIL_IF_NO_PEER_CERT: c_int = 2;
+pub const SSL_VERIFY_CLIENT_ONCE: c_int = 4;
+#[cfg(ossl111)]
+pub const SSL_VERIFY_POST_HANDSHAKE: c_int = 8;
 pub const SSL_CTRL_SET_TMP_DH: c_int = 3;
 pub const SSL_CTRL_SET_TMP_ECDH: c_int = 4;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
@@ -322,10 +552,26 @@ pub const SSL_CTRL_SET_MTU: c_int = 17;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
 pub const SSL_CTRL_OPTIONS: c_int = 32;
 pub const SSL_CTRL_MODE: c_int = 33;
+pub const SSL_CTRL_GET_READ_AHEAD: c_int = 40;
 pub const SSL_CTRL_SET_READ_AHEAD: c_int = 41;
+pub const SSL_CTRL_SESS_NUMBER: c_int = 20;
+pub const SSL_CTRL_SESS_CONNECT: c_int = 21;
+pub const SSL_CTRL_SESS_CONNECT_GOOD: c_int = 22;
+pub const SSL_CTRL_SESS_CONNECT_RENEGOTIATE: c_int = 23;
+pub const SSL_CTRL_SESS_ACCEPT: c_int = 24;
+pub const SSL_CTRL_SESS_ACCEPT_GOOD: c_int = 25;
+pub const SSL_CTRL_SESS_ACCEPT_RENEGOTIATE: c_int = 26;
+pub const SSL_CTRL_SESS_HIT: c_int = 27;
+pub const SSL_CTRL_SESS_CB_HIT: c_int = 28;
+pub const SSL_CTRL_SESS_MISSES: c_int = 29;
+pub const SSL_CTRL_SESS_TIMEOUTS: c_int = 30;
+pub const SSL_CTRL_SESS_CACHE_FULL: c_int = 31;
 pub const SSL_CTRL_SET_SESS_CACHE_SIZE: c_int = 42;
 pub const SSL_CTRL_GET_SESS_CACHE_SIZE: c_int = 43;
 pub const SSL_CTRL_SET_SESS_CACHE_MODE: c_int = 44;
+pub const SSL_CTRL_GET_MAX_CERT_LIST: c_int = 50;
+pub const SSL_CTRL_SET_MAX_CERT_LIST: c_int = 51;
+pub const SSL_CTRL_SET_MAX_SEND_FRAGMENT: c_int = 52;
 pub const SSL_CTRL_SET_TLSEXT_SERVERNAME_CB: c_int = 53;
 pub const SSL_CTRL_SET_TLSEXT_SERVERNAME_ARG: c_int = 54;
 pub const SSL_CTRL_SET_TLSEXT_HOSTNAME: c_int = 55;
@@ -337,13 +583,27 @@ pub const SSL_CTRL_SET_TLSEXT_STATUS_REQ_OCSP_RESP: c_int = 71;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
 pub const SSL_CTRL_CLEAR_OPTIONS: c_int = 77;
 pub const SSL_CTRL_GET_EXTRA_CHAIN_CERTS: c_int = 82;
+#[cfg(ossl102)]
+pub const SSL_CTRL_CHAIN: c_int = 88;
+#[cfg(ossl102)]
+pub const SSL_CTRL_CHAIN_CERT: c_int = 89;
+#[cfg(ossl102)]
+pub const SSL_CTRL_GET_CHAIN_CERTS: c_int = 115;
+#[cfg(ossl111)]
+pub const SSL_CTRL_GET_GROUPS: c_int = 90;
+#[cfg(ossl111)]
+pub const SSL_CTRL_SET_GROUPS: c_int = 91;
 #[cfg(ossl111)]
 pub const SSL_CTRL_SET_GROUPS_LIST: c_int = 92;
+#[cfg(ossl111)]
+pub const SSL_CTRL_GET_SHARED_GROUP: c_int = 93;
 #[cfg(any(libressl, all(ossl102, not(ossl110))))]
 pub const SSL_CTRL_SET_ECDH_AUTO: c_int = 94;
 #[cfg(ossl102)]
 pub const SSL_CTRL_SET_SIGALGS_LIST: c_int = 98;
 #[cfg(ossl102)]
+pub const SSL_CTRL_SET_CLIENT_SIGALGS_LIST: c_int = 102;
+#[cfg(ossl102)]
 pub const SSL_CTRL_SET_VERIFY_CERT_STORE: c_int = 106;
 #[cfg(ossl110)]
 pub const SSL_CTRL_GET_EXTMS_SUPPORT: c_int = 122;
@@ -353,8 +613,43 @@ pub const SSL_CTRL_SET_MIN_PROTO_VERSION: c_int = 123;
 pub const SSL_CTRL_SET_MAX_PROTO_VERSION: c_int = 124;
 #[cfg(ossl110g)]
 pub const SSL_CTRL_GET_MIN_PROTO_VERSION: c_int = 130;
+#[cfg(ossl102)]
+pub const SSL_CTRL_GET_PEER_SIGNATURE_NID: c_int = 108;
+#[cfg(ossl111)]
+pub const SSL_CTRL_GET_PEER_TMP_KEY: c_int = 109;
 #[cfg(ossl110g)]
 pub const SSL_CTRL_GET_MAX_PROTO_VERSION: c_int = 131;
+#[cfg(ossl111)]
+pub const SSL_CTRL_GET_SIGNATURE_NID: c_int = 132;
+#[cfg(ossl300)]
+pub const SSL_CTRL_GET_NEGOTIATED_GROUP: c_int = 134;
+#[cfg(ossl300)]
+pub const SSL_CTRL_SET_RETRY_VERIFY: c_int = 136;
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_get_peer_signature_nid(ssl: *mut SSL, nid: *mut c_int) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_PEER_SIGNATURE_NID, 0, nid as *mut c_void)
+}
+
+#[cfg(ossl111)]
+pub unsafe fn SSL_get_signature_nid(ssl: *mut SSL, nid: *mut c_int) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_SIGNATURE_NID, 0, nid as *mut c_void)
+}
+
+#[cfg(ossl111)]
+pub unsafe fn SSL_get_peer_tmp_key(ssl: *mut SSL, key: *mut *mut EVP_PKEY) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_PEER_TMP_KEY, 0, key as *mut c_void)
+}
+
+#[cfg(ossl300)]
+pub unsafe fn SSL_get_negotiated_group(ssl: *mut SSL) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_NEGOTIATED_GROUP, 0, ptr::null_mut())
+}
+
+#[cfg(ossl300)]
+pub unsafe fn SSL_set_retry_verify(ssl: *mut SSL) -> c_int {
+    (SSL_ctrl(ssl, SSL_CTRL_SET_RETRY_VERIFY, 0, ptr::null_mut()) > 0) as c_int
+}
 
 pub unsafe fn SSL_CTX_set_tmp_dh(ctx: *mut SSL_CTX, dh: *mut DH) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_SET_TMP_DH, 0, dh as *mut c_void)
@@ -376,6 +671,34 @@ pub unsafe fn SSL_CTX_add_extra_chain_cert(ctx: *mut SSL_CTX, x509: *mut X509) -
     SSL_CTX_ctrl(ctx, SSL_CTRL_EXTRA_CHAIN_CERT, 0, x509 as *mut c_void)
 }
 
+#[cfg(ossl102)]
+pub unsafe fn SSL_add1_chain_cert(ssl: *mut SSL, x509: *mut X509) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_CHAIN_CERT, 1, x509 as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_set0_chain(ssl: *mut SSL, chain: *mut stack_st_X509) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_CHAIN, 0, chain as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_add1_chain_cert(ctx: *mut SSL_CTX, x509: *mut X509) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_CHAIN_CERT, 1, x509 as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_get0_chain_certs(
+    ctx: *mut SSL_CTX,
+    chain: *mut *mut stack_st_X509,
+) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_GET_CHAIN_CERTS, 0, chain as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_get0_chain_certs(ssl: *mut SSL, chain: *mut *mut stack_st_X509) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_CHAIN_CERTS, 0, chain as *mut c_void)
+}
+
 pub unsafe fn SSL_CTX_get_extra_chain_certs(
     ctx: *mut SSL_CTX,
     chain: *mut *mut stack_st_X509,
@@ -398,6 +721,50 @@ pub unsafe fn SSL_CTX_set1_groups_list(ctx: *mut SSL_CTX, s: *const c_char) -> c
     )
 }
 
+#[cfg(ossl111)]
+pub unsafe fn SSL_CTX_set1_groups(
+    ctx: *mut SSL_CTX,
+    glist: *const c_int,
+    glistlen: c_long,
+) -> c_long {
+    SSL_CTX_ctrl(
+        ctx,
+        SSL_CTRL_SET_GROUPS,
+        glistlen,
+        glist as *const c_void as *mut c_void,
+    )
+}
+
+#[cfg(ossl111)]
+pub unsafe fn SSL_set1_groups_list(ssl: *mut SSL, s: *const c_char) -> c_long {
+    SSL_ctrl(
+        ssl,
+        SSL_CTRL_SET_GROUPS_LIST,
+        0,
+        s as *const c_void as *mut c_void,
+    )
+}
+
+#[cfg(ossl111)]
+pub unsafe fn SSL_set1_groups(ssl: *mut SSL, glist: *const c_int, glistlen: c_long) -> c_long {
+    SSL_ctrl(
+        ssl,
+        SSL_CTRL_SET_GROUPS,
+        glistlen,
+        glist as *const c_void as *mut c_void,
+    )
+}
+
+#[cfg(ossl111)]
+pub unsafe fn SSL_get1_groups(ssl: *mut SSL, glist: *mut c_int) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_GROUPS, 0, glist as *mut c_void)
+}
+
+#[cfg(ossl111)]
+pub unsafe fn SSL_get_shared_group(ssl: *mut SSL, n: c_long) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_SHARED_GROUP, n, ptr::null_mut())
+}
+
 #[cfg(ossl102)]
 pub unsafe fn SSL_CTX_set1_sigalgs_list(ctx: *mut SSL_CTX, s: *const c_char) -> c_long {
     SSL_CTX_ctrl(
@@ -408,6 +775,36 @@ pub unsafe fn SSL_CTX_set1_sigalgs_list(ctx: *mut SSL_CTX, s: *const c_char) ->
     )
 }
 
+#[cfg(ossl102)]
+pub unsafe fn SSL_set1_sigalgs_list(ssl: *mut SSL, s: *const c_char) -> c_long {
+    SSL_ctrl(
+        ssl,
+        SSL_CTRL_SET_SIGALGS_LIST,
+        0,
+        s as *const c_void as *mut c_void,
+    )
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_set1_client_sigalgs_list(ctx: *mut SSL_CTX, s: *const c_char) -> c_long {
+    SSL_CTX_ctrl(
+        ctx,
+        SSL_CTRL_SET_CLIENT_SIGALGS_LIST,
+        0,
+        s as *const c_void as *mut c_void,
+    )
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_set1_client_sigalgs_list(ssl: *mut SSL, s: *const c_char) -> c_long {
+    SSL_ctrl(
+        ssl,
+        SSL_CTRL_SET_CLIENT_SIGALGS_LIST,
+        0,
+        s as *const c_void as *mut c_void,
+    )
+}
+
 #[cfg(any(libressl, all(ossl102, not(ossl110))))]
 pub unsafe fn SSL_CTX_set_ecdh_auto(ctx: *mut SSL_CTX, onoff: c_int) -> c_int {
     SSL_CTX_ctrl(
@@ -506,6 +903,13 @@ pub const SSL_READ_EARLY_DATA_SUCCESS: c_int = 1;
 #[cfg(ossl111)]
 pub const SSL_READ_EARLY_DATA_FINISH: c_int = 2;
 
+#[cfg(ossl111)]
+pub const SSL_EARLY_DATA_NOT_SENT: c_int = 0;
+#[cfg(ossl111)]
+pub const SSL_EARLY_DATA_REJECTED: c_int = 1;
+#[cfg(ossl111)]
+pub const SSL_EARLY_DATA_ACCEPTED: c_int = 2;
+
 cfg_if! {
     if #[cfg(ossl110)] {
         pub unsafe fn SSL_get_ex_new_index(
@@ -541,6 +945,54 @@ pub unsafe fn SSL_CTX_sess_get_cache_size(ctx: *mut SSL_CTX) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_GET_SESS_CACHE_SIZE, 0, ptr::null_mut())
 }
 
+pub unsafe fn SSL_CTX_sess_number(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_NUMBER, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_connect(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CONNECT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_connect_good(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CONNECT_GOOD, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_connect_renegotiate(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CONNECT_RENEGOTIATE, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_accept(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_ACCEPT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_accept_good(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_ACCEPT_GOOD, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_accept_renegotiate(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_ACCEPT_RENEGOTIATE, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_hits(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_HIT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_cb_hits(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CB_HIT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_misses(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_MISSES, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_timeouts(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_TIMEOUTS, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_cache_full(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CACHE_FULL, 0, ptr::null_mut())
+}
+
 pub unsafe fn SSL_CTX_set_session_cache_mode(ctx: *mut SSL_CTX, m: c_long) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_SET_SESS_CACHE_MODE, m, ptr::null_mut())
 }
@@ -549,6 +1001,10 @@ pub unsafe fn SSL_CTX_set_read_ahead(ctx: *mut SSL_CTX, m: c_long) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_SET_READ_AHEAD, m, ptr::null_mut())
 }
 
+pub unsafe fn SSL_get_read_ahead(ssl: *mut SSL) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_READ_AHEAD, 0, ptr::null_mut())
+}
+
 #[allow(clashing_extern_declarations)]
 extern "C" {
     #[deprecated(note = "use SSL_CTX_set_tmp_dh_callback__fixed_rust instead")]
@@ -610,6 +1066,8 @@ pub unsafe fn SSL_session_reused(ssl: *mut SSL) -> c_int {
     SSL_ctrl(ssl, SSL_CTRL_GET_SESSION_REUSED, 0, ptr::null_mut()) as c_int
 }
 
+#[cfg(ossl110)]
+pub const OPENSSL_INIT_NO_LOAD_SSL_STRINGS: u64 = 0x00100000;
 #[cfg(ossl110)]
 pub const OPENSSL_INIT_LOAD_SSL_STRINGS: u64 = 0x00200000;
 #[cfg(ossl111b)]
@@ -2,4 +2,50 @@ use libc::*;
 
 pub const SSL3_VERSION: c_int = 0x300;
 
+pub const SSL3_AL_WARNING: c_int = 1;
+pub const SSL3_AL_FATAL: c_int = 2;
+
+pub const SSL3_AD_CLOSE_NOTIFY: c_int = 0;
+pub const SSL3_AD_UNEXPECTED_MESSAGE: c_int = 10;
+pub const SSL3_AD_BAD_RECORD_MAC: c_int = 20;
+pub const SSL3_AD_DECOMPRESSION_FAILURE: c_int = 30;
+pub const SSL3_AD_HANDSHAKE_FAILURE: c_int = 40;
+pub const SSL3_AD_NO_CERTIFICATE: c_int = 41;
+pub const SSL3_AD_BAD_CERTIFICATE: c_int = 42;
+pub const SSL3_AD_UNSUPPORTED_CERTIFICATE: c_int = 43;
+pub const SSL3_AD_CERTIFICATE_REVOKED: c_int = 44;
+pub const SSL3_AD_CERTIFICATE_EXPIRED: c_int = 45;
+pub const SSL3_AD_CERTIFICATE_UNKNOWN: c_int = 46;
 pub const SSL3_AD_ILLEGAL_PARAMETER: c_int = 47;
+
+pub const SSL3_RT_CHANGE_CIPHER_SPEC: c_int = 20;
+pub const SSL3_RT_ALERT: c_int = 21;
+pub const SSL3_RT_HANDSHAKE: c_int = 22;
+pub const SSL3_RT_APPLICATION_DATA: c_int = 23;
+pub const SSL3_RT_HEADER: c_int = 0x100;
+#[cfg(ossl111)]
+pub const SSL3_RT_INNER_CONTENT_TYPE: c_int = 0x101;
+
+pub const SSL3_MT_HELLO_REQUEST: c_int = 0;
+pub const SSL3_MT_CLIENT_HELLO: c_int = 1;
+pub const SSL3_MT_SERVER_HELLO: c_int = 2;
+pub const SSL3_MT_NEWSESSION_TICKET: c_int = 4;
+#[cfg(ossl111)]
+pub const SSL3_MT_END_OF_EARLY_DATA: c_int = 5;
+#[cfg(ossl111)]
+pub const SSL3_MT_ENCRYPTED_EXTENSIONS: c_int = 8;
+pub const SSL3_MT_CERTIFICATE: c_int = 11;
+pub const SSL3_MT_SERVER_KEY_EXCHANGE: c_int = 12;
+pub const SSL3_MT_CERTIFICATE_REQUEST: c_int = 13;
+pub const SSL3_MT_SERVER_DONE: c_int = 14;
+pub const SSL3_MT_CERTIFICATE_VERIFY: c_int = 15;
+pub const SSL3_MT_CLIENT_KEY_EXCHANGE: c_int = 16;
+pub const SSL3_MT_FINISHED: c_int = 20;
+pub const SSL3_MT_CERTIFICATE_STATUS: c_int = 22;
+#[cfg(ossl111)]
+pub const SSL3_MT_KEY_UPDATE: c_int = 24;
+#[cfg(ossl320)]
+pub const SSL3_MT_COMPRESSED_CERTIFICATE: c_int = 25;
+pub const SSL3_MT_NEXT_PROTO: c_int = 67;
+#[cfg(ossl111)]
+pub const SSL3_MT_MESSAGE_HASH: c_int = 254;
@@ -10,12 +10,63 @@ pub const TLS1_2_VERSION: c_int = 0x303;
 #[cfg(ossl111)]
 pub const TLS1_3_VERSION: c_int = 0x304;
 
+pub const TLS1_AD_DECRYPTION_FAILED: c_int = 21;
+pub const TLS1_AD_RECORD_OVERFLOW: c_int = 22;
+pub const TLS1_AD_UNKNOWN_CA: c_int = 48;
+pub const TLS1_AD_ACCESS_DENIED: c_int = 49;
 pub const TLS1_AD_DECODE_ERROR: c_int = 50;
+pub const TLS1_AD_DECRYPT_ERROR: c_int = 51;
+pub const TLS1_AD_EXPORT_RESTRICTION: c_int = 60;
+pub const TLS1_AD_PROTOCOL_VERSION: c_int = 70;
+pub const TLS1_AD_INSUFFICIENT_SECURITY: c_int = 71;
+pub const TLS1_AD_INTERNAL_ERROR: c_int = 80;
+pub const TLS1_AD_INAPPROPRIATE_FALLBACK: c_int = 86;
+pub const TLS1_AD_USER_CANCELLED: c_int = 90;
+pub const TLS1_AD_NO_RENEGOTIATION: c_int = 100;
+pub const TLS1_AD_UNSUPPORTED_EXTENSION: c_int = 110;
+pub const TLS1_AD_CERTIFICATE_UNOBTAINABLE: c_int = 111;
 pub const TLS1_AD_UNRECOGNIZED_NAME: c_int = 112;
+pub const TLS1_AD_BAD_CERTIFICATE_STATUS_RESPONSE: c_int = 113;
+pub const TLS1_AD_BAD_CERTIFICATE_HASH_VALUE: c_int = 114;
+pub const TLS1_AD_UNKNOWN_PSK_IDENTITY: c_int = 115;
+pub const TLS1_AD_NO_APPLICATION_PROTOCOL: c_int = 120;
+pub const TLS13_AD_MISSING_EXTENSION: c_int = 109;
+pub const TLS13_AD_CERTIFICATE_REQUIRED: c_int = 116;
 
 pub const TLSEXT_NAMETYPE_host_name: c_int = 0;
 pub const TLSEXT_STATUSTYPE_ocsp: c_int = 1;
 
+#[cfg(ossl300)]
+pub const TLSEXT_nid_unknown: c_int = 0x1000000;
+
+pub const TLSEXT_TYPE_server_name: c_int = 0;
+pub const TLSEXT_TYPE_application_layer_protocol_negotiation: c_int = 16;
+
+#[cfg(ossl111)]
+pub const TLSEXT_max_fragment_length_DISABLED: u8 = 0;
+#[cfg(ossl111)]
+pub const TLSEXT_max_fragment_length_512: u8 = 1;
+#[cfg(ossl111)]
+pub const TLSEXT_max_fragment_length_1024: u8 = 2;
+#[cfg(ossl111)]
+pub const TLSEXT_max_fragment_length_2048: u8 = 3;
+#[cfg(ossl111)]
+pub const TLSEXT_max_fragment_length_4096: u8 = 4;
+
+#[cfg(ossl320)]
+pub const TLSEXT_cert_type_x509: u8 = 0;
+#[cfg(ossl320)]
+pub const TLSEXT_cert_type_rpk: u8 = 2;
+
+#[cfg(ossl320)]
+pub const TLSEXT_comp_cert_none: c_int = 0;
+#[cfg(ossl320)]
+pub const TLSEXT_comp_cert_zlib: c_int = 1;
+#[cfg(ossl320)]
+pub const TLSEXT_comp_cert_brotli: c_int = 2;
+#[cfg(ossl320)]
+pub const TLSEXT_comp_cert_zstd: c_int = 3;
+
 pub unsafe fn SSL_set_tlsext_host_name(s: *mut SSL, name: *mut c_char) -> c_long {
     SSL_ctrl(
         s,
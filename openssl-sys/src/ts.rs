@@ -0,0 +1,49 @@
+use libc::*;
+
+use *;
+
+cfg_if! {
+    if #[cfg(ossl110)] {
+        pub const TS_STATUS_GRANTED: c_int = 0;
+        pub const TS_STATUS_GRANTED_WITH_MODS: c_int = 1;
+        pub const TS_STATUS_REJECTION: c_int = 2;
+        pub const TS_STATUS_WAITING: c_int = 3;
+        pub const TS_STATUS_REVOCATION_WARNING: c_int = 4;
+        pub const TS_STATUS_REVOCATION_NOTIFICATION: c_int = 5;
+
+        pub const TS_TSA_NAME: c_int = 0x01;
+        pub const TS_ORDERING: c_int = 0x02;
+        pub const TS_ESS_CERT_ID_CHAIN: c_int = 0x04;
+
+        pub const TS_VFY_SIGNATURE: c_int = 1 << 0;
+        pub const TS_VFY_VERSION: c_int = 1 << 1;
+        pub const TS_VFY_POLICY: c_int = 1 << 2;
+        pub const TS_VFY_IMPRINT: c_int = 1 << 3;
+        pub const TS_VFY_DATA: c_int = 1 << 4;
+        pub const TS_VFY_NONCE: c_int = 1 << 5;
+        pub const TS_VFY_SIGNER: c_int = 1 << 6;
+        pub const TS_VFY_TSA_NAME: c_int = 1 << 7;
+        pub const TS_VFY_ALL_IMPRINT: c_int = TS_VFY_SIGNATURE
+            | TS_VFY_VERSION
+            | TS_VFY_POLICY
+            | TS_VFY_IMPRINT
+            | TS_VFY_NONCE
+            | TS_VFY_SIGNER
+            | TS_VFY_TSA_NAME;
+        pub const TS_VFY_ALL_DATA: c_int = TS_VFY_SIGNATURE
+            | TS_VFY_VERSION
+            | TS_VFY_POLICY
+            | TS_VFY_DATA
+            | TS_VFY_NONCE
+            | TS_VFY_SIGNER
+            | TS_VFY_TSA_NAME;
+    }
+}
+
+#[cfg(all(ossl110, not(ossl300)))]
+pub unsafe fn TS_VERIFY_CTX_set_certs(
+    ctx: *mut TS_VERIFY_CTX,
+    certs: *mut stack_st_X509,
+) -> *mut stack_st_X509 {
+    TS_VERIFY_CTS_set_certs(ctx, certs)
+}
@@ -7,6 +7,47 @@ pub const X509_FILETYPE_ASN1: c_int = 2;
 pub const X509_FILETYPE_DEFAULT: c_int = 3;
 
 pub const ASN1_R_HEADER_TOO_LONG: c_int = 123;
+#[cfg(not(boringssl))]
+pub const ASN1_R_ILLEGAL_CHARACTERS: c_int = 124;
+#[cfg(not(boringssl))]
+pub const ASN1_R_WRONG_TAG: c_int = 168;
+#[cfg(not(boringssl))]
+pub const ASN1_R_UNKNOWN_SIGNATURE_ALGORITHM: c_int = 199;
+
+pub const X509_R_KEY_TYPE_MISMATCH: c_int = 115;
+pub const X509_R_KEY_VALUES_MISMATCH: c_int = 116;
+
+pub const XN_FLAG_SEP_MASK: c_ulong = 0xf << 16;
+pub const XN_FLAG_COMPAT: c_ulong = 0;
+pub const XN_FLAG_SEP_COMMA_PLUS: c_ulong = 1 << 16;
+pub const XN_FLAG_SEP_CPLUS_SPC: c_ulong = 2 << 16;
+pub const XN_FLAG_SEP_SPLUS_SPC: c_ulong = 3 << 16;
+pub const XN_FLAG_SEP_MULTILINE: c_ulong = 4 << 16;
+pub const XN_FLAG_DN_REV: c_ulong = 1 << 20;
+pub const XN_FLAG_FN_MASK: c_ulong = 0x3 << 21;
+pub const XN_FLAG_FN_SN: c_ulong = 0;
+pub const XN_FLAG_FN_LN: c_ulong = 1 << 21;
+pub const XN_FLAG_FN_OID: c_ulong = 2 << 21;
+pub const XN_FLAG_FN_NONE: c_ulong = 3 << 21;
+pub const XN_FLAG_SPC_EQ: c_ulong = 1 << 23;
+pub const XN_FLAG_DUMP_UNKNOWN_FIELDS: c_ulong = 1 << 24;
+pub const XN_FLAG_FN_ALIGN: c_ulong = 1 << 25;
+pub const XN_FLAG_RFC2253: c_ulong = ASN1_STRFLGS_RFC2253
+    | XN_FLAG_SEP_COMMA_PLUS
+    | XN_FLAG_DN_REV
+    | XN_FLAG_FN_SN
+    | XN_FLAG_DUMP_UNKNOWN_FIELDS;
+pub const XN_FLAG_ONELINE: c_ulong = ASN1_STRFLGS_RFC2253
+    | ASN1_STRFLGS_ESC_QUOTE
+    | XN_FLAG_SEP_CPLUS_SPC
+    | XN_FLAG_SPC_EQ
+    | XN_FLAG_FN_SN;
+pub const XN_FLAG_MULTILINE: c_ulong = ASN1_STRFLGS_ESC_CTRL
+    | ASN1_STRFLGS_ESC_MSB
+    | XN_FLAG_SEP_MULTILINE
+    | XN_FLAG_SPC_EQ
+    | XN_FLAG_FN_LN
+    | XN_FLAG_FN_ALIGN;
 
 cfg_if! {
     if #[cfg(not(ossl110))] {
@@ -15,3 +56,6 @@ cfg_if! {
         pub const X509_LU_CRL: c_int = 2;
     }
 }
+
+#[cfg(ossl340)]
+pub const X509_ACERT_VERSION_2: c_long = 1;
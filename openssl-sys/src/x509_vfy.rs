@@ -131,6 +131,21 @@ pub const X509_V_FLAG_NO_ALT_CHAINS: c_ulong = 0x100000;
 #[cfg(ossl110)]
 pub const X509_V_FLAG_NO_CHECK_TIME: c_ulong = 0x200000;
 
+pub unsafe fn X509_LOOKUP_load_file(
+    ctx: *mut X509_LOOKUP,
+    name: *const c_char,
+    _type: c_int,
+) -> c_int {
+    const X509_L_FILE_LOAD: c_int = 1;
+    X509_LOOKUP_ctrl(
+        ctx,
+        X509_L_FILE_LOAD,
+        name,
+        _type as c_long,
+        std::ptr::null_mut(),
+    )
+}
+
 pub unsafe fn X509_LOOKUP_add_dir(
     ctx: *mut X509_LOOKUP,
     name: *const c_char,
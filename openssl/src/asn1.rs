@@ -37,8 +37,9 @@ use std::str;
 
 use crate::bio::MemBio;
 use crate::bn::{BigNum, BigNumRef};
-use crate::error::ErrorStack;
+use crate::error::{push_error, ErrorStack};
 use crate::nid::Nid;
+use crate::stack::Stackable;
 use crate::string::OpensslString;
 use crate::{cvt, cvt_p};
 use openssl_macros::corresponds;
@@ -592,6 +593,10 @@ foreign_type_and_impl_send_sync! {
     pub struct Asn1ObjectRef;
 }
 
+impl Stackable for Asn1Object {
+    type StackType = ffi::stack_st_ASN1_OBJECT;
+}
+
 impl Asn1Object {
     /// Constructs an ASN.1 Object Identifier from a string representation of the OID.
     #[corresponds(OBJ_txt2obj)]
@@ -605,6 +610,14 @@ impl Asn1Object {
         }
     }
 
+    from_der! {
+        /// Deserializes a DER-encoded ASN.1 object identifier, including its tag and length.
+        #[corresponds(d2i_ASN1_OBJECT)]
+        from_der,
+        Asn1Object,
+        ffi::d2i_ASN1_OBJECT
+    }
+
     /// Return the OID as an DER encoded array of bytes. This is the ASN.1
     /// value, not including tag or length.
     ///
@@ -624,6 +637,13 @@ impl Asn1ObjectRef {
     pub fn nid(&self) -> Nid {
         unsafe { Nid::from_raw(ffi::OBJ_obj2nid(self.as_ptr())) }
     }
+
+    to_der! {
+        /// Serializes the object identifier into DER, including its tag and length.
+        #[corresponds(i2d_ASN1_OBJECT)]
+        to_der,
+        ffi::i2d_ASN1_OBJECT
+    }
 }
 
 impl fmt::Display for Asn1ObjectRef {
@@ -650,6 +670,87 @@ impl fmt::Debug for Asn1ObjectRef {
     }
 }
 
+/// Splits the DER element at the front of `der` off, returning its tag, class and contents.
+#[cfg(not(boringssl))]
+pub(crate) fn get_object<'a>(der: &mut &'a [u8]) -> Result<(c_int, c_int, &'a [u8]), ErrorStack> {
+    unsafe {
+        let mut p = der.as_ptr();
+        let mut len = 0;
+        let mut tag = 0;
+        let mut class = 0;
+        let r = ffi::ASN1_get_object(&mut p, &mut len, &mut tag, &mut class, der.len() as c_long);
+        if r & 0x80 != 0 {
+            return Err(ErrorStack::get());
+        }
+        // DER forbids the indefinite length form, which is flagged by the low bit
+        if r & 0x01 != 0 {
+            return Err(wrong_tag());
+        }
+
+        let header_len = p as usize - der.as_ptr() as usize;
+        let (contents, rest) = der[header_len..].split_at(len as usize);
+        *der = rest;
+        Ok((tag, class, contents))
+    }
+}
+
+/// Appends a DER element with the specified tag, class and contents to `out`.
+///
+/// This is the only place DER headers are written, so that every hand-assembled structure
+/// shares the same length encoding. Only tag numbers below 31 are supported.
+pub(crate) fn put_object(
+    out: &mut Vec<u8>,
+    tag: c_int,
+    class: c_int,
+    constructed: bool,
+    contents: &[u8],
+) {
+    debug_assert!((0..31).contains(&tag));
+    let mut identifier = class | tag;
+    if constructed {
+        identifier |= ffi::V_ASN1_CONSTRUCTED;
+    }
+    out.push(identifier as u8);
+
+    let len = contents.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+    out.extend_from_slice(contents);
+}
+
+/// Returns an error stack reporting an unexpected ASN.1 tag.
+#[cfg(not(boringssl))]
+pub(crate) fn wrong_tag() -> ErrorStack {
+    unsafe {
+        push_error(ffi::ERR_LIB_ASN1, ffi::ASN1_R_WRONG_TAG);
+    }
+    ErrorStack::get()
+}
+
+/// Returns an error stack reporting a signature algorithm OpenSSL does not know about.
+#[cfg(not(boringssl))]
+pub(crate) fn unknown_signature_algorithm() -> ErrorStack {
+    unsafe {
+        push_error(ffi::ERR_LIB_ASN1, ffi::ASN1_R_UNKNOWN_SIGNATURE_ALGORITHM);
+    }
+    ErrorStack::get()
+}
+
+/// Returns an error stack reporting a malformed string.
+#[cfg(not(boringssl))]
+pub(crate) fn illegal_characters() -> ErrorStack {
+    unsafe {
+        push_error(ffi::ERR_LIB_ASN1, ffi::ASN1_R_ILLEGAL_CHARACTERS);
+    }
+    ErrorStack::get()
+}
+
 cfg_if! {
     if #[cfg(any(ossl110, libressl273))] {
         use ffi::ASN1_STRING_get0_data;
@@ -766,4 +867,35 @@ mod tests {
             &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01],
         );
     }
+
+    #[test]
+    #[cfg(not(boringssl))]
+    fn put_get_object() {
+        let mut der = vec![];
+        put_object(&mut der, 2, ffi::V_ASN1_CONTEXT_SPECIFIC, false, b"ab");
+        assert_eq!(der, [0x82, 0x02, b'a', b'b']);
+
+        let long = vec![0; 300];
+        put_object(
+            &mut der,
+            ffi::V_ASN1_SEQUENCE,
+            ffi::V_ASN1_UNIVERSAL,
+            true,
+            &long,
+        );
+        assert_eq!(der[4..8], [0x30, 0x82, 0x01, 0x2c]);
+
+        let mut rest = &der[..];
+        let (tag, class, contents) = get_object(&mut rest).unwrap();
+        assert_eq!(
+            (tag, class, contents),
+            (2, ffi::V_ASN1_CONTEXT_SPECIFIC, &b"ab"[..])
+        );
+        let (tag, class, contents) = get_object(&mut rest).unwrap();
+        assert_eq!(
+            (tag, class, contents),
+            (ffi::V_ASN1_SEQUENCE, ffi::V_ASN1_UNIVERSAL, &long[..])
+        );
+        assert!(rest.is_empty());
+    }
 }
@@ -0,0 +1,416 @@
+//! Batch signature verification.
+//!
+//! The `BatchVerifier` checks many independent `(message, signature, key)` tuples at once,
+//! spreading the work across a number of threads. Each worker initializes a verification
+//! context once per distinct key and copies it for every signature made with that key, so the
+//! per-signature cost is reduced to hashing the message and checking the signature.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use openssl::batch_verify::{AbortPolicy, BatchVerifier, VerifyOutcome};
+//! use openssl::ec::{EcGroup, EcKey};
+//! use openssl::hash::MessageDigest;
+//! use openssl::nid::Nid;
+//! use openssl::pkey::PKey;
+//! use openssl::sign::Signer;
+//!
+//! let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+//! let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+//!
+//! let mut batch = BatchVerifier::new(MessageDigest::sha256());
+//! batch.threads(4).abort_policy(AbortPolicy::FirstInvalid);
+//! for message in &[&b"hello"[..], b"world"] {
+//!     let mut signer = Signer::new(MessageDigest::sha256(), &key).unwrap();
+//!     signer.update(message).unwrap();
+//!     let signature = signer.sign_to_vec().unwrap();
+//!     batch.push(message, &signature, &key);
+//! }
+//!
+//! let outcomes = batch.verify();
+//! assert!(outcomes.iter().all(VerifyOutcome::is_valid));
+//! ```
+use foreign_types::ForeignTypeRef;
+use std::collections::hash_map::{self, HashMap};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::md_ctx::MdCtx;
+use crate::pkey::{HasPublic, PKey, PKeyRef};
+#[cfg(ossl111)]
+use crate::sign::Verifier;
+
+/// Determines when a `BatchVerifier` stops checking the remaining signatures.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AbortPolicy {
+    /// Every signature in the batch is checked.
+    Never,
+    /// Verification stops once any signature is found to be invalid or an error occurs.
+    FirstInvalid,
+    /// Verification stops once an error occurs, but continues past invalid signatures.
+    FirstError,
+}
+
+/// The result of verifying a single entry of a batch.
+#[derive(Debug)]
+pub enum VerifyOutcome {
+    /// The signature is valid.
+    Valid,
+    /// The signature is invalid.
+    Invalid,
+    /// An error occurred while verifying the signature.
+    Error(ErrorStack),
+    /// The signature was not checked because verification was aborted early.
+    Skipped,
+}
+
+impl VerifyOutcome {
+    /// Determines if the signature was checked and found to be valid.
+    pub fn is_valid(&self) -> bool {
+        matches!(self, VerifyOutcome::Valid)
+    }
+}
+
+struct Entry<T> {
+    message: Vec<u8>,
+    signature: Vec<u8>,
+    key: PKey<T>,
+}
+
+/// A type which verifies a batch of signatures across a pool of threads.
+pub struct BatchVerifier<T> {
+    digest: Option<MessageDigest>,
+    threads: usize,
+    policy: AbortPolicy,
+    entries: Vec<Entry<T>>,
+}
+
+impl<T> BatchVerifier<T>
+where
+    T: HasPublic + 'static,
+{
+    /// Creates a new, empty `BatchVerifier` using the specified digest.
+    ///
+    /// This cannot be used with Ed25519 or Ed448 keys. Please refer to `new_without_digest`.
+    pub fn new(digest: MessageDigest) -> BatchVerifier<T> {
+        BatchVerifier::new_intern(Some(digest))
+    }
+
+    /// Creates a new, empty `BatchVerifier` without a digest.
+    ///
+    /// This is the only way to create a `BatchVerifier` for Ed25519 or Ed448 keys. Contexts are
+    /// not reused in this mode since OpenSSL cannot copy them.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn new_without_digest() -> BatchVerifier<T> {
+        BatchVerifier::new_intern(None)
+    }
+
+    fn new_intern(digest: Option<MessageDigest>) -> BatchVerifier<T> {
+        BatchVerifier {
+            digest,
+            threads: 1,
+            policy: AbortPolicy::Never,
+            entries: vec![],
+        }
+    }
+
+    /// Sets the number of threads used to verify the batch.
+    ///
+    /// Defaults to 1, in which case verification runs on the calling thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is 0.
+    pub fn threads(&mut self, threads: usize) -> &mut BatchVerifier<T> {
+        assert!(
+            threads > 0,
+            "a batch must be verified by at least one thread"
+        );
+        self.threads = threads;
+        self
+    }
+
+    /// Sets the policy determining when verification stops early.
+    ///
+    /// Defaults to `AbortPolicy::Never`.
+    pub fn abort_policy(&mut self, policy: AbortPolicy) -> &mut BatchVerifier<T> {
+        self.policy = policy;
+        self
+    }
+
+    /// Adds a signature of `message` made by `key` to the batch.
+    pub fn push(&mut self, message: &[u8], signature: &[u8], key: &PKeyRef<T>) {
+        self.entries.push(Entry {
+            message: message.to_vec(),
+            signature: signature.to_vec(),
+            key: key.to_owned(),
+        });
+    }
+
+    /// Returns the number of signatures in the batch.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Determines if the batch is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Verifies every signature in the batch.
+    ///
+    /// The returned outcomes are in the order the signatures were added. If verification was
+    /// aborted early, the signatures which were not checked are reported as
+    /// `VerifyOutcome::Skipped`.
+    pub fn verify(self) -> Vec<VerifyOutcome> {
+        let len = self.entries.len();
+        let shared = Arc::new(Shared {
+            digest: self.digest,
+            policy: self.policy,
+            entries: self.entries,
+            next: AtomicUsize::new(0),
+            aborted: AtomicBool::new(false),
+        });
+
+        let workers = self.threads.min(len);
+        let checked = if workers <= 1 {
+            shared.run()
+        } else {
+            let handles = (0..workers)
+                .map(|_| {
+                    let shared = shared.clone();
+                    thread::spawn(move || shared.run())
+                })
+                .collect::<Vec<_>>();
+
+            let mut checked = vec![];
+            for handle in handles {
+                match handle.join() {
+                    Ok(outcomes) => checked.extend(outcomes),
+                    Err(e) => std::panic::resume_unwind(e),
+                }
+            }
+            checked
+        };
+
+        let mut outcomes = (0..len).map(|_| VerifyOutcome::Skipped).collect::<Vec<_>>();
+        for (idx, outcome) in checked {
+            outcomes[idx] = outcome;
+        }
+        outcomes
+    }
+}
+
+struct Shared<T> {
+    digest: Option<MessageDigest>,
+    policy: AbortPolicy,
+    entries: Vec<Entry<T>>,
+    next: AtomicUsize,
+    aborted: AtomicBool,
+}
+
+impl<T> Shared<T>
+where
+    T: HasPublic,
+{
+    fn run(&self) -> Vec<(usize, VerifyOutcome)> {
+        let mut contexts = HashMap::new();
+        let mut scratch = None;
+        let mut outcomes = vec![];
+
+        while !self.aborted.load(Ordering::Relaxed) {
+            let idx = self.next.fetch_add(1, Ordering::Relaxed);
+            let entry = match self.entries.get(idx) {
+                Some(entry) => entry,
+                None => break,
+            };
+
+            let result = match self.digest {
+                Some(digest) => verify_with_digest(digest, entry, &mut contexts, &mut scratch),
+                None => verify_without_digest(entry),
+            };
+            let outcome = match result {
+                Ok(true) => VerifyOutcome::Valid,
+                Ok(false) => VerifyOutcome::Invalid,
+                Err(e) => VerifyOutcome::Error(e),
+            };
+
+            let abort = matches!(
+                (&outcome, self.policy),
+                (VerifyOutcome::Invalid, AbortPolicy::FirstInvalid)
+                    | (VerifyOutcome::Error(_), AbortPolicy::FirstInvalid)
+                    | (VerifyOutcome::Error(_), AbortPolicy::FirstError)
+            );
+            if abort {
+                self.aborted.store(true, Ordering::Relaxed);
+            }
+
+            outcomes.push((idx, outcome));
+        }
+
+        outcomes
+    }
+}
+
+fn verify_with_digest<T>(
+    digest: MessageDigest,
+    entry: &Entry<T>,
+    contexts: &mut HashMap<usize, MdCtx>,
+    scratch: &mut Option<MdCtx>,
+) -> Result<bool, ErrorStack>
+where
+    T: HasPublic,
+{
+    unsafe {
+        let template = match contexts.entry(entry.key.as_ptr() as usize) {
+            hash_map::Entry::Occupied(e) => e.into_mut(),
+            hash_map::Entry::Vacant(e) => {
+                let ctx = MdCtx::new()?;
+                let r = ffi::EVP_DigestVerifyInit(
+                    ctx.as_ptr(),
+                    ptr::null_mut(),
+                    digest.as_ptr(),
+                    ptr::null_mut(),
+                    entry.key.as_ptr(),
+                );
+                if r != 1 {
+                    return Err(ErrorStack::get());
+                }
+                e.insert(ctx)
+            }
+        };
+
+        if scratch.is_none() {
+            *scratch = Some(MdCtx::new()?);
+        }
+        let ctx = scratch.as_ref().unwrap();
+
+        if ffi::EVP_MD_CTX_copy_ex(ctx.as_ptr(), template.as_ptr()) != 1 {
+            return Err(ErrorStack::get());
+        }
+        if ffi::EVP_DigestUpdate(
+            ctx.as_ptr(),
+            entry.message.as_ptr() as *const _,
+            entry.message.len(),
+        ) != 1
+        {
+            return Err(ErrorStack::get());
+        }
+
+        let r = ffi::EVP_DigestVerifyFinal(
+            ctx.as_ptr(),
+            entry.signature.as_ptr() as *mut _,
+            entry.signature.len(),
+        );
+        match r {
+            1 => Ok(true),
+            0 => {
+                ErrorStack::get(); // discard error stack
+                Ok(false)
+            }
+            _ => Err(ErrorStack::get()),
+        }
+    }
+}
+
+#[cfg(ossl111)]
+fn verify_without_digest<T>(entry: &Entry<T>) -> Result<bool, ErrorStack>
+where
+    T: HasPublic,
+{
+    let mut verifier = Verifier::new_without_digest(&entry.key)?;
+    verifier.verify_oneshot(&entry.signature, &entry.message)
+}
+
+#[cfg(not(ossl111))]
+fn verify_without_digest<T>(_: &Entry<T>) -> Result<bool, ErrorStack> {
+    unreachable!("a batch without a digest requires OpenSSL 1.1.1")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
+    use crate::pkey::Private;
+    use crate::rsa::Rsa;
+    use crate::sign::Signer;
+
+    fn sign(key: &PKeyRef<Private>, message: &[u8]) -> Vec<u8> {
+        let mut signer = Signer::new(MessageDigest::sha256(), key).unwrap();
+        signer.update(message).unwrap();
+        signer.sign_to_vec().unwrap()
+    }
+
+    #[test]
+    fn mixed_keys() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ec = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let rsa = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+
+        let mut batch = BatchVerifier::new(MessageDigest::sha256());
+        batch.threads(3);
+        for i in 0..20u8 {
+            let key = if i % 2 == 0 { &ec } else { &rsa };
+            let message = [i; 32];
+            let mut signature = sign(key, &message);
+            if i == 7 {
+                let last = signature.len() - 1;
+                signature[last] ^= 1;
+            }
+            batch.push(&message, &signature, key);
+        }
+        assert_eq!(batch.len(), 20);
+
+        let outcomes = batch.verify();
+        assert_eq!(outcomes.len(), 20);
+        for (i, outcome) in outcomes.iter().enumerate() {
+            match outcome {
+                VerifyOutcome::Valid => assert_ne!(i, 7),
+                VerifyOutcome::Invalid => assert_eq!(i, 7),
+                outcome => panic!("unexpected outcome {:?}", outcome),
+            }
+        }
+    }
+
+    #[test]
+    fn abort_on_invalid() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let signature = sign(&key, b"hello");
+
+        let mut batch = BatchVerifier::new(MessageDigest::sha256());
+        batch.abort_policy(AbortPolicy::FirstInvalid);
+        batch.push(b"hello", &signature, &key);
+        batch.push(b"goodbye", &signature, &key);
+        batch.push(b"hello", &signature, &key);
+
+        let outcomes = batch.verify();
+        assert!(matches!(outcomes[0], VerifyOutcome::Valid));
+        assert!(matches!(outcomes[1], VerifyOutcome::Invalid));
+        assert!(matches!(outcomes[2], VerifyOutcome::Skipped));
+    }
+
+    #[test]
+    #[cfg(ossl111)]
+    fn ed25519() {
+        let key = PKey::generate_ed25519().unwrap();
+        let mut signer = Signer::new_without_digest(&key).unwrap();
+        let signature = signer.sign_oneshot_to_vec(b"hello").unwrap();
+
+        let mut batch = BatchVerifier::new_without_digest();
+        batch.threads(2);
+        batch.push(b"hello", &signature, &key);
+        batch.push(b"world", &signature, &key);
+
+        let outcomes = batch.verify();
+        assert!(outcomes[0].is_valid());
+        assert!(matches!(outcomes[1], VerifyOutcome::Invalid));
+    }
+}
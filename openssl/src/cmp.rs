@@ -0,0 +1,582 @@
+//! Certificate Management Protocol clients.
+//!
+//! CMP ([RFC 4210]) is a certificate management protocol used by enterprise and industrial PKIs.
+//! A [`CmpContext`] holds the configuration of a client, including how to reach the CA and how
+//! to authenticate to it, and runs transactions which enroll a new certificate, update the key of
+//! an existing certificate, or revoke a certificate. Messages are exchanged with the CA over HTTP
+//! using OpenSSL's built-in client.
+//!
+//! Requires OpenSSL 3.0.0 or newer.
+//!
+//! [RFC 4210]: https://www.rfc-editor.org/rfc/rfc4210
+//!
+//! # Examples
+//!
+//! Request an initial certificate, authenticating to the CA with a shared secret:
+//!
+//! ```no_run
+//! use openssl::cmp::CmpContext;
+//! use openssl::pkey::PKey;
+//! use openssl::x509::store::X509StoreBuilder;
+//! use openssl::x509::{X509Name, X509};
+//!
+//! let ca = X509::from_pem(&std::fs::read("ca.pem").unwrap()).unwrap();
+//! let mut store = X509StoreBuilder::new().unwrap();
+//! store.add_cert(ca).unwrap();
+//!
+//! let mut name = X509Name::builder().unwrap();
+//! name.append_entry_by_text("CN", "device-1234").unwrap();
+//! let name = name.build();
+//!
+//! let mut ctx = CmpContext::new().unwrap();
+//! ctx.set_server("ca.example.com").unwrap();
+//! ctx.set_server_path("/pkix/").unwrap();
+//! ctx.set_trusted_store(store.build()).unwrap();
+//! ctx.set_reference_value(b"device-1234").unwrap();
+//! ctx.set_secret_value(b"one-time password").unwrap();
+//! ctx.set_new_private_key(&PKey::generate_ed25519().unwrap()).unwrap();
+//! ctx.set_subject_name(&name).unwrap();
+//! let cert = ctx.exec_ir().unwrap();
+//! ```
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::c_int;
+use std::ffi::CString;
+use std::mem;
+use std::ptr;
+
+use crate::error::ErrorStack;
+use crate::pkey::{HasPrivate, PKeyRef};
+use crate::stack::{Stack, StackRef};
+use crate::util::ForeignTypeRefExt;
+use crate::x509::store::X509Store;
+use crate::x509::{CrlReason, X509Extension, X509NameRef, X509Ref, X509ReqRef, X509};
+use crate::{cvt, cvt_p};
+use openssl_macros::corresponds;
+
+/// An option of a `CmpContext`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CmpOption(c_int);
+
+impl CmpOption {
+    /// The maximum severity of log messages, as a syslog level.
+    pub const LOG_VERBOSITY: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_LOG_VERBOSITY);
+    /// Whether to keep the HTTP connection open between messages of a transaction.
+    pub const KEEP_ALIVE: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_KEEP_ALIVE);
+    /// The timeout for each message exchange, in seconds.
+    pub const MSG_TIMEOUT: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_MSG_TIMEOUT);
+    /// The timeout for a whole transaction, in seconds.
+    pub const TOTAL_TIMEOUT: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_TOTAL_TIMEOUT);
+    /// The requested validity period of new certificates, in days.
+    pub const VALIDITY_DAYS: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_VALIDITY_DAYS);
+    /// Whether to omit the subject alternative names of the old certificate from requests.
+    pub const SUBJECTALTNAME_NODEFAULT: CmpOption =
+        CmpOption(ffi::OSSL_CMP_OPT_SUBJECTALTNAME_NODEFAULT);
+    /// Whether to request a critical subject alternative name extension.
+    pub const SUBJECTALTNAME_CRITICAL: CmpOption =
+        CmpOption(ffi::OSSL_CMP_OPT_SUBJECTALTNAME_CRITICAL);
+    /// Whether to request a critical certificate policies extension.
+    pub const POLICIES_CRITICAL: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_POLICIES_CRITICAL);
+    /// The proof-of-possession method used in requests.
+    pub const POPO_METHOD: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_POPO_METHOD);
+    /// Whether to ask the CA to grant implicit confirmation of new certificates.
+    pub const IMPLICIT_CONFIRM: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_IMPLICIT_CONFIRM);
+    /// Whether to skip confirming new certificates.
+    pub const DISABLE_CONFIRM: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_DISABLE_CONFIRM);
+    /// The revocation reason sent in revocation requests.
+    ///
+    /// `set_revocation_reason` is a typed alternative.
+    pub const REVOCATION_REASON: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_REVOCATION_REASON);
+    /// Whether to send requests without protection.
+    pub const UNPROTECTED_SEND: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_UNPROTECTED_SEND);
+    /// Whether to accept negative responses without protection.
+    pub const UNPROTECTED_ERRORS: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_UNPROTECTED_ERRORS);
+    /// The NID of the one-way function used for MAC-based protection.
+    pub const OWF_ALGNID: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_OWF_ALGNID);
+    /// The NID of the MAC algorithm used for MAC-based protection.
+    pub const MAC_ALGNID: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_MAC_ALGNID);
+    /// The NID of the digest algorithm used for signature-based protection.
+    pub const DIGEST_ALGNID: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_DIGEST_ALGNID);
+    /// Whether to ignore the key usage extension of the CA's signing certificate.
+    pub const IGNORE_KEYUSAGE: CmpOption = CmpOption(ffi::OSSL_CMP_OPT_IGNORE_KEYUSAGE);
+    /// Whether to accept trust anchors from the extra certificates of an initialization response.
+    pub const PERMIT_TA_IN_EXTRACERTS_FOR_IR: CmpOption =
+        CmpOption(ffi::OSSL_CMP_OPT_PERMIT_TA_IN_EXTRACERTS_FOR_IR);
+
+    /// Constructs a `CmpOption` from a raw value.
+    pub fn from_raw(raw: c_int) -> CmpOption {
+        CmpOption(raw)
+    }
+
+    /// Returns the raw value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// The status of the last response received from the CA.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CmpStatus(c_int);
+
+impl CmpStatus {
+    /// The request was granted.
+    pub const ACCEPTED: CmpStatus = CmpStatus(ffi::OSSL_CMP_PKISTATUS_accepted);
+    /// The request was granted with modifications.
+    pub const GRANTED_WITH_MODS: CmpStatus = CmpStatus(ffi::OSSL_CMP_PKISTATUS_grantedWithMods);
+    /// The request was rejected.
+    pub const REJECTION: CmpStatus = CmpStatus(ffi::OSSL_CMP_PKISTATUS_rejection);
+    /// The request has not yet been processed.
+    pub const WAITING: CmpStatus = CmpStatus(ffi::OSSL_CMP_PKISTATUS_waiting);
+    /// A revocation of the certificate is imminent.
+    pub const REVOCATION_WARNING: CmpStatus = CmpStatus(ffi::OSSL_CMP_PKISTATUS_revocationWarning);
+    /// The certificate has been revoked.
+    pub const REVOCATION_NOTIFICATION: CmpStatus =
+        CmpStatus(ffi::OSSL_CMP_PKISTATUS_revocationNotification);
+    /// The key of the certificate was already updated.
+    pub const KEY_UPDATE_WARNING: CmpStatus = CmpStatus(ffi::OSSL_CMP_PKISTATUS_keyUpdateWarning);
+
+    /// Constructs a `CmpStatus` from a raw value.
+    pub fn from_raw(raw: c_int) -> CmpStatus {
+        CmpStatus(raw)
+    }
+
+    /// Returns the raw value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::OSSL_CMP_CTX;
+    fn drop = ffi::OSSL_CMP_CTX_free;
+
+    /// The configuration and state of a CMP client.
+    pub struct CmpContext;
+    /// Reference to `CmpContext`.
+    pub struct CmpContextRef;
+}
+
+impl CmpContext {
+    /// Creates a new context.
+    #[corresponds(OSSL_CMP_CTX_new)]
+    pub fn new() -> Result<CmpContext, ErrorStack> {
+        unsafe {
+            ffi::init();
+
+            cvt_p(ffi::OSSL_CMP_CTX_new(ptr::null_mut(), ptr::null())).map(CmpContext)
+        }
+    }
+}
+
+impl CmpContextRef {
+    /// Clears the state of the previous transaction, so that the context can be reused for
+    /// another.
+    ///
+    /// The configuration of the context is kept.
+    #[corresponds(OSSL_CMP_CTX_reinit)]
+    pub fn reinit(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::OSSL_CMP_CTX_reinit(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets an option.
+    #[corresponds(OSSL_CMP_CTX_set_option)]
+    pub fn set_option(&mut self, option: CmpOption, value: i32) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_CMP_CTX_set_option(
+                self.as_ptr(),
+                option.as_raw(),
+                value,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the value of an option.
+    #[corresponds(OSSL_CMP_CTX_get_option)]
+    pub fn option(&self, option: CmpOption) -> i32 {
+        unsafe { ffi::OSSL_CMP_CTX_get_option(self.as_ptr(), option.as_raw()) }
+    }
+
+    /// Sets the host name or IP address of the CA.
+    #[corresponds(OSSL_CMP_CTX_set1_server)]
+    pub fn set_server(&mut self, address: &str) -> Result<(), ErrorStack> {
+        let address = CString::new(address).unwrap();
+        unsafe {
+            cvt(ffi::OSSL_CMP_CTX_set1_server(
+                self.as_ptr(),
+                address.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the port of the CA.
+    ///
+    /// Defaults to 80.
+    #[corresponds(OSSL_CMP_CTX_set_serverPort)]
+    pub fn set_server_port(&mut self, port: u16) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_CMP_CTX_set_serverPort(
+                self.as_ptr(),
+                c_int::from(port),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the HTTP path of the CA.
+    #[corresponds(OSSL_CMP_CTX_set1_serverPath)]
+    pub fn set_server_path(&mut self, path: &str) -> Result<(), ErrorStack> {
+        let path = CString::new(path).unwrap();
+        unsafe {
+            cvt(ffi::OSSL_CMP_CTX_set1_serverPath(
+                self.as_ptr(),
+                path.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the HTTP proxy used to reach the CA, in the same format as the `http_proxy`
+    /// environment variable.
+    #[corresponds(OSSL_CMP_CTX_set1_proxy)]
+    pub fn set_proxy(&mut self, proxy: &str) -> Result<(), ErrorStack> {
+        let proxy = CString::new(proxy).unwrap();
+        unsafe { cvt(ffi::OSSL_CMP_CTX_set1_proxy(self.as_ptr(), proxy.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the hosts which are reached without the proxy, in the same format as the `no_proxy`
+    /// environment variable.
+    #[corresponds(OSSL_CMP_CTX_set1_no_proxy)]
+    pub fn set_no_proxy(&mut self, names: &str) -> Result<(), ErrorStack> {
+        let names = CString::new(names).unwrap();
+        unsafe {
+            cvt(ffi::OSSL_CMP_CTX_set1_no_proxy(
+                self.as_ptr(),
+                names.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Pins the certificate the CA must sign its responses with.
+    ///
+    /// This is an alternative to `set_trusted_store`.
+    #[corresponds(OSSL_CMP_CTX_set1_srvCert)]
+    pub fn set_server_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::OSSL_CMP_CTX_set1_srvCert(self.as_ptr(), cert.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the name the CA's responses must be sent from.
+    #[corresponds(OSSL_CMP_CTX_set1_expected_sender)]
+    pub fn set_expected_sender(&mut self, name: &X509NameRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_CMP_CTX_set1_expected_sender(
+                self.as_ptr(),
+                name.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the trusted certificates used to verify the certificate the CA signs its responses
+    /// with.
+    #[corresponds(OSSL_CMP_CTX_set0_trustedStore)]
+    pub fn set_trusted_store(&mut self, store: X509Store) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_CMP_CTX_set0_trustedStore(
+                self.as_ptr(),
+                store.as_ptr(),
+            ))?;
+            mem::forget(store);
+            Ok(())
+        }
+    }
+
+    /// Sets untrusted certificates, such as intermediates, which may be used to build the chains
+    /// of the CA's and the client's certificates.
+    #[corresponds(OSSL_CMP_CTX_set1_untrusted)]
+    pub fn set_untrusted(&mut self, certs: &StackRef<X509>) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_CMP_CTX_set1_untrusted(
+                self.as_ptr(),
+                certs.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the certificate the client signs its requests with.
+    ///
+    /// For key update and revocation requests this is typically the certificate being updated or
+    /// revoked.
+    #[corresponds(OSSL_CMP_CTX_set1_cert)]
+    pub fn set_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::OSSL_CMP_CTX_set1_cert(self.as_ptr(), cert.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the private key the client signs its requests with.
+    #[corresponds(OSSL_CMP_CTX_set1_pkey)]
+    pub fn set_private_key<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe { cvt(ffi::OSSL_CMP_CTX_set1_pkey(self.as_ptr(), key.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the reference identifying the shared secret used for MAC-based protection.
+    #[corresponds(OSSL_CMP_CTX_set1_referenceValue)]
+    pub fn set_reference_value(&mut self, reference: &[u8]) -> Result<(), ErrorStack> {
+        assert!(reference.len() <= c_int::max_value() as usize);
+        unsafe {
+            cvt(ffi::OSSL_CMP_CTX_set1_referenceValue(
+                self.as_ptr(),
+                reference.as_ptr(),
+                reference.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the shared secret used for MAC-based protection.
+    ///
+    /// MAC-based protection is used instead of signatures when this is set, which is typical for
+    /// initial enrollment with a one-time password.
+    #[corresponds(OSSL_CMP_CTX_set1_secretValue)]
+    pub fn set_secret_value(&mut self, secret: &[u8]) -> Result<(), ErrorStack> {
+        assert!(secret.len() <= c_int::max_value() as usize);
+        unsafe {
+            cvt(ffi::OSSL_CMP_CTX_set1_secretValue(
+                self.as_ptr(),
+                secret.as_ptr(),
+                secret.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the key pair to request a certificate for.
+    ///
+    /// Defaults to the key set with `set_private_key`.
+    #[corresponds(OSSL_CMP_CTX_set0_newPkey)]
+    pub fn set_new_private_key<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            let key = key.to_owned();
+            cvt(ffi::OSSL_CMP_CTX_set0_newPkey(
+                self.as_ptr(),
+                1,
+                key.as_ptr(),
+            ))?;
+            mem::forget(key);
+            Ok(())
+        }
+    }
+
+    /// Sets the subject to request a certificate for.
+    ///
+    /// Defaults to the subject of the certificate being updated, if any.
+    #[corresponds(OSSL_CMP_CTX_set1_subjectName)]
+    pub fn set_subject_name(&mut self, name: &X509NameRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_CMP_CTX_set1_subjectName(
+                self.as_ptr(),
+                name.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the extensions to request in new certificates.
+    #[corresponds(OSSL_CMP_CTX_set0_reqExtensions)]
+    pub fn set_request_extensions(
+        &mut self,
+        extensions: Stack<X509Extension>,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_CMP_CTX_set0_reqExtensions(
+                self.as_ptr(),
+                extensions.as_ptr(),
+            ))?;
+            mem::forget(extensions);
+            Ok(())
+        }
+    }
+
+    /// Sets the certificate to update or revoke.
+    ///
+    /// Defaults to the certificate set with `set_cert`.
+    #[corresponds(OSSL_CMP_CTX_set1_oldCert)]
+    pub fn set_old_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::OSSL_CMP_CTX_set1_oldCert(self.as_ptr(), cert.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the PKCS#10 certificate request sent by `exec_p10cr`.
+    #[corresponds(OSSL_CMP_CTX_set1_p10CSR)]
+    pub fn set_p10_csr(&mut self, csr: &X509ReqRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::OSSL_CMP_CTX_set1_p10CSR(self.as_ptr(), csr.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the revocation reason sent by `exec_rr`.
+    pub fn set_revocation_reason(&mut self, reason: CrlReason) -> Result<(), ErrorStack> {
+        self.set_option(CmpOption::REVOCATION_REASON, reason.as_raw())
+    }
+
+    unsafe fn exec_certreq(
+        &mut self,
+        exec: unsafe fn(*mut ffi::OSSL_CMP_CTX) -> *mut ffi::X509,
+    ) -> Result<X509, ErrorStack> {
+        let cert = cvt_p(exec(self.as_ptr()))?;
+        Ok(X509Ref::from_ptr(cert).to_owned())
+    }
+
+    /// Requests an initial certificate from the CA, returning the new certificate.
+    #[corresponds(OSSL_CMP_exec_IR_ses)]
+    pub fn exec_ir(&mut self) -> Result<X509, ErrorStack> {
+        unsafe { self.exec_certreq(ffi::OSSL_CMP_exec_IR_ses) }
+    }
+
+    /// Requests an additional certificate from the CA, returning the new certificate.
+    #[corresponds(OSSL_CMP_exec_CR_ses)]
+    pub fn exec_cr(&mut self) -> Result<X509, ErrorStack> {
+        unsafe { self.exec_certreq(ffi::OSSL_CMP_exec_CR_ses) }
+    }
+
+    /// Requests a certificate with the PKCS#10 request set with `set_p10_csr`, returning the new
+    /// certificate.
+    #[corresponds(OSSL_CMP_exec_P10CR_ses)]
+    pub fn exec_p10cr(&mut self) -> Result<X509, ErrorStack> {
+        unsafe { self.exec_certreq(ffi::OSSL_CMP_exec_P10CR_ses) }
+    }
+
+    /// Requests a certificate replacing the old certificate with a new key, returning the new
+    /// certificate.
+    #[corresponds(OSSL_CMP_exec_KUR_ses)]
+    pub fn exec_kur(&mut self) -> Result<X509, ErrorStack> {
+        unsafe { self.exec_certreq(ffi::OSSL_CMP_exec_KUR_ses) }
+    }
+
+    /// Requests the revocation of the old certificate.
+    #[corresponds(OSSL_CMP_exec_RR_ses)]
+    pub fn exec_rr(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::OSSL_CMP_exec_RR_ses(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Returns the status of the last response received from the CA, if any.
+    #[corresponds(OSSL_CMP_CTX_get_status)]
+    pub fn status(&self) -> Option<CmpStatus> {
+        unsafe {
+            match ffi::OSSL_CMP_CTX_get_status(self.as_ptr()) {
+                status if status >= 0 => Some(CmpStatus(status)),
+                _ => None,
+            }
+        }
+    }
+
+    /// Returns the failure information bits of the last response received from the CA.
+    ///
+    /// Each bit position is a `PKIFailureInfo` value from RFC 4210.
+    #[corresponds(OSSL_CMP_CTX_get_failInfoCode)]
+    pub fn fail_info(&self) -> i32 {
+        unsafe { ffi::OSSL_CMP_CTX_get_failInfoCode(self.as_ptr()) }
+    }
+
+    /// Returns the certificate enrolled by the last transaction, if any.
+    #[corresponds(OSSL_CMP_CTX_get0_newCert)]
+    pub fn new_cert(&self) -> Option<&X509Ref> {
+        unsafe { X509Ref::from_const_ptr_opt(ffi::OSSL_CMP_CTX_get0_newCert(self.as_ptr())) }
+    }
+
+    /// Returns the chain of the certificate enrolled by the last transaction, if any.
+    #[corresponds(OSSL_CMP_CTX_get1_newChain)]
+    pub fn new_chain(&self) -> Option<Stack<X509>> {
+        unsafe {
+            let chain = ffi::OSSL_CMP_CTX_get1_newChain(self.as_ptr());
+            if chain.is_null() {
+                None
+            } else {
+                Some(Stack::from_ptr(chain))
+            }
+        }
+    }
+
+    /// Returns the CA certificates sent with the response to an initial certificate request,
+    /// if any.
+    ///
+    /// These are not verified, and must be checked out of band before they are trusted.
+    #[corresponds(OSSL_CMP_CTX_get1_caPubs)]
+    pub fn ca_pubs(&self) -> Option<Stack<X509>> {
+        unsafe { cert_stack(ffi::OSSL_CMP_CTX_get1_caPubs(self.as_ptr())) }
+    }
+
+    /// Returns the extra certificates sent with the last response from the CA, if any.
+    #[corresponds(OSSL_CMP_CTX_get1_extraCertsIn)]
+    pub fn extra_certs_in(&self) -> Option<Stack<X509>> {
+        unsafe { cert_stack(ffi::OSSL_CMP_CTX_get1_extraCertsIn(self.as_ptr())) }
+    }
+}
+
+/// Takes ownership of a certificate stack, treating an empty stack like a missing one.
+unsafe fn cert_stack(certs: *mut ffi::stack_st_X509) -> Option<Stack<X509>> {
+    if certs.is_null() {
+        return None;
+    }
+    let certs = Stack::<X509>::from_ptr(certs);
+    if certs.is_empty() {
+        None
+    } else {
+        Some(certs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pkey::PKey;
+    use crate::x509::store::X509StoreBuilder;
+    use crate::x509::X509Name;
+
+    #[test]
+    fn options() {
+        let mut ctx = CmpContext::new().unwrap();
+        ctx.set_option(CmpOption::VALIDITY_DAYS, 30).unwrap();
+        assert_eq!(ctx.option(CmpOption::VALIDITY_DAYS), 30);
+
+        ctx.set_revocation_reason(CrlReason::KEY_COMPROMISE)
+            .unwrap();
+        assert_eq!(
+            ctx.option(CmpOption::REVOCATION_REASON),
+            CrlReason::KEY_COMPROMISE.as_raw()
+        );
+    }
+
+    #[test]
+    fn exec_without_transport() {
+        let ca = X509::from_pem(include_bytes!("../test/root-ca.pem")).unwrap();
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(ca).unwrap();
+
+        let mut name = X509Name::builder().unwrap();
+        name.append_entry_by_text("CN", "device").unwrap();
+        let name = name.build();
+
+        let mut ctx = CmpContext::new().unwrap();
+        ctx.set_option(CmpOption::LOG_VERBOSITY, 0).unwrap();
+        ctx.set_trusted_store(store.build()).unwrap();
+        ctx.set_reference_value(b"device").unwrap();
+        ctx.set_secret_value(b"secret").unwrap();
+        ctx.set_new_private_key(&PKey::generate_ed25519().unwrap())
+            .unwrap();
+        ctx.set_subject_name(&name).unwrap();
+
+        // no server is configured, so the request cannot be sent
+        ctx.exec_ir().unwrap_err();
+        assert!(ctx.new_cert().is_none());
+        assert!(ctx.ca_pubs().is_none());
+        ctx.reinit().unwrap();
+        assert_eq!(ctx.status(), None);
+    }
+}
@@ -6,14 +6,16 @@
 //! Data accepted by this module will be smime type `enveloped-data`.
 
 use bitflags::bitflags;
-use foreign_types::{ForeignType, ForeignTypeRef};
-use libc::c_uint;
+use foreign_types::{ForeignType, ForeignTypeRef, Opaque};
+use libc::{c_long, c_uint};
 use std::ptr;
 
+use crate::asn1::Asn1StringRef;
 use crate::bio::{MemBio, MemBioSlice};
 use crate::error::ErrorStack;
+use crate::pkcs7::SmimeCapabilities;
 use crate::pkey::{HasPrivate, PKeyRef};
-use crate::stack::StackRef;
+use crate::stack::{Stack, StackRef};
 use crate::symm::Cipher;
 use crate::x509::{X509Ref, X509};
 use crate::{cvt, cvt_p};
@@ -67,7 +69,63 @@ foreign_type_and_impl_send_sync! {
     pub struct CmsContentInfoRef;
 }
 
+/// Information about a single signer of a CMS `SignedData` structure.
+///
+/// This is owned by the `CmsContentInfo` it was retrieved from.
+pub struct CmsSignerInfoRef(Opaque);
+
+impl ForeignTypeRef for CmsSignerInfoRef {
+    type CType = ffi::CMS_SignerInfo;
+}
+
+impl CmsSignerInfoRef {
+    /// Returns the contents of the signer's SMIMECapabilities signed attribute, if present.
+    #[corresponds(CMS_signed_get0_data_by_OBJ)]
+    pub fn smime_capabilities(&self) -> Result<Option<SmimeCapabilities>, ErrorStack> {
+        unsafe {
+            let attr = ffi::CMS_signed_get0_data_by_OBJ(
+                self.as_ptr(),
+                ffi::OBJ_nid2obj(ffi::NID_SMIMECapabilities),
+                -3,
+                ffi::V_ASN1_SEQUENCE,
+            );
+            if attr.is_null() {
+                return Ok(None);
+            }
+
+            let der = Asn1StringRef::from_ptr(attr as *mut _).as_slice();
+            let mut p = der.as_ptr();
+            let caps = cvt_p(ffi::d2i_X509_ALGORS(
+                ptr::null_mut(),
+                &mut p,
+                der.len() as c_long,
+            ))?;
+            Ok(Some(SmimeCapabilities(Stack::from_ptr(caps))))
+        }
+    }
+}
+
 impl CmsContentInfoRef {
+    /// Returns information about the signers of a `SignedData` structure.
+    ///
+    /// The returned list is empty if the structure does not contain signed data.
+    #[corresponds(CMS_get0_SignerInfos)]
+    pub fn signer_infos(&self) -> Vec<&CmsSignerInfoRef> {
+        unsafe {
+            let stack = ffi::CMS_get0_SignerInfos(self.as_ptr());
+            if stack.is_null() {
+                // Not signed data; discard the error OpenSSL queues in that case.
+                let _ = ErrorStack::get();
+                return vec![];
+            }
+
+            let stack = stack as *mut ffi::OPENSSL_STACK;
+            (0..ffi::OPENSSL_sk_num(stack))
+                .map(|i| CmsSignerInfoRef::from_ptr(ffi::OPENSSL_sk_value(stack, i) as *mut _))
+                .collect()
+        }
+    }
+
     /// Given the sender's private key, `pkey` and the recipient's certificate, `cert`,
     /// decrypt the data in `self`.
     #[corresponds(CMS_decrypt)]
@@ -201,6 +259,57 @@ impl CmsContentInfo {
         }
     }
 
+    /// Like `sign`, but advertises `capabilities` in the signer's SMIMECapabilities attribute in
+    /// place of OpenSSL's default list.
+    ///
+    /// The `NOATTR` and `NOSMIMECAP` flags must not be set, and `STREAM` is not supported.
+    #[corresponds(CMS_add_smimecap)]
+    pub fn sign_with_smime_capabilities<T>(
+        signcert: &X509Ref,
+        pkey: &PKeyRef<T>,
+        certs: Option<&StackRef<X509>>,
+        data: &[u8],
+        capabilities: &SmimeCapabilities,
+        flags: CMSOptions,
+    ) -> Result<CmsContentInfo, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            let data_bio = MemBioSlice::new(data)?;
+            let certs = certs.map_or(ptr::null_mut(), |p| p.as_ptr());
+
+            let cms = cvt_p(ffi::CMS_sign(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                certs,
+                ptr::null_mut(),
+                (flags | CMSOptions::PARTIAL).bits(),
+            ))?;
+            let cms = CmsContentInfo::from_ptr(cms);
+
+            let signer_info = cvt_p(ffi::CMS_add1_signer(
+                cms.as_ptr(),
+                signcert.as_ptr(),
+                pkey.as_ptr(),
+                ptr::null(),
+                (flags | CMSOptions::NOSMIMECAP).bits(),
+            ))?;
+            cvt(ffi::CMS_add_smimecap(
+                signer_info,
+                capabilities.algorithms().as_ptr(),
+            ))?;
+            cvt(ffi::CMS_final(
+                cms.as_ptr(),
+                data_bio.as_ptr(),
+                ptr::null_mut(),
+                flags.bits(),
+            ))?;
+
+            Ok(cms)
+        }
+    }
+
     /// Given a certificate stack `certs`, data `data`, cipher `cipher` and flags `flags`,
     /// create a CmsContentInfo struct.
     ///
@@ -232,7 +341,9 @@ impl CmsContentInfo {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::nid::Nid;
     use crate::pkcs12::Pkcs12;
+    use crate::pkey::PKey;
     use crate::stack::Stack;
     use crate::x509::X509;
 
@@ -311,4 +422,46 @@ mod test {
             assert_eq!(input, decrypt_without_cert_check);
         }
     }
+
+    #[test]
+    fn cms_smime_capabilities() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let pkey = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+
+        let mut capabilities = SmimeCapabilities::new().unwrap();
+        capabilities.add(Nid::AES_128_CBC, None).unwrap();
+
+        let cms = CmsContentInfo::sign_with_smime_capabilities(
+            &cert,
+            &pkey,
+            None,
+            b"foo",
+            &capabilities,
+            CMSOptions::BINARY,
+        )
+        .unwrap();
+        let cms = CmsContentInfo::from_der(&cms.to_der().unwrap()).unwrap();
+
+        let signer_infos = cms.signer_infos();
+        assert_eq!(signer_infos.len(), 1);
+        let parsed = signer_infos[0].smime_capabilities().unwrap().unwrap();
+        assert_eq!(parsed.algorithms().len(), 1);
+        assert_eq!(
+            parsed.algorithms().get(0).unwrap().object().nid(),
+            Nid::AES_128_CBC
+        );
+
+        let cms = CmsContentInfo::sign(
+            Some(&cert),
+            Some(&pkey),
+            None,
+            Some(b"foo"),
+            CMSOptions::NOSMIMECAP,
+        )
+        .unwrap();
+        assert!(cms.signer_infos()[0]
+            .smime_capabilities()
+            .unwrap()
+            .is_none());
+    }
 }
@@ -0,0 +1,413 @@
+//! Certificate Request Message Format.
+//!
+//! CRMF ([RFC 4211]) certificate requests are an alternative to PKCS#10 requests, used by CMP
+//! and some EST servers. A `CertReqMsg` ([`CrmfMsg`]) describes the certificate being requested
+//! with a template, and proves possession of the private key with a separate
+//! proof-of-possession field. Requests are sent in a `CertReqMessages` sequence, which is
+//! represented as a `Stack<CrmfMsg>`.
+//!
+//! Requires OpenSSL 3.0.0 or newer.
+//!
+//! [RFC 4211]: https://www.rfc-editor.org/rfc/rfc4211
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::crmf::{self, CrmfMsg, CrmfPopoMethod};
+//! use openssl::ec::{EcGroup, EcKey};
+//! use openssl::hash::MessageDigest;
+//! use openssl::nid::Nid;
+//! use openssl::pkey::PKey;
+//! use openssl::stack::Stack;
+//! use openssl::x509::X509Name;
+//!
+//! let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+//! let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+//!
+//! let mut name = X509Name::builder().unwrap();
+//! name.append_entry_by_text("CN", "device").unwrap();
+//! let name = name.build();
+//!
+//! let mut msg = CrmfMsg::new().unwrap();
+//! msg.set_cert_req_id(0).unwrap();
+//! msg.set_public_key(&key).unwrap();
+//! msg.set_subject_name(&name).unwrap();
+//! msg.create_popo(CrmfPopoMethod::SIGNATURE, &key, MessageDigest::sha256())
+//!     .unwrap();
+//!
+//! let mut msgs = Stack::new().unwrap();
+//! msgs.push(msg).unwrap();
+//! let der = crmf::cert_req_messages_to_der(&msgs).unwrap();
+//! ```
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::c_int;
+use std::mem;
+use std::ptr;
+
+use crate::asn1::{Asn1IntegerRef, Asn1Time};
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
+use crate::stack::{Stack, StackRef, Stackable};
+use crate::util::ForeignTypeRefExt;
+use crate::x509::{X509Extension, X509NameRef};
+use crate::{cvt, cvt_p};
+use openssl_macros::corresponds;
+
+/// A method of proving possession of the private key corresponding to a requested certificate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CrmfPopoMethod(c_int);
+
+impl CrmfPopoMethod {
+    /// No proof of possession.
+    pub const NONE: CrmfPopoMethod = CrmfPopoMethod(ffi::OSSL_CRMF_POPO_NONE);
+    /// Possession was verified by a registration authority.
+    pub const RA_VERIFIED: CrmfPopoMethod = CrmfPopoMethod(ffi::OSSL_CRMF_POPO_RAVERIFIED);
+    /// A signature over the request with the private key.
+    pub const SIGNATURE: CrmfPopoMethod = CrmfPopoMethod(ffi::OSSL_CRMF_POPO_SIGNATURE);
+    /// Possession is shown by decrypting the issued certificate.
+    pub const KEY_ENCIPHERMENT: CrmfPopoMethod = CrmfPopoMethod(ffi::OSSL_CRMF_POPO_KEYENC);
+    /// Possession is shown through a key agreement with the CA.
+    pub const KEY_AGREEMENT: CrmfPopoMethod = CrmfPopoMethod(ffi::OSSL_CRMF_POPO_KEYAGREE);
+
+    /// Constructs a `CrmfPopoMethod` from a raw value.
+    pub fn from_raw(raw: c_int) -> CrmfPopoMethod {
+        CrmfPopoMethod(raw)
+    }
+
+    /// Returns the raw value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::OSSL_CRMF_MSG;
+    fn drop = ffi::OSSL_CRMF_MSG_free;
+
+    /// A CRMF certificate request message.
+    pub struct CrmfMsg;
+    /// Reference to `CrmfMsg`.
+    pub struct CrmfMsgRef;
+}
+
+impl Stackable for CrmfMsg {
+    type StackType = ffi::stack_st_OSSL_CRMF_MSG;
+}
+
+impl CrmfMsg {
+    /// Creates a new, empty request message.
+    #[corresponds(OSSL_CRMF_MSG_new)]
+    pub fn new() -> Result<CrmfMsg, ErrorStack> {
+        unsafe {
+            ffi::init();
+
+            cvt_p(ffi::OSSL_CRMF_MSG_new()).map(CrmfMsg)
+        }
+    }
+
+    from_der! {
+        /// Deserializes a DER-encoded `CertReqMsg` structure.
+        #[corresponds(d2i_OSSL_CRMF_MSG)]
+        from_der,
+        CrmfMsg,
+        ffi::d2i_OSSL_CRMF_MSG
+    }
+}
+
+impl CrmfMsgRef {
+    to_der! {
+        /// Serializes the message to its standard DER encoding.
+        #[corresponds(i2d_OSSL_CRMF_MSG)]
+        to_der,
+        ffi::i2d_OSSL_CRMF_MSG
+    }
+
+    /// Sets the identifier which matches the response for this request to the request.
+    #[corresponds(OSSL_CRMF_MSG_set_certReqId)]
+    pub fn set_cert_req_id(&mut self, id: i32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::OSSL_CRMF_MSG_set_certReqId(self.as_ptr(), id)).map(|_| ()) }
+    }
+
+    /// Returns the identifier which matches the response for this request to the request.
+    #[corresponds(OSSL_CRMF_MSG_get_certReqId)]
+    pub fn cert_req_id(&self) -> i32 {
+        unsafe { ffi::OSSL_CRMF_MSG_get_certReqId(self.as_ptr()) }
+    }
+
+    /// Sets the public key to request a certificate for.
+    #[corresponds(OSSL_CRMF_CERTTEMPLATE_fill)]
+    pub fn set_public_key<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe {
+            cvt(ffi::OSSL_CRMF_CERTTEMPLATE_fill(
+                self.template(),
+                key.as_ptr(),
+                ptr::null(),
+                ptr::null(),
+                ptr::null(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the subject to request a certificate for.
+    #[corresponds(OSSL_CRMF_CERTTEMPLATE_fill)]
+    pub fn set_subject_name(&mut self, name: &X509NameRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_CRMF_CERTTEMPLATE_fill(
+                self.template(),
+                ptr::null_mut(),
+                name.as_ptr(),
+                ptr::null(),
+                ptr::null(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the subject the certificate is requested for, if set.
+    #[corresponds(OSSL_CRMF_CERTTEMPLATE_get0_subject)]
+    pub fn subject_name(&self) -> Option<&X509NameRef> {
+        unsafe {
+            X509NameRef::from_const_ptr_opt(ffi::OSSL_CRMF_CERTTEMPLATE_get0_subject(
+                self.template(),
+            ))
+        }
+    }
+
+    /// Sets the issuer of the requested certificate.
+    #[corresponds(OSSL_CRMF_CERTTEMPLATE_fill)]
+    pub fn set_issuer_name(&mut self, name: &X509NameRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_CRMF_CERTTEMPLATE_fill(
+                self.template(),
+                ptr::null_mut(),
+                ptr::null(),
+                name.as_ptr(),
+                ptr::null(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the issuer of the requested certificate, if set.
+    #[corresponds(OSSL_CRMF_CERTTEMPLATE_get0_issuer)]
+    pub fn issuer_name(&self) -> Option<&X509NameRef> {
+        unsafe {
+            X509NameRef::from_const_ptr_opt(ffi::OSSL_CRMF_CERTTEMPLATE_get0_issuer(
+                self.template(),
+            ))
+        }
+    }
+
+    /// Sets the serial number of the requested certificate.
+    #[corresponds(OSSL_CRMF_CERTTEMPLATE_fill)]
+    pub fn set_serial_number(&mut self, serial: &Asn1IntegerRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_CRMF_CERTTEMPLATE_fill(
+                self.template(),
+                ptr::null_mut(),
+                ptr::null(),
+                ptr::null(),
+                serial.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the serial number of the requested certificate, if set.
+    #[corresponds(OSSL_CRMF_CERTTEMPLATE_get0_serialNumber)]
+    pub fn serial_number(&self) -> Option<&Asn1IntegerRef> {
+        unsafe {
+            Asn1IntegerRef::from_const_ptr_opt(ffi::OSSL_CRMF_CERTTEMPLATE_get0_serialNumber(
+                self.template(),
+            ))
+        }
+    }
+
+    /// Sets the requested validity period of the certificate.
+    ///
+    /// Either bound may be left for the CA to choose.
+    #[corresponds(OSSL_CRMF_MSG_set0_validity)]
+    pub fn set_validity(
+        &mut self,
+        not_before: Option<Asn1Time>,
+        not_after: Option<Asn1Time>,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_CRMF_MSG_set0_validity(
+                self.as_ptr(),
+                not_before.as_ref().map_or(ptr::null_mut(), |t| t.as_ptr()),
+                not_after.as_ref().map_or(ptr::null_mut(), |t| t.as_ptr()),
+            ))?;
+        }
+        mem::forget(not_before);
+        mem::forget(not_after);
+        Ok(())
+    }
+
+    /// Adds an extension to request in the certificate.
+    #[corresponds(OSSL_CRMF_MSG_push0_extension)]
+    pub fn add_extension(&mut self, extension: X509Extension) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_CRMF_MSG_push0_extension(
+                self.as_ptr(),
+                extension.as_ptr(),
+            ))?;
+        }
+        mem::forget(extension);
+        Ok(())
+    }
+
+    /// Adds a proof of possession of `key` to the message.
+    ///
+    /// This must be called after the rest of the message has been set, since a signature covers
+    /// the certificate template. `digest` is ignored by methods other than
+    /// `CrmfPopoMethod::SIGNATURE`, and by signature algorithms such as Ed25519 which do not
+    /// use a separate digest.
+    #[corresponds(OSSL_CRMF_MSG_create_popo)]
+    pub fn create_popo<T>(
+        &mut self,
+        method: CrmfPopoMethod,
+        key: &PKeyRef<T>,
+        digest: MessageDigest,
+    ) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            cvt(ffi::OSSL_CRMF_MSG_create_popo(
+                method.as_raw(),
+                self.as_ptr(),
+                key.as_ptr(),
+                digest.as_ptr(),
+                ptr::null_mut(),
+                ptr::null(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    fn template(&self) -> *mut ffi::OSSL_CRMF_CERTTEMPLATE {
+        unsafe { ffi::OSSL_CRMF_MSG_get0_tmpl(self.as_ptr()) }
+    }
+}
+
+from_der! {
+    /// Deserializes a DER-encoded `CertReqMessages` structure.
+    #[corresponds(d2i_OSSL_CRMF_MSGS)]
+    cert_req_messages_from_der,
+    Stack<CrmfMsg>,
+    ffi::d2i_OSSL_CRMF_MSGS
+}
+
+/// Serializes a sequence of request messages as a DER-encoded `CertReqMessages` structure.
+#[corresponds(i2d_OSSL_CRMF_MSGS)]
+pub fn cert_req_messages_to_der(msgs: &StackRef<CrmfMsg>) -> Result<Vec<u8>, ErrorStack> {
+    unsafe {
+        let len = cvt(ffi::i2d_OSSL_CRMF_MSGS(msgs.as_ptr(), ptr::null_mut()))?;
+        let mut buf = vec![0; len as usize];
+        cvt(ffi::i2d_OSSL_CRMF_MSGS(
+            msgs.as_ptr(),
+            &mut buf.as_mut_ptr(),
+        ))?;
+        Ok(buf)
+    }
+}
+
+/// Verifies the proof of possession of the message in `msgs` with the request identifier `id`.
+///
+/// Messages which claim that possession was verified by a registration authority are only
+/// accepted if `accept_ra_verified` is set.
+#[corresponds(OSSL_CRMF_MSGS_verify_popo)]
+pub fn verify_popo(
+    msgs: &StackRef<CrmfMsg>,
+    id: i32,
+    accept_ra_verified: bool,
+) -> Result<(), ErrorStack> {
+    unsafe {
+        cvt(ffi::OSSL_CRMF_MSGS_verify_popo(
+            msgs.as_ptr(),
+            id,
+            accept_ra_verified as c_int,
+            ptr::null_mut(),
+            ptr::null(),
+        ))
+        .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bn::BigNum;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
+    use crate::pkey::{PKey, Private};
+    use crate::x509::X509Name;
+
+    fn ec_key() -> PKey<Private> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+    }
+
+    fn msg(id: i32, template_key: &PKeyRef<Private>, popo_key: &PKeyRef<Private>) -> CrmfMsg {
+        let mut name = X509Name::builder().unwrap();
+        name.append_entry_by_text("CN", "device").unwrap();
+        let name = name.build();
+
+        let mut msg = CrmfMsg::new().unwrap();
+        msg.set_cert_req_id(id).unwrap();
+        msg.set_public_key(template_key).unwrap();
+        msg.set_subject_name(&name).unwrap();
+        msg.set_validity(None, Some(Asn1Time::days_from_now(30).unwrap()))
+            .unwrap();
+        msg.create_popo(CrmfPopoMethod::SIGNATURE, popo_key, MessageDigest::sha256())
+            .unwrap();
+        msg
+    }
+
+    #[test]
+    fn template() {
+        let key = ec_key();
+        let mut msg = msg(7, &key, &key);
+        let serial = BigNum::from_u32(42).unwrap().to_asn1_integer().unwrap();
+        msg.set_serial_number(&serial).unwrap();
+
+        let msg = CrmfMsg::from_der(&msg.to_der().unwrap()).unwrap();
+        assert_eq!(msg.cert_req_id(), 7);
+        let subject = msg.subject_name().unwrap();
+        let cn = subject.entries_by_nid(Nid::COMMONNAME).next().unwrap();
+        assert_eq!(cn.data().as_slice(), b"device");
+        assert!(msg.issuer_name().is_none());
+        assert_eq!(
+            msg.serial_number().unwrap().to_bn().unwrap(),
+            BigNum::from_u32(42).unwrap()
+        );
+    }
+
+    #[test]
+    fn popo() {
+        let key = ec_key();
+
+        let mut msgs = Stack::new().unwrap();
+        msgs.push(msg(0, &key, &key)).unwrap();
+        msgs.push(msg(1, &key, &key)).unwrap();
+
+        let mut der = cert_req_messages_to_der(&msgs).unwrap();
+        let msgs = cert_req_messages_from_der(&der).unwrap();
+        assert_eq!(msgs.len(), 2);
+        verify_popo(&msgs, 0, false).unwrap();
+        verify_popo(&msgs, 1, false).unwrap();
+
+        // the encoding ends with the signature of the last message
+        *der.last_mut().unwrap() ^= 1;
+        let msgs = cert_req_messages_from_der(&der).unwrap();
+        verify_popo(&msgs, 0, false).unwrap();
+        verify_popo(&msgs, 1, false).unwrap_err();
+    }
+}
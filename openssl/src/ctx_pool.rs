@@ -0,0 +1,307 @@
+//! Thread-local pools of EVP contexts.
+//!
+//! Allocating and freeing a context for every message can be a measurable cost in hot paths
+//! such as per-packet MACs or per-request hashing, particularly on OpenSSL 3. The functions in
+//! this module hand out contexts from a small per-thread free list instead, and a context is
+//! reset and returned to the free list of the thread which drops it.
+//!
+//! Requires OpenSSL 1.1.0 or newer.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::ctx_pool;
+//! use openssl::md::Md;
+//!
+//! for data in &[&b"hello"[..], b"world"] {
+//!     // The second iteration reuses the context allocated by the first.
+//!     let mut ctx = ctx_pool::md_ctx().unwrap();
+//!     ctx.digest_init(Md::sha256()).unwrap();
+//!     ctx.digest_update(data).unwrap();
+//!     let mut digest = [0; 32];
+//!     ctx.digest_final(&mut digest).unwrap();
+//! }
+//! ```
+use foreign_types::ForeignType;
+#[cfg(ossl300)]
+use foreign_types::ForeignTypeRef;
+use std::cell::RefCell;
+use std::fmt;
+#[cfg(ossl300)]
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+use crate::cipher_ctx::CipherCtx;
+use crate::error::ErrorStack;
+use crate::md_ctx::MdCtx;
+#[cfg(ossl300)]
+use crate::pkey::PKeyRef;
+#[cfg(ossl300)]
+use crate::pkey_ctx::PkeyCtx;
+
+/// The maximum number of idle contexts of each kind kept by a thread.
+const MAX_IDLE: usize = 16;
+
+// `const` thread local initializers require Rust 1.59.
+thread_local! {
+    #[allow(clippy::missing_const_for_thread_local)]
+    static MD_CTXS: RefCell<Vec<MdCtx>> = RefCell::new(Vec::new());
+    #[allow(clippy::missing_const_for_thread_local)]
+    static CIPHER_CTXS: RefCell<Vec<CipherCtx>> = RefCell::new(Vec::new());
+    #[cfg(ossl300)]
+    #[allow(clippy::missing_const_for_thread_local)]
+    static PKEY_CTXS: RefCell<Vec<(usize, PkeyCtx<()>)>> = RefCell::new(Vec::new());
+}
+
+/// A context type which can be stored in a pool.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait Poolable: private::Sealed {}
+
+mod private {
+    pub trait Sealed: Sized {
+        fn release(self, tag: usize);
+    }
+}
+
+/// A context borrowed from the current thread's pool.
+///
+/// It dereferences to the underlying context, and is returned to the pool of the thread which
+/// drops it.
+pub struct Pooled<T: Poolable> {
+    ctx: Option<T>,
+    tag: usize,
+}
+
+impl<T: Poolable> Pooled<T> {
+    fn new(ctx: T, tag: usize) -> Pooled<T> {
+        Pooled {
+            ctx: Some(ctx),
+            tag,
+        }
+    }
+
+    /// Detaches the context from the pool, so that it is freed rather than reused once dropped.
+    pub fn into_inner(mut self) -> T {
+        self.ctx.take().unwrap()
+    }
+}
+
+impl<T: Poolable> Deref for Pooled<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.ctx.as_ref().unwrap()
+    }
+}
+
+impl<T: Poolable> DerefMut for Pooled<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.ctx.as_mut().unwrap()
+    }
+}
+
+impl<T: Poolable> Drop for Pooled<T> {
+    fn drop(&mut self) {
+        if let Some(ctx) = self.ctx.take() {
+            ctx.release(self.tag);
+        }
+    }
+}
+
+impl<T: Poolable + fmt::Debug> fmt::Debug for Pooled<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, fmt)
+    }
+}
+
+/// Returns a message digest context from the current thread's pool, allocating one if the pool
+/// is empty.
+///
+/// The context is in the same state as one returned by `MdCtx::new`.
+pub fn md_ctx() -> Result<Pooled<MdCtx>, ErrorStack> {
+    let ctx = MD_CTXS
+        .try_with(|ctxs| ctxs.borrow_mut().pop())
+        .ok()
+        .flatten();
+    let ctx = match ctx {
+        Some(ctx) => ctx,
+        None => MdCtx::new()?,
+    };
+    Ok(Pooled::new(ctx, 0))
+}
+
+impl Poolable for MdCtx {}
+
+impl private::Sealed for MdCtx {
+    fn release(self, _: usize) {
+        unsafe {
+            if ffi::EVP_MD_CTX_reset(self.as_ptr()) != 1 {
+                // the context is in an unknown state, so free it instead
+                ErrorStack::get();
+                return;
+            }
+        }
+
+        let _ = MD_CTXS.try_with(|ctxs| {
+            let mut ctxs = ctxs.borrow_mut();
+            if ctxs.len() < MAX_IDLE {
+                ctxs.push(self);
+            }
+        });
+    }
+}
+
+/// Returns a cipher context from the current thread's pool, allocating one if the pool is
+/// empty.
+///
+/// The context is in the same state as one returned by `CipherCtx::new`.
+pub fn cipher_ctx() -> Result<Pooled<CipherCtx>, ErrorStack> {
+    let ctx = CIPHER_CTXS
+        .try_with(|ctxs| ctxs.borrow_mut().pop())
+        .ok()
+        .flatten();
+    let ctx = match ctx {
+        Some(ctx) => ctx,
+        None => CipherCtx::new()?,
+    };
+    Ok(Pooled::new(ctx, 0))
+}
+
+impl Poolable for CipherCtx {}
+
+impl private::Sealed for CipherCtx {
+    fn release(self, _: usize) {
+        unsafe {
+            if ffi::EVP_CIPHER_CTX_reset(self.as_ptr()) != 1 {
+                // the context is in an unknown state, so free it instead
+                ErrorStack::get();
+                return;
+            }
+        }
+
+        let _ = CIPHER_CTXS.try_with(|ctxs| {
+            let mut ctxs = ctxs.borrow_mut();
+            if ctxs.len() < MAX_IDLE {
+                ctxs.push(self);
+            }
+        });
+    }
+}
+
+/// Returns a context for `pkey` from the current thread's pool, allocating one if the pool does
+/// not hold a context for that key.
+///
+/// Pooled contexts keep a reference to their key, so a key is not freed while one of its
+/// contexts is idle in a pool. As with a new context, one of the `*_init` methods must be called
+/// before the context is used, which resets any parameters set by its previous user.
+///
+/// Requires OpenSSL 3.0.0 or newer.
+#[cfg(ossl300)]
+pub fn pkey_ctx<T>(pkey: &PKeyRef<T>) -> Result<Pooled<PkeyCtx<T>>, ErrorStack> {
+    let tag = pkey.as_ptr() as usize;
+    let ctx = PKEY_CTXS
+        .try_with(|ctxs| {
+            let mut ctxs = ctxs.borrow_mut();
+            let idx = ctxs.iter().position(|(key, _)| *key == tag)?;
+            Some(ctxs.swap_remove(idx).1)
+        })
+        .ok()
+        .flatten();
+    let ctx = match ctx {
+        Some(ctx) => unsafe { PkeyCtx::from_ptr(into_ptr(ctx)) },
+        None => PkeyCtx::new(pkey)?,
+    };
+    Ok(Pooled::new(ctx, tag))
+}
+
+#[cfg(ossl300)]
+impl<T> Poolable for PkeyCtx<T> {}
+
+#[cfg(ossl300)]
+impl<T> private::Sealed for PkeyCtx<T> {
+    fn release(self, tag: usize) {
+        let ctx = unsafe { PkeyCtx::<()>::from_ptr(into_ptr(self)) };
+
+        let _ = PKEY_CTXS.try_with(|ctxs| {
+            let mut ctxs = ctxs.borrow_mut();
+            if ctxs.len() < MAX_IDLE {
+                ctxs.push((tag, ctx));
+            }
+        });
+    }
+}
+
+#[cfg(ossl300)]
+fn into_ptr<T>(ctx: PkeyCtx<T>) -> *mut ffi::EVP_PKEY_CTX {
+    let ptr = ctx.as_ptr();
+    mem::forget(ctx);
+    ptr
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cipher::Cipher;
+    use crate::md::Md;
+
+    #[test]
+    fn md_ctx_reuse() {
+        let ptr = {
+            let mut ctx = md_ctx().unwrap();
+            ctx.digest_init(Md::sha256()).unwrap();
+            ctx.digest_update(b"hello").unwrap();
+            ctx.as_ptr()
+        };
+
+        let mut ctx = md_ctx().unwrap();
+        assert_eq!(ctx.as_ptr(), ptr);
+        ctx.digest_init(Md::sha256()).unwrap();
+        let mut digest = [0; 32];
+        ctx.digest_final(&mut digest).unwrap();
+        assert_eq!(
+            hex::encode(digest),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        let ctx = ctx.into_inner();
+        assert_ne!(md_ctx().unwrap().as_ptr(), ctx.as_ptr());
+    }
+
+    #[test]
+    fn cipher_ctx_reuse() {
+        let key = [0; 16];
+        let ptr = {
+            let mut ctx = cipher_ctx().unwrap();
+            ctx.encrypt_init(Some(Cipher::aes_128_ecb()), Some(&key), None)
+                .unwrap();
+            ctx.as_ptr()
+        };
+
+        let encrypt = |ctx: &mut CipherCtx| {
+            ctx.encrypt_init(Some(Cipher::aes_128_ecb()), Some(&key), None)
+                .unwrap();
+            ctx.set_padding(false);
+            let mut out = vec![];
+            ctx.cipher_update_vec(&[1; 16], &mut out).unwrap();
+            ctx.cipher_final_vec(&mut out).unwrap();
+            out
+        };
+
+        let mut ctx = cipher_ctx().unwrap();
+        assert_eq!(ctx.as_ptr(), ptr);
+        assert_eq!(encrypt(&mut ctx), encrypt(&mut CipherCtx::new().unwrap()));
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn pkey_ctx_reuse() {
+        use crate::pkey::PKey;
+
+        let key = PKey::generate_ed25519().unwrap();
+        let other = PKey::generate_ed25519().unwrap();
+        let ptr = pkey_ctx(&key).unwrap().as_ptr();
+        assert_ne!(pkey_ctx(&other).unwrap().as_ptr(), ptr);
+        assert_eq!(pkey_ctx(&key).unwrap().as_ptr(), ptr);
+    }
+}
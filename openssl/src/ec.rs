@@ -23,7 +23,7 @@ use std::ptr;
 use crate::bn::{BigNumContextRef, BigNumRef};
 use crate::error::ErrorStack;
 use crate::nid::Nid;
-use crate::pkey::{HasParams, HasPrivate, HasPublic, Params, Private, Public};
+use crate::pkey::{HasParams, HasPrivate, HasPublic, PKey, Params, Private, Public};
 use crate::util::ForeignTypeRefExt;
 use crate::{cvt, cvt_n, cvt_p, init};
 use openssl_macros::corresponds;
@@ -589,6 +589,11 @@ where
         ffi::i2d_ECPrivateKey
     }
 
+    /// Serializes the private key to a DER-encoded unencrypted PKCS#8 PrivateKeyInfo structure.
+    pub fn private_key_to_pkcs8(&self) -> Result<Vec<u8>, ErrorStack> {
+        PKey::from_ec_key(self.to_owned())?.private_key_to_pkcs8()
+    }
+
     /// Returns the private key value.
     #[corresponds(EC_KEY_get0_private_key)]
     pub fn private_key(&self) -> &BigNumRef {
@@ -848,6 +853,13 @@ impl EcKey<Private> {
         EcKey<Private>,
         ffi::d2i_ECPrivateKey
     }
+
+    /// Decodes a DER-encoded unencrypted PKCS#8 PrivateKeyInfo structure.
+    ///
+    /// Fails if the structure does not contain an elliptic curve key.
+    pub fn private_key_from_pkcs8(der: &[u8]) -> Result<EcKey<Private>, ErrorStack> {
+        PKey::private_key_from_pkcs8(der)?.ec_key()
+    }
 }
 
 impl<T> Clone for EcKey<T> {
@@ -0,0 +1,343 @@
+//! Elliptic Curve Integrated Encryption Scheme.
+//!
+//! ECIES encrypts a message to the holder of an elliptic curve private key. Each message is
+//! encrypted under a fresh ephemeral key pair: the ECDH shared secret between the ephemeral key
+//! and the recipient's key is expanded with the ANSI X9.63 KDF into a key and nonce for an AEAD
+//! cipher.
+//!
+//! The construction works with any curve supported by [`EcGroup`] and produces messages with
+//! the following layout:
+//!
+//! | Field                | Length                                         |
+//! |----------------------|------------------------------------------------|
+//! | Version (`0x01`)     | 1 byte                                         |
+//! | Suite identifier     | 1 byte                                         |
+//! | Ephemeral public key | An uncompressed point on the recipient's curve |
+//! | Ciphertext           | The length of the plaintext                    |
+//! | Tag                  | 16 bytes                                       |
+//!
+//! The first three fields form the message header. It is used as the X9.63 `SharedInfo`, and is
+//! authenticated by the AEAD along with any associated data supplied by the caller. The format
+//! is specific to this crate and is not interoperable with other ECIES implementations, which
+//! differ widely in their choice of KDF, cipher and encoding.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::ec::{EcGroup, EcKey};
+//! use openssl::ecies::{self, EciesSuite};
+//! use openssl::nid::Nid;
+//!
+//! let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+//! let key = EcKey::generate(&group).unwrap();
+//!
+//! let suite = EciesSuite::AES_128_GCM_SHA256;
+//! let message = ecies::encrypt(suite, &key, b"context", b"hello").unwrap();
+//! let plaintext = ecies::decrypt(&key, b"context", &message).unwrap();
+//! assert_eq!(plaintext, b"hello");
+//! ```
+//!
+//! [`EcGroup`]: crate::ec::EcGroup
+use std::error;
+use std::fmt;
+
+use crate::bn::BigNumContext;
+use crate::derive::Deriver;
+use crate::ec::{EcGroupRef, EcKey, EcKeyRef, EcPoint, PointConversionForm};
+use crate::error::ErrorStack;
+use crate::hash::{Hasher, MessageDigest};
+use crate::pkey::{HasPrivate, HasPublic, PKey};
+use crate::symm::{self, Cipher};
+
+const VERSION: u8 = 1;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// An error encrypting or decrypting an ECIES message.
+#[derive(Debug)]
+pub enum Error {
+    /// The message is truncated or its ephemeral public key is malformed.
+    Invalid(String),
+    /// The message's version or suite is not supported.
+    Unsupported(String),
+    /// The message failed authentication, because it was modified, encrypted to a different key
+    /// or the associated data does not match.
+    Decrypt,
+    /// An error reported by OpenSSL.
+    Ssl(ErrorStack),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Invalid(msg) => write!(fmt, "invalid ECIES message: {}", msg),
+            Error::Unsupported(msg) => write!(fmt, "unsupported ECIES message: {}", msg),
+            Error::Decrypt => fmt.write_str("ECIES message failed authentication"),
+            Error::Ssl(e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Ssl(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorStack> for Error {
+    fn from(e: ErrorStack) -> Error {
+        Error::Ssl(e)
+    }
+}
+
+/// The KDF digest and AEAD cipher used to encrypt a message.
+///
+/// The suite is recorded in each message, so [`decrypt`] accepts messages encrypted with any of
+/// them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EciesSuite(u8);
+
+impl EciesSuite {
+    /// X9.63 KDF with SHA-256 and AES-128-GCM.
+    pub const AES_128_GCM_SHA256: EciesSuite = EciesSuite(1);
+
+    /// X9.63 KDF with SHA-256 and AES-256-GCM.
+    pub const AES_256_GCM_SHA256: EciesSuite = EciesSuite(2);
+
+    /// X9.63 KDF with SHA-512 and AES-256-GCM.
+    pub const AES_256_GCM_SHA512: EciesSuite = EciesSuite(3);
+
+    /// X9.63 KDF with SHA-256 and ChaCha20-Poly1305.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CHACHA")))]
+    pub const CHACHA20_POLY1305_SHA256: EciesSuite = EciesSuite(4);
+
+    /// Returns the identifier of the suite in the message header.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u8 {
+        self.0
+    }
+
+    fn params(self) -> Option<(MessageDigest, Cipher)> {
+        match self.0 {
+            1 => Some((MessageDigest::sha256(), Cipher::aes_128_gcm())),
+            2 => Some((MessageDigest::sha256(), Cipher::aes_256_gcm())),
+            3 => Some((MessageDigest::sha512(), Cipher::aes_256_gcm())),
+            #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CHACHA")))]
+            4 => Some((MessageDigest::sha256(), Cipher::chacha20_poly1305())),
+            _ => None,
+        }
+    }
+}
+
+/// Encrypts `plaintext` to the holder of the private half of `key`.
+///
+/// `aad` is authenticated but not encrypted, and must be passed unchanged to [`decrypt`]. It can
+/// be used to bind the message to its context, such as a recipient identifier or protocol name.
+pub fn encrypt<T>(
+    suite: EciesSuite,
+    key: &EcKeyRef<T>,
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, Error>
+where
+    T: HasPublic,
+{
+    let (md, cipher) = suite
+        .params()
+        .ok_or_else(|| Error::Unsupported(format!("suite {}", suite.0)))?;
+    let group = key.group();
+
+    let ephemeral = EcKey::generate(group)?;
+    let mut ctx = BigNumContext::new()?;
+    let mut message = vec![VERSION, suite.0];
+    message.extend_from_slice(&ephemeral.public_key().to_bytes(
+        group,
+        PointConversionForm::UNCOMPRESSED,
+        &mut ctx,
+    )?);
+
+    let peer = PKey::from_ec_key(EcKey::from_public_key(group, key.public_key())?)?;
+    let ephemeral = PKey::from_ec_key(ephemeral)?;
+    let mut deriver = Deriver::new(&ephemeral)?;
+    deriver.set_peer(&peer)?;
+    let secret = deriver.derive_to_vec()?;
+
+    let key_nonce = x963_kdf(md, &secret, &message, cipher.key_len() + NONCE_LEN)?;
+    let (cipher_key, nonce) = key_nonce.split_at(cipher.key_len());
+
+    let mut tag = [0; TAG_LEN];
+    let ciphertext = symm::encrypt_aead(
+        cipher,
+        cipher_key,
+        Some(nonce),
+        &[&message, aad].concat(),
+        plaintext,
+        &mut tag,
+    )?;
+    message.extend_from_slice(&ciphertext);
+    message.extend_from_slice(&tag);
+    Ok(message)
+}
+
+/// Decrypts a message produced by [`encrypt`] with the recipient's private key.
+///
+/// `aad` must match the associated data the message was encrypted with.
+pub fn decrypt<T>(key: &EcKeyRef<T>, aad: &[u8], message: &[u8]) -> Result<Vec<u8>, Error>
+where
+    T: HasPrivate,
+{
+    let group = key.group();
+    let point_len = 1 + 2 * field_len(group);
+    let header_len = 2 + point_len;
+    if message.len() < header_len + TAG_LEN {
+        return Err(Error::Invalid("message is too short".to_string()));
+    }
+
+    if message[0] != VERSION {
+        return Err(Error::Unsupported(format!("version {}", message[0])));
+    }
+    let suite = EciesSuite(message[1]);
+    let (md, cipher) = suite
+        .params()
+        .ok_or_else(|| Error::Unsupported(format!("suite {}", suite.0)))?;
+
+    let (header, body) = message.split_at(header_len);
+    let (ciphertext, tag) = body.split_at(body.len() - TAG_LEN);
+
+    let mut ctx = BigNumContext::new()?;
+    let point = EcPoint::from_bytes(group, &header[2..], &mut ctx)
+        .map_err(|_| Error::Invalid("malformed ephemeral public key".to_string()))?;
+    let peer = PKey::from_ec_key(EcKey::from_public_key(group, &point)?)?;
+    let private_key = PKey::from_ec_key(EcKey::from_private_components(
+        group,
+        key.private_key(),
+        key.public_key(),
+    )?)?;
+    let mut deriver = Deriver::new(&private_key)?;
+    deriver.set_peer(&peer)?;
+    let secret = deriver.derive_to_vec()?;
+
+    let key_nonce = x963_kdf(md, &secret, header, cipher.key_len() + NONCE_LEN)?;
+    let (cipher_key, nonce) = key_nonce.split_at(cipher.key_len());
+
+    symm::decrypt_aead(
+        cipher,
+        cipher_key,
+        Some(nonce),
+        &[header, aad].concat(),
+        ciphertext,
+        tag,
+    )
+    .map_err(|_| Error::Decrypt)
+}
+
+/// Returns the length in bytes of a coordinate on the curve.
+fn field_len(group: &EcGroupRef) -> usize {
+    (group.degree() as usize + 7) >> 3
+}
+
+/// The ANSI X9.63 key derivation function, as specified in SEC 1 section 3.6.1.
+fn x963_kdf(
+    md: MessageDigest,
+    secret: &[u8],
+    shared_info: &[u8],
+    len: usize,
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut out = Vec::with_capacity(len + md.size());
+    let mut counter = 1u32;
+    while out.len() < len {
+        let mut hasher = Hasher::new(md)?;
+        hasher.update(secret)?;
+        hasher.update(&counter.to_be_bytes())?;
+        hasher.update(shared_info)?;
+        out.extend_from_slice(&hasher.finish()?);
+        counter += 1;
+    }
+    out.truncate(len);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ec::EcGroup;
+    use crate::nid::Nid;
+
+    #[test]
+    fn round_trip() {
+        for &nid in &[Nid::X9_62_PRIME256V1, Nid::SECP384R1, Nid::SECP521R1] {
+            let group = EcGroup::from_curve_name(nid).unwrap();
+            let key = EcKey::generate(&group).unwrap();
+            let public_key = EcKey::from_public_key(&group, key.public_key()).unwrap();
+
+            for &suite in &[
+                EciesSuite::AES_128_GCM_SHA256,
+                EciesSuite::AES_256_GCM_SHA256,
+                EciesSuite::AES_256_GCM_SHA512,
+            ] {
+                let message = encrypt(suite, &public_key, b"aad", b"attack at dawn").unwrap();
+                assert_eq!(message[..2], [VERSION, suite.as_raw()]);
+                assert_eq!(decrypt(&key, b"aad", &message).unwrap(), b"attack at dawn");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CHACHA")))]
+    fn chacha20_poly1305() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+
+        let message = encrypt(EciesSuite::CHACHA20_POLY1305_SHA256, &key, &[], b"").unwrap();
+        assert!(decrypt(&key, &[], &message).unwrap().is_empty());
+    }
+
+    #[test]
+    fn tampering() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+        let other = EcKey::generate(&group).unwrap();
+        let message = encrypt(EciesSuite::AES_128_GCM_SHA256, &key, b"aad", b"hello").unwrap();
+
+        assert!(matches!(
+            decrypt(&key, b"other", &message),
+            Err(Error::Decrypt)
+        ));
+        assert!(matches!(
+            decrypt(&other, b"aad", &message),
+            Err(Error::Decrypt)
+        ));
+
+        let mut modified = message.clone();
+        *modified.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            decrypt(&key, b"aad", &modified),
+            Err(Error::Decrypt)
+        ));
+
+        let mut modified = message.clone();
+        modified[0] = 2;
+        assert!(matches!(
+            decrypt(&key, b"aad", &modified),
+            Err(Error::Unsupported(_))
+        ));
+
+        assert!(matches!(
+            decrypt(&key, b"aad", &message[..40]),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn x963_kdf_vector() {
+        // ANSI X9.63 test vector from NIST CAVS, SHA-256 without SharedInfo.
+        let secret = hex::decode("96c05619d56c328ab95fe84b18264b08725b85e33fd34f08").unwrap();
+        let out = x963_kdf(MessageDigest::sha256(), &secret, &[], 16).unwrap();
+        assert_eq!(hex::encode(out), "443024c3dae66b95e6f5670601558f71");
+    }
+}
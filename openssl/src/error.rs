@@ -333,3 +333,27 @@ cfg_if! {
         }
     }
 }
+
+/// Pushes an error with the given library and reason codes onto the OpenSSL error stack.
+#[cfg(ossl300)]
+pub(crate) unsafe fn push_error(lib: c_int, reason: c_int) {
+    ffi::ERR_new();
+    ffi::ERR_set_debug(
+        concat!(file!(), "\0").as_ptr() as *const _,
+        line!() as c_int,
+        ptr::null(),
+    );
+    ffi::ERR_set_error(lib, reason, ptr::null());
+}
+
+/// Pushes an error with the given library and reason codes onto the OpenSSL error stack.
+#[cfg(not(ossl300))]
+pub(crate) unsafe fn push_error(lib: c_int, reason: c_int) {
+    ffi::ERR_put_error(
+        lib,
+        0,
+        reason,
+        concat!(file!(), "\0").as_ptr() as *const _,
+        line!() as _,
+    );
+}
@@ -0,0 +1,159 @@
+//! Building blocks for Enrollment over Secure Transport.
+//!
+//! EST ([RFC 7030]) is a certificate enrollment protocol which runs over HTTPS. An EST client is
+//! assembled from the following pieces:
+//!
+//! * Certificates returned by the `/cacerts`, `/simpleenroll` and `/simplereenroll` operations
+//!   are base64 encoded "certs-only" PKCS#7 structures, which can be parsed with
+//!   [`Pkcs7::from_der`] and read through [`Pkcs7Ref::signed`].
+//! * The attributes the server would like to see in certificate requests are returned by the
+//!   `/csrattrs` operation, and can be parsed with [`CsrAttrs`].
+//! * A certificate request can be bound to the TLS connection it is submitted over by including
+//!   the connection's [`tls-unique`] value with [`set_channel_binding`].
+//!
+//! [RFC 7030]: https://www.rfc-editor.org/rfc/rfc7030
+//! [`Pkcs7::from_der`]: ../pkcs7/struct.Pkcs7.html#method.from_der
+//! [`Pkcs7Ref::signed`]: ../pkcs7/struct.Pkcs7Ref.html#method.signed
+//! [`tls-unique`]: ../ssl/struct.SslRef.html#method.tls_unique
+use crate::asn1::{get_object, wrong_tag, Asn1Object};
+use crate::base64;
+use crate::error::ErrorStack;
+use crate::x509::{X509Attribute, X509ReqBuilder};
+
+/// An entry of a `CsrAttrs` structure.
+pub enum CsrAttr {
+    /// An object identifier, typically naming an attribute or signature algorithm the server
+    /// expects the request to use.
+    Oid(Asn1Object),
+    /// An attribute along with the values the server expects it to have.
+    Attribute(X509Attribute),
+}
+
+/// The attributes an EST server would like to see in certificate requests, as returned by the
+/// `/csrattrs` operation.
+///
+/// See [RFC 7030 section 4.5.2].
+///
+/// [RFC 7030 section 4.5.2]: https://www.rfc-editor.org/rfc/rfc7030#section-4.5.2
+pub struct CsrAttrs(Vec<CsrAttr>);
+
+impl CsrAttrs {
+    /// Deserializes a DER-encoded `CsrAttrs` structure.
+    ///
+    /// The body of a `/csrattrs` response is base64 encoded, and must be decoded first.
+    pub fn from_der(der: &[u8]) -> Result<CsrAttrs, ErrorStack> {
+        let mut der = der;
+        let mut body = match get_object(&mut der)? {
+            (ffi::V_ASN1_SEQUENCE, ffi::V_ASN1_UNIVERSAL, body) => body,
+            _ => return Err(wrong_tag()),
+        };
+
+        let mut attrs = vec![];
+        while !body.is_empty() {
+            let mut rest = body;
+            let (tag, class, _) = get_object(&mut rest)?;
+            let element = &body[..body.len() - rest.len()];
+            let attr = match (tag, class) {
+                (ffi::V_ASN1_OBJECT, ffi::V_ASN1_UNIVERSAL) => {
+                    CsrAttr::Oid(Asn1Object::from_der(element)?)
+                }
+                (ffi::V_ASN1_SEQUENCE, ffi::V_ASN1_UNIVERSAL) => {
+                    CsrAttr::Attribute(X509Attribute::from_der(element)?)
+                }
+                _ => return Err(wrong_tag()),
+            };
+            attrs.push(attr);
+            body = rest;
+        }
+
+        Ok(CsrAttrs(attrs))
+    }
+
+    /// Returns the entries of the structure, in the order the server sent them.
+    pub fn attrs(&self) -> &[CsrAttr] {
+        &self.0
+    }
+}
+
+/// Binds a certificate request to a TLS connection by placing the connection's `tls-unique`
+/// value in the request's `challengePassword` attribute.
+///
+/// `tls_unique` is the value returned by [`SslRef::tls_unique`] for the connection the request
+/// will be submitted over. It is base64 encoded as required by [RFC 7030 section 3.5].
+///
+/// [`SslRef::tls_unique`]: ../ssl/struct.SslRef.html#method.tls_unique
+/// [RFC 7030 section 3.5]: https://www.rfc-editor.org/rfc/rfc7030#section-3.5
+pub fn set_channel_binding(req: &mut X509ReqBuilder, tls_unique: &[u8]) -> Result<(), ErrorStack> {
+    req.set_challenge_password(&base64::encode_block(tls_unique))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::hash::MessageDigest;
+    use crate::nid::Nid;
+    use crate::pkey::PKey;
+    use crate::rsa::Rsa;
+    use crate::x509::X509Req;
+
+    #[test]
+    fn csr_attrs() {
+        // The example from RFC 7030 section 4.5.2
+        let der = base64::decode_block(
+            "MEEGCSqGSIb3DQEJBzASBgcqhkjOPQIBMQcGBSuBBAAiMBYGCSqGSIb3DQEJDjEJBgcrBgEBAQEWBggqhkjOPQQDAw==",
+        )
+        .unwrap();
+        let attrs = CsrAttrs::from_der(&der).unwrap();
+        let attrs = attrs.attrs();
+        assert_eq!(attrs.len(), 4);
+
+        match &attrs[0] {
+            CsrAttr::Oid(oid) => assert_eq!(oid.nid(), Nid::PKCS9_CHALLENGEPASSWORD),
+            _ => panic!("expected an object identifier"),
+        }
+        match &attrs[1] {
+            CsrAttr::Attribute(attr) => {
+                assert_eq!(attr.object().to_string(), "id-ecPublicKey");
+                assert_eq!(attr.value_count(), 1);
+                assert_eq!(attr.value_object(0).unwrap().nid(), Nid::SECP384R1);
+            }
+            _ => panic!("expected an attribute"),
+        }
+        match &attrs[2] {
+            CsrAttr::Attribute(attr) => {
+                assert_eq!(attr.object().nid(), Nid::EXT_REQ);
+                assert_eq!(
+                    attr.value_object(0).unwrap().to_string(),
+                    "1.3.6.1.1.1.1.22"
+                );
+            }
+            _ => panic!("expected an attribute"),
+        }
+        match &attrs[3] {
+            CsrAttr::Oid(oid) => assert_eq!(oid.nid(), Nid::ECDSA_WITH_SHA384),
+            _ => panic!("expected an object identifier"),
+        }
+
+        assert!(CsrAttrs::from_der(&der[2..]).is_err());
+    }
+
+    #[test]
+    fn channel_binding() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let tls_unique = [0x5a; 12];
+
+        let mut builder = X509Req::builder().unwrap();
+        builder.set_pubkey(&key).unwrap();
+        set_channel_binding(&mut builder, &tls_unique).unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        let req = X509Req::from_der(&builder.build().to_der().unwrap()).unwrap();
+
+        let attr = req.attribute_by_nid(Nid::PKCS9_CHALLENGEPASSWORD).unwrap();
+        assert_eq!(attr.value_count(), 1);
+        assert_eq!(
+            attr.value_string(0).unwrap().as_slice(),
+            base64::encode_block(&tls_unique).as_bytes()
+        );
+    }
+}
@@ -0,0 +1,490 @@
+//! Private keys whose operations are performed by a user-provided closure.
+//!
+//! Keys held in a hardware security module or a cloud key management service such as AWS KMS,
+//! Google Cloud KMS or Azure Key Vault can't be exported. [`PKey::from_signer`] wraps the public
+//! half of such a key in a `PKey<Private>` which forwards each private key operation to a
+//! closure, so it can be used anywhere OpenSSL accepts a private key, including
+//! [`SslContextBuilder::set_private_key`].
+//!
+//! RSA and EC keys are supported.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::ec::{EcGroup, EcKey};
+//! use openssl::ecdsa::EcdsaSig;
+//! use openssl::external_key::KeyOperation;
+//! use openssl::hash::MessageDigest;
+//! use openssl::nid::Nid;
+//! use openssl::pkey::PKey;
+//! use openssl::sign::{Signer, Verifier};
+//!
+//! // stands in for a key held by a remote service
+//! let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+//! let remote = EcKey::generate(&group).unwrap();
+//! let public_key = EcKey::from_public_key(&group, remote.public_key()).unwrap();
+//! let public_key = PKey::from_ec_key(public_key).unwrap();
+//!
+//! let key = PKey::from_signer(&public_key, move |op| match op {
+//!     KeyOperation::EcdsaSign { digest } => EcdsaSig::sign(digest, &remote)?.to_der(),
+//!     _ => Err(openssl::error::ErrorStack::get()),
+//! })
+//! .unwrap();
+//!
+//! let mut signer = Signer::new(MessageDigest::sha256(), &key).unwrap();
+//! signer.update(b"hello").unwrap();
+//! let signature = signer.sign_to_vec().unwrap();
+//!
+//! let mut verifier = Verifier::new(MessageDigest::sha256(), &public_key).unwrap();
+//! verifier.update(b"hello").unwrap();
+//! assert!(verifier.verify(&signature).unwrap());
+//! ```
+//!
+//! [`SslContextBuilder::set_private_key`]: crate::ssl::SslContextBuilder::set_private_key
+use foreign_types::ForeignType;
+use libc::{c_int, c_long, c_uchar, c_uint, c_void, size_t};
+use once_cell::sync::OnceCell;
+use std::mem;
+use std::ptr;
+use std::slice;
+
+use crate::bn::BigNumContext;
+use crate::ec::{EcGroupRef, EcKey, EcPointRef, PointConversionForm};
+use crate::error::ErrorStack;
+use crate::nid::Nid;
+use crate::pkey::{HasPublic, Id, PKey, PKeyRef, Private};
+use crate::rsa::Rsa;
+use crate::util::{crypto_malloc, ForeignTypeRefExt};
+use crate::{cvt, cvt_n, cvt_p};
+
+type Callback = Box<dyn Fn(KeyOperation<'_>) -> Result<Vec<u8>, ErrorStack> + Send + Sync>;
+
+/// A private key operation requested of the closure of a key created with
+/// [`PKey::from_signer`].
+///
+/// The closure returns the output of the operation, or an error if it can't be performed.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum KeyOperation<'a> {
+    /// Sign `digest` with ECDSA, returning a DER-encoded `ECDSA-Sig-Value`.
+    EcdsaSign { digest: &'a [u8] },
+    /// Perform ECDH with the peer's public key, an uncompressed point on the key's curve,
+    /// returning the x coordinate of the shared point.
+    Ecdh { peer_public_key: &'a [u8] },
+    /// Sign `digest`, which was computed with the digest `md`, with RSASSA-PKCS1-v1_5,
+    /// returning the signature.
+    RsaPkcs1Sign { md: Nid, digest: &'a [u8] },
+    /// Apply the raw RSA private key operation to `input`, which has already been padded to the
+    /// length of the modulus, returning a result of the same length.
+    ///
+    /// This is used for RSASSA-PSS signatures, such as those required by TLS 1.3.
+    RsaRaw { input: &'a [u8] },
+}
+
+impl PKey<Private> {
+    /// Creates a private key whose operations are performed by `signer`.
+    ///
+    /// `public_key` is the public half of the key, which must be an RSA or EC key. See the
+    /// [module documentation](crate::external_key) for details.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    pub fn from_signer<T, F>(
+        public_key: &PKeyRef<T>,
+        signer: F,
+    ) -> Result<PKey<Private>, ErrorStack>
+    where
+        T: HasPublic,
+        F: Fn(KeyOperation<'_>) -> Result<Vec<u8>, ErrorStack> + 'static + Send + Sync,
+    {
+        let callback = Box::new(Box::new(signer) as Callback);
+        if public_key.id() == Id::RSA {
+            let rsa = public_key.rsa()?;
+            let rsa = Rsa::from_public_components(rsa.n().to_owned()?, rsa.e().to_owned()?)?;
+            unsafe {
+                let idx = ex_index(&RSA_INDEX, ffi::CRYPTO_EX_INDEX_RSA)?;
+                let method = rsa_method()?;
+                let ptr = Box::into_raw(callback);
+                if ffi::RSA_set_ex_data(rsa.as_ptr(), idx, ptr as *mut c_void) <= 0 {
+                    drop(Box::from_raw(ptr));
+                    return Err(ErrorStack::get());
+                }
+                cvt(ffi::RSA_set_method(rsa.as_ptr(), method))?;
+                let ptr = rsa.as_ptr();
+                mem::forget(rsa);
+                PKey::from_rsa(Rsa::from_ptr(ptr))
+            }
+        } else {
+            let ec_key = public_key.ec_key()?;
+            let ec_key = EcKey::from_public_key(ec_key.group(), ec_key.public_key())?;
+            unsafe {
+                let idx = ex_index(&EC_KEY_INDEX, ffi::CRYPTO_EX_INDEX_EC_KEY)?;
+                let method = ec_key_method()?;
+                let ptr = Box::into_raw(callback);
+                if ffi::EC_KEY_set_ex_data(ec_key.as_ptr(), idx, ptr as *mut c_void) <= 0 {
+                    drop(Box::from_raw(ptr));
+                    return Err(ErrorStack::get());
+                }
+                cvt(ffi::EC_KEY_set_method(ec_key.as_ptr(), method))?;
+                let ptr = ec_key.as_ptr();
+                mem::forget(ec_key);
+                PKey::from_ec_key(EcKey::from_ptr(ptr))
+            }
+        }
+    }
+}
+
+static RSA_INDEX: OnceCell<c_int> = OnceCell::new();
+static EC_KEY_INDEX: OnceCell<c_int> = OnceCell::new();
+
+struct RsaMethod(*mut ffi::RSA_METHOD);
+
+unsafe impl Send for RsaMethod {}
+unsafe impl Sync for RsaMethod {}
+
+struct EcKeyMethod(*mut ffi::EC_KEY_METHOD);
+
+unsafe impl Send for EcKeyMethod {}
+unsafe impl Sync for EcKeyMethod {}
+
+fn ex_index(index: &OnceCell<c_int>, class_index: c_int) -> Result<c_int, ErrorStack> {
+    index
+        .get_or_try_init(|| unsafe {
+            ffi::init();
+            cvt_n(ffi::CRYPTO_get_ex_new_index(
+                class_index,
+                0,
+                ptr::null_mut(),
+                None,
+                None,
+                Some(free_callback),
+            ))
+        })
+        .copied()
+}
+
+unsafe extern "C" fn free_callback(
+    _parent: *mut c_void,
+    ptr: *mut c_void,
+    _ad: *mut ffi::CRYPTO_EX_DATA,
+    _idx: c_int,
+    _argl: c_long,
+    _argp: *mut c_void,
+) {
+    if !ptr.is_null() {
+        drop(Box::<Callback>::from_raw(ptr as *mut Callback));
+    }
+}
+
+/// Runs the callback stored in a key's ex data, placing its error on the error stack if it fails.
+unsafe fn invoke(callback: *mut c_void, op: KeyOperation<'_>) -> Option<Vec<u8>> {
+    let callback = (callback as *const Callback).as_ref()?;
+    match callback(op) {
+        Ok(output) => Some(output),
+        Err(e) => {
+            e.put();
+            None
+        }
+    }
+}
+
+// The methods are shared by all keys and live for the rest of the process.
+fn rsa_method() -> Result<*const ffi::RSA_METHOD, ErrorStack> {
+    static METHOD: OnceCell<RsaMethod> = OnceCell::new();
+
+    METHOD
+        .get_or_try_init(|| unsafe {
+            let method = cvt_p(ffi::RSA_meth_dup(ffi::RSA_PKCS1_OpenSSL()))?;
+            cvt(ffi::RSA_meth_set_sign(method, Some(rsa_sign)))?;
+            cvt(ffi::RSA_meth_set_priv_enc(method, Some(rsa_priv_enc)))?;
+            Ok(RsaMethod(method))
+        })
+        .map(|method| method.0 as *const _)
+}
+
+fn ec_key_method() -> Result<*const ffi::EC_KEY_METHOD, ErrorStack> {
+    static METHOD: OnceCell<EcKeyMethod> = OnceCell::new();
+
+    METHOD
+        .get_or_try_init(|| unsafe {
+            let method = cvt_p(ffi::EC_KEY_METHOD_new(ffi::EC_KEY_OpenSSL()))?;
+            ffi::EC_KEY_METHOD_set_sign(method, Some(ec_sign), None, Some(ec_sign_sig));
+            ffi::EC_KEY_METHOD_set_compute_key(method, Some(ec_compute_key));
+            Ok(EcKeyMethod(method))
+        })
+        .map(|method| method.0 as *const _)
+}
+
+unsafe fn rsa_callback(rsa: *const ffi::RSA) -> *mut c_void {
+    match RSA_INDEX.get() {
+        Some(idx) => ffi::RSA_get_ex_data(rsa, *idx),
+        None => ptr::null_mut(),
+    }
+}
+
+unsafe fn ec_key_callback(ec_key: *const ffi::EC_KEY) -> *mut c_void {
+    match EC_KEY_INDEX.get() {
+        Some(idx) => ffi::EC_KEY_get_ex_data(ec_key, *idx),
+        None => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn rsa_sign(
+    type_: c_int,
+    m: *const c_uchar,
+    m_length: c_uint,
+    sigret: *mut c_uchar,
+    siglen: *mut c_uint,
+    rsa: *const ffi::RSA,
+) -> c_int {
+    let op = KeyOperation::RsaPkcs1Sign {
+        md: Nid::from_raw(type_),
+        digest: slice::from_raw_parts(m, m_length as usize),
+    };
+    match invoke(rsa_callback(rsa), op) {
+        Some(signature) if signature.len() <= ffi::RSA_size(rsa) as usize => {
+            ptr::copy_nonoverlapping(signature.as_ptr(), sigret, signature.len());
+            *siglen = signature.len() as c_uint;
+            1
+        }
+        _ => 0,
+    }
+}
+
+unsafe extern "C" fn rsa_priv_enc(
+    flen: c_int,
+    from: *const c_uchar,
+    to: *mut c_uchar,
+    rsa: *mut ffi::RSA,
+    padding: c_int,
+) -> c_int {
+    let size = ffi::RSA_size(rsa);
+    let mut padded = vec![];
+    let input = match padding {
+        ffi::RSA_NO_PADDING => slice::from_raw_parts(from, flen as usize),
+        ffi::RSA_PKCS1_PADDING => {
+            padded.resize(size as usize, 0);
+            if ffi::RSA_padding_add_PKCS1_type_1(padded.as_mut_ptr(), size, from, flen) <= 0 {
+                return -1;
+            }
+            &padded
+        }
+        _ => return -1,
+    };
+    match invoke(rsa_callback(rsa), KeyOperation::RsaRaw { input }) {
+        Some(output) if output.len() == size as usize => {
+            ptr::copy_nonoverlapping(output.as_ptr(), to, output.len());
+            size
+        }
+        _ => -1,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn ec_sign(
+    _type: c_int,
+    dgst: *const c_uchar,
+    dlen: c_int,
+    sig: *mut c_uchar,
+    siglen: *mut c_uint,
+    _kinv: *const ffi::BIGNUM,
+    _r: *const ffi::BIGNUM,
+    eckey: *mut ffi::EC_KEY,
+) -> c_int {
+    let op = KeyOperation::EcdsaSign {
+        digest: slice::from_raw_parts(dgst, dlen as usize),
+    };
+    match invoke(ec_key_callback(eckey), op) {
+        Some(signature) if signature.len() <= ffi::ECDSA_size(eckey) as usize => {
+            ptr::copy_nonoverlapping(signature.as_ptr(), sig, signature.len());
+            *siglen = signature.len() as c_uint;
+            1
+        }
+        _ => 0,
+    }
+}
+
+unsafe extern "C" fn ec_sign_sig(
+    dgst: *const c_uchar,
+    dgst_len: c_int,
+    _in_kinv: *const ffi::BIGNUM,
+    _in_r: *const ffi::BIGNUM,
+    eckey: *mut ffi::EC_KEY,
+) -> *mut ffi::ECDSA_SIG {
+    let op = KeyOperation::EcdsaSign {
+        digest: slice::from_raw_parts(dgst, dgst_len as usize),
+    };
+    match invoke(ec_key_callback(eckey), op) {
+        Some(signature) => ffi::d2i_ECDSA_SIG(
+            ptr::null_mut(),
+            &mut signature.as_ptr(),
+            signature.len() as c_long,
+        ),
+        None => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn ec_compute_key(
+    psec: *mut *mut c_uchar,
+    pseclen: *mut size_t,
+    pub_key: *const ffi::EC_POINT,
+    ecdh: *const ffi::EC_KEY,
+) -> c_int {
+    let group = EcGroupRef::from_const_ptr(ffi::EC_KEY_get0_group(ecdh));
+    let peer_public_key = match BigNumContext::new().and_then(|mut ctx| {
+        EcPointRef::from_const_ptr(pub_key).to_bytes(
+            group,
+            PointConversionForm::UNCOMPRESSED,
+            &mut ctx,
+        )
+    }) {
+        Ok(peer_public_key) => peer_public_key,
+        Err(e) => {
+            e.put();
+            return 0;
+        }
+    };
+    let op = KeyOperation::Ecdh {
+        peer_public_key: &peer_public_key,
+    };
+    let secret = match invoke(ec_key_callback(ecdh), op) {
+        Some(secret) => secret,
+        None => return 0,
+    };
+    // OpenSSL takes ownership of the secret, and frees it with OPENSSL_free
+    let buf = match crypto_malloc(secret.len()) {
+        Ok(buf) => buf as *mut c_uchar,
+        Err(e) => {
+            e.put();
+            return 0;
+        }
+    };
+    ptr::copy_nonoverlapping(secret.as_ptr(), buf, secret.len());
+    *psec = buf;
+    *pseclen = secret.len();
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derive::Deriver;
+    use crate::ec::{EcGroup, EcPoint};
+    use crate::ecdsa::EcdsaSig;
+    use crate::hash::MessageDigest;
+    use crate::pkey::Public;
+    use crate::rsa::Padding;
+    use crate::sign::{Signer, Verifier};
+
+    // the DER-encoded DigestInfo prefix of a SHA-256 digest
+    const SHA256_DIGEST_INFO: &[u8] = &[
+        0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01,
+        0x05, 0x00, 0x04, 0x20,
+    ];
+
+    fn rsa_key() -> (PKey<Private>, PKey<Public>) {
+        let rsa = Rsa::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+        let public_key =
+            Rsa::from_public_components(rsa.n().to_owned().unwrap(), rsa.e().to_owned().unwrap())
+                .unwrap();
+        let public_key = PKey::from_rsa(public_key).unwrap();
+
+        let key = PKey::from_signer(&public_key, move |op| {
+            let mut out = vec![0; rsa.size() as usize];
+            let len = match op {
+                KeyOperation::RsaPkcs1Sign { md, digest } => {
+                    assert_eq!(md, Nid::SHA256);
+                    let mut input = SHA256_DIGEST_INFO.to_vec();
+                    input.extend_from_slice(digest);
+                    rsa.private_encrypt(&input, &mut out, Padding::PKCS1)?
+                }
+                KeyOperation::RsaRaw { input } => {
+                    rsa.private_encrypt(input, &mut out, Padding::NONE)?
+                }
+                op => panic!("unexpected operation {:?}", op),
+            };
+            out.truncate(len);
+            Ok(out)
+        })
+        .unwrap();
+        (key, public_key)
+    }
+
+    fn sign_and_verify(key: &PKey<Private>, public_key: &PKey<Public>, padding: Padding) {
+        let mut signer = Signer::new(MessageDigest::sha256(), key).unwrap();
+        signer.set_rsa_padding(padding).unwrap();
+        signer.update(b"hello world").unwrap();
+        let signature = signer.sign_to_vec().unwrap();
+
+        let mut verifier = Verifier::new(MessageDigest::sha256(), public_key).unwrap();
+        verifier.set_rsa_padding(padding).unwrap();
+        verifier.update(b"hello world").unwrap();
+        assert!(verifier.verify(&signature).unwrap());
+    }
+
+    #[test]
+    fn rsa_pkcs1() {
+        let (key, public_key) = rsa_key();
+        assert_eq!(key.id(), Id::RSA);
+        assert!(key.public_eq(&public_key));
+        sign_and_verify(&key, &public_key, Padding::PKCS1);
+    }
+
+    #[test]
+    fn rsa_pss() {
+        let (key, public_key) = rsa_key();
+        sign_and_verify(&key, &public_key, Padding::PKCS1_PSS);
+    }
+
+    #[test]
+    fn rsa_signer_error() {
+        let rsa = Rsa::generate(2048).unwrap();
+        let public_key = PKey::from_rsa(rsa).unwrap();
+        let key = PKey::from_signer(&public_key, |_| Err(ErrorStack::get())).unwrap();
+
+        let mut signer = Signer::new(MessageDigest::sha256(), &key).unwrap();
+        signer.update(b"hello world").unwrap();
+        assert!(signer.sign_to_vec().is_err());
+    }
+
+    #[test]
+    fn ec() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let remote = EcKey::generate(&group).unwrap();
+        let public_key = EcKey::from_public_key(&group, remote.public_key()).unwrap();
+        let public_key = PKey::from_ec_key(public_key).unwrap();
+
+        let remote = PKey::from_ec_key(remote).unwrap();
+        let key = PKey::from_signer(&public_key, move |op| match op {
+            KeyOperation::EcdsaSign { digest } => {
+                EcdsaSig::sign(digest, &*remote.ec_key()?)?.to_der()
+            }
+            KeyOperation::Ecdh { peer_public_key } => {
+                let mut ctx = BigNumContext::new()?;
+                let point = EcPoint::from_bytes(&group, peer_public_key, &mut ctx)?;
+                let peer = PKey::from_ec_key(EcKey::from_public_key(&group, &point)?)?;
+                let mut deriver = Deriver::new(&remote)?;
+                deriver.set_peer(&peer)?;
+                deriver.derive_to_vec()
+            }
+            op => panic!("unexpected operation {:?}", op),
+        })
+        .unwrap();
+        assert_eq!(key.id(), Id::EC);
+
+        let mut signer = Signer::new(MessageDigest::sha256(), &key).unwrap();
+        signer.update(b"hello world").unwrap();
+        let signature = signer.sign_to_vec().unwrap();
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &public_key).unwrap();
+        verifier.update(b"hello world").unwrap();
+        assert!(verifier.verify(&signature).unwrap());
+
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let peer = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let mut deriver = Deriver::new(&key).unwrap();
+        deriver.set_peer(&peer).unwrap();
+        let secret = deriver.derive_to_vec().unwrap();
+        let mut deriver = Deriver::new(&peer).unwrap();
+        deriver.set_peer(&public_key).unwrap();
+        assert_eq!(secret, deriver.derive_to_vec().unwrap());
+    }
+}
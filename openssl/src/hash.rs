@@ -1,10 +1,16 @@
 use cfg_if::cfg_if;
+use std::cmp;
 use std::ffi::CString;
 use std::fmt;
+use std::fs::File;
 use std::io;
 use std::io::prelude::*;
 use std::ops::{Deref, DerefMut};
+use std::panic;
+use std::path::Path;
 use std::ptr;
+use std::slice;
+use std::thread::{self, JoinHandle};
 
 use crate::error::ErrorStack;
 use crate::nid::Nid;
@@ -419,6 +425,244 @@ pub fn hash_xof(t: MessageDigest, data: &[u8], buf: &mut [u8]) -> Result<(), Err
     h.finish_xof(buf)
 }
 
+/// Computes the hash of the contents of the file at `path` with the non-XOF hasher `t`.
+///
+/// On Unix platforms the file is memory mapped rather than read into a buffer. The file must not
+/// be truncated while it is being hashed.
+pub fn hash_file<P: AsRef<Path>>(path: P, t: MessageDigest) -> io::Result<DigestBytes> {
+    let file = File::open(path)?;
+
+    #[cfg(unix)]
+    {
+        let map = Mmap::new(&file)?;
+        Ok(hash(t, map.as_slice())?)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let mut h = Hasher::new(t)?;
+        io::copy(&mut &file, &mut h)?;
+        Ok(h.finish()?)
+    }
+}
+
+/// Computes tree hashes of data, spreading the work across multiple threads.
+///
+/// The input is split into chunks of `chunk_size` bytes, the last of which may be shorter. Each
+/// chunk is hashed as `H(0x00 || chunk)`, and the result is `H(0x01 || leaf_0 || ... || leaf_n)`.
+/// Empty input is treated as a single empty chunk. The result depends on the digest and chunk
+/// size, but not on the number of threads, and is different from the plain hash of the data.
+#[derive(Copy, Clone)]
+pub struct TreeHasher {
+    md: MessageDigest,
+    chunk_size: usize,
+    threads: usize,
+}
+
+impl TreeHasher {
+    /// Creates a new `TreeHasher` using the non-XOF hasher `t`.
+    ///
+    /// The chunk size defaults to 1 MiB, and hashing runs on the calling thread.
+    pub fn new(t: MessageDigest) -> TreeHasher {
+        TreeHasher {
+            md: t,
+            chunk_size: 1024 * 1024,
+            threads: 1,
+        }
+    }
+
+    /// Sets the size of the chunks which are hashed independently.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn chunk_size(&mut self, chunk_size: usize) -> &mut TreeHasher {
+        assert!(chunk_size > 0, "chunk size must be positive");
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Sets the number of threads used to hash the chunks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is 0.
+    pub fn threads(&mut self, threads: usize) -> &mut TreeHasher {
+        assert!(threads > 0, "at least one thread is required");
+        self.threads = threads;
+        self
+    }
+
+    /// Computes the tree hash of `data`.
+    pub fn hash(&self, data: &[u8]) -> Result<DigestBytes, ErrorStack> {
+        let leaves = self.leaves(data)?;
+
+        let mut h = Hasher::new(self.md)?;
+        h.update(&[1])?;
+        for leaf in &leaves {
+            h.update(leaf)?;
+        }
+        h.finish()
+    }
+
+    /// Computes the tree hash of the contents of the file at `path`.
+    ///
+    /// On Unix platforms the file is memory mapped rather than read into a buffer. The file must
+    /// not be truncated while it is being hashed.
+    pub fn hash_file<P: AsRef<Path>>(&self, path: P) -> io::Result<DigestBytes> {
+        let file = File::open(path)?;
+
+        #[cfg(unix)]
+        {
+            let map = Mmap::new(&file)?;
+            Ok(self.hash(map.as_slice())?)
+        }
+
+        #[cfg(not(unix))]
+        {
+            let mut buf = vec![];
+            (&file).read_to_end(&mut buf)?;
+            Ok(self.hash(&buf)?)
+        }
+    }
+
+    fn leaves(&self, data: &[u8]) -> Result<Vec<DigestBytes>, ErrorStack> {
+        let chunks = cmp::max(1, (data.len() + self.chunk_size - 1) / self.chunk_size);
+        let threads = cmp::min(self.threads, chunks);
+        if threads == 1 {
+            return hash_leaves(self.md, data, self.chunk_size);
+        }
+
+        // each thread hashes a contiguous run of whole chunks
+        let per_thread = (chunks + threads - 1) / threads * self.chunk_size;
+        let parts = data.chunks(per_thread).collect::<Vec<_>>();
+
+        let md = self.md;
+        let chunk_size = self.chunk_size;
+        let mut workers = Workers(vec![]);
+        for part in &parts[1..] {
+            let part = SendSlice(part.as_ptr(), part.len());
+            let handle = thread::Builder::new()
+                .spawn(move || {
+                    // Safety: the slice outlives the thread since `Workers` joins every thread
+                    // before this function returns or unwinds.
+                    let part = unsafe { slice::from_raw_parts(part.0, part.1) };
+                    hash_leaves(md, part, chunk_size)
+                })
+                .ok();
+            workers.0.push(handle);
+        }
+
+        let mut leaves = hash_leaves(md, parts[0], chunk_size)?;
+        for (part, handle) in parts[1..].iter().zip(&mut workers.0) {
+            let part_leaves = match handle.take() {
+                Some(handle) => match handle.join() {
+                    Ok(part_leaves) => part_leaves,
+                    Err(e) => panic::resume_unwind(e),
+                },
+                // the thread could not be spawned, so hash the part here instead
+                None => hash_leaves(md, part, chunk_size),
+            };
+            leaves.extend(part_leaves?);
+        }
+
+        Ok(leaves)
+    }
+}
+
+fn hash_leaves(
+    t: MessageDigest,
+    data: &[u8],
+    chunk_size: usize,
+) -> Result<Vec<DigestBytes>, ErrorStack> {
+    let leaf = |chunk: &[u8]| {
+        let mut h = Hasher::new(t)?;
+        h.update(&[0])?;
+        h.update(chunk)?;
+        h.finish()
+    };
+
+    if data.is_empty() {
+        return Ok(vec![leaf(&[])?]);
+    }
+    data.chunks(chunk_size).map(leaf).collect()
+}
+
+struct SendSlice(*const u8, usize);
+
+unsafe impl Send for SendSlice {}
+
+type Worker = JoinHandle<Result<Vec<DigestBytes>, ErrorStack>>;
+
+struct Workers(Vec<Option<Worker>>);
+
+impl Drop for Workers {
+    fn drop(&mut self) {
+        for handle in self.0.iter_mut().filter_map(Option::take) {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(unix)]
+struct Mmap {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+#[cfg(unix)]
+impl Mmap {
+    fn new(file: &File) -> io::Result<Mmap> {
+        use std::convert::TryFrom;
+        use std::os::unix::io::AsRawFd;
+
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "file too large to map"))?;
+        if len == 0 {
+            // mapping an empty file fails
+            return Ok(Mmap {
+                ptr: ptr::null_mut(),
+                len,
+            });
+        }
+
+        unsafe {
+            let ptr = libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            );
+            if ptr == libc::MAP_FAILED {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(Mmap { ptr, len })
+        }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+
+        unsafe { slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            unsafe {
+                libc::munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use hex::{self, FromHex};
@@ -668,4 +912,58 @@ mod tests {
             MessageDigest::sha256().as_ptr()
         )
     }
+
+    #[test]
+    fn test_hash_file() {
+        let dir = tempdir::TempDir::new("hash_file").unwrap();
+        let path = dir.path().join("data");
+        let data = (0..100_000u32).map(|i| i as u8).collect::<Vec<_>>();
+        std::fs::write(&path, &data).unwrap();
+
+        let res = hash_file(&path, MessageDigest::sha256()).unwrap();
+        assert_eq!(*res, *hash(MessageDigest::sha256(), &data).unwrap());
+
+        std::fs::write(&path, b"").unwrap();
+        let res = hash_file(&path, MessageDigest::sha256()).unwrap();
+        assert_eq!(*res, *hash(MessageDigest::sha256(), b"").unwrap());
+    }
+
+    #[test]
+    fn test_tree_hash() {
+        let data = (0..10_000u32).map(|i| i as u8).collect::<Vec<_>>();
+
+        let mut root = Hasher::new(MessageDigest::sha256()).unwrap();
+        root.update(&[1]).unwrap();
+        for chunk in data.chunks(1000) {
+            let mut leaf = Hasher::new(MessageDigest::sha256()).unwrap();
+            leaf.update(&[0]).unwrap();
+            leaf.update(chunk).unwrap();
+            root.update(&leaf.finish().unwrap()).unwrap();
+        }
+        let expected = root.finish().unwrap();
+
+        let mut hasher = TreeHasher::new(MessageDigest::sha256());
+        hasher.chunk_size(1000);
+        for threads in &[1, 3, 4, 16] {
+            hasher.threads(*threads);
+            assert_eq!(*hasher.hash(&data).unwrap(), *expected);
+        }
+
+        let dir = tempdir::TempDir::new("tree_hash").unwrap();
+        let path = dir.path().join("data");
+        std::fs::write(&path, &data).unwrap();
+        assert_eq!(*hasher.hash_file(&path).unwrap(), *expected);
+    }
+
+    #[test]
+    fn test_tree_hash_empty() {
+        let leaf = hash(MessageDigest::sha256(), &[0]).unwrap();
+        let mut root = vec![1];
+        root.extend_from_slice(&leaf);
+        let expected = hash(MessageDigest::sha256(), &root).unwrap();
+
+        let mut hasher = TreeHasher::new(MessageDigest::sha256());
+        hasher.threads(4);
+        assert_eq!(*hasher.hash(b"").unwrap(), *expected);
+    }
 }
@@ -0,0 +1,120 @@
+//! Hybrid Public Key Encryption keys.
+//!
+//! HPKE, defined in [RFC 9180], combines a key encapsulation mechanism (KEM) with an AEAD to
+//! encrypt messages to a recipient's public key. It is used by Encrypted Client Hello.
+//!
+//! Requires BoringSSL.
+//!
+//! [RFC 9180]: https://www.rfc-editor.org/rfc/rfc9180
+use foreign_types::{ForeignType, ForeignTypeRef};
+use openssl_macros::corresponds;
+
+use crate::error::ErrorStack;
+use crate::{cvt, cvt_p};
+
+/// An HPKE key encapsulation mechanism.
+#[derive(Copy, Clone)]
+pub struct HpkeKem(*const ffi::EVP_HPKE_KEM);
+
+unsafe impl Sync for HpkeKem {}
+unsafe impl Send for HpkeKem {}
+
+impl HpkeKem {
+    /// DHKEM(X25519, HKDF-SHA256).
+    #[corresponds(EVP_hpke_x25519_hkdf_sha256)]
+    pub fn x25519_hkdf_sha256() -> HpkeKem {
+        unsafe { HpkeKem(ffi::EVP_hpke_x25519_hkdf_sha256()) }
+    }
+
+    /// Returns the IANA identifier of the KEM.
+    #[corresponds(EVP_HPKE_KEM_id)]
+    pub fn id(&self) -> u16 {
+        unsafe { ffi::EVP_HPKE_KEM_id(self.0) }
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_ptr(&self) -> *const ffi::EVP_HPKE_KEM {
+        self.0
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::EVP_HPKE_KEY;
+    fn drop = ffi::EVP_HPKE_KEY_free;
+
+    /// An HPKE private key, along with its public key.
+    pub struct HpkeKey;
+    /// Reference to `HpkeKey`.
+    pub struct HpkeKeyRef;
+}
+
+impl HpkeKey {
+    /// Generates a new key for `kem`.
+    #[corresponds(EVP_HPKE_KEY_generate)]
+    pub fn generate(kem: HpkeKem) -> Result<HpkeKey, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let key = cvt_p(ffi::EVP_HPKE_KEY_new()).map(HpkeKey)?;
+            cvt(ffi::EVP_HPKE_KEY_generate(key.as_ptr(), kem.as_ptr()))?;
+            Ok(key)
+        }
+    }
+
+    /// Loads a key for `kem` from its serialized private key.
+    #[corresponds(EVP_HPKE_KEY_init)]
+    pub fn from_private_key(kem: HpkeKem, private_key: &[u8]) -> Result<HpkeKey, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let key = cvt_p(ffi::EVP_HPKE_KEY_new()).map(HpkeKey)?;
+            cvt(ffi::EVP_HPKE_KEY_init(
+                key.as_ptr(),
+                kem.as_ptr(),
+                private_key.as_ptr(),
+                private_key.len(),
+            ))?;
+            Ok(key)
+        }
+    }
+}
+
+impl HpkeKeyRef {
+    /// Returns the key's KEM.
+    #[corresponds(EVP_HPKE_KEY_kem)]
+    pub fn kem(&self) -> HpkeKem {
+        unsafe { HpkeKem(ffi::EVP_HPKE_KEY_kem(self.as_ptr())) }
+    }
+
+    /// Returns the serialized public key.
+    #[corresponds(EVP_HPKE_KEY_public_key)]
+    pub fn public_key(&self) -> Result<Vec<u8>, ErrorStack> {
+        let mut buf = vec![0; ffi::EVP_HPKE_MAX_PUBLIC_KEY_LENGTH as usize];
+        let mut len = 0;
+        unsafe {
+            cvt(ffi::EVP_HPKE_KEY_public_key(
+                self.as_ptr(),
+                buf.as_mut_ptr(),
+                &mut len,
+                buf.len(),
+            ))?;
+        }
+        buf.truncate(len);
+        Ok(buf)
+    }
+
+    /// Returns the serialized private key.
+    #[corresponds(EVP_HPKE_KEY_private_key)]
+    pub fn private_key(&self) -> Result<Vec<u8>, ErrorStack> {
+        let mut buf = vec![0; ffi::EVP_HPKE_MAX_PRIVATE_KEY_LENGTH as usize];
+        let mut len = 0;
+        unsafe {
+            cvt(ffi::EVP_HPKE_KEY_private_key(
+                self.as_ptr(),
+                buf.as_mut_ptr(),
+                &mut len,
+                buf.len(),
+            ))?;
+        }
+        buf.truncate(len);
+        Ok(buf)
+    }
+}
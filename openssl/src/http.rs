@@ -0,0 +1,636 @@
+//! A minimal HTTP client.
+//!
+//! [`HttpClient`] wraps OpenSSL's built-in HTTP client, which is designed for the small
+//! request-response exchanges of PKI protocols such as OCSP, CRL retrieval, CMP and time-stamping
+//! rather than for general use. Responses are read into memory in full, and HTTPS is supported
+//! through an [`SslConnector`].
+//!
+//! Requires OpenSSL 3.0.0 or newer.
+//!
+//! # Examples
+//!
+//! Query an OCSP responder:
+//!
+//! ```no_run
+//! use openssl::http::HttpClient;
+//! use openssl::ocsp::{OcspRequest, OcspResponse};
+//! use std::time::Duration;
+//!
+//! # let request = OcspRequest::new().unwrap();
+//! let mut client = HttpClient::new();
+//! client.set_timeout(Duration::from_secs(10));
+//! client.set_expected_content_type("application/ocsp-response");
+//! let response = client
+//!     .post(
+//!         "http://ocsp.example.com/",
+//!         "application/ocsp-request",
+//!         &request.to_der().unwrap(),
+//!     )
+//!     .unwrap();
+//! let response = OcspResponse::from_der(&response).unwrap();
+//! ```
+use foreign_types::ForeignType;
+use libc::{c_int, c_void};
+use std::error;
+use std::ffi::CString;
+use std::fmt;
+use std::mem;
+use std::ptr;
+use std::time::Duration;
+
+use crate::asn1::Asn1Time;
+use crate::bio::MemBioSlice;
+use crate::error::ErrorStack;
+use crate::ssl::SslConnector;
+use crate::string::OpensslString;
+use crate::x509::{X509Crl, X509Ref};
+use crate::{cvt, cvt_p};
+use openssl_macros::corresponds;
+
+/// The default limit on the size of CRLs downloaded by [`HttpClient::get_crl`].
+const MAX_CRL_LEN: usize = 10 * 1024 * 1024;
+
+/// A client for simple HTTP and HTTPS exchanges.
+#[derive(Clone, Debug)]
+pub struct HttpClient {
+    proxy: Option<CString>,
+    no_proxy: Option<CString>,
+    headers: Vec<(CString, CString)>,
+    expected_content_type: Option<CString>,
+    expect_asn1: bool,
+    max_response_len: usize,
+    timeout: c_int,
+    connector: Option<SslConnector>,
+}
+
+impl Default for HttpClient {
+    fn default() -> HttpClient {
+        HttpClient::new()
+    }
+}
+
+impl HttpClient {
+    /// Creates a client with the default configuration.
+    ///
+    /// The proxy is taken from the `http_proxy` or `https_proxy` environment variables, requests
+    /// do not time out, and responses are limited to 100 KiB. HTTPS URLs can only be used once a
+    /// connector has been set with [`set_connector`].
+    ///
+    /// [`set_connector`]: HttpClient::set_connector
+    pub fn new() -> HttpClient {
+        HttpClient {
+            proxy: None,
+            no_proxy: None,
+            headers: vec![],
+            expected_content_type: None,
+            expect_asn1: false,
+            max_response_len: 0,
+            timeout: 0,
+            connector: None,
+        }
+    }
+
+    /// Sets the HTTP proxy, in the same format as the `http_proxy` environment variable.
+    ///
+    /// An empty string disables the use of a proxy.
+    pub fn set_proxy(&mut self, proxy: &str) {
+        self.proxy = Some(CString::new(proxy).unwrap());
+    }
+
+    /// Sets the hosts which are reached without the proxy, in the same format as the `no_proxy`
+    /// environment variable.
+    pub fn set_no_proxy(&mut self, names: &str) {
+        self.no_proxy = Some(CString::new(names).unwrap());
+    }
+
+    /// Adds a header to every request.
+    pub fn add_header(&mut self, name: &str, value: &str) {
+        self.headers
+            .push((CString::new(name).unwrap(), CString::new(value).unwrap()));
+    }
+
+    /// Sets the content type responses must have.
+    ///
+    /// Responses with a different content type are rejected.
+    pub fn set_expected_content_type(&mut self, content_type: &str) {
+        self.expected_content_type = Some(CString::new(content_type).unwrap());
+    }
+
+    /// Sets whether responses must be a single DER-encoded ASN.1 structure.
+    ///
+    /// Defaults to `false`.
+    pub fn set_expect_asn1(&mut self, expect_asn1: bool) {
+        self.expect_asn1 = expect_asn1;
+    }
+
+    /// Sets the maximum length of response bodies.
+    ///
+    /// Defaults to 100 KiB.
+    pub fn set_max_response_len(&mut self, len: usize) {
+        self.max_response_len = len;
+    }
+
+    /// Sets the time allowed for an entire exchange, including connecting to the server.
+    ///
+    /// The timeout is rounded up to whole seconds. By default, requests do not time out.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        let mut secs = timeout.as_secs();
+        if timeout.subsec_nanos() != 0 {
+            secs += 1;
+        }
+        self.timeout = secs.min(c_int::MAX as u64) as c_int;
+    }
+
+    /// Sets the connector used to establish TLS connections to HTTPS URLs.
+    ///
+    /// The certificate of the server is verified against the host name of the URL, as configured
+    /// in `connector`. As OpenSSL's HTTP client does not report the host of a redirect's target,
+    /// redirects are not followed once a connector has been set.
+    pub fn set_connector(&mut self, connector: SslConnector) {
+        self.connector = Some(connector);
+    }
+
+    /// Sends a GET request to `url`, returning the body of the response.
+    ///
+    /// Redirects are followed unless a connector has been set with [`set_connector`], in which
+    /// case they are reported as errors.
+    ///
+    /// [`set_connector`]: HttpClient::set_connector
+    #[corresponds(OSSL_HTTP_get)]
+    pub fn get(&self, url: &str) -> Result<Vec<u8>, ErrorStack> {
+        self.get_inner(url, self.max_response_len)
+    }
+
+    /// Downloads the CRL at `url`, checking that it was issued and signed by `issuer` and that it
+    /// is currently valid.
+    ///
+    /// Both DER and PEM encoded CRLs are accepted. As CRLs are often larger than the default limit
+    /// on responses, CRLs of up to 10 MiB are accepted unless a limit has been set with
+    /// [`set_max_response_len`].
+    ///
+    /// [`set_max_response_len`]: HttpClient::set_max_response_len
+    pub fn get_crl(&self, url: &str, issuer: &X509Ref) -> Result<X509Crl, CrlError> {
+        let max_response_len = if self.max_response_len == 0 {
+            MAX_CRL_LEN
+        } else {
+            self.max_response_len
+        };
+        let response = self.get_inner(url, max_response_len)?;
+        let crl = match X509Crl::from_der(&response) {
+            Ok(crl) => crl,
+            Err(_) => X509Crl::from_pem(&response)?,
+        };
+
+        if crl.issuer_name().to_der()? != issuer.subject_name().to_der()? {
+            return Err(CrlError::WrongIssuer);
+        }
+        if !crl.verify(&*issuer.public_key()?)? {
+            return Err(CrlError::InvalidSignature);
+        }
+        let now = Asn1Time::days_from_now(0)?;
+        if crl.last_update() > now {
+            return Err(CrlError::NotYetValid);
+        }
+        match crl.next_update() {
+            Some(next_update) if next_update < now => Err(CrlError::Expired),
+            _ => Ok(crl),
+        }
+    }
+
+    fn get_inner(&self, url: &str, max_response_len: usize) -> Result<Vec<u8>, ErrorStack> {
+        // the TLS callback checks the server against the host of the original URL, which would be
+        // wrong after a redirect, so a single exchange is made instead
+        if self.connector.is_some() {
+            return self.transfer(url, None, max_response_len);
+        }
+
+        let url = CString::new(url).unwrap();
+        let headers = Headers::new(&self.headers)?;
+
+        unsafe {
+            let response = cvt_p(ffi::OSSL_HTTP_get(
+                url.as_ptr(),
+                opt_ptr(&self.proxy),
+                opt_ptr(&self.no_proxy),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                None,
+                ptr::null_mut(),
+                0,
+                headers.0,
+                opt_ptr(&self.expected_content_type),
+                self.expect_asn1 as c_int,
+                max_response_len,
+                self.timeout,
+            ))?;
+            read_response(response)
+        }
+    }
+
+    /// Sends a POST request with a body of type `content_type` to `url`, returning the body of
+    /// the response.
+    ///
+    /// Redirects are reported as errors.
+    #[corresponds(OSSL_HTTP_transfer)]
+    pub fn post(&self, url: &str, content_type: &str, body: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let content_type = CString::new(content_type).unwrap();
+        let body = MemBioSlice::new(body)?;
+        self.transfer(url, Some((&content_type, &body)), self.max_response_len)
+    }
+
+    /// Makes a single exchange with the server, sending a POST request if `request` holds a
+    /// content type and body and a GET request otherwise.
+    fn transfer(
+        &self,
+        url: &str,
+        request: Option<(&CString, &MemBioSlice<'_>)>,
+        max_response_len: usize,
+    ) -> Result<Vec<u8>, ErrorStack> {
+        let parsed = Url::parse(url)?;
+        let headers = Headers::new(&self.headers)?;
+        let tls = self.tls(&parsed);
+        let (callback, arg) = tls_callback_args(&tls);
+        let (content_type, body) = match request {
+            Some((content_type, body)) => (content_type.as_ptr(), body.as_ptr()),
+            None => (ptr::null(), ptr::null_mut()),
+        };
+
+        unsafe {
+            let response = cvt_p(ffi::OSSL_HTTP_transfer(
+                ptr::null_mut(),
+                parsed.host.as_ptr(),
+                parsed.port.as_ptr(),
+                parsed.path.as_ptr(),
+                parsed.tls as c_int,
+                opt_ptr(&self.proxy),
+                opt_ptr(&self.no_proxy),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                callback,
+                arg,
+                0,
+                headers.0,
+                content_type,
+                body,
+                opt_ptr(&self.expected_content_type),
+                self.expect_asn1 as c_int,
+                max_response_len,
+                self.timeout,
+                0,
+            ))?;
+            read_response(response)
+        }
+    }
+
+    fn tls(&self, url: &Url) -> Option<Tls<'_>> {
+        self.connector.as_ref().map(|connector| Tls {
+            connector,
+            host: url.host.to_string(),
+        })
+    }
+}
+
+/// An error returned by [`HttpClient::get_crl`].
+#[derive(Debug)]
+pub enum CrlError {
+    /// The CRL could not be downloaded or decoded.
+    Fetch(ErrorStack),
+    /// The CRL was issued by a different CA.
+    WrongIssuer,
+    /// The signature of the CRL is invalid.
+    InvalidSignature,
+    /// The CRL was issued in the future.
+    NotYetValid,
+    /// The CRL is past its next update time.
+    Expired,
+}
+
+impl fmt::Display for CrlError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrlError::Fetch(e) => write!(fmt, "error fetching CRL: {}", e),
+            CrlError::WrongIssuer => fmt.write_str("CRL was issued by a different CA"),
+            CrlError::InvalidSignature => fmt.write_str("CRL signature is invalid"),
+            CrlError::NotYetValid => fmt.write_str("CRL is not yet valid"),
+            CrlError::Expired => fmt.write_str("CRL has expired"),
+        }
+    }
+}
+
+impl error::Error for CrlError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CrlError::Fetch(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorStack> for CrlError {
+    fn from(e: ErrorStack) -> CrlError {
+        CrlError::Fetch(e)
+    }
+}
+
+/// The components of a URL needed to make a request.
+struct Url {
+    tls: bool,
+    host: OpensslString,
+    port: OpensslString,
+    path: OpensslString,
+}
+
+impl Url {
+    fn parse(url: &str) -> Result<Url, ErrorStack> {
+        let url = CString::new(url).unwrap();
+        unsafe {
+            let mut tls = 0;
+            let mut host = ptr::null_mut();
+            let mut port = ptr::null_mut();
+            let mut path = ptr::null_mut();
+            cvt(ffi::OSSL_HTTP_parse_url(
+                url.as_ptr(),
+                &mut tls,
+                ptr::null_mut(),
+                &mut host,
+                &mut port,
+                ptr::null_mut(),
+                &mut path,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            ))?;
+            Ok(Url {
+                tls: tls != 0,
+                host: OpensslString::from_ptr(host),
+                port: OpensslString::from_ptr(port),
+                path: OpensslString::from_ptr(path),
+            })
+        }
+    }
+}
+
+/// A stack of request headers.
+struct Headers(*mut ffi::stack_st_CONF_VALUE);
+
+impl Headers {
+    fn new(headers: &[(CString, CString)]) -> Result<Headers, ErrorStack> {
+        let mut stack = Headers(ptr::null_mut());
+        for (name, value) in headers {
+            unsafe {
+                cvt(ffi::X509V3_add_value(
+                    name.as_ptr(),
+                    value.as_ptr(),
+                    &mut stack.0,
+                ))?;
+            }
+        }
+        Ok(stack)
+    }
+}
+
+impl Drop for Headers {
+    fn drop(&mut self) {
+        unsafe extern "C" fn free(value: *mut c_void) {
+            ffi::X509V3_conf_free(value as *mut _);
+        }
+
+        if !self.0.is_null() {
+            unsafe { ffi::OPENSSL_sk_pop_free(self.0 as *mut _, Some(free)) }
+        }
+    }
+}
+
+struct Tls<'a> {
+    connector: &'a SslConnector,
+    host: String,
+}
+
+fn tls_callback_args(tls: &Option<Tls<'_>>) -> (ffi::OSSL_HTTP_bio_cb_t, *mut c_void) {
+    match tls {
+        Some(tls) => (Some(tls_callback), tls as *const Tls<'_> as *mut c_void),
+        None => (None, ptr::null_mut()),
+    }
+}
+
+unsafe extern "C" fn tls_callback(
+    bio: *mut ffi::BIO,
+    arg: *mut c_void,
+    connect: c_int,
+    detail: c_int,
+) -> *mut ffi::BIO {
+    // detail is set when connecting to an HTTPS URL
+    if connect == 0 || detail == 0 {
+        return bio;
+    }
+
+    let tls = &*(arg as *const Tls<'_>);
+    let ssl = match tls
+        .connector
+        .configure()
+        .and_then(|config| config.into_ssl(&tls.host))
+    {
+        Ok(ssl) => ssl,
+        Err(e) => {
+            e.put();
+            return ptr::null_mut();
+        }
+    };
+
+    let ssl_bio = ffi::BIO_new(ffi::BIO_f_ssl());
+    if ssl_bio.is_null() {
+        return ptr::null_mut();
+    }
+    ffi::SSL_set_connect_state(ssl.as_ptr());
+    // the BIO takes ownership of the SSL
+    let ssl_ptr = ssl.as_ptr();
+    mem::forget(ssl);
+    ffi::BIO_set_ssl(ssl_bio, ssl_ptr, ffi::BIO_CLOSE);
+    ffi::BIO_push(ssl_bio, bio)
+}
+
+/// Reads a response BIO to its end, freeing it.
+///
+/// Unless an ASN.1 response is expected, OpenSSL returns a BIO streaming the body from the
+/// connection rather than a memory BIO holding all of it.
+unsafe fn read_response(bio: *mut ffi::BIO) -> Result<Vec<u8>, ErrorStack> {
+    let mut body = vec![];
+    let mut buf = [0; 4096];
+    let result = loop {
+        let n = ffi::BIO_read(bio, buf.as_mut_ptr() as *mut c_void, buf.len() as c_int);
+        if n > 0 {
+            body.extend_from_slice(&buf[..n as usize]);
+            continue;
+        }
+        // an exhausted memory BIO reports -1 without an error
+        let errors = ErrorStack::get();
+        if n < 0 && !errors.errors().is_empty() {
+            break Err(errors);
+        }
+        break Ok(body);
+    };
+    ffi::BIO_free_all(bio);
+    result
+}
+
+fn opt_ptr(value: &Option<CString>) -> *const libc::c_char {
+    value.as_ref().map_or(ptr::null(), |value| value.as_ptr())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::asn1::Asn1TimeRef;
+    use crate::hash::MessageDigest;
+    use crate::pkey::PKey;
+    use crate::ssl::SslMethod;
+    use crate::x509::{X509CrlBuilder, X509};
+
+    /// Serves a single request, returning its body.
+    fn serve(response: Vec<u8>) -> (String, thread::JoinHandle<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/path", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let stream = listener.accept().unwrap().0;
+            let mut reader = BufReader::new(&stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(len) = line.strip_prefix("content-length:") {
+                    content_length = len.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            (&stream).write_all(&response).unwrap();
+            body
+        });
+        (url, handle)
+    }
+
+    fn client() -> HttpClient {
+        let mut client = HttpClient::new();
+        client.set_proxy("");
+        client.set_timeout(Duration::from_secs(10));
+        client
+    }
+
+    #[test]
+    fn get() {
+        let (url, server) = serve(
+            b"HTTP/1.0 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\nhello"
+                .to_vec(),
+        );
+        let mut client = client();
+        client.add_header("X-Test", "1");
+        client.set_expected_content_type("text/plain");
+        assert_eq!(client.get(&url).unwrap(), b"hello");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn post() {
+        let (url, server) = serve(
+            b"HTTP/1.0 200 OK\r\nContent-Type: application/octet-stream\r\n\
+              Content-Length: 3\r\n\r\nbye"
+                .to_vec(),
+        );
+        let response = client()
+            .post(&url, "application/octet-stream", b"request")
+            .unwrap();
+        assert_eq!(response, b"bye");
+        assert_eq!(server.join().unwrap(), b"request");
+    }
+
+    #[test]
+    fn unexpected_content_type() {
+        let (url, server) = serve(
+            b"HTTP/1.0 200 OK\r\nContent-Type: text/html\r\nContent-Length: 2\r\n\r\nhi".to_vec(),
+        );
+        let mut client = client();
+        client.set_expected_content_type("application/ocsp-response");
+        assert!(client.get(&url).is_err());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn redirect() {
+        let (target, target_server) = serve(
+            b"HTTP/1.0 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\nhello"
+                .to_vec(),
+        );
+        let redirect = format!(
+            "HTTP/1.0 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\n\r\n",
+            target
+        );
+        let (url, server) = serve(redirect.clone().into_bytes());
+        assert_eq!(client().get(&url).unwrap(), b"hello");
+        server.join().unwrap();
+        target_server.join().unwrap();
+
+        // with a connector set, the redirect is rejected rather than followed
+        let (url, server) = serve(redirect.into_bytes());
+        let mut client = client();
+        client.set_connector(SslConnector::builder(SslMethod::tls()).unwrap().build());
+        assert!(client.get(&url).is_err());
+        server.join().unwrap();
+    }
+
+    fn crl_response(issuer: &X509Ref, key: &[u8], next_update: &Asn1TimeRef) -> Vec<u8> {
+        let key = PKey::private_key_from_pem(key).unwrap();
+        let mut builder = X509CrlBuilder::new().unwrap();
+        builder.set_version(1).unwrap();
+        builder.set_issuer_name(issuer.subject_name()).unwrap();
+        builder
+            .set_last_update(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder.set_next_update(next_update).unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        let crl = builder.build().to_der().unwrap();
+
+        let mut response = format!(
+            "HTTP/1.0 200 OK\r\nContent-Type: application/pkix-crl\r\nContent-Length: {}\r\n\r\n",
+            crl.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(&crl);
+        response
+    }
+
+    #[test]
+    fn get_crl() {
+        let ca = X509::from_pem(include_bytes!("../test/root-ca.pem")).unwrap();
+        let ca_key = include_bytes!("../test/root-ca.key");
+        let next_update = Asn1Time::days_from_now(7).unwrap();
+
+        let (url, server) = serve(crl_response(&ca, ca_key, &next_update));
+        let crl = client().get_crl(&url, &ca).unwrap();
+        assert!(crl.next_update().unwrap() == next_update);
+        server.join().unwrap();
+
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let (url, server) = serve(crl_response(&ca, ca_key, &next_update));
+        match client().get_crl(&url, &cert) {
+            Err(CrlError::WrongIssuer) => {}
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("unexpected success"),
+        }
+        server.join().unwrap();
+
+        let (url, server) = serve(crl_response(&ca, ca_key, &Asn1Time::from_unix(0).unwrap()));
+        match client().get_crl(&url, &ca) {
+            Err(CrlError::Expired) => {}
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("unexpected success"),
+        }
+        server.join().unwrap();
+    }
+}
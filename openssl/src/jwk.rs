@@ -0,0 +1,681 @@
+//! JSON Web Key conversions.
+//!
+//! A JSON Web Key ([RFC 7517]) represents a public or private key as a JSON object whose members
+//! hold the key's parameters as unpadded base64url strings. This module converts [`PKey`],
+//! [`Rsa`] and [`EcKey`] values to and from that representation for the key types defined in
+//! [RFC 7518] and [RFC 8037]:
+//!
+//! * `RSA` keys.
+//! * `EC` keys on the `P-256`, `P-384` and `P-521` curves.
+//! * `OKP` keys of type `Ed25519` and `X25519`. These require OpenSSL 1.1.1 or newer.
+//!
+//! Members which don't describe the key material itself, such as `kid`, `use` or `alg`, are
+//! ignored when importing a key and are not emitted when exporting one.
+//!
+//! Requires the `jwk` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::ec::{EcGroup, EcKey};
+//! use openssl::nid::Nid;
+//! use openssl::pkey::PKey;
+//!
+//! let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+//! let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+//!
+//! let jwk = key.public_key_to_jwk().unwrap();
+//! let public_key = PKey::public_key_from_jwk(&jwk).unwrap();
+//! assert!(key.public_eq(&public_key));
+//! ```
+//!
+//! [RFC 7517]: https://www.rfc-editor.org/rfc/rfc7517
+//! [RFC 7518]: https://www.rfc-editor.org/rfc/rfc7518
+//! [RFC 8037]: https://www.rfc-editor.org/rfc/rfc8037
+use serde_json::{Map, Value};
+use std::error;
+use std::fmt;
+
+use crate::base64;
+use crate::bn::{BigNum, BigNumContext, BigNumRef};
+use crate::ec::{EcGroup, EcGroupRef, EcKey, EcKeyRef};
+use crate::error::ErrorStack;
+use crate::nid::Nid;
+use crate::pkey::{HasPrivate, HasPublic, Id, PKey, PKeyRef, Private, Public};
+use crate::rsa::{Rsa, RsaPrivateKeyBuilder, RsaRef};
+
+/// An error converting a key to or from a JWK.
+#[derive(Debug)]
+pub enum Error {
+    /// The JWK is not a JSON object, or a member is missing or malformed.
+    Invalid(String),
+    /// The key type or curve is not supported.
+    Unsupported(String),
+    /// An error reported by OpenSSL.
+    Ssl(ErrorStack),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Invalid(msg) => write!(fmt, "invalid JWK: {}", msg),
+            Error::Unsupported(msg) => write!(fmt, "unsupported JWK: {}", msg),
+            Error::Ssl(e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Ssl(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorStack> for Error {
+    fn from(e: ErrorStack) -> Error {
+        Error::Ssl(e)
+    }
+}
+
+impl PKey<Public> {
+    /// Decodes the public key of a JWK.
+    ///
+    /// Private key members are ignored if present.
+    pub fn public_key_from_jwk(jwk: &str) -> Result<PKey<Public>, Error> {
+        let jwk = Members::parse(jwk)?;
+        match jwk.required("kty")? {
+            "RSA" => Ok(PKey::from_rsa(rsa_public_key(&jwk)?)?),
+            "EC" => Ok(PKey::from_ec_key(ec_public_key(&jwk)?)?),
+            "OKP" => okp_public_key(&jwk),
+            kty => Err(Error::Unsupported(format!("key type `{}`", kty))),
+        }
+    }
+}
+
+impl PKey<Private> {
+    /// Decodes the private key of a JWK.
+    pub fn private_key_from_jwk(jwk: &str) -> Result<PKey<Private>, Error> {
+        let jwk = Members::parse(jwk)?;
+        match jwk.required("kty")? {
+            "RSA" => Ok(PKey::from_rsa(rsa_private_key(&jwk)?)?),
+            "EC" => Ok(PKey::from_ec_key(ec_private_key(&jwk)?)?),
+            "OKP" => okp_private_key(&jwk),
+            kty => Err(Error::Unsupported(format!("key type `{}`", kty))),
+        }
+    }
+}
+
+impl<T> PKeyRef<T>
+where
+    T: HasPublic,
+{
+    /// Serializes the public key to a JWK.
+    pub fn public_key_to_jwk(&self) -> Result<String, Error> {
+        let mut jwk = JwkWriter::new();
+        match self.id() {
+            Id::RSA => write_rsa_public_key(&mut jwk, &*self.rsa()?),
+            Id::EC => write_ec_public_key(&mut jwk, &*self.ec_key()?)?,
+            id => write_okp_public_key(&mut jwk, self, id)?,
+        }
+        Ok(jwk.finish())
+    }
+}
+
+impl<T> PKeyRef<T>
+where
+    T: HasPrivate,
+{
+    /// Serializes the private key to a JWK.
+    ///
+    /// The JWK includes the public key members as well.
+    pub fn private_key_to_jwk(&self) -> Result<String, Error> {
+        let mut jwk = JwkWriter::new();
+        match self.id() {
+            Id::RSA => write_rsa_private_key(&mut jwk, &*self.rsa()?),
+            Id::EC => write_ec_private_key(&mut jwk, &*self.ec_key()?)?,
+            id => write_okp_private_key(&mut jwk, self, id)?,
+        }
+        Ok(jwk.finish())
+    }
+}
+
+impl Rsa<Public> {
+    /// Decodes the public key of an `RSA` JWK.
+    pub fn public_key_from_jwk(jwk: &str) -> Result<Rsa<Public>, Error> {
+        let jwk = Members::parse(jwk)?;
+        jwk.expect_kty("RSA")?;
+        rsa_public_key(&jwk)
+    }
+}
+
+impl Rsa<Private> {
+    /// Decodes the private key of an `RSA` JWK.
+    pub fn private_key_from_jwk(jwk: &str) -> Result<Rsa<Private>, Error> {
+        let jwk = Members::parse(jwk)?;
+        jwk.expect_kty("RSA")?;
+        rsa_private_key(&jwk)
+    }
+}
+
+impl<T> RsaRef<T>
+where
+    T: HasPublic,
+{
+    /// Serializes the public key to an `RSA` JWK.
+    pub fn public_key_to_jwk(&self) -> Result<String, Error> {
+        let mut jwk = JwkWriter::new();
+        write_rsa_public_key(&mut jwk, self);
+        Ok(jwk.finish())
+    }
+}
+
+impl<T> RsaRef<T>
+where
+    T: HasPrivate,
+{
+    /// Serializes the private key to an `RSA` JWK.
+    pub fn private_key_to_jwk(&self) -> Result<String, Error> {
+        let mut jwk = JwkWriter::new();
+        write_rsa_private_key(&mut jwk, self);
+        Ok(jwk.finish())
+    }
+}
+
+impl EcKey<Public> {
+    /// Decodes the public key of an `EC` JWK.
+    pub fn public_key_from_jwk(jwk: &str) -> Result<EcKey<Public>, Error> {
+        let jwk = Members::parse(jwk)?;
+        jwk.expect_kty("EC")?;
+        ec_public_key(&jwk)
+    }
+}
+
+impl EcKey<Private> {
+    /// Decodes the private key of an `EC` JWK.
+    pub fn private_key_from_jwk(jwk: &str) -> Result<EcKey<Private>, Error> {
+        let jwk = Members::parse(jwk)?;
+        jwk.expect_kty("EC")?;
+        ec_private_key(&jwk)
+    }
+}
+
+impl<T> EcKeyRef<T>
+where
+    T: HasPublic,
+{
+    /// Serializes the public key to an `EC` JWK.
+    pub fn public_key_to_jwk(&self) -> Result<String, Error> {
+        let mut jwk = JwkWriter::new();
+        write_ec_public_key(&mut jwk, self)?;
+        Ok(jwk.finish())
+    }
+}
+
+impl<T> EcKeyRef<T>
+where
+    T: HasPrivate,
+{
+    /// Serializes the private key to an `EC` JWK.
+    pub fn private_key_to_jwk(&self) -> Result<String, Error> {
+        let mut jwk = JwkWriter::new();
+        write_ec_private_key(&mut jwk, self)?;
+        Ok(jwk.finish())
+    }
+}
+
+fn rsa_public_key(jwk: &Members) -> Result<Rsa<Public>, Error> {
+    let n = jwk.bn("n")?;
+    let e = jwk.bn("e")?;
+    Ok(Rsa::from_public_components(n, e)?)
+}
+
+fn rsa_private_key(jwk: &Members) -> Result<Rsa<Private>, Error> {
+    if jwk.contains("oth") {
+        return Err(Error::Unsupported("multi-prime RSA key".to_string()));
+    }
+
+    let builder = RsaPrivateKeyBuilder::new(jwk.bn("n")?, jwk.bn("e")?, jwk.bn("d")?)?;
+    let crt = ["p", "q", "dp", "dq", "qi"];
+    let builder = if crt.iter().all(|name| !jwk.contains(name)) {
+        builder
+    } else {
+        builder
+            .set_factors(jwk.bn("p")?, jwk.bn("q")?)?
+            .set_crt_params(jwk.bn("dp")?, jwk.bn("dq")?, jwk.bn("qi")?)?
+    };
+    Ok(builder.build())
+}
+
+fn write_rsa_public_key<T>(jwk: &mut JwkWriter, rsa: &RsaRef<T>)
+where
+    T: HasPublic,
+{
+    jwk.string("kty", "RSA");
+    jwk.bytes("n", &rsa.n().to_vec());
+    jwk.bytes("e", &rsa.e().to_vec());
+}
+
+fn write_rsa_private_key<T>(jwk: &mut JwkWriter, rsa: &RsaRef<T>)
+where
+    T: HasPrivate,
+{
+    jwk.string("kty", "RSA");
+    jwk.bytes("n", &rsa.n().to_vec());
+    jwk.bytes("e", &rsa.e().to_vec());
+    jwk.bytes("d", &rsa.d().to_vec());
+    if let (Some(p), Some(q), Some(dp), Some(dq), Some(qi)) =
+        (rsa.p(), rsa.q(), rsa.dmp1(), rsa.dmq1(), rsa.iqmp())
+    {
+        jwk.bytes("p", &p.to_vec());
+        jwk.bytes("q", &q.to_vec());
+        jwk.bytes("dp", &dp.to_vec());
+        jwk.bytes("dq", &dq.to_vec());
+        jwk.bytes("qi", &qi.to_vec());
+    }
+}
+
+const CURVES: [(&str, Nid); 3] = [
+    ("P-256", Nid::X9_62_PRIME256V1),
+    ("P-384", Nid::SECP384R1),
+    ("P-521", Nid::SECP521R1),
+];
+
+fn ec_group(jwk: &Members) -> Result<EcGroup, Error> {
+    let crv = jwk.required("crv")?;
+    match CURVES.iter().find(|(name, _)| *name == crv) {
+        Some((_, nid)) => Ok(EcGroup::from_curve_name(*nid)?),
+        None => Err(Error::Unsupported(format!("curve `{}`", crv))),
+    }
+}
+
+/// Returns the length in bytes of the coordinates of points on `group`.
+fn field_len(group: &EcGroupRef) -> usize {
+    (group.degree() as usize + 7) >> 3
+}
+
+fn ec_public_key(jwk: &Members) -> Result<EcKey<Public>, Error> {
+    let group = ec_group(jwk)?;
+    let len = field_len(&group);
+    let x = jwk.fixed_bn("x", len)?;
+    let y = jwk.fixed_bn("y", len)?;
+    Ok(EcKey::from_public_key_affine_coordinates(&group, &x, &y)?)
+}
+
+fn ec_private_key(jwk: &Members) -> Result<EcKey<Private>, Error> {
+    let public_key = ec_public_key(jwk)?;
+    let d = jwk.fixed_bn("d", field_len(public_key.group()))?;
+    let key = EcKey::from_private_components(public_key.group(), &d, public_key.public_key())?;
+    key.check_key()?;
+    Ok(key)
+}
+
+fn write_ec_public_key<T>(jwk: &mut JwkWriter, key: &EcKeyRef<T>) -> Result<(), Error>
+where
+    T: HasPublic,
+{
+    let group = key.group();
+    let crv = match CURVES
+        .iter()
+        .find(|(_, nid)| group.curve_name() == Some(*nid))
+    {
+        Some((crv, _)) => crv,
+        None => return Err(Error::Unsupported("curve".to_string())),
+    };
+
+    let mut ctx = BigNumContext::new()?;
+    let mut x = BigNum::new()?;
+    let mut y = BigNum::new()?;
+    key.public_key()
+        .affine_coordinates_gfp(group, &mut x, &mut y, &mut ctx)?;
+
+    let len = field_len(group);
+    jwk.string("kty", "EC");
+    jwk.string("crv", crv);
+    jwk.bytes("x", &padded(&x, len));
+    jwk.bytes("y", &padded(&y, len));
+    Ok(())
+}
+
+fn write_ec_private_key<T>(jwk: &mut JwkWriter, key: &EcKeyRef<T>) -> Result<(), Error>
+where
+    T: HasPrivate,
+{
+    write_ec_public_key(jwk, key)?;
+    jwk.bytes("d", &padded(key.private_key(), field_len(key.group())));
+    Ok(())
+}
+
+/// Returns the big-endian representation of `n`, zero-padded to `len` bytes.
+fn padded(n: &BigNumRef, len: usize) -> Vec<u8> {
+    let bytes = n.to_vec();
+    let mut out = vec![0; len.saturating_sub(bytes.len())];
+    out.extend_from_slice(&bytes);
+    out
+}
+
+#[cfg(ossl111)]
+const OKP_CURVES: [(&str, Id); 2] = [("Ed25519", Id::ED25519), ("X25519", Id::X25519)];
+
+#[cfg(ossl111)]
+fn okp_id(jwk: &Members) -> Result<Id, Error> {
+    let crv = jwk.required("crv")?;
+    match OKP_CURVES.iter().find(|(name, _)| *name == crv) {
+        Some((_, id)) => Ok(*id),
+        None => Err(Error::Unsupported(format!("curve `{}`", crv))),
+    }
+}
+
+#[cfg(ossl111)]
+fn okp_public_key(jwk: &Members) -> Result<PKey<Public>, Error> {
+    let id = okp_id(jwk)?;
+    Ok(PKey::public_key_from_raw_bytes(&jwk.bytes("x")?, id)?)
+}
+
+#[cfg(not(ossl111))]
+fn okp_public_key(_: &Members) -> Result<PKey<Public>, Error> {
+    Err(Error::Unsupported("key type `OKP`".to_string()))
+}
+
+#[cfg(ossl111)]
+fn okp_private_key(jwk: &Members) -> Result<PKey<Private>, Error> {
+    let id = okp_id(jwk)?;
+    let key = PKey::private_key_from_raw_bytes(&jwk.bytes("d")?, id)?;
+    if key.raw_public_key()? != jwk.bytes("x")? {
+        return Err(Error::Invalid("`x` does not match `d`".to_string()));
+    }
+    Ok(key)
+}
+
+#[cfg(not(ossl111))]
+fn okp_private_key(_: &Members) -> Result<PKey<Private>, Error> {
+    Err(Error::Unsupported("key type `OKP`".to_string()))
+}
+
+#[cfg(ossl111)]
+fn write_okp_public_key<T>(jwk: &mut JwkWriter, key: &PKeyRef<T>, id: Id) -> Result<(), Error>
+where
+    T: HasPublic,
+{
+    let crv = match OKP_CURVES.iter().find(|(_, i)| *i == id) {
+        Some((crv, _)) => crv,
+        None => return Err(Error::Unsupported("key type".to_string())),
+    };
+    jwk.string("kty", "OKP");
+    jwk.string("crv", crv);
+    jwk.bytes("x", &key.raw_public_key()?);
+    Ok(())
+}
+
+#[cfg(not(ossl111))]
+fn write_okp_public_key<T>(_: &mut JwkWriter, _: &PKeyRef<T>, _: Id) -> Result<(), Error>
+where
+    T: HasPublic,
+{
+    Err(Error::Unsupported("key type".to_string()))
+}
+
+#[cfg(ossl111)]
+fn write_okp_private_key<T>(jwk: &mut JwkWriter, key: &PKeyRef<T>, id: Id) -> Result<(), Error>
+where
+    T: HasPrivate,
+{
+    write_okp_public_key(jwk, key, id)?;
+    jwk.bytes("d", &key.raw_private_key()?);
+    Ok(())
+}
+
+#[cfg(not(ossl111))]
+fn write_okp_private_key<T>(_: &mut JwkWriter, _: &PKeyRef<T>, _: Id) -> Result<(), Error>
+where
+    T: HasPrivate,
+{
+    Err(Error::Unsupported("key type".to_string()))
+}
+
+/// Accumulates the members of a JWK. They are serialized in lexicographic order.
+struct JwkWriter(Map<String, Value>);
+
+impl JwkWriter {
+    fn new() -> JwkWriter {
+        JwkWriter(Map::new())
+    }
+
+    fn string(&mut self, name: &str, value: &str) {
+        self.0
+            .insert(name.to_string(), Value::String(value.to_string()));
+    }
+
+    fn bytes(&mut self, name: &str, value: &[u8]) {
+        self.string(name, &encode_base64url(value));
+    }
+
+    fn finish(self) -> String {
+        Value::Object(self.0).to_string()
+    }
+}
+
+/// The members of a JWK.
+struct Members(Map<String, Value>);
+
+impl Members {
+    fn parse(json: &str) -> Result<Members, Error> {
+        match serde_json::from_str(json) {
+            Ok(Value::Object(members)) => Ok(Members(members)),
+            Ok(_) => Err(Error::Invalid("not a JSON object".to_string())),
+            Err(e) => Err(Error::Invalid(e.to_string())),
+        }
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    fn required(&self, name: &str) -> Result<&str, Error> {
+        match self.0.get(name) {
+            Some(Value::String(value)) => Ok(value),
+            Some(_) => Err(Error::Invalid(format!("member `{}` is not a string", name))),
+            None => Err(Error::Invalid(format!("missing member `{}`", name))),
+        }
+    }
+
+    fn expect_kty(&self, kty: &str) -> Result<(), Error> {
+        match self.required("kty")? {
+            k if k == kty => Ok(()),
+            k => Err(Error::Invalid(format!(
+                "expected key type `{}`, found `{}`",
+                kty, k
+            ))),
+        }
+    }
+
+    fn bytes(&self, name: &str) -> Result<Vec<u8>, Error> {
+        decode_base64url(self.required(name)?)
+            .ok_or_else(|| Error::Invalid(format!("member `{}` is not base64url", name)))
+    }
+
+    fn bn(&self, name: &str) -> Result<BigNum, Error> {
+        Ok(BigNum::from_slice(&self.bytes(name)?)?)
+    }
+
+    /// Decodes an integer which must be encoded in exactly `len` bytes.
+    fn fixed_bn(&self, name: &str, len: usize) -> Result<BigNum, Error> {
+        let bytes = self.bytes(name)?;
+        if bytes.len() != len {
+            return Err(Error::Invalid(format!(
+                "member `{}` has the wrong length",
+                name
+            )));
+        }
+        Ok(BigNum::from_slice(&bytes)?)
+    }
+}
+
+fn encode_base64url(bytes: &[u8]) -> String {
+    base64::encode_block(bytes)
+        .trim_end_matches('=')
+        .chars()
+        .map(|c| match c {
+            '+' => '-',
+            '/' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+fn decode_base64url(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 4 == 1 {
+        return None;
+    }
+    let mut standard = String::with_capacity(s.len() + 3);
+    for c in s.chars() {
+        standard.push(match c {
+            '-' => '+',
+            '_' => '/',
+            'A'..='Z' | 'a'..='z' | '0'..='9' => c,
+            _ => return None,
+        });
+    }
+    for _ in 0..(4 - s.len() % 4) % 4 {
+        standard.push('=');
+    }
+    base64::decode_block(&standard).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // RFC 7517 appendix A.2
+    const RSA_JWK: &str = r#"{"kty":"RSA",
+        "n":"0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+        "e":"AQAB",
+        "d":"X4cTteJY_gn4FYPsXB8rdXix5vwsg1FLN5E3EaG6RJoVH-HLLKD9M7dx5oo7GURknchnrRweUkC7hT5fJLM0WbFAKNLWY2vv7B6NqXSzUvxT0_YSfqijwp3RTzlBaCxWp4doFk5N2o8Gy_nHNKroADIkJ46pRUohsXywbReAdYaMwFs9tv8d_cPVY3i07a3t8MN6TNwm0dSawm9v47UiCl3Sk5ZiG7xojPLu4sbg1U2jx4IBTNBznbJSzFHK66jT8bgkuqsk0GjskDJk19Z4qwjwbsnn4j2WBii3RL-Us2lGVkY8fkFzme1z0HbIkfz0Y6mqnOYtqc0X4jfcKoAC8Q",
+        "p":"83i-7IvMGXoMXCskv73TKr8637FiO7Z27zv8oj6pbWUQyLPQBQxtPVnwD20R-60eTDmD2ujnMt5PoqMrm8RfmNhVWDtjjMmCMjOpSXicFHj7XOuVIYQyqVWlWEh6dN36GVZYk93N8Bc9vY41xy8B9RzzOGVQzXvNEvn7O0nVbfs",
+        "q":"3dfOR9cuYq-0S-mkFLzgItgMEfFzB2q3hWehMuG0oCuqnb3vobLyumqjVZQO1dIrdwgTnCdpYzBcOfW5r370AFXjiWft_NGEiovonizhKpo9VVS78TzFgxkIdrecRezsZ-1kYd_s1qDbxtkDEgfAITAG9LUnADun4vIcb6yelxk",
+        "dp":"G4sPXkc6Ya9y8oJW9_ILj4xuppu0lzi_H7VTkS8xj5SdX3coE0oimYwxIi2emTAue0UOa5dpgFGyBJ4c8tQ2VF402XRugKDTP8akYhFo5tAA77Qe_NmtuYZc3C3m3I24G2GvR5sSDxUyAN2zq8Lfn9EUms6rY3Ob8YeiKkTiBj0",
+        "dq":"s9lAH9fggBsoFR8Oac2R_E2gw282rT2kGOAhvIllETE1efrA6huUUvMfBcMpn8lqeW6vzznYY5SSQF7pMdC_agI3nG8Ibp1BUb0JUiraRNqUfLhcQb_d9GF4Dh7e74WbRsobRonujTYN1xCaP6TO61jvWrX-L18txXw494Q_cgk",
+        "qi":"GyM_p6JrXySiz1toFgKbWV-JdI3jQ4ypu9rbMWx3rQJBfmt0FoYzgUIZEVFEcOqwemRN81zoDAaa-Bk0KWNGDjJHZDdDmFhW3AN7lI-puxk_mHZGJ11rxyR8O55XLSe3SPmRfKwZI6yU24ZxvQKFYItdldUKGzO6Ia6zTKhAVRU",
+        "alg":"RS256",
+        "kid":"2011-04-29"}"#;
+
+    // RFC 8037 appendix A.1
+    #[cfg(ossl111)]
+    const ED25519_JWK: &str = r#"{"kty":"OKP","crv":"Ed25519",
+        "d":"nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A",
+        "x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#;
+
+    #[test]
+    fn rsa_round_trip() {
+        let key = PKey::private_key_from_jwk(RSA_JWK).unwrap();
+        assert_eq!(key.id(), Id::RSA);
+        assert!(key.rsa().unwrap().check_key().unwrap());
+
+        let jwk = key.private_key_to_jwk().unwrap();
+        let members = Members::parse(&jwk).unwrap();
+        let expected = Members::parse(RSA_JWK).unwrap();
+        assert_eq!(members.0.len(), 9);
+        for (name, value) in &members.0 {
+            assert_eq!(Some(value), expected.0.get(name));
+        }
+        let rsa = Rsa::private_key_from_jwk(&jwk).unwrap();
+        assert_eq!(
+            rsa.private_key_to_der().unwrap(),
+            key.rsa().unwrap().private_key_to_der().unwrap()
+        );
+
+        let public = PKey::public_key_from_jwk(RSA_JWK).unwrap();
+        assert!(public.public_eq(&key));
+        let jwk = public.public_key_to_jwk().unwrap();
+        assert!(!jwk.contains("\"d\""));
+        let rsa = Rsa::public_key_from_jwk(&jwk).unwrap();
+        assert_eq!(rsa.e().to_vec(), [1, 0, 1]);
+    }
+
+    #[test]
+    fn ec_round_trip() {
+        for (_, nid) in &CURVES {
+            let group = EcGroup::from_curve_name(*nid).unwrap();
+            let key = EcKey::generate(&group).unwrap();
+
+            let jwk = key.private_key_to_jwk().unwrap();
+            let key2 = EcKey::private_key_from_jwk(&jwk).unwrap();
+            assert_eq!(key.private_key(), key2.private_key());
+
+            let jwk = key.public_key_to_jwk().unwrap();
+            assert!(!jwk.contains("\"d\""));
+            let public = PKey::public_key_from_jwk(&jwk).unwrap();
+            assert!(public.public_eq(&PKey::from_ec_key(key).unwrap()));
+        }
+    }
+
+    #[test]
+    #[cfg(ossl111)]
+    fn okp_round_trip() {
+        let key = PKey::private_key_from_jwk(ED25519_JWK).unwrap();
+        assert_eq!(key.id(), Id::ED25519);
+        let jwk = key.private_key_to_jwk().unwrap();
+        assert_eq!(
+            jwk,
+            r#"{"crv":"Ed25519","d":"nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A","kty":"OKP","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#
+        );
+
+        let key = PKey::generate_x25519().unwrap();
+        let public = PKey::public_key_from_jwk(&key.public_key_to_jwk().unwrap()).unwrap();
+        assert!(public.public_eq(&key));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            PKey::public_key_from_jwk(r#"{"kty":"oct","k":"AAAA"}"#),
+            Err(Error::Unsupported(_))
+        ));
+        assert!(matches!(
+            PKey::public_key_from_jwk(r#"{"kty":"EC","crv":"P-256","x":"AAAA"}"#),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            Rsa::public_key_from_jwk(r#"{"kty":"RSA","n":"AQAB","e":"AQ+B"}"#),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            EcKey::public_key_from_jwk(RSA_JWK),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            PKey::public_key_from_jwk(r#"{"kty":1}"#),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            PKey::public_key_from_jwk(r#"{"kty":"RSA""#),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn json() {
+        let members =
+            Members::parse(r#" { "a\u00e9" : "\"\\\/" , "b": [1, {"c": null}] } "#).unwrap();
+        assert_eq!(members.required("a\u{e9}").unwrap(), "\"\\/");
+        assert!(members.contains("b"));
+        assert!(matches!(members.required("b"), Err(Error::Invalid(_))));
+        assert!(matches!(members.required("c"), Err(Error::Invalid(_))));
+
+        assert!(Members::parse("{}").is_ok());
+        assert!(Members::parse("{} x").is_err());
+        assert!(Members::parse("[]").is_err());
+    }
+
+    #[test]
+    fn base64url() {
+        assert_eq!(encode_base64url(&[0xfb, 0xff]), "-_8");
+        assert_eq!(decode_base64url("-_8").unwrap(), [0xfb, 0xff]);
+        assert_eq!(decode_base64url("").unwrap(), Vec::<u8>::new());
+        assert!(decode_base64url("-_8=").is_none());
+        assert!(decode_base64url("+/8").is_none());
+        assert!(decode_base64url("A").is_none());
+    }
+}
@@ -0,0 +1,283 @@
+//! HKDF-based key schedules.
+//!
+//! [HKDF] extracts a pseudorandom key from input keying material, then expands it into any number
+//! of output keys bound to distinct `info` strings. Protocols such as TLS 1.3 build their key
+//! schedules from these two steps: each secret is used to derive labeled sub-keys, and is
+//! replaced by a new secret derived from itself to provide forward secrecy.
+//!
+//! [`hkdf_extract`] and [`hkdf_expand`] expose the two steps directly, [`hkdf_expand_label`]
+//! implements the `HKDF-Expand-Label` function of [TLS 1.3], and [`ChainKey`] tracks a secret
+//! that is advanced in place.
+//!
+//! Requires OpenSSL 1.1.1 or newer.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::hash::MessageDigest;
+//! use openssl::kdf::{hkdf_extract, ChainKey};
+//!
+//! let secret = hkdf_extract(MessageDigest::sha256(), b"salt", b"shared secret").unwrap();
+//! let mut chain = ChainKey::new(MessageDigest::sha256(), b"example ", &secret);
+//!
+//! let mut key = [0; 16];
+//! chain.derive(b"key", &[], &mut key).unwrap();
+//! chain.advance().unwrap();
+//!
+//! let mut next_key = [0; 16];
+//! chain.derive(b"key", &[], &mut next_key).unwrap();
+//! assert_ne!(key, next_key);
+//! assert_eq!(chain.generation(), 1);
+//! ```
+//!
+//! [HKDF]: https://www.rfc-editor.org/rfc/rfc5869
+//! [TLS 1.3]: https://www.rfc-editor.org/rfc/rfc8446#section-7.1
+use foreign_types::ForeignTypeRef;
+
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::md::MdRef;
+use crate::pkey::Id;
+use crate::pkey_ctx::{HkdfMode, PkeyCtx};
+
+const TLS13_LABEL_PREFIX: &[u8] = b"tls13 ";
+const KEY_UPDATE_LABEL: &[u8] = b"traffic upd";
+
+/// Performs HKDF-Extract, returning a pseudorandom key the size of the digest.
+pub fn hkdf_extract(md: MessageDigest, salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    let mut ctx = hkdf_ctx(md, HkdfMode::EXTRACT_ONLY, ikm)?;
+    // An empty salt is equivalent to the default salt of zeros, but OpenSSL rejects it.
+    if !salt.is_empty() {
+        ctx.set_hkdf_salt(salt)?;
+    }
+    let mut prk = vec![];
+    ctx.derive_to_vec(&mut prk)?;
+    Ok(prk)
+}
+
+/// Performs HKDF-Expand, filling `out` with key material derived from `prk` and `info`.
+///
+/// # Panics
+///
+/// Panics if `out` is longer than 255 times the digest size.
+pub fn hkdf_expand(
+    md: MessageDigest,
+    prk: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    assert!(out.len() <= 255 * md.size());
+
+    let mut ctx = hkdf_ctx(md, HkdfMode::EXPAND_ONLY, prk)?;
+    ctx.add_hkdf_info(info)?;
+    ctx.derive(Some(out))?;
+    Ok(())
+}
+
+/// Performs the TLS 1.3 `HKDF-Expand-Label` function.
+///
+/// The `tls13 ` prefix is added to `label`, so values computed by this function match those of
+/// the TLS 1.3 key schedule.
+///
+/// # Panics
+///
+/// Panics if the prefixed `label` or the `context` is longer than 255 bytes, or if `out` is
+/// longer than 255 times the digest size.
+pub fn hkdf_expand_label(
+    md: MessageDigest,
+    secret: &[u8],
+    label: &[u8],
+    context: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    expand_label(md, secret, TLS13_LABEL_PREFIX, label, context, out)
+}
+
+/// A secret that is advanced through successive generations, and from which labeled keys are
+/// derived.
+///
+/// Keys are derived with `HKDF-Expand-Label` using a protocol-specific label prefix. Advancing
+/// the chain replaces the secret with `HKDF-Expand-Label(secret, "traffic upd", "", Hash.length)`,
+/// the derivation TLS 1.3 uses to update traffic secrets. With the `tls13 ` prefix, a chain key
+/// initialized with a TLS 1.3 traffic secret therefore follows that connection's key updates.
+///
+/// Keys derived from previous generations can't be recomputed from the current secret.
+pub struct ChainKey {
+    md: MessageDigest,
+    label_prefix: Vec<u8>,
+    secret: Vec<u8>,
+    generation: u64,
+}
+
+impl ChainKey {
+    /// Creates a chain key at generation 0.
+    ///
+    /// `label_prefix` is prepended to every label, and should identify the protocol so that its
+    /// keys are distinct from those of other protocols using the same secret. It is `b"tls13 "`
+    /// in TLS 1.3.
+    pub fn new(md: MessageDigest, label_prefix: &[u8], secret: &[u8]) -> ChainKey {
+        ChainKey {
+            md,
+            label_prefix: label_prefix.to_vec(),
+            secret: secret.to_vec(),
+            generation: 0,
+        }
+    }
+
+    /// Fills `out` with a key derived from the current secret, `label` and `context`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefixed `label` or the `context` is longer than 255 bytes, or if `out` is
+    /// longer than 255 times the digest size.
+    pub fn derive(&self, label: &[u8], context: &[u8], out: &mut [u8]) -> Result<(), ErrorStack> {
+        expand_label(
+            self.md,
+            &self.secret,
+            &self.label_prefix,
+            label,
+            context,
+            out,
+        )
+    }
+
+    /// Replaces the secret with the next generation's.
+    pub fn advance(&mut self) -> Result<(), ErrorStack> {
+        let mut next = vec![0; self.md.size()];
+        self.derive(KEY_UPDATE_LABEL, &[], &mut next)?;
+        self.secret = next;
+        self.generation += 1;
+        Ok(())
+    }
+
+    /// Returns the number of times the chain has been advanced.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the current secret.
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+}
+
+fn expand_label(
+    md: MessageDigest,
+    secret: &[u8],
+    label_prefix: &[u8],
+    label: &[u8],
+    context: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    let label_len = label_prefix.len() + label.len();
+    assert!(label_len <= 255);
+    assert!(context.len() <= 255);
+    assert!(out.len() <= u16::MAX as usize);
+
+    // struct {
+    //     uint16 length;
+    //     opaque label<7..255>;
+    //     opaque context<0..255>;
+    // } HkdfLabel;
+    let mut info = Vec::with_capacity(4 + label_len + context.len());
+    info.extend_from_slice(&(out.len() as u16).to_be_bytes());
+    info.push(label_len as u8);
+    info.extend_from_slice(label_prefix);
+    info.extend_from_slice(label);
+    info.push(context.len() as u8);
+    info.extend_from_slice(context);
+
+    hkdf_expand(md, secret, &info, out)
+}
+
+fn hkdf_ctx(md: MessageDigest, mode: HkdfMode, key: &[u8]) -> Result<PkeyCtx<()>, ErrorStack> {
+    let mut ctx = PkeyCtx::new_id(Id::HKDF)?;
+    ctx.derive_init()?;
+    ctx.set_hkdf_mode(mode)?;
+    // SAFETY: `MessageDigest` and `MdRef` both wrap a static `EVP_MD`.
+    ctx.set_hkdf_md(unsafe { MdRef::from_ptr(md.as_ptr() as *mut _) })?;
+    ctx.set_hkdf_key(key)?;
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hash::hash;
+
+    #[test]
+    fn rfc5869() {
+        // Test case 1 from RFC 5869.
+        let ikm = hex::decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap();
+        let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+        let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+
+        let prk = hkdf_extract(MessageDigest::sha256(), &salt, &ikm).unwrap();
+        assert_eq!(
+            hex::encode(&prk),
+            "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"
+        );
+
+        let mut okm = [0; 42];
+        hkdf_expand(MessageDigest::sha256(), &prk, &info, &mut okm).unwrap();
+        assert_eq!(
+            hex::encode(&okm[..]),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+    }
+
+    #[test]
+    fn tls13_key_schedule() {
+        // The early secret and its "derived" secret from RFC 8448 section 3.
+        let early_secret = hkdf_extract(MessageDigest::sha256(), &[], &[0; 32]).unwrap();
+        assert_eq!(
+            hex::encode(&early_secret),
+            "33ad0a1c607ec03b09e6cd9893680ce210adf300aa1f2660e1b22e10f170f92a"
+        );
+
+        let empty_hash = hash(MessageDigest::sha256(), &[]).unwrap();
+        let mut derived = [0; 32];
+        hkdf_expand_label(
+            MessageDigest::sha256(),
+            &early_secret,
+            b"derived",
+            &empty_hash,
+            &mut derived,
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(derived),
+            "6f2615a108c702c5678f54fc9dbab69716c076189c48250cebeac3576c3611ba"
+        );
+    }
+
+    #[test]
+    fn chain_key() {
+        let secret = [1; 32];
+        let mut chain = ChainKey::new(MessageDigest::sha256(), b"tls13 ", &secret);
+
+        let mut key = [0; 16];
+        chain.derive(b"key", &[], &mut key).unwrap();
+        let mut expected = [0; 16];
+        hkdf_expand_label(MessageDigest::sha256(), &secret, b"key", &[], &mut expected).unwrap();
+        assert_eq!(key, expected);
+
+        chain.advance().unwrap();
+        assert_eq!(chain.generation(), 1);
+        let mut next = [0; 32];
+        hkdf_expand_label(
+            MessageDigest::sha256(),
+            &secret,
+            b"traffic upd",
+            &[],
+            &mut next,
+        )
+        .unwrap();
+        assert_eq!(chain.secret(), next);
+
+        let other = ChainKey::new(MessageDigest::sha256(), b"other ", &secret);
+        let mut other_key = [0; 16];
+        other.derive(b"key", &[], &mut other_key).unwrap();
+        assert_ne!(key, other_key);
+    }
+}
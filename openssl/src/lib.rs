@@ -136,27 +136,49 @@ mod util;
 pub mod aes;
 pub mod asn1;
 pub mod base64;
+pub mod batch_verify;
 pub mod bn;
 pub mod cipher;
 pub mod cipher_ctx;
+#[cfg(ossl300)]
+pub mod cmp;
 #[cfg(all(not(boringssl), not(libressl), not(osslconf = "OPENSSL_NO_CMS")))]
 pub mod cms;
 pub mod conf;
+#[cfg(ossl300)]
+pub mod crmf;
+#[cfg(ossl110)]
+pub mod ctx_pool;
 pub mod derive;
 pub mod dh;
 pub mod dsa;
 pub mod ec;
 pub mod ecdsa;
+pub mod ecies;
 pub mod encrypt;
 #[cfg(not(boringssl))]
 pub mod envelope;
 pub mod error;
+#[cfg(not(boringssl))]
+pub mod est;
+#[cfg(ossl110)]
+pub mod external_key;
 pub mod ex_data;
 #[cfg(not(any(libressl, ossl300)))]
 pub mod fips;
 pub mod hash;
+#[cfg(boringssl)]
+pub mod hpke;
+#[cfg(ossl300)]
+pub mod http;
+#[cfg(feature = "jwk")]
+pub mod jwk;
+#[cfg(ossl111)]
+pub mod kdf;
 #[cfg(ossl300)]
 pub mod lib_ctx;
+#[cfg(ossl110)]
+pub mod lifecycle;
 pub mod md;
 pub mod md_ctx;
 pub mod memcmp;
@@ -174,13 +196,20 @@ pub mod pkey_ctx;
 pub mod provider;
 pub mod rand;
 pub mod rsa;
+#[cfg(not(boringssl))]
+pub mod scep;
 pub mod sha;
 pub mod sign;
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+pub mod srp;
 pub mod srtp;
+pub mod ssh;
 pub mod ssl;
 pub mod stack;
 pub mod string;
 pub mod symm;
+#[cfg(ossl110)]
+pub mod ts;
 pub mod version;
 pub mod x509;
 
@@ -0,0 +1,136 @@
+//! Control over the initialization and cleanup of the OpenSSL library.
+//!
+//! Functions in this crate initialize OpenSSL on demand with default settings. Applications
+//! which need different settings, such as shared libraries which must not register `atexit`
+//! handlers or which load a specific configuration file, can call `init_with` before using any
+//! other function in this crate. Most options only take effect the first time the library is
+//! initialized.
+//!
+//! Requires OpenSSL 1.1.0 or newer.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::lifecycle::{self, InitOptions, InitSettings};
+//!
+//! let mut settings = InitSettings::new().unwrap();
+//! settings.set_config_appname("my_plugin").unwrap();
+//! lifecycle::init_with(InitOptions::LOAD_CONFIG, Some(&settings)).unwrap();
+//! ```
+use bitflags::bitflags;
+use foreign_types::ForeignTypeRef;
+use std::ffi::CString;
+use std::ptr;
+
+use crate::error::ErrorStack;
+use crate::{cvt, cvt_p};
+use openssl_macros::corresponds;
+
+bitflags! {
+    /// Options controlling how the library is initialized.
+    pub struct InitOptions: u64 {
+        const NO_LOAD_CRYPTO_STRINGS = ffi::OPENSSL_INIT_NO_LOAD_CRYPTO_STRINGS;
+        const LOAD_CRYPTO_STRINGS = ffi::OPENSSL_INIT_LOAD_CRYPTO_STRINGS;
+        const ADD_ALL_CIPHERS = ffi::OPENSSL_INIT_ADD_ALL_CIPHERS;
+        const ADD_ALL_DIGESTS = ffi::OPENSSL_INIT_ADD_ALL_DIGESTS;
+        const NO_ADD_ALL_CIPHERS = ffi::OPENSSL_INIT_NO_ADD_ALL_CIPHERS;
+        const NO_ADD_ALL_DIGESTS = ffi::OPENSSL_INIT_NO_ADD_ALL_DIGESTS;
+        const LOAD_CONFIG = ffi::OPENSSL_INIT_LOAD_CONFIG;
+        const NO_LOAD_CONFIG = ffi::OPENSSL_INIT_NO_LOAD_CONFIG;
+        const ASYNC = ffi::OPENSSL_INIT_ASYNC;
+        const NO_LOAD_SSL_STRINGS = ffi::OPENSSL_INIT_NO_LOAD_SSL_STRINGS;
+        const LOAD_SSL_STRINGS = ffi::OPENSSL_INIT_LOAD_SSL_STRINGS;
+        /// Do not register an `atexit` handler to clean up the library when the process exits.
+        ///
+        /// This is always set by this crate's own initialization. Requires OpenSSL 1.1.1b or
+        /// newer.
+        #[cfg(ossl111b)]
+        const NO_ATEXIT = ffi::OPENSSL_INIT_NO_ATEXIT;
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::OPENSSL_INIT_SETTINGS;
+    fn drop = ffi::OPENSSL_INIT_free;
+
+    /// Settings used when the library loads its configuration during initialization.
+    pub struct InitSettings;
+    /// Reference to `InitSettings`.
+    pub struct InitSettingsRef;
+}
+
+impl InitSettings {
+    /// Creates a new set of initialization settings.
+    #[corresponds(OPENSSL_INIT_new)]
+    pub fn new() -> Result<InitSettings, ErrorStack> {
+        unsafe { cvt_p(ffi::OPENSSL_INIT_new()).map(InitSettings) }
+    }
+}
+
+impl InitSettingsRef {
+    /// Sets the name of the configuration file section read when `InitOptions::LOAD_CONFIG` is
+    /// set.
+    #[corresponds(OPENSSL_INIT_set_config_appname)]
+    pub fn set_config_appname(&mut self, appname: &str) -> Result<(), ErrorStack> {
+        let appname = CString::new(appname).unwrap();
+        unsafe {
+            cvt(ffi::OPENSSL_INIT_set_config_appname(
+                self.as_ptr(),
+                appname.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the path of the configuration file read when `InitOptions::LOAD_CONFIG` is set.
+    ///
+    /// Requires OpenSSL 1.1.1b or newer.
+    #[corresponds(OPENSSL_INIT_set_config_filename)]
+    #[cfg(ossl111b)]
+    pub fn set_config_filename(&mut self, filename: &str) -> Result<(), ErrorStack> {
+        let filename = CString::new(filename).unwrap();
+        unsafe {
+            cvt(ffi::OPENSSL_INIT_set_config_filename(
+                self.as_ptr(),
+                filename.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+}
+
+/// Initializes the library with the specified options and settings.
+///
+/// This may be called more than once, with later calls enabling additional options. To take
+/// effect, options such as `InitOptions::NO_ATEXIT` and `InitOptions::LOAD_CONFIG` must be
+/// passed before any other function in this crate is used.
+#[corresponds(OPENSSL_init_ssl)]
+pub fn init_with(
+    options: InitOptions,
+    settings: Option<&InitSettingsRef>,
+) -> Result<(), ErrorStack> {
+    unsafe {
+        cvt(ffi::OPENSSL_init_ssl(
+            options.bits(),
+            settings.map_or(ptr::null(), |s| s.as_ptr()),
+        ))?;
+    }
+
+    ffi::init();
+    Ok(())
+}
+
+/// Releases all resources held by the library.
+///
+/// This is not needed in most applications, since the operating system reclaims the resources
+/// when the process exits. It is intended for shared libraries which are unloaded while the
+/// process continues to run.
+///
+/// # Safety
+///
+/// No function in this crate may be called after this, from any thread, and no object created
+/// by this crate may be used or dropped. The library cannot be initialized again.
+#[corresponds(OPENSSL_cleanup)]
+pub unsafe fn cleanup() {
+    ffi::OPENSSL_cleanup();
+}
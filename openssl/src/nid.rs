@@ -1068,6 +1068,44 @@ impl Nid {
     pub const AES_128_CBC_HMAC_SHA1: Nid = Nid(ffi::NID_aes_128_cbc_hmac_sha1);
     pub const AES_192_CBC_HMAC_SHA1: Nid = Nid(ffi::NID_aes_192_cbc_hmac_sha1);
     pub const AES_256_CBC_HMAC_SHA1: Nid = Nid(ffi::NID_aes_256_cbc_hmac_sha1);
+    #[cfg(ossl110)]
+    pub const KX_RSA: Nid = Nid(ffi::NID_kx_rsa);
+    #[cfg(ossl110)]
+    pub const KX_ECDHE: Nid = Nid(ffi::NID_kx_ecdhe);
+    #[cfg(ossl110)]
+    pub const KX_DHE: Nid = Nid(ffi::NID_kx_dhe);
+    #[cfg(ossl110)]
+    pub const KX_ECDHE_PSK: Nid = Nid(ffi::NID_kx_ecdhe_psk);
+    #[cfg(ossl110)]
+    pub const KX_DHE_PSK: Nid = Nid(ffi::NID_kx_dhe_psk);
+    #[cfg(ossl110)]
+    pub const KX_RSA_PSK: Nid = Nid(ffi::NID_kx_rsa_psk);
+    #[cfg(ossl110)]
+    pub const KX_PSK: Nid = Nid(ffi::NID_kx_psk);
+    #[cfg(ossl110)]
+    pub const KX_SRP: Nid = Nid(ffi::NID_kx_srp);
+    #[cfg(ossl110)]
+    pub const KX_GOST: Nid = Nid(ffi::NID_kx_gost);
+    #[cfg(ossl111)]
+    pub const KX_ANY: Nid = Nid(ffi::NID_kx_any);
+    #[cfg(ossl110)]
+    pub const AUTH_RSA: Nid = Nid(ffi::NID_auth_rsa);
+    #[cfg(ossl110)]
+    pub const AUTH_ECDSA: Nid = Nid(ffi::NID_auth_ecdsa);
+    #[cfg(ossl110)]
+    pub const AUTH_PSK: Nid = Nid(ffi::NID_auth_psk);
+    #[cfg(ossl110)]
+    pub const AUTH_DSS: Nid = Nid(ffi::NID_auth_dss);
+    #[cfg(ossl110)]
+    pub const AUTH_GOST01: Nid = Nid(ffi::NID_auth_gost01);
+    #[cfg(ossl110)]
+    pub const AUTH_GOST12: Nid = Nid(ffi::NID_auth_gost12);
+    #[cfg(ossl110)]
+    pub const AUTH_SRP: Nid = Nid(ffi::NID_auth_srp);
+    #[cfg(ossl110)]
+    pub const AUTH_NULL: Nid = Nid(ffi::NID_auth_null);
+    #[cfg(ossl111)]
+    pub const AUTH_ANY: Nid = Nid(ffi::NID_auth_any);
 }
 
 #[cfg(test)]
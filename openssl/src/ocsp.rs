@@ -4,7 +4,7 @@ use libc::{c_int, c_long, c_ulong};
 use std::mem;
 use std::ptr;
 
-use crate::asn1::Asn1GeneralizedTimeRef;
+use crate::asn1::{Asn1GeneralizedTimeRef, Asn1IntegerRef};
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
 use crate::stack::StackRef;
@@ -234,6 +234,27 @@ impl OcspCertId {
             .map(OcspCertId)
         }
     }
+
+    /// Constructs a certificate ID for the certificate with serial number `serial` issued by
+    /// `issuer`.
+    ///
+    /// Unlike `from_cert`, this does not require the subject certificate itself.
+    #[corresponds(OCSP_cert_id_new)]
+    pub fn from_serial(
+        digest: MessageDigest,
+        serial: &Asn1IntegerRef,
+        issuer: &X509Ref,
+    ) -> Result<OcspCertId, ErrorStack> {
+        unsafe {
+            cvt_p(ffi::OCSP_cert_id_new(
+                digest.as_ptr(),
+                ffi::X509_get_subject_name(issuer.as_ptr()),
+                ffi::X509_get0_pubkey_bitstr(issuer.as_ptr()),
+                serial.as_ptr(),
+            ))
+            .map(OcspCertId)
+        }
+    }
 }
 
 foreign_type_and_impl_send_sync! {
@@ -314,6 +335,18 @@ impl OcspRequest {
         }
     }
 
+    /// Creates a request for the status of certificate `subject` issued by `issuer`.
+    pub fn from_cert(
+        digest: MessageDigest,
+        subject: &X509Ref,
+        issuer: &X509Ref,
+    ) -> Result<OcspRequest, ErrorStack> {
+        let id = OcspCertId::from_cert(digest, subject, issuer)?;
+        let mut req = OcspRequest::new()?;
+        req.add_id(id)?;
+        Ok(req)
+    }
+
     from_der! {
         /// Deserializes a DER-encoded OCSP request.
         #[corresponds(d2i_OCSP_REQUEST)]
@@ -348,3 +381,23 @@ foreign_type_and_impl_send_sync! {
     pub struct OcspOneReq;
     pub struct OcspOneReqRef;
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cert_id_from_serial() {
+        let subject = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let issuer = X509::from_pem(include_bytes!("../test/root-ca.pem")).unwrap();
+
+        let req = OcspRequest::from_cert(MessageDigest::sha1(), &subject, &issuer).unwrap();
+
+        let id = OcspCertId::from_serial(MessageDigest::sha1(), subject.serial_number(), &issuer)
+            .unwrap();
+        let mut req2 = OcspRequest::new().unwrap();
+        req2.add_id(id).unwrap();
+
+        assert_eq!(req.to_der().unwrap(), req2.to_der().unwrap());
+    }
+}
@@ -2,11 +2,15 @@
 
 use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::c_int;
+#[cfg(ossl300)]
+use libc::c_long;
 use std::ffi::CString;
 use std::ptr;
 
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtxRef;
 use crate::nid::Nid;
 use crate::pkey::{HasPrivate, PKey, PKeyRef, Private};
 use crate::stack::Stack;
@@ -57,6 +61,35 @@ impl Pkcs12Ref {
             Ok(ParsedPkcs12 { pkey, cert, chain })
         }
     }
+
+    /// Like `parse`, but decrypts the archive and loads its contents in the library context
+    /// `ctx`, using `properties` to select the providers which are used.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[corresponds(PKCS12_init_ex)]
+    #[cfg(ossl300)]
+    pub fn parse_with_lib_ctx(
+        &self,
+        pass: &str,
+        ctx: Option<&LibCtxRef>,
+        properties: Option<&str>,
+    ) -> Result<ParsedPkcs12, ErrorStack> {
+        let der = self.to_der()?;
+        let properties = properties.map(|s| CString::new(s).unwrap());
+
+        unsafe {
+            let ctx = ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr);
+            let propq = properties.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+
+            let mut p12 = cvt_p(ffi::PKCS12_init_ex(ffi::NID_pkcs7_data, ctx, propq))?;
+            let mut p = der.as_ptr();
+            // d2i_PKCS12 decodes into the existing object, which keeps its library context. On
+            // failure OpenSSL frees it.
+            let p12 = cvt_p(ffi::d2i_PKCS12(&mut p12, &mut p, der.len() as c_long))
+                .map(|p| Pkcs12::from_ptr(p))?;
+            p12.parse(pass)
+        }
+    }
 }
 
 impl Pkcs12 {
@@ -215,6 +248,65 @@ impl Pkcs12Builder {
             Ok(pkcs12)
         }
     }
+
+    /// Like `build`, but fetches the algorithms used to protect the archive from the library
+    /// context `ctx` using `properties`.
+    ///
+    /// `pkey` may be held by any provider which allows it to be exported as a PKCS #8 structure.
+    /// Unlike `build`, the archive is protected by a MAC using the configured `mac_iter` and
+    /// `mac_md`.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[corresponds(PKCS12_create_ex)]
+    #[cfg(ossl300)]
+    pub fn build_with_lib_ctx<T>(
+        self,
+        ctx: Option<&LibCtxRef>,
+        properties: Option<&str>,
+        password: &str,
+        friendly_name: &str,
+        pkey: &PKeyRef<T>,
+        cert: &X509Ref,
+    ) -> Result<Pkcs12, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            let pass = CString::new(password).unwrap();
+            let friendly_name = CString::new(friendly_name).unwrap();
+            let properties = properties.map(|s| CString::new(s).unwrap());
+            let ca = self.ca.as_ref().map_or(ptr::null_mut(), |ca| ca.as_ptr());
+            let md_type = self.mac_md.map_or(ptr::null(), |md_type| md_type.as_ptr());
+
+            let pkcs12 = cvt_p(ffi::PKCS12_create_ex(
+                pass.as_ptr(),
+                friendly_name.as_ptr(),
+                pkey.as_ptr(),
+                cert.as_ptr(),
+                ca,
+                self.nid_key.as_raw(),
+                self.nid_cert.as_raw(),
+                self.iter,
+                -1,
+                0,
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+            ))
+            .map(Pkcs12)?;
+
+            cvt(ffi::PKCS12_set_mac(
+                pkcs12.as_ptr(),
+                pass.as_ptr(),
+                -1,
+                ptr::null_mut(),
+                0,
+                self.mac_iter,
+                md_type,
+            ))?;
+
+            Ok(pkcs12)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -305,4 +397,61 @@ mod test {
         );
         assert!(parsed.pkey.public_eq(&pkey));
     }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn create_and_parse_with_lib_ctx() {
+        use crate::lib_ctx::LibCtx;
+        use crate::provider::Provider;
+
+        let ctx = LibCtx::new().unwrap();
+        let _provider = Provider::load(Some(&ctx), "default").unwrap();
+
+        let pkey = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut name = X509Name::builder().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, "ns.example.com")
+            .unwrap();
+        let name = name.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+            .unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&pkey).unwrap();
+        builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+        let cert = builder.build();
+
+        let mut pkcs12_builder = Pkcs12::builder();
+        pkcs12_builder.mac_md(MessageDigest::sha256());
+        let pkcs12 = pkcs12_builder
+            .build_with_lib_ctx(Some(&ctx), None, "mypass", "ns.example.com", &pkey, &cert)
+            .unwrap();
+        let der = pkcs12.to_der().unwrap();
+
+        let pkcs12 = Pkcs12::from_der(&der).unwrap();
+        assert!(pkcs12
+            .parse_with_lib_ctx("badpass", Some(&ctx), None)
+            .is_err());
+        let parsed = pkcs12
+            .parse_with_lib_ctx("mypass", Some(&ctx), Some("provider=default"))
+            .unwrap();
+
+        assert_eq!(parsed.cert.to_der().unwrap(), cert.to_der().unwrap());
+        assert!(parsed.pkey.public_eq(&pkey));
+        assert!(parsed.chain.is_none());
+        assert!(parsed.cert.verify(&parsed.pkey).unwrap());
+
+        // decryption happens in the requested context, which has no usable algorithms here
+        let null_ctx = LibCtx::new().unwrap();
+        let _null_provider = Provider::load(Some(&null_ctx), "null").unwrap();
+        assert!(pkcs12
+            .parse_with_lib_ctx("mypass", Some(&null_ctx), None)
+            .is_err());
+    }
 }
@@ -1,16 +1,18 @@
 use bitflags::bitflags;
-use foreign_types::{ForeignType, ForeignTypeRef};
+use foreign_types::{ForeignType, ForeignTypeRef, Opaque};
 use libc::c_int;
 use std::mem;
 use std::ptr;
 
 use crate::bio::{MemBio, MemBioSlice};
 use crate::error::ErrorStack;
+use crate::nid::Nid;
 use crate::pkey::{HasPrivate, PKeyRef};
-use crate::stack::{Stack, StackRef};
+use crate::stack::{Stack, StackRef, Stackable};
 use crate::symm::Cipher;
+use crate::util::ForeignTypeRefExt;
 use crate::x509::store::X509StoreRef;
-use crate::x509::{X509Ref, X509};
+use crate::x509::{X509Algorithm, X509Ref, X509};
 use crate::{cvt, cvt_p};
 use openssl_macros::corresponds;
 
@@ -27,6 +29,73 @@ foreign_type_and_impl_send_sync! {
     pub struct Pkcs7Ref;
 }
 
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::PKCS7_SIGNER_INFO;
+    fn drop = ffi::PKCS7_SIGNER_INFO_free;
+
+    /// Information about a single signer of a PKCS#7 `signedData` structure.
+    pub struct Pkcs7SignerInfo;
+
+    /// Reference to `Pkcs7SignerInfo`
+    pub struct Pkcs7SignerInfoRef;
+}
+
+impl Stackable for Pkcs7SignerInfo {
+    type StackType = ffi::stack_st_PKCS7_SIGNER_INFO;
+}
+
+impl Pkcs7SignerInfoRef {
+    /// Returns the contents of the signer's SMIMECapabilities signed attribute, if present.
+    #[corresponds(PKCS7_get_smimecap)]
+    pub fn smime_capabilities(&self) -> Option<SmimeCapabilities> {
+        unsafe {
+            let caps = ffi::PKCS7_get_smimecap(self.as_ptr());
+            if caps.is_null() {
+                None
+            } else {
+                Some(SmimeCapabilities(Stack::from_ptr(caps)))
+            }
+        }
+    }
+}
+
+/// The contents of an SMIMECapabilities signed attribute, as described in [RFC 8551].
+///
+/// This advertises the encryption algorithms the signer supports, in order of preference, so that
+/// correspondents can choose an algorithm the signer will be able to decrypt.
+///
+/// [RFC 8551]: https://www.rfc-editor.org/rfc/rfc8551#section-2.5.2
+pub struct SmimeCapabilities(pub(crate) Stack<X509Algorithm>);
+
+impl SmimeCapabilities {
+    /// Creates an empty list of capabilities.
+    pub fn new() -> Result<SmimeCapabilities, ErrorStack> {
+        Stack::new().map(SmimeCapabilities)
+    }
+
+    /// Appends an algorithm to the list.
+    ///
+    /// Algorithms should be added in order of preference. `key_bits` is encoded as the
+    /// algorithm's parameter, and is only needed for variable key size ciphers such as RC2.
+    #[corresponds(PKCS7_simple_smimecap)]
+    pub fn add(&mut self, nid: Nid, key_bits: Option<u32>) -> Result<(), ErrorStack> {
+        let arg = key_bits.map_or(0, |bits| bits as c_int);
+        unsafe {
+            cvt(ffi::PKCS7_simple_smimecap(
+                self.0.as_ptr(),
+                nid.as_raw(),
+                arg,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the algorithms in the list.
+    pub fn algorithms(&self) -> &StackRef<X509Algorithm> {
+        &self.0
+    }
+}
+
 bitflags! {
     pub struct Pkcs7Flags: c_int {
         const TEXT = ffi::PKCS7_TEXT;
@@ -146,9 +215,100 @@ impl Pkcs7 {
             .map(Pkcs7)
         }
     }
+
+    /// Like `sign`, but advertises `capabilities` in the signer's SMIMECapabilities attribute in
+    /// place of OpenSSL's default list.
+    ///
+    /// The `NOATTR` and `NOSMIMECAP` flags must not be set, and `STREAM` is not supported.
+    #[corresponds(PKCS7_add_attrib_smimecap)]
+    pub fn sign_with_smime_capabilities<PT>(
+        signcert: &X509Ref,
+        pkey: &PKeyRef<PT>,
+        certs: &StackRef<X509>,
+        input: &[u8],
+        capabilities: &SmimeCapabilities,
+        flags: Pkcs7Flags,
+    ) -> Result<Pkcs7, ErrorStack>
+    where
+        PT: HasPrivate,
+    {
+        let input_bio = MemBioSlice::new(input)?;
+        unsafe {
+            let pkcs7 = cvt_p(ffi::PKCS7_sign(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                certs.as_ptr(),
+                ptr::null_mut(),
+                (flags | Pkcs7Flags::PARTIAL).bits,
+            ))
+            .map(Pkcs7)?;
+            let signer_info = cvt_p(ffi::PKCS7_sign_add_signer(
+                pkcs7.as_ptr(),
+                signcert.as_ptr(),
+                pkey.as_ptr(),
+                ptr::null(),
+                (flags | Pkcs7Flags::NOSMIMECAP).bits,
+            ))?;
+            cvt(ffi::PKCS7_add_attrib_smimecap(
+                signer_info,
+                capabilities.0.as_ptr(),
+            ))?;
+            cvt(ffi::PKCS7_final(
+                pkcs7.as_ptr(),
+                input_bio.as_ptr(),
+                flags.bits,
+            ))?;
+
+            Ok(pkcs7)
+        }
+    }
+}
+
+/// The `signedData` content of a PKCS#7 structure.
+pub struct Pkcs7SignedRef(Opaque);
+
+impl ForeignTypeRef for Pkcs7SignedRef {
+    type CType = ffi::PKCS7_SIGNED;
+}
+
+impl Pkcs7SignedRef {
+    /// Returns the certificates included in the structure.
+    ///
+    /// For a degenerate "certs-only" structure, such as the response of a certificate enrollment
+    /// server, these are the only contents.
+    pub fn certificates(&self) -> Option<&StackRef<X509>> {
+        unsafe { StackRef::from_const_ptr_opt((*self.as_ptr()).cert) }
+    }
 }
 
 impl Pkcs7Ref {
+    /// Returns the `signedData` content of the structure.
+    ///
+    /// Returns `None` if the structure does not contain signed data.
+    pub fn signed(&self) -> Option<&Pkcs7SignedRef> {
+        unsafe {
+            if ffi::OBJ_obj2nid((*self.as_ptr()).type_) != ffi::NID_pkcs7_signed {
+                return None;
+            }
+            Pkcs7SignedRef::from_const_ptr_opt((*self.as_ptr()).d.sign)
+        }
+    }
+
+    /// Returns information about the signers of a `signedData` structure.
+    ///
+    /// Returns `None` if the structure does not contain signed data.
+    #[corresponds(PKCS7_get_signer_info)]
+    pub fn signer_info(&self) -> Option<&StackRef<Pkcs7SignerInfo>> {
+        unsafe {
+            let ptr = ffi::PKCS7_get_signer_info(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(StackRef::from_ptr(ptr))
+            }
+        }
+    }
+
     /// Converts PKCS#7 structure to S/MIME format
     #[corresponds(SMIME_write_PKCS7)]
     pub fn to_smime(&self, input: &[u8], flags: Pkcs7Flags) -> Result<Vec<u8>, ErrorStack> {
@@ -284,7 +444,8 @@ impl Pkcs7Ref {
 #[cfg(test)]
 mod tests {
     use crate::hash::MessageDigest;
-    use crate::pkcs7::{Pkcs7, Pkcs7Flags};
+    use crate::nid::Nid;
+    use crate::pkcs7::{Pkcs7, Pkcs7Flags, SmimeCapabilities};
     use crate::pkey::PKey;
     use crate::stack::Stack;
     use crate::symm::Cipher;
@@ -439,4 +600,61 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn smime_capabilities() {
+        let cert = include_bytes!("../test/cert.pem");
+        let cert = X509::from_pem(cert).unwrap();
+        let certs = Stack::new().unwrap();
+        let message = "foo";
+        let pkey = include_bytes!("../test/key.pem");
+        let pkey = PKey::private_key_from_pem(pkey).unwrap();
+
+        let mut capabilities = SmimeCapabilities::new().unwrap();
+        capabilities.add(Nid::AES_256_CBC, None).unwrap();
+        capabilities.add(Nid::DES_EDE3_CBC, None).unwrap();
+
+        let pkcs7 = Pkcs7::sign_with_smime_capabilities(
+            &cert,
+            &pkey,
+            &certs,
+            message.as_bytes(),
+            &capabilities,
+            Pkcs7Flags::empty(),
+        )
+        .unwrap();
+        let pkcs7 = Pkcs7::from_der(&pkcs7.to_der().unwrap()).unwrap();
+
+        let signer_info = pkcs7.signer_info().unwrap();
+        assert_eq!(signer_info.len(), 1);
+        let parsed = signer_info.get(0).unwrap().smime_capabilities().unwrap();
+        let nids = parsed
+            .algorithms()
+            .iter()
+            .map(|alg| alg.object().nid())
+            .collect::<Vec<_>>();
+        assert_eq!(nids, vec![Nid::AES_256_CBC, Nid::DES_EDE3_CBC]);
+
+        let pkcs7 = Pkcs7::sign(
+            &cert,
+            &pkey,
+            &certs,
+            message.as_bytes(),
+            Pkcs7Flags::NOSMIMECAP,
+        )
+        .unwrap();
+        let signer_info = pkcs7.signer_info().unwrap();
+        assert!(signer_info.get(0).unwrap().smime_capabilities().is_none());
+    }
+
+    #[test]
+    fn certs_only() {
+        let pkcs7 = Pkcs7::from_der(include_bytes!("../test/certs-only.p7b")).unwrap();
+        let certs = pkcs7.signed().unwrap().certificates().unwrap();
+        assert_eq!(certs.len(), 2);
+
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        assert_eq!(certs[0].to_der().unwrap(), cert.to_der().unwrap());
+        assert!(pkcs7.signer_info().unwrap().is_empty());
+    }
 }
@@ -39,7 +39,32 @@
 //! let pub_key: Vec<u8> = pkey.public_key_to_pem().unwrap();
 //! println!("{:?}", str::from_utf8(pub_key.as_slice()).unwrap());
 //! ```
+//!
+//! # Key formats
+//!
+//! Keys are commonly exchanged in several encodings, which are easy to confuse since they are
+//! all DER `SEQUENCE`s. [`KeyFormat`] can identify which one a blob is in, and each has
+//! explicitly named conversions:
+//!
+//! | Format | PEM label | Decode | Encode |
+//! |--------|-----------|--------|--------|
+//! | PKCS#1 `RSAPrivateKey` | `RSA PRIVATE KEY` | [`Rsa::private_key_from_der`], [`PKey::private_key_from_der_pkcs1`] | [`RsaRef::private_key_to_der`] |
+//! | PKCS#1 `RSAPublicKey` | `RSA PUBLIC KEY` | [`Rsa::public_key_from_der_pkcs1`], [`PKey::public_key_from_der_pkcs1`] | [`RsaRef::public_key_to_der_pkcs1`], [`PKeyRef::public_key_to_der_pkcs1`] |
+//! | SEC1 `ECPrivateKey` | `EC PRIVATE KEY` | [`EcKey::private_key_from_der`], [`PKey::private_key_from_der_sec1`] | [`EcKeyRef::private_key_to_der`] |
+//! | PKCS#8 `PrivateKeyInfo` | `PRIVATE KEY` | [`PKey::private_key_from_pkcs8`], [`Rsa::private_key_from_pkcs8`], [`EcKey::private_key_from_pkcs8`] | [`PKeyRef::private_key_to_pkcs8`], [`RsaRef::private_key_to_pkcs8`], [`EcKeyRef::private_key_to_pkcs8`] |
+//! | PKCS#8 `EncryptedPrivateKeyInfo` | `ENCRYPTED PRIVATE KEY` | [`PKey::private_key_from_pkcs8_passphrase`] | [`PKeyRef::private_key_to_pkcs8_passphrase`] |
+//! | X.509 `SubjectPublicKeyInfo` | `PUBLIC KEY` | [`PKey::public_key_from_der`], [`Rsa::public_key_from_der`], [`EcKey::public_key_from_der`] | [`PKeyRef::public_key_to_der`], [`RsaRef::public_key_to_der`], [`EcKeyRef::public_key_to_der`] |
+//!
+//! [`RsaRef::private_key_to_der`]: crate::rsa::RsaRef::private_key_to_der
+//! [`RsaRef::public_key_to_der_pkcs1`]: crate::rsa::RsaRef::public_key_to_der_pkcs1
+//! [`RsaRef::private_key_to_pkcs8`]: crate::rsa::RsaRef::private_key_to_pkcs8
+//! [`RsaRef::public_key_to_der`]: crate::rsa::RsaRef::public_key_to_der
+//! [`EcKeyRef::private_key_to_der`]: crate::ec::EcKeyRef::private_key_to_der
+//! [`EcKeyRef::private_key_to_pkcs8`]: crate::ec::EcKeyRef::private_key_to_pkcs8
+//! [`EcKeyRef::public_key_to_der`]: crate::ec::EcKeyRef::public_key_to_der
 #![allow(clippy::missing_safety_doc)]
+#[cfg(not(boringssl))]
+use crate::asn1::get_object;
 use crate::bio::{MemBio, MemBioSlice};
 #[cfg(ossl110)]
 use crate::cipher::CipherRef;
@@ -57,8 +82,10 @@ use cfg_if::cfg_if;
 use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::{c_int, c_long};
 use openssl_macros::corresponds;
+use std::cell::Cell;
 use std::convert::TryFrom;
-use std::ffi::CString;
+use std::error;
+use std::ffi::{CStr, CString};
 use std::fmt;
 use std::mem;
 use std::ptr;
@@ -80,7 +107,7 @@ impl Id {
     pub const RSA: Id = Id(ffi::EVP_PKEY_RSA);
     #[cfg(not(boringssl))]
     pub const HMAC: Id = Id(ffi::EVP_PKEY_HMAC);
-#[cfg(not(boringssl))]
+    #[cfg(not(boringssl))]
     pub const CMAC: Id = Id(ffi::EVP_PKEY_CMAC);
     pub const DSA: Id = Id(ffi::EVP_PKEY_DSA);
     pub const DH: Id = Id(ffi::EVP_PKEY_DH);
@@ -110,6 +137,230 @@ impl Id {
     }
 }
 
+/// An encoding of a public or private key.
+///
+/// See the [module documentation](self#key-formats) for the functions converting to and from
+/// each format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyFormat {
+    /// A PKCS#1 `RSAPrivateKey` structure.
+    Pkcs1PrivateKey,
+    /// A PKCS#1 `RSAPublicKey` structure.
+    Pkcs1PublicKey,
+    /// A SEC1 `ECPrivateKey` structure.
+    Sec1PrivateKey,
+    /// An unencrypted PKCS#8 `PrivateKeyInfo` structure.
+    Pkcs8PrivateKey,
+    /// A PKCS#8 `EncryptedPrivateKeyInfo` structure.
+    Pkcs8EncryptedPrivateKey,
+    /// An X.509 `SubjectPublicKeyInfo` structure.
+    SubjectPublicKeyInfo,
+}
+
+impl KeyFormat {
+    /// Determines the format of a DER-encoded key from its structure.
+    ///
+    /// Only the shape of the outer `SEQUENCE` is examined, so a blob which is detected may still
+    /// fail to decode. Returns `None` if the structure does not match any known format.
+    #[cfg(not(boringssl))]
+    pub fn detect_der(der: &[u8]) -> Option<KeyFormat> {
+        const INTEGER: (c_int, c_int) = (ffi::V_ASN1_INTEGER, ffi::V_ASN1_UNIVERSAL);
+        const BIT_STRING: (c_int, c_int) = (ffi::V_ASN1_BIT_STRING, ffi::V_ASN1_UNIVERSAL);
+        const OCTET_STRING: (c_int, c_int) = (ffi::V_ASN1_OCTET_STRING, ffi::V_ASN1_UNIVERSAL);
+        const SEQUENCE: (c_int, c_int) = (ffi::V_ASN1_SEQUENCE, ffi::V_ASN1_UNIVERSAL);
+
+        let mut der = der;
+        let mut contents = match get_object(&mut der).ok()? {
+            (ffi::V_ASN1_SEQUENCE, ffi::V_ASN1_UNIVERSAL, contents) => contents,
+            _ => return None,
+        };
+        let mut fields = vec![];
+        while !contents.is_empty() {
+            let (tag, class, _) = get_object(&mut contents).ok()?;
+            fields.push((tag, class));
+        }
+
+        match &fields[..] {
+            [INTEGER, INTEGER] => Some(KeyFormat::Pkcs1PublicKey),
+            // version, modulus, public and private exponents, primes and CRT parameters
+            f if f.len() >= 9 && f.iter().all(|f| *f == INTEGER) => {
+                Some(KeyFormat::Pkcs1PrivateKey)
+            }
+            [INTEGER, OCTET_STRING, ..] => Some(KeyFormat::Sec1PrivateKey),
+            [INTEGER, SEQUENCE, OCTET_STRING, ..] => Some(KeyFormat::Pkcs8PrivateKey),
+            [SEQUENCE, OCTET_STRING] => Some(KeyFormat::Pkcs8EncryptedPrivateKey),
+            [SEQUENCE, BIT_STRING] => Some(KeyFormat::SubjectPublicKeyInfo),
+            _ => None,
+        }
+    }
+
+    /// Determines the format of a PEM-encoded key from the label of its first PEM block.
+    ///
+    /// Returns `None` if there is no PEM block or its label is not a known key format.
+    pub fn detect_pem(pem: &[u8]) -> Option<KeyFormat> {
+        const BEGIN: &[u8] = b"-----BEGIN ";
+
+        let start = pem.windows(BEGIN.len()).position(|w| w == BEGIN)? + BEGIN.len();
+        let label = &pem[start..];
+        let end = label.iter().position(|b| *b == b'-')?;
+        let label = &label[..end];
+
+        [
+            KeyFormat::Pkcs1PrivateKey,
+            KeyFormat::Pkcs1PublicKey,
+            KeyFormat::Sec1PrivateKey,
+            KeyFormat::Pkcs8PrivateKey,
+            KeyFormat::Pkcs8EncryptedPrivateKey,
+            KeyFormat::SubjectPublicKeyInfo,
+        ]
+        .iter()
+        .copied()
+        .find(|format| format.pem_label().as_bytes() == label)
+    }
+
+    /// Returns the label used for PEM blocks containing keys in this format.
+    pub fn pem_label(&self) -> &'static str {
+        match self {
+            KeyFormat::Pkcs1PrivateKey => "RSA PRIVATE KEY",
+            KeyFormat::Pkcs1PublicKey => "RSA PUBLIC KEY",
+            KeyFormat::Sec1PrivateKey => "EC PRIVATE KEY",
+            KeyFormat::Pkcs8PrivateKey => "PRIVATE KEY",
+            KeyFormat::Pkcs8EncryptedPrivateKey => "ENCRYPTED PRIVATE KEY",
+            KeyFormat::SubjectPublicKeyInfo => "PUBLIC KEY",
+        }
+    }
+
+    /// Determines if keys in this format contain private components.
+    pub fn is_private(&self) -> bool {
+        !matches!(
+            self,
+            KeyFormat::Pkcs1PublicKey | KeyFormat::SubjectPublicKeyInfo
+        )
+    }
+}
+
+/// An error loading a private key which may be encrypted.
+#[derive(Debug)]
+pub enum PrivateKeyError {
+    /// The key is encrypted, but no passphrase was provided.
+    ///
+    /// The load can be retried once the user has supplied a passphrase.
+    PassphraseRequired(EncryptedKeyInfo),
+    /// An error reported by OpenSSL, such as a malformed key or an incorrect passphrase.
+    Ssl(ErrorStack),
+}
+
+impl fmt::Display for PrivateKeyError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrivateKeyError::PassphraseRequired(_) => {
+                fmt.write_str("a passphrase is required to decrypt the private key")
+            }
+            PrivateKeyError::Ssl(e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl error::Error for PrivateKeyError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PrivateKeyError::PassphraseRequired(_) => None,
+            PrivateKeyError::Ssl(e) => Some(e),
+        }
+    }
+}
+
+impl From<ErrorStack> for PrivateKeyError {
+    fn from(e: ErrorStack) -> PrivateKeyError {
+        PrivateKeyError::Ssl(e)
+    }
+}
+
+/// Information about an encrypted private key which is available without decrypting it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedKeyInfo {
+    pem_label: Option<String>,
+    pem_headers: Vec<(String, String)>,
+}
+
+impl EncryptedKeyInfo {
+    /// Returns the label of the PEM block containing the key, such as `ENCRYPTED PRIVATE KEY` or
+    /// `RSA PRIVATE KEY`.
+    ///
+    /// This is `None` if the key was DER-encoded.
+    pub fn pem_label(&self) -> Option<&str> {
+        self.pem_label.as_deref()
+    }
+
+    /// Returns the headers of the PEM block containing the key, as name and value pairs.
+    ///
+    /// Keys encrypted with the legacy PEM encryption have `Proc-Type` and `DEK-Info` headers,
+    /// while PKCS#8 encrypted keys have none.
+    pub fn pem_headers(&self) -> &[(String, String)] {
+        &self.pem_headers
+    }
+
+    /// Returns the value of the `DEK-Info` PEM header, which names the cipher and IV used to
+    /// encrypt a key with the legacy PEM encryption, such as `AES-128-CBC,<hex IV>`.
+    pub fn dek_info(&self) -> Option<&str> {
+        self.pem_headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("DEK-Info"))
+            .map(|(_, value)| &**value)
+    }
+
+    fn from_der() -> EncryptedKeyInfo {
+        EncryptedKeyInfo {
+            pem_label: None,
+            pem_headers: vec![],
+        }
+    }
+
+    fn from_pem(pem: &[u8]) -> Result<EncryptedKeyInfo, ErrorStack> {
+        unsafe {
+            let bio = MemBioSlice::new(pem)?;
+            loop {
+                let mut name = ptr::null_mut();
+                let mut header = ptr::null_mut();
+                let mut data = ptr::null_mut();
+                let mut len = 0;
+                cvt(ffi::PEM_read_bio(
+                    bio.as_ptr(),
+                    &mut name,
+                    &mut header,
+                    &mut data,
+                    &mut len,
+                ))?;
+                let label = CStr::from_ptr(name).to_string_lossy().into_owned();
+                let headers = CStr::from_ptr(header).to_string_lossy().into_owned();
+                ffi::OPENSSL_free(name as *mut _);
+                ffi::OPENSSL_free(header as *mut _);
+                ffi::OPENSSL_free(data as *mut _);
+
+                // skip over any other blocks the same way PEM_read_bio_PrivateKey does
+                if !label.ends_with("PRIVATE KEY") {
+                    continue;
+                }
+
+                let pem_headers = headers
+                    .lines()
+                    .filter_map(|line| {
+                        let idx = line.find(':')?;
+                        Some((
+                            line[..idx].trim().to_string(),
+                            line[idx + 1..].trim().to_string(),
+                        ))
+                    })
+                    .collect();
+                return Ok(EncryptedKeyInfo {
+                    pem_label: Some(label),
+                    pem_headers,
+                });
+            }
+        }
+    }
+}
+
 /// A trait indicating that a key has parameters.
 pub unsafe trait HasParams {}
 
@@ -220,6 +471,13 @@ where
         ffi::i2d_PUBKEY
     }
 
+    /// Serializes an RSA public key into a DER-encoded PKCS#1 RSAPublicKey structure.
+    ///
+    /// Fails if the key is not an RSA key.
+    pub fn public_key_to_der_pkcs1(&self) -> Result<Vec<u8>, ErrorStack> {
+        self.rsa()?.public_key_to_der_pkcs1()
+    }
+
     /// Returns the size of the key.
     ///
     /// This corresponds to the bit length of the modulus of an RSA key, and the bit length of the
@@ -314,6 +572,26 @@ where
         }
     }
 
+    /// Serializes the private key into a DER-encoded unencrypted PKCS#8 PrivateKeyInfo
+    /// structure.
+    #[corresponds(i2d_PKCS8PrivateKey_bio)]
+    pub fn private_key_to_pkcs8(&self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let bio = MemBio::new()?;
+            cvt(ffi::i2d_PKCS8PrivateKey_bio(
+                bio.as_ptr(),
+                self.as_ptr(),
+                ptr::null(),
+                ptr::null_mut(),
+                0,
+                None,
+                ptr::null_mut(),
+            ))?;
+
+            Ok(bio.get_buf().to_owned())
+        }
+    }
+
     /// Serializes a private key into a DER-formatted PKCS#8, using the supplied password to
     /// encrypt the key.
     ///
@@ -549,6 +827,66 @@ impl PKey<Private> {
         ffi::d2i_AutoPrivateKey
     }
 
+    /// Decodes a DER-encoded PKCS#1 RSAPrivateKey structure.
+    pub fn private_key_from_der_pkcs1(der: &[u8]) -> Result<PKey<Private>, ErrorStack> {
+        Rsa::private_key_from_der(der).and_then(PKey::from_rsa)
+    }
+
+    /// Decodes a DER-encoded SEC1 ECPrivateKey structure.
+    pub fn private_key_from_der_sec1(der: &[u8]) -> Result<PKey<Private>, ErrorStack> {
+        EcKey::private_key_from_der(der).and_then(PKey::from_ec_key)
+    }
+
+    /// Deserializes a private key from a PEM-encoded key type specific or PKCS#8 format, using
+    /// `passphrase` if the key is encrypted.
+    ///
+    /// If the key is encrypted and `passphrase` is `None`, this returns
+    /// [`PrivateKeyError::PassphraseRequired`] rather than prompting for a passphrase, so the
+    /// caller can ask the user for one and try again.
+    #[corresponds(PEM_read_bio_PrivateKey)]
+    pub fn private_key_from_pem_opt_passphrase(
+        pem: &[u8],
+        passphrase: Option<&[u8]>,
+    ) -> Result<PKey<Private>, PrivateKeyError> {
+        let required = Cell::new(false);
+        match PKey::private_key_from_pem_callback(pem, passphrase_cb(passphrase, &required)) {
+            Ok(pkey) => Ok(pkey),
+            Err(_) if required.get() => Err(PrivateKeyError::PassphraseRequired(
+                EncryptedKeyInfo::from_pem(pem)?,
+            )),
+            Err(e) => Err(PrivateKeyError::Ssl(e)),
+        }
+    }
+
+    /// Deserializes a DER-formatted PKCS#8 private key, using `passphrase` if the key is
+    /// encrypted.
+    ///
+    /// If the key is encrypted and `passphrase` is `None`, this returns
+    /// [`PrivateKeyError::PassphraseRequired`] rather than prompting for a passphrase, so the
+    /// caller can ask the user for one and try again.
+    #[corresponds(d2i_PKCS8PrivateKey_bio)]
+    pub fn private_key_from_pkcs8_opt_passphrase(
+        der: &[u8],
+        passphrase: Option<&[u8]>,
+    ) -> Result<PKey<Private>, PrivateKeyError> {
+        // d2i_PKCS8PrivateKey_bio only parses the encrypted form
+        #[cfg(not(boringssl))]
+        {
+            if KeyFormat::detect_der(der) == Some(KeyFormat::Pkcs8PrivateKey) {
+                return Ok(PKey::private_key_from_pkcs8(der)?);
+            }
+        }
+
+        let required = Cell::new(false);
+        match PKey::private_key_from_pkcs8_callback(der, passphrase_cb(passphrase, &required)) {
+            Ok(pkey) => Ok(pkey),
+            Err(_) if required.get() => Err(PrivateKeyError::PassphraseRequired(
+                EncryptedKeyInfo::from_der(),
+            )),
+            Err(e) => Err(PrivateKeyError::Ssl(e)),
+        }
+    }
+
     /// Deserializes a DER-formatted PKCS#8 unencrypted private key.
     ///
     /// This method is mainly for interoperability reasons. Encrypted keyfiles should be preferred.
@@ -641,6 +979,25 @@ impl PKey<Private> {
     }
 }
 
+/// Returns a password callback which supplies `passphrase`, or records that a passphrase was
+/// needed in `required` and fails if there is none.
+fn passphrase_cb<'a>(
+    passphrase: Option<&'a [u8]>,
+    required: &'a Cell<bool>,
+) -> impl FnOnce(&mut [u8]) -> Result<usize, ErrorStack> + 'a {
+    move |buf| match passphrase {
+        Some(passphrase) if passphrase.len() <= buf.len() => {
+            buf[..passphrase.len()].copy_from_slice(passphrase);
+            Ok(passphrase.len())
+        }
+        Some(_) => Err(ErrorStack::get()),
+        None => {
+            required.set(true);
+            Err(ErrorStack::get())
+        }
+    }
+}
+
 impl PKey<Public> {
     from_pem! {
         /// Decodes a PEM-encoded SubjectPublicKeyInfo structure.
@@ -660,6 +1017,11 @@ impl PKey<Public> {
         ffi::d2i_PUBKEY
     }
 
+    /// Decodes a DER-encoded PKCS#1 RSAPublicKey structure.
+    pub fn public_key_from_der_pkcs1(der: &[u8]) -> Result<PKey<Public>, ErrorStack> {
+        Rsa::public_key_from_der_pkcs1(der).and_then(PKey::from_rsa)
+    }
+
     /// Creates a public key from its raw byte representation
     ///
     /// Algorithm types that support raw public keys are X25519, ED25519, X448 or ED448
@@ -826,6 +1188,55 @@ mod tests {
         assert!(password_queried);
     }
 
+    #[test]
+    fn test_private_key_from_pem_opt_passphrase() {
+        let key = include_bytes!("../test/key.pem");
+        PKey::private_key_from_pem_opt_passphrase(key, None).unwrap();
+
+        let rsa = Rsa::generate(2048).unwrap();
+        let pem = rsa
+            .private_key_to_pem_passphrase(Cipher::aes_128_cbc(), b"foobar")
+            .unwrap();
+        match PKey::private_key_from_pem_opt_passphrase(&pem, None) {
+            Err(PrivateKeyError::PassphraseRequired(info)) => {
+                assert_eq!(info.pem_label(), Some("RSA PRIVATE KEY"));
+                assert!(info.dek_info().unwrap().starts_with("AES-128-CBC,"));
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+        PKey::private_key_from_pem_opt_passphrase(&pem, Some(b"foobar")).unwrap();
+        assert!(matches!(
+            PKey::private_key_from_pem_opt_passphrase(&pem, Some(b"fizzbuzz")),
+            Err(PrivateKeyError::Ssl(_))
+        ));
+
+        let pkey = PKey::from_rsa(rsa).unwrap();
+        let pem = pkey
+            .private_key_to_pem_pkcs8_passphrase(Cipher::aes_128_cbc(), b"foobar")
+            .unwrap();
+        match PKey::private_key_from_pem_opt_passphrase(&pem, None) {
+            Err(PrivateKeyError::PassphraseRequired(info)) => {
+                assert_eq!(info.pem_label(), Some("ENCRYPTED PRIVATE KEY"));
+                assert!(info.pem_headers().is_empty());
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+        PKey::private_key_from_pem_opt_passphrase(&pem, Some(b"foobar")).unwrap();
+    }
+
+    #[test]
+    fn test_private_key_from_pkcs8_opt_passphrase() {
+        let key = include_bytes!("../test/pkcs8-nocrypt.der");
+        PKey::private_key_from_pkcs8_opt_passphrase(key, None).unwrap();
+
+        let key = include_bytes!("../test/pkcs8.der");
+        match PKey::private_key_from_pkcs8_opt_passphrase(key, None) {
+            Err(PrivateKeyError::PassphraseRequired(info)) => assert_eq!(info.pem_label(), None),
+            r => panic!("unexpected result {:?}", r),
+        }
+        PKey::private_key_from_pkcs8_opt_passphrase(key, Some(b"mypass")).unwrap();
+    }
+
     #[test]
     fn test_private_key_from_pem() {
         let key = include_bytes!("../test/key.pem");
@@ -931,6 +1342,114 @@ mod tests {
         assert_eq!(ec_key.private_key(), ec_key_.private_key());
     }
 
+    #[test]
+    fn test_key_format_conversions() {
+        let rsa = Rsa::private_key_from_pem(include_bytes!("../test/rsa.pem")).unwrap();
+        let pkcs1 = rsa.private_key_to_der().unwrap();
+        let pkcs8 = rsa.private_key_to_pkcs8().unwrap();
+        let pkey = PKey::private_key_from_der_pkcs1(&pkcs1).unwrap();
+        assert_eq!(pkey.private_key_to_pkcs8().unwrap(), pkcs8);
+        let rsa_ = Rsa::private_key_from_pkcs8(&pkcs8).unwrap();
+        assert_eq!(rsa_.private_key_to_der().unwrap(), pkcs1);
+
+        let pkcs1 = rsa.public_key_to_der_pkcs1().unwrap();
+        let public = PKey::public_key_from_der_pkcs1(&pkcs1).unwrap();
+        assert_eq!(public.public_key_to_der_pkcs1().unwrap(), pkcs1);
+        assert_eq!(
+            public.public_key_to_der().unwrap(),
+            rsa.public_key_to_der().unwrap()
+        );
+
+        let group = crate::ec::EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ec_key = EcKey::generate(&group).unwrap();
+        let sec1 = ec_key.private_key_to_der().unwrap();
+        let pkcs8 = ec_key.private_key_to_pkcs8().unwrap();
+        let pkey = PKey::private_key_from_der_sec1(&sec1).unwrap();
+        assert_eq!(pkey.private_key_to_pkcs8().unwrap(), pkcs8);
+        let ec_key_ = EcKey::private_key_from_pkcs8(&pkcs8).unwrap();
+        assert_eq!(ec_key.private_key(), ec_key_.private_key());
+
+        assert!(EcKey::private_key_from_pkcs8(&rsa.private_key_to_pkcs8().unwrap()).is_err());
+        assert!(pkey.public_key_to_der_pkcs1().is_err());
+    }
+
+    #[test]
+    #[cfg(not(boringssl))]
+    fn test_key_format_detect_der() {
+        let rsa = Rsa::private_key_from_pem(include_bytes!("../test/rsa.pem")).unwrap();
+        let group = crate::ec::EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ec_key = EcKey::generate(&group).unwrap();
+        let pkey = PKey::from_rsa(rsa.clone()).unwrap();
+
+        let cases = [
+            (
+                rsa.private_key_to_der().unwrap(),
+                KeyFormat::Pkcs1PrivateKey,
+            ),
+            (
+                rsa.public_key_to_der_pkcs1().unwrap(),
+                KeyFormat::Pkcs1PublicKey,
+            ),
+            (
+                ec_key.private_key_to_der().unwrap(),
+                KeyFormat::Sec1PrivateKey,
+            ),
+            (
+                ec_key.private_key_to_pkcs8().unwrap(),
+                KeyFormat::Pkcs8PrivateKey,
+            ),
+            (
+                pkey.private_key_to_pkcs8().unwrap(),
+                KeyFormat::Pkcs8PrivateKey,
+            ),
+            (
+                pkey.private_key_to_pkcs8_passphrase(Cipher::aes_128_cbc(), b"mypass")
+                    .unwrap(),
+                KeyFormat::Pkcs8EncryptedPrivateKey,
+            ),
+            (
+                rsa.public_key_to_der().unwrap(),
+                KeyFormat::SubjectPublicKeyInfo,
+            ),
+            (
+                ec_key.public_key_to_der().unwrap(),
+                KeyFormat::SubjectPublicKeyInfo,
+            ),
+        ];
+        for (der, format) in &cases {
+            assert_eq!(KeyFormat::detect_der(der), Some(*format));
+        }
+
+        let dsa = PKey::private_key_from_pem(include_bytes!("../test/dsa.pem")).unwrap();
+        assert_eq!(
+            KeyFormat::detect_der(&dsa.private_key_to_der().unwrap()),
+            None
+        );
+        assert_eq!(KeyFormat::detect_der(b"not a key"), None);
+    }
+
+    #[test]
+    fn test_key_format_detect_pem() {
+        assert_eq!(
+            KeyFormat::detect_pem(include_bytes!("../test/rsa.pem")),
+            Some(KeyFormat::Pkcs1PrivateKey)
+        );
+        assert_eq!(
+            KeyFormat::detect_pem(include_bytes!("../test/key.pem")),
+            Some(KeyFormat::Pkcs8PrivateKey)
+        );
+        assert_eq!(
+            KeyFormat::detect_pem(include_bytes!("../test/key.pem.pub")),
+            Some(KeyFormat::SubjectPublicKeyInfo)
+        );
+        assert_eq!(
+            KeyFormat::detect_pem(include_bytes!("../test/dsa.pem")),
+            None
+        );
+        assert!(KeyFormat::Sec1PrivateKey.is_private());
+        assert!(!KeyFormat::Pkcs1PublicKey.is_private());
+    }
+
     #[test]
     #[cfg(not(boringssl))]
     fn test_dh_conversion() {
@@ -11,6 +11,10 @@
 //! rand_bytes(&mut buf).unwrap();
 //! ```
 use libc::{c_int, size_t};
+#[cfg(all(unix, not(boringssl)))]
+use once_cell::sync::OnceCell;
+#[cfg(all(unix, not(boringssl)))]
+use std::io;
 
 use crate::cvt;
 use crate::error::ErrorStack;
@@ -53,6 +57,53 @@ pub fn keep_random_devices_open(keep: bool) {
     }
 }
 
+/// Reseeds the random number generator from the operating system's entropy source.
+///
+/// A process which is resumed from a virtual machine snapshot, or is cloned in some other way
+/// which OpenSSL cannot detect, would otherwise produce the same random bytes as every other
+/// process resumed from the same snapshot. This should be called as soon as possible after such
+/// an event, before any secrets are generated.
+#[corresponds(RAND_poll)]
+#[cfg(not(boringssl))]
+pub fn reseed() -> Result<(), ErrorStack> {
+    unsafe {
+        ffi::init();
+        cvt(ffi::RAND_poll()).map(|_| ())
+    }
+}
+
+/// Registers a `pthread_atfork` handler which reseeds the random number generator in the child
+/// process after every call to `fork`.
+///
+/// OpenSSL 1.1.1 and newer reseed automatically after a fork, but older versions do not, so a
+/// server which initializes this crate before forking its workers could otherwise hand every
+/// worker the same random number generator state.
+///
+/// The handler runs in the child before `fork` returns, and uses OpenSSL's locks. As with other
+/// fork handlers, the process must not fork while another thread may be using OpenSSL. Calling
+/// this more than once has no further effect.
+#[cfg(all(unix, not(boringssl)))]
+pub fn reseed_on_fork() -> Result<(), io::Error> {
+    static REGISTERED: OnceCell<c_int> = OnceCell::new();
+
+    ffi::init();
+    let r =
+        *REGISTERED.get_or_init(|| unsafe { libc::pthread_atfork(None, None, Some(reseed_child)) });
+    if r == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::from_raw_os_error(r))
+    }
+}
+
+#[cfg(all(unix, not(boringssl)))]
+unsafe extern "C" fn reseed_child() {
+    if ffi::RAND_poll() != 1 {
+        // nothing can be reported from here; later calls will fail if the generator is unusable
+        ffi::ERR_clear_error();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::rand_bytes;
@@ -62,4 +113,20 @@ mod tests {
         let mut buf = [0; 32];
         rand_bytes(&mut buf).unwrap();
     }
+
+    #[test]
+    #[cfg(not(boringssl))]
+    fn test_reseed() {
+        super::reseed().unwrap();
+
+        let mut buf = [0; 32];
+        rand_bytes(&mut buf).unwrap();
+    }
+
+    #[test]
+    #[cfg(all(unix, not(boringssl)))]
+    fn test_reseed_on_fork() {
+        super::reseed_on_fork().unwrap();
+        super::reseed_on_fork().unwrap();
+    }
 }
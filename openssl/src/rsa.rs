@@ -32,7 +32,7 @@ use std::ptr;
 
 use crate::bn::{BigNum, BigNumRef};
 use crate::error::ErrorStack;
-use crate::pkey::{HasPrivate, HasPublic, Private, Public};
+use crate::pkey::{HasPrivate, HasPublic, PKey, Private, Public};
 use crate::util::ForeignTypeRefExt;
 use crate::{cvt, cvt_n, cvt_p};
 use openssl_macros::corresponds;
@@ -121,6 +121,11 @@ where
         ffi::i2d_RSAPrivateKey
     }
 
+    /// Serializes the private key to a DER-encoded unencrypted PKCS#8 PrivateKeyInfo structure.
+    pub fn private_key_to_pkcs8(&self) -> Result<Vec<u8>, ErrorStack> {
+        PKey::from_rsa(self.to_owned())?.private_key_to_pkcs8()
+    }
+
     /// Decrypts data using the private key, returning the number of decrypted bytes.
     ///
     /// # Panics
@@ -566,6 +571,13 @@ impl Rsa<Private> {
         Rsa<Private>,
         ffi::d2i_RSAPrivateKey
     }
+
+    /// Decodes a DER-encoded unencrypted PKCS#8 PrivateKeyInfo structure.
+    ///
+    /// Fails if the structure does not contain an RSA key.
+    pub fn private_key_from_pkcs8(der: &[u8]) -> Result<Rsa<Private>, ErrorStack> {
+        PKey::private_key_from_pkcs8(der)?.rsa()
+    }
 }
 
 impl<T> fmt::Debug for Rsa<T> {
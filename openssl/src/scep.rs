@@ -0,0 +1,582 @@
+//! Simple Certificate Enrollment Protocol messages.
+//!
+//! SCEP ([RFC 8894]) is a certificate enrollment protocol commonly used by network equipment and
+//! mobile device management systems. Every request and response is a `pkiMessage`: a PKCS#7
+//! `signedData` structure whose content is itself a PKCS#7 `envelopedData` structure, with the
+//! protocol state carried in signed attributes of the signer.
+//!
+//! A message is built by enveloping the request (typically a DER-encoded certificate request)
+//! for the CA with [`envelope`], and signing the result with a [`PkiMessageBuilder`]. Responses
+//! are checked and parsed with [`PkiMessage::verify`]. Issued certificates are returned in a
+//! degenerate "certs-only" PKCS#7 structure, which [`PkiMessage::decrypt_certificates`] unpacks.
+//!
+//! [RFC 8894]: https://www.rfc-editor.org/rfc/rfc8894
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::{c_int, c_void};
+use once_cell::sync::OnceCell;
+use std::convert::TryInto;
+use std::ffi::CString;
+use std::mem;
+use std::ptr;
+use std::str;
+
+use crate::asn1::{get_object, wrong_tag, Asn1String};
+use crate::bio::MemBioSlice;
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::nid::Nid;
+use crate::pkcs7::{Pkcs7, Pkcs7Flags, Pkcs7Ref, Pkcs7SignerInfoRef};
+use crate::pkey::{HasPrivate, PKeyRef};
+use crate::stack::{Stack, StackRef};
+use crate::symm::Cipher;
+use crate::x509::store::X509StoreRef;
+use crate::x509::{X509Ref, X509};
+use crate::{cvt, cvt_p};
+
+/// The type of operation a `pkiMessage` carries.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MessageType(u32);
+
+impl MessageType {
+    /// A response to a `PKCS_REQ`, `RENEWAL_REQ`, `CERT_POLL`, `GET_CERT` or `GET_CRL` message.
+    pub const CERT_REP: MessageType = MessageType(3);
+
+    /// A request for a certificate replacing one the client already holds.
+    pub const RENEWAL_REQ: MessageType = MessageType(17);
+
+    /// A request for a new certificate.
+    pub const PKCS_REQ: MessageType = MessageType(19);
+
+    /// A poll for the result of a request which is pending manual approval.
+    pub const CERT_POLL: MessageType = MessageType(20);
+
+    /// A request for a previously issued certificate.
+    pub const GET_CERT: MessageType = MessageType(21);
+
+    /// A request for a CRL.
+    pub const GET_CRL: MessageType = MessageType(22);
+
+    /// Constructs a `MessageType` from a raw value.
+    pub fn from_raw(raw: u32) -> MessageType {
+        MessageType(raw)
+    }
+
+    /// Returns the raw value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u32 {
+        self.0
+    }
+}
+
+/// The status of a request, reported in a `CERT_REP` message.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PkiStatus(u32);
+
+impl PkiStatus {
+    /// The request was granted.
+    pub const SUCCESS: PkiStatus = PkiStatus(0);
+
+    /// The request was rejected. The reason is given by the message's `failInfo`.
+    pub const FAILURE: PkiStatus = PkiStatus(2);
+
+    /// The request is awaiting manual approval.
+    pub const PENDING: PkiStatus = PkiStatus(3);
+
+    /// Constructs a `PkiStatus` from a raw value.
+    pub fn from_raw(raw: u32) -> PkiStatus {
+        PkiStatus(raw)
+    }
+
+    /// Returns the raw value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u32 {
+        self.0
+    }
+}
+
+/// The reason a request was rejected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FailInfo(u32);
+
+impl FailInfo {
+    /// Unrecognized or unsupported algorithm.
+    pub const BAD_ALG: FailInfo = FailInfo(0);
+
+    /// Integrity check of the message failed.
+    pub const BAD_MESSAGE_CHECK: FailInfo = FailInfo(1);
+
+    /// The transaction was not permitted or supported.
+    pub const BAD_REQUEST: FailInfo = FailInfo(2);
+
+    /// The signing time of the message was not close enough to the system time.
+    pub const BAD_TIME: FailInfo = FailInfo(3);
+
+    /// No certificate could be identified matching the provided criteria.
+    pub const BAD_CERT_ID: FailInfo = FailInfo(4);
+
+    /// Constructs a `FailInfo` from a raw value.
+    pub fn from_raw(raw: u32) -> FailInfo {
+        FailInfo(raw)
+    }
+
+    /// Returns the raw value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u32 {
+        self.0
+    }
+}
+
+/// The `Nid`s of the SCEP signed attributes, which OpenSSL does not know about by default.
+struct Nids {
+    message_type: Nid,
+    pki_status: Nid,
+    fail_info: Nid,
+    sender_nonce: Nid,
+    recipient_nonce: Nid,
+    transaction_id: Nid,
+}
+
+fn nids() -> Result<&'static Nids, ErrorStack> {
+    static NIDS: OnceCell<Nids> = OnceCell::new();
+
+    NIDS.get_or_try_init(|| {
+        Ok(Nids {
+            message_type: nid("2.16.840.1.113733.1.9.2", "messageType")?,
+            pki_status: nid("2.16.840.1.113733.1.9.3", "pkiStatus")?,
+            fail_info: nid("2.16.840.1.113733.1.9.4", "failInfo")?,
+            sender_nonce: nid("2.16.840.1.113733.1.9.5", "senderNonce")?,
+            recipient_nonce: nid("2.16.840.1.113733.1.9.6", "recipientNonce")?,
+            transaction_id: nid("2.16.840.1.113733.1.9.7", "transactionID")?,
+        })
+    })
+}
+
+/// Looks up the `Nid` of `oid`, registering it if it is not already known.
+fn nid(oid: &str, name: &str) -> Result<Nid, ErrorStack> {
+    let raw = unsafe {
+        ffi::init();
+        let oid = CString::new(oid).unwrap();
+        ffi::OBJ_txt2nid(oid.as_ptr())
+    };
+    if raw == ffi::NID_undef {
+        Nid::create(oid, name, name)
+    } else {
+        Ok(Nid::from_raw(raw))
+    }
+}
+
+/// Envelopes `content` for `recipients`, producing the DER-encoded PKCS#7 `envelopedData`
+/// structure which forms the content of a `pkiMessage`.
+///
+/// Requests are enveloped for the CA (or its designated RA) certificate, and responses for the
+/// certificate the client signed its request with.
+pub fn envelope(
+    content: &[u8],
+    recipients: &StackRef<X509>,
+    cipher: Cipher,
+) -> Result<Vec<u8>, ErrorStack> {
+    Pkcs7::encrypt(recipients, content, cipher, Pkcs7Flags::BINARY)?.to_der()
+}
+
+/// A builder for signed `pkiMessage` structures.
+pub struct PkiMessageBuilder {
+    message_type: MessageType,
+    transaction_id: String,
+    sender_nonce: Vec<u8>,
+    recipient_nonce: Option<Vec<u8>>,
+    pki_status: Option<PkiStatus>,
+    fail_info: Option<FailInfo>,
+}
+
+impl PkiMessageBuilder {
+    /// Creates a builder for a message of the specified type.
+    ///
+    /// `transaction_id` must be the same for every message of a single enrollment, and
+    /// `sender_nonce` should be 16 random bytes which are fresh for each message.
+    pub fn new(
+        message_type: MessageType,
+        transaction_id: &str,
+        sender_nonce: &[u8],
+    ) -> PkiMessageBuilder {
+        PkiMessageBuilder {
+            message_type,
+            transaction_id: transaction_id.to_string(),
+            sender_nonce: sender_nonce.to_vec(),
+            recipient_nonce: None,
+            pki_status: None,
+            fail_info: None,
+        }
+    }
+
+    /// Sets the `recipientNonce` attribute, which echoes the `senderNonce` of the request a
+    /// `CERT_REP` message is responding to.
+    pub fn recipient_nonce(&mut self, nonce: &[u8]) -> &mut PkiMessageBuilder {
+        self.recipient_nonce = Some(nonce.to_vec());
+        self
+    }
+
+    /// Sets the `pkiStatus` attribute, which is required in `CERT_REP` messages.
+    pub fn pki_status(&mut self, status: PkiStatus) -> &mut PkiMessageBuilder {
+        self.pki_status = Some(status);
+        self
+    }
+
+    /// Sets the `failInfo` attribute, which is required in `CERT_REP` messages with a status of
+    /// `FAILURE`.
+    pub fn fail_info(&mut self, fail_info: FailInfo) -> &mut PkiMessageBuilder {
+        self.fail_info = Some(fail_info);
+        self
+    }
+
+    /// Signs `message_data` with `pkey`, producing a `pkiMessage`.
+    ///
+    /// `message_data` is normally the output of [`envelope`], and is empty for `CERT_REP`
+    /// messages which do not have a status of `SUCCESS`. `signcert` is included in the message.
+    pub fn sign<PT>(
+        &self,
+        signcert: &X509Ref,
+        pkey: &PKeyRef<PT>,
+        md: MessageDigest,
+        message_data: &[u8],
+    ) -> Result<Pkcs7, ErrorStack>
+    where
+        PT: HasPrivate,
+    {
+        let nids = nids()?;
+        let flags = Pkcs7Flags::BINARY;
+        let input_bio = MemBioSlice::new(message_data)?;
+        unsafe {
+            let pkcs7 = cvt_p(ffi::PKCS7_sign(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                (flags | Pkcs7Flags::PARTIAL).bits(),
+            ))
+            .map(|p| Pkcs7::from_ptr(p))?;
+            let signer_info = cvt_p(ffi::PKCS7_sign_add_signer(
+                pkcs7.as_ptr(),
+                signcert.as_ptr(),
+                pkey.as_ptr(),
+                md.as_ptr(),
+                (flags | Pkcs7Flags::NOSMIMECAP).bits(),
+            ))?;
+
+            let message_type = self.message_type.0.to_string();
+            add_attribute(
+                signer_info,
+                nids.message_type,
+                ffi::V_ASN1_PRINTABLESTRING,
+                message_type.as_bytes(),
+            )?;
+            add_attribute(
+                signer_info,
+                nids.transaction_id,
+                ffi::V_ASN1_PRINTABLESTRING,
+                self.transaction_id.as_bytes(),
+            )?;
+            add_attribute(
+                signer_info,
+                nids.sender_nonce,
+                ffi::V_ASN1_OCTET_STRING,
+                &self.sender_nonce,
+            )?;
+            if let Some(nonce) = &self.recipient_nonce {
+                add_attribute(
+                    signer_info,
+                    nids.recipient_nonce,
+                    ffi::V_ASN1_OCTET_STRING,
+                    nonce,
+                )?;
+            }
+            if let Some(status) = self.pki_status {
+                add_attribute(
+                    signer_info,
+                    nids.pki_status,
+                    ffi::V_ASN1_PRINTABLESTRING,
+                    status.0.to_string().as_bytes(),
+                )?;
+            }
+            if let Some(fail_info) = self.fail_info {
+                add_attribute(
+                    signer_info,
+                    nids.fail_info,
+                    ffi::V_ASN1_PRINTABLESTRING,
+                    fail_info.0.to_string().as_bytes(),
+                )?;
+            }
+
+            cvt(ffi::PKCS7_final(
+                pkcs7.as_ptr(),
+                input_bio.as_ptr(),
+                flags.bits(),
+            ))?;
+            Ok(pkcs7)
+        }
+    }
+}
+
+/// Adds a signed attribute with a single string value of the specified type.
+unsafe fn add_attribute(
+    signer_info: *mut ffi::PKCS7_SIGNER_INFO,
+    nid: Nid,
+    ty: c_int,
+    value: &[u8],
+) -> Result<(), ErrorStack> {
+    let string = cvt_p(ffi::ASN1_STRING_type_new(ty)).map(|p| Asn1String::from_ptr(p))?;
+    cvt(ffi::ASN1_STRING_set(
+        string.as_ptr(),
+        value.as_ptr() as *const c_void,
+        value.len().try_into().unwrap(),
+    ))?;
+    cvt(ffi::PKCS7_add_signed_attribute(
+        signer_info,
+        nid.as_raw(),
+        ty,
+        string.as_ptr() as *mut c_void,
+    ))?;
+    mem::forget(string);
+    Ok(())
+}
+
+/// A `pkiMessage` whose signature has been verified.
+pub struct PkiMessage {
+    pkcs7: Pkcs7,
+    message_data: Vec<u8>,
+}
+
+impl PkiMessage {
+    /// Verifies the signature of a `pkiMessage`, returning the verified message.
+    ///
+    /// The arguments are passed to [`Pkcs7Ref::verify`]. Clients typically enroll with a
+    /// self-signed certificate, so servers will generally need to pass `Pkcs7Flags::NOVERIFY`
+    /// and authenticate the request by other means, such as its `challengePassword`.
+    ///
+    /// [`Pkcs7Ref::verify`]: ../pkcs7/struct.Pkcs7Ref.html#method.verify
+    pub fn verify(
+        pkcs7: Pkcs7,
+        certs: &StackRef<X509>,
+        store: &X509StoreRef,
+        flags: Pkcs7Flags,
+    ) -> Result<PkiMessage, ErrorStack> {
+        let mut message_data = vec![];
+        pkcs7.verify(
+            certs,
+            store,
+            None,
+            Some(&mut message_data),
+            flags | Pkcs7Flags::BINARY,
+        )?;
+        Ok(PkiMessage {
+            pkcs7,
+            message_data,
+        })
+    }
+
+    /// Returns the underlying PKCS#7 structure.
+    pub fn pkcs7(&self) -> &Pkcs7Ref {
+        &self.pkcs7
+    }
+
+    /// Returns the signed content of the message.
+    ///
+    /// This is normally a DER-encoded PKCS#7 `envelopedData` structure, and is empty for
+    /// `CERT_REP` messages which do not have a status of `SUCCESS`.
+    pub fn message_data(&self) -> &[u8] {
+        &self.message_data
+    }
+
+    /// Returns the `messageType` attribute.
+    pub fn message_type(&self) -> Option<MessageType> {
+        let nid = nids().ok()?.message_type;
+        self.number(nid).map(MessageType)
+    }
+
+    /// Returns the `transactionID` attribute.
+    pub fn transaction_id(&self) -> Option<String> {
+        let nid = nids().ok()?.transaction_id;
+        let value = self.attribute(nid, ffi::V_ASN1_PRINTABLESTRING)?;
+        String::from_utf8(value).ok()
+    }
+
+    /// Returns the `senderNonce` attribute.
+    pub fn sender_nonce(&self) -> Option<Vec<u8>> {
+        let nid = nids().ok()?.sender_nonce;
+        self.attribute(nid, ffi::V_ASN1_OCTET_STRING)
+    }
+
+    /// Returns the `recipientNonce` attribute.
+    pub fn recipient_nonce(&self) -> Option<Vec<u8>> {
+        let nid = nids().ok()?.recipient_nonce;
+        self.attribute(nid, ffi::V_ASN1_OCTET_STRING)
+    }
+
+    /// Returns the `pkiStatus` attribute.
+    pub fn pki_status(&self) -> Option<PkiStatus> {
+        let nid = nids().ok()?.pki_status;
+        self.number(nid).map(PkiStatus)
+    }
+
+    /// Returns the `failInfo` attribute.
+    pub fn fail_info(&self) -> Option<FailInfo> {
+        let nid = nids().ok()?.fail_info;
+        self.number(nid).map(FailInfo)
+    }
+
+    /// Decrypts the `envelopedData` content of the message with the recipient's key and
+    /// certificate.
+    pub fn decrypt<PT>(&self, pkey: &PKeyRef<PT>, cert: &X509Ref) -> Result<Vec<u8>, ErrorStack>
+    where
+        PT: HasPrivate,
+    {
+        Pkcs7::from_der(&self.message_data)?.decrypt(pkey, cert, Pkcs7Flags::BINARY)
+    }
+
+    /// Decrypts the content of a successful `CERT_REP` message, returning the certificates
+    /// carried in its degenerate "certs-only" PKCS#7 structure.
+    pub fn decrypt_certificates<PT>(
+        &self,
+        pkey: &PKeyRef<PT>,
+        cert: &X509Ref,
+    ) -> Result<Stack<X509>, ErrorStack>
+    where
+        PT: HasPrivate,
+    {
+        let certs_only = Pkcs7::from_der(&self.decrypt(pkey, cert)?)?;
+        let certs = certs_only
+            .signed()
+            .and_then(|signed| signed.certificates())
+            .ok_or_else(wrong_tag)?;
+
+        let mut out = Stack::new()?;
+        for cert in certs {
+            out.push(cert.to_owned())?;
+        }
+        Ok(out)
+    }
+
+    fn signer_info(&self) -> Option<&Pkcs7SignerInfoRef> {
+        self.pkcs7.signer_info()?.get(0)
+    }
+
+    /// Returns the contents of the single string value of a signed attribute of type `ty`.
+    fn attribute(&self, nid: Nid, ty: c_int) -> Option<Vec<u8>> {
+        let signer_info = self.signer_info()?;
+        let der = unsafe {
+            let value = ffi::PKCS7_get_signed_attribute(signer_info.as_ptr(), nid.as_raw());
+            if value.is_null() {
+                return None;
+            }
+            let len = ffi::i2d_ASN1_TYPE(value, ptr::null_mut());
+            if len <= 0 {
+                return None;
+            }
+            let mut der = vec![0; len as usize];
+            ffi::i2d_ASN1_TYPE(value, &mut der.as_mut_ptr());
+            der
+        };
+
+        let mut der = &*der;
+        match get_object(&mut der) {
+            Ok((tag, ffi::V_ASN1_UNIVERSAL, contents)) if tag == ty => Some(contents.to_vec()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a signed attribute holding a decimal number as a PrintableString.
+    fn number(&self, nid: Nid) -> Option<u32> {
+        let value = self.attribute(nid, ffi::V_ASN1_PRINTABLESTRING)?;
+        str::from_utf8(&value).ok()?.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::pkey::PKey;
+    use crate::x509::store::X509StoreBuilder;
+
+    #[test]
+    fn pkcs_req_round_trip() {
+        let ca_cert = X509::from_pem(include_bytes!("../test/root-ca.pem")).unwrap();
+        let ca_key = PKey::private_key_from_pem(include_bytes!("../test/root-ca.key")).unwrap();
+        let client_cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let client_key = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+        let csr = b"a DER-encoded certificate request";
+
+        let mut recipients = Stack::new().unwrap();
+        recipients.push(ca_cert.clone()).unwrap();
+        let enveloped = envelope(csr, &recipients, Cipher::aes_256_cbc()).unwrap();
+
+        let nonce = [0x11; 16];
+        let pkcs7 = PkiMessageBuilder::new(MessageType::PKCS_REQ, "transaction-1", &nonce)
+            .sign(
+                &client_cert,
+                &client_key,
+                MessageDigest::sha256(),
+                &enveloped,
+            )
+            .unwrap();
+        let pkcs7 = Pkcs7::from_der(&pkcs7.to_der().unwrap()).unwrap();
+
+        let certs = Stack::new().unwrap();
+        let store = X509StoreBuilder::new().unwrap().build();
+        let message = PkiMessage::verify(pkcs7, &certs, &store, Pkcs7Flags::NOVERIFY).unwrap();
+
+        assert_eq!(message.message_type(), Some(MessageType::PKCS_REQ));
+        assert_eq!(message.transaction_id().as_deref(), Some("transaction-1"));
+        assert_eq!(message.sender_nonce().as_deref(), Some(&nonce[..]));
+        assert_eq!(message.recipient_nonce(), None);
+        assert_eq!(message.pki_status(), None);
+        assert_eq!(message.fail_info(), None);
+        assert_eq!(message.decrypt(&ca_key, &ca_cert).unwrap(), &csr[..]);
+    }
+
+    #[test]
+    fn cert_rep() {
+        let ca_cert = X509::from_pem(include_bytes!("../test/root-ca.pem")).unwrap();
+        let ca_key = PKey::private_key_from_pem(include_bytes!("../test/root-ca.key")).unwrap();
+        let client_cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let client_key = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+
+        let mut recipients = Stack::new().unwrap();
+        recipients.push(client_cert.clone()).unwrap();
+        let enveloped = envelope(
+            include_bytes!("../test/certs-only.p7b"),
+            &recipients,
+            Cipher::aes_128_cbc(),
+        )
+        .unwrap();
+
+        let pkcs7 = PkiMessageBuilder::new(MessageType::CERT_REP, "transaction-1", &[0x22; 16])
+            .recipient_nonce(&[0x11; 16])
+            .pki_status(PkiStatus::SUCCESS)
+            .sign(&ca_cert, &ca_key, MessageDigest::sha256(), &enveloped)
+            .unwrap();
+
+        let certs = Stack::new().unwrap();
+        let store = X509StoreBuilder::new().unwrap().build();
+        let message = PkiMessage::verify(pkcs7, &certs, &store, Pkcs7Flags::NOVERIFY).unwrap();
+
+        assert_eq!(message.message_type(), Some(MessageType::CERT_REP));
+        assert_eq!(message.pki_status(), Some(PkiStatus::SUCCESS));
+        assert_eq!(message.recipient_nonce().as_deref(), Some(&[0x11; 16][..]));
+
+        let issued = message
+            .decrypt_certificates(&client_key, &client_cert)
+            .unwrap();
+        assert_eq!(issued.len(), 2);
+        assert_eq!(issued[0].to_der().unwrap(), client_cert.to_der().unwrap());
+
+        let pkcs7 = PkiMessageBuilder::new(MessageType::CERT_REP, "transaction-1", &[0x33; 16])
+            .pki_status(PkiStatus::FAILURE)
+            .fail_info(FailInfo::BAD_REQUEST)
+            .sign(&ca_cert, &ca_key, MessageDigest::sha256(), &[])
+            .unwrap();
+        let message = PkiMessage::verify(pkcs7, &certs, &store, Pkcs7Flags::NOVERIFY).unwrap();
+        assert_eq!(message.pki_status(), Some(PkiStatus::FAILURE));
+        assert_eq!(message.fail_info(), Some(FailInfo::BAD_REQUEST));
+        assert!(message.message_data().is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+//! Secure Remote Password (SRP) support.
+//!
+//! TLS-SRP ([RFC 5054]) authenticates a client to a server with a password. The server never
+//! stores the password itself, but a verifier derived from it and a random salt, which are
+//! generated when the user enrolls with [`SrpVerifier::generate`] and later handed to OpenSSL
+//! from the callback registered with [`SslContextBuilder::set_srp_username_callback`].
+//!
+//! SRP is deprecated as of OpenSSL 3.0 and is not available in TLS 1.3, so it should only be
+//! used by deployments which need to keep supporting existing SRP clients. The SRP cipher
+//! suites, such as `SRP-AES-256-CBC-SHA`, must be enabled explicitly.
+//!
+//! [RFC 5054]: https://tools.ietf.org/html/rfc5054
+//! [`SslContextBuilder::set_srp_username_callback`]: crate::ssl::SslContextBuilder::set_srp_username_callback
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::bn::BigNum;
+//! use openssl::srp::{SrpGroup, SrpVerifier};
+//!
+//! let group = SrpGroup::rfc5054(2048).unwrap();
+//! let verifier = SrpVerifier::generate("alice", "password123", &group).unwrap();
+//!
+//! // store the salt and verifier alongside the username
+//! let salt = verifier.salt().to_vec();
+//! let value = verifier.verifier().to_vec();
+//!
+//! // and reload them when the user connects
+//! let verifier = SrpVerifier::from_parts(
+//!     BigNum::from_slice(&salt).unwrap(),
+//!     BigNum::from_slice(&value).unwrap(),
+//! );
+//! ```
+use foreign_types::ForeignType;
+use openssl_macros::corresponds;
+use std::ffi::CString;
+use std::ptr;
+
+use crate::bn::{BigNum, BigNumRef};
+use crate::error::ErrorStack;
+use crate::util::ForeignTypeRefExt;
+use crate::{cvt, init};
+
+/// The group parameters of an SRP exchange.
+///
+/// The client and server must use the same group, which the server sends to the client during
+/// the handshake.
+pub struct SrpGroup {
+    n: BigNum,
+    g: BigNum,
+}
+
+impl SrpGroup {
+    /// Returns one of the groups defined in appendix A of RFC 5054, identified by the size of its
+    /// prime in bits.
+    ///
+    /// Returns `None` for sizes other than 1024, 1536, 2048, 3072, 4096, 6144 and 8192. Groups
+    /// smaller than 2048 bits should not be used for new verifiers.
+    #[corresponds(SRP_get_default_gN)]
+    pub fn rfc5054(bits: u32) -> Option<SrpGroup> {
+        init();
+        let id = CString::new(bits.to_string()).unwrap();
+        unsafe {
+            let gn = ffi::SRP_get_default_gN(id.as_ptr());
+            if gn.is_null() {
+                return None;
+            }
+            let n = BigNumRef::from_const_ptr((*gn).N).to_owned().ok()?;
+            let g = BigNumRef::from_const_ptr((*gn).g).to_owned().ok()?;
+            Some(SrpGroup { n, g })
+        }
+    }
+
+    /// Returns the prime modulus `N` of the group.
+    pub fn n(&self) -> &BigNumRef {
+        &self.n
+    }
+
+    /// Returns the generator `g` of the group.
+    pub fn g(&self) -> &BigNumRef {
+        &self.g
+    }
+}
+
+/// The salt and verifier a server stores for an SRP user.
+pub struct SrpVerifier {
+    salt: BigNum,
+    verifier: BigNum,
+}
+
+impl SrpVerifier {
+    /// Derives the verifier of a user's password with a new random salt.
+    #[corresponds(SRP_create_verifier_BN)]
+    pub fn generate(
+        username: &str,
+        password: &str,
+        group: &SrpGroup,
+    ) -> Result<SrpVerifier, ErrorStack> {
+        init();
+        let username = CString::new(username).unwrap();
+        let password = CString::new(password).unwrap();
+        unsafe {
+            let mut salt = ptr::null_mut();
+            let mut verifier = ptr::null_mut();
+            cvt(ffi::SRP_create_verifier_BN(
+                username.as_ptr(),
+                password.as_ptr(),
+                &mut salt,
+                &mut verifier,
+                group.n.as_ptr(),
+                group.g.as_ptr(),
+            ))?;
+            Ok(SrpVerifier {
+                salt: BigNum::from_ptr(salt),
+                verifier: BigNum::from_ptr(verifier),
+            })
+        }
+    }
+
+    /// Creates a verifier from a previously stored salt and verifier.
+    pub fn from_parts(salt: BigNum, verifier: BigNum) -> SrpVerifier {
+        SrpVerifier { salt, verifier }
+    }
+
+    /// Returns the salt of the verifier.
+    pub fn salt(&self) -> &BigNumRef {
+        &self.salt
+    }
+
+    /// Returns the verifier value.
+    pub fn verifier(&self) -> &BigNumRef {
+        &self.verifier
+    }
+}
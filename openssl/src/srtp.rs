@@ -61,4 +61,130 @@ impl SrtpProfileId {
     pub fn as_raw(&self) -> c_ulong {
         self.0
     }
+
+    /// Returns the length of the profile's SRTP master key in bytes, if known.
+    ///
+    /// Lengths are known for the AES counter mode profiles of RFC 5764 and the AES-GCM profiles
+    /// of RFC 7714.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn master_key_len(&self) -> Option<usize> {
+        self.key_salt_len().map(|(key, _)| key)
+    }
+
+    /// Returns the length of the profile's SRTP master salt in bytes, if known.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn master_salt_len(&self) -> Option<usize> {
+        self.key_salt_len().map(|(_, salt)| salt)
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn key_salt_len(&self) -> Option<(usize, usize)> {
+        match *self {
+            SrtpProfileId::SRTP_AES128_CM_SHA1_80 | SrtpProfileId::SRTP_AES128_CM_SHA1_32 => {
+                Some((16, 14))
+            }
+            #[cfg(ossl110)]
+            SrtpProfileId::SRTP_AEAD_AES_128_GCM => Some((16, 12)),
+            #[cfg(ossl110)]
+            SrtpProfileId::SRTP_AEAD_AES_256_GCM => Some((32, 12)),
+            _ => None,
+        }
+    }
+}
+
+/// SRTP master keys and salts derived from a DTLS-SRTP handshake.
+///
+/// Created by [`SslRef::srtp_keying_material`].
+///
+/// [`SslRef::srtp_keying_material`]: crate::ssl::SslRef::srtp_keying_material
+pub struct SrtpKeyingMaterial {
+    profile: SrtpProfileId,
+    is_server: bool,
+    material: Vec<u8>,
+    key_len: usize,
+    salt_len: usize,
+}
+
+impl SrtpKeyingMaterial {
+    /// Splits keying material exported with the `EXTRACTOR-dtls_srtp` label into its keys and
+    /// salts, which RFC 5764 orders as client key, server key, client salt, server salt.
+    pub(crate) fn new(
+        profile: SrtpProfileId,
+        is_server: bool,
+        material: Vec<u8>,
+        key_len: usize,
+        salt_len: usize,
+    ) -> SrtpKeyingMaterial {
+        debug_assert_eq!(material.len(), 2 * (key_len + salt_len));
+        SrtpKeyingMaterial {
+            profile,
+            is_server,
+            material,
+            key_len,
+            salt_len,
+        }
+    }
+
+    /// Returns the SRTP profile the keys were derived for.
+    pub fn profile(&self) -> SrtpProfileId {
+        self.profile
+    }
+
+    /// Returns the master key used to protect packets sent by the client.
+    pub fn client_key(&self) -> &[u8] {
+        &self.material[..self.key_len]
+    }
+
+    /// Returns the master key used to protect packets sent by the server.
+    pub fn server_key(&self) -> &[u8] {
+        &self.material[self.key_len..2 * self.key_len]
+    }
+
+    /// Returns the master salt used to protect packets sent by the client.
+    pub fn client_salt(&self) -> &[u8] {
+        let start = 2 * self.key_len;
+        &self.material[start..start + self.salt_len]
+    }
+
+    /// Returns the master salt used to protect packets sent by the server.
+    pub fn server_salt(&self) -> &[u8] {
+        let start = 2 * self.key_len + self.salt_len;
+        &self.material[start..start + self.salt_len]
+    }
+
+    /// Returns the master key used to protect packets sent by this side of the connection.
+    pub fn local_key(&self) -> &[u8] {
+        if self.is_server {
+            self.server_key()
+        } else {
+            self.client_key()
+        }
+    }
+
+    /// Returns the master salt used to protect packets sent by this side of the connection.
+    pub fn local_salt(&self) -> &[u8] {
+        if self.is_server {
+            self.server_salt()
+        } else {
+            self.client_salt()
+        }
+    }
+
+    /// Returns the master key used to unprotect packets received from the peer.
+    pub fn remote_key(&self) -> &[u8] {
+        if self.is_server {
+            self.client_key()
+        } else {
+            self.server_key()
+        }
+    }
+
+    /// Returns the master salt used to unprotect packets received from the peer.
+    pub fn remote_salt(&self) -> &[u8] {
+        if self.is_server {
+            self.client_salt()
+        } else {
+            self.server_salt()
+        }
+    }
 }
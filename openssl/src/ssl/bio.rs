@@ -5,6 +5,7 @@ use ffi::{
 };
 use libc::{c_char, c_int, c_long, c_void, strlen};
 use std::any::Any;
+use std::cmp;
 use std::io;
 use std::io::prelude::*;
 use std::panic::{catch_unwind, AssertUnwindSafe};
@@ -19,6 +20,7 @@ pub struct StreamState<S> {
     pub error: Option<io::Error>,
     pub panic: Option<Box<dyn Any + Send>>,
     pub dtls_mtu_size: c_long,
+    pub buffered: Vec<u8>,
 }
 
 /// Safe wrapper for `BIO_METHOD`
@@ -33,7 +35,10 @@ impl BioMethod {
 unsafe impl Sync for BioMethod {}
 unsafe impl Send for BioMethod {}
 
-pub fn new<S: Read + Write>(stream: S) -> Result<(*mut BIO, BioMethod), ErrorStack> {
+pub fn new<S: Read + Write>(
+    stream: S,
+    buffered: Vec<u8>,
+) -> Result<(*mut BIO, BioMethod), ErrorStack> {
     let method = BioMethod::new::<S>()?;
 
     let state = Box::new(StreamState {
@@ -41,6 +46,7 @@ pub fn new<S: Read + Write>(stream: S) -> Result<(*mut BIO, BioMethod), ErrorSta
         error: None,
         panic: None,
         dtls_mtu_size: 0,
+        buffered,
     });
 
     unsafe {
@@ -71,6 +77,14 @@ pub unsafe fn get_mut<'a, S: 'a>(bio: *mut BIO) -> &'a mut S {
     &mut state(bio).stream
 }
 
+/// Removes the stream from the BIO, leaving it uninitialized.
+pub unsafe fn take_stream<S>(bio: *mut BIO) -> S {
+    let state = Box::<StreamState<S>>::from_raw(BIO_get_data(bio) as *mut _);
+    BIO_set_data(bio, ptr::null_mut());
+    BIO_set_init(bio, 0);
+    state.stream
+}
+
 pub unsafe fn set_dtls_mtu_size<S>(bio: *mut BIO, mtu_size: usize) {
     if mtu_size as u64 > c_long::max_value() as u64 {
         panic!(
@@ -113,6 +127,13 @@ unsafe extern "C" fn bread<S: Read>(bio: *mut BIO, buf: *mut c_char, len: c_int)
     let state = state::<S>(bio);
     let buf = slice::from_raw_parts_mut(buf as *mut _, len as usize);
 
+    if !state.buffered.is_empty() {
+        let len = cmp::min(buf.len(), state.buffered.len());
+        buf[..len].copy_from_slice(&state.buffered[..len]);
+        state.buffered.drain(..len);
+        return len as c_int;
+    }
+
     match catch_unwind(AssertUnwindSafe(|| state.stream.read(buf))) {
         Ok(Ok(len)) => len as c_int,
         Ok(Err(err)) => {
@@ -147,6 +168,10 @@ unsafe extern "C" fn ctrl<S: Write>(
     _num: c_long,
     _ptr: *mut c_void,
 ) -> c_long {
+    // the stream may have been removed by `take_stream`
+    if BIO_get_data(bio).is_null() {
+        return 0;
+    }
     let state = state::<S>(bio);
 
     if cmd == BIO_CTRL_FLUSH {
@@ -182,8 +207,9 @@ unsafe extern "C" fn destroy<S>(bio: *mut BIO) -> c_int {
     }
 
     let data = BIO_get_data(bio);
-    assert!(!data.is_null());
-    Box::<StreamState<S>>::from_raw(data as *mut _);
+    if !data.is_null() {
+        Box::<StreamState<S>>::from_raw(data as *mut _);
+    }
     BIO_set_data(bio, ptr::null_mut());
     BIO_set_init(bio, 0);
     1
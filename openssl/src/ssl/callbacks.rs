@@ -3,9 +3,9 @@ use foreign_types::ForeignType;
 use foreign_types::ForeignTypeRef;
 #[cfg(any(ossl111, not(osslconf = "OPENSSL_NO_PSK")))]
 use libc::c_char;
+use libc::{c_int, c_uchar, c_uint, c_void, size_t};
 #[cfg(ossl111)]
-use libc::size_t;
-use libc::{c_int, c_uchar, c_uint, c_void};
+use std::any::Any;
 #[cfg(any(ossl111, not(osslconf = "OPENSSL_NO_PSK")))]
 use std::ffi::CStr;
 use std::mem;
@@ -19,20 +19,27 @@ use crate::dh::Dh;
 #[cfg(all(ossl101, not(ossl110)))]
 use crate::ec::EcKey;
 use crate::error::ErrorStack;
-use crate::pkey::Params;
+#[cfg(ossl111)]
+use crate::hash::MessageDigest;
+use crate::pkey::{PKey, Params, Private};
 #[cfg(any(ossl102, libressl261))]
 use crate::ssl::AlpnError;
+#[cfg(ossl102)]
+use crate::ssl::CertCallbackResponse;
 use crate::ssl::{
-    try_get_session_ctx_index, SniError, Ssl, SslAlert, SslContext, SslContextRef, SslRef,
-    SslSession, SslSessionRef,
+    try_get_session_ctx_index, SniError, Ssl, SslAlert, SslContext, SslContextRef, SslInfoEvent,
+    SslMessage, SslRef, SslSession, SslSessionRef,
 };
 #[cfg(ossl111)]
 use crate::ssl::{ClientHelloResponse, ExtensionContext};
-#[cfg(ossl111)]
+#[cfg(boringssl)]
+use crate::ssl::{QuicEncryptionLevel, QuicMethod, SslCipherRef};
+#[cfg(ossl110)]
+use crate::ssl::{SslSecurityCheck, SslSecurityOp};
 use crate::util::ForeignTypeRefExt;
 #[cfg(ossl111)]
 use crate::x509::X509Ref;
-use crate::x509::{X509StoreContext, X509StoreContextRef};
+use crate::x509::{X509StoreContext, X509StoreContextRef, X509};
 
 pub extern "C" fn raw_verify<F>(preverify_ok: c_int, x509_ctx: *mut ffi::X509_STORE_CTX) -> c_int
 where
@@ -56,6 +63,18 @@ where
     }
 }
 
+pub extern "C" fn raw_cert_verify<F>(x509_ctx: *mut ffi::X509_STORE_CTX, arg: *mut c_void) -> c_int
+where
+    F: Fn(&mut X509StoreContextRef) -> bool + 'static + Sync + Send,
+{
+    unsafe {
+        let ctx = X509StoreContextRef::from_ptr_mut(x509_ctx);
+        let callback = &*(arg as *const F);
+
+        callback(ctx) as c_int
+    }
+}
+
 #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
 pub extern "C" fn raw_client_psk<F>(
     ssl: *mut ffi::SSL,
@@ -176,6 +195,30 @@ where
     }
 }
 
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+pub unsafe extern "C" fn raw_srp_username<F>(
+    ssl: *mut ffi::SSL,
+    ad: *mut c_int,
+    _arg: *mut c_void,
+) -> c_int
+where
+    F: Fn(&mut SslRef) -> Result<(), SslAlert> + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: srp username callback missing") as *const F;
+
+    match (*callback)(ssl) {
+        Ok(()) => ffi::SSL_ERROR_NONE,
+        Err(alert) => {
+            *ad = alert.0;
+            ffi::SSL3_AL_FATAL
+        }
+    }
+}
+
 #[cfg(any(ossl102, libressl261))]
 pub extern "C" fn raw_alpn_select<F>(
     ssl: *mut ffi::SSL,
@@ -441,6 +484,91 @@ where
     callback(ssl, line);
 }
 
+pub unsafe extern "C" fn raw_info<F>(ssl: *const ffi::SSL, where_: c_int, ret: c_int)
+where
+    F: Fn(&SslRef, SslInfoEvent) + 'static + Sync + Send,
+{
+    let event = match SslInfoEvent::from_raw(where_, ret) {
+        Some(event) => event,
+        None => return,
+    };
+    let ssl = SslRef::from_const_ptr(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: info callback missing");
+
+    callback(ssl, event);
+}
+
+#[cfg(ossl110)]
+pub unsafe extern "C" fn raw_security<F>(
+    ssl: *const ffi::SSL,
+    ctx: *const ffi::SSL_CTX,
+    op: c_int,
+    bits: c_int,
+    nid: c_int,
+    other: *mut c_void,
+    _ex: *mut c_void,
+) -> c_int
+where
+    F: Fn(&SslSecurityCheck<'_>) -> bool + 'static + Sync + Send,
+{
+    // Checks made against the context itself have no connection.
+    let ssl = if ssl.is_null() {
+        None
+    } else {
+        Some(SslRef::from_const_ptr(ssl))
+    };
+    let ctx = match ssl {
+        Some(ssl) => ssl.ssl_context(),
+        None => SslContextRef::from_const_ptr(ctx),
+    };
+    let callback = ctx
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: security callback missing");
+    let check = SslSecurityCheck {
+        ssl,
+        op: SslSecurityOp(op),
+        bits,
+        nid,
+        other,
+    };
+
+    callback(&check) as c_int
+}
+
+pub unsafe extern "C" fn raw_message<F>(
+    write_p: c_int,
+    version: c_int,
+    content_type: c_int,
+    buf: *const c_void,
+    len: size_t,
+    ssl: *mut ffi::SSL,
+    _arg: *mut c_void,
+) where
+    F: Fn(&SslRef, &SslMessage<'_>) + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: message callback missing");
+    let data = if len == 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(buf as *const u8, len)
+    };
+    let message = SslMessage {
+        sent: write_p != 0,
+        version,
+        content_type,
+        data,
+    };
+
+    callback(ssl, &message);
+}
+
 #[cfg(ossl111)]
 pub unsafe extern "C" fn raw_stateless_cookie_generate<F>(
     ssl: *mut ffi::SSL,
@@ -549,6 +677,13 @@ where
 #[cfg(ossl111)]
 pub struct CustomExtAddState<T>(Option<T>);
 
+/// Owns the custom extension callbacks registered on a context.
+///
+/// Each callback is handed to OpenSSL as the extension's `add_arg` or `parse_arg`, so callbacks of
+/// the same type can be registered for several extension types without clobbering each other.
+#[cfg(ossl111)]
+pub struct CustomExtCallbacks(pub Vec<Box<dyn Any + Sync + Send>>);
+
 #[cfg(ossl111)]
 pub extern "C" fn raw_custom_ext_add<F, T>(
     ssl: *mut ffi::SSL,
@@ -559,7 +694,7 @@ pub extern "C" fn raw_custom_ext_add<F, T>(
     x: *mut ffi::X509,
     chainidx: size_t,
     al: *mut c_int,
-    _: *mut c_void,
+    add_arg: *mut c_void,
 ) -> c_int
 where
     F: Fn(&mut SslRef, ExtensionContext, Option<(usize, &X509Ref)>) -> Result<Option<T>, SslAlert>
@@ -570,10 +705,7 @@ where
 {
     unsafe {
         let ssl = SslRef::from_ptr_mut(ssl);
-        let callback = ssl
-            .ssl_context()
-            .ex_data(SslContext::cached_ex_index::<F>())
-            .expect("BUG: custom ext add callback missing") as *const F;
+        let callback = add_arg as *const F;
         let ectx = ExtensionContext::from_bits_truncate(context);
         let cert = if ectx.contains(ExtensionContext::TLS1_3_CERTIFICATE) {
             Some((chainidx, X509Ref::from_ptr(x)))
@@ -637,7 +769,7 @@ pub extern "C" fn raw_custom_ext_parse<F>(
     x: *mut ffi::X509,
     chainidx: size_t,
     al: *mut c_int,
-    _: *mut c_void,
+    parse_arg: *mut c_void,
 ) -> c_int
 where
     F: Fn(&mut SslRef, ExtensionContext, &[u8], Option<(usize, &X509Ref)>) -> Result<(), SslAlert>
@@ -647,10 +779,7 @@ where
 {
     unsafe {
         let ssl = SslRef::from_ptr_mut(ssl);
-        let callback = ssl
-            .ssl_context()
-            .ex_data(SslContext::cached_ex_index::<F>())
-            .expect("BUG: custom ext parse callback missing") as *const F;
+        let callback = parse_arg as *const F;
         let ectx = ExtensionContext::from_bits_truncate(context);
         let slice = slice::from_raw_parts(input as *const u8, inlen as usize);
         let cert = if ectx.contains(ExtensionContext::TLS1_3_CERTIFICATE) {
@@ -668,6 +797,49 @@ where
     }
 }
 
+pub unsafe extern "C" fn raw_client_cert<F>(
+    ssl: *mut ffi::SSL,
+    x509: *mut *mut ffi::X509,
+    pkey: *mut *mut ffi::EVP_PKEY,
+) -> c_int
+where
+    F: Fn(&mut SslRef) -> Option<(X509, PKey<Private>)> + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: client cert callback missing") as *const F;
+
+    match (*callback)(ssl) {
+        Some((cert, key)) => {
+            *x509 = cert.as_ptr();
+            *pkey = key.as_ptr();
+            mem::forget(cert);
+            mem::forget(key);
+            1
+        }
+        None => 0,
+    }
+}
+
+#[cfg(ossl102)]
+pub unsafe extern "C" fn raw_cert<F>(ssl: *mut ffi::SSL, arg: *mut c_void) -> c_int
+where
+    F: Fn(&mut SslRef) -> Result<CertCallbackResponse, ErrorStack> + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = arg as *const F;
+
+    match (*callback)(ssl) {
+        Ok(r) => r.0,
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
 #[cfg(ossl111)]
 pub unsafe extern "C" fn raw_client_hello<F>(
     ssl: *mut ffi::SSL,
@@ -694,3 +866,292 @@ where
         }
     }
 }
+
+#[cfg(ossl300)]
+pub unsafe extern "C" fn raw_async<F>(_ssl: *mut ffi::SSL, arg: *mut c_void) -> c_int
+where
+    F: Fn() + 'static + Sync + Send,
+{
+    // the connection may be in use by another thread, so only the closure is touched
+    let callback = arg as *const F;
+
+    (*callback)();
+    1
+}
+
+#[cfg(ossl111)]
+pub unsafe extern "C" fn raw_generate_session_ticket<F>(
+    ssl: *mut ffi::SSL,
+    arg: *mut c_void,
+) -> c_int
+where
+    F: Fn(&mut SslRef) -> Result<(), ErrorStack> + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = arg as *const F;
+
+    match (*callback)(ssl) {
+        Ok(()) => 1,
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+/// The identity most recently returned by a PSK use session callback.
+///
+/// OpenSSL holds on to the identity pointer after the callback returns, so it is kept alive in the
+/// connection's ex data.
+#[cfg(ossl111)]
+struct PskIdentity(Vec<u8>);
+
+#[cfg(ossl111)]
+pub unsafe extern "C" fn raw_psk_use_session<F>(
+    ssl: *mut ffi::SSL,
+    md: *const ffi::EVP_MD,
+    id: *mut *const c_uchar,
+    idlen: *mut size_t,
+    sess: *mut *mut ffi::SSL_SESSION,
+) -> c_int
+where
+    F: Fn(&mut SslRef, Option<MessageDigest>) -> Result<Option<(Vec<u8>, SslSession)>, ErrorStack>
+        + 'static
+        + Sync
+        + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: psk use session callback missing") as *const F;
+    let md = if md.is_null() {
+        None
+    } else {
+        Some(MessageDigest::from_ptr(md))
+    };
+
+    match (*callback)(ssl, md) {
+        Ok(Some((identity, session))) => {
+            let idx = Ssl::cached_ex_index::<PskIdentity>();
+            match ssl.ex_data_mut(idx) {
+                Some(old) => old.0 = identity,
+                None => ssl.set_ex_data(idx, PskIdentity(identity)),
+            }
+            let identity = &ssl.ex_data(idx).unwrap().0;
+            *id = identity.as_ptr();
+            *idlen = identity.len();
+            *sess = session.as_ptr();
+            mem::forget(session);
+            1
+        }
+        Ok(None) => {
+            *sess = ptr::null_mut();
+            1
+        }
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+#[cfg(ossl111)]
+pub unsafe extern "C" fn raw_psk_find_session<F>(
+    ssl: *mut ffi::SSL,
+    identity: *const c_uchar,
+    identity_len: size_t,
+    sess: *mut *mut ffi::SSL_SESSION,
+) -> c_int
+where
+    F: Fn(&mut SslRef, &[u8]) -> Result<Option<SslSession>, ErrorStack> + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: psk find session callback missing") as *const F;
+    let identity = slice::from_raw_parts(identity, identity_len);
+
+    match (*callback)(ssl, identity) {
+        Ok(Some(session)) => {
+            *sess = session.as_ptr();
+            mem::forget(session);
+            1
+        }
+        Ok(None) => {
+            *sess = ptr::null_mut();
+            1
+        }
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+#[cfg(boringssl)]
+unsafe fn quic_method(ssl: &SslRef) -> Arc<dyn QuicMethod> {
+    ssl.ex_data(Ssl::cached_ex_index::<Arc<dyn QuicMethod>>())
+        .expect("BUG: quic method missing")
+        .clone()
+}
+
+#[cfg(boringssl)]
+pub unsafe extern "C" fn raw_quic_set_read_secret(
+    ssl: *mut ffi::SSL,
+    level: ffi::ssl_encryption_level_t,
+    cipher: *const ffi::SSL_CIPHER,
+    secret: *const u8,
+    secret_len: usize,
+) -> c_int {
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let method = quic_method(ssl);
+    let cipher = SslCipherRef::from_ptr(cipher as *mut _);
+    let secret = slice::from_raw_parts(secret, secret_len);
+
+    match method.set_read_secret(ssl, QuicEncryptionLevel::from_raw(level), cipher, secret) {
+        Ok(()) => 1,
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+#[cfg(boringssl)]
+pub unsafe extern "C" fn raw_quic_set_write_secret(
+    ssl: *mut ffi::SSL,
+    level: ffi::ssl_encryption_level_t,
+    cipher: *const ffi::SSL_CIPHER,
+    secret: *const u8,
+    secret_len: usize,
+) -> c_int {
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let method = quic_method(ssl);
+    let cipher = SslCipherRef::from_ptr(cipher as *mut _);
+    let secret = slice::from_raw_parts(secret, secret_len);
+
+    match method.set_write_secret(ssl, QuicEncryptionLevel::from_raw(level), cipher, secret) {
+        Ok(()) => 1,
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+#[cfg(boringssl)]
+pub unsafe extern "C" fn raw_quic_add_handshake_data(
+    ssl: *mut ffi::SSL,
+    level: ffi::ssl_encryption_level_t,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let method = quic_method(ssl);
+    let data = slice::from_raw_parts(data, len);
+
+    match method.add_handshake_data(ssl, QuicEncryptionLevel::from_raw(level), data) {
+        Ok(()) => 1,
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+#[cfg(boringssl)]
+pub unsafe extern "C" fn raw_quic_flush_flight(ssl: *mut ffi::SSL) -> c_int {
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let method = quic_method(ssl);
+
+    match method.flush_flight(ssl) {
+        Ok(()) => 1,
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+#[cfg(boringssl)]
+pub unsafe extern "C" fn raw_quic_send_alert(
+    ssl: *mut ffi::SSL,
+    level: ffi::ssl_encryption_level_t,
+    alert: u8,
+) -> c_int {
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let method = quic_method(ssl);
+
+    match method.send_alert(
+        ssl,
+        QuicEncryptionLevel::from_raw(level),
+        SslAlert(alert as c_int),
+    ) {
+        Ok(()) => 1,
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+#[cfg(boringssl)]
+pub unsafe extern "C" fn raw_compress_certificate<F>(
+    ssl: *mut ffi::SSL,
+    out: *mut ffi::CBB,
+    input: *const u8,
+    input_len: usize,
+) -> c_int
+where
+    F: Fn(&mut SslRef, &[u8]) -> Result<Vec<u8>, ErrorStack> + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: certificate compression callback missing") as *const F;
+    let input = slice::from_raw_parts(input, input_len);
+
+    match (*callback)(ssl, input) {
+        Ok(compressed) => ffi::CBB_add_bytes(out, compressed.as_ptr(), compressed.len()),
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+#[cfg(boringssl)]
+pub unsafe extern "C" fn raw_decompress_certificate<F>(
+    ssl: *mut ffi::SSL,
+    out: *mut *mut ffi::CRYPTO_BUFFER,
+    uncompressed_len: usize,
+    input: *const u8,
+    input_len: usize,
+) -> c_int
+where
+    F: Fn(&mut SslRef, &[u8], usize) -> Result<Vec<u8>, ErrorStack> + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: certificate decompression callback missing") as *const F;
+    let input = slice::from_raw_parts(input, input_len);
+
+    match (*callback)(ssl, input, uncompressed_len) {
+        // the peer's claimed length must match exactly
+        Ok(decompressed) if decompressed.len() == uncompressed_len => {
+            *out =
+                ffi::CRYPTO_BUFFER_new(decompressed.as_ptr(), decompressed.len(), ptr::null_mut());
+            !(*out).is_null() as c_int
+        }
+        Ok(_) => 0,
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
@@ -1,14 +1,23 @@
 use cfg_if::cfg_if;
+use std::fmt;
 use std::io::{Read, Write};
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 
 use crate::dh::Dh;
 use crate::error::ErrorStack;
+#[cfg(not(any(boringssl, osslconf = "OPENSSL_NO_OCSP")))]
+use crate::ocsp::OcspResponse;
+#[cfg(not(any(boringssl, osslconf = "OPENSSL_NO_OCSP")))]
+use crate::ssl::StatusType;
+#[cfg(ossl102)]
+use crate::ssl::{CertCallbackResponse, SniResolver};
 use crate::ssl::{
     HandshakeError, Ssl, SslContext, SslContextBuilder, SslContextRef, SslMethod, SslMode,
     SslOptions, SslRef, SslStream, SslVerifyMode,
 };
 use crate::version;
+use crate::x509::{X509Ref, X509VerifyResult};
 
 const FFDHE_2048: &str = "
 -----BEGIN DH PARAMETERS-----
@@ -94,7 +103,8 @@ impl SslConnector {
         Ssl::new(&self.0).map(|ssl| ConnectConfiguration {
             ssl,
             sni: true,
-            verify_hostname: true,
+            hostname_verification: HostnameVerification::Full,
+            verification_hostname: None,
         })
     }
 
@@ -113,6 +123,18 @@ impl SslConnector {
 pub struct SslConnectorBuilder(SslContextBuilder);
 
 impl SslConnectorBuilder {
+    /// Enables the OCSP response callbacks registered per connection with
+    /// [`ConnectConfiguration::set_ocsp_response_callback`].
+    ///
+    /// This installs a status callback on the context, replacing any set with
+    /// [`SslContextBuilder::set_status_callback`] or
+    /// [`SslContextBuilder::set_ocsp_response_callback`]. Connections without a registered
+    /// callback are not affected.
+    #[cfg(not(any(boringssl, osslconf = "OPENSSL_NO_OCSP")))]
+    pub fn enable_ocsp_response_callbacks(&mut self) -> Result<(), ErrorStack> {
+        self.0.set_status_callback(connect_ocsp_status)
+    }
+
     /// Consumes the builder, returning an `SslConnector`.
     pub fn build(self) -> SslConnector {
         SslConnector(self.0.build())
@@ -133,11 +155,81 @@ impl DerefMut for SslConnectorBuilder {
     }
 }
 
+/// A matcher used by [`HostnameVerification::Custom`] to check the peer's certificate against
+/// the domain being connected to.
+pub type HostnameMatcher = Arc<dyn Fn(&str, &X509Ref) -> bool + 'static + Sync + Send>;
+
+/// The hostname verification performed by a `ConnectConfiguration`.
+///
+/// The certificate chain is verified according to the context's configuration in every case.
+#[derive(Clone)]
+pub enum HostnameVerification {
+    /// The peer's certificate must be valid for the domain being connected to.
+    ///
+    /// This is the default.
+    Full,
+    /// The peer's certificate is not checked against the domain.
+    ///
+    /// # Warning
+    ///
+    /// If hostname verification is not used, *any* valid certificate for *any* site will be
+    /// trusted for use from any other. This introduces a significant vulnerability to
+    /// man-in-the-middle attacks.
+    ChainOnly,
+    /// The peer's certificate is checked against the domain by a custom matcher.
+    ///
+    /// The matcher is called with the domain and the leaf certificate once the chain has been
+    /// verified, and returns whether the certificate is acceptable. It replaces any verify
+    /// callback configured on the context for the session.
+    Custom(HostnameMatcher),
+}
+
+impl HostnameVerification {
+    /// Constructs a `Custom` policy from a matcher closure.
+    pub fn custom<F>(matcher: F) -> HostnameVerification
+    where
+        F: Fn(&str, &X509Ref) -> bool + 'static + Sync + Send,
+    {
+        HostnameVerification::Custom(Arc::new(matcher))
+    }
+}
+
+impl fmt::Debug for HostnameVerification {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostnameVerification::Full => fmt.write_str("Full"),
+            HostnameVerification::ChainOnly => fmt.write_str("ChainOnly"),
+            HostnameVerification::Custom(_) => fmt.write_str("Custom(..)"),
+        }
+    }
+}
+
+#[cfg(not(any(boringssl, osslconf = "OPENSSL_NO_OCSP")))]
+#[allow(clippy::type_complexity)]
+struct ConnectOcspCallback(
+    Arc<dyn Fn(&mut SslRef, Option<OcspResponse>) -> Result<bool, ErrorStack> + Sync + Send>,
+);
+
+#[cfg(not(any(boringssl, osslconf = "OPENSSL_NO_OCSP")))]
+fn connect_ocsp_status(ssl: &mut SslRef) -> Result<bool, ErrorStack> {
+    let callback = match ssl.ex_data(Ssl::cached_ex_index::<ConnectOcspCallback>()) {
+        Some(callback) => callback.0.clone(),
+        None => return Ok(true),
+    };
+    let response = ssl.ocsp_response()?;
+    callback(ssl, response)
+}
+
 /// A type which allows for configuration of a client-side TLS session before connection.
+///
+/// Other per-connection settings, such as the ALPN protocols offered with
+/// [`SslRef::set_alpn_protos`], can be changed through the configuration's `SslRef` without
+/// affecting the connector.
 pub struct ConnectConfiguration {
     ssl: Ssl,
     sni: bool,
-    verify_hostname: bool,
+    hostname_verification: HostnameVerification,
+    verification_hostname: Option<String>,
 }
 
 impl ConnectConfiguration {
@@ -170,19 +262,118 @@ impl ConnectConfiguration {
     /// used, *any* valid certificate for *any* site will be trusted for use from any other. This
     /// introduces a significant vulnerability to man-in-the-middle attacks.
     pub fn set_verify_hostname(&mut self, verify_hostname: bool) {
-        self.verify_hostname = verify_hostname;
+        self.hostname_verification = if verify_hostname {
+            HostnameVerification::Full
+        } else {
+            HostnameVerification::ChainOnly
+        };
+    }
+
+    /// A builder-style version of `set_hostname_verification`.
+    pub fn hostname_verification(mut self, policy: HostnameVerification) -> ConnectConfiguration {
+        self.set_hostname_verification(policy);
+        self
+    }
+
+    /// Configures the hostname verification policy used when connecting.
+    ///
+    /// Defaults to `HostnameVerification::Full`.
+    pub fn set_hostname_verification(&mut self, policy: HostnameVerification) {
+        self.hostname_verification = policy;
+    }
+
+    /// A builder-style version of `set_verification_hostname`.
+    pub fn verification_hostname(mut self, hostname: &str) -> ConnectConfiguration {
+        self.set_verification_hostname(hostname);
+        self
+    }
+
+    /// Sets the hostname the server's certificate is verified against, in place of the domain
+    /// passed to `connect`.
+    ///
+    /// The domain passed to `connect` is still used for SNI. This allows a proxy to connect to an
+    /// upstream by address, or through a shared front end, while checking the certificate
+    /// against the name it expects.
+    pub fn set_verification_hostname(&mut self, hostname: &str) {
+        self.verification_hostname = Some(hostname.to_string());
+    }
+
+    /// A builder-style version of `set_use_session_tickets`.
+    pub fn use_session_tickets(mut self, use_tickets: bool) -> ConnectConfiguration {
+        self.set_use_session_tickets(use_tickets);
+        self
+    }
+
+    /// Configures the use of session tickets for this connection.
+    ///
+    /// Disabling tickets sets [`SslOptions::NO_TICKET`], so that a TLS 1.2 client does not offer
+    /// the session ticket extension. TLS 1.3 servers may still issue tickets, which are only used
+    /// if the session is later resumed.
+    ///
+    /// Defaults to the connector's configuration.
+    pub fn set_use_session_tickets(&mut self, use_tickets: bool) {
+        if use_tickets {
+            self.ssl.clear_options(SslOptions::NO_TICKET);
+        } else {
+            self.ssl.set_options(SslOptions::NO_TICKET);
+        }
+    }
+
+    /// Requests a stapled OCSP response from the server and sets a callback to validate it.
+    ///
+    /// This is a per-connection version of [`SslContextBuilder::set_ocsp_response_callback`],
+    /// and behaves in the same way. It relies on a status callback installed on the context by
+    /// [`SslConnectorBuilder::enable_ocsp_response_callbacks`], and the callback is never invoked
+    /// if that was not called or the status callback has since been replaced.
+    #[cfg(not(any(boringssl, osslconf = "OPENSSL_NO_OCSP")))]
+    pub fn set_ocsp_response_callback<F>(&mut self, callback: F) -> Result<(), ErrorStack>
+    where
+        F: Fn(&mut SslRef, Option<OcspResponse>) -> Result<bool, ErrorStack>
+            + 'static
+            + Sync
+            + Send,
+    {
+        self.ssl.set_status_type(StatusType::OCSP)?;
+        self.ssl.set_ex_data(
+            Ssl::cached_ex_index::<ConnectOcspCallback>(),
+            ConnectOcspCallback(Arc::new(callback)),
+        );
+        Ok(())
     }
 
     /// Returns an `Ssl` configured to connect to the provided domain.
     ///
-    /// The domain is used for SNI and hostname verification if enabled.
+    /// The domain is used for SNI, and for hostname verification if enabled and not overridden
+    /// by `set_verification_hostname`.
     pub fn into_ssl(mut self, domain: &str) -> Result<Ssl, ErrorStack> {
         if self.sni {
             self.ssl.set_hostname(domain)?;
         }
 
-        if self.verify_hostname {
-            setup_verify_hostname(&mut self.ssl, domain)?;
+        let verification_hostname = self.verification_hostname.take();
+        let domain = verification_hostname.as_deref().unwrap_or(domain);
+        match self.hostname_verification {
+            HostnameVerification::Full => setup_verify_hostname(&mut self.ssl, domain)?,
+            HostnameVerification::ChainOnly => {}
+            HostnameVerification::Custom(matcher) => {
+                let domain = domain.to_string();
+                let mode = self.ssl.verify_mode();
+                self.ssl
+                    .set_verify_callback(mode, move |preverify_ok, x509_ctx| {
+                        if !preverify_ok || x509_ctx.error_depth() != 0 {
+                            return preverify_ok;
+                        }
+
+                        let ok = match x509_ctx.current_cert() {
+                            Some(cert) => matcher(&domain, cert),
+                            None => false,
+                        };
+                        if !ok {
+                            x509_ctx.set_error(X509VerifyResult::APPLICATION_VERIFICATION);
+                        }
+                        ok
+                    });
+            }
         }
 
         Ok(self.ssl)
@@ -340,6 +531,24 @@ impl SslAcceptor {
 pub struct SslAcceptorBuilder(SslContextBuilder);
 
 impl SslAcceptorBuilder {
+    /// Selects the certificate presented to each client from `resolver`, based on the server name
+    /// it requested.
+    ///
+    /// Clients which do not send a server name, or request one without an entry in the resolver,
+    /// are presented with the certificate configured on the builder. The resolver can be updated
+    /// after the acceptor has been built.
+    ///
+    /// This replaces any callback configured with [`SslContextBuilder::set_cert_callback`].
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[cfg(ossl102)]
+    pub fn set_sni_resolver(&mut self, resolver: Arc<SniResolver>) {
+        self.set_cert_callback(move |ssl| {
+            resolver.install(ssl)?;
+            Ok(CertCallbackResponse::SUCCESS)
+        });
+    }
+
     /// Consumes the builder, returning a `SslAcceptor`.
     pub fn build(self) -> SslAcceptor {
         SslAcceptor(self.0.build())
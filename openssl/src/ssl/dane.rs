@@ -0,0 +1,194 @@
+//! DANE TLSA record types.
+//!
+//! See [RFC 6698] and [RFC 7671] for the semantics of the individual record fields.
+//!
+//! Requires OpenSSL 1.1.0 or newer.
+//!
+//! [RFC 6698]: https://datatracker.ietf.org/doc/html/rfc6698
+//! [RFC 7671]: https://datatracker.ietf.org/doc/html/rfc7671
+use bitflags::bitflags;
+use libc::c_ulong;
+use std::fmt;
+
+use crate::pkey::{PKeyRef, Public};
+use crate::x509::X509Ref;
+
+/// The certificate usage field of a TLSA record.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TlsaUsage(u8);
+
+impl TlsaUsage {
+    /// `PKIX-TA(0)` - the record matches a trust anchor which must also pass PKIX validation.
+    pub const PKIX_TA: TlsaUsage = TlsaUsage(0);
+    /// `PKIX-EE(1)` - the record matches the end-entity certificate which must also pass PKIX
+    /// validation.
+    pub const PKIX_EE: TlsaUsage = TlsaUsage(1);
+    /// `DANE-TA(2)` - the record matches a trust anchor for the peer's chain.
+    pub const DANE_TA: TlsaUsage = TlsaUsage(2);
+    /// `DANE-EE(3)` - the record matches the end-entity certificate directly.
+    pub const DANE_EE: TlsaUsage = TlsaUsage(3);
+
+    /// Constructs a `TlsaUsage` from a raw record value.
+    pub fn from_raw(raw: u8) -> TlsaUsage {
+        TlsaUsage(raw)
+    }
+
+    /// Returns the raw record value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u8 {
+        self.0
+    }
+}
+
+/// The selector field of a TLSA record.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TlsaSelector(u8);
+
+impl TlsaSelector {
+    /// `Cert(0)` - the record matches the full DER-encoded certificate.
+    pub const CERT: TlsaSelector = TlsaSelector(0);
+    /// `SPKI(1)` - the record matches the DER-encoded SubjectPublicKeyInfo of the certificate.
+    pub const SPKI: TlsaSelector = TlsaSelector(1);
+
+    /// Constructs a `TlsaSelector` from a raw record value.
+    pub fn from_raw(raw: u8) -> TlsaSelector {
+        TlsaSelector(raw)
+    }
+
+    /// Returns the raw record value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u8 {
+        self.0
+    }
+}
+
+/// The matching type field of a TLSA record.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TlsaMatchingType(u8);
+
+impl TlsaMatchingType {
+    /// `Full(0)` - the record data is the selected content itself.
+    pub const FULL: TlsaMatchingType = TlsaMatchingType(0);
+    /// `SHA2-256(1)` - the record data is a SHA-256 digest of the selected content.
+    pub const SHA2_256: TlsaMatchingType = TlsaMatchingType(1);
+    /// `SHA2-512(2)` - the record data is a SHA-512 digest of the selected content.
+    pub const SHA2_512: TlsaMatchingType = TlsaMatchingType(2);
+
+    /// Constructs a `TlsaMatchingType` from a raw record value.
+    pub fn from_raw(raw: u8) -> TlsaMatchingType {
+        TlsaMatchingType(raw)
+    }
+
+    /// Returns the raw record value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u8 {
+        self.0
+    }
+}
+
+bitflags! {
+    /// Flags controlling DANE verification.
+    pub struct DaneFlags: c_ulong {
+        /// Do not check the peer's name against a `DANE-EE(3)` certificate.
+        ///
+        /// RFC 7671 specifies that the name checks are skipped for `DANE-EE(3)` matches, which
+        /// is what SMTP clients following RFC 7672 want.
+        const NO_DANE_EE_NAMECHECKS = ffi::DANE_FLAG_NO_DANE_EE_NAMECHECKS;
+    }
+}
+
+/// The TLSA record which authenticated the peer.
+///
+/// Returned by [`SslRef::dane_tlsa`].
+///
+/// [`SslRef::dane_tlsa`]: crate::ssl::SslRef::dane_tlsa
+pub struct DaneTlsa<'a> {
+    pub(crate) depth: u32,
+    pub(crate) usage: TlsaUsage,
+    pub(crate) selector: TlsaSelector,
+    pub(crate) matching_type: TlsaMatchingType,
+    pub(crate) data: &'a [u8],
+}
+
+impl<'a> DaneTlsa<'a> {
+    /// Returns the depth in the peer's chain of the certificate matched by the record.
+    ///
+    /// A depth of 0 corresponds to the peer's end-entity certificate.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Returns the record's certificate usage.
+    pub fn usage(&self) -> TlsaUsage {
+        self.usage
+    }
+
+    /// Returns the record's selector.
+    pub fn selector(&self) -> TlsaSelector {
+        self.selector
+    }
+
+    /// Returns the record's matching type.
+    pub fn matching_type(&self) -> TlsaMatchingType {
+        self.matching_type
+    }
+
+    /// Returns the record's certificate association data.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+impl fmt::Debug for DaneTlsa<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("DaneTlsa")
+            .field("depth", &self.depth)
+            .field("usage", &self.usage)
+            .field("selector", &self.selector)
+            .field("matching_type", &self.matching_type)
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+/// The trust anchor or end-entity certificate which authenticated the peer.
+///
+/// Returned by [`SslRef::dane_authority`].
+///
+/// [`SslRef::dane_authority`]: crate::ssl::SslRef::dane_authority
+pub struct DaneAuthority<'a> {
+    pub(crate) depth: u32,
+    pub(crate) certificate: Option<&'a X509Ref>,
+    pub(crate) public_key: Option<&'a PKeyRef<Public>>,
+}
+
+impl<'a> DaneAuthority<'a> {
+    /// Returns the depth in the peer's chain of the matched certificate.
+    ///
+    /// If the match was a bare `DANE-TA(2)` public key which signed the top of the peer's chain,
+    /// this is one greater than the depth of the last certificate in the chain.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Returns the matched certificate, if the record matched a certificate.
+    pub fn certificate(&self) -> Option<&'a X509Ref> {
+        self.certificate
+    }
+
+    /// Returns the matched public key, if the record matched a `DANE-TA(2)` public key not
+    /// present as a certificate in the peer's chain.
+    pub fn public_key(&self) -> Option<&'a PKeyRef<Public>> {
+        self.public_key
+    }
+}
+
+impl fmt::Debug for DaneAuthority<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("DaneAuthority")
+            .field("depth", &self.depth)
+            .field("certificate", &self.certificate.map(|c| c.subject_name()))
+            .field("public_key", &self.public_key.is_some())
+            .finish()
+    }
+}
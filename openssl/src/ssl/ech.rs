@@ -0,0 +1,147 @@
+//! Encrypted Client Hello server keys.
+//!
+//! Requires BoringSSL.
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::{c_int, c_void};
+use openssl_macros::corresponds;
+use std::ffi::CString;
+use std::mem;
+use std::ptr;
+use std::slice;
+
+use crate::error::ErrorStack;
+use crate::hpke::HpkeKeyRef;
+use crate::{cvt, cvt_p};
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::SSL_ECH_KEYS;
+    fn drop = ffi::SSL_ECH_KEYS_free;
+
+    /// A builder for `SslEchKeys`.
+    pub struct SslEchKeysBuilder;
+    /// Reference to `SslEchKeysBuilder`.
+    pub struct SslEchKeysBuilderRef;
+}
+
+impl SslEchKeysBuilder {
+    /// Creates an empty set of keys.
+    #[corresponds(SSL_ECH_KEYS_new)]
+    pub fn new() -> Result<SslEchKeysBuilder, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::SSL_ECH_KEYS_new()).map(SslEchKeysBuilder)
+        }
+    }
+
+    /// Constructs the `SslEchKeys`.
+    pub fn build(self) -> SslEchKeys {
+        let keys = SslEchKeys(self.0);
+        mem::forget(self);
+        keys
+    }
+}
+
+impl SslEchKeysBuilderRef {
+    /// Adds an `ECHConfig` and the private key corresponding to the public key it contains.
+    ///
+    /// Configs with `is_retry_config` set are sent to clients whose ECH was rejected, so they can
+    /// retry with a current config. Older configs should be kept without the flag while clients
+    /// may still be using them.
+    #[corresponds(SSL_ECH_KEYS_add)]
+    pub fn add(
+        &mut self,
+        is_retry_config: bool,
+        ech_config: &[u8],
+        key: &HpkeKeyRef,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_ECH_KEYS_add(
+                self.as_ptr(),
+                is_retry_config as c_int,
+                ech_config.as_ptr(),
+                ech_config.len(),
+                key.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::SSL_ECH_KEYS;
+    fn drop = ffi::SSL_ECH_KEYS_free;
+
+    /// A set of Encrypted Client Hello configs and private keys used by a server.
+    pub struct SslEchKeys;
+    /// Reference to `SslEchKeys`.
+    pub struct SslEchKeysRef;
+}
+
+impl SslEchKeys {
+    /// Returns a builder for a set of keys.
+    pub fn builder() -> Result<SslEchKeysBuilder, ErrorStack> {
+        SslEchKeysBuilder::new()
+    }
+}
+
+impl SslEchKeysRef {
+    /// Determines if two of the configs share a config ID.
+    ///
+    /// Clients use the config ID to select a key, so duplicates make decryption slower.
+    #[corresponds(SSL_ECH_KEYS_has_duplicate_config_id)]
+    pub fn has_duplicate_config_id(&self) -> bool {
+        unsafe { ffi::SSL_ECH_KEYS_has_duplicate_config_id(self.as_ptr()) != 0 }
+    }
+
+    /// Returns the retry configs as an `ECHConfigList`.
+    ///
+    /// This is the value to publish in the `ech` parameter of the server's HTTPS DNS record.
+    #[corresponds(SSL_ECH_KEYS_marshal_retry_configs)]
+    pub fn marshal_retry_configs(&self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let mut len = 0;
+            cvt(ffi::SSL_ECH_KEYS_marshal_retry_configs(
+                self.as_ptr(),
+                &mut ptr,
+                &mut len,
+            ))?;
+            let configs = slice::from_raw_parts(ptr, len).to_vec();
+            ffi::OPENSSL_free(ptr as *mut c_void);
+            Ok(configs)
+        }
+    }
+}
+
+/// Serializes an `ECHConfig` for `key`, for use with [`SslEchKeysBuilderRef::add`].
+///
+/// `config_id` should be unique among the server's configs. `public_name` is the name clients put
+/// in the unencrypted outer hello, and `max_name_len` is the length of the longest name the server
+/// serves, which clients pad to; 0 selects a default.
+///
+/// [`SslEchKeysBuilderRef::add`]: struct.SslEchKeysBuilderRef.html#method.add
+#[corresponds(SSL_marshal_ech_config)]
+pub fn marshal_ech_config(
+    config_id: u8,
+    key: &HpkeKeyRef,
+    public_name: &str,
+    max_name_len: usize,
+) -> Result<Vec<u8>, ErrorStack> {
+    let public_name = CString::new(public_name).unwrap();
+    unsafe {
+        ffi::init();
+        let mut ptr = ptr::null_mut();
+        let mut len = 0;
+        cvt(ffi::SSL_marshal_ech_config(
+            &mut ptr,
+            &mut len,
+            config_id,
+            key.as_ptr(),
+            public_name.as_ptr(),
+            max_name_len,
+        ))?;
+        let config = slice::from_raw_parts(ptr, len).to_vec();
+        ffi::OPENSSL_free(ptr as *mut c_void);
+        Ok(config)
+    }
+}
@@ -32,12 +32,35 @@ impl ErrorCode {
     /// An error occurred in the SSL library.
     pub const SSL: ErrorCode = ErrorCode(ffi::SSL_ERROR_SSL);
 
+    /// The certificate callback indicated that it needed to be retried.
+    pub const WANT_X509_LOOKUP: ErrorCode = ErrorCode(ffi::SSL_ERROR_WANT_X509_LOOKUP);
+
+    /// An asynchronous job was paused while waiting for an operation such as an engine's
+    /// cryptographic computation to complete.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(ossl110)]
+    pub const WANT_ASYNC: ErrorCode = ErrorCode(ffi::SSL_ERROR_WANT_ASYNC);
+
+    /// No asynchronous job was available to run the operation.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(ossl110)]
+    pub const WANT_ASYNC_JOB: ErrorCode = ErrorCode(ffi::SSL_ERROR_WANT_ASYNC_JOB);
+
     /// The client hello callback indicated that it needed to be retried.
     ///
     /// Requires OpenSSL 1.1.1 or newer.
     #[cfg(ossl111)]
     pub const WANT_CLIENT_HELLO_CB: ErrorCode = ErrorCode(ffi::SSL_ERROR_WANT_CLIENT_HELLO_CB);
 
+    /// A certificate verification callback suspended verification with
+    /// `SslRef::set_retry_verify`.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub const WANT_RETRY_VERIFY: ErrorCode = ErrorCode(ffi::SSL_ERROR_WANT_RETRY_VERIFY);
+
     pub fn from_raw(raw: c_int) -> ErrorCode {
         ErrorCode(raw)
     }
@@ -132,6 +155,80 @@ impl error::Error for Error {
     }
 }
 
+/// The condition an interrupted handshake is waiting on before it can be resumed.
+///
+/// This is returned by [`MidHandshakeSslStream::want`]. New variants are added as OpenSSL gains
+/// retryable conditions, and the set of variants already depends on the OpenSSL version, so
+/// matches must include a wildcard arm.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HandshakeWant {
+    /// The underlying stream must become readable.
+    Read,
+    /// The underlying stream must become writable.
+    Write,
+    /// The certificate callback requested that it be retried.
+    X509Lookup,
+    /// An asynchronous job is paused, and its wait file descriptors must become ready.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(ossl110)]
+    Async,
+    /// No asynchronous job was available, and one must be released by another connection.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(ossl110)]
+    AsyncJob,
+    /// The client hello callback requested that it be retried.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    ClientHello,
+    /// A certificate verification callback suspended verification.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    RetryVerify,
+}
+
+impl HandshakeWant {
+    /// Returns the condition corresponding to an error code, or `None` if the code represents a
+    /// failure from which the handshake can't be resumed.
+    pub fn from_code(code: ErrorCode) -> Option<HandshakeWant> {
+        match code {
+            ErrorCode::WANT_READ => Some(HandshakeWant::Read),
+            ErrorCode::WANT_WRITE => Some(HandshakeWant::Write),
+            ErrorCode::WANT_X509_LOOKUP => Some(HandshakeWant::X509Lookup),
+            #[cfg(ossl110)]
+            ErrorCode::WANT_ASYNC => Some(HandshakeWant::Async),
+            #[cfg(ossl110)]
+            ErrorCode::WANT_ASYNC_JOB => Some(HandshakeWant::AsyncJob),
+            #[cfg(ossl111)]
+            ErrorCode::WANT_CLIENT_HELLO_CB => Some(HandshakeWant::ClientHello),
+            #[cfg(ossl300)]
+            ErrorCode::WANT_RETRY_VERIFY => Some(HandshakeWant::RetryVerify),
+            _ => None,
+        }
+    }
+
+    /// Returns the error code corresponding to this condition.
+    pub fn code(self) -> ErrorCode {
+        match self {
+            HandshakeWant::Read => ErrorCode::WANT_READ,
+            HandshakeWant::Write => ErrorCode::WANT_WRITE,
+            HandshakeWant::X509Lookup => ErrorCode::WANT_X509_LOOKUP,
+            #[cfg(ossl110)]
+            HandshakeWant::Async => ErrorCode::WANT_ASYNC,
+            #[cfg(ossl110)]
+            HandshakeWant::AsyncJob => ErrorCode::WANT_ASYNC_JOB,
+            #[cfg(ossl111)]
+            HandshakeWant::ClientHello => ErrorCode::WANT_CLIENT_HELLO_CB,
+            #[cfg(ossl300)]
+            HandshakeWant::RetryVerify => ErrorCode::WANT_RETRY_VERIFY,
+        }
+    }
+}
+
 /// An error or intermediate state after a TLS handshake attempt.
 // FIXME overhaul
 #[derive(Debug)]
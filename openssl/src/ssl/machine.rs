@@ -0,0 +1,228 @@
+use foreign_types::ForeignTypeRef;
+use libc::c_int;
+use std::cmp;
+
+use crate::cvt_p;
+use crate::error::ErrorStack;
+use crate::ssl::error::InnerError;
+use crate::ssl::{Error, ErrorCode, ShutdownResult, Ssl, SslRef};
+use openssl_macros::corresponds;
+
+/// A TLS session driven entirely through in-memory buffers.
+///
+/// Unlike `SslStream`, an `SslMachine` does not perform any IO itself. Ciphertext received from
+/// the peer is handed to the machine with [`read_tls`], and ciphertext produced by OpenSSL is
+/// collected with [`write_tls`] and must be delivered to the peer by the caller. This allows the
+/// session to be driven from an event loop or async runtime without an intermediate blocking
+/// adapter.
+///
+/// Operations which cannot make progress without more data from the peer fail with
+/// `ErrorCode::WANT_READ`. Pending output should be flushed after every operation, regardless of
+/// its result.
+///
+/// [`read_tls`]: SslMachine::read_tls
+/// [`write_tls`]: SslMachine::write_tls
+pub struct SslMachine {
+    ssl: Ssl,
+}
+
+impl SslMachine {
+    /// Creates a new `SslMachine`, attaching a pair of memory BIOs to the provided `Ssl`.
+    ///
+    /// Either `Ssl::set_connect_state` or `Ssl::set_accept_state` must be called before the
+    /// handshake is driven with `do_handshake`, or `connect` or `accept` must be used instead.
+    pub fn new(ssl: Ssl) -> Result<SslMachine, ErrorStack> {
+        unsafe {
+            let rbio = cvt_p(ffi::BIO_new(ffi::BIO_s_mem()))?;
+            let wbio = match cvt_p(ffi::BIO_new(ffi::BIO_s_mem())) {
+                Ok(wbio) => wbio,
+                Err(e) => {
+                    ffi::BIO_free_all(rbio);
+                    return Err(e);
+                }
+            };
+            // An empty read BIO should signal "retry" rather than EOF.
+            ffi::BIO_set_mem_eof_return(rbio, -1);
+            ffi::SSL_set_bio(ssl.as_ptr(), rbio, wbio);
+        }
+
+        Ok(SslMachine { ssl })
+    }
+
+    /// Supplies ciphertext received from the peer.
+    ///
+    /// Returns the number of bytes consumed, which is always the full length of `buf`.
+    pub fn read_tls(&mut self, buf: &[u8]) -> Result<usize, ErrorStack> {
+        let mut written = 0;
+        while written < buf.len() {
+            let len = cmp::min(buf.len() - written, c_int::max_value() as usize) as c_int;
+            let ret =
+                unsafe { ffi::BIO_write(self.rbio(), buf[written..].as_ptr() as *const _, len) };
+            if ret <= 0 {
+                return Err(ErrorStack::get());
+            }
+            written += ret as usize;
+        }
+
+        Ok(written)
+    }
+
+    /// Retrieves ciphertext which should be sent to the peer.
+    ///
+    /// Returns the number of bytes written into `buf`, which is zero if no output is pending.
+    pub fn write_tls(&mut self, buf: &mut [u8]) -> usize {
+        if buf.is_empty() || !self.wants_write() {
+            return 0;
+        }
+
+        let len = cmp::min(buf.len(), c_int::max_value() as usize) as c_int;
+        let ret = unsafe { ffi::BIO_read(self.wbio(), buf.as_mut_ptr() as *mut _, len) };
+        cmp::max(ret, 0) as usize
+    }
+
+    /// Returns the number of bytes of ciphertext waiting to be retrieved with `write_tls`.
+    pub fn pending_tls(&self) -> usize {
+        unsafe { ffi::BIO_pending(self.wbio()) as usize }
+    }
+
+    /// Returns `true` if there is ciphertext waiting to be retrieved with `write_tls`.
+    pub fn wants_write(&self) -> bool {
+        self.pending_tls() > 0
+    }
+
+    /// Advances a client-side TLS handshake.
+    #[corresponds(SSL_connect)]
+    pub fn connect(&mut self) -> Result<(), Error> {
+        let ret = unsafe { ffi::SSL_connect(self.ssl.as_ptr()) };
+        if ret > 0 {
+            Ok(())
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
+    /// Advances a server-side TLS handshake.
+    #[corresponds(SSL_accept)]
+    pub fn accept(&mut self) -> Result<(), Error> {
+        let ret = unsafe { ffi::SSL_accept(self.ssl.as_ptr()) };
+        if ret > 0 {
+            Ok(())
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
+    /// Performs a stateless server-side TLSv1.3 handshake.
+    ///
+    /// Requires that cookie generation and verification callbacks were set on the SSL context.
+    ///
+    /// Returns `Ok(true)` if a ClientHello containing a valid cookie was read, in which case the
+    /// handshake should be continued via `accept`. If a HelloRetryRequest containing a fresh cookie
+    /// was produced, `Ok(false)` is returned and it should be flushed to the client before calling
+    /// this method again. Like the other operations, this fails with `ErrorCode::WANT_READ` if no
+    /// complete ClientHello has been buffered yet.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_stateless)]
+    #[cfg(ossl111)]
+    pub fn stateless(&mut self) -> Result<bool, Error> {
+        match unsafe { ffi::SSL_stateless(self.ssl.as_ptr()) } {
+            1 => Ok(true),
+            0 => Ok(false),
+            n => Err(self.make_error(n)),
+        }
+    }
+
+    /// Advances the handshake as far as the currently buffered input allows.
+    ///
+    /// This will fail if `set_accept_state` or `set_connect_state` was not called first.
+    #[corresponds(SSL_do_handshake)]
+    pub fn do_handshake(&mut self) -> Result<(), Error> {
+        let ret = unsafe { ffi::SSL_do_handshake(self.ssl.as_ptr()) };
+        if ret > 0 {
+            Ok(())
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
+    /// Decrypts application data from the buffered ciphertext.
+    #[corresponds(SSL_read)]
+    pub fn ssl_read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        // See `SslStream::ssl_read` for why we short-circuit on zero-length buffers
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let ret = self.ssl.read(buf);
+        if ret > 0 {
+            Ok(ret as usize)
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
+    /// Encrypts application data, making the resulting records available through `write_tls`.
+    #[corresponds(SSL_write)]
+    pub fn ssl_write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let ret = self.ssl.write(buf);
+        if ret > 0 {
+            Ok(ret as usize)
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
+    /// Shuts down the session.
+    ///
+    /// See `SslStream::shutdown` for details.
+    #[corresponds(SSL_shutdown)]
+    pub fn shutdown(&mut self) -> Result<ShutdownResult, Error> {
+        match unsafe { ffi::SSL_shutdown(self.ssl.as_ptr()) } {
+            0 => Ok(ShutdownResult::Sent),
+            1 => Ok(ShutdownResult::Received),
+            n => Err(self.make_error(n)),
+        }
+    }
+
+    /// Returns a shared reference to the `Ssl` object associated with this machine.
+    pub fn ssl(&self) -> &SslRef {
+        &self.ssl
+    }
+
+    /// Returns a mutable reference to the `Ssl` object associated with this machine.
+    pub fn ssl_mut(&mut self) -> &mut SslRef {
+        &mut self.ssl
+    }
+
+    fn rbio(&self) -> *mut ffi::BIO {
+        unsafe { ffi::SSL_get_rbio(self.ssl.as_ptr()) }
+    }
+
+    fn wbio(&self) -> *mut ffi::BIO {
+        unsafe { ffi::SSL_get_wbio(self.ssl.as_ptr()) }
+    }
+
+    fn make_error(&mut self, ret: c_int) -> Error {
+        let code = self.ssl.get_error(ret);
+
+        let cause = match code {
+            ErrorCode::SSL => Some(InnerError::Ssl(ErrorStack::get())),
+            ErrorCode::SYSCALL => {
+                let errs = ErrorStack::get();
+                if errs.errors().is_empty() {
+                    None
+                } else {
+                    Some(InnerError::Ssl(errs))
+                }
+            }
+            _ => None,
+        };
+
+        Error { code, cause }
+    }
+}
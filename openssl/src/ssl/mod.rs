@@ -71,6 +71,8 @@ use libc::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong, c_void};
 // use std::io::prelude::*;
 // use std::marker::PhantomData;
 // use std::mem::{self, ManuallyDrop};
+#[cfg(all(ossl110, unix))]
+use std::os::unix::io::RawFd;
 // use std::ops::{Deref, DerefMut};
 // use std::panic::resume_unwind;
 // use std::path::Path;
@@ -78,22 +80,32 @@ use libc::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong, c_void};
 // use std::slice;
 // use std::str;
 // use std::sync::{Arc, Mutex};
+#[cfg(all(ossl102, not(osslconf = "OPENSSL_NO_SSL_TRACE")))]
+use crate::bio::MemBio;
 use crate::dh::{Dh, DhRef};
 #[cfg(all(ossl101, not(ossl110)))]
 use crate::ec::EcKey;
 use crate::ec::EcKeyRef;
-use crate::error::ErrorStack;
+use crate::error::{push_error, ErrorStack};
 use crate::ex_data::Index;
-#[cfg(ossl111)]
-use crate::hash::MessageDigest;
 #[cfg(ossl110)]
+use crate::hash::MessageDigest;
+#[cfg(ossl102)]
 use crate::nid::Nid;
-use crate::pkey::{HasPrivate, PKeyRef, Params, Private};
-use crate::srtp::{SrtpProtectionProfile, SrtpProtectionProfileRef};
+#[cfg(not(any(boringssl, osslconf = "OPENSSL_NO_OCSP")))]
+use crate::ocsp::OcspResponse;
+#[cfg(ossl320)]
+use crate::pkey::HasPublic;
+#[cfg(ossl111)]
+use crate::pkey::Public;
+use crate::pkey::{HasPrivate, PKey, PKeyRef, Params, Private};
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+use crate::srp::{SrpGroup, SrpVerifier};
+use crate::srtp::{SrtpKeyingMaterial, SrtpProtectionProfile, SrtpProtectionProfileRef};
 use crate::ssl::bio::BioMethod;
 use crate::ssl::callbacks::*;
 use crate::ssl::error::InnerError;
-use crate::stack::{Stack, StackRef};
+use crate::stack::{Stack, StackRef, Stackable};
 use crate::util::{ForeignTypeExt, ForeignTypeRefExt};
 use crate::x509::store::{X509Store, X509StoreBuilderRef, X509StoreRef};
 #[cfg(any(ossl102, libressl261))]
@@ -112,6 +124,7 @@ use std::ffi::{CStr, CString};
 use std::fmt;
 use std::io;
 use std::io::prelude::*;
+use std::io::IoSlice;
 use std::marker::PhantomData;
 use std::mem::{self, ManuallyDrop};
 use std::ops::{Deref, DerefMut};
@@ -121,16 +134,36 @@ use std::ptr;
 use std::slice;
 use std::str;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 pub use crate::ssl::connector::{
-    ConnectConfiguration, SslAcceptor, SslAcceptorBuilder, SslConnector, SslConnectorBuilder,
+    ConnectConfiguration, HostnameMatcher, HostnameVerification, SslAcceptor, SslAcceptorBuilder,
+    SslConnector, SslConnectorBuilder,
+};
+#[cfg(ossl110)]
+pub use crate::ssl::dane::{
+    DaneAuthority, DaneFlags, DaneTlsa, TlsaMatchingType, TlsaSelector, TlsaUsage,
+};
+#[cfg(boringssl)]
+pub use crate::ssl::ech::{
+    marshal_ech_config, SslEchKeys, SslEchKeysBuilder, SslEchKeysBuilderRef, SslEchKeysRef,
 };
-pub use crate::ssl::error::{Error, ErrorCode, HandshakeError};
+pub use crate::ssl::error::{Error, ErrorCode, HandshakeError, HandshakeWant};
+pub use crate::ssl::machine::SslMachine;
+#[cfg(ossl102)]
+pub use crate::ssl::resolver::{CertifiedKey, SniResolver};
 
 mod bio;
 mod callbacks;
 mod connector;
+#[cfg(ossl110)]
+mod dane;
+#[cfg(boringssl)]
+mod ech;
 mod error;
+mod machine;
+#[cfg(ossl102)]
+mod resolver;
 #[cfg(test)]
 mod test;
 
@@ -276,12 +309,42 @@ bitflags! {
         #[cfg(ossl110h)]
         const NO_RENEGOTIATION = ffi::SSL_OP_NO_RENEGOTIATION;
 
+        /// Allow clients to initiate renegotiation in TLSv1.2 and earlier.
+        ///
+        /// Servers reject client-initiated renegotiation by default.
+        ///
+        /// Requires OpenSSL 3.0.0 or newer.
+        #[cfg(ossl300)]
+        const ALLOW_CLIENT_RENEGOTIATION = ffi::SSL_OP_ALLOW_CLIENT_RENEGOTIATION;
+
         /// Enable TLSv1.3 Compatibility mode.
         ///
         /// Requires OpenSSL 1.1.1 or newer. This is on by default in 1.1.1, but a future version
         /// may have this disabled by default.
         #[cfg(ossl111)]
         const ENABLE_MIDDLEBOX_COMPAT = ffi::SSL_OP_ENABLE_MIDDLEBOX_COMPAT;
+
+        /// Allow TLSv1.3 resumption and external pre-shared key handshakes without an (EC)DHE key
+        /// exchange.
+        ///
+        /// This loses forward secrecy, but is needed to interoperate with constrained peers which
+        /// only support the `psk_ke` key exchange mode.
+        ///
+        /// Requires OpenSSL 1.1.1 or newer.
+        #[cfg(ossl111)]
+        const ALLOW_NO_DHE_KEX = ffi::SSL_OP_ALLOW_NO_DHE_KEX;
+
+        /// Disables sending compressed certificates.
+        ///
+        /// Requires OpenSSL 3.2.0 or newer.
+        #[cfg(ossl320)]
+        const NO_TX_CERTIFICATE_COMPRESSION = ffi::SSL_OP_NO_TX_CERTIFICATE_COMPRESSION;
+
+        /// Disables receiving compressed certificates.
+        ///
+        /// Requires OpenSSL 3.2.0 or newer.
+        #[cfg(ossl320)]
+        const NO_RX_CERTIFICATE_COMPRESSION = ffi::SSL_OP_NO_RX_CERTIFICATE_COMPRESSION;
     }
 }
 
@@ -331,9 +394,129 @@ bitflags! {
         /// Do not use this unless you know what you're doing!
         #[cfg(not(libressl))]
         const SEND_FALLBACK_SCSV = ffi::SSL_MODE_SEND_FALLBACK_SCSV;
+
+        /// Runs cryptographic operations in asynchronous jobs.
+        ///
+        /// An engine or provider that offloads operations such as RSA or ECDSA signing to
+        /// hardware can then pause the handshake while the operation is in progress, which is
+        /// reported as `ErrorCode::WANT_ASYNC`. The operation should be retried once one of the
+        /// connection's async file descriptors becomes readable, or once the async callback is
+        /// invoked.
+        ///
+        /// Requires OpenSSL 1.1.0 or newer.
+        #[cfg(ossl110)]
+        const ASYNC = ffi::SSL_MODE_ASYNC;
+    }
+}
+
+#[cfg(ossl330)]
+bitflags! {
+    /// Flags controlling the behavior of [`SslStream::ssl_write_with_flags`].
+    pub struct SslWriteFlags: u64 {
+        /// Indicates that no more data will be written after this call.
+        ///
+        /// This is only supported by QUIC streams, where it concludes the stream. Writes with this
+        /// flag on a TLS connection fail.
+        const CONCLUDE = ffi::SSL_WRITE_FLAG_CONCLUDE;
+    }
+}
+
+#[cfg(ossl320)]
+bitflags! {
+    /// Flags controlling the behavior of [`SslRef::new_stream`].
+    pub struct QuicStreamFlags: u64 {
+        /// Creates a unidirectional stream rather than a bidirectional one.
+        const UNI = ffi::SSL_STREAM_FLAG_UNI;
+
+        /// Fails rather than blocking if the peer's stream limit has been reached.
+        const NO_BLOCK = ffi::SSL_STREAM_FLAG_NO_BLOCK;
+
+        /// Opens the stream immediately, rather than when data is first written to it.
+        const ADVANCE = ffi::SSL_STREAM_FLAG_ADVANCE;
+    }
+}
+
+#[cfg(ossl320)]
+bitflags! {
+    /// Flags controlling the behavior of [`SslRef::accept_stream`].
+    pub struct QuicAcceptStreamFlags: u64 {
+        /// Returns immediately if no incoming stream is queued, even in blocking mode.
+        const NO_BLOCK = ffi::SSL_ACCEPT_STREAM_NO_BLOCK;
+    }
+}
+
+/// How a QUIC connection manages the default stream it wraps.
+///
+/// Requires OpenSSL 3.2.0 or newer.
+#[cfg(ossl320)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct QuicDefaultStreamMode(u32);
+
+#[cfg(ossl320)]
+impl QuicDefaultStreamMode {
+    /// No default stream is created; streams must be opened explicitly with
+    /// [`SslRef::new_stream`].
+    pub const NONE: QuicDefaultStreamMode =
+        QuicDefaultStreamMode(ffi::SSL_DEFAULT_STREAM_MODE_NONE);
+
+    /// A bidirectional default stream is created when the connection is first read or written.
+    pub const AUTO_BIDI: QuicDefaultStreamMode =
+        QuicDefaultStreamMode(ffi::SSL_DEFAULT_STREAM_MODE_AUTO_BIDI);
+
+    /// A unidirectional default stream is created when the connection is first read or written,
+    /// in the direction of that first operation.
+    pub const AUTO_UNI: QuicDefaultStreamMode =
+        QuicDefaultStreamMode(ffi::SSL_DEFAULT_STREAM_MODE_AUTO_UNI);
+
+    /// Constructs a `QuicDefaultStreamMode` from a raw OpenSSL value.
+    pub fn from_raw(raw: u32) -> QuicDefaultStreamMode {
+        QuicDefaultStreamMode(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u32 {
+        self.0
+    }
+}
+
+/// The directions in which data can flow on a QUIC stream.
+///
+/// Requires OpenSSL 3.2.0 or newer.
+#[cfg(ossl320)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct QuicStreamType(c_int);
+
+#[cfg(ossl320)]
+impl QuicStreamType {
+    /// The object is not a stream, or has no default stream.
+    pub const NONE: QuicStreamType = QuicStreamType(ffi::SSL_STREAM_TYPE_NONE);
+
+    /// A unidirectional stream opened by the peer.
+    pub const READ: QuicStreamType = QuicStreamType(ffi::SSL_STREAM_TYPE_READ);
+
+    /// A unidirectional stream opened locally.
+    pub const WRITE: QuicStreamType = QuicStreamType(ffi::SSL_STREAM_TYPE_WRITE);
+
+    /// A bidirectional stream. This is also the type of a TLS connection.
+    pub const BIDI: QuicStreamType = QuicStreamType(ffi::SSL_STREAM_TYPE_BIDI);
+
+    /// Constructs a `QuicStreamType` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> QuicStreamType {
+        QuicStreamType(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
     }
 }
 
+/// The largest amount of data which `SslStream::ssl_write_vectored` will copy into a single write,
+/// matching the maximum plaintext size of a TLS record.
+const MAX_COALESCED_WRITE: usize = 16 * 1024;
+
 /// A type specifying the kind of protocol an `SslContext` will speak.
 #[derive(Copy, Clone)]
 pub struct SslMethod(*const ffi::SSL_METHOD);
@@ -363,6 +546,24 @@ impl SslMethod {
         unsafe { SslMethod(TLS_server_method()) }
     }
 
+    /// Speak QUIC as a client, with the connection driven by the caller.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(OSSL_QUIC_client_method)]
+    #[cfg(all(ossl320, not(osslconf = "OPENSSL_NO_QUIC")))]
+    pub fn quic_client() -> SslMethod {
+        unsafe { SslMethod(ffi::OSSL_QUIC_client_method()) }
+    }
+
+    /// Speak QUIC as a client, with the connection driven by a background thread.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(OSSL_QUIC_client_thread_method)]
+    #[cfg(all(ossl320, not(osslconf = "OPENSSL_NO_QUIC")))]
+    pub fn quic_client_thread() -> SslMethod {
+        unsafe { SslMethod(ffi::OSSL_QUIC_client_thread_method()) }
+    }
+
     /// Constructs an `SslMethod` from a pointer to the underlying OpenSSL value.
     ///
     /// # Safety
@@ -401,6 +602,22 @@ bitflags! {
         ///
         /// This should be paired with `SSL_VERIFY_PEER`. It has no effect on the client side.
         const FAIL_IF_NO_PEER_CERT = ffi::SSL_VERIFY_FAIL_IF_NO_PEER_CERT;
+
+        /// On the server side, only request a certificate from the client during the initial
+        /// handshake, and not on renegotiation or TLS 1.3 post-handshake authentication.
+        ///
+        /// This should be paired with `SSL_VERIFY_PEER`. It has no effect on the client side.
+        #[cfg(not(boringssl))]
+        const CLIENT_ONCE = ffi::SSL_VERIFY_CLIENT_ONCE;
+
+        /// On the server side, don't request a certificate from the client during the initial
+        /// TLS 1.3 handshake, but only when [`SslRef::verify_client_post_handshake`] is called.
+        ///
+        /// This should be paired with `SSL_VERIFY_PEER`. It has no effect on the client side.
+        ///
+        /// Requires OpenSSL 1.1.1 or newer.
+        #[cfg(ossl111)]
+        const POST_HANDSHAKE = ffi::SSL_VERIFY_POST_HANDSHAKE;
     }
 }
 
@@ -555,6 +772,14 @@ fn try_get_session_ctx_index() -> Result<&'static Index<Ssl, SslContext>, ErrorS
     SESSION_CTX_INDEX.get_or_try_init(Ssl::new_ex_index)
 }
 
+/// Returns an error stack reporting an unusable buffer length.
+fn bad_length() -> ErrorStack {
+    unsafe {
+        push_error(ffi::ERR_LIB_SSL, ffi::SSL_R_BAD_LENGTH);
+    }
+    ErrorStack::get()
+}
+
 unsafe extern "C" fn free_data_box<T>(
     _parent: *mut c_void,
     ptr: *mut c_void,
@@ -582,15 +807,187 @@ impl SniError {
     pub const NOACK: SniError = SniError(ffi::SSL_TLSEXT_ERR_NOACK);
 }
 
+/// A TLS signature algorithm.
+///
+/// Returned by [`SslRef::shared_sigalgs`].
+///
+/// Requires OpenSSL 1.0.2 or newer.
+#[cfg(ossl102)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslSigalg {
+    signature_type: Nid,
+    digest: Nid,
+    nid: Nid,
+    code_point: u16,
+}
+
+#[cfg(ossl102)]
+impl SslSigalg {
+    /// Returns the public key algorithm of the signature.
+    pub fn signature_type(&self) -> Nid {
+        self.signature_type
+    }
+
+    /// Returns the digest of the signature.
+    ///
+    /// This is `Nid::UNDEF` for algorithms without a separate digest, such as Ed25519.
+    pub fn digest(&self) -> Nid {
+        self.digest
+    }
+
+    /// Returns the combined signature and digest algorithm, for example
+    /// `Nid::SHA256WITHRSAENCRYPTION`.
+    ///
+    /// This is `Nid::UNDEF` if there is no such combined algorithm.
+    pub fn nid(&self) -> Nid {
+        self.nid
+    }
+
+    /// Returns the TLS `SignatureScheme` code point of the algorithm.
+    pub fn code_point(&self) -> u16 {
+        self.code_point
+    }
+}
+
 /// An SSL/TLS alert.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct SslAlert(c_int);
 
 impl SslAlert {
-    /// Alert 112 - `unrecognized_name`.
-    pub const UNRECOGNIZED_NAME: SslAlert = SslAlert(ffi::SSL_AD_UNRECOGNIZED_NAME);
+    pub const CLOSE_NOTIFY: SslAlert = SslAlert(ffi::SSL_AD_CLOSE_NOTIFY);
+    pub const UNEXPECTED_MESSAGE: SslAlert = SslAlert(ffi::SSL_AD_UNEXPECTED_MESSAGE);
+    pub const BAD_RECORD_MAC: SslAlert = SslAlert(ffi::SSL_AD_BAD_RECORD_MAC);
+    pub const RECORD_OVERFLOW: SslAlert = SslAlert(ffi::SSL_AD_RECORD_OVERFLOW);
+    pub const HANDSHAKE_FAILURE: SslAlert = SslAlert(ffi::SSL_AD_HANDSHAKE_FAILURE);
+    pub const BAD_CERTIFICATE: SslAlert = SslAlert(ffi::SSL_AD_BAD_CERTIFICATE);
+    pub const UNSUPPORTED_CERTIFICATE: SslAlert = SslAlert(ffi::SSL_AD_UNSUPPORTED_CERTIFICATE);
+    pub const CERTIFICATE_REVOKED: SslAlert = SslAlert(ffi::SSL_AD_CERTIFICATE_REVOKED);
+    pub const CERTIFICATE_EXPIRED: SslAlert = SslAlert(ffi::SSL_AD_CERTIFICATE_EXPIRED);
+    pub const CERTIFICATE_UNKNOWN: SslAlert = SslAlert(ffi::SSL_AD_CERTIFICATE_UNKNOWN);
     pub const ILLEGAL_PARAMETER: SslAlert = SslAlert(ffi::SSL_AD_ILLEGAL_PARAMETER);
+    pub const UNKNOWN_CA: SslAlert = SslAlert(ffi::SSL_AD_UNKNOWN_CA);
+    pub const ACCESS_DENIED: SslAlert = SslAlert(ffi::SSL_AD_ACCESS_DENIED);
     pub const DECODE_ERROR: SslAlert = SslAlert(ffi::SSL_AD_DECODE_ERROR);
+    pub const DECRYPT_ERROR: SslAlert = SslAlert(ffi::SSL_AD_DECRYPT_ERROR);
+    pub const PROTOCOL_VERSION: SslAlert = SslAlert(ffi::SSL_AD_PROTOCOL_VERSION);
+    pub const INSUFFICIENT_SECURITY: SslAlert = SslAlert(ffi::SSL_AD_INSUFFICIENT_SECURITY);
+    pub const INTERNAL_ERROR: SslAlert = SslAlert(ffi::SSL_AD_INTERNAL_ERROR);
+    pub const INAPPROPRIATE_FALLBACK: SslAlert = SslAlert(ffi::SSL_AD_INAPPROPRIATE_FALLBACK);
+    pub const USER_CANCELLED: SslAlert = SslAlert(ffi::SSL_AD_USER_CANCELLED);
+    pub const NO_RENEGOTIATION: SslAlert = SslAlert(ffi::SSL_AD_NO_RENEGOTIATION);
+    pub const MISSING_EXTENSION: SslAlert = SslAlert(ffi::SSL_AD_MISSING_EXTENSION);
+    pub const UNSUPPORTED_EXTENSION: SslAlert = SslAlert(ffi::SSL_AD_UNSUPPORTED_EXTENSION);
+    /// Alert 112 - `unrecognized_name`.
+    pub const UNRECOGNIZED_NAME: SslAlert = SslAlert(ffi::SSL_AD_UNRECOGNIZED_NAME);
+    pub const BAD_CERTIFICATE_STATUS_RESPONSE: SslAlert =
+        SslAlert(ffi::SSL_AD_BAD_CERTIFICATE_STATUS_RESPONSE);
+    pub const UNKNOWN_PSK_IDENTITY: SslAlert = SslAlert(ffi::SSL_AD_UNKNOWN_PSK_IDENTITY);
+    pub const CERTIFICATE_REQUIRED: SslAlert = SslAlert(ffi::SSL_AD_CERTIFICATE_REQUIRED);
+    pub const NO_APPLICATION_PROTOCOL: SslAlert = SslAlert(ffi::SSL_AD_NO_APPLICATION_PROTOCOL);
+
+    /// Constructs an `SslAlert` from a raw alert description.
+    pub fn from_raw(raw: c_int) -> SslAlert {
+        SslAlert(raw)
+    }
+
+    /// Returns the raw alert description.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+
+    /// Returns a human readable description of the alert, such as `"handshake failure"`.
+    #[corresponds(SSL_alert_desc_string_long)]
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn description(&self) -> &'static str {
+        unsafe {
+            let ptr = ffi::SSL_alert_desc_string_long(self.0);
+            str::from_utf8(CStr::from_ptr(ptr).to_bytes()).unwrap()
+        }
+    }
+}
+
+/// The level of an SSL/TLS alert.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SslAlertLevel {
+    /// A warning, after which the connection may continue.
+    Warning,
+    /// A fatal alert, after which the connection is closed.
+    Fatal,
+    /// An alert with an invalid level, as received from the peer.
+    Other(i32),
+}
+
+impl SslAlertLevel {
+    /// Constructs an `SslAlertLevel` from a raw alert level.
+    pub fn from_raw(raw: c_int) -> SslAlertLevel {
+        match raw {
+            ffi::SSL3_AL_WARNING => SslAlertLevel::Warning,
+            ffi::SSL3_AL_FATAL => SslAlertLevel::Fatal,
+            raw => SslAlertLevel::Other(raw),
+        }
+    }
+
+    /// Returns the raw alert level.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        match *self {
+            SslAlertLevel::Warning => ffi::SSL3_AL_WARNING,
+            SslAlertLevel::Fatal => ffi::SSL3_AL_FATAL,
+            SslAlertLevel::Other(raw) => raw,
+        }
+    }
+}
+
+/// A connection lifecycle event passed to an info callback.
+///
+/// Events are passed to the callback configured with [`SslContextBuilder::set_info_callback`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SslInfoEvent {
+    /// A handshake, or a renegotiation, has started.
+    HandshakeStart,
+    /// A handshake has completed.
+    HandshakeDone,
+    /// The handshake state machine has moved to a new state.
+    ///
+    /// The new state can be retrieved with [`SslRef::state_string_long`].
+    StateChange,
+    /// An alert was received from the peer.
+    AlertRead {
+        /// The alert's level.
+        level: SslAlertLevel,
+        /// The alert's description.
+        alert: SslAlert,
+    },
+    /// An alert was sent to the peer.
+    AlertWritten {
+        /// The alert's level.
+        level: SslAlertLevel,
+        /// The alert's description.
+        alert: SslAlert,
+    },
+}
+
+impl SslInfoEvent {
+    fn from_raw(where_: c_int, ret: c_int) -> Option<SslInfoEvent> {
+        if where_ & ffi::SSL_CB_ALERT != 0 {
+            let level = SslAlertLevel::from_raw(ret >> 8);
+            let alert = SslAlert(ret & 0xff);
+            if where_ & ffi::SSL_CB_READ != 0 {
+                Some(SslInfoEvent::AlertRead { level, alert })
+            } else {
+                Some(SslInfoEvent::AlertWritten { level, alert })
+            }
+        } else if where_ & ffi::SSL_CB_HANDSHAKE_START != 0 {
+            Some(SslInfoEvent::HandshakeStart)
+        } else if where_ & ffi::SSL_CB_HANDSHAKE_DONE != 0 {
+            Some(SslInfoEvent::HandshakeDone)
+        } else if where_ & ffi::SSL_CB_LOOP != 0 {
+            Some(SslInfoEvent::StateChange)
+        } else {
+            None
+        }
+    }
 }
 
 /// An error returned from an ALPN selection callback.
@@ -628,2397 +1025,6244 @@ impl ClientHelloResponse {
     pub const RETRY: ClientHelloResponse = ClientHelloResponse(ffi::SSL_CLIENT_HELLO_RETRY);
 }
 
-/// An SSL/TLS protocol version.
+/// The status of an asynchronous job, as reported by the engine or provider running it.
+///
+/// Requires OpenSSL 3.0.0 or newer.
+#[cfg(ossl300)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct SslVersion(c_int);
+pub struct AsyncStatus(c_int);
 
-impl SslVersion {
-    /// SSLv3
-    pub const SSL3: SslVersion = SslVersion(ffi::SSL3_VERSION);
+#[cfg(ossl300)]
+impl AsyncStatus {
+    /// The engine or provider does not report the status of its jobs.
+    pub const UNSUPPORTED: AsyncStatus = AsyncStatus(ffi::ASYNC_STATUS_UNSUPPORTED);
 
-    /// TLSv1.0
-    pub const TLS1: SslVersion = SslVersion(ffi::TLS1_VERSION);
+    /// The job failed.
+    pub const ERR: AsyncStatus = AsyncStatus(ffi::ASYNC_STATUS_ERR);
 
-    /// TLSv1.1
-    pub const TLS1_1: SslVersion = SslVersion(ffi::TLS1_1_VERSION);
+    /// The job is in progress or has completed.
+    pub const OK: AsyncStatus = AsyncStatus(ffi::ASYNC_STATUS_OK);
 
-    /// TLSv1.2
-    pub const TLS1_2: SslVersion = SslVersion(ffi::TLS1_2_VERSION);
+    /// The engine or provider could not accept the job, which should be retried later.
+    pub const EAGAIN: AsyncStatus = AsyncStatus(ffi::ASYNC_STATUS_EAGAIN);
 
-    /// TLSv1.3
-    ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[cfg(ossl111)]
-    pub const TLS1_3: SslVersion = SslVersion(ffi::TLS1_3_VERSION);
-}
+    /// Constructs an `AsyncStatus` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> AsyncStatus {
+        AsyncStatus(raw)
+    }
 
-cfg_if! {
-    if #[cfg(boringssl)] {
-        type SslTy = i32;
-        type SslModeTy = i32;
-        type SslCacheTy = i64;
-        type SslCacheSize = c_ulong;
-        type MtuTy = u32;
-        type BitTy = u32;
-        type SizeTy = usize;
-    } else {
-        type SslTy = u64;
-        type SslModeTy = i64;
-        type SslCacheTy = i64;
-        type SslCacheSize = c_long;
-        type MtuTy = i64;
-        type BitTy = u64;
-        type SizeTy = u32;
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
     }
 }
 
-/// A standard implementation of protocol selection for Application Layer Protocol Negotiation
-/// (ALPN).
-///
-/// `server` should contain the server's list of supported protocols and `client` the client's. They
-/// must both be in the ALPN wire format. See the documentation for
-/// [`SslContextBuilder::set_alpn_protos`] for details.
-///
-/// It will select the first protocol supported by the server which is also supported by the client.
+/// The result of a certificate callback.
 ///
-/// [`SslContextBuilder::set_alpn_protos`]: struct.SslContextBuilder.html#method.set_alpn_protos
-#[corresponds(SSL_select_next_proto)]
-pub fn select_next_proto<'a>(server: &[u8], client: &'a [u8]) -> Option<&'a [u8]> {
-    unsafe {
-        let mut out = ptr::null_mut();
-        let mut outlen = 0;
-        let r = ffi::SSL_select_next_proto(
-            &mut out,
-            &mut outlen,
-            server.as_ptr(),
-            server.len() as c_uint,
-            client.as_ptr(),
-            client.len() as c_uint,
-        );
-        if r == ffi::OPENSSL_NPN_NEGOTIATED {
-            Some(slice::from_raw_parts(out as *const u8, outlen as usize))
-        } else {
-            None
-        }
-    }
+/// Requires OpenSSL 1.0.2 or newer.
+#[cfg(ossl102)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CertCallbackResponse(c_int);
+
+#[cfg(ossl102)]
+impl CertCallbackResponse {
+    /// Continue the handshake.
+    pub const SUCCESS: CertCallbackResponse = CertCallbackResponse(1);
+
+    /// Return from the handshake with an `ErrorCode::WANT_X509_LOOKUP` error.
+    ///
+    /// The callback will be invoked again when the handshake is resumed.
+    pub const RETRY: CertCallbackResponse = CertCallbackResponse(-1);
 }
 
-/// A builder for `SslContext`s.
-pub struct SslContextBuilder(SslContext);
+/// A maximum fragment length negotiated with the RFC 6066 `max_fragment_length` extension.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MaxFragmentLength(u8);
 
-impl SslContextBuilder {
-    /// Creates a new `SslContextBuilder`.
-    #[corresponds(SSL_CTX_new)]
-    pub fn new(method: SslMethod) -> Result<SslContextBuilder, ErrorStack> {
-        unsafe {
-            init();
-            let ctx = cvt_p(ffi::SSL_CTX_new(method.as_ptr()))?;
+#[cfg(ossl111)]
+impl MaxFragmentLength {
+    /// The extension is not used, and records may carry up to 16 KiB of plaintext.
+    pub const DISABLED: MaxFragmentLength =
+        MaxFragmentLength(ffi::TLSEXT_max_fragment_length_DISABLED);
 
-            Ok(SslContextBuilder::from_ptr(ctx))
-        }
-    }
+    /// Records carry at most 512 bytes of plaintext.
+    pub const LEN_512: MaxFragmentLength = MaxFragmentLength(ffi::TLSEXT_max_fragment_length_512);
 
-    /// Creates an `SslContextBuilder` from a pointer to a raw OpenSSL value.
-    ///
-    /// # Safety
-    ///
-    /// The caller must ensure that the pointer is valid and uniquely owned by the builder.
-    pub unsafe fn from_ptr(ctx: *mut ffi::SSL_CTX) -> SslContextBuilder {
-        SslContextBuilder(SslContext::from_ptr(ctx))
-    }
+    /// Records carry at most 1024 bytes of plaintext.
+    pub const LEN_1024: MaxFragmentLength = MaxFragmentLength(ffi::TLSEXT_max_fragment_length_1024);
 
-    /// Returns a pointer to the raw OpenSSL value.
-    pub fn as_ptr(&self) -> *mut ffi::SSL_CTX {
-        self.0.as_ptr()
-    }
+    /// Records carry at most 2048 bytes of plaintext.
+    pub const LEN_2048: MaxFragmentLength = MaxFragmentLength(ffi::TLSEXT_max_fragment_length_2048);
 
-    /// Configures the certificate verification method for new connections.
-    #[corresponds(SSL_CTX_set_verify)]
-    pub fn set_verify(&mut self, mode: SslVerifyMode) {
-        unsafe {
-            ffi::SSL_CTX_set_verify(self.as_ptr(), mode.bits as c_int, None);
-        }
+    /// Records carry at most 4096 bytes of plaintext.
+    pub const LEN_4096: MaxFragmentLength = MaxFragmentLength(ffi::TLSEXT_max_fragment_length_4096);
+
+    /// Constructs a `MaxFragmentLength` from a raw OpenSSL value.
+    pub fn from_raw(raw: u8) -> MaxFragmentLength {
+        MaxFragmentLength(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns the maximum number of plaintext bytes per record, or `None` if the extension is
+    /// not in use.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn size(&self) -> Option<usize> {
+        match self.0 {
+            1..=4 => Some(256 << self.0),
+            _ => None,
+        }
+    }
+}
+
+/// The type of a TLS 1.3 key update.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KeyUpdateType(c_int);
+
+#[cfg(ossl111)]
+impl KeyUpdateType {
+    /// No key update is pending.
+    pub const NONE: KeyUpdateType = KeyUpdateType(ffi::SSL_KEY_UPDATE_NONE);
+
+    /// Update the local sending keys only.
+    pub const NOT_REQUESTED: KeyUpdateType = KeyUpdateType(ffi::SSL_KEY_UPDATE_NOT_REQUESTED);
+
+    /// Update the local sending keys and request that the peer update its own.
+    pub const REQUESTED: KeyUpdateType = KeyUpdateType(ffi::SSL_KEY_UPDATE_REQUESTED);
+
+    /// Constructs a `KeyUpdateType` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> KeyUpdateType {
+        KeyUpdateType(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// The status of early data on a TLS 1.3 connection.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslEarlyDataStatus(c_int);
+
+#[cfg(ossl111)]
+impl SslEarlyDataStatus {
+    /// No early data was sent by the client.
+    pub const NOT_SENT: SslEarlyDataStatus = SslEarlyDataStatus(ffi::SSL_EARLY_DATA_NOT_SENT);
+
+    /// Early data was sent by the client but rejected by the server.
+    ///
+    /// A client must resend any early data as normal application data after the handshake.
+    pub const REJECTED: SslEarlyDataStatus = SslEarlyDataStatus(ffi::SSL_EARLY_DATA_REJECTED);
+
+    /// Early data was sent by the client and accepted by the server.
+    pub const ACCEPTED: SslEarlyDataStatus = SslEarlyDataStatus(ffi::SSL_EARLY_DATA_ACCEPTED);
+
+    /// Constructs an `SslEarlyDataStatus` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> SslEarlyDataStatus {
+        SslEarlyDataStatus(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// A QUIC encryption level.
+///
+/// Requires BoringSSL.
+#[cfg(boringssl)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct QuicEncryptionLevel(ffi::ssl_encryption_level_t);
+
+#[cfg(boringssl)]
+impl QuicEncryptionLevel {
+    /// The Initial encryption level, used for the ClientHello and ServerHello.
+    pub const INITIAL: QuicEncryptionLevel =
+        QuicEncryptionLevel(ffi::ssl_encryption_level_t_ssl_encryption_initial);
+
+    /// The 0-RTT encryption level.
+    pub const EARLY_DATA: QuicEncryptionLevel =
+        QuicEncryptionLevel(ffi::ssl_encryption_level_t_ssl_encryption_early_data);
+
+    /// The Handshake encryption level.
+    pub const HANDSHAKE: QuicEncryptionLevel =
+        QuicEncryptionLevel(ffi::ssl_encryption_level_t_ssl_encryption_handshake);
+
+    /// The 1-RTT encryption level, used for application data and post-handshake messages.
+    pub const APPLICATION: QuicEncryptionLevel =
+        QuicEncryptionLevel(ffi::ssl_encryption_level_t_ssl_encryption_application);
+
+    /// Constructs a `QuicEncryptionLevel` from a raw value.
+    pub fn from_raw(raw: ffi::ssl_encryption_level_t) -> QuicEncryptionLevel {
+        QuicEncryptionLevel(raw)
+    }
+
+    /// Returns the raw value of the encryption level.
+    pub fn as_raw(&self) -> ffi::ssl_encryption_level_t {
+        self.0
+    }
+}
+
+/// The interface between BoringSSL's TLS handshake and a QUIC transport.
+///
+/// Rather than reading and writing TLS records, a session configured with
+/// [`SslRef::set_quic_method`] hands handshake messages and traffic secrets to these methods, and
+/// receives the peer's handshake messages through [`SslRef::provide_quic_data`]. Errors returned
+/// from any method abort the handshake.
+///
+/// Requires BoringSSL.
+#[cfg(boringssl)]
+pub trait QuicMethod: 'static + Sync + Send {
+    /// Installs the secret used to decrypt packets received at `level`.
+    fn set_read_secret(
+        &self,
+        ssl: &mut SslRef,
+        level: QuicEncryptionLevel,
+        cipher: &SslCipherRef,
+        secret: &[u8],
+    ) -> Result<(), ErrorStack>;
+
+    /// Installs the secret used to encrypt packets sent at `level`.
+    fn set_write_secret(
+        &self,
+        ssl: &mut SslRef,
+        level: QuicEncryptionLevel,
+        cipher: &SslCipherRef,
+        secret: &[u8],
+    ) -> Result<(), ErrorStack>;
+
+    /// Queues handshake data to be sent to the peer in CRYPTO frames at `level`.
+    fn add_handshake_data(
+        &self,
+        ssl: &mut SslRef,
+        level: QuicEncryptionLevel,
+        data: &[u8],
+    ) -> Result<(), ErrorStack>;
+
+    /// Signals that all queued handshake data should be transmitted.
+    fn flush_flight(&self, ssl: &mut SslRef) -> Result<(), ErrorStack>;
+
+    /// Sends a fatal alert to the peer as a QUIC CONNECTION_CLOSE at `level`.
+    fn send_alert(
+        &self,
+        ssl: &mut SslRef,
+        level: QuicEncryptionLevel,
+        alert: SslAlert,
+    ) -> Result<(), ErrorStack>;
+}
+
+#[cfg(boringssl)]
+static QUIC_METHOD: ffi::SSL_QUIC_METHOD = ffi::SSL_QUIC_METHOD {
+    set_read_secret: Some(raw_quic_set_read_secret),
+    set_write_secret: Some(raw_quic_set_write_secret),
+    add_handshake_data: Some(raw_quic_add_handshake_data),
+    flush_flight: Some(raw_quic_flush_flight),
+    send_alert: Some(raw_quic_send_alert),
+};
+
+/// A certificate compression algorithm, as defined in [RFC 8879].
+///
+/// Requires OpenSSL 3.2.0 or newer, or BoringSSL.
+///
+/// [RFC 8879]: https://www.rfc-editor.org/rfc/rfc8879
+#[cfg(any(ossl320, boringssl))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CertificateCompressionAlgorithm(u16);
+
+#[cfg(any(ossl320, boringssl))]
+impl CertificateCompressionAlgorithm {
+    /// zlib, as described in RFC 1950.
+    pub const ZLIB: CertificateCompressionAlgorithm = CertificateCompressionAlgorithm(1);
+
+    /// Brotli, as described in RFC 7932.
+    pub const BROTLI: CertificateCompressionAlgorithm = CertificateCompressionAlgorithm(2);
+
+    /// Zstandard, as described in RFC 8478.
+    pub const ZSTD: CertificateCompressionAlgorithm = CertificateCompressionAlgorithm(3);
+
+    /// Constructs a `CertificateCompressionAlgorithm` from its IANA code point.
+    pub fn from_raw(raw: u16) -> CertificateCompressionAlgorithm {
+        CertificateCompressionAlgorithm(raw)
+    }
+
+    /// Returns the IANA code point of the algorithm.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u16 {
+        self.0
+    }
+}
+
+/// A certificate type negotiated with the [RFC 7250] `client_certificate_type` and
+/// `server_certificate_type` extensions.
+///
+/// Requires OpenSSL 3.2.0 or newer.
+///
+/// [RFC 7250]: https://www.rfc-editor.org/rfc/rfc7250
+#[cfg(ossl320)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CertificateType(u8);
+
+#[cfg(ossl320)]
+impl CertificateType {
+    /// An X.509 certificate.
+    pub const X509: CertificateType = CertificateType(ffi::TLSEXT_cert_type_x509);
+
+    /// A bare public key, which must be authenticated out of band.
+    pub const RAW_PUBLIC_KEY: CertificateType = CertificateType(ffi::TLSEXT_cert_type_rpk);
+
+    /// Constructs a `CertificateType` from its IANA code point.
+    pub fn from_raw(raw: u8) -> CertificateType {
+        CertificateType(raw)
+    }
+
+    /// Returns the IANA code point of the certificate type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u8 {
+        self.0
+    }
+}
+
+/// An SSL/TLS protocol version.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslVersion(c_int);
+
+impl SslVersion {
+    /// SSLv3
+    pub const SSL3: SslVersion = SslVersion(ffi::SSL3_VERSION);
+
+    /// TLSv1.0
+    pub const TLS1: SslVersion = SslVersion(ffi::TLS1_VERSION);
+
+    /// TLSv1.1
+    pub const TLS1_1: SslVersion = SslVersion(ffi::TLS1_1_VERSION);
+
+    /// TLSv1.2
+    pub const TLS1_2: SslVersion = SslVersion(ffi::TLS1_2_VERSION);
+
+    /// TLSv1.3
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub const TLS1_3: SslVersion = SslVersion(ffi::TLS1_3_VERSION);
+}
+
+/// The type of a TLS handshake message.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SslHandshakeType(u8);
+
+impl SslHandshakeType {
+    pub const HELLO_REQUEST: SslHandshakeType = SslHandshakeType(ffi::SSL3_MT_HELLO_REQUEST as u8);
+    pub const CLIENT_HELLO: SslHandshakeType = SslHandshakeType(ffi::SSL3_MT_CLIENT_HELLO as u8);
+    pub const SERVER_HELLO: SslHandshakeType = SslHandshakeType(ffi::SSL3_MT_SERVER_HELLO as u8);
+    pub const NEW_SESSION_TICKET: SslHandshakeType =
+        SslHandshakeType(ffi::SSL3_MT_NEWSESSION_TICKET as u8);
+    #[cfg(ossl111)]
+    pub const END_OF_EARLY_DATA: SslHandshakeType =
+        SslHandshakeType(ffi::SSL3_MT_END_OF_EARLY_DATA as u8);
+    #[cfg(ossl111)]
+    pub const ENCRYPTED_EXTENSIONS: SslHandshakeType =
+        SslHandshakeType(ffi::SSL3_MT_ENCRYPTED_EXTENSIONS as u8);
+    pub const CERTIFICATE: SslHandshakeType = SslHandshakeType(ffi::SSL3_MT_CERTIFICATE as u8);
+    pub const SERVER_KEY_EXCHANGE: SslHandshakeType =
+        SslHandshakeType(ffi::SSL3_MT_SERVER_KEY_EXCHANGE as u8);
+    pub const CERTIFICATE_REQUEST: SslHandshakeType =
+        SslHandshakeType(ffi::SSL3_MT_CERTIFICATE_REQUEST as u8);
+    pub const SERVER_HELLO_DONE: SslHandshakeType =
+        SslHandshakeType(ffi::SSL3_MT_SERVER_DONE as u8);
+    pub const CERTIFICATE_VERIFY: SslHandshakeType =
+        SslHandshakeType(ffi::SSL3_MT_CERTIFICATE_VERIFY as u8);
+    pub const CLIENT_KEY_EXCHANGE: SslHandshakeType =
+        SslHandshakeType(ffi::SSL3_MT_CLIENT_KEY_EXCHANGE as u8);
+    pub const FINISHED: SslHandshakeType = SslHandshakeType(ffi::SSL3_MT_FINISHED as u8);
+    pub const CERTIFICATE_STATUS: SslHandshakeType =
+        SslHandshakeType(ffi::SSL3_MT_CERTIFICATE_STATUS as u8);
+    #[cfg(ossl111)]
+    pub const KEY_UPDATE: SslHandshakeType = SslHandshakeType(ffi::SSL3_MT_KEY_UPDATE as u8);
+    #[cfg(ossl320)]
+    pub const COMPRESSED_CERTIFICATE: SslHandshakeType =
+        SslHandshakeType(ffi::SSL3_MT_COMPRESSED_CERTIFICATE as u8);
+    pub const NEXT_PROTO: SslHandshakeType = SslHandshakeType(ffi::SSL3_MT_NEXT_PROTO as u8);
+    #[cfg(ossl111)]
+    pub const MESSAGE_HASH: SslHandshakeType = SslHandshakeType(ffi::SSL3_MT_MESSAGE_HASH as u8);
+
+    /// Constructs an `SslHandshakeType` from a raw value.
+    pub fn from_raw(raw: u8) -> SslHandshakeType {
+        SslHandshakeType(raw)
+    }
+
+    /// Returns the raw value of the handshake type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u8 {
+        self.0
+    }
+}
+
+/// The content type of a TLS record.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslContentType(c_int);
+
+impl SslContentType {
+    pub const CHANGE_CIPHER_SPEC: SslContentType = SslContentType(ffi::SSL3_RT_CHANGE_CIPHER_SPEC);
+    pub const ALERT: SslContentType = SslContentType(ffi::SSL3_RT_ALERT);
+    pub const HANDSHAKE: SslContentType = SslContentType(ffi::SSL3_RT_HANDSHAKE);
+    pub const APPLICATION_DATA: SslContentType = SslContentType(ffi::SSL3_RT_APPLICATION_DATA);
+
+    /// The pseudo content type used by message callbacks for record headers.
+    pub const HEADER: SslContentType = SslContentType(ffi::SSL3_RT_HEADER);
+
+    /// The pseudo content type used by message callbacks for the inner content type of
+    /// encrypted TLS 1.3 records.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub const INNER_CONTENT_TYPE: SslContentType = SslContentType(ffi::SSL3_RT_INNER_CONTENT_TYPE);
+
+    /// Constructs an `SslContentType` from a raw content type.
+    pub fn from_raw(raw: c_int) -> SslContentType {
+        SslContentType(raw)
+    }
+
+    /// Returns the raw content type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// The kind of a protocol message passed to a message callback.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SslMessageKind {
+    /// The header of a record.
+    RecordHeader,
+    /// The inner content type of an encrypted TLS 1.3 record.
+    InnerContentType,
+    /// A ChangeCipherSpec message.
+    ChangeCipherSpec,
+    /// An alert. Its data is the alert level followed by its description.
+    Alert,
+    /// A handshake message, including its 4 byte header.
+    Handshake(SslHandshakeType),
+    /// Application data.
+    ApplicationData,
+    /// A message with some other content type, such as a DTLS heartbeat.
+    Other(i32),
+}
+
+/// A protocol message sent or received on a connection.
+///
+/// Messages are passed to the callback configured with
+/// [`SslContextBuilder::set_message_callback`].
+pub struct SslMessage<'a> {
+    sent: bool,
+    version: c_int,
+    content_type: c_int,
+    data: &'a [u8],
+}
+
+impl<'a> SslMessage<'a> {
+    /// Returns `true` if the message was sent, or `false` if it was received.
+    pub fn is_sent(&self) -> bool {
+        self.sent
+    }
+
+    /// Returns the protocol version the message was sent or received with.
+    pub fn version(&self) -> SslVersion {
+        SslVersion(self.version)
+    }
+
+    /// Returns the raw content type of the message.
+    ///
+    /// [`SslContentType::from_raw`] converts it to a typed value.
+    pub fn content_type(&self) -> i32 {
+        self.content_type
+    }
+
+    /// Returns the level and description of an alert message.
+    ///
+    /// Returns `None` if the message is not a well-formed alert.
+    pub fn alert(&self) -> Option<(SslAlertLevel, SslAlert)> {
+        match (self.content_type, self.data) {
+            (ffi::SSL3_RT_ALERT, &[level, description]) => Some((
+                SslAlertLevel::from_raw(level as c_int),
+                SslAlert(description as c_int),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns the kind of the message.
+    pub fn kind(&self) -> SslMessageKind {
+        match self.content_type {
+            ffi::SSL3_RT_HEADER => SslMessageKind::RecordHeader,
+            #[cfg(ossl111)]
+            ffi::SSL3_RT_INNER_CONTENT_TYPE => SslMessageKind::InnerContentType,
+            ffi::SSL3_RT_CHANGE_CIPHER_SPEC => SslMessageKind::ChangeCipherSpec,
+            ffi::SSL3_RT_ALERT => SslMessageKind::Alert,
+            ffi::SSL3_RT_HANDSHAKE => match self.data.first() {
+                Some(&ty) => SslMessageKind::Handshake(SslHandshakeType(ty)),
+                None => SslMessageKind::Other(self.content_type),
+            },
+            ffi::SSL3_RT_APPLICATION_DATA => SslMessageKind::ApplicationData,
+            content_type => SslMessageKind::Other(content_type),
+        }
+    }
+
+    /// Returns the raw bytes of the message.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns a human readable description of the message, decoding handshake messages field by
+    /// field.
+    ///
+    /// `ssl` must be the connection the message was passed to the callback for.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer, built with `enable-ssl-trace`.
+    #[corresponds(SSL_trace)]
+    #[cfg(all(ossl102, not(osslconf = "OPENSSL_NO_SSL_TRACE")))]
+    pub fn trace(&self, ssl: &SslRef) -> Result<String, ErrorStack> {
+        let bio = MemBio::new()?;
+        unsafe {
+            ffi::SSL_trace(
+                self.sent as c_int,
+                self.version,
+                self.content_type,
+                self.data.as_ptr() as *const c_void,
+                self.data.len(),
+                ssl.as_ptr(),
+                bio.as_ptr() as *mut c_void,
+            );
+        }
+        Ok(String::from_utf8_lossy(bio.get_buf()).into_owned())
+    }
+}
+
+impl<'a> fmt::Debug for SslMessage<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SslMessage")
+            .field("sent", &self.sent)
+            .field("version", &self.version())
+            .field("kind", &self.kind())
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
+
+/// The kind of check performed by a security callback.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+#[cfg(ossl110)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslSecurityOp(c_int);
+
+#[cfg(ossl110)]
+impl SslSecurityOp {
+    /// A cipher is being considered for the local cipher list.
+    pub const CIPHER_SUPPORTED: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_CIPHER_SUPPORTED);
+    /// A cipher shared with the peer is being considered.
+    pub const CIPHER_SHARED: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_CIPHER_SHARED);
+    /// The cipher selected by the peer is being checked.
+    pub const CIPHER_CHECK: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_CIPHER_CHECK);
+    /// A group is being considered for the local group list.
+    pub const CURVE_SUPPORTED: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_CURVE_SUPPORTED);
+    /// A group shared with the peer is being considered.
+    pub const CURVE_SHARED: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_CURVE_SHARED);
+    /// The group selected by the peer is being checked.
+    pub const CURVE_CHECK: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_CURVE_CHECK);
+    /// Temporary Diffie-Hellman parameters are being checked.
+    pub const TMP_DH: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_TMP_DH);
+    /// A protocol version is being considered.
+    pub const VERSION: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_VERSION);
+    /// The use of session tickets is being considered.
+    pub const TICKET: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_TICKET);
+    /// A signature algorithm is being considered for the local list.
+    pub const SIGALG_SUPPORTED: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_SIGALG_SUPPORTED);
+    /// A signature algorithm shared with the peer is being considered.
+    pub const SIGALG_SHARED: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_SIGALG_SHARED);
+    /// The signature algorithm used by the peer is being checked.
+    pub const SIGALG_CHECK: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_SIGALG_CHECK);
+    /// A signature algorithm is being considered to determine the usable cipher suites.
+    pub const SIGALG_MASK: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_SIGALG_MASK);
+    /// The use of compression is being considered.
+    pub const COMPRESSION: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_COMPRESSION);
+    /// The key of a local end-entity certificate is being checked.
+    pub const EE_KEY: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_EE_KEY);
+    /// The key of a local CA certificate is being checked.
+    pub const CA_KEY: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_CA_KEY);
+    /// The signature digest of a local CA certificate is being checked.
+    pub const CA_MD: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_CA_MD);
+    /// The key of the peer's end-entity certificate is being checked.
+    pub const PEER_EE_KEY: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_PEER_EE_KEY);
+    /// The key of a CA certificate in the peer's chain is being checked.
+    pub const PEER_CA_KEY: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_PEER_CA_KEY);
+    /// The signature digest of a certificate in the peer's chain is being checked.
+    pub const PEER_CA_MD: SslSecurityOp = SslSecurityOp(ffi::SSL_SECOP_PEER_CA_MD);
+
+    /// Constructs an `SslSecurityOp` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> SslSecurityOp {
+        SslSecurityOp(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+
+    /// Returns `true` if the check concerns a certificate sent by the peer.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn is_peer(&self) -> bool {
+        self.0 & ffi::SSL_SECOP_PEER != 0
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn other_type(&self) -> c_int {
+        self.0 & ffi::SSL_SECOP_OTHER_TYPE
+    }
+}
+
+/// A check passed to a security callback.
+///
+/// Checks are passed to the callback configured with
+/// [`SslContextBuilder::set_security_callback`].
+///
+/// Requires OpenSSL 1.1.0 or newer.
+#[cfg(ossl110)]
+pub struct SslSecurityCheck<'a> {
+    ssl: Option<&'a SslRef>,
+    op: SslSecurityOp,
+    bits: c_int,
+    nid: c_int,
+    other: *mut c_void,
+}
+
+#[cfg(ossl110)]
+impl<'a> SslSecurityCheck<'a> {
+    /// Returns the connection being checked.
+    ///
+    /// This is `None` for checks made against the context itself, such as when a certificate is
+    /// configured with [`SslContextBuilder::set_certificate`].
+    pub fn ssl(&self) -> Option<&'a SslRef> {
+        self.ssl
+    }
+
+    /// Returns the kind of check being performed.
+    pub fn op(&self) -> SslSecurityOp {
+        self.op
+    }
+
+    /// Returns the number of security bits provided by the item being checked.
+    ///
+    /// For example, AES-128 provides 128 bits and a 2048 bit RSA key provides 112 bits.
+    pub fn bits(&self) -> i32 {
+        self.bits
+    }
+
+    /// Returns the identifier associated with the check.
+    ///
+    /// This is the group for group checks, and the digest for signature algorithm and
+    /// certificate signature checks. It is `Nid::UNDEF` for other checks.
+    pub fn nid(&self) -> Nid {
+        if self.op == SslSecurityOp::VERSION {
+            Nid::UNDEF
+        } else {
+            Nid::from_raw(self.nid)
+        }
+    }
+
+    /// Returns the protocol version being considered by a version check.
+    pub fn version(&self) -> Option<SslVersion> {
+        if self.op == SslSecurityOp::VERSION {
+            Some(SslVersion(self.nid))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the cipher being considered by a cipher check.
+    pub fn cipher(&self) -> Option<&'a SslCipherRef> {
+        if self.op.other_type() == ffi::SSL_SECOP_OTHER_CIPHER && !self.other.is_null() {
+            unsafe { Some(SslCipherRef::from_const_ptr(self.other as *const _)) }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the certificate being considered by a certificate check.
+    pub fn certificate(&self) -> Option<&'a X509Ref> {
+        if self.op.other_type() == ffi::SSL_SECOP_OTHER_CERT && !self.other.is_null() {
+            unsafe { Some(X509Ref::from_const_ptr(self.other as *const _)) }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the TLS code point of the group or signature algorithm being considered by a group
+    /// or signature algorithm check.
+    pub fn code_point(&self) -> Option<u16> {
+        let other_type = self.op.other_type();
+        if (other_type == ffi::SSL_SECOP_OTHER_CURVE || other_type == ffi::SSL_SECOP_OTHER_SIGALG)
+            && !self.other.is_null()
+        {
+            let bytes = unsafe { slice::from_raw_parts(self.other as *const u8, 2) };
+            Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(ossl110)]
+impl<'a> fmt::Debug for SslSecurityCheck<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SslSecurityCheck")
+            .field("op", &self.op)
+            .field("bits", &self.bits)
+            .field("nid", &self.nid())
+            .finish()
+    }
+}
+
+cfg_if! {
+    if #[cfg(boringssl)] {
+        type SslTy = i32;
+        type SslModeTy = i32;
+        type SslCacheTy = i64;
+        type SslCacheSize = c_ulong;
+        type MtuTy = u32;
+        type BitTy = u32;
+        type SizeTy = usize;
+    } else {
+        type SslTy = u64;
+        type SslModeTy = i64;
+        type SslCacheTy = i64;
+        type SslCacheSize = c_long;
+        type MtuTy = i64;
+        type BitTy = u64;
+        type SizeTy = u32;
+    }
+}
+
+/// A standard implementation of protocol selection for Application Layer Protocol Negotiation
+/// (ALPN).
+///
+/// `server` should contain the server's list of supported protocols and `client` the client's. They
+/// must both be in the ALPN wire format. See the documentation for
+/// [`SslContextBuilder::set_alpn_protos`] for details.
+///
+/// It will select the first protocol supported by the server which is also supported by the client.
+///
+/// [`SslContextBuilder::set_alpn_protos`]: struct.SslContextBuilder.html#method.set_alpn_protos
+#[corresponds(SSL_select_next_proto)]
+pub fn select_next_proto<'a>(server: &[u8], client: &'a [u8]) -> Option<&'a [u8]> {
+    unsafe {
+        let mut out = ptr::null_mut();
+        let mut outlen = 0;
+        let r = ffi::SSL_select_next_proto(
+            &mut out,
+            &mut outlen,
+            server.as_ptr(),
+            server.len() as c_uint,
+            client.as_ptr(),
+            client.len() as c_uint,
+        );
+        if r == ffi::OPENSSL_NPN_NEGOTIATED {
+            Some(slice::from_raw_parts(out as *const u8, outlen as usize))
+        } else {
+            None
+        }
+    }
+}
+
+/// Splits a big-endian `u16` length-prefixed vector off the front of a TLS message.
+#[cfg(ossl111)]
+fn read_u16_prefixed(buf: &[u8]) -> Option<(&[u8], &[u8])> {
+    if buf.len() < 2 {
+        return None;
+    }
+    let (len, rest) = buf.split_at(2);
+    let len = usize::from(u16::from_be_bytes([len[0], len[1]]));
+    if rest.len() < len {
+        return None;
+    }
+    Some(rest.split_at(len))
+}
+
+/// A builder for `SslContext`s.
+pub struct SslContextBuilder(SslContext);
+
+impl SslContextBuilder {
+    /// Creates a new `SslContextBuilder`.
+    #[corresponds(SSL_CTX_new)]
+    pub fn new(method: SslMethod) -> Result<SslContextBuilder, ErrorStack> {
+        unsafe {
+            init();
+            let ctx = cvt_p(ffi::SSL_CTX_new(method.as_ptr()))?;
+
+            Ok(SslContextBuilder::from_ptr(ctx))
+        }
+    }
+
+    /// Creates an `SslContextBuilder` from a pointer to a raw OpenSSL value.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the pointer is valid and uniquely owned by the builder.
+    pub unsafe fn from_ptr(ctx: *mut ffi::SSL_CTX) -> SslContextBuilder {
+        SslContextBuilder(SslContext::from_ptr(ctx))
+    }
+
+    /// Returns a pointer to the raw OpenSSL value.
+    pub fn as_ptr(&self) -> *mut ffi::SSL_CTX {
+        self.0.as_ptr()
+    }
+
+    /// Configures the certificate verification method for new connections.
+    #[corresponds(SSL_CTX_set_verify)]
+    pub fn set_verify(&mut self, mode: SslVerifyMode) {
+        unsafe {
+            ffi::SSL_CTX_set_verify(self.as_ptr(), mode.bits as c_int, None);
+        }
+    }
+
+    /// Replaces OpenSSL's certificate chain verification for new connections with a callback.
+    ///
+    /// The callback is passed the `X509StoreContext` before any verification has been performed,
+    /// and should return a boolean indicating if verification succeeded. It will typically end by
+    /// calling `X509StoreContextRef::verify_cert`, optionally after supplying additional untrusted
+    /// intermediates with `X509StoreContextRef::verify_cert_with_untrusted`, for example ones
+    /// fetched from the leaf certificate's `caIssuers` URIs.
+    ///
+    /// The verification mode configured with `set_verify` still determines whether a failure aborts
+    /// the handshake.
+    #[corresponds(SSL_CTX_set_cert_verify_callback)]
+    pub fn set_cert_verify_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut X509StoreContextRef) -> bool + 'static + Sync + Send,
+    {
+        unsafe {
+            let arg = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_cert_verify_callback(self.as_ptr(), Some(raw_cert_verify::<F>), arg);
+        }
     }
 
     /// Configures the certificate verification method for new connections and
     /// registers a verification callback.
     ///
-    /// The callback is passed a boolean indicating if OpenSSL's internal verification succeeded as
-    /// well as a reference to the `X509StoreContext` which can be used to examine the certificate
-    /// chain. It should return a boolean indicating if verification succeeded.
-    #[corresponds(SSL_CTX_set_verify)]
-    pub fn set_verify_callback<F>(&mut self, mode: SslVerifyMode, verify: F)
-    where
-        F: Fn(bool, &mut X509StoreContextRef) -> bool + 'static + Sync + Send,
-    {
+    /// The callback is passed a boolean indicating if OpenSSL's internal verification succeeded as
+    /// well as a reference to the `X509StoreContext` which can be used to examine the certificate
+    /// chain. It should return a boolean indicating if verification succeeded.
+    #[corresponds(SSL_CTX_set_verify)]
+    pub fn set_verify_callback<F>(&mut self, mode: SslVerifyMode, verify: F)
+    where
+        F: Fn(bool, &mut X509StoreContextRef) -> bool + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), verify);
+            ffi::SSL_CTX_set_verify(self.as_ptr(), mode.bits as c_int, Some(raw_verify::<F>));
+        }
+    }
+
+    /// Configures the server name indication (SNI) callback for new connections.
+    ///
+    /// SNI is used to allow a single server to handle requests for multiple domains, each of which
+    /// has its own certificate chain and configuration.
+    ///
+    /// Obtain the server name with the `servername` method and then set the corresponding context
+    /// with `set_ssl_context`
+    #[corresponds(SSL_CTX_set_tlsext_servername_callback)]
+    // FIXME tlsext prefix?
+    pub fn set_servername_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, &mut SslAlert) -> Result<(), SniError> + 'static + Sync + Send,
+    {
+        unsafe {
+            // The SNI callback is somewhat unique in that the callback associated with the original
+            // context associated with an SSL can be used even if the SSL's context has been swapped
+            // out. When that happens, we wouldn't be able to look up the callback's state in the
+            // context's ex data. Instead, pass the pointer directly as the servername arg. It's
+            // still stored in ex data to manage the lifetime.
+            let arg = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_tlsext_servername_arg(self.as_ptr(), arg);
+
+            let f: extern "C" fn(_, _, _) -> _ = raw_sni::<F>;
+            #[cfg(not(boringssl))]
+            let f: extern "C" fn() = mem::transmute(f);
+            ffi::SSL_CTX_set_tlsext_servername_callback(self.as_ptr(), Some(f));
+        }
+    }
+
+    /// Sets the certificate verification depth.
+    ///
+    /// If the peer's certificate chain is longer than this value, verification will fail.
+    #[corresponds(SSL_CTX_set_verify_depth)]
+    pub fn set_verify_depth(&mut self, depth: u32) {
+        unsafe {
+            ffi::SSL_CTX_set_verify_depth(self.as_ptr(), depth as c_int);
+        }
+    }
+
+    /// Enables or disables quiet shutdown.
+    ///
+    /// When enabled, shutting a session down marks it as fully shut down without sending a
+    /// close notify message to the peer or waiting for one in return. This is not standards
+    /// compliant, and leaves the peer unable to tell a deliberate close from a truncation attack.
+    ///
+    /// Defaults to `false`.
+    #[corresponds(SSL_CTX_set_quiet_shutdown)]
+    pub fn set_quiet_shutdown(&mut self, quiet: bool) {
+        unsafe { ffi::SSL_CTX_set_quiet_shutdown(self.as_ptr(), quiet as c_int) }
+    }
+
+    /// Enables or disables TLS 1.3 post-handshake authentication on the client side.
+    ///
+    /// Clients must opt in before a server can request a certificate after the handshake with
+    /// [`SslRef::verify_client_post_handshake`]. The client's certificate must be configured
+    /// before the handshake; it is sent when the server's request is processed while reading.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set_post_handshake_auth)]
+    #[cfg(ossl111)]
+    pub fn set_post_handshake_auth(&mut self, enable: bool) {
+        unsafe { ffi::SSL_CTX_set_post_handshake_auth(self.as_ptr(), enable as c_int) }
+    }
+
+    /// Sets a custom certificate store for verifying peer certificates.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_CTX_set0_verify_cert_store)]
+    #[cfg(ossl102)]
+    pub fn set_verify_cert_store(&mut self, cert_store: X509Store) -> Result<(), ErrorStack> {
+        unsafe {
+            let ptr = cert_store.as_ptr();
+            cvt(ffi::SSL_CTX_set0_verify_cert_store(self.as_ptr(), ptr) as c_int)?;
+            mem::forget(cert_store);
+
+            Ok(())
+        }
+    }
+
+    /// Replaces the context's certificate store.
+    #[corresponds(SSL_CTX_set_cert_store)]
+    pub fn set_cert_store(&mut self, cert_store: X509Store) {
+        unsafe {
+            ffi::SSL_CTX_set_cert_store(self.as_ptr(), cert_store.as_ptr());
+            mem::forget(cert_store);
+        }
+    }
+
+    /// Controls read ahead behavior.
+    ///
+    /// If enabled, OpenSSL will read as much data as is available from the underlying stream,
+    /// instead of a single record at a time.
+    ///
+    /// It has no effect when used with DTLS.
+    #[corresponds(SSL_CTX_set_read_ahead)]
+    pub fn set_read_ahead(&mut self, read_ahead: bool) {
+        unsafe {
+            ffi::SSL_CTX_set_read_ahead(self.as_ptr(), read_ahead as SslBitType);
+        }
+    }
+
+    /// Sets the mode used by the context, returning the previous mode.
+    #[corresponds(SSL_CTX_set_mode)]
+    pub fn set_mode(&mut self, mode: SslMode) -> SslMode {
+        unsafe {
+            let bits = ffi::SSL_CTX_set_mode(self.as_ptr(), mode.bits() as MtuTy) as SslModeTy;
+            SslMode { bits }
+        }
+    }
+
+    /// Sets the parameters to be used during ephemeral Diffie-Hellman key exchange.
+    #[corresponds(SSL_CTX_set_tmp_dh)]
+    pub fn set_tmp_dh(&mut self, dh: &DhRef<Params>) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_set_tmp_dh(self.as_ptr(), dh.as_ptr()) as c_int).map(|_| ()) }
+    }
+
+    /// Sets the callback which will generate parameters to be used during ephemeral Diffie-Hellman
+    /// key exchange.
+    ///
+    /// The callback is provided with a reference to the `Ssl` for the session, as well as a boolean
+    /// indicating if the selected cipher is export-grade, and the key length. The export and key
+    /// length options are archaic and should be ignored in almost all cases.
+    #[corresponds(SSL_CTX_set_tmp_dh_callback)]
+    pub fn set_tmp_dh_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, bool, u32) -> Result<Dh<Params>, ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+
+            #[cfg(not(boringssl))]
+            ffi::SSL_CTX_set_tmp_dh_callback__fixed_rust(self.as_ptr(), Some(raw_tmp_dh::<F>));
+            #[cfg(boringssl)]
+            ffi::SSL_CTX_set_tmp_dh_callback(self.as_ptr(), Some(raw_tmp_dh::<F>));
+        }
+    }
+
+    /// Sets the parameters to be used during ephemeral elliptic curve Diffie-Hellman key exchange.
+    #[corresponds(SSL_CTX_set_tmp_ecdh)]
+    pub fn set_tmp_ecdh(&mut self, key: &EcKeyRef<Params>) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_set_tmp_ecdh(self.as_ptr(), key.as_ptr()) as c_int).map(|_| ()) }
+    }
+
+    /// Sets the callback which will generate parameters to be used during ephemeral elliptic curve
+    /// Diffie-Hellman key exchange.
+    ///
+    /// The callback is provided with a reference to the `Ssl` for the session, as well as a boolean
+    /// indicating if the selected cipher is export-grade, and the key length. The export and key
+    /// length options are archaic and should be ignored in almost all cases.
+    ///
+    /// Requires OpenSSL 1.0.1 or 1.0.2.
+    #[corresponds(SSL_CTX_set_tmp_ecdh_callback)]
+    #[cfg(all(ossl101, not(ossl110)))]
+    #[deprecated(note = "this function leaks memory and does not exist on newer OpenSSL versions")]
+    pub fn set_tmp_ecdh_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, bool, u32) -> Result<EcKey<Params>, ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_tmp_ecdh_callback__fixed_rust(self.as_ptr(), Some(raw_tmp_ecdh::<F>));
+        }
+    }
+
+    /// Use the default locations of trusted certificates for verification.
+    ///
+    /// These locations are read from the `SSL_CERT_FILE` and `SSL_CERT_DIR` environment variables
+    /// if present, or defaults specified at OpenSSL build time otherwise.
+    #[corresponds(SSL_CTX_set_default_verify_paths)]
+    pub fn set_default_verify_paths(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_set_default_verify_paths(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Loads trusted root certificates from a file.
+    ///
+    /// The file should contain a sequence of PEM-formatted CA certificates.
+    #[corresponds(SSL_CTX_load_verify_locations)]
+    pub fn set_ca_file<P: AsRef<Path>>(&mut self, file: P) -> Result<(), ErrorStack> {
+        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_load_verify_locations(
+                self.as_ptr(),
+                file.as_ptr() as *const _,
+                ptr::null(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the list of CA names sent to the client.
+    ///
+    /// The CA certificates must still be added to the trust root - they are not automatically set
+    /// as trusted by this method.
+    #[corresponds(SSL_CTX_set_client_CA_list)]
+    pub fn set_client_ca_list(&mut self, list: Stack<X509Name>) {
+        unsafe {
+            ffi::SSL_CTX_set_client_CA_list(self.as_ptr(), list.as_ptr());
+            mem::forget(list);
+        }
+    }
+
+    /// Add the provided CA certificate to the list sent by the server to the client when
+    /// requesting client-side TLS authentication.
+    #[corresponds(SSL_CTX_add_client_CA)]
+    #[cfg(not(libressl))]
+    pub fn add_client_ca(&mut self, cacert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_add_client_CA(self.as_ptr(), cacert.as_ptr())).map(|_| ()) }
+    }
+
+    /// Set the context identifier for sessions.
+    ///
+    /// This value identifies the server's session cache to clients, telling them when they're
+    /// able to reuse sessions. It should be set to a unique value per server, unless multiple
+    /// servers share a session cache.
+    ///
+    /// This value should be set when using client certificates, or each request will fail its
+    /// handshake and need to be restarted.
+    #[corresponds(SSL_CTX_set_session_id_context)]
+    pub fn set_session_id_context(&mut self, sid_ctx: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(sid_ctx.len() <= c_uint::max_value() as usize);
+            cvt(ffi::SSL_CTX_set_session_id_context(
+                self.as_ptr(),
+                sid_ctx.as_ptr(),
+                sid_ctx.len() as SizeTy,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Loads a leaf certificate from a file.
+    ///
+    /// Only a single certificate will be loaded - use `add_extra_chain_cert` to add the remainder
+    /// of the certificate chain, or `set_certificate_chain_file` to load the entire chain from a
+    /// single file.
+    #[corresponds(SSL_CTX_use_certificate_file)]
+    pub fn set_certificate_file<P: AsRef<Path>>(
+        &mut self,
+        file: P,
+        file_type: SslFiletype,
+    ) -> Result<(), ErrorStack> {
+        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_use_certificate_file(
+                self.as_ptr(),
+                file.as_ptr() as *const _,
+                file_type.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Loads a certificate chain from a file.
+    ///
+    /// The file should contain a sequence of PEM-formatted certificates, the first being the leaf
+    /// certificate, and the remainder forming the chain of certificates up to and including the
+    /// trusted root certificate.
+    #[corresponds(SSL_CTX_use_certificate_chain_file)]
+    pub fn set_certificate_chain_file<P: AsRef<Path>>(
+        &mut self,
+        file: P,
+    ) -> Result<(), ErrorStack> {
+        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_use_certificate_chain_file(
+                self.as_ptr(),
+                file.as_ptr() as *const _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the leaf certificate.
+    ///
+    /// Use `add_extra_chain_cert` to add the remainder of the certificate chain.
+    #[corresponds(SSL_CTX_use_certificate)]
+    pub fn set_certificate(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_use_certificate(self.as_ptr(), cert.as_ptr())).map(|_| ()) }
+    }
+
+    /// Appends a certificate to the certificate chain.
+    ///
+    /// This chain should contain all certificates necessary to go from the certificate specified by
+    /// `set_certificate` to a trusted root.
+    #[corresponds(SSL_CTX_add_extra_chain_cert)]
+    pub fn add_extra_chain_cert(&mut self, cert: X509) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_add_extra_chain_cert(self.as_ptr(), cert.as_ptr()) as c_int)?;
+            mem::forget(cert);
+            Ok(())
+        }
+    }
+
+    /// Appends a certificate to the certificate chain of the current leaf certificate.
+    ///
+    /// Unlike [`add_extra_chain_cert`], the chain is tied to the leaf certificate, so contexts
+    /// with several certificates, such as an RSA and an ECDSA certificate, can serve a different
+    /// chain with each.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// [`add_extra_chain_cert`]: SslContextBuilder::add_extra_chain_cert
+    #[corresponds(SSL_CTX_add1_chain_cert)]
+    #[cfg(ossl102)]
+    pub fn add_chain_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_add1_chain_cert(self.as_ptr(), cert.as_ptr()) as c_int).map(|_| ())
+        }
+    }
+
+    /// Loads the private key from a file.
+    #[corresponds(SSL_CTX_use_PrivateKey_file)]
+    pub fn set_private_key_file<P: AsRef<Path>>(
+        &mut self,
+        file: P,
+        file_type: SslFiletype,
+    ) -> Result<(), ErrorStack> {
+        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_use_PrivateKey_file(
+                self.as_ptr(),
+                file.as_ptr() as *const _,
+                file_type.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the private key.
+    #[corresponds(SSL_CTX_use_PrivateKey)]
+    pub fn set_private_key<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe { cvt(ffi::SSL_CTX_use_PrivateKey(self.as_ptr(), key.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the list of supported ciphers for protocols before TLSv1.3.
+    ///
+    /// The `set_ciphersuites` method controls the cipher suites for TLSv1.3.
+    ///
+    /// See [`ciphers`] for details on the format.
+    ///
+    /// [`ciphers`]: https://www.openssl.org/docs/man1.1.0/apps/ciphers.html
+    #[corresponds(SSL_CTX_set_cipher_list)]
+    pub fn set_cipher_list(&mut self, cipher_list: &str) -> Result<(), ErrorStack> {
+        let cipher_list = CString::new(cipher_list).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_set_cipher_list(
+                self.as_ptr(),
+                cipher_list.as_ptr() as *const _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the list of supported ciphers for the TLSv1.3 protocol.
+    ///
+    /// The `set_cipher_list` method controls the cipher suites for protocols before TLSv1.3.
+    ///
+    /// The format consists of TLSv1.3 cipher suite names separated by `:` characters in order of
+    /// preference.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set_ciphersuites)]
+    #[cfg(ossl111)]
+    pub fn set_ciphersuites(&mut self, cipher_list: &str) -> Result<(), ErrorStack> {
+        let cipher_list = CString::new(cipher_list).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_set_ciphersuites(
+                self.as_ptr(),
+                cipher_list.as_ptr() as *const _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Enables ECDHE key exchange with an automatically chosen curve list.
+    ///
+    /// Requires OpenSSL 1.0.2.
+    #[corresponds(SSL_CTX_set_ecdh_auto)]
+    #[cfg(any(libressl, all(ossl102, not(ossl110))))]
+    pub fn set_ecdh_auto(&mut self, onoff: bool) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_set_ecdh_auto(self.as_ptr(), onoff as c_int)).map(|_| ()) }
+    }
+
+    /// Sets the options used by the context, returning the old set.
+    ///
+    /// # Note
+    ///
+    /// This *enables* the specified options, but does not disable unspecified options. Use
+    /// `clear_options` for that.
+    #[corresponds(SSL_CTX_set_options)]
+    pub fn set_options(&mut self, option: SslOptions) -> SslOptions {
+        let bits =
+            unsafe { ffi::SSL_CTX_set_options(self.as_ptr(), option.bits() as BitTy) } as SslTy;
+        SslOptions { bits }
+    }
+
+    /// Returns the options used by the context.
+    #[corresponds(SSL_CTX_get_options)]
+    pub fn options(&self) -> SslOptions {
+        let bits = unsafe { ffi::SSL_CTX_get_options(self.as_ptr()) } as SslTy;
+        SslOptions { bits }
+    }
+
+    /// Clears the options used by the context, returning the old set.
+    #[corresponds(SSL_CTX_clear_options)]
+    pub fn clear_options(&mut self, option: SslOptions) -> SslOptions {
+        let bits =
+            unsafe { ffi::SSL_CTX_clear_options(self.as_ptr(), option.bits() as BitTy) } as SslTy;
+        SslOptions { bits }
+    }
+
+    /// Sets the minimum supported protocol version.
+    ///
+    /// A value of `None` will enable protocol versions down the the lowest version supported by
+    /// OpenSSL.
+    ///
+    /// Requires OpenSSL 1.1.0 or LibreSSL 2.6.1 or newer.
+    #[corresponds(SSL_CTX_set_min_proto_version)]
+    #[cfg(any(ossl110, libressl261))]
+    pub fn set_min_proto_version(&mut self, version: Option<SslVersion>) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_set_min_proto_version(
+                self.as_ptr(),
+                version.map_or(0, |v| v.0 as _),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the maximum supported protocol version.
+    ///
+    /// A value of `None` will enable protocol versions down the the highest version supported by
+    /// OpenSSL.
+    ///
+    /// Requires OpenSSL 1.1.0 or or LibreSSL 2.6.1 or newer.
+    #[corresponds(SSL_CTX_set_max_proto_version)]
+    #[cfg(any(ossl110, libressl261))]
+    pub fn set_max_proto_version(&mut self, version: Option<SslVersion>) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_set_max_proto_version(
+                self.as_ptr(),
+                version.map_or(0, |v| v.0 as _),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Gets the minimum supported protocol version.
+    ///
+    /// A value of `None` indicates that all versions down the the lowest version supported by
+    /// OpenSSL are enabled.
+    ///
+    /// Requires OpenSSL 1.1.0g or LibreSSL 2.7.0 or newer.
+    #[corresponds(SSL_CTX_get_min_proto_version)]
+    #[cfg(any(ossl110g, libressl270))]
+    pub fn min_proto_version(&mut self) -> Option<SslVersion> {
+        unsafe {
+            let r = ffi::SSL_CTX_get_min_proto_version(self.as_ptr());
+            if r == 0 {
+                None
+            } else {
+                Some(SslVersion(r))
+            }
+        }
+    }
+
+    /// Gets the maximum supported protocol version.
+    ///
+    /// A value of `None` indicates that all versions down the the highest version supported by
+    /// OpenSSL are enabled.
+    ///
+    /// Requires OpenSSL 1.1.0g or LibreSSL 2.7.0 or newer.
+    #[corresponds(SSL_CTX_get_max_proto_version)]
+    #[cfg(any(ossl110g, libressl270))]
+    pub fn max_proto_version(&mut self) -> Option<SslVersion> {
+        unsafe {
+            let r = ffi::SSL_CTX_get_max_proto_version(self.as_ptr());
+            if r == 0 {
+                None
+            } else {
+                Some(SslVersion(r))
+            }
+        }
+    }
+
+    /// Sets the protocols to sent to the server for Application Layer Protocol Negotiation (ALPN).
+    ///
+    /// The input must be in ALPN "wire format". It consists of a sequence of supported protocol
+    /// names prefixed by their byte length. For example, the protocol list consisting of `spdy/1`
+    /// and `http/1.1` is encoded as `b"\x06spdy/1\x08http/1.1"`. The protocols are ordered by
+    /// preference.
+    ///
+    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    #[corresponds(SSL_CTX_set_alpn_protos)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn set_alpn_protos(&mut self, protocols: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(protocols.len() <= c_uint::max_value() as usize);
+            let r = ffi::SSL_CTX_set_alpn_protos(
+                self.as_ptr(),
+                protocols.as_ptr(),
+                protocols.len() as c_uint,
+            );
+            // fun fact, SSL_CTX_set_alpn_protos has a reversed return code D:
+            if r == 0 {
+                Ok(())
+            } else {
+                Err(ErrorStack::get())
+            }
+        }
+    }
+
+    /// Enables the DTLS extension "use_srtp" as defined in RFC5764.
+    #[corresponds(SSL_CTX_set_tlsext_use_srtp)]
+    pub fn set_tlsext_use_srtp(&mut self, protocols: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            let cstr = CString::new(protocols).unwrap();
+
+            let r = ffi::SSL_CTX_set_tlsext_use_srtp(self.as_ptr(), cstr.as_ptr());
+            // fun fact, set_tlsext_use_srtp has a reversed return code D:
+            if r == 0 {
+                Ok(())
+            } else {
+                Err(ErrorStack::get())
+            }
+        }
+    }
+
+    /// Sets the callback used by a server to select a protocol for Application Layer Protocol
+    /// Negotiation (ALPN).
+    ///
+    /// The callback is provided with the client's protocol list in ALPN wire format. See the
+    /// documentation for [`SslContextBuilder::set_alpn_protos`] for details. It should return one
+    /// of those protocols on success. The [`select_next_proto`] function implements the standard
+    /// protocol selection algorithm.
+    ///
+    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    ///
+    /// [`SslContextBuilder::set_alpn_protos`]: struct.SslContextBuilder.html#method.set_alpn_protos
+    /// [`select_next_proto`]: fn.select_next_proto.html
+    #[corresponds(SSL_CTX_set_alpn_select_cb)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn set_alpn_select_callback<F>(&mut self, callback: F)
+    where
+        F: for<'a> Fn(&mut SslRef, &'a [u8]) -> Result<&'a [u8], AlpnError> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_alpn_select_cb__fixed_rust(
+                self.as_ptr(),
+                Some(callbacks::raw_alpn_select::<F>),
+                ptr::null_mut(),
+            );
+        }
+    }
+
+    /// Checks for consistency between the private key and certificate.
+    #[corresponds(SSL_CTX_check_private_key)]
+    pub fn check_private_key(&self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_check_private_key(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Returns a shared reference to the context's certificate store.
+    #[corresponds(SSL_CTX_get_cert_store)]
+    pub fn cert_store(&self) -> &X509StoreBuilderRef {
+        unsafe { X509StoreBuilderRef::from_ptr(ffi::SSL_CTX_get_cert_store(self.as_ptr())) }
+    }
+
+    /// Returns a mutable reference to the context's certificate store.
+    #[corresponds(SSL_CTX_get_cert_store)]
+    pub fn cert_store_mut(&mut self) -> &mut X509StoreBuilderRef {
+        unsafe { X509StoreBuilderRef::from_ptr_mut(ffi::SSL_CTX_get_cert_store(self.as_ptr())) }
+    }
+
+    /// Returns a reference to the X509 verification configuration.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_CTX_get0_param)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn verify_param(&self) -> &X509VerifyParamRef {
+        unsafe { X509VerifyParamRef::from_ptr(ffi::SSL_CTX_get0_param(self.as_ptr())) }
+    }
+
+    /// Returns a mutable reference to the X509 verification configuration.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_CTX_get0_param)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn verify_param_mut(&mut self) -> &mut X509VerifyParamRef {
+        unsafe { X509VerifyParamRef::from_ptr_mut(ffi::SSL_CTX_get0_param(self.as_ptr())) }
+    }
+
+    /// Sets the callback dealing with OCSP stapling.
+    ///
+    /// On the client side, this callback is responsible for validating the OCSP status response
+    /// returned by the server. The status may be retrieved with the `SslRef::ocsp_status` method.
+    /// A response of `Ok(true)` indicates that the OCSP status is valid, and a response of
+    /// `Ok(false)` indicates that the OCSP status is invalid and the handshake should be
+    /// terminated.
+    ///
+    /// On the server side, this callback is responsible for setting the OCSP status response to be
+    /// returned to clients. The status may be set with the `SslRef::set_ocsp_status` method. A
+    /// response of `Ok(true)` indicates that the OCSP status should be returned to the client, and
+    /// `Ok(false)` indicates that the status should not be returned to the client.
+    #[corresponds(SSL_CTX_set_tlsext_status_cb)]
+    pub fn set_status_callback<F>(&mut self, callback: F) -> Result<(), ErrorStack>
+    where
+        F: Fn(&mut SslRef) -> Result<bool, ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            cvt(
+                ffi::SSL_CTX_set_tlsext_status_cb(self.as_ptr(), Some(raw_tlsext_status::<F>))
+                    as c_int,
+            )
+            .map(|_| ())
+        }
+    }
+
+    /// Sets a client-side callback which validates the OCSP response stapled by the server.
+    ///
+    /// This is a typed alternative to [`set_status_callback`] for clients. The callback is passed
+    /// the parsed response, or `None` if the server did not staple one. It runs after the server's
+    /// certificate chain has been verified, so [`SslRef::verified_chain`] can be used to find the
+    /// issuer needed to check the response. A response of `Ok(true)` continues the handshake, and
+    /// `Ok(false)` terminates it. A stapled response which cannot be parsed terminates the
+    /// handshake with an error.
+    ///
+    /// The callback is only invoked if the client requested a status response with
+    /// [`SslRef::set_status_type`].
+    ///
+    /// [`set_status_callback`]: SslContextBuilder::set_status_callback
+    #[corresponds(SSL_CTX_set_tlsext_status_cb)]
+    #[cfg(not(any(boringssl, osslconf = "OPENSSL_NO_OCSP")))]
+    pub fn set_ocsp_response_callback<F>(&mut self, callback: F) -> Result<(), ErrorStack>
+    where
+        F: Fn(&mut SslRef, Option<OcspResponse>) -> Result<bool, ErrorStack>
+            + 'static
+            + Sync
+            + Send,
+    {
+        self.set_status_callback(move |ssl| {
+            let response = ssl.ocsp_response()?;
+            callback(ssl, response)
+        })
+    }
+
+    /// Sets the callback for providing an identity and pre-shared key for a TLS-PSK client.
+    ///
+    /// The callback will be called with the SSL context, an identity hint if one was provided
+    /// by the server, a mutable slice for each of the identity and pre-shared key bytes. The
+    /// identity must be written as a null-terminated C string.
+    #[corresponds(SSL_CTX_set_psk_client_callback)]
+    #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
+    pub fn set_psk_client_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, Option<&[u8]>, &mut [u8], &mut [u8]) -> Result<usize, ErrorStack>
+            + 'static
+            + Sync
+            + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_psk_client_callback(self.as_ptr(), Some(raw_client_psk::<F>));
+        }
+    }
+
+    #[deprecated(since = "0.10.10", note = "renamed to `set_psk_client_callback`")]
+    #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
+    pub fn set_psk_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, Option<&[u8]>, &mut [u8], &mut [u8]) -> Result<usize, ErrorStack>
+            + 'static
+            + Sync
+            + Send,
+    {
+        self.set_psk_client_callback(callback)
+    }
+
+    /// Sets the callback for providing an identity and pre-shared key for a TLS-PSK server.
+    ///
+    /// The callback will be called with the SSL context, an identity provided by the client,
+    /// and, a mutable slice for the pre-shared key bytes. The callback returns the number of
+    /// bytes in the pre-shared key.
+    #[corresponds(SSL_CTX_set_psk_server_callback)]
+    #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
+    pub fn set_psk_server_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, Option<&[u8]>, &mut [u8]) -> Result<usize, ErrorStack>
+            + 'static
+            + Sync
+            + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_psk_server_callback(self.as_ptr(), Some(raw_server_psk::<F>));
+        }
+    }
+
+    /// Sets the identity hint sent by a TLS-PSK server to help the client select a pre-shared key.
+    ///
+    /// It is passed to the client's [`set_psk_client_callback`] callback. TLS 1.3 has no
+    /// equivalent.
+    ///
+    /// [`set_psk_client_callback`]: SslContextBuilder::set_psk_client_callback
+    #[corresponds(SSL_CTX_use_psk_identity_hint)]
+    #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
+    pub fn set_psk_identity_hint(&mut self, hint: &str) -> Result<(), ErrorStack> {
+        let hint = CString::new(hint).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_use_psk_identity_hint(
+                self.as_ptr(),
+                hint.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the callback used by a TLS-SRP server to look up the verifier of a user.
+    ///
+    /// The callback is called once the client's username is known, which is available from
+    /// [`SslRef::srp_username`]. It should pass the user's verifier to
+    /// [`SslRef::set_srp_server_param`], or return an alert to abort the handshake, typically
+    /// [`SslAlert::UNKNOWN_PSK_IDENTITY`] if the user is not known.
+    ///
+    /// SRP is only supported up to TLS 1.2, and requires one of the `SRP` cipher suites to be
+    /// enabled with [`set_cipher_list`].
+    ///
+    /// [`set_cipher_list`]: SslContextBuilder::set_cipher_list
+    #[corresponds(SSL_CTX_set_srp_username_callback)]
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+    pub fn set_srp_username_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef) -> Result<(), SslAlert> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_srp_username_callback(self.as_ptr(), Some(raw_srp_username::<F>));
+        }
+    }
+
+    /// Sets the username a TLS-SRP client authenticates as.
+    #[corresponds(SSL_CTX_set_srp_username)]
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+    pub fn set_srp_username(&mut self, username: &str) -> Result<(), ErrorStack> {
+        let username = CString::new(username).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_set_srp_username(
+                self.as_ptr(),
+                username.as_ptr() as *mut _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the password a TLS-SRP client authenticates with.
+    #[corresponds(SSL_CTX_set_srp_password)]
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+    pub fn set_srp_password(&mut self, password: &str) -> Result<(), ErrorStack> {
+        let password = CString::new(password).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_set_srp_password(
+                self.as_ptr(),
+                password.as_ptr() as *mut _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the minimum size in bits of the group a TLS-SRP client accepts from the server.
+    ///
+    /// Defaults to 1024.
+    #[corresponds(SSL_CTX_set_srp_strength)]
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+    pub fn set_srp_strength(&mut self, bits: u32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_set_srp_strength(self.as_ptr(), bits as c_int)).map(|_| ()) }
+    }
+
+    /// Sets the callback used by a TLS 1.3 client to offer an external pre-shared key.
+    ///
+    /// The callback is passed the handshake digest of the cipher suite the server selected, or
+    /// `None` for the initial hello. It returns the PSK identity along with a session holding the
+    /// key, which can be constructed with [`SslSession::from_psk`], or `None` to offer no PSK. The
+    /// session's cipher suite must use the digest passed to the callback, if any.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// [`SslSession::from_psk`]: struct.SslSession.html#method.from_psk
+    #[corresponds(SSL_CTX_set_psk_use_session_callback)]
+    #[cfg(ossl111)]
+    pub fn set_psk_use_session_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(
+                &mut SslRef,
+                Option<MessageDigest>,
+            ) -> Result<Option<(Vec<u8>, SslSession)>, ErrorStack>
+            + 'static
+            + Sync
+            + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_psk_use_session_callback(
+                self.as_ptr(),
+                Some(raw_psk_use_session::<F>),
+            );
+        }
+    }
+
+    /// Sets the callback used by a TLS 1.3 server to look up an external pre-shared key.
+    ///
+    /// The callback is passed the PSK identity offered by the client, and returns a session
+    /// holding the corresponding key, or `None` if the identity is not known.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set_psk_find_session_callback)]
+    #[cfg(ossl111)]
+    pub fn set_psk_find_session_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, &[u8]) -> Result<Option<SslSession>, ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_psk_find_session_callback(
+                self.as_ptr(),
+                Some(raw_psk_find_session::<F>),
+            );
+        }
+    }
+
+    /// Sets the certificate compression algorithms supported by this context, in order of
+    /// preference.
+    ///
+    /// The list is advertised to the peer in the `compress_certificate` extension, and used to
+    /// pick an algorithm for the certificates sent to a peer which advertises one as well.
+    /// Algorithms OpenSSL was built without are ignored.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_CTX_set1_cert_comp_preference)]
+    #[cfg(ossl320)]
+    pub fn set_certificate_compression_preference(
+        &mut self,
+        algorithms: &[CertificateCompressionAlgorithm],
+    ) -> Result<(), ErrorStack> {
+        let mut algorithms = algorithms
+            .iter()
+            .map(|a| c_int::from(a.as_raw()))
+            .collect::<Vec<_>>();
+        unsafe {
+            cvt(ffi::SSL_CTX_set1_cert_comp_preference(
+                self.as_ptr(),
+                algorithms.as_mut_ptr(),
+                algorithms.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Compresses the context's certificate chain ahead of time with each algorithm in its
+    /// certificate compression preference list.
+    ///
+    /// Otherwise, the chain is compressed again for every handshake. This must be called after
+    /// the certificate chain is configured.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_CTX_compress_certs)]
+    #[cfg(ossl320)]
+    pub fn compress_certificates(&mut self) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_compress_certs(
+                self.as_ptr(),
+                ffi::TLSEXT_comp_cert_none,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Registers a certificate compression algorithm.
+    ///
+    /// `compress` is passed the encoded `Certificate` message to send and returns its compressed
+    /// form. `decompress` is passed a compressed `Certificate` message received from the peer and
+    /// the uncompressed length the peer claims, and returns the decompressed message, which must
+    /// be exactly that long.
+    ///
+    /// Algorithms are advertised in the order they are registered.
+    ///
+    /// BoringSSL does not tell the callbacks which algorithm they are invoked for, so they are
+    /// looked up by type. Registering a callback whose type is already used by another algorithm
+    /// of this context fails.
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_CTX_add_cert_compression_alg)]
+    #[cfg(boringssl)]
+    pub fn add_certificate_compression_algorithm<C, D>(
+        &mut self,
+        algorithm: CertificateCompressionAlgorithm,
+        compress: C,
+        decompress: D,
+    ) -> Result<(), ErrorStack>
+    where
+        C: Fn(&mut SslRef, &[u8]) -> Result<Vec<u8>, ErrorStack> + 'static + Sync + Send,
+        D: Fn(&mut SslRef, &[u8], usize) -> Result<Vec<u8>, ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            let compress_index = SslContext::cached_ex_index::<C>();
+            let decompress_index = SslContext::cached_ex_index::<D>();
+            if !ffi::SSL_CTX_get_ex_data(self.as_ptr(), compress_index.as_raw()).is_null()
+                || !ffi::SSL_CTX_get_ex_data(self.as_ptr(), decompress_index.as_raw()).is_null()
+            {
+                push_error(ffi::ERR_LIB_SSL, ffi::ERR_R_SHOULD_NOT_HAVE_BEEN_CALLED);
+                return Err(ErrorStack::get());
+            }
+
+            self.set_ex_data(compress_index, compress);
+            self.set_ex_data(decompress_index, decompress);
+            cvt(ffi::SSL_CTX_add_cert_compression_alg(
+                self.as_ptr(),
+                algorithm.as_raw(),
+                Some(raw_compress_certificate::<C>),
+                Some(raw_decompress_certificate::<D>),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the keys used by a server to decrypt Encrypted Client Hello messages.
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_CTX_set1_ech_keys)]
+    #[cfg(boringssl)]
+    pub fn set_ech_keys(&mut self, keys: &SslEchKeysRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_set1_ech_keys(self.as_ptr(), keys.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the callback which is called when new sessions are negotiated.
+    ///
+    /// This can be used by clients to implement session caching. While in TLSv1.2 the session is
+    /// available to access via [`SslRef::session`] immediately after the handshake completes, this
+    /// is not the case for TLSv1.3. There, a session is not generally available immediately, and
+    /// the server may provide multiple session tokens to the client over a single session. The new
+    /// session callback is a portable way to deal with both cases.
+    ///
+    /// Note that session caching must be enabled for the callback to be invoked, and it defaults
+    /// off for clients. [`set_session_cache_mode`] controls that behavior.
+    ///
+    /// [`SslRef::session`]: struct.SslRef.html#method.session
+    /// [`set_session_cache_mode`]: #method.set_session_cache_mode
+    #[corresponds(SSL_CTX_sess_set_new_cb)]
+    pub fn set_new_session_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, SslSession) + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_sess_set_new_cb(self.as_ptr(), Some(callbacks::raw_new_session::<F>));
+        }
+    }
+
+    /// Sets the callback which is called when sessions are removed from the context.
+    ///
+    /// Sessions can be removed because they have timed out or because they are considered faulty.
+    #[corresponds(SSL_CTX_sess_set_remove_cb)]
+    pub fn set_remove_session_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslContextRef, &SslSessionRef) + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_sess_set_remove_cb(
+                self.as_ptr(),
+                Some(callbacks::raw_remove_session::<F>),
+            );
+        }
+    }
+
+    /// Sets the callback which is called when a client proposed to resume a session but it was not
+    /// found in the internal cache.
+    ///
+    /// The callback is passed a reference to the session ID provided by the client. It should
+    /// return the session corresponding to that ID if available. This is only used for servers, not
+    /// clients.
+    ///
+    /// # Safety
+    ///
+    /// The returned `SslSession` must not be associated with a different `SslContext`.
+    #[corresponds(SSL_CTX_sess_set_get_cb)]
+    pub unsafe fn set_get_session_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, &[u8]) -> Option<SslSession> + 'static + Sync + Send,
+    {
+        self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+        ffi::SSL_CTX_sess_set_get_cb(self.as_ptr(), Some(callbacks::raw_get_session::<F>));
+    }
+
+    /// Sets the TLS key logging callback.
+    ///
+    /// The callback is invoked whenever TLS key material is generated, and is passed a line of NSS
+    /// SSLKEYLOGFILE-formatted text. This can be used by tools like Wireshark to decrypt message
+    /// traffic. The line does not contain a trailing newline.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set_keylog_callback)]
+    #[cfg(ossl111)]
+    pub fn set_keylog_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef, &str) + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_keylog_callback(self.as_ptr(), Some(callbacks::raw_keylog::<F>));
+        }
+    }
+
+    /// Sets a callback which is informed of connection lifecycle events.
+    ///
+    /// The callback is invoked when a handshake starts and completes, as the handshake moves
+    /// between states, and for every alert sent or received on connections made with the context.
+    /// It is intended for logging and cannot affect the connection.
+    #[corresponds(SSL_CTX_set_info_callback)]
+    pub fn set_info_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef, SslInfoEvent) + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_info_callback(self.as_ptr(), Some(callbacks::raw_info::<F>));
+        }
+    }
+
+    /// Sets the security level of the context.
+    ///
+    /// The level determines the minimum key sizes, signature digests and protocol versions
+    /// permitted by the default security policy, from 0 (everything permitted) to 5.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CTX_set_security_level)]
+    #[cfg(ossl110)]
+    pub fn set_security_level(&mut self, level: u32) {
+        unsafe { ffi::SSL_CTX_set_security_level(self.as_ptr(), level as c_int) }
+    }
+
+    /// Sets the callback used to enforce the security policy of the context.
+    ///
+    /// The callback is consulted whenever OpenSSL considers a cipher, group, signature algorithm,
+    /// protocol version or certificate key, and returns `true` to permit it. It replaces the
+    /// default policy derived from the [security level] entirely, so anything the callback
+    /// permits is allowed regardless of the configured level.
+    ///
+    /// Connections copy the callback when they are created, so it must be configured before any
+    /// `Ssl` is created from the context.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [security level]: SslContextBuilder::set_security_level
+    #[corresponds(SSL_CTX_set_security_callback)]
+    #[cfg(ossl110)]
+    pub fn set_security_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslSecurityCheck<'_>) -> bool + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_security_callback(self.as_ptr(), Some(raw_security::<F>));
+        }
+    }
+
+    /// Sets the callback for observing protocol messages.
+    ///
+    /// The callback is invoked with each record header, handshake message, alert and
+    /// ChangeCipherSpec message sent or received on connections made with the context. It is
+    /// intended for debugging; [`SslMessage::trace`] formats messages in detail.
+    #[corresponds(SSL_CTX_set_msg_callback)]
+    pub fn set_message_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef, &SslMessage<'_>) + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_msg_callback(self.as_ptr(), Some(raw_message::<F>));
+        }
+    }
+
+    /// Enables DANE verification for connections made with the context.
+    ///
+    /// This must be called before [`SslRef::dane_enable`] can be used on individual connections.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CTX_dane_enable)]
+    #[cfg(ossl110)]
+    pub fn dane_enable(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_dane_enable(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Configures the digest used for a TLSA matching type.
+    ///
+    /// `ord` sets the preference of the matching type; when a peer's TLSA records use several
+    /// matching types, only those with the highest preference are considered. Passing `None` for
+    /// `md` disables the matching type.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CTX_dane_mtype_set)]
+    #[cfg(ossl110)]
+    pub fn dane_mtype_set(
+        &mut self,
+        md: Option<MessageDigest>,
+        matching_type: TlsaMatchingType,
+        ord: u8,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_dane_mtype_set(
+                self.as_ptr(),
+                md.map_or(ptr::null(), |md| md.as_ptr()),
+                matching_type.as_raw(),
+                ord,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets flags controlling DANE verification for connections made with the context.
+    ///
+    /// Returns the previous flags.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CTX_dane_set_flags)]
+    #[cfg(ossl110)]
+    pub fn set_dane_flags(&mut self, flags: DaneFlags) -> DaneFlags {
+        unsafe {
+            DaneFlags::from_bits_truncate(ffi::SSL_CTX_dane_set_flags(self.as_ptr(), flags.bits()))
+        }
+    }
+
+    /// Clears flags controlling DANE verification for connections made with the context.
+    ///
+    /// Returns the previous flags.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CTX_dane_clear_flags)]
+    #[cfg(ossl110)]
+    pub fn clear_dane_flags(&mut self, flags: DaneFlags) -> DaneFlags {
+        unsafe {
+            DaneFlags::from_bits_truncate(ffi::SSL_CTX_dane_clear_flags(
+                self.as_ptr(),
+                flags.bits(),
+            ))
+        }
+    }
+
+    /// Sets the session caching mode use for connections made with the context.
+    ///
+    /// Returns the previous session caching mode.
+    #[corresponds(SSL_CTX_set_session_cache_mode)]
+    pub fn set_session_cache_mode(&mut self, mode: SslSessionCacheMode) -> SslSessionCacheMode {
+        unsafe {
+            let bits = ffi::SSL_CTX_set_session_cache_mode(self.as_ptr(), mode.bits());
+            SslSessionCacheMode { bits }
+        }
+    }
+
+    /// Sets the callback for generating an application cookie for TLS1.3
+    /// stateless handshakes.
+    ///
+    /// The callback will be called with the SSL context and a slice into which the cookie
+    /// should be written. The callback should return the number of bytes written.
+    #[corresponds(SSL_CTX_set_stateless_cookie_generate_cb)]
+    #[cfg(ossl111)]
+    pub fn set_stateless_cookie_generate_cb<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, &mut [u8]) -> Result<usize, ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_stateless_cookie_generate_cb(
+                self.as_ptr(),
+                Some(raw_stateless_cookie_generate::<F>),
+            );
+        }
+    }
+
+    /// Sets the callback for verifying an application cookie for TLS1.3
+    /// stateless handshakes.
+    ///
+    /// The callback will be called with the SSL context and the cookie supplied by the
+    /// client. It should return true if and only if the cookie is valid.
+    ///
+    /// Note that the OpenSSL implementation independently verifies the integrity of
+    /// application cookies using an HMAC before invoking the supplied callback.
+    #[corresponds(SSL_CTX_set_stateless_cookie_verify_cb)]
+    #[cfg(ossl111)]
+    pub fn set_stateless_cookie_verify_cb<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, &[u8]) -> bool + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_stateless_cookie_verify_cb(
+                self.as_ptr(),
+                Some(raw_stateless_cookie_verify::<F>),
+            )
+        }
+    }
+
+    /// Sets the callback for generating a DTLSv1 cookie
+    ///
+    /// The callback will be called with the SSL context and a slice into which the cookie
+    /// should be written. The callback should return the number of bytes written.
+    #[corresponds(SSL_CTX_set_cookie_generate_cb)]
+    #[cfg(not(boringssl))]
+    pub fn set_cookie_generate_cb<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, &mut [u8]) -> Result<usize, ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_cookie_generate_cb(self.as_ptr(), Some(raw_cookie_generate::<F>));
+        }
+    }
+
+    /// Sets the callback for verifying a DTLSv1 cookie
+    ///
+    /// The callback will be called with the SSL context and the cookie supplied by the
+    /// client. It should return true if and only if the cookie is valid.
+    #[corresponds(SSL_CTX_set_cookie_verify_cb)]
+    #[cfg(not(boringssl))]
+    pub fn set_cookie_verify_cb<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, &[u8]) -> bool + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_cookie_verify_cb(self.as_ptr(), Some(raw_cookie_verify::<F>));
+        }
+    }
+
+    /// Sets the extra data at the specified index.
+    ///
+    /// This can be used to provide data to callbacks registered with the context. Use the
+    /// `SslContext::new_ex_index` method to create an `Index`.
+    #[corresponds(SSL_CTX_set_ex_data)]
+    pub fn set_ex_data<T>(&mut self, index: Index<SslContext, T>, data: T) {
+        self.set_ex_data_inner(index, data);
+    }
+
+    fn set_ex_data_inner<T>(&mut self, index: Index<SslContext, T>, data: T) -> *mut c_void {
+        unsafe {
+            let data = Box::into_raw(Box::new(data)) as *mut c_void;
+            ffi::SSL_CTX_set_ex_data(self.as_ptr(), index.as_raw(), data);
+            data
+        }
+    }
+
+    /// Adds a custom extension for a TLS/DTLS client or server for all supported protocol versions.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_add_custom_ext)]
+    #[cfg(ossl111)]
+    pub fn add_custom_ext<AddFn, ParseFn, T>(
+        &mut self,
+        ext_type: u16,
+        context: ExtensionContext,
+        add_cb: AddFn,
+        parse_cb: ParseFn,
+    ) -> Result<(), ErrorStack>
+    where
+        AddFn: Fn(
+                &mut SslRef,
+                ExtensionContext,
+                Option<(usize, &X509Ref)>,
+            ) -> Result<Option<T>, SslAlert>
+            + 'static
+            + Sync
+            + Send,
+        T: AsRef<[u8]> + 'static + Sync + Send,
+        ParseFn: Fn(
+                &mut SslRef,
+                ExtensionContext,
+                &[u8],
+                Option<(usize, &X509Ref)>,
+            ) -> Result<(), SslAlert>
+            + 'static
+            + Sync
+            + Send,
+    {
+        let ret = unsafe {
+            let add_arg = self.retain_custom_ext_callback(add_cb);
+            let parse_arg = self.retain_custom_ext_callback(parse_cb);
+
+            ffi::SSL_CTX_add_custom_ext(
+                self.as_ptr(),
+                ext_type as c_uint,
+                context.bits(),
+                Some(raw_custom_ext_add::<AddFn, T>),
+                Some(raw_custom_ext_free::<T>),
+                add_arg,
+                Some(raw_custom_ext_parse::<ParseFn>),
+                parse_arg,
+            )
+        };
+        if ret == 1 {
+            Ok(())
+        } else {
+            Err(ErrorStack::get())
+        }
+    }
+
+    /// Moves a custom extension callback into the context, returning a pointer to it which remains
+    /// valid until the context is freed.
+    #[cfg(ossl111)]
+    fn retain_custom_ext_callback<F>(&mut self, callback: F) -> *mut c_void
+    where
+        F: 'static + Sync + Send,
+    {
+        unsafe {
+            let idx = SslContext::cached_ex_index::<CustomExtCallbacks>();
+            let mut callbacks =
+                ffi::SSL_CTX_get_ex_data(self.as_ptr(), idx.as_raw()) as *mut CustomExtCallbacks;
+            if callbacks.is_null() {
+                callbacks = self.set_ex_data_inner(idx, CustomExtCallbacks(vec![])) as *mut _;
+            }
+
+            let callback = Box::new(callback);
+            let ptr = &*callback as *const F as *mut c_void;
+            (*callbacks).0.push(callback);
+            ptr
+        }
+    }
+
+    /// Sets the maximum amount of early data that will be accepted on incoming connections.
+    ///
+    /// Defaults to 0.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn set_max_early_data(&mut self, bytes: u32) -> Result<(), ErrorStack> {
+        if unsafe { ffi::SSL_CTX_set_max_early_data(self.as_ptr(), bytes) } == 1 {
+            Ok(())
+        } else {
+            Err(ErrorStack::get())
+        }
+    }
+
+    /// Sets the maximum amount of early data that will be read before early data is rejected.
+    ///
+    /// Unlike `set_max_early_data`, this value is not advertised to clients in session tickets, so
+    /// it can be lowered without invalidating tickets which have already been issued.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set_recv_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn set_recv_max_early_data(&mut self, bytes: u32) -> Result<(), ErrorStack> {
+        if unsafe { ffi::SSL_CTX_set_recv_max_early_data(self.as_ptr(), bytes) } == 1 {
+            Ok(())
+        } else {
+            Err(ErrorStack::get())
+        }
+    }
+
+    /// Sets a callback which will be invoked just after the client's hello message is received.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set_client_hello_cb)]
+    #[cfg(ossl111)]
+    pub fn set_client_hello_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, &mut SslAlert) -> Result<ClientHelloResponse, ErrorStack>
+            + 'static
+            + Sync
+            + Send,
+    {
+        unsafe {
+            let ptr = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_client_hello_cb(
+                self.as_ptr(),
+                Some(callbacks::raw_client_hello::<F>),
+                ptr,
+            );
+        }
+    }
+
+    /// Sets a callback which will be invoked when an asynchronous job paused by
+    /// [`SslMode::ASYNC`] is ready to be resumed.
+    ///
+    /// This is an alternative to polling the connection's async file descriptors, for engines
+    /// and providers which support it. The callback may be invoked from a thread owned by the
+    /// engine while another thread is driving the connection, so it is not passed the connection
+    /// and should typically only wake the task driving it. Use [`SslRef::set_async_callback`] to
+    /// register a callback for a single connection.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[corresponds(SSL_CTX_set_async_callback)]
+    #[cfg(ossl300)]
+    pub fn set_async_callback<F>(&mut self, callback: F) -> Result<(), ErrorStack>
+    where
+        F: Fn() + 'static + Sync + Send,
+    {
+        unsafe {
+            let ptr = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
+            cvt(ffi::SSL_CTX_set_async_callback_arg(self.as_ptr(), ptr))?;
+            cvt(ffi::SSL_CTX_set_async_callback(
+                self.as_ptr(),
+                Some(callbacks::raw_async::<F>),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the number of TLS 1.3 session tickets a server issues after a full handshake.
+    ///
+    /// Defaults to 2. Setting it to 0 disables the tickets sent after the handshake without
+    /// disabling session resumption via the session cache.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set_num_tickets)]
+    #[cfg(ossl111)]
+    pub fn set_num_tickets(&mut self, num_tickets: usize) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_set_num_tickets(self.as_ptr(), num_tickets)).map(|_| ()) }
+    }
+
+    /// Sets a callback which will be invoked each time a server is about to issue a session
+    /// ticket.
+    ///
+    /// The callback can inspect the connection, or return an error to abort the handshake. It is
+    /// invoked once per ticket, so TLS 1.3 servers will see it called
+    /// [`num_tickets`](SslContextRef::num_tickets) times per full handshake.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set_session_ticket_cb)]
+    #[cfg(ossl111)]
+    pub fn set_session_ticket_generate_callback<F>(&mut self, callback: F) -> Result<(), ErrorStack>
+    where
+        F: Fn(&mut SslRef) -> Result<(), ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            let ptr = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
+            cvt(ffi::SSL_CTX_set_session_ticket_cb(
+                self.as_ptr(),
+                Some(callbacks::raw_generate_session_ticket::<F>),
+                None,
+                ptr,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets a callback which will be invoked when the server requests a client certificate.
+    ///
+    /// The callback is only invoked if no certificate has been configured on the context or
+    /// connection. It should return the certificate and private key to present, for example after
+    /// prompting the user or querying a smartcard, or `None` to continue the handshake without a
+    /// client certificate.
+    #[corresponds(SSL_CTX_set_client_cert_cb)]
+    pub fn set_client_cert_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef) -> Option<(X509, PKey<Private>)> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_client_cert_cb(self.as_ptr(), Some(raw_client_cert::<F>));
+        }
+    }
+
+    /// Sets a callback which will be invoked to select the certificate to present to the peer.
+    ///
+    /// On the server side, the callback is invoked after the client's hello message has been
+    /// processed, so the server name, the client's signature algorithms and the underlying
+    /// transport can be inspected to pick a certificate, which is then installed with
+    /// `SslRef::set_certificate`, `SslRef::set_private_key` and `SslRef::add_chain_cert`. On the
+    /// client side, it is invoked when the server requests a client certificate.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_CTX_set_cert_cb)]
+    #[cfg(ossl102)]
+    pub fn set_cert_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef) -> Result<CertCallbackResponse, ErrorStack> + 'static + Sync + Send,
+    {
+        unsafe {
+            let ptr = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_cert_cb(self.as_ptr(), Some(callbacks::raw_cert::<F>), ptr);
+        }
+    }
+
+    /// Sets the context's session cache size limit, returning the previous limit.
+    ///
+    /// A value of 0 means that the cache size is unbounded.
+    #[corresponds(SSL_CTX_sess_set_cache_size)]
+    #[allow(clippy::useless_conversion)]
+    pub fn set_session_cache_size(&mut self, size: i32) -> i64 {
+        unsafe {
+            ffi::SSL_CTX_sess_set_cache_size(self.as_ptr(), size as SslCacheSize) as SslCacheTy
+        }
+    }
+
+    /// Sets the maximum size of the certificate chain accepted from the peer, in bytes.
+    ///
+    /// A peer sending a larger `Certificate` message causes the handshake to fail, which bounds
+    /// the memory a hostile peer can make the connection buffer. In BoringSSL, the limit applies
+    /// to every handshake message. Defaults to 100 KiB.
+    #[corresponds(SSL_CTX_set_max_cert_list)]
+    pub fn set_max_cert_list(&mut self, size: usize) {
+        unsafe {
+            ffi::SSL_CTX_set_max_cert_list(self.as_ptr(), size as _);
+        }
+    }
+
+    /// Sets the maximum fragment length requested from the server with the RFC 6066
+    /// `max_fragment_length` extension.
+    ///
+    /// This allows constrained clients to use smaller receive buffers. Once the server accepts
+    /// the extension, both peers limit the records they send to the negotiated length. Servers
+    /// accept the extension automatically.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set_tlsext_max_fragment_length)]
+    #[cfg(ossl111)]
+    pub fn set_max_fragment_length(&mut self, len: MaxFragmentLength) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_set_tlsext_max_fragment_length(
+                self.as_ptr(),
+                len.0,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the maximum number of plaintext bytes placed in each record sent.
+    ///
+    /// Unlike [`set_max_fragment_length`], this is not negotiated with the peer and only limits
+    /// the local side's records, for example to fit the record size limit a peer is known to
+    /// have. The value must be between 512 and 16384.
+    ///
+    /// [`set_max_fragment_length`]: SslContextBuilder::set_max_fragment_length
+    #[corresponds(SSL_CTX_set_max_send_fragment)]
+    pub fn set_max_send_fragment(&mut self, size: usize) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_set_max_send_fragment(self.as_ptr(), size as _) as c_int).map(|_| ())
+        }
+    }
+
+    /// Sets the context's supported signature algorithms.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_CTX_set1_sigalgs_list)]
+    #[cfg(ossl102)]
+    pub fn set_sigalgs_list(&mut self, sigalgs: &str) -> Result<(), ErrorStack> {
+        let sigalgs = CString::new(sigalgs).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_set1_sigalgs_list(self.as_ptr(), sigalgs.as_ptr()) as c_int)
+                .map(|_| ())
+        }
+    }
+
+    /// Sets the signature algorithms accepted for client authentication.
+    ///
+    /// A server sends these in its certificate request, and they then apply to the client's
+    /// handshake signature and certificate chain, independently of the list configured with
+    /// [`set_sigalgs_list`]. A client uses them to verify its own certificate is acceptable.
+    ///
+    /// The format is the same as that of [`set_sigalgs_list`].
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// [`set_sigalgs_list`]: SslContextBuilder::set_sigalgs_list
+    #[corresponds(SSL_CTX_set1_client_sigalgs_list)]
+    #[cfg(ossl102)]
+    pub fn set_client_sigalgs_list(&mut self, sigalgs: &str) -> Result<(), ErrorStack> {
+        let sigalgs = CString::new(sigalgs).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_set1_client_sigalgs_list(self.as_ptr(), sigalgs.as_ptr()) as c_int)
+                .map(|_| ())
+        }
+    }
+
+    /// Sets the types of certificate the client may authenticate with, in order of preference.
+    ///
+    /// A client offers these types, and a server accepts the first of the client's types that
+    /// is in its own list. Raw public keys must be authenticated by other means, for example
+    /// with [`SslRef::add_expected_rpk`].
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_CTX_set1_client_cert_type)]
+    #[cfg(ossl320)]
+    pub fn set_client_cert_types(&mut self, types: &[CertificateType]) -> Result<(), ErrorStack> {
+        let types = types.iter().map(|t| t.0).collect::<Vec<_>>();
+        unsafe {
+            cvt(ffi::SSL_CTX_set1_client_cert_type(
+                self.as_ptr(),
+                types.as_ptr(),
+                types.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the types of certificate the server may authenticate with, in order of preference.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_CTX_set1_server_cert_type)]
+    #[cfg(ossl320)]
+    pub fn set_server_cert_types(&mut self, types: &[CertificateType]) -> Result<(), ErrorStack> {
+        let types = types.iter().map(|t| t.0).collect::<Vec<_>>();
+        unsafe {
+            cvt(ffi::SSL_CTX_set1_server_cert_type(
+                self.as_ptr(),
+                types.as_ptr(),
+                types.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the context's supported elliptic curve groups.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set1_groups_list)]
+    #[cfg(ossl111)]
+    pub fn set_groups_list(&mut self, groups: &str) -> Result<(), ErrorStack> {
+        let groups = CString::new(groups).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_set1_groups_list(self.as_ptr(), groups.as_ptr()) as c_int).map(|_| ())
+        }
+    }
+
+    /// Sets the context's supported groups from a list of NIDs, in order of preference.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set1_groups)]
+    #[cfg(ossl111)]
+    pub fn set_groups(&mut self, groups: &[Nid]) -> Result<(), ErrorStack> {
+        let groups = groups.iter().map(|n| n.as_raw()).collect::<Vec<_>>();
+        unsafe {
+            cvt(
+                ffi::SSL_CTX_set1_groups(self.as_ptr(), groups.as_ptr(), groups.len() as c_long)
+                    as c_int,
+            )
+            .map(|_| ())
+        }
+    }
+
+    /// Consumes the builder, returning a new `SslContext`.
+    pub fn build(self) -> SslContext {
+        self.0
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::SSL_CTX;
+    fn drop = ffi::SSL_CTX_free;
+
+    /// A context object for TLS streams.
+    ///
+    /// Applications commonly configure a single `SslContext` that is shared by all of its
+    /// `SslStreams`.
+    pub struct SslContext;
+
+    /// Reference to [`SslContext`]
+    ///
+    /// [`SslContext`]: struct.SslContext.html
+    pub struct SslContextRef;
+}
+
+impl Clone for SslContext {
+    fn clone(&self) -> Self {
+        (**self).to_owned()
+    }
+}
+
+impl ToOwned for SslContextRef {
+    type Owned = SslContext;
+
+    fn to_owned(&self) -> Self::Owned {
+        unsafe {
+            SSL_CTX_up_ref(self.as_ptr());
+            SslContext::from_ptr(self.as_ptr())
+        }
+    }
+}
+
+// TODO: add useful info here
+impl fmt::Debug for SslContext {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "SslContext")
+    }
+}
+
+impl SslContext {
+    /// Creates a new builder object for an `SslContext`.
+    pub fn builder(method: SslMethod) -> Result<SslContextBuilder, ErrorStack> {
+        SslContextBuilder::new(method)
+    }
+
+    /// Returns a new extra data index.
+    ///
+    /// Each invocation of this function is guaranteed to return a distinct index. These can be used
+    /// to store data in the context that can be retrieved later by callbacks, for example.
+    #[corresponds(SSL_CTX_get_ex_new_index)]
+    pub fn new_ex_index<T>() -> Result<Index<SslContext, T>, ErrorStack>
+    where
+        T: 'static + Sync + Send,
+    {
+        unsafe {
+            ffi::init();
+            #[cfg(boringssl)]
+            let idx = cvt_n(get_new_idx(Some(free_data_box::<T>)))?;
+            #[cfg(not(boringssl))]
+            let idx = cvt_n(get_new_idx(free_data_box::<T>))?;
+            Ok(Index::from_raw(idx))
+        }
+    }
+
+    // FIXME should return a result?
+    fn cached_ex_index<T>() -> Index<SslContext, T>
+    where
+        T: 'static + Sync + Send,
+    {
+        unsafe {
+            let idx = *INDEXES
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(TypeId::of::<T>())
+                .or_insert_with(|| SslContext::new_ex_index::<T>().unwrap().as_raw());
+            Index::from_raw(idx)
+        }
+    }
+}
+
+impl SslContextRef {
+    /// Returns the certificate associated with this `SslContext`, if present.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_CTX_get0_certificate)]
+    #[cfg(any(ossl102, ossl110))]
+    pub fn certificate(&self) -> Option<&X509Ref> {
+        unsafe {
+            let ptr = ffi::SSL_CTX_get0_certificate(self.as_ptr());
+            X509Ref::from_const_ptr_opt(ptr)
+        }
+    }
+
+    /// Returns the private key associated with this `SslContext`, if present.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_CTX_get0_privatekey)]
+    #[cfg(any(ossl102, ossl110))]
+    pub fn private_key(&self) -> Option<&PKeyRef<Private>> {
+        unsafe {
+            let ptr = ffi::SSL_CTX_get0_privatekey(self.as_ptr());
+            PKeyRef::from_const_ptr_opt(ptr)
+        }
+    }
+
+    /// Returns a shared reference to the certificate store used for verification.
+    #[corresponds(SSL_CTX_get_cert_store)]
+    pub fn cert_store(&self) -> &X509StoreRef {
+        unsafe { X509StoreRef::from_ptr(ffi::SSL_CTX_get_cert_store(self.as_ptr())) }
+    }
+
+    /// Returns a shared reference to the stack of certificates making up the chain from the leaf.
+    #[corresponds(SSL_CTX_get_extra_chain_certs)]
+    pub fn extra_chain_certs(&self) -> &StackRef<X509> {
+        unsafe {
+            let mut chain = ptr::null_mut();
+            ffi::SSL_CTX_get_extra_chain_certs(self.as_ptr(), &mut chain);
+            StackRef::from_const_ptr_opt(chain).expect("extra chain certs must not be null")
+        }
+    }
+
+    /// Returns the certificate chain of the current leaf certificate, if one has been configured.
+    ///
+    /// This is the chain loaded by [`SslContextBuilder::set_certificate_chain_file`] or added with
+    /// [`SslContextBuilder::add_chain_cert`]. Certificates added with
+    /// [`SslContextBuilder::add_extra_chain_cert`] are returned by [`extra_chain_certs`] instead,
+    /// although OpenSSL falls back to this chain there when no extra certificates are present.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// [`extra_chain_certs`]: SslContextRef::extra_chain_certs
+    #[corresponds(SSL_CTX_get0_chain_certs)]
+    #[cfg(ossl102)]
+    pub fn chain_certs(&self) -> Option<&StackRef<X509>> {
+        unsafe {
+            let mut chain = ptr::null_mut();
+            ffi::SSL_CTX_get0_chain_certs(self.as_ptr(), &mut chain);
+            StackRef::from_const_ptr_opt(chain)
+        }
+    }
+
+    /// Checks for consistency between the private key and certificate.
+    ///
+    /// Returns an error if either is missing or if they do not match.
+    #[corresponds(SSL_CTX_check_private_key)]
+    pub fn check_private_key(&self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_check_private_key(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Returns a reference to the extra data at the specified index.
+    #[corresponds(SSL_CTX_get_ex_data)]
+    pub fn ex_data<T>(&self, index: Index<SslContext, T>) -> Option<&T> {
+        unsafe {
+            let data = ffi::SSL_CTX_get_ex_data(self.as_ptr(), index.as_raw());
+            if data.is_null() {
+                None
+            } else {
+                Some(&*(data as *const T))
+            }
+        }
+    }
+
+    /// Gets the maximum amount of early data that will be accepted on incoming connections.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_get_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn max_early_data(&self) -> u32 {
+        unsafe { ffi::SSL_CTX_get_max_early_data(self.as_ptr()) }
+    }
+
+    /// Gets the maximum amount of early data that will be read on incoming connections.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_get_recv_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn recv_max_early_data(&self) -> u32 {
+        unsafe { ffi::SSL_CTX_get_recv_max_early_data(self.as_ptr()) }
+    }
+
+    /// Returns the certificate chain compressed with `algorithm` by
+    /// [`SslContextBuilder::compress_certificates`], along with its uncompressed length.
+    ///
+    /// Returns `None` if the chain has not been compressed with `algorithm`.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    ///
+    /// [`SslContextBuilder::compress_certificates`]: struct.SslContextBuilder.html#method.compress_certificates
+    #[corresponds(SSL_CTX_get1_compressed_cert)]
+    #[cfg(ossl320)]
+    pub fn compressed_certificate(
+        &self,
+        algorithm: CertificateCompressionAlgorithm,
+    ) -> Option<(Vec<u8>, usize)> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let mut orig_len = 0;
+            let len = ffi::SSL_CTX_get1_compressed_cert(
+                self.as_ptr(),
+                c_int::from(algorithm.as_raw()),
+                &mut ptr,
+                &mut orig_len,
+            );
+            if len == 0 {
+                return None;
+            }
+
+            let data = slice::from_raw_parts(ptr, len).to_vec();
+            ffi::OPENSSL_free(ptr as *mut c_void);
+            Some((data, orig_len))
+        }
+    }
+
+    /// Adds a session to the context's cache.
+    ///
+    /// Returns `true` if the session was successfully added to the cache, and `false` if it was already present.
+    ///
+    /// # Safety
+    ///
+    /// The caller of this method is responsible for ensuring that the session has never been used with another
+    /// `SslContext` than this one.
+    #[corresponds(SSL_CTX_add_session)]
+    pub unsafe fn add_session(&self, session: &SslSessionRef) -> bool {
+        ffi::SSL_CTX_add_session(self.as_ptr(), session.as_ptr()) != 0
+    }
+
+    /// Removes a session from the context's cache and marks it as non-resumable.
+    ///
+    /// Returns `true` if the session was successfully found and removed, and `false` otherwise.
+    ///
+    /// # Safety
+    ///
+    /// The caller of this method is responsible for ensuring that the session has never been used with another
+    /// `SslContext` than this one.
+    #[corresponds(SSL_CTX_remove_session)]
+    pub unsafe fn remove_session(&self, session: &SslSessionRef) -> bool {
+        ffi::SSL_CTX_remove_session(self.as_ptr(), session.as_ptr()) != 0
+    }
+
+    /// Returns the context's session cache size limit.
+    ///
+    /// A value of 0 means that the cache size is unbounded.
+    #[corresponds(SSL_CTX_sess_get_cache_size)]
+    #[allow(clippy::useless_conversion)]
+    pub fn session_cache_size(&self) -> i64 {
+        unsafe { ffi::SSL_CTX_sess_get_cache_size(self.as_ptr()) as c_long }
+    }
+
+    /// Returns statistics about the context's session cache and the handshakes performed with it.
+    ///
+    /// The statistics are cumulative over the lifetime of the context.
+    #[corresponds(SSL_CTX_sess_hits)]
+    pub fn session_cache_stats(&self) -> SessionCacheStats {
+        unsafe {
+            let ctx = self.as_ptr();
+            SessionCacheStats {
+                size: ffi::SSL_CTX_sess_number(ctx) as u64,
+                connects: ffi::SSL_CTX_sess_connect(ctx) as u64,
+                connects_good: ffi::SSL_CTX_sess_connect_good(ctx) as u64,
+                connects_renegotiate: ffi::SSL_CTX_sess_connect_renegotiate(ctx) as u64,
+                accepts: ffi::SSL_CTX_sess_accept(ctx) as u64,
+                accepts_good: ffi::SSL_CTX_sess_accept_good(ctx) as u64,
+                accepts_renegotiate: ffi::SSL_CTX_sess_accept_renegotiate(ctx) as u64,
+                hits: ffi::SSL_CTX_sess_hits(ctx) as u64,
+                callback_hits: ffi::SSL_CTX_sess_cb_hits(ctx) as u64,
+                misses: ffi::SSL_CTX_sess_misses(ctx) as u64,
+                timeouts: ffi::SSL_CTX_sess_timeouts(ctx) as u64,
+                cache_full: ffi::SSL_CTX_sess_cache_full(ctx) as u64,
+            }
+        }
+    }
+
+    /// Returns the maximum size of the certificate chain accepted from the peer, in bytes.
+    #[corresponds(SSL_CTX_get_max_cert_list)]
+    pub fn max_cert_list(&self) -> usize {
+        unsafe { ffi::SSL_CTX_get_max_cert_list(self.as_ptr()) as usize }
+    }
+
+    /// Returns the ciphers enabled by the context's cipher list and TLS 1.3 ciphersuites, in
+    /// order of preference.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CTX_get_ciphers)]
+    #[cfg(ossl110)]
+    pub fn ciphers(&self) -> Option<&StackRef<SslCipher>> {
+        unsafe { StackRef::from_const_ptr_opt(ffi::SSL_CTX_get_ciphers(self.as_ptr())) }
+    }
+
+    /// Returns the number of TLS 1.3 session tickets a server issues after a full handshake.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_get_num_tickets)]
+    #[cfg(ossl111)]
+    pub fn num_tickets(&self) -> usize {
+        unsafe { ffi::SSL_CTX_get_num_tickets(self.as_ptr()) }
+    }
+
+    /// Returns the security level of the context.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CTX_get_security_level)]
+    #[cfg(ossl110)]
+    pub fn security_level(&self) -> u32 {
+        unsafe { ffi::SSL_CTX_get_security_level(self.as_ptr()) as u32 }
+    }
+
+    /// Returns the verify mode that was set on this context from [`SslContextBuilder::set_verify`].
+    ///
+    /// [`SslContextBuilder::set_verify`]: struct.SslContextBuilder.html#method.set_verify
+    #[corresponds(SSL_CTX_get_verify_mode)]
+    pub fn verify_mode(&self) -> SslVerifyMode {
+        let mode = unsafe { ffi::SSL_CTX_get_verify_mode(self.as_ptr()) };
+        SslVerifyMode::from_bits(mode).expect("SSL_CTX_get_verify_mode returned invalid mode")
+    }
+
+    /// Returns `true` if quiet shutdown is enabled.
+    #[corresponds(SSL_CTX_get_quiet_shutdown)]
+    pub fn quiet_shutdown(&self) -> bool {
+        unsafe { ffi::SSL_CTX_get_quiet_shutdown(self.as_ptr()) != 0 }
+    }
+}
+
+/// Statistics about an `SslContext`'s session cache, returned by
+/// [`SslContextRef::session_cache_stats`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SessionCacheStats {
+    size: u64,
+    connects: u64,
+    connects_good: u64,
+    connects_renegotiate: u64,
+    accepts: u64,
+    accepts_good: u64,
+    accepts_renegotiate: u64,
+    hits: u64,
+    callback_hits: u64,
+    misses: u64,
+    timeouts: u64,
+    cache_full: u64,
+}
+
+impl SessionCacheStats {
+    /// Returns the number of sessions currently in the cache.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the number of client handshakes started.
+    pub fn connects(&self) -> u64 {
+        self.connects
+    }
+
+    /// Returns the number of client handshakes completed successfully.
+    pub fn connects_good(&self) -> u64 {
+        self.connects_good
+    }
+
+    /// Returns the number of client renegotiations started.
+    pub fn connects_renegotiate(&self) -> u64 {
+        self.connects_renegotiate
+    }
+
+    /// Returns the number of server handshakes started.
+    pub fn accepts(&self) -> u64 {
+        self.accepts
+    }
+
+    /// Returns the number of server handshakes completed successfully.
+    pub fn accepts_good(&self) -> u64 {
+        self.accepts_good
+    }
+
+    /// Returns the number of server renegotiations started.
+    pub fn accepts_renegotiate(&self) -> u64 {
+        self.accepts_renegotiate
+    }
+
+    /// Returns the number of sessions resumed from the internal cache.
+    ///
+    /// Sessions resumed from tickets are not counted.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns the number of sessions resumed from an external cache through the callback set
+    /// with [`SslContextBuilder::set_get_session_callback`].
+    pub fn callback_hits(&self) -> u64 {
+        self.callback_hits
+    }
+
+    /// Returns the number of session IDs sent by clients which could not be found in the cache.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Returns the number of sessions sent by clients which were found but had expired.
+    pub fn timeouts(&self) -> u64 {
+        self.timeouts
+    }
+
+    /// Returns the number of sessions removed from the cache because it was full.
+    pub fn cache_full(&self) -> u64 {
+        self.cache_full
+    }
+}
+
+/// Information about the state of a cipher.
+pub struct CipherBits {
+    /// The number of secret bits used for the cipher.
+    pub secret: i32,
+
+    /// The number of bits processed by the chosen algorithm.
+    pub algorithm: i32,
+}
+
+/// Information about a cipher.
+pub struct SslCipher(*mut ffi::SSL_CIPHER);
+
+impl ForeignType for SslCipher {
+    type CType = ffi::SSL_CIPHER;
+    type Ref = SslCipherRef;
+
+    #[inline]
+    unsafe fn from_ptr(ptr: *mut ffi::SSL_CIPHER) -> SslCipher {
+        SslCipher(ptr)
+    }
+
+    #[inline]
+    fn as_ptr(&self) -> *mut ffi::SSL_CIPHER {
+        self.0
+    }
+}
+
+impl Deref for SslCipher {
+    type Target = SslCipherRef;
+
+    fn deref(&self) -> &SslCipherRef {
+        unsafe { SslCipherRef::from_ptr(self.0) }
+    }
+}
+
+impl DerefMut for SslCipher {
+    fn deref_mut(&mut self) -> &mut SslCipherRef {
+        unsafe { SslCipherRef::from_ptr_mut(self.0) }
+    }
+}
+
+impl Stackable for SslCipher {
+    type StackType = ffi::stack_st_SSL_CIPHER;
+}
+
+/// Reference to an [`SslCipher`].
+///
+/// [`SslCipher`]: struct.SslCipher.html
+pub struct SslCipherRef(Opaque);
+
+impl ForeignTypeRef for SslCipherRef {
+    type CType = ffi::SSL_CIPHER;
+}
+
+impl SslCipherRef {
+    /// Returns the name of the cipher.
+    #[corresponds(SSL_CIPHER_get_name)]
+    pub fn name(&self) -> &'static str {
         unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), verify);
-            ffi::SSL_CTX_set_verify(self.as_ptr(), mode.bits as c_int, Some(raw_verify::<F>));
+            let ptr = ffi::SSL_CIPHER_get_name(self.as_ptr());
+            CStr::from_ptr(ptr).to_str().unwrap()
         }
     }
 
-    /// Configures the server name indication (SNI) callback for new connections.
+    /// Returns the RFC-standard name of the cipher, if one exists.
     ///
-    /// SNI is used to allow a single server to handle requests for multiple domains, each of which
-    /// has its own certificate chain and configuration.
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CIPHER_standard_name)]
+    #[cfg(ossl111)]
+    pub fn standard_name(&self) -> Option<&'static str> {
+        unsafe {
+            let ptr = ffi::SSL_CIPHER_standard_name(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ptr).to_str().unwrap())
+            }
+        }
+    }
+
+    /// Returns the two-byte identifier of the cipher as sent on the wire.
     ///
-    /// Obtain the server name with the `servername` method and then set the corresponding context
-    /// with `set_ssl_context`
-    #[corresponds(SSL_CTX_set_tlsext_servername_callback)]
-    // FIXME tlsext prefix?
-    pub fn set_servername_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, &mut SslAlert) -> Result<(), SniError> + 'static + Sync + Send,
-    {
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CIPHER_get_protocol_id)]
+    #[cfg(ossl111)]
+    pub fn protocol_id(&self) -> u16 {
+        unsafe { ffi::SSL_CIPHER_get_protocol_id(self.as_ptr()) }
+    }
+
+    /// Returns the SSL/TLS protocol version that first defined the cipher.
+    #[corresponds(SSL_CIPHER_get_version)]
+    pub fn version(&self) -> &'static str {
+        let version = unsafe {
+            let ptr = ffi::SSL_CIPHER_get_version(self.as_ptr());
+            CStr::from_ptr(ptr as *const _)
+        };
+
+        str::from_utf8(version.to_bytes()).unwrap()
+    }
+
+    /// Returns the number of bits used for the cipher.
+    #[corresponds(SSL_CIPHER_get_bits)]
+    #[allow(clippy::useless_conversion)]
+    pub fn bits(&self) -> CipherBits {
         unsafe {
-            // The SNI callback is somewhat unique in that the callback associated with the original
-            // context associated with an SSL can be used even if the SSL's context has been swapped
-            // out. When that happens, we wouldn't be able to look up the callback's state in the
-            // context's ex data. Instead, pass the pointer directly as the servername arg. It's
-            // still stored in ex data to manage the lifetime.
-            let arg = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_set_tlsext_servername_arg(self.as_ptr(), arg);
+            let mut algo_bits = 0;
+            let secret_bits = ffi::SSL_CIPHER_get_bits(self.as_ptr(), &mut algo_bits);
+            CipherBits {
+                secret: secret_bits.into(),
+                algorithm: algo_bits.into(),
+            }
+        }
+    }
 
-            let f: extern "C" fn(_, _, _) -> _ = raw_sni::<F>;
-            #[cfg(not(boringssl))]
-            let f: extern "C" fn() = mem::transmute(f);
-            ffi::SSL_CTX_set_tlsext_servername_callback(
-                self.as_ptr(),
-                Some(f),
-            );
+    /// Returns a textual description of the cipher.
+    #[corresponds(SSL_CIPHER_description)]
+    pub fn description(&self) -> String {
+        unsafe {
+            // SSL_CIPHER_description requires a buffer of at least 128 bytes.
+            let mut buf = [0; 128];
+            let ptr = ffi::SSL_CIPHER_description(self.as_ptr(), buf.as_mut_ptr(), 128);
+            String::from_utf8(CStr::from_ptr(ptr as *const _).to_bytes().to_vec()).unwrap()
+        }
+    }
+
+    /// Returns the handshake digest of the cipher.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CIPHER_get_handshake_digest)]
+    #[cfg(ossl111)]
+    pub fn handshake_digest(&self) -> Option<MessageDigest> {
+        unsafe {
+            let ptr = ffi::SSL_CIPHER_get_handshake_digest(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(MessageDigest::from_ptr(ptr))
+            }
+        }
+    }
+
+    /// Returns the NID corresponding to the cipher.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CIPHER_get_cipher_nid)]
+    #[cfg(any(ossl110))]
+    pub fn cipher_nid(&self) -> Option<Nid> {
+        let n = unsafe { ffi::SSL_CIPHER_get_cipher_nid(self.as_ptr()) };
+        if n == 0 {
+            None
+        } else {
+            Some(Nid::from_raw(n))
+        }
+    }
+
+    /// Returns the NID of the MAC digest used by the cipher.
+    ///
+    /// Returns `None` for AEAD ciphers, which have no separate MAC.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CIPHER_get_digest_nid)]
+    #[cfg(ossl110)]
+    pub fn digest_nid(&self) -> Option<Nid> {
+        let n = unsafe { ffi::SSL_CIPHER_get_digest_nid(self.as_ptr()) };
+        if n == 0 {
+            None
+        } else {
+            Some(Nid::from_raw(n))
+        }
+    }
+
+    /// Returns the NID of the cipher's key exchange method, such as [`Nid::KX_ECDHE`].
+    ///
+    /// TLS 1.3 ciphers, which do not determine the key exchange, return `Nid::KX_ANY`.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CIPHER_get_kx_nid)]
+    #[cfg(ossl110)]
+    pub fn kx_nid(&self) -> Option<Nid> {
+        let n = unsafe { ffi::SSL_CIPHER_get_kx_nid(self.as_ptr()) };
+        if n == 0 {
+            None
+        } else {
+            Some(Nid::from_raw(n))
+        }
+    }
+
+    /// Returns the NID of the cipher's authentication method, such as [`Nid::AUTH_RSA`].
+    ///
+    /// TLS 1.3 ciphers, which do not determine the authentication method, return
+    /// `Nid::AUTH_ANY`.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CIPHER_get_auth_nid)]
+    #[cfg(ossl110)]
+    pub fn auth_nid(&self) -> Option<Nid> {
+        let n = unsafe { ffi::SSL_CIPHER_get_auth_nid(self.as_ptr()) };
+        if n == 0 {
+            None
+        } else {
+            Some(Nid::from_raw(n))
         }
     }
 
-    /// Sets the certificate verification depth.
+    /// Determines if the cipher is an AEAD cipher, such as AES-GCM or ChaCha20-Poly1305.
     ///
-    /// If the peer's certificate chain is longer than this value, verification will fail.
-    #[corresponds(SSL_CTX_set_verify_depth)]
-    pub fn set_verify_depth(&mut self, depth: u32) {
-        unsafe {
-            ffi::SSL_CTX_set_verify_depth(self.as_ptr(), depth as c_int);
-        }
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CIPHER_is_aead)]
+    #[cfg(ossl110)]
+    pub fn is_aead(&self) -> bool {
+        unsafe { ffi::SSL_CIPHER_is_aead(self.as_ptr()) != 0 }
     }
 
-    /// Sets a custom certificate store for verifying peer certificates.
+    /// Returns OpenSSL's internal identifier for the cipher.
     ///
-    /// Requires OpenSSL 1.0.2 or newer.
-    #[corresponds(SSL_CTX_set0_verify_cert_store)]
-    #[cfg(ossl102)]
-    pub fn set_verify_cert_store(&mut self, cert_store: X509Store) -> Result<(), ErrorStack> {
-        unsafe {
-            let ptr = cert_store.as_ptr();
-            cvt(ffi::SSL_CTX_set0_verify_cert_store(self.as_ptr(), ptr) as c_int)?;
-            mem::forget(cert_store);
+    /// The low two bytes are the cipher's TLS code point.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CIPHER_get_id)]
+    #[cfg(ossl110)]
+    pub fn id(&self) -> u32 {
+        unsafe { ffi::SSL_CIPHER_get_id(self.as_ptr()) }
+    }
+}
 
-            Ok(())
-        }
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::SSL_SESSION;
+    fn drop = ffi::SSL_SESSION_free;
+
+    /// An encoded SSL session.
+    ///
+    /// These can be cached to share sessions across connections.
+    pub struct SslSession;
+
+    /// Reference to [`SslSession`].
+    ///
+    /// [`SslSession`]: struct.SslSession.html
+    pub struct SslSessionRef;
+}
+
+impl Clone for SslSession {
+    fn clone(&self) -> SslSession {
+        SslSessionRef::to_owned(self)
     }
+}
 
-    /// Replaces the context's certificate store.
-    #[corresponds(SSL_CTX_set_cert_store)]
-    pub fn set_cert_store(&mut self, cert_store: X509Store) {
+impl SslSession {
+    /// Creates a new, empty session.
+    #[corresponds(SSL_SESSION_new)]
+    pub fn new() -> Result<SslSession, ErrorStack> {
         unsafe {
-            ffi::SSL_CTX_set_cert_store(self.as_ptr(), cert_store.as_ptr());
-            mem::forget(cert_store);
+            ffi::init();
+            cvt_p(ffi::SSL_SESSION_new()).map(SslSession)
         }
     }
 
-    /// Controls read ahead behavior.
+    /// Creates a TLS 1.3 session holding an external pre-shared key.
     ///
-    /// If enabled, OpenSSL will read as much data as is available from the underlying stream,
-    /// instead of a single record at a time.
+    /// `cipher` must be a TLS 1.3 cipher suite, and is used with the key to establish the
+    /// connection. Ciphers can be looked up with [`SslRef::find_cipher`].
     ///
-    /// It has no effect when used with DTLS.
-    #[corresponds(SSL_CTX_set_read_ahead)]
-    pub fn set_read_ahead(&mut self, read_ahead: bool) {
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// [`SslRef::find_cipher`]: struct.SslRef.html#method.find_cipher
+    #[cfg(ossl111)]
+    pub fn from_psk(key: &[u8], cipher: &SslCipherRef) -> Result<SslSession, ErrorStack> {
+        let mut session = SslSession::new()?;
+        session.set_master_key(key)?;
+        session.set_cipher(cipher)?;
+        session.set_protocol_version(SslVersion::TLS1_3)?;
+        Ok(session)
+    }
+
+    from_der! {
+        /// Deserializes a DER-encoded session structure.
+        #[corresponds(d2i_SSL_SESSION)]
+        from_der,
+        SslSession,
+        ffi::d2i_SSL_SESSION
+    }
+}
+
+impl ToOwned for SslSessionRef {
+    type Owned = SslSession;
+
+    fn to_owned(&self) -> SslSession {
         unsafe {
-            ffi::SSL_CTX_set_read_ahead(self.as_ptr(), read_ahead as SslBitType);
+            SSL_SESSION_up_ref(self.as_ptr());
+            SslSession(self.as_ptr())
         }
     }
+}
 
-    /// Sets the mode used by the context, returning the previous mode.
-    #[corresponds(SSL_CTX_set_mode)]
-    pub fn set_mode(&mut self, mode: SslMode) -> SslMode {
+impl SslSessionRef {
+    /// Returns the SSL session ID.
+    #[corresponds(SSL_SESSION_get_id)]
+    pub fn id(&self) -> &[u8] {
         unsafe {
-            let bits = ffi::SSL_CTX_set_mode(self.as_ptr(), mode.bits() as MtuTy) as SslModeTy;
-            SslMode { bits }
+            let mut len = 0;
+            let p = ffi::SSL_SESSION_get_id(self.as_ptr(), &mut len);
+            slice::from_raw_parts(p as *const u8, len as usize)
         }
     }
 
-    /// Sets the parameters to be used during ephemeral Diffie-Hellman key exchange.
-    #[corresponds(SSL_CTX_set_tmp_dh)]
-    pub fn set_tmp_dh(&mut self, dh: &DhRef<Params>) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_CTX_set_tmp_dh(self.as_ptr(), dh.as_ptr()) as c_int).map(|_| ()) }
+    /// Returns the length of the master key.
+    #[corresponds(SSL_SESSION_get_master_key)]
+    pub fn master_key_len(&self) -> usize {
+        unsafe { SSL_SESSION_get_master_key(self.as_ptr(), ptr::null_mut(), 0) }
     }
 
-    /// Sets the callback which will generate parameters to be used during ephemeral Diffie-Hellman
-    /// key exchange.
+    /// Copies the master key into the provided buffer.
     ///
-    /// The callback is provided with a reference to the `Ssl` for the session, as well as a boolean
-    /// indicating if the selected cipher is export-grade, and the key length. The export and key
-    /// length options are archaic and should be ignored in almost all cases.
-    #[corresponds(SSL_CTX_set_tmp_dh_callback)]
-    pub fn set_tmp_dh_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, bool, u32) -> Result<Dh<Params>, ErrorStack> + 'static + Sync + Send,
-    {
-        unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-
-            #[cfg(not(boringssl))]
-            ffi::SSL_CTX_set_tmp_dh_callback__fixed_rust(self.as_ptr(), Some(raw_tmp_dh::<F>));
-            #[cfg(boringssl)]
-            ffi::SSL_CTX_set_tmp_dh_callback(self.as_ptr(), Some(raw_tmp_dh::<F>));
-        }
-    }
-
-    /// Sets the parameters to be used during ephemeral elliptic curve Diffie-Hellman key exchange.
-    #[corresponds(SSL_CTX_set_tmp_ecdh)]
-    pub fn set_tmp_ecdh(&mut self, key: &EcKeyRef<Params>) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_CTX_set_tmp_ecdh(self.as_ptr(), key.as_ptr()) as c_int).map(|_| ()) }
+    /// Returns the number of bytes written, or the size of the master key if the buffer is empty.
+    #[corresponds(SSL_SESSION_get_master_key)]
+    pub fn master_key(&self, buf: &mut [u8]) -> usize {
+        unsafe { SSL_SESSION_get_master_key(self.as_ptr(), buf.as_mut_ptr(), buf.len()) }
     }
 
-    /// Sets the callback which will generate parameters to be used during ephemeral elliptic curve
-    /// Diffie-Hellman key exchange.
+    /// Sets the master key of the session.
     ///
-    /// The callback is provided with a reference to the `Ssl` for the session, as well as a boolean
-    /// indicating if the selected cipher is export-grade, and the key length. The export and key
-    /// length options are archaic and should be ignored in almost all cases.
+    /// For TLS 1.3 sessions this is the resumption or external pre-shared key.
     ///
-    /// Requires OpenSSL 1.0.1 or 1.0.2.
-    #[corresponds(SSL_CTX_set_tmp_ecdh_callback)]
-    #[cfg(all(ossl101, not(ossl110)))]
-    #[deprecated(note = "this function leaks memory and does not exist on newer OpenSSL versions")]
-    pub fn set_tmp_ecdh_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, bool, u32) -> Result<EcKey<Params>, ErrorStack> + 'static + Sync + Send,
-    {
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_set1_master_key)]
+    #[cfg(ossl111)]
+    pub fn set_master_key(&mut self, key: &[u8]) -> Result<(), ErrorStack> {
         unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_set_tmp_ecdh_callback__fixed_rust(self.as_ptr(), Some(raw_tmp_ecdh::<F>));
+            cvt(ffi::SSL_SESSION_set1_master_key(
+                self.as_ptr(),
+                key.as_ptr(),
+                key.len(),
+            ))
+            .map(|_| ())
         }
     }
 
-    /// Use the default locations of trusted certificates for verification.
+    /// Sets the cipher suite of the session.
     ///
-    /// These locations are read from the `SSL_CERT_FILE` and `SSL_CERT_DIR` environment variables
-    /// if present, or defaults specified at OpenSSL build time otherwise.
-    #[corresponds(SSL_CTX_set_default_verify_paths)]
-    pub fn set_default_verify_paths(&mut self) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_CTX_set_default_verify_paths(self.as_ptr())).map(|_| ()) }
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_set_cipher)]
+    #[cfg(ossl111)]
+    pub fn set_cipher(&mut self, cipher: &SslCipherRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_SESSION_set_cipher(self.as_ptr(), cipher.as_ptr())).map(|_| ()) }
     }
 
-    /// Loads trusted root certificates from a file.
+    /// Sets the TLS protocol version of the session.
     ///
-    /// The file should contain a sequence of PEM-formatted CA certificates.
-    #[corresponds(SSL_CTX_load_verify_locations)]
-    pub fn set_ca_file<P: AsRef<Path>>(&mut self, file: P) -> Result<(), ErrorStack> {
-        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_set_protocol_version)]
+    #[cfg(ossl111)]
+    pub fn set_protocol_version(&mut self, version: SslVersion) -> Result<(), ErrorStack> {
         unsafe {
-            cvt(ffi::SSL_CTX_load_verify_locations(
+            cvt(ffi::SSL_SESSION_set_protocol_version(
                 self.as_ptr(),
-                file.as_ptr() as *const _,
-                ptr::null(),
+                version.0,
             ))
             .map(|_| ())
         }
     }
 
-    /// Sets the list of CA names sent to the client.
+    /// Sets the server name the session was established with.
     ///
-    /// The CA certificates must still be added to the trust root - they are not automatically set
-    /// as trusted by this method.
-    #[corresponds(SSL_CTX_set_client_CA_list)]
-    pub fn set_client_ca_list(&mut self, list: Stack<X509Name>) {
+    /// OpenSSL only accepts early data with an external pre-shared key if this matches the name
+    /// the client requests.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_set1_hostname)]
+    #[cfg(ossl111)]
+    pub fn set_hostname(&mut self, hostname: &str) -> Result<(), ErrorStack> {
+        let hostname = CString::new(hostname).unwrap();
         unsafe {
-            ffi::SSL_CTX_set_client_CA_list(self.as_ptr(), list.as_ptr());
-            mem::forget(list);
+            cvt(ffi::SSL_SESSION_set1_hostname(
+                self.as_ptr(),
+                hostname.as_ptr(),
+            ))
+            .map(|_| ())
         }
     }
 
-    /// Add the provided CA certificate to the list sent by the server to the client when
-    /// requesting client-side TLS authentication.
-    #[corresponds(SSL_CTX_add_client_CA)]
-    #[cfg(not(libressl))]
-    pub fn add_client_ca(&mut self, cacert: &X509Ref) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_CTX_add_client_CA(self.as_ptr(), cacert.as_ptr())).map(|_| ()) }
-    }
-
-    /// Set the context identifier for sessions.
+    /// Sets the ALPN protocol the session was established with.
     ///
-    /// This value identifies the server's session cache to clients, telling them when they're
-    /// able to reuse sessions. It should be set to a unique value per server, unless multiple
-    /// servers share a session cache.
+    /// OpenSSL only accepts early data with an external pre-shared key if this matches the
+    /// protocol negotiated for the connection.
     ///
-    /// This value should be set when using client certificates, or each request will fail its
-    /// handshake and need to be restarted.
-    #[corresponds(SSL_CTX_set_session_id_context)]
-    pub fn set_session_id_context(&mut self, sid_ctx: &[u8]) -> Result<(), ErrorStack> {
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_set1_alpn_selected)]
+    #[cfg(ossl111)]
+    pub fn set_alpn_selected(&mut self, protocol: &[u8]) -> Result<(), ErrorStack> {
         unsafe {
-            assert!(sid_ctx.len() <= c_uint::max_value() as usize);
-            cvt(ffi::SSL_CTX_set_session_id_context(
+            cvt(ffi::SSL_SESSION_set1_alpn_selected(
                 self.as_ptr(),
-                sid_ctx.as_ptr(),
-                sid_ctx.len() as SizeTy,
+                protocol.as_ptr(),
+                protocol.len(),
             ))
             .map(|_| ())
         }
     }
 
-    /// Loads a leaf certificate from a file.
+    /// Sets the maximum amount of early data that can be sent on this session.
     ///
-    /// Only a single certificate will be loaded - use `add_extra_chain_cert` to add the remainder
-    /// of the certificate chain, or `set_certificate_chain_file` to load the entire chain from a
-    /// single file.
-    #[corresponds(SSL_CTX_use_certificate_file)]
-    pub fn set_certificate_file<P: AsRef<Path>>(
-        &mut self,
-        file: P,
-        file_type: SslFiletype,
-    ) -> Result<(), ErrorStack> {
-        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
-        unsafe {
-            cvt(ffi::SSL_CTX_use_certificate_file(
-                self.as_ptr(),
-                file.as_ptr() as *const _,
-                file_type.as_raw(),
-            ))
-            .map(|_| ())
+    /// Sessions holding external pre-shared keys must set this for the client to send early data.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_set_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn set_max_early_data(&mut self, bytes: u32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_SESSION_set_max_early_data(self.as_ptr(), bytes)).map(|_| ()) }
+    }
+
+    /// Gets the maximum amount of early data that can be sent on this session.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_get_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn max_early_data(&self) -> u32 {
+        unsafe { ffi::SSL_SESSION_get_max_early_data(self.as_ptr()) }
+    }
+
+    /// Returns the time at which the session was established, in seconds since the Unix epoch.
+    #[corresponds(SSL_SESSION_get_time)]
+    #[allow(clippy::useless_conversion)]
+    pub fn time(&self) -> SslTimeTy {
+        unsafe { ffi::SSL_SESSION_get_time(self.as_ptr()) }
+    }
+
+    /// Returns the sessions timeout, in seconds.
+    ///
+    /// A session older than this time should not be used for session resumption.
+    #[corresponds(SSL_SESSION_get_timeout)]
+    #[allow(clippy::useless_conversion)]
+    pub fn timeout(&self) -> i64 {
+        unsafe { ffi::SSL_SESSION_get_timeout(self.as_ptr()).into() }
+    }
+
+    /// Returns the session's TLS protocol version.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_SESSION_get_protocol_version)]
+    #[cfg(ossl110)]
+    pub fn protocol_version(&self) -> SslVersion {
+        unsafe {
+            let version = ffi::SSL_SESSION_get_protocol_version(self.as_ptr());
+            SslVersion(version)
         }
     }
 
-    /// Loads a certificate chain from a file.
+    /// Returns the cipher suite of the session.
     ///
-    /// The file should contain a sequence of PEM-formatted certificates, the first being the leaf
-    /// certificate, and the remainder forming the chain of certificates up to and including the
-    /// trusted root certificate.
-    #[corresponds(SSL_CTX_use_certificate_chain_file)]
-    pub fn set_certificate_chain_file<P: AsRef<Path>>(
-        &mut self,
-        file: P,
-    ) -> Result<(), ErrorStack> {
-        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
+    /// Requires OpenSSL 1.1.0 or LibreSSL 2.7.0 or newer.
+    #[corresponds(SSL_SESSION_get0_cipher)]
+    #[cfg(any(ossl110, libressl270))]
+    pub fn cipher(&self) -> Option<&SslCipherRef> {
         unsafe {
-            cvt(ffi::SSL_CTX_use_certificate_chain_file(
-                self.as_ptr(),
-                file.as_ptr() as *const _,
-            ))
-            .map(|_| ())
+            let ptr = ffi::SSL_SESSION_get0_cipher(self.as_ptr());
+            SslCipherRef::from_const_ptr_opt(ptr)
         }
     }
 
-    /// Sets the leaf certificate.
+    /// Returns the lifetime hint the server sent along with the session ticket, in seconds.
     ///
-    /// Use `add_extra_chain_cert` to add the remainder of the certificate chain.
-    #[corresponds(SSL_CTX_use_certificate)]
-    pub fn set_certificate(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_CTX_use_certificate(self.as_ptr(), cert.as_ptr())).map(|_| ()) }
+    /// This is 0 if the server did not specify a lifetime or if the session has no ticket.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_SESSION_get_ticket_lifetime_hint)]
+    #[cfg(ossl110)]
+    #[allow(clippy::useless_conversion)]
+    pub fn ticket_lifetime_hint(&self) -> u64 {
+        unsafe { ffi::SSL_SESSION_get_ticket_lifetime_hint(self.as_ptr()).into() }
     }
 
-    /// Appends a certificate to the certificate chain.
+    /// Returns the session ticket sent by the server, if there is one.
     ///
-    /// This chain should contain all certificates necessary to go from the certificate specified by
-    /// `set_certificate` to a trusted root.
-    #[corresponds(SSL_CTX_add_extra_chain_cert)]
-    pub fn add_extra_chain_cert(&mut self, cert: X509) -> Result<(), ErrorStack> {
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_SESSION_get0_ticket)]
+    #[cfg(ossl110)]
+    pub fn ticket(&self) -> Option<&[u8]> {
         unsafe {
-            cvt(ffi::SSL_CTX_add_extra_chain_cert(self.as_ptr(), cert.as_ptr()) as c_int)?;
-            mem::forget(cert);
-            Ok(())
+            let mut ptr = ptr::null();
+            let mut len = 0;
+            ffi::SSL_SESSION_get0_ticket(self.as_ptr(), &mut ptr, &mut len);
+            if ptr.is_null() || len == 0 {
+                None
+            } else {
+                Some(slice::from_raw_parts(ptr, len))
+            }
         }
     }
 
-    /// Loads the private key from a file.
-    #[corresponds(SSL_CTX_use_PrivateKey_file)]
-    pub fn set_private_key_file<P: AsRef<Path>>(
-        &mut self,
-        file: P,
-        file_type: SslFiletype,
-    ) -> Result<(), ErrorStack> {
-        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
+    /// Determines if the session can be used to resume a connection.
+    ///
+    /// A session can be resumed if it has a session ID which the server may have cached or a
+    /// session ticket. [`SslRef::session_reused`] reports whether resumption actually happened.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_is_resumable)]
+    #[cfg(ossl111)]
+    pub fn is_resumable(&self) -> bool {
+        unsafe { ffi::SSL_SESSION_is_resumable(self.as_ptr()) != 0 }
+    }
+
+    /// Determines if the session includes a session ticket.
+    ///
+    /// If a connection resumed a session with a ticket, [`SslRef::session_reused`] returns
+    /// `true` and the connection's session has a ticket; otherwise a resumed session was looked
+    /// up by its ID in the server's session cache.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_has_ticket)]
+    #[cfg(ossl111)]
+    pub fn has_ticket(&self) -> bool {
+        unsafe { ffi::SSL_SESSION_has_ticket(self.as_ptr()) != 0 }
+    }
+
+    /// Returns the maximum fragment length negotiated for the session.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_get_max_fragment_length)]
+    #[cfg(ossl111)]
+    pub fn max_fragment_length(&self) -> MaxFragmentLength {
+        unsafe { MaxFragmentLength(ffi::SSL_SESSION_get_max_fragment_length(self.as_ptr())) }
+    }
+
+    to_der! {
+        /// Serializes the session into a DER-encoded structure.
+        #[corresponds(i2d_SSL_SESSION)]
+        to_der,
+        ffi::i2d_SSL_SESSION
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::SSL;
+    fn drop = ffi::SSL_free;
+
+    /// The state of an SSL/TLS session.
+    ///
+    /// `Ssl` objects are created from an [`SslContext`], which provides configuration defaults.
+    /// These defaults can be overridden on a per-`Ssl` basis, however.
+    ///
+    /// [`SslContext`]: struct.SslContext.html
+    pub struct Ssl;
+
+    /// Reference to an [`Ssl`].
+    ///
+    /// [`Ssl`]: struct.Ssl.html
+    pub struct SslRef;
+}
+
+impl fmt::Debug for Ssl {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, fmt)
+    }
+}
+
+impl Ssl {
+    /// Returns a new extra data index.
+    ///
+    /// Each invocation of this function is guaranteed to return a distinct index. These can be used
+    /// to store data in the context that can be retrieved later by callbacks, for example.
+    #[corresponds(SSL_get_ex_new_index)]
+    pub fn new_ex_index<T>() -> Result<Index<Ssl, T>, ErrorStack>
+    where
+        T: 'static + Sync + Send,
+    {
         unsafe {
-            cvt(ffi::SSL_CTX_use_PrivateKey_file(
-                self.as_ptr(),
-                file.as_ptr() as *const _,
-                file_type.as_raw(),
-            ))
-            .map(|_| ())
+            ffi::init();
+            #[cfg(boringssl)]
+            let idx = cvt_n(get_new_ssl_idx(Some(free_data_box::<T>)))?;
+            #[cfg(not(boringssl))]
+            let idx = cvt_n(get_new_ssl_idx(free_data_box::<T>))?;
+            Ok(Index::from_raw(idx))
         }
     }
 
-    /// Sets the private key.
-    #[corresponds(SSL_CTX_use_PrivateKey)]
-    pub fn set_private_key<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    // FIXME should return a result?
+    fn cached_ex_index<T>() -> Index<Ssl, T>
     where
-        T: HasPrivate,
+        T: 'static + Sync + Send,
     {
-        unsafe { cvt(ffi::SSL_CTX_use_PrivateKey(self.as_ptr(), key.as_ptr())).map(|_| ()) }
+        unsafe {
+            let idx = *SSL_INDEXES
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(TypeId::of::<T>())
+                .or_insert_with(|| Ssl::new_ex_index::<T>().unwrap().as_raw());
+            Index::from_raw(idx)
+        }
     }
 
-    /// Sets the list of supported ciphers for protocols before TLSv1.3.
-    ///
-    /// The `set_ciphersuites` method controls the cipher suites for TLSv1.3.
+    /// Creates a new `Ssl`.
     ///
-    /// See [`ciphers`] for details on the format.
+    /// This corresponds to [`SSL_new`].
     ///
-    /// [`ciphers`]: https://www.openssl.org/docs/man1.1.0/apps/ciphers.html
-    #[corresponds(SSL_CTX_set_cipher_list)]
-    pub fn set_cipher_list(&mut self, cipher_list: &str) -> Result<(), ErrorStack> {
-        let cipher_list = CString::new(cipher_list).unwrap();
+    /// [`SSL_new`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_new.html
+    #[corresponds(SSL_new)]
+    pub fn new(ctx: &SslContextRef) -> Result<Ssl, ErrorStack> {
+        let session_ctx_index = try_get_session_ctx_index()?;
         unsafe {
-            cvt(ffi::SSL_CTX_set_cipher_list(
-                self.as_ptr(),
-                cipher_list.as_ptr() as *const _,
-            ))
-            .map(|_| ())
+            let ptr = cvt_p(ffi::SSL_new(ctx.as_ptr()))?;
+            let mut ssl = Ssl::from_ptr(ptr);
+            ssl.set_ex_data(*session_ctx_index, ctx.to_owned());
+
+            Ok(ssl)
         }
     }
 
-    /// Sets the list of supported ciphers for the TLSv1.3 protocol.
+    /// Initiates a client-side TLS handshake.
     ///
-    /// The `set_cipher_list` method controls the cipher suites for protocols before TLSv1.3.
+    /// This corresponds to [`SSL_connect`].
     ///
-    /// The format consists of TLSv1.3 cipher suite names separated by `:` characters in order of
-    /// preference.
+    /// # Warning
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_CTX_set_ciphersuites)]
-    #[cfg(ossl111)]
-    pub fn set_ciphersuites(&mut self, cipher_list: &str) -> Result<(), ErrorStack> {
-        let cipher_list = CString::new(cipher_list).unwrap();
+    /// OpenSSL's default configuration is insecure. It is highly recommended to use
+    /// `SslConnector` rather than `Ssl` directly, as it manages that configuration.
+    ///
+    /// [`SSL_connect`]: https://www.openssl.org/docs/manmaster/man3/SSL_connect.html
+    #[corresponds(SSL_connect)]
+    #[allow(deprecated)]
+    pub fn connect<S>(self, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
+    where
+        S: Read + Write,
+    {
+        SslStreamBuilder::new(self, stream).connect()
+    }
+
+    /// Initiates a server-side TLS handshake.
+    ///
+    /// This corresponds to [`SSL_accept`].
+    ///
+    /// # Warning
+    ///
+    /// OpenSSL's default configuration is insecure. It is highly recommended to use
+    /// `SslAcceptor` rather than `Ssl` directly, as it manages that configuration.
+    ///
+    /// [`SSL_accept`]: https://www.openssl.org/docs/manmaster/man3/SSL_accept.html
+    #[corresponds(SSL_accept)]
+    #[allow(deprecated)]
+    pub fn accept<S>(self, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
+    where
+        S: Read + Write,
+    {
+        SslStreamBuilder::new(self, stream).accept()
+    }
+}
+
+impl fmt::Debug for SslRef {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Ssl")
+            .field("state", &self.state_string_long())
+            .field("verify_result", &self.verify_result())
+            .finish()
+    }
+}
+
+impl SslRef {
+    fn get_raw_rbio(&self) -> *mut ffi::BIO {
+        unsafe { ffi::SSL_get_rbio(self.as_ptr()) }
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> c_int {
+        let len = cmp::min(c_int::max_value() as usize, buf.len()) as c_int;
+        unsafe { ffi::SSL_read(self.as_ptr(), buf.as_ptr() as *mut c_void, len) }
+    }
+
+    fn peek(&mut self, buf: &mut [u8]) -> c_int {
+        let len = cmp::min(c_int::max_value() as usize, buf.len()) as c_int;
+        unsafe { ffi::SSL_peek(self.as_ptr(), buf.as_ptr() as *mut c_void, len) }
+    }
+
+    fn write(&mut self, buf: &[u8]) -> c_int {
+        let len = cmp::min(c_int::max_value() as usize, buf.len()) as c_int;
+        unsafe { ffi::SSL_write(self.as_ptr(), buf.as_ptr() as *const c_void, len) }
+    }
+
+    #[cfg(ossl330)]
+    fn write_ex2(&mut self, buf: &[u8], flags: SslWriteFlags, written: &mut usize) -> c_int {
         unsafe {
-            cvt(ffi::SSL_CTX_set_ciphersuites(
+            ffi::SSL_write_ex2(
                 self.as_ptr(),
-                cipher_list.as_ptr() as *const _,
-            ))
-            .map(|_| ())
+                buf.as_ptr() as *const c_void,
+                buf.len(),
+                flags.bits(),
+                written,
+            )
         }
     }
 
-    /// Enables ECDHE key exchange with an automatically chosen curve list.
-    ///
-    /// Requires OpenSSL 1.0.2.
-    #[corresponds(SSL_CTX_set_ecdh_auto)]
-    #[cfg(any(libressl, all(ossl102, not(ossl110))))]
-    pub fn set_ecdh_auto(&mut self, onoff: bool) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_CTX_set_ecdh_auto(self.as_ptr(), onoff as c_int)).map(|_| ()) }
+    fn get_error(&self, ret: c_int) -> ErrorCode {
+        unsafe { ErrorCode::from_raw(ffi::SSL_get_error(self.as_ptr(), ret)) }
+    }
+
+    /// Configure as an outgoing stream from a client.
+    #[corresponds(SSL_set_connect_state)]
+    pub fn set_connect_state(&mut self) {
+        unsafe { ffi::SSL_set_connect_state(self.as_ptr()) }
+    }
+
+    /// Configure as an incoming stream to a server.
+    #[corresponds(SSL_set_accept_state)]
+    pub fn set_accept_state(&mut self) {
+        unsafe { ffi::SSL_set_accept_state(self.as_ptr()) }
     }
 
-    /// Sets the options used by the context, returning the old set.
-    ///
-    /// # Note
+    /// Like [`SslContextBuilder::set_verify`].
     ///
-    /// This *enables* the specified options, but does not disable unspecified options. Use
-    /// `clear_options` for that.
-    #[corresponds(SSL_CTX_set_options)]
-    pub fn set_options(&mut self, option: SslOptions) -> SslOptions {
-        let bits =
-            unsafe { ffi::SSL_CTX_set_options(self.as_ptr(), option.bits() as BitTy) } as SslTy;
-        SslOptions { bits }
+    /// [`SslContextBuilder::set_verify`]: struct.SslContextBuilder.html#method.set_verify
+    #[corresponds(SSL_set_verify)]
+    pub fn set_verify(&mut self, mode: SslVerifyMode) {
+        unsafe { ffi::SSL_set_verify(self.as_ptr(), mode.bits as c_int, None) }
     }
 
-    /// Returns the options used by the context.
-    #[corresponds(SSL_CTX_get_options)]
-    pub fn options(&self) -> SslOptions {
-        let bits = unsafe { ffi::SSL_CTX_get_options(self.as_ptr()) } as SslTy;
-        SslOptions { bits }
+    /// Returns the verify mode that was set using `set_verify`.
+    #[corresponds(SSL_set_verify_mode)]
+    pub fn verify_mode(&self) -> SslVerifyMode {
+        let mode = unsafe { ffi::SSL_get_verify_mode(self.as_ptr()) };
+        SslVerifyMode::from_bits(mode).expect("SSL_get_verify_mode returned invalid mode")
     }
 
-    /// Clears the options used by the context, returning the old set.
-    #[corresponds(SSL_CTX_clear_options)]
-    pub fn clear_options(&mut self, option: SslOptions) -> SslOptions {
-        let bits =
-            unsafe { ffi::SSL_CTX_clear_options(self.as_ptr(), option.bits() as BitTy) } as SslTy;
-        SslOptions { bits }
+    /// Like [`SslContextBuilder::set_quiet_shutdown`].
+    #[corresponds(SSL_set_quiet_shutdown)]
+    pub fn set_quiet_shutdown(&mut self, quiet: bool) {
+        unsafe { ffi::SSL_set_quiet_shutdown(self.as_ptr(), quiet as c_int) }
     }
 
-    /// Sets the minimum supported protocol version.
-    ///
-    /// A value of `None` will enable protocol versions down the the lowest version supported by
-    /// OpenSSL.
-    ///
-    /// Requires OpenSSL 1.1.0 or LibreSSL 2.6.1 or newer.
-    #[corresponds(SSL_CTX_set_min_proto_version)]
-    #[cfg(any(ossl110, libressl261))]
-    pub fn set_min_proto_version(&mut self, version: Option<SslVersion>) -> Result<(), ErrorStack> {
-        unsafe {
-            cvt(ffi::SSL_CTX_set_min_proto_version(
-                self.as_ptr(),
-                version.map_or(0, |v| v.0 as _),
-            ))
-            .map(|_| ())
-        }
+    /// Like [`SslContextRef::quiet_shutdown`].
+    #[corresponds(SSL_get_quiet_shutdown)]
+    pub fn quiet_shutdown(&self) -> bool {
+        unsafe { ffi::SSL_get_quiet_shutdown(self.as_ptr()) != 0 }
     }
 
-    /// Sets the maximum supported protocol version.
-    ///
-    /// A value of `None` will enable protocol versions down the the highest version supported by
-    /// OpenSSL.
-    ///
-    /// Requires OpenSSL 1.1.0 or or LibreSSL 2.6.1 or newer.
-    #[corresponds(SSL_CTX_set_max_proto_version)]
-    #[cfg(any(ossl110, libressl261))]
-    pub fn set_max_proto_version(&mut self, version: Option<SslVersion>) -> Result<(), ErrorStack> {
-        unsafe {
-            cvt(ffi::SSL_CTX_set_max_proto_version(
-                self.as_ptr(),
-                version.map_or(0, |v| v.0 as _),
-            ))
-            .map(|_| ())
-        }
+    /// Returns the session's shutdown state.
+    #[corresponds(SSL_get_shutdown)]
+    pub fn get_shutdown(&self) -> ShutdownState {
+        let bits = unsafe { ffi::SSL_get_shutdown(self.as_ptr()) };
+        ShutdownState::from_bits_truncate(bits)
     }
 
-    /// Gets the minimum supported protocol version.
+    /// Sets the session's shutdown state.
     ///
-    /// A value of `None` indicates that all versions down the the lowest version supported by
-    /// OpenSSL are enabled.
+    /// This can be used to tell OpenSSL that the session should be cached even if a full two-way
+    /// shutdown was not completed.
+    #[corresponds(SSL_set_shutdown)]
+    pub fn set_shutdown(&mut self, state: ShutdownState) {
+        unsafe { ffi::SSL_set_shutdown(self.as_ptr(), state.bits()) }
+    }
+
+    /// Like [`SslContextBuilder::set_verify_callback`].
     ///
-    /// Requires OpenSSL 1.1.0g or LibreSSL 2.7.0 or newer.
-    #[corresponds(SSL_CTX_get_min_proto_version)]
-    #[cfg(any(ossl110g, libressl270))]
-    pub fn min_proto_version(&mut self) -> Option<SslVersion> {
+    /// [`SslContextBuilder::set_verify_callback`]: struct.SslContextBuilder.html#method.set_verify_callback
+    #[corresponds(SSL_set_verify)]
+    pub fn set_verify_callback<F>(&mut self, mode: SslVerifyMode, verify: F)
+    where
+        F: Fn(bool, &mut X509StoreContextRef) -> bool + 'static + Sync + Send,
+    {
         unsafe {
-            let r = ffi::SSL_CTX_get_min_proto_version(self.as_ptr());
-            if r == 0 {
-                None
-            } else {
-                Some(SslVersion(r))
-            }
+            // this needs to be in an Arc since the callback can register a new callback!
+            self.set_ex_data(Ssl::cached_ex_index(), Arc::new(verify));
+            ffi::SSL_set_verify(self.as_ptr(), mode.bits as c_int, Some(ssl_raw_verify::<F>));
         }
     }
 
-    /// Gets the maximum supported protocol version.
+    /// Like [`SslContextBuilder::set_post_handshake_auth`].
     ///
-    /// A value of `None` indicates that all versions down the the highest version supported by
-    /// OpenSSL are enabled.
-    ///
-    /// Requires OpenSSL 1.1.0g or LibreSSL 2.7.0 or newer.
-    #[corresponds(SSL_CTX_get_max_proto_version)]
-    #[cfg(any(ossl110g, libressl270))]
-    pub fn max_proto_version(&mut self) -> Option<SslVersion> {
-        unsafe {
-            let r = ffi::SSL_CTX_get_max_proto_version(self.as_ptr());
-            if r == 0 {
-                None
-            } else {
-                Some(SslVersion(r))
-            }
-        }
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_set_post_handshake_auth)]
+    #[cfg(ossl111)]
+    pub fn set_post_handshake_auth(&mut self, enable: bool) {
+        unsafe { ffi::SSL_set_post_handshake_auth(self.as_ptr(), enable as c_int) }
     }
 
-    /// Sets the protocols to sent to the server for Application Layer Protocol Negotiation (ALPN).
+    /// Schedules a TLS 1.3 key update, replacing the traffic keys used to protect data sent on
+    /// the connection.
     ///
-    /// The input must be in ALPN "wire format". It consists of a sequence of supported protocol
-    /// names prefixed by their byte length. For example, the protocol list consisting of `spdy/1`
-    /// and `http/1.1` is encoded as `b"\x06spdy/1\x08http/1.1"`. The protocols are ordered by
-    /// preference.
+    /// Like [`verify_client_post_handshake`], this only queues the update, which is sent by the
+    /// next write or by [`SslStream::do_handshake`]. Long-lived connections can use this to
+    /// limit the amount of data protected by a single key, for example by comparing
+    /// [`bytes_written`] against a threshold. `update_type` must not be `KeyUpdateType::NONE`.
     ///
-    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
-    #[corresponds(SSL_CTX_set_alpn_protos)]
-    #[cfg(any(ossl102, libressl261))]
-    pub fn set_alpn_protos(&mut self, protocols: &[u8]) -> Result<(), ErrorStack> {
-        unsafe {
-            assert!(protocols.len() <= c_uint::max_value() as usize);
-            let r = ffi::SSL_CTX_set_alpn_protos(
-                self.as_ptr(),
-                protocols.as_ptr(),
-                protocols.len() as c_uint,
-            );
-            // fun fact, SSL_CTX_set_alpn_protos has a reversed return code D:
-            if r == 0 {
-                Ok(())
-            } else {
-                Err(ErrorStack::get())
-            }
-        }
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// [`verify_client_post_handshake`]: SslRef::verify_client_post_handshake
+    /// [`bytes_written`]: SslRef::bytes_written
+    #[corresponds(SSL_key_update)]
+    #[cfg(ossl111)]
+    pub fn key_update(&mut self, update_type: KeyUpdateType) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_key_update(self.as_ptr(), update_type.0)).map(|_| ()) }
     }
 
-    /// Enables the DTLS extension "use_srtp" as defined in RFC5764.
-    #[corresponds(SSL_CTX_set_tlsext_use_srtp)]
-    pub fn set_tlsext_use_srtp(&mut self, protocols: &str) -> Result<(), ErrorStack> {
-        unsafe {
-            let cstr = CString::new(protocols).unwrap();
-
-            let r = ffi::SSL_CTX_set_tlsext_use_srtp(self.as_ptr(), cstr.as_ptr());
-            // fun fact, set_tlsext_use_srtp has a reversed return code D:
-            if r == 0 {
-                Ok(())
-            } else {
-                Err(ErrorStack::get())
-            }
-        }
+    /// Returns the type of the key update which has been scheduled but not yet sent.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_get_key_update_type)]
+    #[cfg(ossl111)]
+    pub fn key_update_type(&self) -> KeyUpdateType {
+        unsafe { KeyUpdateType(ffi::SSL_get_key_update_type(self.as_ptr())) }
     }
 
-    /// Sets the callback used by a server to select a protocol for Application Layer Protocol
-    /// Negotiation (ALPN).
-    ///
-    /// The callback is provided with the client's protocol list in ALPN wire format. See the
-    /// documentation for [`SslContextBuilder::set_alpn_protos`] for details. It should return one
-    /// of those protocols on success. The [`select_next_proto`] function implements the standard
-    /// protocol selection algorithm.
-    ///
-    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    /// Schedules a renegotiation of a TLS 1.2 or earlier connection.
     ///
-    /// [`SslContextBuilder::set_alpn_protos`]: struct.SslContextBuilder.html#method.set_alpn_protos
-    /// [`select_next_proto`]: fn.select_next_proto.html
-    #[corresponds(SSL_CTX_set_alpn_select_cb)]
-    #[cfg(any(ossl102, libressl261))]
-    pub fn set_alpn_select_callback<F>(&mut self, callback: F)
-    where
-        F: for<'a> Fn(&mut SslRef, &'a [u8]) -> Result<&'a [u8], AlpnError> + 'static + Sync + Send,
-    {
-        unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_set_alpn_select_cb__fixed_rust(
-                self.as_ptr(),
-                Some(callbacks::raw_alpn_select::<F>),
-                ptr::null_mut(),
-            );
-        }
+    /// A client sends a new ClientHello on the next call to [`SslStream::do_handshake`], which
+    /// then completes the renegotiation, or on the next read or write. A server sends a
+    /// HelloRequest, and the client's response is processed by subsequent reads. Servers reject
+    /// client-initiated renegotiation by default since OpenSSL 3.0.0.
+    #[corresponds(SSL_renegotiate)]
+    #[cfg(not(boringssl))]
+    pub fn renegotiate(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_renegotiate(self.as_ptr())).map(|_| ()) }
     }
 
-    /// Checks for consistency between the private key and certificate.
-    #[corresponds(SSL_CTX_check_private_key)]
-    pub fn check_private_key(&self) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_CTX_check_private_key(self.as_ptr())).map(|_| ()) }
+    /// Like [`renegotiate`], but attempts to resume the current session.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`renegotiate`]: SslRef::renegotiate
+    #[corresponds(SSL_renegotiate_abbreviated)]
+    #[cfg(ossl110)]
+    pub fn renegotiate_abbreviated(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_renegotiate_abbreviated(self.as_ptr())).map(|_| ()) }
     }
 
-    /// Returns a shared reference to the context's certificate store.
-    #[corresponds(SSL_CTX_get_cert_store)]
-    pub fn cert_store(&self) -> &X509StoreBuilderRef {
-        unsafe { X509StoreBuilderRef::from_ptr(ffi::SSL_CTX_get_cert_store(self.as_ptr())) }
+    /// Determines if a renegotiation has been scheduled but has not yet completed.
+    #[corresponds(SSL_renegotiate_pending)]
+    #[cfg(not(boringssl))]
+    pub fn renegotiate_pending(&self) -> bool {
+        unsafe { ffi::SSL_renegotiate_pending(self.as_ptr()) != 0 }
     }
 
-    /// Returns a mutable reference to the context's certificate store.
-    #[corresponds(SSL_CTX_get_cert_store)]
-    pub fn cert_store_mut(&mut self) -> &mut X509StoreBuilderRef {
-        unsafe { X509StoreBuilderRef::from_ptr_mut(ffi::SSL_CTX_get_cert_store(self.as_ptr())) }
+    /// Requests a certificate from the client after a TLS 1.3 handshake has completed.
+    ///
+    /// The request is only queued by this method: it is sent by the next write, or by
+    /// [`SslStream::do_handshake`], and the client's certificate is verified with the connection's
+    /// verify mode and callback when its response is read. This fails if the connection is not
+    /// using TLS 1.3 or the client did not enable post-handshake authentication.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_verify_client_post_handshake)]
+    #[cfg(ossl111)]
+    pub fn verify_client_post_handshake(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_verify_client_post_handshake(self.as_ptr())).map(|_| ()) }
     }
 
-    /// Returns a reference to the X509 verification configuration.
+    /// Determines if the connection is paused waiting for an asynchronous job to complete.
     ///
-    /// Requires OpenSSL 1.0.2 or newer.
-    #[corresponds(SSL_CTX_get0_param)]
-    #[cfg(any(ossl102, libressl261))]
-    pub fn verify_param(&self) -> &X509VerifyParamRef {
-        unsafe { X509VerifyParamRef::from_ptr(ffi::SSL_CTX_get0_param(self.as_ptr())) }
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_waiting_for_async)]
+    #[cfg(ossl110)]
+    pub fn waiting_for_async(&self) -> bool {
+        unsafe { ffi::SSL_waiting_for_async(self.as_ptr()) != 0 }
     }
 
-    /// Returns a mutable reference to the X509 verification configuration.
+    /// Returns the file descriptors which will become readable when a paused asynchronous job
+    /// can be resumed.
     ///
-    /// Requires OpenSSL 1.0.2 or newer.
-    #[corresponds(SSL_CTX_get0_param)]
-    #[cfg(any(ossl102, libressl261))]
-    pub fn verify_param_mut(&mut self) -> &mut X509VerifyParamRef {
-        unsafe { X509VerifyParamRef::from_ptr_mut(ffi::SSL_CTX_get0_param(self.as_ptr())) }
+    /// This fails if the connection has not run an asynchronous job.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_get_all_async_fds)]
+    #[cfg(all(ossl110, unix))]
+    pub fn async_fds(&self) -> Result<Vec<RawFd>, ErrorStack> {
+        unsafe {
+            let mut len = 0;
+            cvt(ffi::SSL_get_all_async_fds(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut len,
+            ))?;
+            let mut fds = vec![0; len];
+            cvt(ffi::SSL_get_all_async_fds(
+                self.as_ptr(),
+                fds.as_mut_ptr(),
+                &mut len,
+            ))?;
+            fds.truncate(len);
+            Ok(fds)
+        }
     }
 
-    /// Sets the callback dealing with OCSP stapling.
+    /// Returns the async file descriptors which were added and removed since this was last
+    /// called, as a pair of `(added, removed)`.
     ///
-    /// On the client side, this callback is responsible for validating the OCSP status response
-    /// returned by the server. The status may be retrieved with the `SslRef::ocsp_status` method.
-    /// A response of `Ok(true)` indicates that the OCSP status is valid, and a response of
-    /// `Ok(false)` indicates that the OCSP status is invalid and the handshake should be
-    /// terminated.
+    /// This allows an event loop to update its registrations incrementally rather than
+    /// re-registering every descriptor returned by [`async_fds`].
     ///
-    /// On the server side, this callback is responsible for setting the OCSP status response to be
-    /// returned to clients. The status may be set with the `SslRef::set_ocsp_status` method. A
-    /// response of `Ok(true)` indicates that the OCSP status should be returned to the client, and
-    /// `Ok(false)` indicates that the status should not be returned to the client.
-    #[corresponds(SSL_CTX_set_tlsext_status_cb)]
-    pub fn set_status_callback<F>(&mut self, callback: F) -> Result<(), ErrorStack>
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`async_fds`]: SslRef::async_fds
+    #[corresponds(SSL_get_changed_async_fds)]
+    #[cfg(all(ossl110, unix))]
+    pub fn changed_async_fds(&self) -> Result<(Vec<RawFd>, Vec<RawFd>), ErrorStack> {
+        unsafe {
+            let mut added_len = 0;
+            let mut removed_len = 0;
+            cvt(ffi::SSL_get_changed_async_fds(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut added_len,
+                ptr::null_mut(),
+                &mut removed_len,
+            ))?;
+            let mut added = vec![0; added_len];
+            let mut removed = vec![0; removed_len];
+            cvt(ffi::SSL_get_changed_async_fds(
+                self.as_ptr(),
+                added.as_mut_ptr(),
+                &mut added_len,
+                removed.as_mut_ptr(),
+                &mut removed_len,
+            ))?;
+            added.truncate(added_len);
+            removed.truncate(removed_len);
+            Ok((added, removed))
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_async_callback`], but for this connection only.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[corresponds(SSL_set_async_callback)]
+    #[cfg(ossl300)]
+    pub fn set_async_callback<F>(&mut self, callback: F) -> Result<(), ErrorStack>
     where
-        F: Fn(&mut SslRef) -> Result<bool, ErrorStack> + 'static + Sync + Send,
+        F: Fn() + 'static + Sync + Send,
     {
         unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-            cvt(
-                ffi::SSL_CTX_set_tlsext_status_cb(self.as_ptr(), Some(raw_tlsext_status::<F>))
-                    as c_int,
-            )
+            let index = Ssl::cached_ex_index::<F>();
+            self.set_ex_data(index, callback);
+            let ptr = ffi::SSL_get_ex_data(self.as_ptr(), index.as_raw());
+            cvt(ffi::SSL_set_async_callback_arg(self.as_ptr(), ptr))?;
+            cvt(ffi::SSL_set_async_callback(
+                self.as_ptr(),
+                Some(callbacks::raw_async::<F>),
+            ))
             .map(|_| ())
         }
     }
 
-    /// Sets the callback for providing an identity and pre-shared key for a TLS-PSK client.
+    /// Returns the status of the connection's most recent asynchronous job.
     ///
-    /// The callback will be called with the SSL context, an identity hint if one was provided
-    /// by the server, a mutable slice for each of the identity and pre-shared key bytes. The
-    /// identity must be written as a null-terminated C string.
-    #[corresponds(SSL_CTX_set_psk_client_callback)]
-    #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
-    pub fn set_psk_client_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, Option<&[u8]>, &mut [u8], &mut [u8]) -> Result<usize, ErrorStack>
-            + 'static
-            + Sync
-            + Send,
-    {
+    /// This fails if the connection has not run an asynchronous job.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[corresponds(SSL_get_async_status)]
+    #[cfg(ossl300)]
+    pub fn async_status(&self) -> Result<AsyncStatus, ErrorStack> {
         unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_set_psk_client_callback(self.as_ptr(), Some(raw_client_psk::<F>));
+            let mut status = 0;
+            cvt(ffi::SSL_get_async_status(self.as_ptr(), &mut status))?;
+            Ok(AsyncStatus(status))
         }
     }
 
-    #[deprecated(since = "0.10.10", note = "renamed to `set_psk_client_callback`")]
-    #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
-    pub fn set_psk_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, Option<&[u8]>, &mut [u8], &mut [u8]) -> Result<usize, ErrorStack>
-            + 'static
-            + Sync
-            + Send,
-    {
-        self.set_psk_client_callback(callback)
+    /// Suspends certificate verification, to be resumed when the handshake is next driven.
+    ///
+    /// This should be called from a verification callback, which should then return `true`. The
+    /// handshake will return with an `ErrorCode::WANT_RETRY_VERIFY` error, allowing a
+    /// potentially slow check such as a revocation lookup to be performed elsewhere. Verification,
+    /// including the callback, is run again when the handshake is resumed. The connection can be
+    /// reached from the callback's `X509StoreContextRef` with [`X509StoreContext::ssl_idx`].
+    ///
+    /// [`X509StoreContext::ssl_idx`]: crate::x509::X509StoreContext::ssl_idx
+    ///
+    /// Returns `false` if verification can't be suspended at this point of the handshake.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[corresponds(SSL_set_retry_verify)]
+    #[cfg(ossl300)]
+    pub fn set_retry_verify(&mut self) -> bool {
+        unsafe { ffi::SSL_set_retry_verify(self.as_ptr()) != 0 }
     }
 
-    /// Sets the callback for providing an identity and pre-shared key for a TLS-PSK server.
+    /// Like [`SslContextBuilder::set_tmp_dh`].
     ///
-    /// The callback will be called with the SSL context, an identity provided by the client,
-    /// and, a mutable slice for the pre-shared key bytes. The callback returns the number of
-    /// bytes in the pre-shared key.
-    #[corresponds(SSL_CTX_set_psk_server_callback)]
-    #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
-    pub fn set_psk_server_callback<F>(&mut self, callback: F)
+    /// [`SslContextBuilder::set_tmp_dh`]: struct.SslContextBuilder.html#method.set_tmp_dh
+    #[corresponds(SSL_set_tmp_dh)]
+    pub fn set_tmp_dh(&mut self, dh: &DhRef<Params>) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_tmp_dh(self.as_ptr(), dh.as_ptr()) as c_int).map(|_| ()) }
+    }
+
+    /// Like [`SslContextBuilder::set_tmp_dh_callback`].
+    ///
+    /// [`SslContextBuilder::set_tmp_dh_callback`]: struct.SslContextBuilder.html#method.set_tmp_dh_callback
+    #[corresponds(SSL_set_tmp_dh_callback)]
+    pub fn set_tmp_dh_callback<F>(&mut self, callback: F)
     where
-        F: Fn(&mut SslRef, Option<&[u8]>, &mut [u8]) -> Result<usize, ErrorStack>
-            + 'static
-            + Sync
-            + Send,
+        F: Fn(&mut SslRef, bool, u32) -> Result<Dh<Params>, ErrorStack> + 'static + Sync + Send,
     {
         unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_set_psk_server_callback(self.as_ptr(), Some(raw_server_psk::<F>));
+            // this needs to be in an Arc since the callback can register a new callback!
+            self.set_ex_data(Ssl::cached_ex_index(), Arc::new(callback));
+            #[cfg(boringssl)]
+            ffi::SSL_set_tmp_dh_callback(self.as_ptr(), Some(raw_tmp_dh_ssl::<F>));
+            #[cfg(not(boringssl))]
+            ffi::SSL_set_tmp_dh_callback(self.as_ptr(), raw_tmp_dh_ssl::<F>);
         }
     }
 
-    /// Sets the callback which is called when new sessions are negotiated.
-    ///
-    /// This can be used by clients to implement session caching. While in TLSv1.2 the session is
-    /// available to access via [`SslRef::session`] immediately after the handshake completes, this
-    /// is not the case for TLSv1.3. There, a session is not generally available immediately, and
-    /// the server may provide multiple session tokens to the client over a single session. The new
-    /// session callback is a portable way to deal with both cases.
+    /// Like [`SslContextBuilder::set_tmp_ecdh`].
     ///
-    /// Note that session caching must be enabled for the callback to be invoked, and it defaults
-    /// off for clients. [`set_session_cache_mode`] controls that behavior.
+    /// [`SslContextBuilder::set_tmp_ecdh`]: struct.SslContextBuilder.html#method.set_tmp_ecdh
+    #[corresponds(SSL_set_tmp_ecdh)]
+    pub fn set_tmp_ecdh(&mut self, key: &EcKeyRef<Params>) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_tmp_ecdh(self.as_ptr(), key.as_ptr()) as c_int).map(|_| ()) }
+    }
+
+    /// Like [`SslContextBuilder::set_tmp_ecdh_callback`].
     ///
-    /// [`SslRef::session`]: struct.SslRef.html#method.session
-    /// [`set_session_cache_mode`]: #method.set_session_cache_mode
-    #[corresponds(SSL_CTX_sess_set_new_cb)]
-    pub fn set_new_session_callback<F>(&mut self, callback: F)
+    /// Requires OpenSSL 1.0.1 or 1.0.2.
+    #[corresponds(SSL_set_tmp_ecdh_callback)]
+    #[cfg(any(all(ossl101, not(ossl110))))]
+    #[deprecated(note = "this function leaks memory and does not exist on newer OpenSSL versions")]
+    pub fn set_tmp_ecdh_callback<F>(&mut self, callback: F)
     where
-        F: Fn(&mut SslRef, SslSession) + 'static + Sync + Send,
+        F: Fn(&mut SslRef, bool, u32) -> Result<EcKey<Params>, ErrorStack> + 'static + Sync + Send,
     {
         unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_sess_set_new_cb(self.as_ptr(), Some(callbacks::raw_new_session::<F>));
+            // this needs to be in an Arc since the callback can register a new callback!
+            self.set_ex_data(Ssl::cached_ex_index(), Arc::new(callback));
+            ffi::SSL_set_tmp_ecdh_callback__fixed_rust(self.as_ptr(), Some(raw_tmp_ecdh_ssl::<F>));
         }
     }
 
-    /// Sets the callback which is called when sessions are removed from the context.
+    /// Like [`SslContextBuilder::set_ecdh_auto`].
     ///
-    /// Sessions can be removed because they have timed out or because they are considered faulty.
-    #[corresponds(SSL_CTX_sess_set_remove_cb)]
-    pub fn set_remove_session_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(&SslContextRef, &SslSessionRef) + 'static + Sync + Send,
-    {
+    /// Requires OpenSSL 1.0.2.
+    ///
+    /// [`SslContextBuilder::set_tmp_ecdh`]: struct.SslContextBuilder.html#method.set_tmp_ecdh
+    #[corresponds(SSL_set_ecdh_auto)]
+    #[cfg(all(ossl102, not(ossl110)))]
+    pub fn set_ecdh_auto(&mut self, onoff: bool) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_ecdh_auto(self.as_ptr(), onoff as c_int)).map(|_| ()) }
+    }
+
+    /// Like [`SslContextBuilder::set_alpn_protos`].
+    ///
+    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    ///
+    /// [`SslContextBuilder::set_alpn_protos`]: struct.SslContextBuilder.html#method.set_alpn_protos
+    #[corresponds(SSL_set_alpn_protos)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn set_alpn_protos(&mut self, protocols: &[u8]) -> Result<(), ErrorStack> {
         unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_sess_set_remove_cb(
+            assert!(protocols.len() <= c_uint::max_value() as usize);
+            let r = ffi::SSL_set_alpn_protos(
                 self.as_ptr(),
-                Some(callbacks::raw_remove_session::<F>),
+                protocols.as_ptr(),
+                protocols.len() as c_uint,
             );
+            // fun fact, SSL_set_alpn_protos has a reversed return code D:
+            if r == 0 {
+                Ok(())
+            } else {
+                Err(ErrorStack::get())
+            }
         }
     }
 
-    /// Sets the callback which is called when a client proposed to resume a session but it was not
-    /// found in the internal cache.
+    /// Returns the current cipher if the session is active.
+    #[corresponds(SSL_get_current_cipher)]
+    pub fn current_cipher(&self) -> Option<&SslCipherRef> {
+        unsafe {
+            let ptr = ffi::SSL_get_current_cipher(self.as_ptr());
+
+            SslCipherRef::from_const_ptr_opt(ptr)
+        }
+    }
+
+    /// Returns the ciphers enabled by the connection's cipher list and TLS 1.3 ciphersuites, in
+    /// order of preference.
+    #[corresponds(SSL_get_ciphers)]
+    pub fn ciphers(&self) -> Option<&StackRef<SslCipher>> {
+        unsafe { StackRef::from_const_ptr_opt(ffi::SSL_get_ciphers(self.as_ptr())) }
+    }
+
+    /// Returns the ciphers which would actually be used by the connection.
     ///
-    /// The callback is passed a reference to the session ID provided by the client. It should
-    /// return the session corresponding to that ID if available. This is only used for servers, not
-    /// clients.
+    /// Unlike [`ciphers`], this excludes ciphers which are disabled by the connection's
+    /// protocol version range or security level. On the client these are the ciphers offered in
+    /// the ClientHello.
     ///
-    /// # Safety
+    /// Returns `None` if no ciphers are usable.
     ///
-    /// The returned `SslSession` must not be associated with a different `SslContext`.
-    #[corresponds(SSL_CTX_sess_set_get_cb)]
-    pub unsafe fn set_get_session_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, &[u8]) -> Option<SslSession> + 'static + Sync + Send,
-    {
-        self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-        ffi::SSL_CTX_sess_set_get_cb(self.as_ptr(), Some(callbacks::raw_get_session::<F>));
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`ciphers`]: SslRef::ciphers
+    #[corresponds(SSL_get1_supported_ciphers)]
+    #[cfg(ossl110)]
+    pub fn supported_ciphers(&self) -> Option<Stack<SslCipher>> {
+        unsafe { Stack::from_ptr_opt(ffi::SSL_get1_supported_ciphers(self.as_ptr())) }
     }
 
-    /// Sets the TLS key logging callback.
+    /// Returns the group used for the key exchange.
     ///
-    /// The callback is invoked whenever TLS key material is generated, and is passed a line of NSS
-    /// SSLKEYLOGFILE-formatted text. This can be used by tools like Wireshark to decrypt message
-    /// traffic. The line does not contain a trailing newline.
+    /// Returns `None` before the handshake, or if the group has no NID, as is the case for groups
+    /// implemented by providers. Use [`SslRef::negotiated_group_name`] to identify those.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[corresponds(SSL_get_negotiated_group)]
+    #[cfg(ossl300)]
+    pub fn negotiated_group(&self) -> Option<Nid> {
+        let nid = unsafe { ffi::SSL_get_negotiated_group(self.as_ptr()) as c_int };
+        if nid == 0 || nid & ffi::TLSEXT_nid_unknown != 0 {
+            None
+        } else {
+            Some(Nid::from_raw(nid))
+        }
+    }
+
+    /// Returns the name of the group used for the key exchange.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_get0_group_name)]
+    #[cfg(ossl320)]
+    pub fn negotiated_group_name(&self) -> Option<&str> {
+        unsafe {
+            let ptr = ffi::SSL_get0_group_name(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                CStr::from_ptr(ptr).to_str().ok()
+            }
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_groups_list`].
     ///
     /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_CTX_set_keylog_callback)]
+    #[corresponds(SSL_set1_groups_list)]
     #[cfg(ossl111)]
-    pub fn set_keylog_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(&SslRef, &str) + 'static + Sync + Send,
-    {
+    pub fn set_groups_list(&mut self, groups: &str) -> Result<(), ErrorStack> {
+        let groups = CString::new(groups).unwrap();
         unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_set_keylog_callback(self.as_ptr(), Some(callbacks::raw_keylog::<F>));
+            cvt(ffi::SSL_set1_groups_list(self.as_ptr(), groups.as_ptr()) as c_int).map(|_| ())
         }
     }
 
-    /// Sets the session caching mode use for connections made with the context.
+    /// Like [`SslContextBuilder::set_groups`].
     ///
-    /// Returns the previous session caching mode.
-    #[corresponds(SSL_CTX_set_session_cache_mode)]
-    pub fn set_session_cache_mode(&mut self, mode: SslSessionCacheMode) -> SslSessionCacheMode {
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_set1_groups)]
+    #[cfg(ossl111)]
+    pub fn set_groups(&mut self, groups: &[Nid]) -> Result<(), ErrorStack> {
+        let groups = groups.iter().map(|n| n.as_raw()).collect::<Vec<_>>();
         unsafe {
-            let bits = ffi::SSL_CTX_set_session_cache_mode(self.as_ptr(), mode.bits());
-            SslSessionCacheMode { bits }
+            cvt(
+                ffi::SSL_set1_groups(self.as_ptr(), groups.as_ptr(), groups.len() as c_long)
+                    as c_int,
+            )
+            .map(|_| ())
         }
     }
 
-    /// Sets the callback for generating an application cookie for TLS1.3
-    /// stateless handshakes.
+    /// Returns the groups advertised by the peer in its supported groups extension.
     ///
-    /// The callback will be called with the SSL context and a slice into which the cookie
-    /// should be written. The callback should return the number of bytes written.
-    #[corresponds(SSL_CTX_set_stateless_cookie_generate_cb)]
+    /// This is primarily useful on the server side, where it reports the client's groups in
+    /// order of preference. Groups which are not known to OpenSSL are omitted.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_get1_groups)]
     #[cfg(ossl111)]
-    pub fn set_stateless_cookie_generate_cb<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, &mut [u8]) -> Result<usize, ErrorStack> + 'static + Sync + Send,
-    {
+    pub fn peer_groups(&self) -> Vec<Nid> {
         unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_set_stateless_cookie_generate_cb(
-                self.as_ptr(),
-                Some(raw_stateless_cookie_generate::<F>),
-            );
+            let len = ffi::SSL_get1_groups(self.as_ptr(), ptr::null_mut());
+            if len <= 0 {
+                return vec![];
+            }
+            let mut groups = vec![0; len as usize];
+            ffi::SSL_get1_groups(self.as_ptr(), groups.as_mut_ptr());
+            groups
+                .into_iter()
+                .filter(|&n| n & ffi::TLSEXT_nid_unknown == 0)
+                .map(Nid::from_raw)
+                .collect()
         }
     }
 
-    /// Sets the callback for verifying an application cookie for TLS1.3
-    /// stateless handshakes.
+    /// Returns the groups supported by both sides of the connection.
     ///
-    /// The callback will be called with the SSL context and the cookie supplied by the
-    /// client. It should return true if and only if the cookie is valid.
+    /// This is only available on the server, where the groups are ordered by preference,
+    /// honoring [`SslOptions::CIPHER_SERVER_PREFERENCE`]. Clients always get an empty list.
+    /// Groups which are not known to OpenSSL are omitted.
     ///
-    /// Note that the OpenSSL implementation independently verifies the integrity of
-    /// application cookies using an HMAC before invoking the supplied callback.
-    #[corresponds(SSL_CTX_set_stateless_cookie_verify_cb)]
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_get_shared_group)]
     #[cfg(ossl111)]
-    pub fn set_stateless_cookie_verify_cb<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, &[u8]) -> bool + 'static + Sync + Send,
-    {
+    pub fn shared_groups(&self) -> Vec<Nid> {
+        unsafe {
+            let len = ffi::SSL_get_shared_group(self.as_ptr(), -1);
+            (0..len)
+                .map(|i| ffi::SSL_get_shared_group(self.as_ptr(), i) as c_int)
+                .filter(|&n| n != 0 && n & ffi::TLSEXT_nid_unknown == 0)
+                .map(Nid::from_raw)
+                .collect()
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_sigalgs_list`].
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_set1_sigalgs_list)]
+    #[cfg(ossl102)]
+    pub fn set_sigalgs_list(&mut self, sigalgs: &str) -> Result<(), ErrorStack> {
+        let sigalgs = CString::new(sigalgs).unwrap();
+        unsafe {
+            cvt(ffi::SSL_set1_sigalgs_list(self.as_ptr(), sigalgs.as_ptr()) as c_int).map(|_| ())
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_client_sigalgs_list`].
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_set1_client_sigalgs_list)]
+    #[cfg(ossl102)]
+    pub fn set_client_sigalgs_list(&mut self, sigalgs: &str) -> Result<(), ErrorStack> {
+        let sigalgs = CString::new(sigalgs).unwrap();
         unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_set_stateless_cookie_verify_cb(
-                self.as_ptr(),
-                Some(raw_stateless_cookie_verify::<F>),
-            )
+            cvt(ffi::SSL_set1_client_sigalgs_list(self.as_ptr(), sigalgs.as_ptr()) as c_int)
+                .map(|_| ())
         }
     }
 
-    /// Sets the callback for generating a DTLSv1 cookie
+    /// Like [`SslContextBuilder::set_client_cert_types`].
     ///
-    /// The callback will be called with the SSL context and a slice into which the cookie
-    /// should be written. The callback should return the number of bytes written.
-    #[corresponds(SSL_CTX_set_cookie_generate_cb)]
-    #[cfg(not(boringssl))]
-    pub fn set_cookie_generate_cb<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, &mut [u8]) -> Result<usize, ErrorStack> + 'static + Sync + Send,
-    {
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_set1_client_cert_type)]
+    #[cfg(ossl320)]
+    pub fn set_client_cert_types(&mut self, types: &[CertificateType]) -> Result<(), ErrorStack> {
+        let types = types.iter().map(|t| t.0).collect::<Vec<_>>();
         unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_set_cookie_generate_cb(self.as_ptr(), Some(raw_cookie_generate::<F>));
+            cvt(ffi::SSL_set1_client_cert_type(
+                self.as_ptr(),
+                types.as_ptr(),
+                types.len(),
+            ))
+            .map(|_| ())
         }
     }
 
-    /// Sets the callback for verifying a DTLSv1 cookie
+    /// Like [`SslContextBuilder::set_server_cert_types`].
     ///
-    /// The callback will be called with the SSL context and the cookie supplied by the
-    /// client. It should return true if and only if the cookie is valid.
-    #[corresponds(SSL_CTX_set_cookie_verify_cb)]
-    #[cfg(not(boringssl))]
-    pub fn set_cookie_verify_cb<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, &[u8]) -> bool + 'static + Sync + Send,
-    {
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_set1_server_cert_type)]
+    #[cfg(ossl320)]
+    pub fn set_server_cert_types(&mut self, types: &[CertificateType]) -> Result<(), ErrorStack> {
+        let types = types.iter().map(|t| t.0).collect::<Vec<_>>();
         unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_set_cookie_verify_cb(self.as_ptr(), Some(raw_cookie_verify::<F>));
+            cvt(ffi::SSL_set1_server_cert_type(
+                self.as_ptr(),
+                types.as_ptr(),
+                types.len(),
+            ))
+            .map(|_| ())
         }
     }
 
-    /// Sets the extra data at the specified index.
+    /// Returns the type of certificate negotiated for the client.
     ///
-    /// This can be used to provide data to callbacks registered with the context. Use the
-    /// `SslContext::new_ex_index` method to create an `Index`.
-    #[corresponds(SSL_CTX_set_ex_data)]
-    pub fn set_ex_data<T>(&mut self, index: Index<SslContext, T>, data: T) {
-        self.set_ex_data_inner(index, data);
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_get_negotiated_client_cert_type)]
+    #[cfg(ossl320)]
+    pub fn negotiated_client_cert_type(&self) -> CertificateType {
+        unsafe { CertificateType(ffi::SSL_get_negotiated_client_cert_type(self.as_ptr()) as u8) }
     }
 
-    fn set_ex_data_inner<T>(&mut self, index: Index<SslContext, T>, data: T) -> *mut c_void {
-        unsafe {
-            let data = Box::into_raw(Box::new(data)) as *mut c_void;
-            ffi::SSL_CTX_set_ex_data(self.as_ptr(), index.as_raw(), data);
-            data
-        }
+    /// Returns the type of certificate negotiated for the server.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_get_negotiated_server_cert_type)]
+    #[cfg(ossl320)]
+    pub fn negotiated_server_cert_type(&self) -> CertificateType {
+        unsafe { CertificateType(ffi::SSL_get_negotiated_server_cert_type(self.as_ptr()) as u8) }
     }
 
-    /// Adds a custom extension for a TLS/DTLS client or server for all supported protocol versions.
+    /// Adds a raw public key which the peer is trusted to authenticate with.
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_CTX_add_custom_ext)]
-    #[cfg(ossl111)]
-    pub fn add_custom_ext<AddFn, ParseFn, T>(
-        &mut self,
-        ext_type: u16,
-        context: ExtensionContext,
-        add_cb: AddFn,
-        parse_cb: ParseFn,
-    ) -> Result<(), ErrorStack>
+    /// A peer authenticating with a raw public key passes verification only if its key matches
+    /// one added with this method. The keys are stored as DANE-EE TLSA records, so DANE must first
+    /// be enabled with [`SslRef::dane_enable`].
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_add_expected_rpk)]
+    #[cfg(ossl320)]
+    pub fn add_expected_rpk<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
     where
-        AddFn: Fn(
-                &mut SslRef,
-                ExtensionContext,
-                Option<(usize, &X509Ref)>,
-            ) -> Result<Option<T>, SslAlert>
-            + 'static
-            + Sync
-            + Send,
-        T: AsRef<[u8]> + 'static + Sync + Send,
-        ParseFn: Fn(
-                &mut SslRef,
-                ExtensionContext,
-                &[u8],
-                Option<(usize, &X509Ref)>,
-            ) -> Result<(), SslAlert>
-            + 'static
-            + Sync
-            + Send,
+        T: HasPublic,
     {
-        let ret = unsafe {
-            self.set_ex_data(SslContext::cached_ex_index::<AddFn>(), add_cb);
-            self.set_ex_data(SslContext::cached_ex_index::<ParseFn>(), parse_cb);
+        unsafe { cvt(ffi::SSL_add_expected_rpk(self.as_ptr(), key.as_ptr())).map(|_| ()) }
+    }
 
-            ffi::SSL_CTX_add_custom_ext(
-                self.as_ptr(),
-                ext_type as c_uint,
-                context.bits(),
-                Some(raw_custom_ext_add::<AddFn, T>),
-                Some(raw_custom_ext_free::<T>),
-                ptr::null_mut(),
-                Some(raw_custom_ext_parse::<ParseFn>),
-                ptr::null_mut(),
-            )
-        };
-        if ret == 1 {
-            Ok(())
-        } else {
-            Err(ErrorStack::get())
+    /// Returns the raw public key the peer authenticated with, if any.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_get0_peer_rpk)]
+    #[cfg(ossl320)]
+    pub fn peer_rpk(&self) -> Option<&PKeyRef<Public>> {
+        unsafe {
+            let ptr = ffi::SSL_get0_peer_rpk(self.as_ptr());
+            PKeyRef::from_const_ptr_opt(ptr)
         }
     }
 
-    /// Sets the maximum amount of early data that will be accepted on incoming connections.
-    ///
-    /// Defaults to 0.
+    /// Returns the ephemeral key the peer used for the key exchange.
     ///
     /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_CTX_set_max_early_data)]
+    #[corresponds(SSL_get_peer_tmp_key)]
     #[cfg(ossl111)]
-    pub fn set_max_early_data(&mut self, bytes: u32) -> Result<(), ErrorStack> {
-        if unsafe { ffi::SSL_CTX_set_max_early_data(self.as_ptr(), bytes) } == 1 {
-            Ok(())
-        } else {
-            Err(ErrorStack::get())
+    pub fn peer_tmp_key(&self) -> Result<PKey<Public>, ErrorStack> {
+        unsafe {
+            let mut key = ptr::null_mut();
+            cvt(ffi::SSL_get_peer_tmp_key(self.as_ptr(), &mut key) as c_int)?;
+            Ok(PKey::from_ptr(key))
         }
     }
 
-    /// Sets a callback which will be invoked just after the client's hello message is received.
+    /// Returns the digest the peer used to sign the handshake.
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_CTX_set_client_hello_cb)]
-    #[cfg(ossl111)]
-    pub fn set_client_hello_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, &mut SslAlert) -> Result<ClientHelloResponse, ErrorStack>
-            + 'static
-            + Sync
-            + Send,
-    {
+    /// This is `Nid::UNDEF` for signature algorithms without a separate digest, such as Ed25519.
+    /// Returns `None` if the peer has not signed the handshake.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_get_peer_signature_nid)]
+    #[cfg(ossl102)]
+    pub fn peer_signature_digest(&self) -> Option<Nid> {
         unsafe {
-            let ptr = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
-            ffi::SSL_CTX_set_client_hello_cb(
-                self.as_ptr(),
-                Some(callbacks::raw_client_hello::<F>),
-                ptr,
-            );
+            let mut nid = 0;
+            if ffi::SSL_get_peer_signature_nid(self.as_ptr(), &mut nid) > 0 {
+                Some(Nid::from_raw(nid))
+            } else {
+                None
+            }
         }
     }
 
-    /// Sets the context's session cache size limit, returning the previous limit.
+    /// Returns the type of signature the peer used to sign the handshake.
     ///
-    /// A value of 0 means that the cache size is unbounded.
-    #[corresponds(SSL_CTX_sess_set_cache_size)]
-    #[allow(clippy::useless_conversion)]
-    pub fn set_session_cache_size(&mut self, size: i32) -> i64 {
+    /// This is the public key algorithm, for example `Nid::RSAENCRYPTION` for PKCS#1 v1.5 RSA
+    /// signatures or `Nid::RSASSAPSS` for RSA-PSS signatures. Returns `None` if the peer has not
+    /// signed the handshake.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_get_peer_signature_type_nid)]
+    #[cfg(ossl111)]
+    pub fn peer_signature_type(&self) -> Option<Nid> {
         unsafe {
-            ffi::SSL_CTX_sess_set_cache_size(self.as_ptr(), size as SslCacheSize) as SslCacheTy
+            let mut nid = 0;
+            if ffi::SSL_get_peer_signature_type_nid(self.as_ptr(), &mut nid) > 0 {
+                Some(Nid::from_raw(nid))
+            } else {
+                None
+            }
         }
     }
 
-    /// Sets the context's supported signature algorithms.
+    /// Returns the digest used to sign the handshake locally.
     ///
-    /// Requires OpenSSL 1.0.2 or newer.
-    #[corresponds(SSL_CTX_set1_sigalgs_list)]
-    #[cfg(ossl102)]
-    pub fn set_sigalgs_list(&mut self, sigalgs: &str) -> Result<(), ErrorStack> {
-        let sigalgs = CString::new(sigalgs).unwrap();
+    /// Like [`SslRef::peer_signature_digest`], but for this side of the connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_get_signature_nid)]
+    #[cfg(ossl111)]
+    pub fn signature_digest(&self) -> Option<Nid> {
         unsafe {
-            cvt(ffi::SSL_CTX_set1_sigalgs_list(self.as_ptr(), sigalgs.as_ptr()) as c_int)
-                .map(|_| ())
+            let mut nid = 0;
+            if ffi::SSL_get_signature_nid(self.as_ptr(), &mut nid) > 0 {
+                Some(Nid::from_raw(nid))
+            } else {
+                None
+            }
         }
     }
 
-    /// Sets the context's supported elliptic curve groups.
+    /// Returns the type of signature used to sign the handshake locally.
+    ///
+    /// Like [`SslRef::peer_signature_type`], but for this side of the connection.
     ///
     /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_CTX_set1_groups_list)]
+    #[corresponds(SSL_get_signature_type_nid)]
     #[cfg(ossl111)]
-    pub fn set_groups_list(&mut self, groups: &str) -> Result<(), ErrorStack> {
-        let groups = CString::new(groups).unwrap();
+    pub fn signature_type(&self) -> Option<Nid> {
         unsafe {
-            cvt(ffi::SSL_CTX_set1_groups_list(self.as_ptr(), groups.as_ptr()) as c_int).map(|_| ())
+            let mut nid = 0;
+            if ffi::SSL_get_signature_type_nid(self.as_ptr(), &mut nid) > 0 {
+                Some(Nid::from_raw(nid))
+            } else {
+                None
+            }
         }
     }
 
-    /// Consumes the builder, returning a new `SslContext`.
-    pub fn build(self) -> SslContext {
-        self.0
+    /// Returns the signature algorithms supported by both sides of the connection.
+    ///
+    /// The algorithms are in order of preference. They are only available once the peer's
+    /// list of signature algorithms has been received.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_get_shared_sigalgs)]
+    #[cfg(ossl102)]
+    pub fn shared_sigalgs(&self) -> Vec<SslSigalg> {
+        unsafe {
+            let ptr = self.as_ptr();
+            let count = ffi::SSL_get_shared_sigalgs(
+                ptr,
+                0,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+
+            let mut sigalgs = Vec::with_capacity(cmp::max(count, 0) as usize);
+            for idx in 0..count {
+                let mut sign = 0;
+                let mut hash = 0;
+                let mut sign_hash = 0;
+                let mut rsig = 0;
+                let mut rhash = 0;
+                ffi::SSL_get_shared_sigalgs(
+                    ptr,
+                    idx,
+                    &mut sign,
+                    &mut hash,
+                    &mut sign_hash,
+                    &mut rsig,
+                    &mut rhash,
+                );
+                sigalgs.push(SslSigalg {
+                    signature_type: Nid::from_raw(sign),
+                    digest: Nid::from_raw(hash),
+                    nid: Nid::from_raw(sign_hash),
+                    code_point: u16::from(rhash) << 8 | u16::from(rsig),
+                });
+            }
+            sigalgs
+        }
     }
-}
 
-foreign_type_and_impl_send_sync! {
-    type CType = ffi::SSL_CTX;
-    fn drop = ffi::SSL_CTX_free;
+    /// Returns a short string describing the state of the session.
+    #[corresponds(SSL_state_string)]
+    pub fn state_string(&self) -> &'static str {
+        let state = unsafe {
+            let ptr = ffi::SSL_state_string(self.as_ptr());
+            CStr::from_ptr(ptr as *const _)
+        };
 
-    /// A context object for TLS streams.
-    ///
-    /// Applications commonly configure a single `SslContext` that is shared by all of its
-    /// `SslStreams`.
-    pub struct SslContext;
+        str::from_utf8(state.to_bytes()).unwrap()
+    }
 
-    /// Reference to [`SslContext`]
-    ///
-    /// [`SslContext`]: struct.SslContext.html
-    pub struct SslContextRef;
-}
+    /// Returns a longer string describing the state of the session.
+    #[corresponds(SSL_state_string_long)]
+    pub fn state_string_long(&self) -> &'static str {
+        let state = unsafe {
+            let ptr = ffi::SSL_state_string_long(self.as_ptr());
+            CStr::from_ptr(ptr as *const _)
+        };
 
-impl Clone for SslContext {
-    fn clone(&self) -> Self {
-        (**self).to_owned()
+        str::from_utf8(state.to_bytes()).unwrap()
     }
-}
 
-impl ToOwned for SslContextRef {
-    type Owned = SslContext;
+    /// Sets the host name to be sent to the server for Server Name Indication (SNI).
+    ///
+    /// It has no effect for a server-side connection.
+    #[corresponds(SSL_set_tlsext_host_name)]
+    pub fn set_hostname(&mut self, hostname: &str) -> Result<(), ErrorStack> {
+        let cstr = CString::new(hostname).unwrap();
+        unsafe {
+            cvt(ffi::SSL_set_tlsext_host_name(self.as_ptr(), cstr.as_ptr() as *mut _) as c_int)
+                .map(|_| ())
+        }
+    }
 
-    fn to_owned(&self) -> Self::Owned {
+    /// Returns the peer's certificate, if present.
+    #[corresponds(SSL_get_peer_certificate)]
+    pub fn peer_certificate(&self) -> Option<X509> {
         unsafe {
-            SSL_CTX_up_ref(self.as_ptr());
-            SslContext::from_ptr(self.as_ptr())
+            let ptr = SSL_get1_peer_certificate(self.as_ptr());
+            X509::from_ptr_opt(ptr)
         }
     }
-}
 
-// TODO: add useful info here
-impl fmt::Debug for SslContext {
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "SslContext")
+    /// Returns the certificate chain of the peer, if present.
+    ///
+    /// On the client side, the chain includes the leaf certificate, but on the server side it does
+    /// not. Fun!
+    #[corresponds(SSL_get_peer_cert_chain)]
+    pub fn peer_cert_chain(&self) -> Option<&StackRef<X509>> {
+        unsafe {
+            let ptr = ffi::SSL_get_peer_cert_chain(self.as_ptr());
+            StackRef::from_const_ptr_opt(ptr)
+        }
     }
-}
 
-impl SslContext {
-    /// Creates a new builder object for an `SslContext`.
-    pub fn builder(method: SslMethod) -> Result<SslContextBuilder, ErrorStack> {
-        SslContextBuilder::new(method)
+    /// Returns the DER encoding of each certificate in the peer's certificate chain.
+    ///
+    /// Returns `Ok(None)` if the peer did not present a chain. See `peer_cert_chain` for which
+    /// certificates are included.
+    pub fn peer_cert_chain_der(&self) -> Result<Option<Vec<Vec<u8>>>, ErrorStack> {
+        match self.peer_cert_chain() {
+            Some(chain) => chain
+                .iter()
+                .map(|cert| cert.to_der())
+                .collect::<Result<_, _>>()
+                .map(Some),
+            None => Ok(None),
+        }
     }
 
-    /// Returns a new extra data index.
+    /// Returns the peer's certificate chain as a concatenated PEM bundle.
     ///
-    /// Each invocation of this function is guaranteed to return a distinct index. These can be used
-    /// to store data in the context that can be retrieved later by callbacks, for example.
-    #[corresponds(SSL_CTX_get_ex_new_index)]
-    pub fn new_ex_index<T>() -> Result<Index<SslContext, T>, ErrorStack>
-    where
-        T: 'static + Sync + Send,
-    {
-        unsafe {
-            ffi::init();
-            #[cfg(boringssl)]
-            let idx = cvt_n(get_new_idx(Some(free_data_box::<T>)))?;
-            #[cfg(not(boringssl))]
-            let idx = cvt_n(get_new_idx(free_data_box::<T>))?;
-            Ok(Index::from_raw(idx))
+    /// Returns `Ok(None)` if the peer did not present a chain. See `peer_cert_chain` for which
+    /// certificates are included.
+    pub fn peer_cert_chain_pem(&self) -> Result<Option<Vec<u8>>, ErrorStack> {
+        let chain = match self.peer_cert_chain() {
+            Some(chain) => chain,
+            None => return Ok(None),
+        };
+
+        let mut pem = vec![];
+        for cert in chain {
+            pem.extend_from_slice(&cert.to_pem()?);
         }
+        Ok(Some(pem))
     }
 
-    // FIXME should return a result?
-    fn cached_ex_index<T>() -> Index<SslContext, T>
-    where
-        T: 'static + Sync + Send,
-    {
+    /// Returns the verified certificate chain of the peer, including the leaf certificate.
+    ///
+    /// If verification was not successful (i.e. [`verify_result`] does not return
+    /// [`X509VerifyResult::OK`]), this chain may be incomplete or invalid.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`verify_result`]: #method.verify_result
+    /// [`X509VerifyResult::OK`]: ../x509/struct.X509VerifyResult.html#associatedconstant.OK
+    #[corresponds(SSL_get0_verified_chain)]
+    #[cfg(ossl110)]
+    pub fn verified_chain(&self) -> Option<&StackRef<X509>> {
         unsafe {
-            let idx = *INDEXES
-                .lock()
-                .unwrap_or_else(|e| e.into_inner())
-                .entry(TypeId::of::<T>())
-                .or_insert_with(|| SslContext::new_ex_index::<T>().unwrap().as_raw());
-            Index::from_raw(idx)
+            let ptr = ffi::SSL_get0_verified_chain(self.as_ptr());
+            StackRef::from_const_ptr_opt(ptr)
         }
     }
-}
 
-impl SslContextRef {
-    /// Returns the certificate associated with this `SslContext`, if present.
-    ///
-    /// Requires OpenSSL 1.0.2 or newer.
-    #[corresponds(SSL_CTX_get0_certificate)]
-    #[cfg(any(ossl102, ossl110))]
+    /// Like [`SslContext::certificate`].
+    #[corresponds(SSL_get_certificate)]
     pub fn certificate(&self) -> Option<&X509Ref> {
         unsafe {
-            let ptr = ffi::SSL_CTX_get0_certificate(self.as_ptr());
+            let ptr = ffi::SSL_get_certificate(self.as_ptr());
             X509Ref::from_const_ptr_opt(ptr)
         }
     }
 
-    /// Returns the private key associated with this `SslContext`, if present.
+    /// Like [`SslContext::private_key`].
     ///
-    /// Requires OpenSSL 1.0.2 or newer.
-    #[corresponds(SSL_CTX_get0_privatekey)]
-    #[cfg(any(ossl102, ossl110))]
+    /// This corresponds to `SSL_get_privatekey`.
+    ///
+    /// [`SslContext::private_key`]: struct.SslContext.html#method.private_key
+    #[corresponds(SSL_get_certificate)]
     pub fn private_key(&self) -> Option<&PKeyRef<Private>> {
         unsafe {
-            let ptr = ffi::SSL_CTX_get0_privatekey(self.as_ptr());
+            let ptr = ffi::SSL_get_privatekey(self.as_ptr());
             PKeyRef::from_const_ptr_opt(ptr)
         }
     }
 
-    /// Returns a shared reference to the certificate store used for verification.
-    #[corresponds(SSL_CTX_get_cert_store)]
-    pub fn cert_store(&self) -> &X509StoreRef {
-        unsafe { X509StoreRef::from_ptr(ffi::SSL_CTX_get_cert_store(self.as_ptr())) }
+    /// Checks for consistency between the private key and certificate.
+    #[corresponds(SSL_check_private_key)]
+    pub fn check_private_key(&self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_check_private_key(self.as_ptr())).map(|_| ()) }
     }
 
-    /// Returns a shared reference to the stack of certificates making up the chain from the leaf.
-    #[corresponds(SSL_CTX_get_extra_chain_certs)]
-    pub fn extra_chain_certs(&self) -> &StackRef<X509> {
+    #[deprecated(since = "0.10.5", note = "renamed to `version_str`")]
+    pub fn version(&self) -> &str {
+        self.version_str()
+    }
+
+    /// Returns the protocol version of the session.
+    #[corresponds(SSL_version)]
+    pub fn version2(&self) -> Option<SslVersion> {
         unsafe {
-            let mut chain = ptr::null_mut();
-            ffi::SSL_CTX_get_extra_chain_certs(self.as_ptr(), &mut chain);
-            StackRef::from_const_ptr_opt(chain).expect("extra chain certs must not be null")
+            let r = ffi::SSL_version(self.as_ptr());
+            if r == 0 {
+                None
+            } else {
+                Some(SslVersion(r))
+            }
         }
     }
 
-    /// Returns a reference to the extra data at the specified index.
-    #[corresponds(SSL_CTX_get_ex_data)]
-    pub fn ex_data<T>(&self, index: Index<SslContext, T>) -> Option<&T> {
+    /// Returns a string describing the protocol version of the session.
+    #[corresponds(SSL_get_version)]
+    pub fn version_str(&self) -> &'static str {
+        let version = unsafe {
+            let ptr = ffi::SSL_get_version(self.as_ptr());
+            CStr::from_ptr(ptr as *const _)
+        };
+
+        str::from_utf8(version.to_bytes()).unwrap()
+    }
+
+    /// Returns the protocol selected via Application Layer Protocol Negotiation (ALPN).
+    ///
+    /// The protocol's name is returned is an opaque sequence of bytes. It is up to the client
+    /// to interpret it.
+    ///
+    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    #[corresponds(SSL_get0_alpn_selected)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn selected_alpn_protocol(&self) -> Option<&[u8]> {
         unsafe {
-            let data = ffi::SSL_CTX_get_ex_data(self.as_ptr(), index.as_raw());
+            let mut data: *const c_uchar = ptr::null();
+            let mut len: c_uint = 0;
+            // Get the negotiated protocol from the SSL instance.
+            // `data` will point at a `c_uchar` array; `len` will contain the length of this array.
+            ffi::SSL_get0_alpn_selected(self.as_ptr(), &mut data, &mut len);
+
             if data.is_null() {
                 None
             } else {
-                Some(&*(data as *const T))
+                Some(slice::from_raw_parts(data, len as usize))
             }
         }
     }
 
-    /// Gets the maximum amount of early data that will be accepted on incoming connections.
+    /// Configures the Application-Layer Protocol Settings (ALPS) sent for an ALPN protocol.
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_CTX_get_max_early_data)]
-    #[cfg(ossl111)]
-    pub fn max_early_data(&self) -> u32 {
-        unsafe { ffi::SSL_CTX_get_max_early_data(self.as_ptr()) }
+    /// If `protocol` is negotiated via ALPN, `settings` is sent to the peer in the handshake,
+    /// for example to carry HTTP/2 SETTINGS. ALPS is only negotiated if both sides configure
+    /// settings for the selected protocol, and requires TLSv1.3.
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_add_application_settings)]
+    #[cfg(boringssl)]
+    pub fn add_application_settings(
+        &mut self,
+        protocol: &[u8],
+        settings: &[u8],
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_add_application_settings(
+                self.as_ptr(),
+                protocol.as_ptr(),
+                protocol.len(),
+                settings.as_ptr(),
+                settings.len(),
+            ))
+            .map(|_| ())
+        }
     }
 
-    /// Adds a session to the context's cache.
-    ///
-    /// Returns `true` if the session was successfully added to the cache, and `false` if it was already present.
+    /// Determines whether the ALPS extension is sent with the new codepoint.
     ///
-    /// # Safety
+    /// Both peers must agree on the codepoint for ALPS to be negotiated.
     ///
-    /// The caller of this method is responsible for ensuring that the session has never been used with another
-    /// `SslContext` than this one.
-    #[corresponds(SSL_CTX_add_session)]
-    pub unsafe fn add_session(&self, session: &SslSessionRef) -> bool {
-        ffi::SSL_CTX_add_session(self.as_ptr(), session.as_ptr()) != 0
+    /// Requires BoringSSL.
+    #[corresponds(SSL_set_alps_use_new_codepoint)]
+    #[cfg(boringssl)]
+    pub fn set_alps_use_new_codepoint(&mut self, use_new: bool) {
+        unsafe { ffi::SSL_set_alps_use_new_codepoint(self.as_ptr(), use_new as c_int) }
     }
 
-    /// Removes a session from the context's cache and marks it as non-resumable.
-    ///
-    /// Returns `true` if the session was successfully found and removed, and `false` otherwise.
-    ///
-    /// # Safety
+    /// Returns `true` if Application-Layer Protocol Settings were negotiated.
     ///
-    /// The caller of this method is responsible for ensuring that the session has never been used with another
-    /// `SslContext` than this one.
-    #[corresponds(SSL_CTX_remove_session)]
-    pub unsafe fn remove_session(&self, session: &SslSessionRef) -> bool {
-        ffi::SSL_CTX_remove_session(self.as_ptr(), session.as_ptr()) != 0
+    /// Requires BoringSSL.
+    #[corresponds(SSL_has_application_settings)]
+    #[cfg(boringssl)]
+    pub fn has_application_settings(&self) -> bool {
+        unsafe { ffi::SSL_has_application_settings(self.as_ptr()) != 0 }
     }
 
-    /// Returns the context's session cache size limit.
+    /// Returns the Application-Layer Protocol Settings sent by the peer, if ALPS was negotiated.
     ///
-    /// A value of 0 means that the cache size is unbounded.
-    #[corresponds(SSL_CTX_sess_get_cache_size)]
-    #[allow(clippy::useless_conversion)]
-    pub fn session_cache_size(&self) -> i64 {
-        unsafe { ffi::SSL_CTX_sess_get_cache_size(self.as_ptr()) as c_long }
+    /// Requires BoringSSL.
+    #[corresponds(SSL_get0_peer_application_settings)]
+    #[cfg(boringssl)]
+    pub fn peer_application_settings(&self) -> Option<&[u8]> {
+        if !self.has_application_settings() {
+            return None;
+        }
+
+        unsafe {
+            let mut data = ptr::null();
+            let mut len = 0;
+            ffi::SSL_get0_peer_application_settings(self.as_ptr(), &mut data, &mut len);
+
+            if data.is_null() {
+                Some(&[])
+            } else {
+                Some(slice::from_raw_parts(data, len))
+            }
+        }
     }
 
-    /// Returns the verify mode that was set on this context from [`SslContextBuilder::set_verify`].
+    /// Enables the DTLS extension "use_srtp" as defined in RFC5764.
     ///
-    /// [`SslContextBuilder::set_verify`]: struct.SslContextBuilder.html#method.set_verify
-    #[corresponds(SSL_CTX_get_verify_mode)]
-    pub fn verify_mode(&self) -> SslVerifyMode {
-        let mode = unsafe { ffi::SSL_CTX_get_verify_mode(self.as_ptr()) };
-        SslVerifyMode::from_bits(mode).expect("SSL_CTX_get_verify_mode returned invalid mode")
+    /// This corresponds to [`SSL_set_tlsext_use_srtp`].
+    ///
+    /// [`SSL_set_tlsext_use_srtp`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_tlsext_use_srtp.html
+    #[corresponds(SSL_set_tlsext_use_srtp)]
+    pub fn set_tlsext_use_srtp(&mut self, protocols: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            let cstr = CString::new(protocols).unwrap();
+
+            let r = ffi::SSL_set_tlsext_use_srtp(self.as_ptr(), cstr.as_ptr());
+            // fun fact, set_tlsext_use_srtp has a reversed return code D:
+            if r == 0 {
+                Ok(())
+            } else {
+                Err(ErrorStack::get())
+            }
+        }
     }
-}
 
-/// Information about the state of a cipher.
-pub struct CipherBits {
-    /// The number of secret bits used for the cipher.
-    pub secret: i32,
+    /// Gets all SRTP profiles that are enabled for handshake via set_tlsext_use_srtp
+    ///
+    /// DTLS extension "use_srtp" as defined in RFC5764 has to be enabled.
+    ///
+    /// This corresponds to [`SSL_get_srtp_profiles`].
+    ///
+    /// [`SSL_get_srtp_profiles`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_tlsext_use_srtp.html
+    #[corresponds(SSL_get_srtp_profiles)]
+    pub fn srtp_profiles(&self) -> Option<&StackRef<SrtpProtectionProfile>> {
+        unsafe {
+            let chain = ffi::SSL_get_srtp_profiles(self.as_ptr());
 
-    /// The number of bits processed by the chosen algorithm.
-    pub algorithm: i32,
-}
+            StackRef::from_const_ptr_opt(chain)
+        }
+    }
 
-/// Information about a cipher.
-pub struct SslCipher(*mut ffi::SSL_CIPHER);
+    /// Gets the SRTP profile selected by handshake.
+    ///
+    /// DTLS extension "use_srtp" as defined in RFC5764 has to be enabled.
+    #[corresponds(SSL_get_selected_srtp_profile)]
+    pub fn selected_srtp_profile(&self) -> Option<&SrtpProtectionProfileRef> {
+        unsafe {
+            let profile = ffi::SSL_get_selected_srtp_profile(self.as_ptr());
 
-impl ForeignType for SslCipher {
-    type CType = ffi::SSL_CIPHER;
-    type Ref = SslCipherRef;
+            SrtpProtectionProfileRef::from_const_ptr_opt(profile)
+        }
+    }
 
-    #[inline]
-    unsafe fn from_ptr(ptr: *mut ffi::SSL_CIPHER) -> SslCipher {
-        SslCipher(ptr)
+    /// Derives the SRTP master keys and salts for the selected SRTP profile, as described in
+    /// RFC 5764.
+    ///
+    /// Returns `Ok(None)` if no SRTP profile was selected, or if the key and salt lengths of the
+    /// selected profile are not known.
+    pub fn srtp_keying_material(&self) -> Result<Option<SrtpKeyingMaterial>, ErrorStack> {
+        let profile = match self.selected_srtp_profile() {
+            Some(profile) => profile.id(),
+            None => return Ok(None),
+        };
+        let (key_len, salt_len) = match (profile.master_key_len(), profile.master_salt_len()) {
+            (Some(key_len), Some(salt_len)) => (key_len, salt_len),
+            _ => return Ok(None),
+        };
+
+        let mut material = vec![0; 2 * (key_len + salt_len)];
+        self.export_keying_material(&mut material, "EXTRACTOR-dtls_srtp", None)?;
+        Ok(Some(SrtpKeyingMaterial::new(
+            profile,
+            self.is_server(),
+            material,
+            key_len,
+            salt_len,
+        )))
     }
 
-    #[inline]
-    fn as_ptr(&self) -> *mut ffi::SSL_CIPHER {
-        self.0
+    /// Returns the number of bytes remaining in the currently processed TLS record.
+    ///
+    /// If this is greater than 0, the next call to `read` will not call down to the underlying
+    /// stream.
+    #[corresponds(SSL_pending)]
+    pub fn pending(&self) -> usize {
+        unsafe { ffi::SSL_pending(self.as_ptr()) as usize }
     }
-}
 
-impl Deref for SslCipher {
-    type Target = SslCipherRef;
+    /// Determines if the connection has buffered data which has not yet been returned by `read`.
+    ///
+    /// Unlike [`pending`], this includes records which have been read from the underlying stream
+    /// but not yet decrypted, so a `true` value means that a `read` may make progress without
+    /// the stream being readable. Event loops should drain the connection while this returns
+    /// `true` before waiting for readability.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`pending`]: SslRef::pending
+    #[corresponds(SSL_has_pending)]
+    #[cfg(ossl110)]
+    pub fn has_pending(&self) -> bool {
+        unsafe { ffi::SSL_has_pending(self.as_ptr()) != 0 }
+    }
 
-    fn deref(&self) -> &SslCipherRef {
-        unsafe { SslCipherRef::from_ptr(self.0) }
+    /// Returns the total number of bytes of TLS records read from the underlying stream.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(BIO_number_read)]
+    #[cfg(ossl110)]
+    pub fn bytes_read(&self) -> u64 {
+        unsafe {
+            let bio = ffi::SSL_get_rbio(self.as_ptr());
+            if bio.is_null() {
+                0
+            } else {
+                ffi::BIO_number_read(bio)
+            }
+        }
     }
-}
 
-impl DerefMut for SslCipher {
-    fn deref_mut(&mut self) -> &mut SslCipherRef {
-        unsafe { SslCipherRef::from_ptr_mut(self.0) }
+    /// Returns the total number of bytes of TLS records written to the underlying stream.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(BIO_number_written)]
+    #[cfg(ossl110)]
+    pub fn bytes_written(&self) -> u64 {
+        unsafe {
+            let bio = ffi::SSL_get_wbio(self.as_ptr());
+            if bio.is_null() {
+                0
+            } else {
+                ffi::BIO_number_written(bio)
+            }
+        }
     }
-}
 
-/// Reference to an [`SslCipher`].
-///
-/// [`SslCipher`]: struct.SslCipher.html
-pub struct SslCipherRef(Opaque);
+    /// Returns the sequence number of the next record to be read, which is the number of records
+    /// read in the current epoch.
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_get_read_sequence)]
+    #[cfg(boringssl)]
+    pub fn read_sequence(&self) -> u64 {
+        unsafe { ffi::SSL_get_read_sequence(self.as_ptr()) }
+    }
 
-impl ForeignTypeRef for SslCipherRef {
-    type CType = ffi::SSL_CIPHER;
-}
+    /// Returns the sequence number of the next record to be written, which is the number of
+    /// records written in the current epoch.
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_get_write_sequence)]
+    #[cfg(boringssl)]
+    pub fn write_sequence(&self) -> u64 {
+        unsafe { ffi::SSL_get_write_sequence(self.as_ptr()) }
+    }
 
-impl SslCipherRef {
-    /// Returns the name of the cipher.
-    #[corresponds(SSL_CIPHER_get_name)]
-    pub fn name(&self) -> &'static str {
-        unsafe {
-            let ptr = ffi::SSL_CIPHER_get_name(self.as_ptr());
-            CStr::from_ptr(ptr).to_str().unwrap()
-        }
+    /// Returns the servername sent by the client via Server Name Indication (SNI).
+    ///
+    /// It is only useful on the server side.
+    ///
+    /// # Note
+    ///
+    /// While the SNI specification requires that servernames be valid domain names (and therefore
+    /// ASCII), OpenSSL does not enforce this restriction. If the servername provided by the client
+    /// is not valid UTF-8, this function will return `None`. The `servername_raw` method returns
+    /// the raw bytes and does not have this restriction.
+    ///
+    /// [`SSL_get_servername`]: https://www.openssl.org/docs/manmaster/man3/SSL_get_servername.html
+    #[corresponds(SSL_get_servername)]
+    // FIXME maybe rethink in 0.11?
+    pub fn servername(&self, type_: NameType) -> Option<&str> {
+        self.servername_raw(type_)
+            .and_then(|b| str::from_utf8(b).ok())
     }
 
-    /// Returns the RFC-standard name of the cipher, if one exists.
+    /// Returns the servername sent by the client via Server Name Indication (SNI).
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_CIPHER_standard_name)]
-    #[cfg(ossl111)]
-    pub fn standard_name(&self) -> Option<&'static str> {
+    /// It is only useful on the server side.
+    ///
+    /// # Note
+    ///
+    /// Unlike `servername`, this method does not require the name be valid UTF-8.
+    #[corresponds(SSL_get_servername)]
+    pub fn servername_raw(&self, type_: NameType) -> Option<&[u8]> {
         unsafe {
-            let ptr = ffi::SSL_CIPHER_standard_name(self.as_ptr());
-            if ptr.is_null() {
+            let name = ffi::SSL_get_servername(self.as_ptr(), type_.0);
+            if name.is_null() {
                 None
             } else {
-                Some(CStr::from_ptr(ptr).to_str().unwrap())
+                Some(CStr::from_ptr(name as *const _).to_bytes())
             }
         }
     }
 
-    /// Returns the SSL/TLS protocol version that first defined the cipher.
-    #[corresponds(SSL_CIPHER_get_version)]
-    pub fn version(&self) -> &'static str {
-        let version = unsafe {
-            let ptr = ffi::SSL_CIPHER_get_version(self.as_ptr());
-            CStr::from_ptr(ptr as *const _)
-        };
-
-        str::from_utf8(version.to_bytes()).unwrap()
+    /// Like [`SslContextBuilder::set_psk_identity_hint`].
+    #[corresponds(SSL_use_psk_identity_hint)]
+    #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
+    pub fn set_psk_identity_hint(&mut self, hint: &str) -> Result<(), ErrorStack> {
+        let hint = CString::new(hint).unwrap();
+        unsafe { cvt(ffi::SSL_use_psk_identity_hint(self.as_ptr(), hint.as_ptr())).map(|_| ()) }
     }
 
-    /// Returns the number of bits used for the cipher.
-    #[corresponds(SSL_CIPHER_get_bits)]
-    #[allow(clippy::useless_conversion)]
-    pub fn bits(&self) -> CipherBits {
+    /// Returns the PSK identity hint sent by the server, if any.
+    #[corresponds(SSL_get_psk_identity_hint)]
+    #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
+    pub fn psk_identity_hint(&self) -> Option<&[u8]> {
         unsafe {
-            let mut algo_bits = 0;
-            let secret_bits = ffi::SSL_CIPHER_get_bits(self.as_ptr(), &mut algo_bits);
-            CipherBits {
-                secret: secret_bits.into(),
-                algorithm: algo_bits.into(),
+            let hint = ffi::SSL_get_psk_identity_hint(self.as_ptr());
+            if hint.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(hint).to_bytes())
             }
         }
     }
 
-    /// Returns a textual description of the cipher.
-    #[corresponds(SSL_CIPHER_description)]
-    pub fn description(&self) -> String {
+    /// Returns the identity the client used in a TLS-PSK handshake, if any.
+    #[corresponds(SSL_get_psk_identity)]
+    #[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
+    pub fn psk_identity(&self) -> Option<&[u8]> {
         unsafe {
-            // SSL_CIPHER_description requires a buffer of at least 128 bytes.
-            let mut buf = [0; 128];
-            let ptr = ffi::SSL_CIPHER_description(self.as_ptr(), buf.as_mut_ptr(), 128);
-            String::from_utf8(CStr::from_ptr(ptr as *const _).to_bytes().to_vec()).unwrap()
+            let identity = ffi::SSL_get_psk_identity(self.as_ptr());
+            if identity.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(identity).to_bytes())
+            }
         }
     }
 
-    /// Returns the handshake digest of the cipher.
-    ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_CIPHER_get_handshake_digest)]
-    #[cfg(ossl111)]
-    pub fn handshake_digest(&self) -> Option<MessageDigest> {
+    /// Returns the username sent by a TLS-SRP client, if any.
+    #[corresponds(SSL_get_srp_username)]
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+    pub fn srp_username(&self) -> Option<&str> {
         unsafe {
-            let ptr = ffi::SSL_CIPHER_get_handshake_digest(self.as_ptr());
-            if ptr.is_null() {
+            let username = ffi::SSL_get_srp_username(self.as_ptr());
+            if username.is_null() {
                 None
             } else {
-                Some(MessageDigest::from_ptr(ptr))
+                str::from_utf8(CStr::from_ptr(username).to_bytes()).ok()
             }
         }
     }
 
-    /// Returns the NID corresponding to the cipher.
+    /// Sets the group and verifier a TLS-SRP server uses to authenticate the client.
     ///
-    /// Requires OpenSSL 1.1.0 or newer.
-    #[corresponds(SSL_CIPHER_get_cipher_nid)]
-    #[cfg(any(ossl110))]
-    pub fn cipher_nid(&self) -> Option<Nid> {
-        let n = unsafe { ffi::SSL_CIPHER_get_cipher_nid(self.as_ptr()) };
-        if n == 0 {
-            None
-        } else {
-            Some(Nid::from_raw(n))
+    /// This should be called from the callback registered with
+    /// [`SslContextBuilder::set_srp_username_callback`].
+    #[corresponds(SSL_set_srp_server_param)]
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+    pub fn set_srp_server_param(
+        &mut self,
+        group: &SrpGroup,
+        verifier: &SrpVerifier,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_set_srp_server_param(
+                self.as_ptr(),
+                group.n().as_ptr(),
+                group.g().as_ptr(),
+                verifier.salt().as_ptr(),
+                verifier.verifier().as_ptr(),
+                ptr::null_mut(),
+            ))
+            .map(|_| ())
         }
     }
-}
-
-foreign_type_and_impl_send_sync! {
-    type CType = ffi::SSL_SESSION;
-    fn drop = ffi::SSL_SESSION_free;
 
-    /// An encoded SSL session.
+    /// Changes the context corresponding to the current connection.
     ///
-    /// These can be cached to share sessions across connections.
-    pub struct SslSession;
+    /// It is most commonly used in the Server Name Indication (SNI) callback.
+    #[corresponds(SSL_set_SSL_CTX)]
+    pub fn set_ssl_context(&mut self, ctx: &SslContextRef) -> Result<(), ErrorStack> {
+        unsafe { cvt_p(ffi::SSL_set_SSL_CTX(self.as_ptr(), ctx.as_ptr())).map(|_| ()) }
+    }
 
-    /// Reference to [`SslSession`].
-    ///
-    /// [`SslSession`]: struct.SslSession.html
-    pub struct SslSessionRef;
-}
+    /// Returns the context corresponding to the current connection.
+    #[corresponds(SSL_get_SSL_CTX)]
+    pub fn ssl_context(&self) -> &SslContextRef {
+        unsafe {
+            let ssl_ctx = ffi::SSL_get_SSL_CTX(self.as_ptr());
+            SslContextRef::from_ptr(ssl_ctx)
+        }
+    }
 
-impl Clone for SslSession {
-    fn clone(&self) -> SslSession {
-        SslSessionRef::to_owned(self)
+    /// Sets the leaf certificate for this connection, overriding the one set on the context.
+    ///
+    /// Use `add_chain_cert` to add the remainder of the certificate chain.
+    #[corresponds(SSL_use_certificate)]
+    pub fn set_certificate(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_use_certificate(self.as_ptr(), cert.as_ptr())).map(|_| ()) }
     }
-}
 
-impl SslSession {
-    from_der! {
-        /// Deserializes a DER-encoded session structure.
-        #[corresponds(d2i_SSL_SESSION)]
-        from_der,
-        SslSession,
-        ffi::d2i_SSL_SESSION
+    /// Sets the private key for this connection, overriding the one set on the context.
+    #[corresponds(SSL_use_PrivateKey)]
+    pub fn set_private_key<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe { cvt(ffi::SSL_use_PrivateKey(self.as_ptr(), key.as_ptr())).map(|_| ()) }
     }
-}
 
-impl ToOwned for SslSessionRef {
-    type Owned = SslSession;
+    /// Appends a certificate to the certificate chain of the current leaf certificate of this
+    /// connection.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_add1_chain_cert)]
+    #[cfg(ossl102)]
+    pub fn add_chain_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_add1_chain_cert(self.as_ptr(), cert.as_ptr()) as c_int).map(|_| ()) }
+    }
 
-    fn to_owned(&self) -> SslSession {
-        unsafe {
-            SSL_SESSION_up_ref(self.as_ptr());
-            SslSession(self.as_ptr())
-        }
+    /// Removes all certificates from the certificate chain of the current leaf certificate of
+    /// this connection.
+    ///
+    /// A chain inherited from the context is otherwise kept when the leaf certificate is replaced
+    /// with `set_certificate`.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_set0_chain)]
+    #[cfg(ossl102)]
+    pub fn clear_chain_certs(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set0_chain(self.as_ptr(), ptr::null_mut()) as c_int).map(|_| ()) }
     }
-}
 
-impl SslSessionRef {
-    /// Returns the SSL session ID.
-    #[corresponds(SSL_SESSION_get_id)]
-    pub fn id(&self) -> &[u8] {
+    /// Like [`SslContextRef::chain_certs`].
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_get0_chain_certs)]
+    #[cfg(ossl102)]
+    pub fn chain_certs(&self) -> Option<&StackRef<X509>> {
         unsafe {
-            let mut len = 0;
-            let p = ffi::SSL_SESSION_get_id(self.as_ptr(), &mut len);
-            slice::from_raw_parts(p as *const u8, len as usize)
+            let mut chain = ptr::null_mut();
+            ffi::SSL_get0_chain_certs(self.as_ptr(), &mut chain);
+            StackRef::from_const_ptr_opt(chain)
         }
     }
 
-    /// Returns the length of the master key.
-    #[corresponds(SSL_SESSION_get_master_key)]
-    pub fn master_key_len(&self) -> usize {
-        unsafe { SSL_SESSION_get_master_key(self.as_ptr(), ptr::null_mut(), 0) }
-    }
-
-    /// Copies the master key into the provided buffer.
+    /// Returns a mutable reference to the X509 verification configuration.
     ///
-    /// Returns the number of bytes written, or the size of the master key if the buffer is empty.
-    #[corresponds(SSL_SESSION_get_master_key)]
-    pub fn master_key(&self, buf: &mut [u8]) -> usize {
-        unsafe { SSL_SESSION_get_master_key(self.as_ptr(), buf.as_mut_ptr(), buf.len()) }
+    /// Requires OpenSSL 1.0.2 or newer.
+    #[corresponds(SSL_get0_param)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn param_mut(&mut self) -> &mut X509VerifyParamRef {
+        unsafe { X509VerifyParamRef::from_ptr_mut(ffi::SSL_get0_param(self.as_ptr())) }
     }
 
-    /// Gets the maximum amount of early data that can be sent on this session.
+    /// Enables DANE verification of the peer's certificate chain.
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_SESSION_get_max_early_data)]
-    #[cfg(ossl111)]
-    pub fn max_early_data(&self) -> u32 {
-        unsafe { ffi::SSL_SESSION_get_max_early_data(self.as_ptr()) }
+    /// `base_domain` is the TLSA base domain, which is used as the reference identifier for name
+    /// checks and, on clients, as the SNI hostname if one has not already been set. The context
+    /// must have had DANE enabled with [`SslContextBuilder::dane_enable`].
+    ///
+    /// Verification fails unless at least one usable TLSA record is added with
+    /// [`SslRef::dane_tlsa_add`]. The verify mode must also include [`SslVerifyMode::PEER`] for
+    /// a failed match to abort the handshake.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_dane_enable)]
+    #[cfg(ossl110)]
+    pub fn dane_enable(&mut self, base_domain: &str) -> Result<(), ErrorStack> {
+        let base_domain = CString::new(base_domain).unwrap();
+        unsafe { cvt(ffi::SSL_dane_enable(self.as_ptr(), base_domain.as_ptr())).map(|_| ()) }
     }
 
-    /// Returns the time at which the session was established, in seconds since the Unix epoch.
-    #[corresponds(SSL_SESSION_get_time)]
-    #[allow(clippy::useless_conversion)]
-    pub fn time(&self) -> SslTimeTy {
-        unsafe { ffi::SSL_SESSION_get_time(self.as_ptr()) }
+    /// Adds a TLSA record to match against the peer's certificate chain.
+    ///
+    /// Returns `false` if the record is well-formed but unusable, for example because its
+    /// matching type is not supported, in which case it is ignored as RFC 6698 requires.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_dane_tlsa_add)]
+    #[cfg(ossl110)]
+    pub fn dane_tlsa_add(
+        &mut self,
+        usage: TlsaUsage,
+        selector: TlsaSelector,
+        matching_type: TlsaMatchingType,
+        data: &[u8],
+    ) -> Result<bool, ErrorStack> {
+        unsafe {
+            cvt_n(ffi::SSL_dane_tlsa_add(
+                self.as_ptr(),
+                usage.as_raw(),
+                selector.as_raw(),
+                matching_type.as_raw(),
+                data.as_ptr(),
+                data.len(),
+            ))
+            .map(|r| r > 0)
+        }
     }
 
-    /// Returns the sessions timeout, in seconds.
+    /// Sets flags controlling DANE verification of this connection.
     ///
-    /// A session older than this time should not be used for session resumption.
-    #[corresponds(SSL_SESSION_get_timeout)]
-    #[allow(clippy::useless_conversion)]
-    pub fn timeout(&self) -> i64 {
-        unsafe { ffi::SSL_SESSION_get_timeout(self.as_ptr()).into() }
+    /// Returns the previous flags.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_dane_set_flags)]
+    #[cfg(ossl110)]
+    pub fn set_dane_flags(&mut self, flags: DaneFlags) -> DaneFlags {
+        unsafe {
+            DaneFlags::from_bits_truncate(ffi::SSL_dane_set_flags(self.as_ptr(), flags.bits()))
+        }
     }
 
-    /// Returns the session's TLS protocol version.
+    /// Clears flags controlling DANE verification of this connection.
+    ///
+    /// Returns the previous flags.
     ///
     /// Requires OpenSSL 1.1.0 or newer.
-    #[corresponds(SSL_SESSION_get_protocol_version)]
+    #[corresponds(SSL_dane_clear_flags)]
     #[cfg(ossl110)]
-    pub fn protocol_version(&self) -> SslVersion {
+    pub fn clear_dane_flags(&mut self, flags: DaneFlags) -> DaneFlags {
         unsafe {
-            let version = ffi::SSL_SESSION_get_protocol_version(self.as_ptr());
-            SslVersion(version)
+            DaneFlags::from_bits_truncate(ffi::SSL_dane_clear_flags(self.as_ptr(), flags.bits()))
         }
     }
 
-    to_der! {
-        /// Serializes the session into a DER-encoded structure.
-        #[corresponds(i2d_SSL_SESSION)]
-        to_der,
-        ffi::i2d_SSL_SESSION
-    }
-}
+    /// Returns the certificate or public key which matched a TLSA record during verification.
+    ///
+    /// Returns `None` if DANE is not enabled or no TLSA record matched.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_get0_dane_authority)]
+    #[cfg(ossl110)]
+    pub fn dane_authority(&self) -> Option<DaneAuthority<'_>> {
+        unsafe {
+            let mut cert = ptr::null_mut();
+            let mut spki = ptr::null_mut();
+            let depth = ffi::SSL_get0_dane_authority(self.as_ptr(), &mut cert, &mut spki);
+            if depth < 0 {
+                return None;
+            }
 
-foreign_type_and_impl_send_sync! {
-    type CType = ffi::SSL;
-    fn drop = ffi::SSL_free;
+            Some(DaneAuthority {
+                depth: depth as u32,
+                certificate: X509Ref::from_const_ptr_opt(cert),
+                public_key: PKeyRef::from_const_ptr_opt(spki),
+            })
+        }
+    }
 
-    /// The state of an SSL/TLS session.
+    /// Returns the TLSA record which matched the peer's certificate chain during verification.
     ///
-    /// `Ssl` objects are created from an [`SslContext`], which provides configuration defaults.
-    /// These defaults can be overridden on a per-`Ssl` basis, however.
+    /// Returns `None` if DANE is not enabled or no TLSA record matched.
     ///
-    /// [`SslContext`]: struct.SslContext.html
-    pub struct Ssl;
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_get0_dane_tlsa)]
+    #[cfg(ossl110)]
+    pub fn dane_tlsa(&self) -> Option<DaneTlsa<'_>> {
+        unsafe {
+            let mut usage = 0;
+            let mut selector = 0;
+            let mut mtype = 0;
+            let mut data = ptr::null();
+            let mut len = 0;
+            let depth = ffi::SSL_get0_dane_tlsa(
+                self.as_ptr(),
+                &mut usage,
+                &mut selector,
+                &mut mtype,
+                &mut data,
+                &mut len,
+            );
+            if depth < 0 {
+                return None;
+            }
 
-    /// Reference to an [`Ssl`].
-    ///
-    /// [`Ssl`]: struct.Ssl.html
-    pub struct SslRef;
-}
+            Some(DaneTlsa {
+                depth: depth as u32,
+                usage: TlsaUsage::from_raw(usage),
+                selector: TlsaSelector::from_raw(selector),
+                matching_type: TlsaMatchingType::from_raw(mtype),
+                data: slice::from_raw_parts(data, len),
+            })
+        }
+    }
 
-impl fmt::Debug for Ssl {
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(&**self, fmt)
+    /// Returns the certificate verification result.
+    #[corresponds(SSL_get_verify_result)]
+    pub fn verify_result(&self) -> X509VerifyResult {
+        unsafe { X509VerifyResult::from_raw(ffi::SSL_get_verify_result(self.as_ptr()) as c_int) }
     }
-}
 
-impl Ssl {
-    /// Returns a new extra data index.
-    ///
-    /// Each invocation of this function is guaranteed to return a distinct index. These can be used
-    /// to store data in the context that can be retrieved later by callbacks, for example.
-    #[corresponds(SSL_get_ex_new_index)]
-    pub fn new_ex_index<T>() -> Result<Index<Ssl, T>, ErrorStack>
-    where
-        T: 'static + Sync + Send,
-    {
+    /// Returns a shared reference to the SSL session.
+    #[corresponds(SSL_get_session)]
+    pub fn session(&self) -> Option<&SslSessionRef> {
         unsafe {
-            ffi::init();
-            #[cfg(boringssl)]
-            let idx = cvt_n(get_new_ssl_idx(Some(free_data_box::<T>)))?;
-            #[cfg(not(boringssl))]
-            let idx = cvt_n(get_new_ssl_idx(free_data_box::<T>))?;
-            Ok(Index::from_raw(idx))
+            let p = ffi::SSL_get_session(self.as_ptr());
+            SslSessionRef::from_const_ptr_opt(p)
         }
     }
 
-    // FIXME should return a result?
-    fn cached_ex_index<T>() -> Index<Ssl, T>
-    where
-        T: 'static + Sync + Send,
-    {
+    /// Copies the `client_random` value sent by the client in the TLS handshake into a buffer.
+    ///
+    /// Returns the number of bytes copied, or if the buffer is empty, the size of the `client_random`
+    /// value.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_get_client_random)]
+    #[cfg(any(ossl110))]
+    pub fn client_random(&self, buf: &mut [u8]) -> usize {
         unsafe {
-            let idx = *SSL_INDEXES
-                .lock()
-                .unwrap_or_else(|e| e.into_inner())
-                .entry(TypeId::of::<T>())
-                .or_insert_with(|| Ssl::new_ex_index::<T>().unwrap().as_raw());
-            Index::from_raw(idx)
+            ffi::SSL_get_client_random(self.as_ptr(), buf.as_mut_ptr() as *mut c_uchar, buf.len())
         }
     }
 
-    /// Creates a new `Ssl`.
+    /// Copies the `server_random` value sent by the server in the TLS handshake into a buffer.
     ///
-    /// This corresponds to [`SSL_new`].
+    /// Returns the number of bytes copied, or if the buffer is empty, the size of the `server_random`
+    /// value.
     ///
-    /// [`SSL_new`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_new.html
-    #[corresponds(SSL_new)]
-    pub fn new(ctx: &SslContextRef) -> Result<Ssl, ErrorStack> {
-        let session_ctx_index = try_get_session_ctx_index()?;
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_get_server_random)]
+    #[cfg(any(ossl110))]
+    pub fn server_random(&self, buf: &mut [u8]) -> usize {
         unsafe {
-            let ptr = cvt_p(ffi::SSL_new(ctx.as_ptr()))?;
-            let mut ssl = Ssl::from_ptr(ptr);
-            ssl.set_ex_data(*session_ctx_index, ctx.to_owned());
+            ffi::SSL_get_server_random(self.as_ptr(), buf.as_mut_ptr() as *mut c_uchar, buf.len())
+        }
+    }
 
-            Ok(ssl)
+    /// Derives keying material for application use in accordance to RFC 5705.
+    #[corresponds(SSL_export_keying_material)]
+    pub fn export_keying_material(
+        &self,
+        out: &mut [u8],
+        label: &str,
+        context: Option<&[u8]>,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            let (context, contextlen, use_context) = match context {
+                Some(context) => (context.as_ptr() as *const c_uchar, context.len(), 1),
+                None => (ptr::null(), 0, 0),
+            };
+            cvt(ffi::SSL_export_keying_material(
+                self.as_ptr(),
+                out.as_mut_ptr() as *mut c_uchar,
+                out.len(),
+                label.as_ptr() as *const c_char,
+                label.len(),
+                context,
+                contextlen,
+                use_context,
+            ))
+            .map(|_| ())
         }
     }
 
-    /// Initiates a client-side TLS handshake.
+    /// Like `export_keying_material`, but returns `len` bytes of keying material in a newly
+    /// created buffer.
     ///
-    /// This corresponds to [`SSL_connect`].
+    /// Returns an error if `len` is 0.
     ///
-    /// # Warning
+    /// ```no_run
+    /// # use openssl::ssl::SslRef;
+    /// # fn f(ssl: &SslRef) -> Result<(), openssl::error::ErrorStack> {
+    /// let key = ssl.keying_material(32, "EXPORTER-example", Some(b"context"))?;
+    /// # Ok(()) }
+    /// ```
+    #[corresponds(SSL_export_keying_material)]
+    pub fn keying_material(
+        &self,
+        len: usize,
+        label: &str,
+        context: Option<&[u8]>,
+    ) -> Result<Vec<u8>, ErrorStack> {
+        self.keying_material_into(vec![0; len], label, context)
+    }
+
+    /// Like `export_keying_material`, but fills and returns `out`, which allows the size of the
+    /// keying material to be fixed by its type.
     ///
-    /// OpenSSL's default configuration is insecure. It is highly recommended to use
-    /// `SslConnector` rather than `Ssl` directly, as it manages that configuration.
+    /// Returns an error if `out` is empty.
     ///
-    /// [`SSL_connect`]: https://www.openssl.org/docs/manmaster/man3/SSL_connect.html
-    #[corresponds(SSL_connect)]
-    #[allow(deprecated)]
-    pub fn connect<S>(self, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
+    /// ```no_run
+    /// # use openssl::ssl::SslRef;
+    /// # fn f(ssl: &SslRef) -> Result<(), openssl::error::ErrorStack> {
+    /// let key: [u8; 32] = ssl.keying_material_into([0; 32], "EXPORTER-example", None)?;
+    /// # Ok(()) }
+    /// ```
+    #[corresponds(SSL_export_keying_material)]
+    pub fn keying_material_into<T>(
+        &self,
+        mut out: T,
+        label: &str,
+        context: Option<&[u8]>,
+    ) -> Result<T, ErrorStack>
     where
-        S: Read + Write,
+        T: AsMut<[u8]>,
     {
-        SslStreamBuilder::new(self, stream).connect()
+        if out.as_mut().is_empty() {
+            return Err(bad_length());
+        }
+        self.export_keying_material(out.as_mut(), label, context)?;
+        Ok(out)
     }
 
-    /// Initiates a server-side TLS handshake.
-    ///
-    /// This corresponds to [`SSL_accept`].
+    /// Derives keying material for application use in accordance to RFC 5705 from the early
+    /// exporter master secret.
     ///
-    /// # Warning
+    /// The early exporter master secret is derived from the PSK, so values exported by the client
+    /// while sending 0-RTT data match those exported by the server once it accepts that data.
     ///
-    /// OpenSSL's default configuration is insecure. It is highly recommended to use
-    /// `SslAcceptor` rather than `Ssl` directly, as it manages that configuration.
+    /// This function is only usable with TLSv1.3, wherein there is no distinction between an empty context and no
+    /// context. Therefore, unlike `export_keying_material`, `context` must always be supplied.
     ///
-    /// [`SSL_accept`]: https://www.openssl.org/docs/manmaster/man3/SSL_accept.html
-    #[corresponds(SSL_accept)]
-    #[allow(deprecated)]
-    pub fn accept<S>(self, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
-    where
-        S: Read + Write,
-    {
-        SslStreamBuilder::new(self, stream).accept()
-    }
-}
-
-impl fmt::Debug for SslRef {
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt.debug_struct("Ssl")
-            .field("state", &self.state_string_long())
-            .field("verify_result", &self.verify_result())
-            .finish()
-    }
-}
-
-impl SslRef {
-    fn get_raw_rbio(&self) -> *mut ffi::BIO {
-        unsafe { ffi::SSL_get_rbio(self.as_ptr()) }
-    }
-
-    fn read(&mut self, buf: &mut [u8]) -> c_int {
-        let len = cmp::min(c_int::max_value() as usize, buf.len()) as c_int;
-        unsafe { ffi::SSL_read(self.as_ptr(), buf.as_ptr() as *mut c_void, len) }
-    }
-
-    fn peek(&mut self, buf: &mut [u8]) -> c_int {
-        let len = cmp::min(c_int::max_value() as usize, buf.len()) as c_int;
-        unsafe { ffi::SSL_peek(self.as_ptr(), buf.as_ptr() as *mut c_void, len) }
-    }
-
-    fn write(&mut self, buf: &[u8]) -> c_int {
-        let len = cmp::min(c_int::max_value() as usize, buf.len()) as c_int;
-        unsafe { ffi::SSL_write(self.as_ptr(), buf.as_ptr() as *const c_void, len) }
-    }
-
-    fn get_error(&self, ret: c_int) -> ErrorCode {
-        unsafe { ErrorCode::from_raw(ffi::SSL_get_error(self.as_ptr(), ret)) }
-    }
-
-    /// Configure as an outgoing stream from a client.
-    #[corresponds(SSL_set_connect_state)]
-    pub fn set_connect_state(&mut self) {
-        unsafe { ffi::SSL_set_connect_state(self.as_ptr()) }
-    }
-
-    /// Configure as an incoming stream to a server.
-    #[corresponds(SSL_set_accept_state)]
-    pub fn set_accept_state(&mut self) {
-        unsafe { ffi::SSL_set_accept_state(self.as_ptr()) }
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_export_keying_material_early)]
+    #[cfg(ossl111)]
+    pub fn export_keying_material_early(
+        &self,
+        out: &mut [u8],
+        label: &str,
+        context: &[u8],
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_export_keying_material_early(
+                self.as_ptr(),
+                out.as_mut_ptr() as *mut c_uchar,
+                out.len(),
+                label.as_ptr() as *const c_char,
+                label.len(),
+                context.as_ptr() as *const c_uchar,
+                context.len(),
+            ))
+            .map(|_| ())
+        }
     }
 
-    /// Like [`SslContextBuilder::set_verify`].
+    /// Like `export_keying_material_early`, but returns `len` bytes of keying material in a newly
+    /// created buffer.
     ///
-    /// [`SslContextBuilder::set_verify`]: struct.SslContextBuilder.html#method.set_verify
-    #[corresponds(SSL_set_verify)]
-    pub fn set_verify(&mut self, mode: SslVerifyMode) {
-        unsafe { ffi::SSL_set_verify(self.as_ptr(), mode.bits as c_int, None) }
-    }
-
-    /// Returns the verify mode that was set using `set_verify`.
-    #[corresponds(SSL_set_verify_mode)]
-    pub fn verify_mode(&self) -> SslVerifyMode {
-        let mode = unsafe { ffi::SSL_get_verify_mode(self.as_ptr()) };
-        SslVerifyMode::from_bits(mode).expect("SSL_get_verify_mode returned invalid mode")
+    /// Returns an error if `len` is 0.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_export_keying_material_early)]
+    #[cfg(ossl111)]
+    pub fn keying_material_early(
+        &self,
+        len: usize,
+        label: &str,
+        context: &[u8],
+    ) -> Result<Vec<u8>, ErrorStack> {
+        self.keying_material_early_into(vec![0; len], label, context)
     }
 
-    /// Like [`SslContextBuilder::set_verify_callback`].
+    /// Like `export_keying_material_early`, but fills and returns `out`, which allows the size of
+    /// the keying material to be fixed by its type.
     ///
-    /// [`SslContextBuilder::set_verify_callback`]: struct.SslContextBuilder.html#method.set_verify_callback
-    #[corresponds(SSL_set_verify)]
-    pub fn set_verify_callback<F>(&mut self, mode: SslVerifyMode, verify: F)
+    /// Returns an error if `out` is empty.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_export_keying_material_early)]
+    #[cfg(ossl111)]
+    pub fn keying_material_early_into<T>(
+        &self,
+        mut out: T,
+        label: &str,
+        context: &[u8],
+    ) -> Result<T, ErrorStack>
     where
-        F: Fn(bool, &mut X509StoreContextRef) -> bool + 'static + Sync + Send,
+        T: AsMut<[u8]>,
     {
-        unsafe {
-            // this needs to be in an Arc since the callback can register a new callback!
-            self.set_ex_data(Ssl::cached_ex_index(), Arc::new(verify));
-            ffi::SSL_set_verify(self.as_ptr(), mode.bits as c_int, Some(ssl_raw_verify::<F>));
+        if out.as_mut().is_empty() {
+            return Err(bad_length());
         }
+        self.export_keying_material_early(out.as_mut(), label, context)?;
+        Ok(out)
     }
 
-    /// Like [`SslContextBuilder::set_tmp_dh`].
+    /// Sets the session to be used.
     ///
-    /// [`SslContextBuilder::set_tmp_dh`]: struct.SslContextBuilder.html#method.set_tmp_dh
-    #[corresponds(SSL_set_tmp_dh)]
-    pub fn set_tmp_dh(&mut self, dh: &DhRef<Params>) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_set_tmp_dh(self.as_ptr(), dh.as_ptr()) as c_int).map(|_| ()) }
+    /// This should be called before the handshake to attempt to reuse a previously established
+    /// session. If the server is not willing to reuse the session, a new one will be transparently
+    /// negotiated.
+    ///
+    /// # Safety
+    ///
+    /// The caller of this method is responsible for ensuring that the session is associated
+    /// with the same `SslContext` as this `Ssl`.
+    #[corresponds(SSL_set_session)]
+    pub unsafe fn set_session(&mut self, session: &SslSessionRef) -> Result<(), ErrorStack> {
+        cvt(ffi::SSL_set_session(self.as_ptr(), session.as_ptr())).map(|_| ())
     }
 
-    /// Like [`SslContextBuilder::set_tmp_dh_callback`].
+    /// Determines if the session provided to `set_session` was successfully reused.
     ///
-    /// [`SslContextBuilder::set_tmp_dh_callback`]: struct.SslContextBuilder.html#method.set_tmp_dh_callback
-    #[corresponds(SSL_set_tmp_dh_callback)]
-    pub fn set_tmp_dh_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, bool, u32) -> Result<Dh<Params>, ErrorStack> + 'static + Sync + Send,
-    {
+    /// If it was, [`SslRef::session`] returns the resumed session.
+    #[corresponds(SSL_session_reused)]
+    pub fn session_reused(&self) -> bool {
+        unsafe { ffi::SSL_session_reused(self.as_ptr()) != 0 }
+    }
+
+    /// Sets the status response a client wishes the server to reply with.
+    #[corresponds(SSL_set_tlsext_status_type)]
+    pub fn set_status_type(&mut self, type_: StatusType) -> Result<(), ErrorStack> {
         unsafe {
-            // this needs to be in an Arc since the callback can register a new callback!
-            self.set_ex_data(Ssl::cached_ex_index(), Arc::new(callback));
-            #[cfg(boringssl)]
-            ffi::SSL_set_tmp_dh_callback(self.as_ptr(), Some(raw_tmp_dh_ssl::<F>));
-            #[cfg(not(boringssl))]
-            ffi::SSL_set_tmp_dh_callback(self.as_ptr(), raw_tmp_dh_ssl::<F>);
+            cvt(ffi::SSL_set_tlsext_status_type(self.as_ptr(), type_.as_raw()) as c_int).map(|_| ())
         }
     }
 
-    /// Like [`SslContextBuilder::set_tmp_ecdh`].
+    /// Determines if current session used Extended Master Secret
     ///
-    /// [`SslContextBuilder::set_tmp_ecdh`]: struct.SslContextBuilder.html#method.set_tmp_ecdh
-    #[corresponds(SSL_set_tmp_ecdh)]
-    pub fn set_tmp_ecdh(&mut self, key: &EcKeyRef<Params>) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_set_tmp_ecdh(self.as_ptr(), key.as_ptr()) as c_int).map(|_| ()) }
+    /// Returns `None` if the handshake is still in-progress.
+    #[corresponds(SSL_get_extms_support)]
+    #[cfg(ossl110)]
+    pub fn extms_support(&self) -> Option<bool> {
+        unsafe {
+            match ffi::SSL_get_extms_support(self.as_ptr()) {
+                -1 => None,
+                ret => Some(ret != 0),
+            }
+        }
     }
 
-    /// Like [`SslContextBuilder::set_tmp_ecdh_callback`].
-    ///
-    /// Requires OpenSSL 1.0.1 or 1.0.2.
-    #[corresponds(SSL_set_tmp_ecdh_callback)]
-    #[cfg(any(all(ossl101, not(ossl110))))]
-    #[deprecated(note = "this function leaks memory and does not exist on newer OpenSSL versions")]
-    pub fn set_tmp_ecdh_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(&mut SslRef, bool, u32) -> Result<EcKey<Params>, ErrorStack> + 'static + Sync + Send,
-    {
+    /// Returns the server's OCSP response, if present.
+    #[corresponds(SSL_get_tlsext_status_ocsp_resp)]
+    #[cfg(not(boringssl))]
+    pub fn ocsp_status(&self) -> Option<&[u8]> {
         unsafe {
-            // this needs to be in an Arc since the callback can register a new callback!
-            self.set_ex_data(Ssl::cached_ex_index(), Arc::new(callback));
-            ffi::SSL_set_tmp_ecdh_callback__fixed_rust(self.as_ptr(), Some(raw_tmp_ecdh_ssl::<F>));
+            let mut p = ptr::null_mut();
+            let len = ffi::SSL_get_tlsext_status_ocsp_resp(self.as_ptr(), &mut p);
+
+            if len < 0 {
+                None
+            } else {
+                Some(slice::from_raw_parts(p as *const u8, len as usize))
+            }
         }
     }
 
-    /// Like [`SslContextBuilder::set_ecdh_auto`].
-    ///
-    /// Requires OpenSSL 1.0.2.
+    /// Returns the server's OCSP response, parsed, if present.
     ///
-    /// [`SslContextBuilder::set_tmp_ecdh`]: struct.SslContextBuilder.html#method.set_tmp_ecdh
-    #[corresponds(SSL_set_ecdh_auto)]
-    #[cfg(all(ossl102, not(ossl110)))]
-    pub fn set_ecdh_auto(&mut self, onoff: bool) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_set_ecdh_auto(self.as_ptr(), onoff as c_int)).map(|_| ()) }
+    /// This is the parsed form of [`SslRef::ocsp_status`].
+    #[corresponds(SSL_get_tlsext_status_ocsp_resp)]
+    #[cfg(not(any(boringssl, osslconf = "OPENSSL_NO_OCSP")))]
+    pub fn ocsp_response(&self) -> Result<Option<OcspResponse>, ErrorStack> {
+        self.ocsp_status().map(OcspResponse::from_der).transpose()
     }
 
-    /// Like [`SslContextBuilder::set_alpn_protos`].
-    ///
-    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    /// Sets the OCSP response to be returned to the client.
+    #[corresponds(SSL_set_tlsext_status_oscp_resp)]
+    #[cfg(not(boringssl))]
+    pub fn set_ocsp_status(&mut self, response: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(response.len() <= c_int::max_value() as usize);
+            let p = cvt_p(ffi::OPENSSL_malloc(response.len() as _))?;
+            ptr::copy_nonoverlapping(response.as_ptr(), p as *mut u8, response.len());
+            cvt(ffi::SSL_set_tlsext_status_ocsp_resp(
+                self.as_ptr(),
+                p as *mut c_uchar,
+                response.len() as i64,
+            ) as c_int)
+            .map(|_| ())
+        }
+    }
+
+    /// Determines if this `Ssl` is configured for server-side or client-side use.
+    #[corresponds(SSL_is_server)]
+    pub fn is_server(&self) -> bool {
+        unsafe { SSL_is_server(self.as_ptr()) != 0 }
+    }
+
+    /// Sets the extra data at the specified index.
     ///
-    /// [`SslContextBuilder::set_alpn_protos`]: struct.SslContextBuilder.html#method.set_alpn_protos
-    #[corresponds(SSL_set_alpn_protos)]
-    #[cfg(any(ossl102, libressl261))]
-    pub fn set_alpn_protos(&mut self, protocols: &[u8]) -> Result<(), ErrorStack> {
+    /// This can be used to provide data to callbacks registered with the context. Use the
+    /// `Ssl::new_ex_index` method to create an `Index`.
+    #[corresponds(SSL_set_ex_data)]
+    pub fn set_ex_data<T>(&mut self, index: Index<Ssl, T>, data: T) {
         unsafe {
-            assert!(protocols.len() <= c_uint::max_value() as usize);
-            let r = ffi::SSL_set_alpn_protos(
+            let data = Box::new(data);
+            ffi::SSL_set_ex_data(
                 self.as_ptr(),
-                protocols.as_ptr(),
-                protocols.len() as c_uint,
+                index.as_raw(),
+                Box::into_raw(data) as *mut c_void,
             );
-            // fun fact, SSL_set_alpn_protos has a reversed return code D:
-            if r == 0 {
-                Ok(())
+        }
+    }
+
+    /// Returns a reference to the extra data at the specified index.
+    #[corresponds(SSL_get_ex_data)]
+    pub fn ex_data<T>(&self, index: Index<Ssl, T>) -> Option<&T> {
+        unsafe {
+            let data = ffi::SSL_get_ex_data(self.as_ptr(), index.as_raw());
+            if data.is_null() {
+                None
             } else {
-                Err(ErrorStack::get())
+                Some(&*(data as *const T))
             }
         }
     }
 
-    /// Returns the current cipher if the session is active.
-    #[corresponds(SSL_get_current_cipher)]
-    pub fn current_cipher(&self) -> Option<&SslCipherRef> {
+    /// Returns a mutable reference to the extra data at the specified index.
+    #[corresponds(SSL_get_ex_data)]
+    pub fn ex_data_mut<T>(&mut self, index: Index<Ssl, T>) -> Option<&mut T> {
         unsafe {
-            let ptr = ffi::SSL_get_current_cipher(self.as_ptr());
+            let data = ffi::SSL_get_ex_data(self.as_ptr(), index.as_raw());
+            if data.is_null() {
+                None
+            } else {
+                Some(&mut *(data as *mut T))
+            }
+        }
+    }
 
-            SslCipherRef::from_const_ptr_opt(ptr)
+    /// Sets the maximum amount of early data that will be accepted on this connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_set_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn set_max_early_data(&mut self, bytes: u32) -> Result<(), ErrorStack> {
+        if unsafe { ffi::SSL_set_max_early_data(self.as_ptr(), bytes) } == 1 {
+            Ok(())
+        } else {
+            Err(ErrorStack::get())
         }
     }
 
-    /// Returns a short string describing the state of the session.
-    #[corresponds(SSL_state_string)]
-    pub fn state_string(&self) -> &'static str {
-        let state = unsafe {
-            let ptr = ffi::SSL_state_string(self.as_ptr());
-            CStr::from_ptr(ptr as *const _)
-        };
+    /// Gets the maximum amount of early data that can be sent on this connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_get_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn max_early_data(&self) -> u32 {
+        unsafe { ffi::SSL_get_max_early_data(self.as_ptr()) }
+    }
 
-        str::from_utf8(state.to_bytes()).unwrap()
+    /// Sets the maximum amount of early data that will be read on this connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_set_recv_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn set_recv_max_early_data(&mut self, bytes: u32) -> Result<(), ErrorStack> {
+        if unsafe { ffi::SSL_set_recv_max_early_data(self.as_ptr(), bytes) } == 1 {
+            Ok(())
+        } else {
+            Err(ErrorStack::get())
+        }
     }
 
-    /// Returns a longer string describing the state of the session.
-    #[corresponds(SSL_state_string_long)]
-    pub fn state_string_long(&self) -> &'static str {
-        let state = unsafe {
-            let ptr = ffi::SSL_state_string_long(self.as_ptr());
-            CStr::from_ptr(ptr as *const _)
-        };
+    /// Gets the maximum amount of early data that will be read on this connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_get_recv_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn recv_max_early_data(&self) -> u32 {
+        unsafe { ffi::SSL_get_recv_max_early_data(self.as_ptr()) }
+    }
 
-        str::from_utf8(state.to_bytes()).unwrap()
+    /// Returns whether early data was sent, and if so whether the server accepted it.
+    ///
+    /// The status is only meaningful once the handshake has completed.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_get_early_data_status)]
+    #[cfg(ossl111)]
+    pub fn early_data_status(&self) -> SslEarlyDataStatus {
+        unsafe { SslEarlyDataStatus(ffi::SSL_get_early_data_status(self.as_ptr())) }
+    }
+
+    /// Copies the contents of the last Finished message sent to the peer into the provided buffer.
+    ///
+    /// The total size of the message is returned, so this can be used to determine the size of the
+    /// buffer required.
+    #[corresponds(SSL_get_finished)]
+    pub fn finished(&self, buf: &mut [u8]) -> usize {
+        unsafe { ffi::SSL_get_finished(self.as_ptr(), buf.as_mut_ptr() as *mut c_void, buf.len()) }
     }
 
-    /// Sets the host name to be sent to the server for Server Name Indication (SNI).
+    /// Copies the contents of the last Finished message received from the peer into the provided
+    /// buffer.
     ///
-    /// It has no effect for a server-side connection.
-    #[corresponds(SSL_set_tlsext_host_name)]
-    pub fn set_hostname(&mut self, hostname: &str) -> Result<(), ErrorStack> {
-        let cstr = CString::new(hostname).unwrap();
+    /// The total size of the message is returned, so this can be used to determine the size of the
+    /// buffer required.
+    #[corresponds(SSL_get_peer_finished)]
+    pub fn peer_finished(&self, buf: &mut [u8]) -> usize {
         unsafe {
-            cvt(ffi::SSL_set_tlsext_host_name(self.as_ptr(), cstr.as_ptr() as *mut _) as c_int)
-                .map(|_| ())
+            ffi::SSL_get_peer_finished(self.as_ptr(), buf.as_mut_ptr() as *mut c_void, buf.len())
         }
     }
 
-    /// Returns the peer's certificate, if present.
-    #[corresponds(SSL_get_peer_certificate)]
-    pub fn peer_certificate(&self) -> Option<X509> {
-        unsafe {
-            let ptr = SSL_get1_peer_certificate(self.as_ptr());
-            X509::from_ptr_opt(ptr)
+    /// Returns the `tls-unique` channel binding of the connection, as described in [RFC 5929].
+    ///
+    /// This is the first Finished message of the most recent handshake, which was sent by the
+    /// client in a full handshake and by the server in an abbreviated one. It can be included in a
+    /// certificate request to tie the request to the TLS connection it is submitted over, as is
+    /// done by EST ([RFC 7030]).
+    ///
+    /// `tls-unique` is not defined for TLS 1.3, so `None` is returned for such connections, as well
+    /// as before the handshake has completed.
+    ///
+    /// [RFC 5929]: https://www.rfc-editor.org/rfc/rfc5929#section-3
+    /// [RFC 7030]: https://www.rfc-editor.org/rfc/rfc7030#section-3.5
+    pub fn tls_unique(&self) -> Option<Vec<u8>> {
+        #[cfg(ossl111)]
+        {
+            if self.version2() == Some(SslVersion::TLS1_3) {
+                return None;
+            }
         }
-    }
 
-    /// Returns the certificate chain of the peer, if present.
-    ///
-    /// On the client side, the chain includes the leaf certificate, but on the server side it does
-    /// not. Fun!
-    #[corresponds(SSL_get_peer_cert_chain)]
-    pub fn peer_cert_chain(&self) -> Option<&StackRef<X509>> {
-        unsafe {
-            let ptr = ffi::SSL_get_peer_cert_chain(self.as_ptr());
-            StackRef::from_const_ptr_opt(ptr)
+        let mut buf = [0; 64];
+        let len = if self.is_server() == self.session_reused() {
+            self.finished(&mut buf)
+        } else {
+            self.peer_finished(&mut buf)
+        };
+        if len == 0 {
+            None
+        } else {
+            Some(buf[..cmp::min(len, buf.len())].to_vec())
         }
     }
 
-    /// Returns the verified certificate chain of the peer, including the leaf certificate.
-    ///
-    /// If verification was not successful (i.e. [`verify_result`] does not return
-    /// [`X509VerifyResult::OK`]), this chain may be incomplete or invalid.
+    /// Determines if the initial handshake has been completed.
+    #[corresponds(SSL_is_init_finished)]
+    #[cfg(ossl110)]
+    pub fn is_init_finished(&self) -> bool {
+        unsafe { ffi::SSL_is_init_finished(self.as_ptr()) != 0 }
+    }
+
+    /// Determines if the client's hello message is in the SSLv2 format.
     ///
-    /// Requires OpenSSL 1.1.0 or newer.
+    /// This can only be used inside of the client hello callback. Otherwise, `false` is returned.
     ///
-    /// [`verify_result`]: #method.verify_result
-    /// [`X509VerifyResult::OK`]: ../x509/struct.X509VerifyResult.html#associatedconstant.OK
-    #[corresponds(SSL_get0_verified_chain)]
-    #[cfg(ossl110)]
-    pub fn verified_chain(&self) -> Option<&StackRef<X509>> {
-        unsafe {
-            let ptr = ffi::SSL_get0_verified_chain(self.as_ptr());
-            StackRef::from_const_ptr_opt(ptr)
-        }
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_client_hello_isv2)]
+    #[cfg(ossl111)]
+    pub fn client_hello_isv2(&self) -> bool {
+        unsafe { ffi::SSL_client_hello_isv2(self.as_ptr()) != 0 }
     }
 
-    /// Like [`SslContext::certificate`].
-    #[corresponds(SSL_get_certificate)]
-    pub fn certificate(&self) -> Option<&X509Ref> {
+    /// Returns the legacy version field of the client's hello message.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_client_hello_get0_legacy_version)]
+    #[cfg(ossl111)]
+    pub fn client_hello_legacy_version(&self) -> Option<SslVersion> {
         unsafe {
-            let ptr = ffi::SSL_get_certificate(self.as_ptr());
-            X509Ref::from_const_ptr_opt(ptr)
+            let version = ffi::SSL_client_hello_get0_legacy_version(self.as_ptr());
+            if version == 0 {
+                None
+            } else {
+                Some(SslVersion(version as c_int))
+            }
         }
     }
 
-    /// Like [`SslContext::private_key`].
+    /// Returns the random field of the client's hello message.
     ///
-    /// This corresponds to `SSL_get_privatekey`.
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
     ///
-    /// [`SslContext::private_key`]: struct.SslContext.html#method.private_key
-    #[corresponds(SSL_get_certificate)]
-    pub fn private_key(&self) -> Option<&PKeyRef<Private>> {
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_client_hello_get0_random)]
+    #[cfg(ossl111)]
+    pub fn client_hello_random(&self) -> Option<&[u8]> {
         unsafe {
-            let ptr = ffi::SSL_get_privatekey(self.as_ptr());
-            PKeyRef::from_const_ptr_opt(ptr)
+            let mut ptr = ptr::null();
+            let len = ffi::SSL_client_hello_get0_random(self.as_ptr(), &mut ptr);
+            if len == 0 {
+                None
+            } else {
+                Some(slice::from_raw_parts(ptr, len))
+            }
         }
     }
 
-    #[deprecated(since = "0.10.5", note = "renamed to `version_str`")]
-    pub fn version(&self) -> &str {
-        self.version_str()
-    }
-
-    /// Returns the protocol version of the session.
-    #[corresponds(SSL_version)]
-    pub fn version2(&self) -> Option<SslVersion> {
+    /// Returns the session ID field of the client's hello message.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_client_hello_get0_session_id)]
+    #[cfg(ossl111)]
+    pub fn client_hello_session_id(&self) -> Option<&[u8]> {
         unsafe {
-            let r = ffi::SSL_version(self.as_ptr());
-            if r == 0 {
+            let mut ptr = ptr::null();
+            let len = ffi::SSL_client_hello_get0_session_id(self.as_ptr(), &mut ptr);
+            if len == 0 {
                 None
             } else {
-                Some(SslVersion(r))
+                Some(slice::from_raw_parts(ptr, len))
             }
         }
     }
 
-    /// Returns a string describing the protocol version of the session.
-    #[corresponds(SSL_get_version)]
-    pub fn version_str(&self) -> &'static str {
-        let version = unsafe {
-            let ptr = ffi::SSL_get_version(self.as_ptr());
-            CStr::from_ptr(ptr as *const _)
-        };
-
-        str::from_utf8(version.to_bytes()).unwrap()
-    }
-
-    /// Returns the protocol selected via Application Layer Protocol Negotiation (ALPN).
+    /// Returns the ciphers field of the client's hello message.
     ///
-    /// The protocol's name is returned is an opaque sequence of bytes. It is up to the client
-    /// to interpret it.
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
     ///
-    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
-    #[corresponds(SSL_get0_alpn_selected)]
-    #[cfg(any(ossl102, libressl261))]
-    pub fn selected_alpn_protocol(&self) -> Option<&[u8]> {
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_client_hello_get0_ciphers)]
+    #[cfg(ossl111)]
+    pub fn client_hello_ciphers(&self) -> Option<&[u8]> {
         unsafe {
-            let mut data: *const c_uchar = ptr::null();
-            let mut len: c_uint = 0;
-            // Get the negotiated protocol from the SSL instance.
-            // `data` will point at a `c_uchar` array; `len` will contain the length of this array.
-            ffi::SSL_get0_alpn_selected(self.as_ptr(), &mut data, &mut len);
-
-            if data.is_null() {
+            let mut ptr = ptr::null();
+            let len = ffi::SSL_client_hello_get0_ciphers(self.as_ptr(), &mut ptr);
+            if len == 0 {
                 None
             } else {
-                Some(slice::from_raw_parts(data, len as usize))
+                Some(slice::from_raw_parts(ptr, len))
             }
         }
     }
 
-    /// Enables the DTLS extension "use_srtp" as defined in RFC5764.
+    /// Returns the compression methods field of the client's hello message.
     ///
-    /// This corresponds to [`SSL_set_tlsext_use_srtp`].
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
     ///
-    /// [`SSL_set_tlsext_use_srtp`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_tlsext_use_srtp.html
-    #[corresponds(SSL_set_tlsext_use_srtp)]
-    pub fn set_tlsext_use_srtp(&mut self, protocols: &str) -> Result<(), ErrorStack> {
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_client_hello_get0_compression_methods)]
+    #[cfg(ossl111)]
+    pub fn client_hello_compression_methods(&self) -> Option<&[u8]> {
         unsafe {
-            let cstr = CString::new(protocols).unwrap();
-
-            let r = ffi::SSL_set_tlsext_use_srtp(self.as_ptr(), cstr.as_ptr());
-            // fun fact, set_tlsext_use_srtp has a reversed return code D:
-            if r == 0 {
-                Ok(())
+            let mut ptr = ptr::null();
+            let len = ffi::SSL_client_hello_get0_compression_methods(self.as_ptr(), &mut ptr);
+            if len == 0 {
+                None
             } else {
-                Err(ErrorStack::get())
+                Some(slice::from_raw_parts(ptr, len))
             }
         }
     }
 
-    /// Gets all SRTP profiles that are enabled for handshake via set_tlsext_use_srtp
+    /// Returns the identifiers of the cipher suites offered in the client's hello message, in the
+    /// client's order of preference.
     ///
-    /// DTLS extension "use_srtp" as defined in RFC5764 has to be enabled.
+    /// Unlike `client_hello_cipher_list`, this includes GREASE values and cipher suites unknown to
+    /// OpenSSL, which makes it suitable for fingerprinting.
     ///
-    /// This corresponds to [`SSL_get_srtp_profiles`].
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
     ///
-    /// [`SSL_get_srtp_profiles`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_tlsext_use_srtp.html
-    #[corresponds(SSL_get_srtp_profiles)]
-    pub fn srtp_profiles(&self) -> Option<&StackRef<SrtpProtectionProfile>> {
-        unsafe {
-            let chain = ffi::SSL_get_srtp_profiles(self.as_ptr());
-
-            StackRef::from_const_ptr_opt(chain)
-        }
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_cipher_ids(&self) -> Option<Vec<u16>> {
+        self.client_hello_ciphers().map(|ciphers| {
+            ciphers
+                .chunks_exact(2)
+                .map(|id| u16::from_be_bytes([id[0], id[1]]))
+                .collect()
+        })
     }
 
-    /// Gets the SRTP profile selected by handshake.
+    /// Looks up a cipher suite by its two-byte IANA identifier.
     ///
-    /// DTLS extension "use_srtp" as defined in RFC5764 has to be enabled.
-    #[corresponds(SSL_get_selected_srtp_profile)]
-    pub fn selected_srtp_profile(&self) -> Option<&SrtpProtectionProfileRef> {
+    /// Returns `None` if the cipher suite is not known to OpenSSL.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CIPHER_find)]
+    #[cfg(ossl111)]
+    pub fn find_cipher(&self, id: u16) -> Option<&SslCipherRef> {
         unsafe {
-            let profile = ffi::SSL_get_selected_srtp_profile(self.as_ptr());
-
-            SrtpProtectionProfileRef::from_const_ptr_opt(profile)
+            SslCipherRef::from_const_ptr_opt(ffi::SSL_CIPHER_find(
+                self.as_ptr(),
+                id.to_be_bytes().as_ptr(),
+            ))
         }
     }
 
-    /// Returns the number of bytes remaining in the currently processed TLS record.
+    /// Sets the certificate compression algorithms supported by this connection, in order of
+    /// preference, overriding those set on the context.
     ///
-    /// If this is greater than 0, the next call to `read` will not call down to the underlying
-    /// stream.
-    #[corresponds(SSL_pending)]
-    pub fn pending(&self) -> usize {
-        unsafe { ffi::SSL_pending(self.as_ptr()) as usize }
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_set1_cert_comp_preference)]
+    #[cfg(ossl320)]
+    pub fn set_certificate_compression_preference(
+        &mut self,
+        algorithms: &[CertificateCompressionAlgorithm],
+    ) -> Result<(), ErrorStack> {
+        let mut algorithms = algorithms
+            .iter()
+            .map(|a| c_int::from(a.as_raw()))
+            .collect::<Vec<_>>();
+        unsafe {
+            cvt(ffi::SSL_set1_cert_comp_preference(
+                self.as_ptr(),
+                algorithms.as_mut_ptr(),
+                algorithms.len(),
+            ))
+            .map(|_| ())
+        }
     }
 
-    /// Returns the servername sent by the client via Server Name Indication (SNI).
+    /// Returns the cipher suites offered in the client's hello message which are known to
+    /// OpenSSL, in the client's order of preference.
     ///
-    /// It is only useful on the server side.
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
     ///
-    /// # Note
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CIPHER_find)]
+    #[cfg(ossl111)]
+    pub fn client_hello_cipher_list(&self) -> Option<Vec<&SslCipherRef>> {
+        self.client_hello_ciphers().map(|ciphers| {
+            ciphers
+                .chunks_exact(2)
+                .filter_map(|id| unsafe {
+                    SslCipherRef::from_const_ptr_opt(ffi::SSL_CIPHER_find(
+                        self.as_ptr(),
+                        id.as_ptr(),
+                    ))
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the types of the extensions present in the client's hello message, in the order
+    /// they were sent.
     ///
-    /// While the SNI specification requires that servernames be valid domain names (and therefore
-    /// ASCII), OpenSSL does not enforce this restriction. If the servername provided by the client
-    /// is not valid UTF-8, this function will return `None`. The `servername_raw` method returns
-    /// the raw bytes and does not have this restriction.
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
     ///
-    /// [`SSL_get_servername`]: https://www.openssl.org/docs/manmaster/man3/SSL_get_servername.html
-    #[corresponds(SSL_get_servername)]
-    // FIXME maybe rethink in 0.11?
-    pub fn servername(&self, type_: NameType) -> Option<&str> {
-        self.servername_raw(type_)
-            .and_then(|b| str::from_utf8(b).ok())
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_client_hello_get1_extensions_present)]
+    #[cfg(ossl111)]
+    pub fn client_hello_extension_types(&self) -> Option<Vec<u16>> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let mut len = 0;
+            if ffi::SSL_client_hello_get1_extensions_present(self.as_ptr(), &mut ptr, &mut len) != 1
+            {
+                return None;
+            }
+
+            let types = if len == 0 {
+                vec![]
+            } else {
+                slice::from_raw_parts(ptr, len)
+                    .iter()
+                    .map(|&ty| ty as u16)
+                    .collect()
+            };
+            ffi::OPENSSL_free(ptr as *mut c_void);
+            Some(types)
+        }
     }
 
-    /// Returns the servername sent by the client via Server Name Indication (SNI).
-    ///
-    /// It is only useful on the server side.
+    /// Returns the body of the extension of the specified type in the client's hello message.
     ///
-    /// # Note
+    /// This can only be used inside of the client hello callback. Otherwise, or if the client did
+    /// not send the extension, `None` is returned.
     ///
-    /// Unlike `servername`, this method does not require the name be valid UTF-8.
-    #[corresponds(SSL_get_servername)]
-    pub fn servername_raw(&self, type_: NameType) -> Option<&[u8]> {
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_client_hello_get0_ext)]
+    #[cfg(ossl111)]
+    pub fn client_hello_extension(&self, ext_type: u16) -> Option<&[u8]> {
         unsafe {
-            let name = ffi::SSL_get_servername(self.as_ptr(), type_.0);
-            if name.is_null() {
-                None
+            let mut ptr = ptr::null();
+            let mut len = 0;
+            if ffi::SSL_client_hello_get0_ext(self.as_ptr(), ext_type as c_uint, &mut ptr, &mut len)
+                != 1
+            {
+                return None;
+            }
+
+            if len == 0 {
+                Some(&[])
             } else {
-                Some(CStr::from_ptr(name as *const _).to_bytes())
+                Some(slice::from_raw_parts(ptr, len))
             }
         }
     }
 
-    /// Changes the context corresponding to the current connection.
+    /// Returns the host name requested by the client's hello message through the server name
+    /// indication extension.
     ///
-    /// It is most commonly used in the Server Name Indication (SNI) callback.
-    #[corresponds(SSL_set_SSL_CTX)]
-    pub fn set_ssl_context(&mut self, ctx: &SslContextRef) -> Result<(), ErrorStack> {
-        unsafe { cvt_p(ffi::SSL_set_SSL_CTX(self.as_ptr(), ctx.as_ptr())).map(|_| ()) }
-    }
-
-    /// Returns the context corresponding to the current connection.
-    #[corresponds(SSL_get_SSL_CTX)]
-    pub fn ssl_context(&self) -> &SslContextRef {
-        unsafe {
-            let ssl_ctx = ffi::SSL_get_SSL_CTX(self.as_ptr());
-            SslContextRef::from_ptr(ssl_ctx)
+    /// The server name is not yet available from `servername` while the client hello callback
+    /// runs, so this parses it out of the raw extension instead.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, or if the client did
+    /// not request a host name, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_servername(&self) -> Option<&str> {
+        let mut list =
+            read_u16_prefixed(self.client_hello_extension(ffi::TLSEXT_TYPE_server_name as u16)?)?.0;
+        while !list.is_empty() {
+            let (&name_type, rest) = list.split_first()?;
+            let (name, rest) = read_u16_prefixed(rest)?;
+            if c_int::from(name_type) == ffi::TLSEXT_NAMETYPE_host_name {
+                return str::from_utf8(name).ok();
+            }
+            list = rest;
         }
+
+        None
     }
 
-    /// Returns a mutable reference to the X509 verification configuration.
+    /// Returns the protocols offered by the client's hello message through the ALPN extension, in
+    /// the client's order of preference.
     ///
-    /// Requires OpenSSL 1.0.2 or newer.
-    #[corresponds(SSL_get0_param)]
-    #[cfg(any(ossl102, libressl261))]
-    pub fn param_mut(&mut self) -> &mut X509VerifyParamRef {
-        unsafe { X509VerifyParamRef::from_ptr_mut(ffi::SSL_get0_param(self.as_ptr())) }
-    }
+    /// This can only be used inside of the client hello callback. Otherwise, or if the client did
+    /// not send the extension, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_alpn_protocols(&self) -> Option<Vec<&[u8]>> {
+        let mut list = read_u16_prefixed(self.client_hello_extension(
+            ffi::TLSEXT_TYPE_application_layer_protocol_negotiation as u16,
+        )?)?
+        .0;
+        let mut protocols = vec![];
+        while let Some((&len, rest)) = list.split_first() {
+            let len = usize::from(len);
+            if rest.len() < len {
+                return None;
+            }
+            let (protocol, rest) = rest.split_at(len);
+            protocols.push(protocol);
+            list = rest;
+        }
 
-    /// Returns the certificate verification result.
-    #[corresponds(SSL_get_verify_result)]
-    pub fn verify_result(&self) -> X509VerifyResult {
-        unsafe { X509VerifyResult::from_raw(ffi::SSL_get_verify_result(self.as_ptr()) as c_int) }
+        Some(protocols)
     }
 
-    /// Returns a shared reference to the SSL session.
-    #[corresponds(SSL_get_session)]
-    pub fn session(&self) -> Option<&SslSessionRef> {
+    /// Like [`SslContextBuilder::set_max_cert_list`].
+    #[corresponds(SSL_set_max_cert_list)]
+    pub fn set_max_cert_list(&mut self, size: usize) {
         unsafe {
-            let p = ffi::SSL_get_session(self.as_ptr());
-            SslSessionRef::from_const_ptr_opt(p)
+            ffi::SSL_set_max_cert_list(self.as_ptr(), size as _);
         }
     }
 
-    /// Copies the `client_random` value sent by the client in the TLS handshake into a buffer.
-    ///
-    /// Returns the number of bytes copied, or if the buffer is empty, the size of the `client_random`
-    /// value.
+    /// Like [`SslContextBuilder::set_max_fragment_length`].
     ///
-    /// Requires OpenSSL 1.1.0 or newer.
-    #[corresponds(SSL_get_client_random)]
-    #[cfg(any(ossl110))]
-    pub fn client_random(&self, buf: &mut [u8]) -> usize {
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_set_tlsext_max_fragment_length)]
+    #[cfg(ossl111)]
+    pub fn set_max_fragment_length(&mut self, len: MaxFragmentLength) -> Result<(), ErrorStack> {
         unsafe {
-            ffi::SSL_get_client_random(self.as_ptr(), buf.as_mut_ptr() as *mut c_uchar, buf.len())
+            cvt(ffi::SSL_set_tlsext_max_fragment_length(
+                self.as_ptr(),
+                len.0,
+            ))
+            .map(|_| ())
         }
     }
 
-    /// Copies the `server_random` value sent by the server in the TLS handshake into a buffer.
-    ///
-    /// Returns the number of bytes copied, or if the buffer is empty, the size of the `server_random`
-    /// value.
-    ///
-    /// Requires OpenSSL 1.1.0 or newer.
-    #[corresponds(SSL_get_server_random)]
-    #[cfg(any(ossl110))]
-    pub fn server_random(&self, buf: &mut [u8]) -> usize {
+    /// Like [`SslContextBuilder::set_max_send_fragment`].
+    #[corresponds(SSL_set_max_send_fragment)]
+    pub fn set_max_send_fragment(&mut self, size: usize) -> Result<(), ErrorStack> {
         unsafe {
-            ffi::SSL_get_server_random(self.as_ptr(), buf.as_mut_ptr() as *mut c_uchar, buf.len())
+            cvt(ffi::SSL_set_max_send_fragment(self.as_ptr(), size as _) as c_int).map(|_| ())
         }
     }
 
-    /// Derives keying material for application use in accordance to RFC 5705.
-    #[corresponds(SSL_export_keying_material)]
-    pub fn export_keying_material(
-        &self,
-        out: &mut [u8],
-        label: &str,
-        context: Option<&[u8]>,
-    ) -> Result<(), ErrorStack> {
-        unsafe {
-            let (context, contextlen, use_context) = match context {
-                Some(context) => (context.as_ptr() as *const c_uchar, context.len(), 1),
-                None => (ptr::null(), 0, 0),
-            };
-            cvt(ffi::SSL_export_keying_material(
-                self.as_ptr(),
-                out.as_mut_ptr() as *mut c_uchar,
-                out.len(),
-                label.as_ptr() as *const c_char,
-                label.len(),
-                context,
-                contextlen,
-                use_context,
-            ))
-            .map(|_| ())
-        }
+    /// Returns the maximum size of the certificate chain accepted from the peer, in bytes.
+    #[corresponds(SSL_get_max_cert_list)]
+    pub fn max_cert_list(&self) -> usize {
+        unsafe { ffi::SSL_get_max_cert_list(self.as_ptr()) as usize }
     }
 
-    /// Derives keying material for application use in accordance to RFC 5705.
+    /// Sets options on this connection, returning the new set.
     ///
-    /// This function is only usable with TLSv1.3, wherein there is no distinction between an empty context and no
-    /// context. Therefore, unlike `export_keying_material`, `context` must always be supplied.
+    /// Options set on the context are inherited when the connection is created; this allows
+    /// them to be adjusted per connection, for example to set [`SslOptions::NO_TICKET`] for a
+    /// single client.
+    #[corresponds(SSL_set_options)]
+    pub fn set_options(&mut self, option: SslOptions) -> SslOptions {
+        let bits = unsafe { ffi::SSL_set_options(self.as_ptr(), option.bits() as BitTy) } as SslTy;
+        SslOptions { bits }
+    }
+
+    /// Returns the options used by this connection.
+    #[corresponds(SSL_get_options)]
+    pub fn options(&self) -> SslOptions {
+        let bits = unsafe { ffi::SSL_get_options(self.as_ptr()) } as SslTy;
+        SslOptions { bits }
+    }
+
+    /// Clears options on this connection, returning the new set.
+    #[corresponds(SSL_clear_options)]
+    pub fn clear_options(&mut self, option: SslOptions) -> SslOptions {
+        let bits =
+            unsafe { ffi::SSL_clear_options(self.as_ptr(), option.bits() as BitTy) } as SslTy;
+        SslOptions { bits }
+    }
+
+    /// Like [`SslContextBuilder::set_num_tickets`].
     ///
     /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_export_keying_material_early)]
+    #[corresponds(SSL_set_num_tickets)]
     #[cfg(ossl111)]
-    pub fn export_keying_material_early(
-        &self,
-        out: &mut [u8],
-        label: &str,
-        context: &[u8],
-    ) -> Result<(), ErrorStack> {
-        unsafe {
-            cvt(ffi::SSL_export_keying_material_early(
-                self.as_ptr(),
-                out.as_mut_ptr() as *mut c_uchar,
-                out.len(),
-                label.as_ptr() as *const c_char,
-                label.len(),
-                context.as_ptr() as *const c_uchar,
-                context.len(),
-            ))
-            .map(|_| ())
-        }
+    pub fn set_num_tickets(&mut self, num_tickets: usize) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_num_tickets(self.as_ptr(), num_tickets)).map(|_| ()) }
     }
 
-    /// Sets the session to be used.
+    /// Like [`SslContextRef::num_tickets`].
     ///
-    /// This should be called before the handshake to attempt to reuse a previously established
-    /// session. If the server is not willing to reuse the session, a new one will be transparently
-    /// negotiated.
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_get_num_tickets)]
+    #[cfg(ossl111)]
+    pub fn num_tickets(&self) -> usize {
+        unsafe { ffi::SSL_get_num_tickets(self.as_ptr()) }
+    }
+
+    /// Like [`SslContextBuilder::set_security_level`].
     ///
-    /// # Safety
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_set_security_level)]
+    #[cfg(ossl110)]
+    pub fn set_security_level(&mut self, level: u32) {
+        unsafe { ffi::SSL_set_security_level(self.as_ptr(), level as c_int) }
+    }
+
+    /// Like [`SslContextRef::security_level`].
     ///
-    /// The caller of this method is responsible for ensuring that the session is associated
-    /// with the same `SslContext` as this `Ssl`.
-    #[corresponds(SSL_set_session)]
-    pub unsafe fn set_session(&mut self, session: &SslSessionRef) -> Result<(), ErrorStack> {
-        cvt(ffi::SSL_set_session(self.as_ptr(), session.as_ptr())).map(|_| ())
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_get_security_level)]
+    #[cfg(ossl110)]
+    pub fn security_level(&self) -> u32 {
+        unsafe { ffi::SSL_get_security_level(self.as_ptr()) as u32 }
     }
 
-    /// Determines if the session provided to `set_session` was successfully reused.
-    #[corresponds(SSL_session_reused)]
-    pub fn session_reused(&self) -> bool {
-        unsafe { ffi::SSL_session_reused(self.as_ptr()) != 0 }
+    /// Sets the MTU used for DTLS connections.
+    #[corresponds(SSL_set_mtu)]
+    pub fn set_mtu(&mut self, mtu: u32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_mtu(self.as_ptr(), mtu as MtuTy) as c_int).map(|_| ()) }
     }
 
-    /// Sets the status response a client wishes the server to reply with.
-    #[corresponds(SSL_set_tlsext_status_type)]
-    pub fn set_status_type(&mut self, type_: StatusType) -> Result<(), ErrorStack> {
-        unsafe {
-            cvt(ffi::SSL_set_tlsext_status_type(self.as_ptr(), type_.as_raw()) as c_int).map(|_| ())
-        }
+    /// Sets the MTU of the underlying link for DTLS connections.
+    ///
+    /// Unlike `set_mtu`, this is the MTU including the overhead of the datagram transport.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(DTLS_set_link_mtu)]
+    #[cfg(ossl110)]
+    pub fn set_link_mtu(&mut self, mtu: u32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::DTLS_set_link_mtu(self.as_ptr(), mtu as c_long) as c_int).map(|_| ()) }
     }
 
-    /// Determines if current session used Extended Master Secret
+    /// Returns the smallest link MTU supported for DTLS connections.
     ///
-    /// Returns `None` if the handshake is still in-progress.
-    #[corresponds(SSL_get_extms_support)]
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(DTLS_get_link_min_mtu)]
     #[cfg(ossl110)]
-    pub fn extms_support(&self) -> Option<bool> {
-        unsafe {
-            match ffi::SSL_get_extms_support(self.as_ptr()) {
-                -1 => None,
-                ret => Some(ret != 0),
-            }
-        }
+    pub fn link_min_mtu(&self) -> u32 {
+        unsafe { ffi::DTLS_get_link_min_mtu(self.as_ptr()) as u32 }
     }
 
-    /// Returns the server's OCSP response, if present.
-    #[corresponds(SSL_get_tlsext_status_ocsp_resp)]
-    #[cfg(not(boringssl))]
-    pub fn ocsp_status(&self) -> Option<&[u8]> {
+    /// Returns the time remaining until the DTLS retransmission timer expires.
+    ///
+    /// Returns `None` if no timer is running. Once the timer has expired, `handle_dtls_timeout`
+    /// should be called.
+    #[corresponds(DTLSv1_get_timeout)]
+    pub fn dtls_timeout(&self) -> Option<Duration> {
         unsafe {
-            let mut p = ptr::null_mut();
-            let len = ffi::SSL_get_tlsext_status_ocsp_resp(self.as_ptr(), &mut p);
-
-            if len < 0 {
-                None
+            let mut tv = mem::zeroed();
+            if ffi::DTLSv1_get_timeout(self.as_ptr(), &mut tv) > 0 {
+                Some(Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000))
             } else {
-                Some(slice::from_raw_parts(p as *const u8, len as usize))
+                None
             }
         }
     }
 
-    /// Sets the OCSP response to be returned to the client.
-    #[corresponds(SSL_set_tlsext_status_oscp_resp)]
-    #[cfg(not(boringssl))]
-    pub fn set_ocsp_status(&mut self, response: &[u8]) -> Result<(), ErrorStack> {
-        unsafe {
-            assert!(response.len() <= c_int::max_value() as usize);
-            let p = cvt_p(ffi::OPENSSL_malloc(response.len() as _))?;
-            ptr::copy_nonoverlapping(response.as_ptr(), p as *mut u8, response.len());
-            cvt(ffi::SSL_set_tlsext_status_ocsp_resp(
-                self.as_ptr(),
-                p as *mut c_uchar,
-                response.len() as i64,
-            ) as c_int)
-            .map(|_| ())
-        }
+    /// Handles an expired DTLS retransmission timer, retransmitting the last flight if necessary.
+    ///
+    /// Returns `true` if the timer had expired and was handled.
+    #[corresponds(DTLSv1_handle_timeout)]
+    pub fn handle_dtls_timeout(&mut self) -> Result<bool, ErrorStack> {
+        unsafe { cvt_n(ffi::DTLSv1_handle_timeout(self.as_ptr()) as c_int).map(|r| r > 0) }
     }
 
-    /// Determines if this `Ssl` is configured for server-side or client-side use.
-    #[corresponds(SSL_is_server)]
-    pub fn is_server(&self) -> bool {
-        unsafe { SSL_is_server(self.as_ptr()) != 0 }
+    /// Performs any pending network I/O and timer processing on a QUIC connection.
+    ///
+    /// This should be called when the underlying socket becomes readable or writable, or when the
+    /// deadline returned by `event_timeout` passes. Calling it on a TLS connection has no effect.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_handle_events)]
+    #[cfg(ossl320)]
+    pub fn handle_events(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_handle_events(self.as_ptr())).map(|_| ()) }
     }
 
-    /// Sets the extra data at the specified index.
+    /// Returns the time remaining until `handle_events` should next be called.
     ///
-    /// This can be used to provide data to callbacks registered with the context. Use the
-    /// `Ssl::new_ex_index` method to create an `Index`.
-    #[corresponds(SSL_set_ex_data)]
-    pub fn set_ex_data<T>(&mut self, index: Index<Ssl, T>, data: T) {
+    /// Returns `None` if no timer is running.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_get_event_timeout)]
+    #[cfg(ossl320)]
+    pub fn event_timeout(&self) -> Result<Option<Duration>, ErrorStack> {
         unsafe {
-            let data = Box::new(data);
-            ffi::SSL_set_ex_data(
+            let mut tv = mem::zeroed::<libc::timeval>();
+            let mut is_infinite = 0;
+            cvt(ffi::SSL_get_event_timeout(
                 self.as_ptr(),
-                index.as_raw(),
-                Box::into_raw(data) as *mut c_void,
-            );
+                &mut tv,
+                &mut is_infinite,
+            ))?;
+            if is_infinite != 0 {
+                Ok(None)
+            } else {
+                Ok(Some(Duration::new(
+                    tv.tv_sec as u64,
+                    tv.tv_usec as u32 * 1000,
+                )))
+            }
         }
     }
 
-    /// Returns a reference to the extra data at the specified index.
-    #[corresponds(SSL_get_ex_data)]
-    pub fn ex_data<T>(&self, index: Index<Ssl, T>) -> Option<&T> {
+    /// Sets whether I/O on a QUIC connection or stream blocks.
+    ///
+    /// QUIC objects block by default when the underlying network BIO supports it.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_set_blocking_mode)]
+    #[cfg(ossl320)]
+    pub fn set_blocking_mode(&mut self, blocking: bool) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_blocking_mode(self.as_ptr(), blocking as c_int)).map(|_| ()) }
+    }
+
+    /// Returns whether I/O on a QUIC connection or stream blocks.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_get_blocking_mode)]
+    #[cfg(ossl320)]
+    pub fn blocking_mode(&self) -> bool {
+        unsafe { ffi::SSL_get_blocking_mode(self.as_ptr()) == 1 }
+    }
+
+    /// Returns `true` if this object is a connection rather than a QUIC stream.
+    ///
+    /// TLS objects are always connections.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_is_connection)]
+    #[cfg(ossl320)]
+    pub fn is_connection(&self) -> bool {
+        unsafe { ffi::SSL_is_connection(self.as_ptr()) != 0 }
+    }
+
+    /// Returns the directions in which data can flow on this stream.
+    ///
+    /// For a QUIC connection object, this describes its default stream, if any.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_get_stream_type)]
+    #[cfg(ossl320)]
+    pub fn stream_type(&self) -> QuicStreamType {
+        unsafe { QuicStreamType(ffi::SSL_get_stream_type(self.as_ptr())) }
+    }
+
+    /// Returns the QUIC stream ID of this stream, or of the default stream of a connection.
+    ///
+    /// Returns `None` if there is no such stream.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_get_stream_id)]
+    #[cfg(ossl320)]
+    pub fn stream_id(&self) -> Option<u64> {
         unsafe {
-            let data = ffi::SSL_get_ex_data(self.as_ptr(), index.as_raw());
-            if data.is_null() {
-                None
-            } else {
-                Some(&*(data as *const T))
+            match ffi::SSL_get_stream_id(self.as_ptr()) {
+                u64::MAX => None,
+                id => Some(id),
             }
         }
     }
 
-    /// Returns a mutable reference to the extra data at the specified index.
-    #[corresponds(SSL_get_ex_data)]
-    pub fn ex_data_mut<T>(&mut self, index: Index<Ssl, T>) -> Option<&mut T> {
+    /// Concludes the sending part of this QUIC stream, signalling to the peer that no more data
+    /// will be written.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_stream_conclude)]
+    #[cfg(ossl320)]
+    pub fn stream_conclude(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_stream_conclude(self.as_ptr(), 0)).map(|_| ()) }
+    }
+
+    /// Opens a new locally initiated stream on this QUIC connection.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_new_stream)]
+    #[cfg(ossl320)]
+    pub fn new_stream(&mut self, flags: QuicStreamFlags) -> Result<Ssl, ErrorStack> {
         unsafe {
-            let data = ffi::SSL_get_ex_data(self.as_ptr(), index.as_raw());
-            if data.is_null() {
-                None
+            let ptr = cvt_p(ffi::SSL_new_stream(self.as_ptr(), flags.bits()))?;
+            Ok(Ssl::from_ptr(ptr))
+        }
+    }
+
+    /// Returns the next stream opened by the peer on this QUIC connection.
+    ///
+    /// Returns `None` if no stream is queued and the call did not block, either because the
+    /// connection is in nonblocking mode or because `QuicAcceptStreamFlags::NO_BLOCK` was passed.
+    /// Incoming streams are only queued once the default stream mode has been set to
+    /// `QuicDefaultStreamMode::NONE`.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_accept_stream)]
+    #[cfg(ossl320)]
+    pub fn accept_stream(
+        &mut self,
+        flags: QuicAcceptStreamFlags,
+    ) -> Result<Option<Ssl>, ErrorStack> {
+        unsafe {
+            let ptr = ffi::SSL_accept_stream(self.as_ptr(), flags.bits());
+            if !ptr.is_null() {
+                return Ok(Some(Ssl::from_ptr(ptr)));
+            }
+            let errors = ErrorStack::get();
+            if errors.errors().is_empty() {
+                Ok(None)
             } else {
-                Some(&mut *(data as *mut T))
+                Err(errors)
             }
         }
     }
 
-    /// Sets the maximum amount of early data that will be accepted on this connection.
+    /// Returns the number of peer-initiated streams waiting to be returned by `accept_stream`.
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_set_max_early_data)]
-    #[cfg(ossl111)]
-    pub fn set_max_early_data(&mut self, bytes: u32) -> Result<(), ErrorStack> {
-        if unsafe { ffi::SSL_set_max_early_data(self.as_ptr(), bytes) } == 1 {
-            Ok(())
-        } else {
-            Err(ErrorStack::get())
-        }
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_get_accept_stream_queue_len)]
+    #[cfg(ossl320)]
+    pub fn accept_stream_queue_len(&self) -> usize {
+        unsafe { ffi::SSL_get_accept_stream_queue_len(self.as_ptr()) }
     }
 
-    /// Gets the maximum amount of early data that can be sent on this connection.
+    /// Sets how this QUIC connection manages its default stream.
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_get_max_early_data)]
-    #[cfg(ossl111)]
-    pub fn max_early_data(&self) -> u32 {
-        unsafe { ffi::SSL_get_max_early_data(self.as_ptr()) }
+    /// This must be called before the default stream is created. Set it to
+    /// `QuicDefaultStreamMode::NONE` to use `new_stream` and `accept_stream` exclusively.
+    ///
+    /// Requires OpenSSL 3.2.0 or newer.
+    #[corresponds(SSL_set_default_stream_mode)]
+    #[cfg(ossl320)]
+    pub fn set_default_stream_mode(
+        &mut self,
+        mode: QuicDefaultStreamMode,
+    ) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_default_stream_mode(self.as_ptr(), mode.0)).map(|_| ()) }
     }
 
-    /// Copies the contents of the last Finished message sent to the peer into the provided buffer.
+    /// Configures the session to run the TLS handshake over QUIC.
     ///
-    /// The total size of the message is returned, so this can be used to determine the size of the
-    /// buffer required.
-    #[corresponds(SSL_get_finished)]
-    pub fn finished(&self, buf: &mut [u8]) -> usize {
-        unsafe { ffi::SSL_get_finished(self.as_ptr(), buf.as_mut_ptr() as *mut c_void, buf.len()) }
+    /// `method` receives handshake data and traffic secrets in place of TLS records.
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_set_quic_method)]
+    #[cfg(boringssl)]
+    pub fn set_quic_method<M>(&mut self, method: M) -> Result<(), ErrorStack>
+    where
+        M: QuicMethod,
+    {
+        let method: Arc<dyn QuicMethod> = Arc::new(method);
+        self.set_ex_data(Ssl::cached_ex_index::<Arc<dyn QuicMethod>>(), method);
+        unsafe { cvt(ffi::SSL_set_quic_method(self.as_ptr(), &QUIC_METHOD)).map(|_| ()) }
     }
 
-    /// Copies the contents of the last Finished message received from the peer into the provided
-    /// buffer.
+    /// Supplies handshake data received from the peer in CRYPTO frames at `level`.
     ///
-    /// The total size of the message is returned, so this can be used to determine the size of the
-    /// buffer required.
-    #[corresponds(SSL_get_peer_finished)]
-    pub fn peer_finished(&self, buf: &mut [u8]) -> usize {
+    /// The handshake should then be driven forward with `SslStream::do_handshake` or
+    /// `SslMachine::do_handshake`, or with `process_quic_post_handshake` once it has completed.
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_provide_quic_data)]
+    #[cfg(boringssl)]
+    pub fn provide_quic_data(
+        &mut self,
+        level: QuicEncryptionLevel,
+        data: &[u8],
+    ) -> Result<(), ErrorStack> {
         unsafe {
-            ffi::SSL_get_peer_finished(self.as_ptr(), buf.as_mut_ptr() as *mut c_void, buf.len())
+            cvt(ffi::SSL_provide_quic_data(
+                self.as_ptr(),
+                level.as_raw(),
+                data.as_ptr(),
+                data.len(),
+            ))
+            .map(|_| ())
         }
     }
 
-    /// Determines if the initial handshake has been completed.
-    #[corresponds(SSL_is_init_finished)]
-    #[cfg(ossl110)]
-    pub fn is_init_finished(&self) -> bool {
-        unsafe { ffi::SSL_is_init_finished(self.as_ptr()) != 0 }
+    /// Processes any post-handshake messages supplied with `provide_quic_data`.
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_process_quic_post_handshake)]
+    #[cfg(boringssl)]
+    pub fn process_quic_post_handshake(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_process_quic_post_handshake(self.as_ptr())).map(|_| ()) }
     }
 
-    /// Determines if the client's hello message is in the SSLv2 format.
+    /// Returns the encryption level at which the handshake currently expects to read data.
     ///
-    /// This can only be used inside of the client hello callback. Otherwise, `false` is returned.
+    /// Requires BoringSSL.
+    #[corresponds(SSL_quic_read_level)]
+    #[cfg(boringssl)]
+    pub fn quic_read_level(&self) -> QuicEncryptionLevel {
+        unsafe { QuicEncryptionLevel(ffi::SSL_quic_read_level(self.as_ptr())) }
+    }
+
+    /// Returns the encryption level at which the handshake currently writes data.
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_client_hello_isv2)]
-    #[cfg(ossl111)]
-    pub fn client_hello_isv2(&self) -> bool {
-        unsafe { ffi::SSL_client_hello_isv2(self.as_ptr()) != 0 }
+    /// Requires BoringSSL.
+    #[corresponds(SSL_quic_write_level)]
+    #[cfg(boringssl)]
+    pub fn quic_write_level(&self) -> QuicEncryptionLevel {
+        unsafe { QuicEncryptionLevel(ffi::SSL_quic_write_level(self.as_ptr())) }
     }
 
-    /// Returns the legacy version field of the client's hello message.
+    /// Returns the maximum number of bytes of handshake data which may be buffered at `level`
+    /// before `provide_quic_data` is called.
     ///
-    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    /// Requires BoringSSL.
+    #[corresponds(SSL_quic_max_handshake_flight_len)]
+    #[cfg(boringssl)]
+    pub fn quic_max_handshake_flight_len(&self, level: QuicEncryptionLevel) -> usize {
+        unsafe { ffi::SSL_quic_max_handshake_flight_len(self.as_ptr(), level.as_raw()) }
+    }
+
+    /// Sets the QUIC transport parameters to send to the peer.
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_client_hello_get0_legacy_version)]
-    #[cfg(ossl111)]
-    pub fn client_hello_legacy_version(&self) -> Option<SslVersion> {
+    /// Requires BoringSSL.
+    #[corresponds(SSL_set_quic_transport_params)]
+    #[cfg(boringssl)]
+    pub fn set_quic_transport_params(&mut self, params: &[u8]) -> Result<(), ErrorStack> {
         unsafe {
-            let version = ffi::SSL_client_hello_get0_legacy_version(self.as_ptr());
-            if version == 0 {
-                None
-            } else {
-                Some(SslVersion(version as c_int))
-            }
+            cvt(ffi::SSL_set_quic_transport_params(
+                self.as_ptr(),
+                params.as_ptr(),
+                params.len(),
+            ))
+            .map(|_| ())
         }
     }
 
-    /// Returns the random field of the client's hello message.
-    ///
-    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    /// Returns the QUIC transport parameters sent by the peer, if any.
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_client_hello_get0_random)]
-    #[cfg(ossl111)]
-    pub fn client_hello_random(&self) -> Option<&[u8]> {
+    /// Requires BoringSSL.
+    #[corresponds(SSL_get_peer_quic_transport_params)]
+    #[cfg(boringssl)]
+    pub fn peer_quic_transport_params(&self) -> Option<&[u8]> {
         unsafe {
             let mut ptr = ptr::null();
-            let len = ffi::SSL_client_hello_get0_random(self.as_ptr(), &mut ptr);
-            if len == 0 {
+            let mut len = 0;
+            ffi::SSL_get_peer_quic_transport_params(self.as_ptr(), &mut ptr, &mut len);
+            if ptr.is_null() {
                 None
             } else {
                 Some(slice::from_raw_parts(ptr, len))
@@ -3026,36 +7270,74 @@ impl SslRef {
         }
     }
 
-    /// Returns the session ID field of the client's hello message.
+    /// Sets the context which must match between connections for 0-RTT data to be accepted.
     ///
-    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    /// This should include a serialization of the transport parameters and any other
+    /// application state relevant to early data.
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_client_hello_get0_session_id)]
-    #[cfg(ossl111)]
-    pub fn client_hello_session_id(&self) -> Option<&[u8]> {
+    /// Requires BoringSSL.
+    #[corresponds(SSL_set_quic_early_data_context)]
+    #[cfg(boringssl)]
+    pub fn set_quic_early_data_context(&mut self, context: &[u8]) -> Result<(), ErrorStack> {
         unsafe {
-            let mut ptr = ptr::null();
-            let len = ffi::SSL_client_hello_get0_session_id(self.as_ptr(), &mut ptr);
-            if len == 0 {
-                None
-            } else {
-                Some(slice::from_raw_parts(ptr, len))
-            }
+            cvt(ffi::SSL_set_quic_early_data_context(
+                self.as_ptr(),
+                context.as_ptr(),
+                context.len(),
+            ))
+            .map(|_| ())
         }
     }
 
-    /// Returns the ciphers field of the client's hello message.
+    /// Configures a client to offer Encrypted Client Hello using one of the configs in
+    /// `ech_config_list`, an `ECHConfigList` typically obtained from the server's HTTPS DNS
+    /// record.
     ///
-    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    /// Requires BoringSSL.
+    #[corresponds(SSL_set1_ech_config_list)]
+    #[cfg(boringssl)]
+    pub fn set_ech_config_list(&mut self, ech_config_list: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_set1_ech_config_list(
+                self.as_ptr(),
+                ech_config_list.as_ptr(),
+                ech_config_list.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Configures a client to send a GREASE ECH extension when it is not offering ECH.
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_client_hello_get0_ciphers)]
-    #[cfg(ossl111)]
-    pub fn client_hello_ciphers(&self) -> Option<&[u8]> {
+    /// Requires BoringSSL.
+    #[corresponds(SSL_set_enable_ech_grease)]
+    #[cfg(boringssl)]
+    pub fn set_enable_ech_grease(&mut self, enable: bool) {
+        unsafe { ffi::SSL_set_enable_ech_grease(self.as_ptr(), enable as c_int) }
+    }
+
+    /// Determines if the server accepted Encrypted Client Hello.
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_ech_accepted)]
+    #[cfg(boringssl)]
+    pub fn ech_accepted(&self) -> bool {
+        unsafe { ffi::SSL_ech_accepted(self.as_ptr()) != 0 }
+    }
+
+    /// Returns the `ECHConfigList` the server sent after rejecting ECH, if any.
+    ///
+    /// The handshake fails in that case, and may be retried with these configs. If the server
+    /// sent none, ECH should be disabled for the retry.
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_get0_ech_retry_configs)]
+    #[cfg(boringssl)]
+    pub fn ech_retry_configs(&self) -> Option<&[u8]> {
         unsafe {
             let mut ptr = ptr::null();
-            let len = ffi::SSL_client_hello_get0_ciphers(self.as_ptr(), &mut ptr);
+            let mut len = 0;
+            ffi::SSL_get0_ech_retry_configs(self.as_ptr(), &mut ptr, &mut len);
             if len == 0 {
                 None
             } else {
@@ -3064,30 +7346,24 @@ impl SslRef {
         }
     }
 
-    /// Returns the compression methods field of the client's hello message.
-    ///
-    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    /// Returns the public name the client must authenticate the server's certificate against
+    /// after ECH was rejected, if any.
     ///
-    /// Requires OpenSSL 1.1.1 or newer.
-    #[corresponds(SSL_client_hello_get0_compression_methods)]
-    #[cfg(ossl111)]
-    pub fn client_hello_compression_methods(&self) -> Option<&[u8]> {
+    /// Requires BoringSSL.
+    #[corresponds(SSL_get0_ech_name_override)]
+    #[cfg(boringssl)]
+    pub fn ech_name_override(&self) -> Option<&[u8]> {
         unsafe {
             let mut ptr = ptr::null();
-            let len = ffi::SSL_client_hello_get0_compression_methods(self.as_ptr(), &mut ptr);
+            let mut len = 0;
+            ffi::SSL_get0_ech_name_override(self.as_ptr(), &mut ptr, &mut len);
             if len == 0 {
                 None
             } else {
-                Some(slice::from_raw_parts(ptr, len))
+                Some(slice::from_raw_parts(ptr as *const u8, len))
             }
         }
     }
-
-    /// Sets the MTU used for DTLS connections.
-    #[corresponds(SSL_set_mtu)]
-    pub fn set_mtu(&mut self, mtu: u32) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_set_mtu(self.as_ptr(), mtu as MtuTy) as c_int).map(|_| ()) }
-    }
 }
 
 /// An SSL stream midway through the handshake process.
@@ -3118,10 +7394,30 @@ impl<S> MidHandshakeSslStream<S> {
         &self.error
     }
 
+    /// Returns what the handshake is waiting on before it can be resumed with [`handshake`].
+    ///
+    /// Event loops can use this to register interest in the right event: readability or
+    /// writability of the stream for `Read` and `Write`, the asynchronous job's wait file
+    /// descriptors for `Async`, or completion of the application's own work for the callback
+    /// conditions. `None` is returned if the handshake failed and can't be resumed.
+    ///
+    /// [`handshake`]: MidHandshakeSslStream::handshake
+    pub fn want(&self) -> Option<HandshakeWant> {
+        HandshakeWant::from_code(self.error.code())
+    }
+
     /// Consumes `self`, returning its error.
     pub fn into_error(self) -> Error {
         self.error
     }
+
+    /// Consumes `self`, dropping the stream and returning the `Ssl` reset for a new connection.
+    ///
+    /// See [`SslStream::into_ssl`] for details.
+    #[corresponds(SSL_clear)]
+    pub fn into_ssl(self) -> Result<Ssl, ErrorStack> {
+        self.stream.into_ssl()
+    }
 }
 
 impl<S> MidHandshakeSslStream<S>
@@ -3188,7 +7484,26 @@ impl<S: Read + Write> SslStream<S> {
     /// explicitly perform the handshake.
     #[corresponds(SSL_set_bio)]
     pub fn new(ssl: Ssl, stream: S) -> Result<Self, ErrorStack> {
-        let (bio, method) = bio::new(stream)?;
+        SslStream::new_buffered(ssl, stream, vec![])
+    }
+
+    /// Creates a new `SslStream` over a stream which has already been read from.
+    ///
+    /// This is intended for protocols such as SMTP, IMAP and PostgreSQL which upgrade a plaintext
+    /// connection to TLS with a STARTTLS-style exchange. Applications typically read the plaintext
+    /// part of the protocol through their own buffer, which may already hold the start of the
+    /// peer's first TLS record by the time the upgrade is agreed upon. Those bytes must be passed
+    /// as `buffered` so that they are fed to the TLS session before anything else is read from
+    /// `stream`; discarding them corrupts the handshake.
+    ///
+    /// The `SslStream` takes ownership of the buffered bytes, and plaintext which precedes them
+    /// remains the responsibility of the caller. The session only reads from `stream` as far as
+    /// the end of each TLS record unless read-ahead is enabled with
+    /// [`SslContextBuilder::set_read_ahead`], so [`SslStream::into_parts`] can be used to return
+    /// to plaintext after a TLS shutdown as long as read-ahead is left disabled.
+    #[corresponds(SSL_set_bio)]
+    pub fn new_buffered(ssl: Ssl, stream: S, buffered: Vec<u8>) -> Result<Self, ErrorStack> {
+        let (bio, method) = bio::new(stream, buffered)?;
         unsafe {
             ffi::SSL_set_bio(ssl.as_ptr(), bio, bio);
         }
@@ -3335,6 +7650,33 @@ impl<S: Read + Write> SslStream<S> {
         }
     }
 
+    /// Listens for a DTLS ClientHello containing a valid cookie before committing any
+    /// per-connection state.
+    ///
+    /// Requires that cookie generation and verification callbacks were set on the SSL context.
+    ///
+    /// Returns `Ok(true)` if a ClientHello with a valid cookie was read, in which case the
+    /// handshake should be continued via `accept`. If a HelloVerifyRequest was sent instead, or no
+    /// complete ClientHello was available, `Ok(false)` is returned and this method should be
+    /// called again once more data arrives.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(DTLSv1_listen)]
+    #[cfg(ossl110)]
+    pub fn dtls_listen(&mut self) -> Result<bool, Error> {
+        let addr = unsafe { cvt_p(ffi::BIO_ADDR_new()) }.map_err(|e| Error {
+            code: ErrorCode::SSL,
+            cause: Some(InnerError::Ssl(e)),
+        })?;
+        let ret = unsafe { ffi::DTLSv1_listen(self.ssl.as_ptr(), addr) };
+        unsafe { ffi::BIO_ADDR_free(addr) };
+        match ret {
+            1 => Ok(true),
+            0 => Ok(false),
+            n => Err(self.make_error(n)),
+        }
+    }
+
     /// Like `read`, but returns an `ssl::Error` rather than an `io::Error`.
     ///
     /// It is particularly useful with a non-blocking socket, where the error value will identify if
@@ -3358,6 +7700,44 @@ impl<S: Read + Write> SslStream<S> {
         }
     }
 
+    /// Consumes the `SslStream`, returning the underlying stream along with any application data
+    /// which has been decrypted but not yet read.
+    ///
+    /// This is useful for protocols which stop using TLS partway through a connection. No shutdown
+    /// is performed.
+    ///
+    /// TLS data which has been read from the stream but not yet decrypted cannot be recovered, and
+    /// resuming on the stream without it would start partway through a record. The `SslStream` is
+    /// therefore returned unchanged in `Err` if read-ahead is enabled (see
+    /// [`SslContextBuilder::set_read_ahead`]), or if part of a record has been read, for example
+    /// by a non-blocking read which has not completed.
+    pub fn into_parts(mut self) -> Result<(S, Vec<u8>), SslStream<S>> {
+        if unsafe { ffi::SSL_get_read_ahead(self.ssl.as_ptr()) } != 0 {
+            return Err(self);
+        }
+        // without read-ahead, a record is only read once the previous one has been consumed
+        #[cfg(ossl110)]
+        {
+            if self.ssl.pending() == 0 && self.ssl.has_pending() {
+                return Err(self);
+            }
+        }
+
+        let mut buf = vec![0; self.ssl.pending()];
+        let mut len = 0;
+        while len < buf.len() {
+            let ret = self.ssl.read(&mut buf[len..]);
+            if ret <= 0 {
+                break;
+            }
+            len += ret as usize;
+        }
+        buf.truncate(len);
+
+        let stream = unsafe { bio::take_stream(self.ssl.get_raw_rbio()) };
+        Ok((stream, buf))
+    }
+
     /// Like `write`, but returns an `ssl::Error` rather than an `io::Error`.
     ///
     /// It is particularly useful with a non-blocking socket, where the error value will identify if
@@ -3377,6 +7757,56 @@ impl<S: Read + Write> SslStream<S> {
         }
     }
 
+    /// Like `ssl_write`, but writes data from a slice of buffers.
+    ///
+    /// Small buffers are copied into a single write of up to 16KB so that they are sent in as few
+    /// records as possible. A buffer at least that large is written directly. Like `ssl_write`,
+    /// this returns the number of bytes written, which may be less than the total length of the
+    /// buffers.
+    ///
+    /// Since the data may be copied into a temporary buffer, non-blocking streams which retry a
+    /// write after a `WANT_WRITE` error must enable [`SslMode::ACCEPT_MOVING_WRITE_BUFFER`].
+    pub fn ssl_write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> Result<usize, Error> {
+        let mut bufs = bufs.iter().filter(|b| !b.is_empty()).peekable();
+        let first = match bufs.next() {
+            Some(first) => first,
+            None => return Ok(0),
+        };
+        if first.len() >= MAX_COALESCED_WRITE || bufs.peek().is_none() {
+            return self.ssl_write(first);
+        }
+
+        let mut buf = Vec::with_capacity(MAX_COALESCED_WRITE);
+        buf.extend_from_slice(first);
+        for b in bufs {
+            let len = cmp::min(b.len(), MAX_COALESCED_WRITE - buf.len());
+            buf.extend_from_slice(&b[..len]);
+            if buf.len() == MAX_COALESCED_WRITE {
+                break;
+            }
+        }
+        self.ssl_write(&buf)
+    }
+
+    /// Like `ssl_write`, but accepts flags controlling the write.
+    ///
+    /// Requires OpenSSL 3.3.0 or newer.
+    #[corresponds(SSL_write_ex2)]
+    #[cfg(ossl330)]
+    pub fn ssl_write_with_flags(
+        &mut self,
+        buf: &[u8],
+        flags: SslWriteFlags,
+    ) -> Result<usize, Error> {
+        let mut written = 0;
+        let ret = self.ssl.write_ex2(buf, flags, &mut written);
+        if ret > 0 {
+            Ok(written)
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
     /// Reads data from the stream, without removing it from the queue.
     #[corresponds(SSL_peek)]
     pub fn ssl_peek(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
@@ -3411,6 +7841,37 @@ impl<S: Read + Write> SslStream<S> {
         }
     }
 
+    /// Fully shuts down the session, returning once the peer's close notify message has been
+    /// received.
+    ///
+    /// A close notify message is sent to the peer if that has not already been done, and any
+    /// application data the peer sends before its own close notify message is discarded. A
+    /// successful return therefore guarantees that the peer closed the session deliberately,
+    /// rather than the connection being truncated.
+    ///
+    /// This is intended for use with blocking streams; with nonblocking streams, use `shutdown`
+    /// and `ssl_read` directly.
+    pub fn shutdown_bidirectional(&mut self) -> Result<(), Error> {
+        if !self.ssl.get_shutdown().contains(ShutdownState::SENT)
+            && self.shutdown()? == ShutdownResult::Received
+        {
+            return Ok(());
+        }
+
+        // Calling SSL_shutdown again fails if application data arrives first, so drain the
+        // connection until the peer's close notify is seen.
+        let mut buf = [0; 1024];
+        while !self.ssl.get_shutdown().contains(ShutdownState::RECEIVED) {
+            match self.ssl_read(&mut buf) {
+                Ok(_) => {}
+                Err(ref e) if e.code() == ErrorCode::ZERO_RETURN => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
     /// Returns the session's shutdown state.
     #[corresponds(SSL_get_shutdown)]
     pub fn get_shutdown(&mut self) -> ShutdownState {
@@ -3428,6 +7889,18 @@ impl<S: Read + Write> SslStream<S> {
     pub fn set_shutdown(&mut self, state: ShutdownState) {
         unsafe { ffi::SSL_set_shutdown(self.ssl.as_ptr(), state.bits()) }
     }
+
+    /// Handles an expired DTLS retransmission timer, retransmitting the last flight if necessary.
+    ///
+    /// Returns `true` if the timer had expired and was handled. See `SslRef::dtls_timeout`.
+    #[corresponds(DTLSv1_handle_timeout)]
+    pub fn handle_dtls_timeout(&mut self) -> Result<bool, Error> {
+        match unsafe { ffi::DTLSv1_handle_timeout(self.ssl.as_ptr()) } {
+            0 => Ok(false),
+            n if n > 0 => Ok(true),
+            n => Err(self.make_error(n as c_int)),
+        }
+    }
 }
 
 impl<S> SslStream<S> {
@@ -3476,6 +7949,9 @@ impl<S> SslStream<S> {
 
     /// Returns a mutable reference to the underlying stream.
     ///
+    /// Data buffered by the session is unaffected, so this can be used to temporarily access the
+    /// stream, for example to change socket options.
+    ///
     /// # Warning
     ///
     /// It is inadvisable to read from or write to the underlying stream as it
@@ -3491,6 +7967,32 @@ impl<S> SslStream<S> {
     pub fn ssl(&self) -> &SslRef {
         &self.ssl
     }
+
+    /// Returns a mutable reference to the `Ssl` object associated with this stream.
+    pub fn ssl_mut(&mut self) -> &mut SslRef {
+        &mut self.ssl
+    }
+
+    /// Consumes the `SslStream`, dropping the underlying stream and returning the `Ssl` reset so
+    /// that it can be used for a new connection.
+    ///
+    /// The configuration of the `Ssl`, such as its hostname and verification settings, is
+    /// retained, as is the session if the handshake did not complete. This allows a client
+    /// which races connection attempts to several addresses to reuse its TLS state for the next
+    /// attempt after one fails.
+    #[corresponds(SSL_clear)]
+    pub fn into_ssl(self) -> Result<Ssl, ErrorStack> {
+        let mut this = ManuallyDrop::new(self);
+        unsafe {
+            let ssl = ManuallyDrop::take(&mut this.ssl);
+            let method = ManuallyDrop::take(&mut this.method);
+            // frees the BIO along with the stream, which has to happen before the method is dropped
+            ffi::SSL_set_bio(ssl.as_ptr(), ptr::null_mut(), ptr::null_mut());
+            drop(method);
+            cvt(ffi::SSL_clear(ssl.as_ptr()))?;
+            Ok(ssl)
+        }
+    }
 }
 
 impl<S: Read + Write> Read for SslStream<S> {
@@ -3528,6 +8030,20 @@ impl<S: Read + Write> Write for SslStream<S> {
         }
     }
 
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        loop {
+            match self.ssl_write_vectored(bufs) {
+                Ok(n) => return Ok(n),
+                Err(ref e) if e.code() == ErrorCode::WANT_READ && e.io_error().is_none() => {}
+                Err(e) => {
+                    return Err(e
+                        .into_io_error()
+                        .unwrap_or_else(|e| io::Error::new(io::ErrorKind::Other, e)));
+                }
+            }
+        }
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         self.get_mut().flush()
     }
@@ -0,0 +1,264 @@
+//! Certificate selection based on the Server Name Indication extension.
+//!
+//! An [`SniResolver`] maps hostnames to certificates and their private keys. Once installed on an
+//! acceptor with [`SslAcceptorBuilder::set_sni_resolver`], the certificate presented to each client
+//! is chosen from the name it requested. Entries can be added, replaced and reloaded from disk
+//! while the acceptor is in use.
+//!
+//! Requires OpenSSL 1.0.2 or newer.
+//!
+//! [`SslAcceptorBuilder::set_sni_resolver`]: crate::ssl::SslAcceptorBuilder::set_sni_resolver
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use crate::cvt;
+use crate::error::ErrorStack;
+use crate::pkey::{PKey, PKeyRef, Private};
+use crate::ssl::{NameType, SslRef};
+use crate::x509::{X509Ref, X509};
+use foreign_types::ForeignTypeRef;
+
+/// A certificate, the remainder of its chain, and its private key.
+pub struct CertifiedKey {
+    cert: X509,
+    chain: Vec<X509>,
+    key: PKey<Private>,
+}
+
+impl CertifiedKey {
+    /// Creates a new `CertifiedKey`.
+    ///
+    /// `chain` should not include `cert` itself. Returns an error if `key` does not correspond to
+    /// the public key of `cert`.
+    pub fn new(
+        cert: X509,
+        chain: Vec<X509>,
+        key: PKey<Private>,
+    ) -> Result<CertifiedKey, ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_check_private_key(cert.as_ptr(), key.as_ptr()))?;
+        }
+
+        Ok(CertifiedKey { cert, chain, key })
+    }
+
+    /// Loads a `CertifiedKey` from PEM-encoded data.
+    ///
+    /// The first certificate in `cert_chain` is the leaf certificate, and any following it form
+    /// the remainder of the chain.
+    pub fn from_pem(cert_chain: &[u8], key: &[u8]) -> Result<CertifiedKey, ErrorStack> {
+        let cert = X509::from_pem(cert_chain)?;
+        let chain = X509::stack_from_pem(cert_chain)?
+            .into_iter()
+            .skip(1)
+            .collect();
+        let key = PKey::private_key_from_pem(key)?;
+        CertifiedKey::new(cert, chain, key)
+    }
+
+    /// Loads a `CertifiedKey` from PEM-encoded files.
+    ///
+    /// See [`CertifiedKey::from_pem`].
+    pub fn from_pem_files<P, Q>(cert_chain: P, key: Q) -> io::Result<CertifiedKey>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let cert_chain = fs::read(cert_chain)?;
+        let key = fs::read(key)?;
+        Ok(CertifiedKey::from_pem(&cert_chain, &key)?)
+    }
+
+    /// Returns the leaf certificate.
+    pub fn certificate(&self) -> &X509Ref {
+        &self.cert
+    }
+
+    /// Returns the certificates following the leaf certificate in the chain.
+    pub fn chain(&self) -> &[X509] {
+        &self.chain
+    }
+
+    /// Returns the private key.
+    pub fn private_key(&self) -> &PKeyRef<Private> {
+        &self.key
+    }
+
+    fn install(&self, ssl: &mut SslRef) -> Result<(), ErrorStack> {
+        ssl.set_certificate(&self.cert)?;
+        ssl.clear_chain_certs()?;
+        for cert in &self.chain {
+            ssl.add_chain_cert(cert)?;
+        }
+        ssl.set_private_key(&self.key)
+    }
+}
+
+impl fmt::Debug for CertifiedKey {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("CertifiedKey")
+            .field("cert", &self.cert)
+            .field("chain_len", &self.chain.len())
+            .finish()
+    }
+}
+
+struct Entry {
+    key: Arc<CertifiedKey>,
+    files: Option<(PathBuf, PathBuf)>,
+}
+
+/// A table mapping hostnames to the certificates presented for them.
+///
+/// Hostnames are matched case-insensitively. A name of the form `*.example.com` matches any name
+/// with exactly one additional leading label, such as `www.example.com`, and is only consulted if
+/// there is no entry for the exact name.
+///
+/// All methods take `&self`, so a resolver shared with an acceptor through an `Arc` can be
+/// updated while connections are being accepted. Each handshake uses the entry current when the
+/// client's hello message is processed.
+#[derive(Default)]
+pub struct SniResolver {
+    entries: RwLock<HashMap<String, Entry>>,
+}
+
+impl SniResolver {
+    /// Creates a new, empty resolver.
+    pub fn new() -> SniResolver {
+        SniResolver::default()
+    }
+
+    /// Adds an entry for `hostname`, returning the entry it replaced.
+    pub fn insert(&self, hostname: &str, key: CertifiedKey) -> Option<Arc<CertifiedKey>> {
+        self.insert_entry(
+            hostname,
+            Entry {
+                key: Arc::new(key),
+                files: None,
+            },
+        )
+    }
+
+    /// Adds an entry for `hostname` loaded from PEM-encoded files, returning the entry it
+    /// replaced.
+    ///
+    /// The files are remembered so that the entry is refreshed by [`SniResolver::reload`]. See
+    /// [`CertifiedKey::from_pem`] for the expected contents.
+    pub fn insert_pem_files<P, Q>(
+        &self,
+        hostname: &str,
+        cert_chain: P,
+        key: Q,
+    ) -> io::Result<Option<Arc<CertifiedKey>>>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let cert_chain = cert_chain.as_ref().to_path_buf();
+        let key = key.as_ref().to_path_buf();
+        let certified = CertifiedKey::from_pem_files(&cert_chain, &key)?;
+        Ok(self.insert_entry(
+            hostname,
+            Entry {
+                key: Arc::new(certified),
+                files: Some((cert_chain, key)),
+            },
+        ))
+    }
+
+    /// Removes the entry for `hostname`, returning it.
+    pub fn remove(&self, hostname: &str) -> Option<Arc<CertifiedKey>> {
+        self.entries
+            .write()
+            .unwrap()
+            .remove(&normalize(hostname))
+            .map(|e| e.key)
+    }
+
+    /// Returns the entry which would be used for a client requesting `servername`.
+    pub fn resolve(&self, servername: &str) -> Option<Arc<CertifiedKey>> {
+        let servername = normalize(servername);
+        let entries = self.entries.read().unwrap();
+        if let Some(entry) = entries.get(&servername) {
+            return Some(entry.key.clone());
+        }
+
+        let parent = &servername[servername.find('.')? + 1..];
+        if parent.is_empty() {
+            return None;
+        }
+        entries.get(&format!("*.{}", parent)).map(|e| e.key.clone())
+    }
+
+    /// Reloads every entry added with [`SniResolver::insert_pem_files`] from disk.
+    ///
+    /// All files are loaded before any entry is replaced, so if any of them fails to load, an
+    /// error is returned and the resolver is left unchanged.
+    pub fn reload(&self) -> io::Result<()> {
+        let files = self
+            .entries
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|(name, e)| e.files.clone().map(|f| (name.clone(), f)))
+            .collect::<Vec<_>>();
+
+        let mut loaded = Vec::with_capacity(files.len());
+        for (name, (cert_chain, key)) in files {
+            let certified = CertifiedKey::from_pem_files(&cert_chain, &key)?;
+            loaded.push((name, (cert_chain, key), certified));
+        }
+
+        let mut entries = self.entries.write().unwrap();
+        for (name, files, certified) in loaded {
+            // skip entries which were replaced or removed while the files were being read
+            if let Some(entry) = entries.get_mut(&name) {
+                if entry.files.as_ref() == Some(&files) {
+                    entry.key = Arc::new(certified);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Installs the entry matching the servername requested on `ssl`, if there is one.
+    pub(crate) fn install(&self, ssl: &mut SslRef) -> Result<(), ErrorStack> {
+        let servername = match ssl.servername(NameType::HOST_NAME) {
+            Some(servername) => servername,
+            None => return Ok(()),
+        };
+
+        match self.resolve(servername) {
+            Some(key) => key.install(ssl),
+            None => Ok(()),
+        }
+    }
+
+    fn insert_entry(&self, hostname: &str, entry: Entry) -> Option<Arc<CertifiedKey>> {
+        self.entries
+            .write()
+            .unwrap()
+            .insert(normalize(hostname), entry)
+            .map(|e| e.key)
+    }
+}
+
+impl fmt::Debug for SniResolver {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = self.entries.read().unwrap();
+        let mut names = entries.keys().collect::<Vec<_>>();
+        names.sort();
+        fmt.debug_struct("SniResolver")
+            .field("hostnames", &names)
+            .finish()
+    }
+}
+
+fn normalize(hostname: &str) -> String {
+    hostname.trim_end_matches('.').to_ascii_lowercase()
+}
@@ -3,14 +3,15 @@
 use std::env;
 use std::fs::File;
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::{self, BufReader, IoSlice};
 use std::iter;
 use std::mem;
 use std::net::UdpSocket;
 use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::path::Path;
 use std::process::{Child, ChildStdin, Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 use tempdir::TempDir;
@@ -18,21 +19,40 @@ use tempdir::TempDir;
 use crate::dh::Dh;
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
+use crate::nid::Nid;
 #[cfg(not(boringssl))]
 use crate::ocsp::{OcspResponse, OcspResponseStatus};
-use crate::pkey::PKey;
+use crate::pkey::{Id, PKey};
 use crate::srtp::SrtpProfileId;
 use crate::ssl;
 use crate::ssl::test::server::Server;
+#[cfg(ossl300)]
+use crate::ssl::AsyncStatus;
+#[cfg(ossl102)]
+use crate::ssl::CertCallbackResponse;
 #[cfg(any(ossl110, ossl111, libressl261))]
 use crate::ssl::SslVersion;
+#[cfg(ossl330)]
+use crate::ssl::SslWriteFlags;
+#[cfg(ossl320)]
+use crate::ssl::{CertificateCompressionAlgorithm, CertificateType};
 #[cfg(ossl111)]
-use crate::ssl::{ClientHelloResponse, ExtensionContext};
 use crate::ssl::{
-    Error, HandshakeError, MidHandshakeSslStream, ShutdownResult, ShutdownState, Ssl, SslAcceptor,
-    SslAcceptorBuilder, SslConnector, SslContext, SslContextBuilder, SslFiletype, SslMethod,
-    SslOptions, SslSessionCacheMode, SslStream, SslVerifyMode, StatusType,
+    ClientHelloResponse, ExtensionContext, KeyUpdateType, MaxFragmentLength, SslEarlyDataStatus,
+    SslSession,
+};
+use crate::ssl::{
+    Error, ErrorCode, HandshakeError, HandshakeWant, HostnameVerification, MidHandshakeSslStream,
+    ShutdownResult, ShutdownState, Ssl, SslAcceptor, SslAcceptorBuilder, SslAlert, SslAlertLevel,
+    SslCipher, SslConnector, SslContentType, SslContext, SslContextBuilder, SslFiletype,
+    SslHandshakeType, SslInfoEvent, SslMachine, SslMessageKind, SslMethod, SslMode, SslOptions,
+    SslSessionCacheMode, SslStream, SslVerifyMode, StatusType,
 };
+#[cfg(all(ossl320, not(osslconf = "OPENSSL_NO_QUIC")))]
+use crate::ssl::{QuicAcceptStreamFlags, QuicDefaultStreamMode, QuicStreamFlags, QuicStreamType};
+#[cfg(boringssl)]
+use crate::ssl::{QuicEncryptionLevel, QuicMethod, SslCipherRef, SslRef};
+use crate::stack::StackRef;
 #[cfg(ossl102)]
 use crate::x509::store::X509StoreBuilder;
 #[cfg(ossl102)]
@@ -158,6 +178,38 @@ fn verify_callback_load_certs() {
     client.connect();
 }
 
+#[test]
+fn cert_verify_callback_override_bad() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+    client.ctx().set_cert_verify_callback(|x509| {
+        assert!(x509.chain().is_none());
+        false
+    });
+
+    client.connect_err();
+}
+
+#[test]
+fn cert_verify_callback_override_ok() {
+    let server = Server::builder().build();
+
+    let mut client = server.client();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+    client.ctx().set_cert_verify_callback(|x509| {
+        #[cfg(ossl110)]
+        assert!(x509.cert().is_some());
+        assert!(!x509.verify_cert().unwrap());
+        true
+    });
+
+    client.connect();
+}
+
 #[test]
 fn verify_trusted_get_error_ok() {
     let server = Server::builder().build();
@@ -292,6 +344,129 @@ fn pending() {
     assert_eq!(s.read(&mut [0; 10]).unwrap(), 9);
 }
 
+#[test]
+fn into_parts() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| {
+        s.write_all(b"0123456789").unwrap();
+        let (mut stream, buf) = s.into_parts().unwrap();
+        assert!(buf.is_empty());
+        stream.write_all(b"plaintext").unwrap();
+    });
+    let server = server.build();
+
+    let mut s = server.client().connect();
+    let mut buf = [0; 1];
+    s.read_exact(&mut buf).unwrap();
+
+    let (mut stream, buf) = s.into_parts().unwrap();
+    assert_eq!(buf, b"123456789");
+    let mut buf = [0; 9];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"plaintext");
+}
+
+#[test]
+fn into_parts_read_ahead() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| s.write_all(b"0123456789").unwrap());
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_read_ahead(true);
+    let mut s = client.connect();
+    s.read_exact(&mut [0]).unwrap();
+
+    let mut s = s.into_parts().err().unwrap();
+    let mut buf = [0; 9];
+    s.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"123456789");
+}
+
+#[test]
+fn get_mut_keeps_buffered_data() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| s.write_all(b"0123456789").unwrap());
+    let server = server.build();
+
+    let mut s = server.client().connect();
+    s.read_exact(&mut [0]).unwrap();
+
+    s.get_mut()
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    assert_eq!(s.ssl().pending(), 9);
+    let mut buf = [0; 9];
+    s.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"123456789");
+}
+
+#[test]
+fn new_buffered() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "STARTTLS\r\n");
+        // make sure that part of the handshake is sitting in our buffer
+        reader.fill_buf().unwrap();
+        let buffered = reader.buffer().to_vec();
+        assert!(!buffered.is_empty());
+        let stream = reader.into_inner();
+
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+            .unwrap();
+        let ssl = Ssl::new(&ctx.build()).unwrap();
+        let mut stream = SslStream::new_buffered(ssl, stream, buffered).unwrap();
+        stream.accept().unwrap();
+        stream.write_all(b"hello").unwrap();
+    });
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(b"STARTTLS\r\n").unwrap();
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    let ssl = Ssl::new(&ctx.build()).unwrap();
+    let mut stream = ssl.connect(stream).unwrap();
+
+    let mut buf = [0; 5];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+
+    guard.join().unwrap();
+}
+
+#[test]
+fn into_ssl() {
+    // a peer which hangs up without responding
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let guard = thread::spawn(move || drop(listener.accept().unwrap()));
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    ssl.set_hostname("foobar.com").unwrap();
+    let ssl = match ssl.connect(TcpStream::connect(addr).unwrap()) {
+        Err(HandshakeError::Failure(s)) => s.into_ssl().unwrap(),
+        _ => panic!("expected a handshake failure"),
+    };
+    guard.join().unwrap();
+
+    let server = Server::builder().build();
+    let mut s = ssl.connect(server.connect_tcp()).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+    assert_eq!(
+        s.ssl().servername(ssl::NameType::HOST_NAME),
+        Some("foobar.com")
+    );
+}
+
 #[test]
 fn state() {
     let server = Server::builder().build();
@@ -365,6 +540,104 @@ fn test_connect_with_srtp_ctx() {
     assert_eq!(buf[..], buf2[..]);
 }
 
+#[test]
+fn srtp_keying_material() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut ctx = SslContext::builder(SslMethod::dtls()).unwrap();
+        ctx.set_tlsext_use_srtp("SRTP_AES128_CM_SHA1_80").unwrap();
+        ctx.set_certificate_file(Path::new("test/cert.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_private_key_file(Path::new("test/key.pem"), SslFiletype::PEM)
+            .unwrap();
+        let mut ssl = Ssl::new(&ctx.build()).unwrap();
+        ssl.set_mtu(1500).unwrap();
+        let mut stream = ssl.accept(stream).unwrap();
+
+        let material = stream.ssl().srtp_keying_material().unwrap().unwrap();
+        stream.write_all(&[0]).unwrap();
+
+        (
+            material.local_key().to_vec(),
+            material.local_salt().to_vec(),
+            material.remote_key().to_vec(),
+            material.remote_salt().to_vec(),
+        )
+    });
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut ctx = SslContext::builder(SslMethod::dtls()).unwrap();
+    ctx.set_tlsext_use_srtp("SRTP_AES128_CM_SHA1_80").unwrap();
+    let mut ssl = Ssl::new(&ctx.build()).unwrap();
+    ssl.set_mtu(1500).unwrap();
+    let mut stream = ssl.connect(stream).unwrap();
+
+    let material = stream.ssl().srtp_keying_material().unwrap().unwrap();
+    assert_eq!(material.profile(), SrtpProfileId::SRTP_AES128_CM_SHA1_80);
+    let mut raw = [0; 60];
+    stream
+        .ssl()
+        .export_keying_material(&mut raw, "EXTRACTOR-dtls_srtp", None)
+        .unwrap();
+    assert_eq!(material.client_key(), &raw[..16]);
+    assert_eq!(material.server_key(), &raw[16..32]);
+    assert_eq!(material.client_salt(), &raw[32..46]);
+    assert_eq!(material.server_salt(), &raw[46..]);
+    assert_eq!(material.local_key(), material.client_key());
+    assert_eq!(material.local_salt(), material.client_salt());
+
+    stream.read_exact(&mut [0]).unwrap();
+
+    let (server_key, server_salt, client_key, client_salt) = guard.join().unwrap();
+    assert_eq!(material.remote_key(), &server_key[..]);
+    assert_eq!(material.remote_salt(), &server_salt[..]);
+    assert_eq!(material.local_key(), &client_key[..]);
+    assert_eq!(material.local_salt(), &client_salt[..]);
+}
+
+#[test]
+#[cfg(ossl110)]
+fn dtls_listen() {
+    const COOKIE: &[u8] = b"oatmeal raisin";
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut ctx = SslContext::builder(SslMethod::dtls()).unwrap();
+        ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_options(SslOptions::COOKIE_EXCHANGE);
+        ctx.set_cookie_generate_cb(|_, buf| {
+            buf[..COOKIE.len()].copy_from_slice(COOKIE);
+            Ok(COOKIE.len())
+        });
+        ctx.set_cookie_verify_cb(|_, cookie| cookie == COOKIE);
+        let mut ssl = Ssl::new(&ctx.build()).unwrap();
+        ssl.set_mtu(1500).unwrap();
+        let mut stream = SslStream::new(ssl, stream).unwrap();
+
+        while !stream.dtls_listen().unwrap() {}
+        stream.accept().unwrap();
+        stream.write_all(&[0]).unwrap();
+    });
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let ctx = SslContext::builder(SslMethod::dtls()).unwrap();
+    let mut ssl = Ssl::new(&ctx.build()).unwrap();
+    ssl.set_mtu(1500).unwrap();
+    let mut stream = ssl.connect(stream).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+
+    guard.join().unwrap();
+}
+
 /// Tests that when both the client as well as the server use SRTP and their
 /// lists of supported protocols have an overlap -- with only ONE protocol
 /// being valid for both.
@@ -493,6 +766,54 @@ fn test_alpn_server_unilateral() {
     assert_eq!(None, s.ssl().selected_alpn_protocol());
 }
 
+#[test]
+fn write_vectored() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| {
+        let mut buf = vec![0; 20 * 1024 + 11];
+        s.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf[..11], b"hello world");
+        assert!(buf[11..].iter().all(|&b| b == 1));
+    });
+    let server = server.build();
+
+    let mut s = server.client().connect();
+    let big = vec![1; 20 * 1024];
+    let bufs = [
+        IoSlice::new(b"hello"),
+        IoSlice::new(b""),
+        IoSlice::new(b" world"),
+        IoSlice::new(&big),
+    ];
+    assert_eq!(s.write_vectored(&bufs).unwrap(), 16 * 1024);
+    assert_eq!(
+        s.write_vectored(&[IoSlice::new(&big[16 * 1024 - 11..])])
+            .unwrap(),
+        4 * 1024 + 11
+    );
+}
+
+#[test]
+#[cfg(ossl330)]
+fn write_with_flags() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| {
+        let mut buf = [0; 5];
+        s.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hello");
+    });
+    let server = server.build();
+
+    let mut s = server.client().connect();
+    s.ssl_write_with_flags(b"hello", SslWriteFlags::CONCLUDE)
+        .unwrap_err();
+    assert_eq!(
+        s.ssl_write_with_flags(b"hello", SslWriteFlags::empty())
+            .unwrap(),
+        5
+    );
+}
+
 #[test]
 #[should_panic(expected = "blammo")]
 fn write_panic() {
@@ -633,6 +954,79 @@ fn add_extra_chain_cert() {
     ctx.add_extra_chain_cert(cert).unwrap();
 }
 
+#[test]
+#[cfg(ossl111)]
+fn security_callback() {
+    use super::{SslSecurityOp, SslVersion};
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_security_level(2);
+    assert_eq!(ctx.build().security_level(), 2);
+
+    // The 2048 bit test key only provides 112 bits of security.
+    let cert = X509::from_pem(CERT).unwrap();
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_security_callback(|check| {
+        assert!(check.ssl().is_none() || check.op() != SslSecurityOp::EE_KEY);
+        !(check.op() == SslSecurityOp::EE_KEY && check.bits() < 128)
+    });
+    ctx.set_certificate(&cert).unwrap_err();
+
+    static SAW_VERSION: AtomicBool = AtomicBool::new(false);
+
+    let server = Server::builder().build();
+    let mut client = server.client();
+    client.ctx().set_security_callback(|check| {
+        if check.version().is_some() && check.ssl().is_some() {
+            SAW_VERSION.store(true, Ordering::SeqCst);
+        }
+        match check.cipher() {
+            Some(cipher) => cipher.standard_name() == Some("TLS_CHACHA20_POLY1305_SHA256"),
+            None => true,
+        }
+    });
+    let s = client.connect();
+    assert_eq!(s.ssl().version2(), Some(SslVersion::TLS1_3));
+    assert_eq!(
+        s.ssl().current_cipher().unwrap().standard_name(),
+        Some("TLS_CHACHA20_POLY1305_SHA256")
+    );
+    assert!(SAW_VERSION.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg(ossl102)]
+fn certificate_chain_introspection() {
+    let cert = X509::from_pem(CERT).unwrap();
+    let root = X509::from_pem(ROOT_CERT).unwrap();
+    let key = PKey::private_key_from_pem(KEY).unwrap();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate(&cert).unwrap();
+    ctx.add_chain_cert(&root).unwrap();
+    ctx.set_private_key(&key).unwrap();
+    let ctx = ctx.build();
+
+    assert_eq!(
+        ctx.certificate().unwrap().to_der().unwrap(),
+        cert.to_der().unwrap()
+    );
+    assert!(ctx.private_key().is_some());
+    ctx.check_private_key().unwrap();
+
+    let chain = ctx.chain_certs().unwrap();
+    assert_eq!(chain.len(), 1);
+    assert_eq!(chain[0].to_der().unwrap(), root.to_der().unwrap());
+
+    let ssl = Ssl::new(&ctx).unwrap();
+    assert_eq!(ssl.chain_certs().unwrap().len(), 1);
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate(&cert).unwrap();
+    let ctx = ctx.build();
+    assert!(ctx.check_private_key().is_err());
+}
+
 #[test]
 #[cfg(ossl102)]
 fn verify_valid_hostname() {
@@ -697,63 +1091,149 @@ fn connector_invalid_hostname() {
 }
 
 #[test]
-fn connector_invalid_no_hostname_verification() {
-    let server = Server::builder().build();
+#[cfg(any(ossl102, libressl261))]
+fn connector_per_connection_overrides() {
+    fn connector() -> SslConnector {
+        let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+        connector.set_ca_file("test/root-ca.pem").unwrap();
+        // the test certificates have expired, but everything else should still be checked
+        connector.set_verify_callback(SslVerifyMode::PEER, |ok, ctx| {
+            ok || ctx.error().as_raw() == ffi::X509_V_ERR_CERT_HAS_EXPIRED
+        });
+        connector
+            .set_max_proto_version(Some(SslVersion::TLS1_2))
+            .unwrap();
+        connector.build()
+    }
 
-    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
-    connector.set_ca_file("test/root-ca.pem").unwrap();
+    let mut server = Server::builder();
+    server.ctx().set_alpn_select_callback(|_, client| {
+        ssl::select_next_proto(b"\x08http/1.1\x02h2", client).ok_or(ssl::AlpnError::NOACK)
+    });
+    let server = server.build();
 
-    let s = server.connect_tcp();
-    let mut s = connector
-        .build()
+    let mut config = connector()
         .configure()
         .unwrap()
-        .verify_hostname(false)
-        .connect("bogus.com", s)
-        .unwrap();
-    s.read_exact(&mut [0]).unwrap();
-}
+        .verification_hostname("foobar.com")
+        .use_session_tickets(false);
+    config.set_alpn_protos(b"\x02h2").unwrap();
+    let s = config.connect("bogus.com", server.connect_tcp()).unwrap();
+    assert_eq!(s.ssl().selected_alpn_protocol(), Some(&b"h2"[..]));
+    assert!(!s.ssl().session().unwrap().has_ticket());
 
-#[test]
-fn connector_no_hostname_still_verifies() {
     let mut server = Server::builder();
     server.should_error();
     let server = server.build();
 
-    let connector = SslConnector::builder(SslMethod::tls()).unwrap().build();
-
-    let s = server.connect_tcp();
-    assert!(connector
+    connector()
         .configure()
         .unwrap()
-        .verify_hostname(false)
-        .connect("fizzbuzz.com", s)
-        .is_err());
+        .verification_hostname("bogus.com")
+        .connect("foobar.com", server.connect_tcp())
+        .unwrap_err();
 }
 
 #[test]
-fn connector_no_hostname_can_disable_verify() {
+fn connector_invalid_no_hostname_verification() {
     let server = Server::builder().build();
 
     let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
-    connector.set_verify(SslVerifyMode::NONE);
-    let connector = connector.build();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
 
     let s = server.connect_tcp();
     let mut s = connector
+        .build()
         .configure()
         .unwrap()
         .verify_hostname(false)
-        .connect("foobar.com", s)
+        .connect("bogus.com", s)
         .unwrap();
     s.read_exact(&mut [0]).unwrap();
 }
 
-fn test_mozilla_server(new: fn(SslMethod) -> Result<SslAcceptorBuilder, ErrorStack>) {
-    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
-    let port = listener.local_addr().unwrap().port();
-
-    let t = thread::spawn(move || {
+#[test]
+#[cfg(any(ossl102, libressl261))]
+fn connector_custom_hostname_verification() {
+    let server = Server::builder().build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    // 2017-07-14, while the certificates are valid
+    connector.verify_param_mut().set_time(1_500_000_000);
+
+    let s = server.connect_tcp();
+    let mut s = connector
+        .build()
+        .configure()
+        .unwrap()
+        .hostname_verification(HostnameVerification::custom(|domain, cert| {
+            domain == "bogus.com" && cert.subject_name().entries().count() > 0
+        }))
+        .connect("bogus.com", s)
+        .unwrap();
+    s.read_exact(&mut [0]).unwrap();
+}
+
+#[test]
+fn connector_custom_hostname_verification_rejects() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+
+    let s = server.connect_tcp();
+    connector
+        .build()
+        .configure()
+        .unwrap()
+        .hostname_verification(HostnameVerification::custom(|_, _| false))
+        .connect("foobar.com", s)
+        .unwrap_err();
+}
+
+#[test]
+fn connector_no_hostname_still_verifies() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let connector = SslConnector::builder(SslMethod::tls()).unwrap().build();
+
+    let s = server.connect_tcp();
+    assert!(connector
+        .configure()
+        .unwrap()
+        .verify_hostname(false)
+        .connect("fizzbuzz.com", s)
+        .is_err());
+}
+
+#[test]
+fn connector_no_hostname_can_disable_verify() {
+    let server = Server::builder().build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_verify(SslVerifyMode::NONE);
+    let connector = connector.build();
+
+    let s = server.connect_tcp();
+    let mut s = connector
+        .configure()
+        .unwrap()
+        .verify_hostname(false)
+        .connect("foobar.com", s)
+        .unwrap();
+    s.read_exact(&mut [0]).unwrap();
+}
+
+fn test_mozilla_server(new: fn(SslMethod) -> Result<SslAcceptorBuilder, ErrorStack>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let t = thread::spawn(move || {
         let key = PKey::private_key_from_pem(KEY).unwrap();
         let cert = X509::from_pem(CERT).unwrap();
         let mut acceptor = new(SslMethod::tls()).unwrap();
@@ -823,594 +1303,2536 @@ fn shutdown() {
 }
 
 #[test]
-fn client_ca_list() {
-    let names = X509Name::load_client_ca_file("test/root-ca.pem").unwrap();
-    assert_eq!(names.len(), 1);
+fn shutdown_bidirectional() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| {
+        s.shutdown_bidirectional().unwrap();
+        assert_eq!(
+            s.ssl().get_shutdown(),
+            ShutdownState::SENT | ShutdownState::RECEIVED
+        );
+    });
+    let server = server.build();
 
-    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
-    ctx.set_client_ca_list(names);
+    let mut s = server.client().connect();
+    // discarded by the server while it waits for our close notify
+    s.write_all(b"trailing data").unwrap();
+
+    let mut buf = [0; 1];
+    assert_eq!(
+        s.ssl_read(&mut buf).unwrap_err().code(),
+        ErrorCode::ZERO_RETURN
+    );
+    assert_eq!(s.ssl().get_shutdown(), ShutdownState::RECEIVED);
+    assert_eq!(s.shutdown().unwrap(), ShutdownResult::Received);
 }
 
 #[test]
-fn cert_store() {
-    let server = Server::builder().build();
+fn quiet_shutdown() {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_quiet_shutdown(true);
+    let ctx = ctx.build();
+    assert!(ctx.quiet_shutdown());
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    assert!(ssl.quiet_shutdown());
+    ssl.set_quiet_shutdown(false);
+    assert!(!ssl.quiet_shutdown());
 
-    let mut client = server.client();
-    let cert = X509::from_pem(ROOT_CERT).unwrap();
-    client.ctx().cert_store_mut().add_cert(cert).unwrap();
-    client.ctx().set_verify(SslVerifyMode::PEER);
+    let mut server = Server::builder();
+    server.ctx().set_quiet_shutdown(true);
+    server.io_cb(|mut s| {
+        assert_eq!(s.shutdown().unwrap(), ShutdownResult::Received);
+        assert_eq!(
+            s.ssl().get_shutdown(),
+            ShutdownState::SENT | ShutdownState::RECEIVED
+        );
+    });
+    let server = server.build();
 
-    client.connect();
+    let mut s = server.client().connect();
+    // no close notify is sent, so the connection just ends
+    let err = s.ssl_read(&mut [0]).unwrap_err();
+    assert_ne!(err.code(), ErrorCode::ZERO_RETURN);
+    assert!(s.ssl().get_shutdown().is_empty());
+    s.ssl_mut().set_shutdown(ShutdownState::RECEIVED);
+    assert_eq!(s.ssl().get_shutdown(), ShutdownState::RECEIVED);
 }
 
 #[test]
-#[cfg_attr(any(libressl321, boringssl), ignore)]
-fn tmp_dh_callback() {
-    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+fn message_callback() {
+    let server = Server::builder().build();
 
-    let mut server = Server::builder();
-    server.ctx().set_tmp_dh_callback(|_, _, _| {
-        CALLED_BACK.store(true, Ordering::SeqCst);
-        let dh = include_bytes!("../../../test/dhparams.pem");
-        Dh::params_from_pem(dh)
+    let messages = Arc::new(Mutex::new(vec![]));
+    let mut client = server.client();
+    let messages2 = messages.clone();
+    client.ctx().set_message_callback(move |_, message| {
+        if let SslMessageKind::Handshake(ty) = message.kind() {
+            messages2.lock().unwrap().push((message.is_sent(), ty));
+        }
     });
+    client.connect();
 
-    let server = server.build();
+    let messages = messages.lock().unwrap();
+    assert_eq!(messages[0], (true, SslHandshakeType::CLIENT_HELLO));
+    assert!(messages.contains(&(false, SslHandshakeType::SERVER_HELLO)));
+    assert!(messages.contains(&(false, SslHandshakeType::FINISHED)));
+    assert!(messages.contains(&(true, SslHandshakeType::FINISHED)));
+}
 
+#[test]
+fn info_callback() {
+    let server = Server::builder().build();
+
+    let events = Arc::new(Mutex::new(vec![]));
     let mut client = server.client();
-    // TLS 1.3 has no DH suites, so make sure we don't pick that version
-    #[cfg(ossl111)]
-    client.ctx().set_options(super::SslOptions::NO_TLSV1_3);
-    client.ctx().set_cipher_list("EDH").unwrap();
+    let events2 = events.clone();
+    client
+        .ctx()
+        .set_info_callback(move |_, event| events2.lock().unwrap().push(event));
     client.connect();
 
-    assert!(CALLED_BACK.load(Ordering::SeqCst));
+    let events = events.lock().unwrap();
+    assert_eq!(events[0], SslInfoEvent::HandshakeStart);
+    assert!(events.contains(&SslInfoEvent::StateChange));
+    assert!(events.contains(&SslInfoEvent::HandshakeDone));
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, SslInfoEvent::AlertWritten { .. })));
 }
 
 #[test]
-#[cfg(all(ossl101, not(ossl110)))]
-#[allow(deprecated)]
-fn tmp_ecdh_callback() {
-    use crate::ec::EcKey;
-    use crate::nid::Nid;
-
-    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
-
+fn info_callback_alert() {
     let mut server = Server::builder();
-    server.ctx().set_tmp_ecdh_callback(|_, _, _| {
-        CALLED_BACK.store(true, Ordering::SeqCst);
-        EcKey::from_curve_name(Nid::X9_62_PRIME256V1)
-    });
-
+    server.should_error();
     let server = server.build();
 
+    let alerts = Arc::new(Mutex::new(vec![]));
     let mut client = server.client();
-    client.ctx().set_cipher_list("ECDH").unwrap();
-    client.connect();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+    let alerts2 = alerts.clone();
+    client.ctx().set_info_callback(move |_, event| {
+        if let SslInfoEvent::AlertWritten { level, alert } = event {
+            alerts2.lock().unwrap().push((level, alert));
+        }
+    });
+    client.connect_err();
 
-    assert!(CALLED_BACK.load(Ordering::SeqCst));
+    let alerts = alerts.lock().unwrap();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].0, SslAlertLevel::Fatal);
+    assert_ne!(alerts[0].1.description(), "unknown");
 }
 
 #[test]
-#[cfg_attr(any(libressl321, boringssl), ignore)]
-fn tmp_dh_callback_ssl() {
-    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
-
+fn message_callback_alert() {
     let mut server = Server::builder();
-    server.ssl_cb(|ssl| {
-        ssl.set_tmp_dh_callback(|_, _, _| {
-            CALLED_BACK.store(true, Ordering::SeqCst);
-            let dh = include_bytes!("../../../test/dhparams.pem");
-            Dh::params_from_pem(dh)
-        });
-    });
-
+    server.should_error();
     let server = server.build();
 
+    let alerts = Arc::new(Mutex::new(vec![]));
     let mut client = server.client();
-    // TLS 1.3 has no DH suites, so make sure we don't pick that version
-    #[cfg(ossl111)]
-    client.ctx().set_options(super::SslOptions::NO_TLSV1_3);
-    client.ctx().set_cipher_list("EDH").unwrap();
-    client.connect();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+    let alerts2 = alerts.clone();
+    client.ctx().set_message_callback(move |_, message| {
+        if SslContentType::from_raw(message.content_type()) == SslContentType::ALERT {
+            alerts2.lock().unwrap().push(message.alert().unwrap());
+        } else {
+            assert_eq!(message.alert(), None);
+        }
+    });
+    client.connect_err();
 
-    assert!(CALLED_BACK.load(Ordering::SeqCst));
+    let alerts = alerts.lock().unwrap();
+    assert_eq!(*alerts, [(SslAlertLevel::Fatal, SslAlert::UNKNOWN_CA)]);
+    assert_eq!(alerts[0].0.as_raw(), 2);
+    assert_eq!(SslAlert::from_raw(48), SslAlert::UNKNOWN_CA);
+    assert_eq!(alerts[0].1.description(), "unknown CA");
 }
 
 #[test]
-#[cfg(all(ossl101, not(ossl110)))]
-#[allow(deprecated)]
-fn tmp_ecdh_callback_ssl() {
-    use crate::ec::EcKey;
-    use crate::nid::Nid;
+#[cfg(all(ossl102, not(osslconf = "OPENSSL_NO_SSL_TRACE")))]
+fn message_trace() {
+    let server = Server::builder().build();
 
-    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+    let trace = Arc::new(Mutex::new(String::new()));
+    let mut client = server.client();
+    let trace2 = trace.clone();
+    client.ctx().set_message_callback(move |ssl, message| {
+        trace2
+            .lock()
+            .unwrap()
+            .push_str(&message.trace(ssl).unwrap());
+    });
+    client.connect();
 
+    let trace = trace.lock().unwrap();
+    assert!(trace.contains("ClientHello"), "{}", trace);
+    assert!(trace.contains("ServerHello"), "{}", trace);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn post_handshake_auth() {
     let mut server = Server::builder();
-    server.ssl_cb(|ssl| {
-        ssl.set_tmp_ecdh_callback(|_, _, _| {
-            CALLED_BACK.store(true, Ordering::SeqCst);
-            EcKey::from_curve_name(Nid::X9_62_PRIME256V1)
-        });
+    server.ctx().set_verify_callback(
+        SslVerifyMode::PEER | SslVerifyMode::POST_HANDSHAKE,
+        |_, _| true,
+    );
+    server.io_cb(|mut s| {
+        assert!(s.ssl().peer_certificate().is_none());
+        s.ssl_mut().verify_client_post_handshake().unwrap();
+        s.write_all(&[0]).unwrap();
+        s.read_exact(&mut [0]).unwrap();
+        assert!(s.ssl().peer_certificate().is_some());
     });
-
     let server = server.build();
 
     let mut client = server.client();
-    client.ctx().set_cipher_list("ECDH").unwrap();
-    client.connect();
-
-    assert!(CALLED_BACK.load(Ordering::SeqCst));
+    client.ctx().set_post_handshake_auth(true);
+    client
+        .ctx()
+        .set_certificate_file("test/cert.pem", SslFiletype::PEM)
+        .unwrap();
+    client
+        .ctx()
+        .set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    let mut s = client.connect();
+    s.read_exact(&mut [0]).unwrap();
+    s.write_all(&[0]).unwrap();
 }
 
 #[test]
-fn idle_session() {
+fn handshake_want() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    stream.set_nonblocking(true).unwrap();
+
     let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
     let ssl = Ssl::new(&ctx).unwrap();
-    assert!(ssl.session().is_none());
+    let mid = match ssl.connect(stream) {
+        Err(HandshakeError::WouldBlock(mid)) => mid,
+        _ => panic!("expected the handshake to block"),
+    };
+    assert_eq!(mid.want(), Some(HandshakeWant::Read));
+    assert_eq!(HandshakeWant::Read.code(), ErrorCode::WANT_READ);
+    assert_eq!(HandshakeWant::from_code(ErrorCode::SSL), None);
 }
 
 #[test]
-#[cfg_attr(libressl321, ignore)]
-fn active_session() {
-    let server = Server::builder().build();
+fn max_cert_list() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
 
-    let s = server.client().connect();
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_max_cert_list(64);
+    assert_eq!(ctx.build().max_cert_list(), 64);
 
-    let session = s.ssl().session().unwrap();
-    let len = session.master_key_len();
-    let mut buf = vec![0; len - 1];
-    let copied = session.master_key(&mut buf);
-    assert_eq!(copied, buf.len());
-    let mut buf = vec![0; len + 1];
-    let copied = session.master_key(&mut buf);
-    assert_eq!(copied, len);
+    let mut client = server.client();
+    client.ctx().set_max_cert_list(64);
+    let mut client = client.build().builder();
+    assert_eq!(client.ssl().max_cert_list(), 64);
+    client.connect_err();
 }
 
 #[test]
-#[cfg(not(boringssl))]
-fn status_callbacks() {
-    static CALLED_BACK_SERVER: AtomicBool = AtomicBool::new(false);
-    static CALLED_BACK_CLIENT: AtomicBool = AtomicBool::new(false);
-
+#[cfg(ossl111)]
+fn max_fragment_length() {
     let mut server = Server::builder();
-    server
-        .ctx()
-        .set_status_callback(|ssl| {
-            CALLED_BACK_SERVER.store(true, Ordering::SeqCst);
-            let response = OcspResponse::create(OcspResponseStatus::UNAUTHORIZED, None).unwrap();
-            let response = response.to_der().unwrap();
-            ssl.set_ocsp_status(&response).unwrap();
-            Ok(true)
-        })
-        .unwrap();
-
+    server.ctx().set_max_send_fragment(512).unwrap();
+    server.io_cb(|mut s| {
+        assert_eq!(
+            s.ssl().session().unwrap().max_fragment_length(),
+            MaxFragmentLength::LEN_1024
+        );
+        s.write_all(&[0; 2048]).unwrap();
+    });
     let server = server.build();
 
     let mut client = server.client();
     client
         .ctx()
-        .set_status_callback(|ssl| {
-            CALLED_BACK_CLIENT.store(true, Ordering::SeqCst);
-            let response = OcspResponse::from_der(ssl.ocsp_status().unwrap()).unwrap();
-            assert_eq!(response.status(), OcspResponseStatus::UNAUTHORIZED);
-            Ok(true)
-        })
+        .set_max_fragment_length(MaxFragmentLength::LEN_1024)
         .unwrap();
+    let mut s = client.connect();
+    assert_eq!(
+        s.ssl().session().unwrap().max_fragment_length(),
+        MaxFragmentLength::LEN_1024
+    );
+    assert_eq!(MaxFragmentLength::LEN_1024.size(), Some(1024));
+    assert_eq!(MaxFragmentLength::DISABLED.size(), None);
 
-    let mut client = client.build().builder();
-    client.ssl().set_status_type(StatusType::OCSP).unwrap();
+    let mut buf = [0; 2048];
+    assert_eq!(s.ssl_read(&mut buf).unwrap(), 512);
+    s.read_exact(&mut buf[512..]).unwrap();
+}
 
-    client.connect();
+#[test]
+#[cfg(ossl110)]
+fn pending_and_byte_counts() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| s.write_all(b"hello").unwrap());
+    let server = server.build();
 
-    assert!(CALLED_BACK_SERVER.load(Ordering::SeqCst));
-    assert!(CALLED_BACK_CLIENT.load(Ordering::SeqCst));
+    let mut s = server.client().connect();
+    let handshake_read = s.ssl().bytes_read();
+    assert!(handshake_read > 0);
+    assert!(s.ssl().bytes_written() > 0);
+
+    let mut buf = [0; 2];
+    s.ssl_read(&mut buf).unwrap();
+    assert_eq!(&buf, b"he");
+    assert_eq!(s.ssl().pending(), 3);
+    assert!(s.ssl().has_pending());
+    assert!(s.ssl().bytes_read() > handshake_read + 5);
+
+    let mut buf = [0; 3];
+    s.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"llo");
+    assert_eq!(s.ssl().pending(), 0);
 }
 
 #[test]
-#[cfg_attr(libressl321, ignore)]
-fn new_session_callback() {
-    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
-
+#[cfg(ossl300)]
+fn async_mode() {
     let mut server = Server::builder();
-    server.ctx().set_session_id_context(b"foo").unwrap();
-
+    server.ctx().set_mode(SslMode::ASYNC);
+    server.ctx().set_async_callback(|| {}).unwrap();
+    server.io_cb(|s| {
+        assert!(!s.ssl().waiting_for_async());
+        assert_eq!(s.ssl().async_status().unwrap(), AsyncStatus::UNSUPPORTED);
+        #[cfg(unix)]
+        {
+            assert!(s.ssl().async_fds().unwrap().is_empty());
+            let (added, removed) = s.ssl().changed_async_fds().unwrap();
+            assert!(added.is_empty());
+            assert!(removed.is_empty());
+        }
+    });
     let server = server.build();
 
     let mut client = server.client();
+    client.ctx().set_mode(SslMode::ASYNC);
+    let client = client.build();
+    let mut client = client.builder();
+    client.ssl().set_async_callback(|| {}).unwrap();
+    let s = client.connect();
+    assert!(!s.ssl().waiting_for_async());
+    assert_eq!(s.ssl().async_status().unwrap(), AsyncStatus::UNSUPPORTED);
+}
 
-    client
-        .ctx()
-        .set_session_cache_mode(SslSessionCacheMode::CLIENT | SslSessionCacheMode::NO_INTERNAL);
-    client
-        .ctx()
-        .set_new_session_callback(|_, _| CALLED_BACK.store(true, Ordering::SeqCst));
+#[test]
+#[cfg(ossl300)]
+fn retry_verify() {
+    let server = Server::builder().build();
 
-    client.connect();
+    let retried = Arc::new(AtomicBool::new(false));
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_verify_callback(SslVerifyMode::PEER, {
+        let retried = retried.clone();
+        move |_, x509_ctx| {
+            if !retried.swap(true, Ordering::SeqCst) {
+                let ssl = x509_ctx
+                    .ex_data_mut(X509StoreContext::ssl_idx().unwrap())
+                    .unwrap();
+                assert!(ssl.set_retry_verify());
+            }
+            true
+        }
+    });
+    let ssl = Ssl::new(&ctx.build()).unwrap();
 
-    assert!(CALLED_BACK.load(Ordering::SeqCst));
+    let mid = match ssl.connect(server.connect_tcp()) {
+        Err(HandshakeError::Failure(mid)) => mid,
+        _ => panic!("expected the handshake to be suspended"),
+    };
+    assert_eq!(mid.error().code(), ErrorCode::WANT_RETRY_VERIFY);
+    assert_eq!(mid.want(), Some(HandshakeWant::RetryVerify));
+    assert!(retried.load(Ordering::SeqCst));
+
+    let mut s = mid.handshake().unwrap();
+    s.read_exact(&mut [0]).unwrap();
 }
 
 #[test]
-#[cfg_attr(libressl321, ignore)]
-fn new_session_callback_swapped_ctx() {
-    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
-
+#[cfg(ossl111)]
+fn client_sigalgs_list() {
     let mut server = Server::builder();
-    server.ctx().set_session_id_context(b"foo").unwrap();
-
+    server.ctx().set_verify_callback(
+        SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT,
+        |_, _| true,
+    );
+    server
+        .ctx()
+        .set_client_sigalgs_list("rsa_pss_rsae_sha384")
+        .unwrap();
+    server.io_cb(|s| {
+        assert_eq!(s.ssl().peer_signature_digest(), Some(Nid::SHA384));
+    });
     let server = server.build();
 
     let mut client = server.client();
-
     client
         .ctx()
-        .set_session_cache_mode(SslSessionCacheMode::CLIENT | SslSessionCacheMode::NO_INTERNAL);
+        .set_certificate_file("test/cert.pem", SslFiletype::PEM)
+        .unwrap();
     client
         .ctx()
-        .set_new_session_callback(|_, _| CALLED_BACK.store(true, Ordering::SeqCst));
-
-    let mut client = client.build().builder();
-
-    let ctx = SslContextBuilder::new(SslMethod::tls()).unwrap().build();
-    client.ssl().set_ssl_context(&ctx).unwrap();
+        .set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    let s = client.connect();
+    assert_eq!(s.ssl().signature_digest(), Some(Nid::SHA384));
 
-    client.connect();
+    let mut server = Server::builder();
+    server.ctx().set_verify_callback(
+        SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT,
+        |_, _| true,
+    );
+    server
+        .ctx()
+        .set_client_sigalgs_list("ECDSA+SHA256")
+        .unwrap();
+    server.should_error();
+    let server = server.build();
 
-    assert!(CALLED_BACK.load(Ordering::SeqCst));
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_certificate_file("test/cert.pem", SslFiletype::PEM)
+        .unwrap();
+    client
+        .ctx()
+        .set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    // A TLS 1.3 client completes its handshake before the server rejects its certificate.
+    client
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    client.connect_err();
 }
 
 #[test]
-fn keying_export() {
-    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
-    let addr = listener.local_addr().unwrap();
-
-    let label = "EXPERIMENTAL test";
-    let context = b"my context";
+#[cfg(ossl320)]
+fn raw_public_keys() {
+    let cert = X509::from_pem(CERT).unwrap();
+    let key = cert.public_key().unwrap();
 
-    let guard = thread::spawn(move || {
-        let stream = listener.accept().unwrap().0;
-        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
-        ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
-            .unwrap();
-        ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
-            .unwrap();
-        let ssl = Ssl::new(&ctx.build()).unwrap();
-        let mut stream = ssl.accept(stream).unwrap();
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_server_cert_types(&[CertificateType::RAW_PUBLIC_KEY, CertificateType::X509])
+        .unwrap();
+    let server = server.build();
 
-        let mut buf = [0; 32];
-        stream
-            .ssl()
-            .export_keying_material(&mut buf, label, Some(context))
-            .unwrap();
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_server_cert_types(&[CertificateType::RAW_PUBLIC_KEY])
+        .unwrap();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+    client.ctx().dane_enable().unwrap();
+    let mut client = client.build().builder();
+    client.ssl().dane_enable("foobar.com").unwrap();
+    client.ssl().add_expected_rpk(&key).unwrap();
+    let s = client.connect();
 
-        stream.write_all(&[0]).unwrap();
+    assert_eq!(
+        s.ssl().negotiated_server_cert_type(),
+        CertificateType::RAW_PUBLIC_KEY
+    );
+    assert_eq!(s.ssl().negotiated_client_cert_type(), CertificateType::X509);
+    assert!(s.ssl().peer_rpk().unwrap().public_eq(&key));
+    assert!(s.ssl().peer_certificate().is_none());
+}
 
-        buf
+#[test]
+#[cfg(ossl111)]
+fn key_update() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| {
+        let mut buf = [0; 1];
+        s.read_exact(&mut buf).unwrap();
+        s.write_all(&buf).unwrap();
     });
+    let server = server.build();
 
-    let stream = TcpStream::connect(addr).unwrap();
-    let ctx = SslContext::builder(SslMethod::tls()).unwrap();
-    let ssl = Ssl::new(&ctx.build()).unwrap();
-    let mut stream = ssl.connect(stream).unwrap();
-
-    let mut buf = [1; 32];
-    stream
-        .ssl()
-        .export_keying_material(&mut buf, label, Some(context))
-        .unwrap();
-
-    stream.read_exact(&mut [0]).unwrap();
-
-    let buf2 = guard.join().unwrap();
+    let mut s = server.client().connect();
+    assert_eq!(s.ssl().key_update_type(), KeyUpdateType::NONE);
+    s.ssl_mut().key_update(KeyUpdateType::REQUESTED).unwrap();
+    assert_eq!(s.ssl().key_update_type(), KeyUpdateType::REQUESTED);
+    s.write_all(&[1]).unwrap();
+    assert_eq!(s.ssl().key_update_type(), KeyUpdateType::NONE);
 
-    assert_eq!(buf, buf2);
+    let mut buf = [0; 1];
+    s.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [1]);
 }
 
 #[test]
-#[cfg(any(ossl110, libressl261))]
-fn no_version_overlap() {
+#[cfg(ossl111)]
+fn renegotiate() {
     let mut server = Server::builder();
-    server.ctx().set_min_proto_version(None).unwrap();
+    #[cfg(ossl300)]
     server
         .ctx()
-        .set_max_proto_version(Some(SslVersion::TLS1_1))
-        .unwrap();
-    #[cfg(any(ossl110g, libressl270))]
-    assert_eq!(server.ctx().max_proto_version(), Some(SslVersion::TLS1_1));
-    server.should_error();
+        .set_options(SslOptions::ALLOW_CLIENT_RENEGOTIATION);
+    server.io_cb(|mut s| {
+        s.read_exact(&mut [0]).unwrap();
+        assert!(!s.ssl().renegotiate_pending());
+    });
     let server = server.build();
 
     let mut client = server.client();
     client
         .ctx()
-        .set_min_proto_version(Some(SslVersion::TLS1_2))
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
         .unwrap();
-    #[cfg(ossl110g)]
-    assert_eq!(client.ctx().min_proto_version(), Some(SslVersion::TLS1_2));
-    client.ctx().set_max_proto_version(None).unwrap();
-
-    client.connect_err();
+    let mut s = client.connect();
+    assert!(!s.ssl().renegotiate_pending());
+    s.ssl_mut().renegotiate().unwrap();
+    assert!(s.ssl().renegotiate_pending());
+    s.do_handshake().unwrap();
+    assert!(!s.ssl().renegotiate_pending());
+    s.write_all(&[0]).unwrap();
 }
 
 #[test]
 #[cfg(ossl111)]
-fn custom_extensions() {
-    static FOUND_EXTENSION: AtomicBool = AtomicBool::new(false);
+fn negotiated_parameters() {
+    let mut server = Server::builder();
+    server.io_cb(|s| {
+        let sigalgs = s.ssl().shared_sigalgs();
+        assert!(sigalgs.iter().any(|sigalg| sigalg.code_point() == 0x0804
+            && sigalg.signature_type() == Nid::RSASSAPSS
+            && sigalg.digest() == Nid::SHA256));
+        assert_eq!(s.ssl().signature_type(), Some(Nid::RSASSAPSS));
+        assert_eq!(s.ssl().peer_signature_type(), None);
+    });
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_groups_list("X25519").unwrap();
+    let s = client.connect();
+
+    assert_eq!(s.ssl().peer_signature_type(), Some(Nid::RSASSAPSS));
+    assert_eq!(s.ssl().peer_signature_digest(), Some(Nid::SHA256));
+    assert_eq!(s.ssl().signature_type(), None);
+    assert_eq!(s.ssl().peer_tmp_key().unwrap().id(), Id::X25519);
+    #[cfg(ossl300)]
+    assert_eq!(
+        s.ssl().negotiated_group(),
+        Some(Nid::from_raw(Id::X25519.as_raw()))
+    );
+    #[cfg(ossl320)]
+    assert!(s
+        .ssl()
+        .negotiated_group_name()
+        .unwrap()
+        .eq_ignore_ascii_case("X25519"));
+    assert!(s.ssl().session().unwrap().has_ticket());
+}
 
+#[test]
+#[cfg(ossl111)]
+fn groups() {
     let mut server = Server::builder();
     server
         .ctx()
-        .add_custom_ext(
-            12345,
-            ExtensionContext::CLIENT_HELLO,
-            |_, _, _| -> Result<Option<&'static [u8]>, _> { unreachable!() },
-            |_, _, data, _| {
-                FOUND_EXTENSION.store(data == b"hello", Ordering::SeqCst);
-                Ok(())
-            },
-        )
+        .set_groups(&[Nid::SECP384R1, Nid::X9_62_PRIME256V1])
         .unwrap();
+    server
+        .ctx()
+        .set_options(SslOptions::CIPHER_SERVER_PREFERENCE);
+    server.io_cb(|s| {
+        assert_eq!(
+            s.ssl().peer_groups(),
+            vec![Nid::X9_62_PRIME256V1, Nid::SECP384R1]
+        );
+        assert_eq!(
+            s.ssl().shared_groups(),
+            vec![Nid::SECP384R1, Nid::X9_62_PRIME256V1]
+        );
+    });
+    let server = server.build();
+
+    let mut client = server.client().build().builder();
+    client.ssl().set_groups_list("P-256:P-384").unwrap();
+    let s = client.connect();
+
+    assert!(s.ssl().shared_groups().is_empty());
+    #[cfg(ossl300)]
+    assert_eq!(s.ssl().negotiated_group(), Some(Nid::X9_62_PRIME256V1));
+}
+
+#[test]
+#[cfg(ossl110)]
+fn dane_ee_spki() {
+    use crate::hash;
+    use crate::ssl::{TlsaMatchingType, TlsaSelector, TlsaUsage};
+
+    let cert = X509::from_pem(CERT).unwrap();
+    let spki = cert.public_key().unwrap().public_key_to_der().unwrap();
+    let digest = hash::hash(MessageDigest::sha256(), &spki).unwrap();
+
+    let server = Server::builder().build();
 
+    let mut client = server.client();
+    client.ctx().dane_enable().unwrap();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+    let mut client = client.build().builder();
+    client.ssl().dane_enable("foobar.com").unwrap();
+    assert!(client
+        .ssl()
+        .dane_tlsa_add(
+            TlsaUsage::DANE_EE,
+            TlsaSelector::SPKI,
+            TlsaMatchingType::SHA2_256,
+            &digest,
+        )
+        .unwrap());
+    let s = client.connect();
+
+    let tlsa = s.ssl().dane_tlsa().unwrap();
+    assert_eq!(tlsa.depth(), 0);
+    assert_eq!(tlsa.usage(), TlsaUsage::DANE_EE);
+    assert_eq!(tlsa.selector(), TlsaSelector::SPKI);
+    assert_eq!(tlsa.matching_type(), TlsaMatchingType::SHA2_256);
+    assert_eq!(tlsa.data(), &*digest);
+
+    let authority = s.ssl().dane_authority().unwrap();
+    assert_eq!(authority.depth(), 0);
+    assert_eq!(
+        authority.certificate().unwrap().to_der().unwrap(),
+        cert.to_der().unwrap()
+    );
+    assert!(authority.public_key().is_none());
+}
+
+#[test]
+#[cfg(ossl110)]
+fn dane_mismatch() {
+    use crate::ssl::{TlsaMatchingType, TlsaSelector, TlsaUsage};
+
+    let mut server = Server::builder();
+    server.should_error();
     let server = server.build();
 
     let mut client = server.client();
+    client.ctx().dane_enable().unwrap();
+    client.ctx().set_ca_file("test/root-ca.pem").unwrap();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+    let mut client = client.build().builder();
+    client.ssl().dane_enable("foobar.com").unwrap();
     client
-        .ctx()
-        .add_custom_ext(
-            12345,
-            ssl::ExtensionContext::CLIENT_HELLO,
-            |_, _, _| Ok(Some(b"hello")),
-            |_, _, _, _| unreachable!(),
+        .ssl()
+        .dane_tlsa_add(
+            TlsaUsage::DANE_EE,
+            TlsaSelector::SPKI,
+            TlsaMatchingType::SHA2_256,
+            &[0; 32],
         )
         .unwrap();
-
-    client.connect();
-
-    assert!(FOUND_EXTENSION.load(Ordering::SeqCst));
+    client.connect_err();
 }
 
-fn _check_kinds() {
-    fn is_send<T: Send>() {}
-    fn is_sync<T: Sync>() {}
+#[test]
+fn client_ca_list() {
+    let names = X509Name::load_client_ca_file("test/root-ca.pem").unwrap();
+    assert_eq!(names.len(), 1);
 
-    is_send::<SslStream<TcpStream>>();
-    is_sync::<SslStream<TcpStream>>();
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_client_ca_list(names);
 }
 
 #[test]
-#[cfg(ossl111)]
-fn stateless() {
-    use super::SslOptions;
+fn cert_store() {
+    let server = Server::builder().build();
 
-    #[derive(Debug)]
+    let mut client = server.client();
+    let cert = X509::from_pem(ROOT_CERT).unwrap();
+    client.ctx().cert_store_mut().add_cert(cert).unwrap();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+
+    client.connect();
+}
+
+#[test]
+#[cfg_attr(any(libressl321, boringssl), ignore)]
+fn tmp_dh_callback() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ctx().set_tmp_dh_callback(|_, _, _| {
+        CALLED_BACK.store(true, Ordering::SeqCst);
+        let dh = include_bytes!("../../../test/dhparams.pem");
+        Dh::params_from_pem(dh)
+    });
+
+    let server = server.build();
+
+    let mut client = server.client();
+    // TLS 1.3 has no DH suites, so make sure we don't pick that version
+    #[cfg(ossl111)]
+    client.ctx().set_options(super::SslOptions::NO_TLSV1_3);
+    client.ctx().set_cipher_list("EDH").unwrap();
+    client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg(all(ossl101, not(ossl110)))]
+#[allow(deprecated)]
+fn tmp_ecdh_callback() {
+    use crate::ec::EcKey;
+    use crate::nid::Nid;
+
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ctx().set_tmp_ecdh_callback(|_, _, _| {
+        CALLED_BACK.store(true, Ordering::SeqCst);
+        EcKey::from_curve_name(Nid::X9_62_PRIME256V1)
+    });
+
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_cipher_list("ECDH").unwrap();
+    client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg_attr(any(libressl321, boringssl), ignore)]
+fn tmp_dh_callback_ssl() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ssl_cb(|ssl| {
+        ssl.set_tmp_dh_callback(|_, _, _| {
+            CALLED_BACK.store(true, Ordering::SeqCst);
+            let dh = include_bytes!("../../../test/dhparams.pem");
+            Dh::params_from_pem(dh)
+        });
+    });
+
+    let server = server.build();
+
+    let mut client = server.client();
+    // TLS 1.3 has no DH suites, so make sure we don't pick that version
+    #[cfg(ossl111)]
+    client.ctx().set_options(super::SslOptions::NO_TLSV1_3);
+    client.ctx().set_cipher_list("EDH").unwrap();
+    client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg(all(ossl101, not(ossl110)))]
+#[allow(deprecated)]
+fn tmp_ecdh_callback_ssl() {
+    use crate::ec::EcKey;
+    use crate::nid::Nid;
+
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ssl_cb(|ssl| {
+        ssl.set_tmp_ecdh_callback(|_, _, _| {
+            CALLED_BACK.store(true, Ordering::SeqCst);
+            EcKey::from_curve_name(Nid::X9_62_PRIME256V1)
+        });
+    });
+
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_cipher_list("ECDH").unwrap();
+    client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+fn idle_session() {
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let ssl = Ssl::new(&ctx).unwrap();
+    assert!(ssl.session().is_none());
+}
+
+#[test]
+#[cfg_attr(libressl321, ignore)]
+fn active_session() {
+    let server = Server::builder().build();
+
+    let s = server.client().connect();
+
+    let session = s.ssl().session().unwrap();
+    let len = session.master_key_len();
+    let mut buf = vec![0; len - 1];
+    let copied = session.master_key(&mut buf);
+    assert_eq!(copied, buf.len());
+    let mut buf = vec![0; len + 1];
+    let copied = session.master_key(&mut buf);
+    assert_eq!(copied, len);
+}
+
+#[test]
+#[cfg(not(boringssl))]
+fn status_callbacks() {
+    static CALLED_BACK_SERVER: AtomicBool = AtomicBool::new(false);
+    static CALLED_BACK_CLIENT: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_status_callback(|ssl| {
+            CALLED_BACK_SERVER.store(true, Ordering::SeqCst);
+            let response = OcspResponse::create(OcspResponseStatus::UNAUTHORIZED, None).unwrap();
+            let response = response.to_der().unwrap();
+            ssl.set_ocsp_status(&response).unwrap();
+            Ok(true)
+        })
+        .unwrap();
+
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_status_callback(|ssl| {
+            CALLED_BACK_CLIENT.store(true, Ordering::SeqCst);
+            let response = OcspResponse::from_der(ssl.ocsp_status().unwrap()).unwrap();
+            assert_eq!(response.status(), OcspResponseStatus::UNAUTHORIZED);
+            Ok(true)
+        })
+        .unwrap();
+
+    let mut client = client.build().builder();
+    client.ssl().set_status_type(StatusType::OCSP).unwrap();
+
+    client.connect();
+
+    assert!(CALLED_BACK_SERVER.load(Ordering::SeqCst));
+    assert!(CALLED_BACK_CLIENT.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg(not(boringssl))]
+fn ocsp_response_callback() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_status_callback(|ssl| {
+            let response = OcspResponse::create(OcspResponseStatus::UNAUTHORIZED, None).unwrap();
+            ssl.set_ocsp_status(&response.to_der().unwrap()).unwrap();
+            Ok(true)
+        })
+        .unwrap();
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_verify_callback(SslVerifyMode::PEER, |_, _| true);
+    client
+        .ctx()
+        .set_ocsp_response_callback(|ssl, response| {
+            CALLED_BACK.store(true, Ordering::SeqCst);
+            assert_eq!(response.unwrap().status(), OcspResponseStatus::UNAUTHORIZED);
+            #[cfg(ossl110)]
+            assert!(ssl.verified_chain().is_some());
+            assert!(ssl.peer_certificate().is_some());
+            Ok(true)
+        })
+        .unwrap();
+
+    let mut client = client.build().builder();
+    client.ssl().set_status_type(StatusType::OCSP).unwrap();
+    client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg(not(boringssl))]
+fn connector_ocsp_response_callback() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_verify_callback(SslVerifyMode::PEER, |_, _| true);
+    connector.enable_ocsp_response_callbacks().unwrap();
+    let connector = connector.build();
+
+    let mut config = connector.configure().unwrap().verify_hostname(false);
+    config
+        .set_ocsp_response_callback(|_, response| Ok(response.is_some()))
+        .unwrap();
+    config
+        .connect("foobar.com", server.connect_tcp())
+        .unwrap_err();
+}
+
+#[test]
+#[cfg(ossl110)]
+fn session_cache_stats() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut server_ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    server_ctx
+        .set_certificate_file("test/cert.pem", SslFiletype::PEM)
+        .unwrap();
+    server_ctx
+        .set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    server_ctx.set_session_id_context(b"foo").unwrap();
+    server_ctx.set_options(SslOptions::NO_TICKET);
+    server_ctx
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let server_ctx = server_ctx.build();
+
+    let guard = thread::spawn({
+        let server_ctx = server_ctx.clone();
+        move || {
+            for _ in 0..2 {
+                let stream = listener.accept().unwrap().0;
+                let mut stream = Ssl::new(&server_ctx).unwrap().accept(stream).unwrap();
+                stream.write_all(&[0]).unwrap();
+                // Sessions of connections which are not shut down cleanly are evicted.
+                stream.shutdown().unwrap();
+            }
+        }
+    });
+
+    let client_ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut stream = Ssl::new(&client_ctx).unwrap().connect(stream).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    let session = stream.ssl().session().unwrap().to_owned();
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut ssl = Ssl::new(&client_ctx).unwrap();
+    unsafe { ssl.set_session(&session).unwrap() };
+    let mut stream = ssl.connect(stream).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    assert!(stream.ssl().session_reused());
+
+    guard.join().unwrap();
+
+    let stats = server_ctx.session_cache_stats();
+    assert_eq!(stats.size(), 1);
+    assert_eq!(stats.accepts(), 2);
+    assert_eq!(stats.accepts_good(), 2);
+    assert_eq!(stats.hits(), 1);
+    assert_eq!(stats.timeouts(), 0);
+    assert_eq!(stats.cache_full(), 0);
+
+    let stats = client_ctx.session_cache_stats();
+    assert_eq!(stats.connects(), 2);
+    assert_eq!(stats.connects_good(), 2);
+    assert_eq!(stats.accepts(), 0);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn session_tickets() {
+    static GENERATED: AtomicUsize = AtomicUsize::new(0);
+    static RECEIVED: AtomicUsize = AtomicUsize::new(0);
+
+    let mut server = Server::builder();
+    server.ctx().set_num_tickets(3).unwrap();
+    server
+        .ctx()
+        .set_session_ticket_generate_callback(|_| {
+            GENERATED.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        })
+        .unwrap();
+    server.ssl_cb(|s| assert_eq!(s.num_tickets(), 3));
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_session_cache_mode(SslSessionCacheMode::CLIENT | SslSessionCacheMode::NO_INTERNAL);
+    client.ctx().set_new_session_callback(|_, _| {
+        RECEIVED.fetch_add(1, Ordering::SeqCst);
+    });
+    client.connect();
+
+    assert_eq!(GENERATED.load(Ordering::SeqCst), 3);
+    assert_eq!(RECEIVED.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn external_private_key() {
+    use crate::external_key::KeyOperation;
+    use crate::rsa::{Padding, Rsa};
+
+    static SIGNED: AtomicBool = AtomicBool::new(false);
+
+    let rsa = Rsa::private_key_from_pem(include_bytes!("../../../test/key.pem")).unwrap();
+    let public_key = PKey::from_rsa(rsa.clone()).unwrap();
+    let key = PKey::from_signer(&public_key, move |op| match op {
+        // TLS 1.3 requires RSASSA-PSS, which is padded before reaching the signer
+        KeyOperation::RsaRaw { input } => {
+            SIGNED.store(true, Ordering::SeqCst);
+            let mut out = vec![0; rsa.size() as usize];
+            rsa.private_encrypt(input, &mut out, Padding::NONE)?;
+            Ok(out)
+        }
+        op => panic!("unexpected operation {:?}", op),
+    })
+    .unwrap();
+
+    let mut server = Server::builder();
+    server.ctx().set_private_key(&key).unwrap();
+    let server = server.build();
+
+    let s = server.client().connect();
+    assert_eq!(s.ssl().version2(), Some(SslVersion::TLS1_3));
+    assert!(SIGNED.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg(ossl111)]
+fn session_introspection() {
+    let server = Server::builder().build();
+    let s = server.client().connect();
+
+    let session = s.ssl().session().unwrap();
+    assert!(session.has_ticket());
+    assert!(session.is_resumable());
+    assert!(session.ticket().is_some());
+    assert!(session.ticket_lifetime_hint() > 0);
+    assert_eq!(session.protocol_version(), s.ssl().version2().unwrap());
+    assert_eq!(
+        session.cipher().unwrap().name(),
+        s.ssl().current_cipher().unwrap().name()
+    );
+    assert!(!s.ssl().session_reused());
+}
+
+#[test]
+#[cfg(ossl111)]
+fn session_tickets_disabled_per_connection() {
+    static GENERATED: AtomicUsize = AtomicUsize::new(0);
+
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_session_ticket_generate_callback(|_| {
+            GENERATED.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        })
+        .unwrap();
+    server.ssl_cb(|s| {
+        assert!(!s.options().contains(SslOptions::NO_TICKET));
+        let options = s.set_options(SslOptions::NO_TICKET);
+        assert!(options.contains(SslOptions::NO_TICKET));
+        s.set_num_tickets(1).unwrap();
+    });
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let s = client.connect();
+    assert!(!s.ssl().session().unwrap().has_ticket());
+
+    assert_eq!(GENERATED.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+#[cfg_attr(libressl321, ignore)]
+fn new_session_callback() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ctx().set_session_id_context(b"foo").unwrap();
+
+    let server = server.build();
+
+    let mut client = server.client();
+
+    client
+        .ctx()
+        .set_session_cache_mode(SslSessionCacheMode::CLIENT | SslSessionCacheMode::NO_INTERNAL);
+    client
+        .ctx()
+        .set_new_session_callback(|_, _| CALLED_BACK.store(true, Ordering::SeqCst));
+
+    client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg_attr(libressl321, ignore)]
+fn new_session_callback_swapped_ctx() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ctx().set_session_id_context(b"foo").unwrap();
+
+    let server = server.build();
+
+    let mut client = server.client();
+
+    client
+        .ctx()
+        .set_session_cache_mode(SslSessionCacheMode::CLIENT | SslSessionCacheMode::NO_INTERNAL);
+    client
+        .ctx()
+        .set_new_session_callback(|_, _| CALLED_BACK.store(true, Ordering::SeqCst));
+
+    let mut client = client.build().builder();
+
+    let ctx = SslContextBuilder::new(SslMethod::tls()).unwrap().build();
+    client.ssl().set_ssl_context(&ctx).unwrap();
+
+    client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+fn keying_export() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let label = "EXPERIMENTAL test";
+    let context = b"my context";
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+            .unwrap();
+        ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+            .unwrap();
+        let ssl = Ssl::new(&ctx.build()).unwrap();
+        let mut stream = ssl.accept(stream).unwrap();
+
+        let mut buf = [0; 32];
+        stream
+            .ssl()
+            .export_keying_material(&mut buf, label, Some(context))
+            .unwrap();
+
+        stream.write_all(&[0]).unwrap();
+
+        buf
+    });
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    let ssl = Ssl::new(&ctx.build()).unwrap();
+    let mut stream = ssl.connect(stream).unwrap();
+
+    let mut buf = [1; 32];
+    stream
+        .ssl()
+        .export_keying_material(&mut buf, label, Some(context))
+        .unwrap();
+
+    stream.read_exact(&mut [0]).unwrap();
+
+    let buf2 = guard.join().unwrap();
+
+    assert_eq!(buf, buf2);
+}
+
+#[test]
+#[cfg(any(ossl110, libressl261))]
+fn no_version_overlap() {
+    let mut server = Server::builder();
+    server.ctx().set_min_proto_version(None).unwrap();
+    server
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_1))
+        .unwrap();
+    #[cfg(any(ossl110g, libressl270))]
+    assert_eq!(server.ctx().max_proto_version(), Some(SslVersion::TLS1_1));
+    server.should_error();
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_min_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    #[cfg(ossl110g)]
+    assert_eq!(client.ctx().min_proto_version(), Some(SslVersion::TLS1_2));
+    client.ctx().set_max_proto_version(None).unwrap();
+
+    client.connect_err();
+}
+
+#[test]
+#[cfg(ossl111)]
+fn custom_extensions() {
+    static FOUND_EXTENSION: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .add_custom_ext(
+            12345,
+            ExtensionContext::CLIENT_HELLO,
+            |_, _, _| -> Result<Option<&'static [u8]>, _> { unreachable!() },
+            |_, _, data, _| {
+                FOUND_EXTENSION.store(data == b"hello", Ordering::SeqCst);
+                Ok(())
+            },
+        )
+        .unwrap();
+
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .add_custom_ext(
+            12345,
+            ssl::ExtensionContext::CLIENT_HELLO,
+            |_, _, _| Ok(Some(b"hello")),
+            |_, _, _, _| unreachable!(),
+        )
+        .unwrap();
+
+    client.connect();
+
+    assert!(FOUND_EXTENSION.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg(ossl111)]
+fn custom_extensions_shared_callback_type() {
+    // Every call registers closures of the same types, which must not replace one another.
+    fn add_ext(ctx: &mut SslContextBuilder, ext_type: u16, found: &'static AtomicBool) {
+        let data = ext_type.to_be_bytes().to_vec();
+        let expected = data.clone();
+        ctx.add_custom_ext(
+            ext_type,
+            ExtensionContext::CLIENT_HELLO,
+            move |_, _, _| Ok(Some(data.clone())),
+            move |_, _, data, _| {
+                found.store(data == &*expected, Ordering::SeqCst);
+                Ok(())
+            },
+        )
+        .unwrap();
+    }
+
+    static FOUND_A: AtomicBool = AtomicBool::new(false);
+    static FOUND_B: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    add_ext(server.ctx(), 12345, &FOUND_A);
+    add_ext(server.ctx(), 12346, &FOUND_B);
+    let server = server.build();
+
+    static UNUSED: AtomicBool = AtomicBool::new(false);
+    let mut client = server.client();
+    add_ext(client.ctx(), 12345, &UNUSED);
+    add_ext(client.ctx(), 12346, &UNUSED);
+    client.connect();
+
+    assert!(FOUND_A.load(Ordering::SeqCst));
+    assert!(FOUND_B.load(Ordering::SeqCst));
+}
+
+fn _check_kinds() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    is_send::<SslStream<TcpStream>>();
+    is_sync::<SslStream<TcpStream>>();
+}
+
+#[test]
+#[cfg(ossl111)]
+fn stateless() {
+    use super::SslOptions;
+
+    #[derive(Debug)]
     struct MemoryStream {
         incoming: io::Cursor<Vec<u8>>,
         outgoing: Vec<u8>,
     }
 
-    impl MemoryStream {
-        pub fn new() -> Self {
-            Self {
-                incoming: io::Cursor::new(Vec::new()),
-                outgoing: Vec::new(),
-            }
-        }
+    impl MemoryStream {
+        pub fn new() -> Self {
+            Self {
+                incoming: io::Cursor::new(Vec::new()),
+                outgoing: Vec::new(),
+            }
+        }
+
+        pub fn extend_incoming(&mut self, data: &[u8]) {
+            self.incoming.get_mut().extend_from_slice(data);
+        }
+
+        pub fn take_outgoing(&mut self) -> Outgoing<'_> {
+            Outgoing(&mut self.outgoing)
+        }
+    }
+
+    impl Read for MemoryStream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.incoming.read(buf)?;
+            if self.incoming.position() == self.incoming.get_ref().len() as u64 {
+                self.incoming.set_position(0);
+                self.incoming.get_mut().clear();
+            }
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    "no data available",
+                ));
+            }
+            Ok(n)
+        }
+    }
+
+    impl Write for MemoryStream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.outgoing.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    pub struct Outgoing<'a>(&'a mut Vec<u8>);
+
+    impl<'a> Drop for Outgoing<'a> {
+        fn drop(&mut self) {
+            self.0.clear();
+        }
+    }
+
+    impl<'a> ::std::ops::Deref for Outgoing<'a> {
+        type Target = [u8];
+        fn deref(&self) -> &[u8] {
+            self.0
+        }
+    }
+
+    impl<'a> AsRef<[u8]> for Outgoing<'a> {
+        fn as_ref(&self) -> &[u8] {
+            self.0
+        }
+    }
+
+    fn send(from: &mut MemoryStream, to: &mut MemoryStream) {
+        to.extend_incoming(&from.take_outgoing());
+    }
+
+    //
+    // Setup
+    //
+
+    let mut client_ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    client_ctx.clear_options(SslOptions::ENABLE_MIDDLEBOX_COMPAT);
+    let mut client_stream =
+        SslStream::new(Ssl::new(&client_ctx.build()).unwrap(), MemoryStream::new()).unwrap();
+
+    let mut server_ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    server_ctx
+        .set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    server_ctx
+        .set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    const COOKIE: &[u8] = b"chocolate chip";
+    server_ctx.set_stateless_cookie_generate_cb(|_tls, buf| {
+        buf[0..COOKIE.len()].copy_from_slice(COOKIE);
+        Ok(COOKIE.len())
+    });
+    server_ctx.set_stateless_cookie_verify_cb(|_tls, buf| buf == COOKIE);
+    let mut server_stream =
+        SslStream::new(Ssl::new(&server_ctx.build()).unwrap(), MemoryStream::new()).unwrap();
+
+    //
+    // Handshake
+    //
+
+    // Initial ClientHello
+    client_stream.connect().unwrap_err();
+    send(client_stream.get_mut(), server_stream.get_mut());
+    // HelloRetryRequest
+    assert!(!server_stream.stateless().unwrap());
+    send(server_stream.get_mut(), client_stream.get_mut());
+    // Second ClientHello
+    client_stream.do_handshake().unwrap_err();
+    send(client_stream.get_mut(), server_stream.get_mut());
+    // OldServerHello
+    assert!(server_stream.stateless().unwrap());
+    server_stream.accept().unwrap_err();
+    send(server_stream.get_mut(), client_stream.get_mut());
+    // Finished
+    client_stream.do_handshake().unwrap();
+    send(client_stream.get_mut(), server_stream.get_mut());
+    server_stream.do_handshake().unwrap();
+}
+
+#[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
+#[test]
+fn psk_ciphers() {
+    const CIPHER: &str = "PSK-AES256-CBC-SHA";
+    const PSK: &[u8] = b"thisisaverysecurekey";
+    const CLIENT_IDENT: &[u8] = b"thisisaclient";
+    static CLIENT_CALLED: AtomicBool = AtomicBool::new(false);
+    static SERVER_CALLED: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ctx().set_cipher_list(CIPHER).unwrap();
+    server.ctx().set_psk_server_callback(|_, identity, psk| {
+        eprintln!("hello???!");
+        assert!(identity.unwrap_or(&[]) == CLIENT_IDENT);
+        eprintln!("hello???!");
+        psk[..PSK.len()].copy_from_slice(PSK);
+        SERVER_CALLED.store(true, Ordering::SeqCst);
+        Ok(PSK.len())
+    });
+
+    let server = server.build();
+
+    let mut client = server.client();
+    // This test relies on TLS 1.2 suites
+    #[cfg(ossl111)]
+    client.ctx().set_options(super::SslOptions::NO_TLSV1_3);
+    client.ctx().set_cipher_list(CIPHER).unwrap();
+    client
+        .ctx()
+        .set_psk_client_callback(move |_, _, identity, psk| {
+            eprintln!("hello???! 2");
+            identity[..CLIENT_IDENT.len()].copy_from_slice(CLIENT_IDENT);
+            identity[CLIENT_IDENT.len()] = 0;
+            psk[..PSK.len()].copy_from_slice(PSK);
+            CLIENT_CALLED.store(true, Ordering::SeqCst);
+            Ok(PSK.len())
+        });
+
+    client.connect();
+
+    assert!(SERVER_CALLED.load(Ordering::SeqCst));
+    assert!(CLIENT_CALLED.load(Ordering::SeqCst));
+}
+
+#[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
+#[test]
+fn psk_identity_hint() {
+    const CIPHER: &str = "PSK-AES256-CBC-SHA";
+    const PSK: &[u8] = b"thisisaverysecurekey";
+    const CLIENT_IDENT: &[u8] = b"thisisaclient";
+
+    let mut server = Server::builder();
+    server.ctx().set_cipher_list(CIPHER).unwrap();
+    server.ctx().set_psk_identity_hint("thisisahint").unwrap();
+    server.ctx().set_psk_server_callback(|_, _, psk| {
+        psk[..PSK.len()].copy_from_slice(PSK);
+        Ok(PSK.len())
+    });
+    server.io_cb(|s| assert_eq!(s.ssl().psk_identity(), Some(CLIENT_IDENT)));
+    let server = server.build();
+
+    let mut client = server.client();
+    #[cfg(ossl111)]
+    client.ctx().set_options(super::SslOptions::NO_TLSV1_3);
+    client.ctx().set_cipher_list(CIPHER).unwrap();
+    client
+        .ctx()
+        .set_psk_client_callback(move |_, hint, identity, psk| {
+            assert_eq!(hint, Some(&b"thisisahint"[..]));
+            identity[..CLIENT_IDENT.len()].copy_from_slice(CLIENT_IDENT);
+            identity[CLIENT_IDENT.len()] = 0;
+            psk[..PSK.len()].copy_from_slice(PSK);
+            Ok(PSK.len())
+        });
+
+    let s = client.connect();
+    assert_eq!(s.ssl().psk_identity_hint(), Some(&b"thisisahint"[..]));
+}
+
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_SRP")))]
+#[test]
+fn srp() {
+    use crate::bn::BigNum;
+    use crate::srp::{SrpGroup, SrpVerifier};
+
+    const CIPHER: &str = "SRP-AES-256-CBC-SHA";
+
+    let group = SrpGroup::rfc5054(2048).unwrap();
+    let verifier = SrpVerifier::generate("alice", "password123", &group).unwrap();
+    let verifier = SrpVerifier::from_parts(
+        BigNum::from_slice(&verifier.salt().to_vec()).unwrap(),
+        BigNum::from_slice(&verifier.verifier().to_vec()).unwrap(),
+    );
+
+    let mut server = Server::builder();
+    server.ctx().set_cipher_list(CIPHER).unwrap();
+    server.ctx().set_srp_username_callback(move |ssl| {
+        if ssl.srp_username() != Some("alice") {
+            return Err(SslAlert::UNKNOWN_PSK_IDENTITY);
+        }
+        ssl.set_srp_server_param(&group, &verifier)
+            .map_err(|_| SslAlert::INTERNAL_ERROR)
+    });
+    server.io_cb(|s| assert_eq!(s.ssl().srp_username(), Some("alice")));
+    let server = server.build();
+
+    let mut client = server.client();
+    #[cfg(ossl111)]
+    client.ctx().set_options(super::SslOptions::NO_TLSV1_3);
+    client.ctx().set_cipher_list(CIPHER).unwrap();
+    client.ctx().set_srp_strength(2048).unwrap();
+    client.ctx().set_srp_username("alice").unwrap();
+    client.ctx().set_srp_password("password123").unwrap();
+
+    let s = client.connect();
+    assert_eq!(s.ssl().current_cipher().unwrap().name(), CIPHER);
+}
+
+#[test]
+fn sni_callback_swapped_ctx() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_servername_callback(|_, _| {
+        CALLED_BACK.store(true, Ordering::SeqCst);
+        Ok(())
+    });
+
+    let keyed_ctx = mem::replace(server.ctx(), ctx).build();
+    server.ssl_cb(move |ssl| ssl.set_ssl_context(&keyed_ctx).unwrap());
+
+    let server = server.build();
+
+    server.client().connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+fn client_cert_callback() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_verify_callback(SslVerifyMode::PEER, |_, _| true);
+    server.io_cb(|s| assert!(s.ssl().peer_certificate().is_some()));
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_client_cert_callback(|_| {
+        CALLED_BACK.store(true, Ordering::SeqCst);
+        let cert = X509::from_pem(CERT).unwrap();
+        let key = PKey::private_key_from_pem(KEY).unwrap();
+        Some((cert, key))
+    });
+    client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
 
-        pub fn extend_incoming(&mut self, data: &[u8]) {
-            self.incoming.get_mut().extend_from_slice(data);
-        }
+#[test]
+#[cfg(ossl102)]
+fn cert_callback() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
 
-        pub fn take_outgoing(&mut self) -> Outgoing<'_> {
-            Outgoing(&mut self.outgoing)
+    let mut server = Server::builder();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_cert_callback(|ssl| {
+        assert_eq!(ssl.servername(ssl::NameType::HOST_NAME), Some("foobar.com"));
+
+        let cert = X509::from_pem(CERT).unwrap();
+        let key = PKey::private_key_from_pem(KEY).unwrap();
+        let root = X509::from_pem(ROOT_CERT).unwrap();
+        ssl.set_certificate(&cert)?;
+        ssl.set_private_key(&key)?;
+        ssl.add_chain_cert(&root)?;
+
+        CALLED_BACK.store(true, Ordering::SeqCst);
+        Ok(CertCallbackResponse::SUCCESS)
+    });
+    *server.ctx() = ctx;
+
+    let server = server.build();
+
+    let mut client = server.client().build().builder();
+    client.ssl().set_hostname("foobar.com").unwrap();
+    let s = client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+    let peer = s.ssl().peer_certificate().unwrap();
+    let cert = X509::from_pem(CERT).unwrap();
+    assert_eq!(
+        peer.digest(MessageDigest::sha256()).unwrap().to_vec(),
+        cert.digest(MessageDigest::sha256()).unwrap().to_vec()
+    );
+    assert_eq!(s.ssl().peer_cert_chain().unwrap().len(), 2);
+}
+
+#[test]
+#[cfg(any(ossl110, libressl261))]
+fn tls_unique() {
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    server.io_cb(|mut s| {
+        let tls_unique = s.ssl().tls_unique().unwrap();
+        s.write_all(&tls_unique).unwrap();
+    });
+    let server = server.build();
+
+    let mut s = server.client().connect();
+    let tls_unique = s.ssl().tls_unique().unwrap();
+    assert_eq!(tls_unique.len(), 12);
+    let mut buf = [0; 12];
+    s.read_exact(&mut buf).unwrap();
+    assert_eq!(tls_unique, buf);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn client_hello() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ctx().set_client_hello_callback(|ssl, _| {
+        assert!(!ssl.client_hello_isv2());
+        assert_eq!(ssl.client_hello_legacy_version(), Some(SslVersion::TLS1_2));
+        assert!(ssl.client_hello_random().is_some());
+        assert!(ssl.client_hello_session_id().is_some());
+        assert!(ssl.client_hello_ciphers().is_some());
+        assert!(ssl.client_hello_compression_methods().is_some());
+
+        CALLED_BACK.store(true, Ordering::SeqCst);
+        Ok(ClientHelloResponse::SUCCESS)
+    });
+
+    let server = server.build();
+    server.client().connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg(ossl111)]
+fn client_hello_inspection() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ctx().set_client_hello_callback(|ssl, _| {
+        assert_eq!(ssl.client_hello_servername(), Some("foobar.com"));
+        assert_eq!(
+            ssl.client_hello_alpn_protocols(),
+            Some(vec![&b"http/1.1"[..], &b"spdy/3.1"[..]])
+        );
+
+        let types = ssl.client_hello_extension_types().unwrap();
+        assert!(types.contains(&0));
+        assert!(types.contains(&16));
+        assert_eq!(ssl.client_hello_extension(0xfafa), None);
+        assert!(ssl.client_hello_extension(16).is_some());
+
+        let ids = ssl.client_hello_cipher_ids().unwrap();
+        let ciphers = ssl.client_hello_cipher_list().unwrap();
+        assert!(!ciphers.is_empty());
+        for cipher in &ciphers {
+            assert!(ids.contains(&cipher.protocol_id()));
         }
+        assert!(ciphers
+            .iter()
+            .any(|c| c.standard_name() == Some("TLS_AES_128_GCM_SHA256")));
+
+        CALLED_BACK.store(true, Ordering::SeqCst);
+        Ok(ClientHelloResponse::SUCCESS)
+    });
+
+    let server = server.build();
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_alpn_protos(b"\x08http/1.1\x08spdy/3.1")
+        .unwrap();
+    let mut client = client.build().builder();
+    client.ssl().set_hostname("foobar.com").unwrap();
+    client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg(ossl111)]
+fn openssl_cipher_name() {
+    assert_eq!(
+        super::cipher_name("TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384"),
+        "ECDHE-RSA-AES256-SHA384",
+    );
+
+    assert_eq!(super::cipher_name("asdf"), "(NONE)");
+}
+
+#[test]
+#[cfg(ossl111)]
+fn cipher_enumeration() {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_cipher_list("ECDHE-RSA-AES128-GCM-SHA256:AES256-SHA")
+        .unwrap();
+    ctx.set_ciphersuites("TLS_AES_128_GCM_SHA256").unwrap();
+    let ctx = ctx.build();
+
+    let names = |ciphers: &StackRef<SslCipher>| {
+        ciphers
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names(ctx.ciphers().unwrap()),
+        [
+            "TLS_AES_128_GCM_SHA256",
+            "ECDHE-RSA-AES128-GCM-SHA256",
+            "AES256-SHA"
+        ]
+    );
+
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    ssl.set_connect_state();
+    assert_eq!(names(ssl.ciphers().unwrap()), names(ctx.ciphers().unwrap()));
+    ssl.set_options(SslOptions::NO_TLSV1_3);
+    assert_eq!(
+        names(&ssl.supported_ciphers().unwrap()),
+        ["ECDHE-RSA-AES128-GCM-SHA256", "AES256-SHA"]
+    );
+
+    let ciphers = ctx.ciphers().unwrap();
+    let tls13 = &ciphers[0];
+    assert!(tls13.is_aead());
+    assert_eq!(tls13.kx_nid(), Some(Nid::KX_ANY));
+    assert_eq!(tls13.auth_nid(), Some(Nid::AUTH_ANY));
+    assert_eq!(tls13.id() & 0xffff, 0x1301);
+
+    let gcm = &ciphers[1];
+    assert!(gcm.is_aead());
+    assert_eq!(gcm.kx_nid(), Some(Nid::KX_ECDHE));
+    assert_eq!(gcm.auth_nid(), Some(Nid::AUTH_RSA));
+    assert_eq!(gcm.digest_nid(), None);
+
+    let cbc = &ciphers[2];
+    assert!(!cbc.is_aead());
+    assert_eq!(cbc.kx_nid(), Some(Nid::KX_RSA));
+    assert_eq!(cbc.auth_nid(), Some(Nid::AUTH_RSA));
+    assert_eq!(cbc.digest_nid(), Some(Nid::SHA1));
+}
+
+#[test]
+fn session_cache_size() {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_session_cache_size(1234);
+    let ctx = ctx.build();
+    assert_eq!(ctx.session_cache_size(), 1234);
+}
+
+fn pump(from: &mut SslMachine, to: &mut SslMachine) {
+    let mut buf = [0; 4096];
+    while from.wants_write() {
+        let len = from.write_tls(&mut buf);
+        to.read_tls(&buf[..len]).unwrap();
     }
+}
 
-    impl Read for MemoryStream {
-        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-            let n = self.incoming.read(buf)?;
-            if self.incoming.position() == self.incoming.get_ref().len() as u64 {
-                self.incoming.set_position(0);
-                self.incoming.get_mut().clear();
+fn machine_ctx() -> SslContextBuilder {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx
+}
+
+/// Runs a handshake between two in-memory sessions, returning `(client, server)`.
+fn machine_pair(client: Ssl, server: Ssl) -> (SslMachine, SslMachine) {
+    let mut client = SslMachine::new(client).unwrap();
+    let mut server = SslMachine::new(server).unwrap();
+
+    let mut client_done = false;
+    let mut server_done = false;
+    while !client_done || !server_done {
+        if !client_done {
+            match client.connect() {
+                Ok(()) => client_done = true,
+                Err(e) => assert_eq!(e.code(), ErrorCode::WANT_READ),
             }
-            if n == 0 {
-                return Err(io::Error::new(
-                    io::ErrorKind::WouldBlock,
-                    "no data available",
-                ));
+            pump(&mut client, &mut server);
+        }
+        if !server_done {
+            match server.accept() {
+                Ok(()) => server_done = true,
+                Err(e) => assert_eq!(e.code(), ErrorCode::WANT_READ),
             }
-            Ok(n)
+            pump(&mut server, &mut client);
         }
     }
 
-    impl Write for MemoryStream {
-        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-            self.outgoing.write(buf)
-        }
+    (client, server)
+}
 
-        fn flush(&mut self) -> io::Result<()> {
-            Ok(())
-        }
-    }
+#[test]
+#[cfg(ossl111)]
+fn machine_stateless() {
+    const COOKIE: &[u8] = b"oatmeal raisin";
 
-    pub struct Outgoing<'a>(&'a mut Vec<u8>);
+    let mut server_ctx = machine_ctx();
+    server_ctx.set_stateless_cookie_generate_cb(|_, buf| {
+        buf[..COOKIE.len()].copy_from_slice(COOKIE);
+        Ok(COOKIE.len())
+    });
+    server_ctx.set_stateless_cookie_verify_cb(|_, buf| buf == COOKIE);
+    let server_ctx = server_ctx.build();
 
-    impl<'a> Drop for Outgoing<'a> {
-        fn drop(&mut self) {
-            self.0.clear();
+    let mut client_ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    client_ctx.clear_options(SslOptions::ENABLE_MIDDLEBOX_COMPAT);
+    let client_ctx = client_ctx.build();
+
+    let mut client = SslMachine::new(Ssl::new(&client_ctx).unwrap()).unwrap();
+    let mut server = SslMachine::new(Ssl::new(&server_ctx).unwrap()).unwrap();
+
+    // Nothing has been received yet
+    assert_eq!(server.stateless().unwrap_err().code(), ErrorCode::WANT_READ);
+    assert!(!server.wants_write());
+
+    // Initial ClientHello is answered with a HelloRetryRequest
+    assert_eq!(client.connect().unwrap_err().code(), ErrorCode::WANT_READ);
+    pump(&mut client, &mut server);
+    assert!(!server.stateless().unwrap());
+    assert!(server.wants_write());
+    pump(&mut server, &mut client);
+
+    // Second ClientHello carries the cookie
+    assert_eq!(client.connect().unwrap_err().code(), ErrorCode::WANT_READ);
+    pump(&mut client, &mut server);
+    assert!(server.stateless().unwrap());
+
+    assert_eq!(server.accept().unwrap_err().code(), ErrorCode::WANT_READ);
+    pump(&mut server, &mut client);
+    client.connect().unwrap();
+    pump(&mut client, &mut server);
+    server.accept().unwrap();
+}
+
+#[test]
+#[cfg(ossl102)]
+fn sni_resolver() {
+    use super::{CertifiedKey, SniResolver};
+    use std::fs;
+
+    let dir = TempDir::new("sni_resolver").unwrap();
+    let cert_path = dir.path().join("cert.pem");
+    let key_path = dir.path().join("key.pem");
+    fs::copy("test/root-ca.pem", &cert_path).unwrap();
+    fs::copy("test/root-ca.key", &key_path).unwrap();
+
+    let resolver = Arc::new(SniResolver::new());
+    resolver
+        .insert_pem_files("*.Example.com", &cert_path, &key_path)
+        .unwrap();
+
+    let root = X509::from_pem(ROOT_CERT).unwrap();
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor
+        .set_certificate_chain_file("test/cert.pem")
+        .unwrap();
+    acceptor.add_chain_cert(&root).unwrap();
+    acceptor
+        .set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    acceptor.set_sni_resolver(resolver.clone());
+    let acceptor = acceptor.build();
+
+    let client_ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let peer_chain = |servername: Option<&str>| {
+        let mut client = Ssl::new(&client_ctx).unwrap();
+        if let Some(servername) = servername {
+            client.set_hostname(servername).unwrap();
         }
+        let (client, _) = machine_pair(client, Ssl::new(acceptor.context()).unwrap());
+        client
+            .ssl()
+            .peer_cert_chain()
+            .unwrap()
+            .iter()
+            .map(|c| c.to_der().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let leaf = X509::from_pem(CERT).unwrap().to_der().unwrap();
+    let root = root.to_der().unwrap();
+    assert_eq!(peer_chain(Some("www.example.com")), [root.clone()]);
+    assert_eq!(peer_chain(Some("foobar.com")), [leaf.clone(), root.clone()]);
+    assert_eq!(
+        peer_chain(Some("example.com")),
+        [leaf.clone(), root.clone()]
+    );
+    assert_eq!(
+        peer_chain(Some("a.b.example.com")),
+        [leaf.clone(), root.clone()]
+    );
+    assert_eq!(peer_chain(None), [leaf.clone(), root.clone()]);
+
+    let resolved = |name| {
+        resolver
+            .resolve(name)
+            .map(|k| k.certificate().to_der().unwrap())
+    };
+
+    // Certificates are reloaded from disk in place
+    fs::copy("test/cert.pem", &cert_path).unwrap();
+    fs::copy("test/key.pem", &key_path).unwrap();
+    resolver.reload().unwrap();
+    assert_eq!(resolved("WWW.example.com."), Some(leaf.clone()));
+
+    // A certificate which does not match its key is rejected
+    fs::copy("test/root-ca.pem", &cert_path).unwrap();
+    resolver.reload().unwrap_err();
+    assert_eq!(resolved("www.example.com"), Some(leaf));
+
+    let key = CertifiedKey::from_pem(ROOT_CERT, &fs::read("test/root-ca.key").unwrap()).unwrap();
+    assert!(key.chain().is_empty());
+    resolver.insert("www.example.com", key);
+    assert_eq!(peer_chain(Some("www.example.com")), [root]);
+    assert!(resolver.remove("*.example.com").is_some());
+    assert!(resolved("mail.example.com").is_none());
+}
+
+#[test]
+fn machine_handshake() {
+    let server_ctx = machine_ctx().build();
+    let client_ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let (mut client, mut server) = machine_pair(
+        Ssl::new(&client_ctx).unwrap(),
+        Ssl::new(&server_ctx).unwrap(),
+    );
+
+    client.ssl_write(b"hello").unwrap();
+    pump(&mut client, &mut server);
+    let mut buf = [0; 5];
+    assert_eq!(server.ssl_read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+
+    assert_eq!(
+        server.ssl_read(&mut buf).unwrap_err().code(),
+        ErrorCode::WANT_READ
+    );
+}
+
+#[test]
+fn keying_material() {
+    let server_ctx = machine_ctx().build();
+    let client_ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let (client, server) = machine_pair(
+        Ssl::new(&client_ctx).unwrap(),
+        Ssl::new(&server_ctx).unwrap(),
+    );
+
+    let a = client
+        .ssl()
+        .keying_material(32, "EXPORTER-test", Some(b"context"))
+        .unwrap();
+    let b = server
+        .ssl()
+        .keying_material(32, "EXPORTER-test", Some(b"context"))
+        .unwrap();
+    assert_eq!(a.len(), 32);
+    assert_eq!(a, b);
+
+    let c = server
+        .ssl()
+        .keying_material(32, "EXPORTER-test", None)
+        .unwrap();
+    assert_ne!(a, c);
+
+    let d: [u8; 32] = server
+        .ssl()
+        .keying_material_into([0; 32], "EXPORTER-test", Some(b"context"))
+        .unwrap();
+    assert_eq!(&d[..], &a[..]);
+
+    assert!(client
+        .ssl()
+        .keying_material(0, "EXPORTER-test", None)
+        .is_err());
+    assert!(client
+        .ssl()
+        .keying_material_into([0; 0], "EXPORTER-test", None)
+        .is_err());
+}
+
+#[test]
+fn peer_cert_chain_der_and_pem() {
+    let server_ctx = machine_ctx().build();
+    let client_ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let (client, server) = machine_pair(
+        Ssl::new(&client_ctx).unwrap(),
+        Ssl::new(&server_ctx).unwrap(),
+    );
+
+    let cert = X509::from_pem(CERT).unwrap();
+    let der = client.ssl().peer_cert_chain_der().unwrap().unwrap();
+    assert_eq!(der, vec![cert.to_der().unwrap()]);
+    let pem = client.ssl().peer_cert_chain_pem().unwrap().unwrap();
+    assert_eq!(X509::stack_from_pem(&pem).unwrap().len(), 1);
+
+    assert!(server.ssl().peer_cert_chain_der().unwrap().is_none());
+    assert!(server.ssl().peer_cert_chain_pem().unwrap().is_none());
+}
+
+#[test]
+fn dtls_timeout() {
+    let ctx = SslContext::builder(SslMethod::dtls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    assert!(ssl.dtls_timeout().is_none());
+    ssl.set_mtu(1500).unwrap();
+    #[cfg(ossl110)]
+    {
+        ssl.set_link_mtu(1500).unwrap();
+        assert!(ssl.link_min_mtu() > 0);
     }
 
-    impl<'a> ::std::ops::Deref for Outgoing<'a> {
-        type Target = [u8];
-        fn deref(&self) -> &[u8] {
-            self.0
-        }
-    }
+    let mut client = SslMachine::new(ssl).unwrap();
+    assert_eq!(client.connect().unwrap_err().code(), ErrorCode::WANT_READ);
+    assert!(client.wants_write());
+
+    let timeout = client.ssl().dtls_timeout().unwrap();
+    assert!(timeout <= Duration::from_secs(1));
+    assert!(!client.ssl_mut().handle_dtls_timeout().unwrap());
+}
+
+#[test]
+#[cfg(ossl111)]
+fn early_data() {
+    let mut server_ctx = machine_ctx();
+    server_ctx.set_max_early_data(1024).unwrap();
+    server_ctx.set_recv_max_early_data(512).unwrap();
+    let server_ctx = server_ctx.build();
+    assert_eq!(server_ctx.max_early_data(), 1024);
+    assert_eq!(server_ctx.recv_max_early_data(), 512);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut stream = SslStream::new(Ssl::new(&server_ctx).unwrap(), stream).unwrap();
+        stream.accept().unwrap();
+        assert_eq!(
+            stream.ssl().early_data_status(),
+            SslEarlyDataStatus::NOT_SENT
+        );
+        stream.write_all(&[0]).unwrap();
+        stream.read_exact(&mut [0]).unwrap();
 
-    impl<'a> AsRef<[u8]> for Outgoing<'a> {
-        fn as_ref(&self) -> &[u8] {
-            self.0
+        let stream = listener.accept().unwrap().0;
+        let mut ssl = Ssl::new(&server_ctx).unwrap();
+        ssl.set_accept_state();
+        let mut stream = SslStream::new(ssl, stream).unwrap();
+        let mut buf = [0; 5];
+        let mut len = 0;
+        loop {
+            match stream.read_early_data(&mut buf[len..]).unwrap() {
+                0 => break,
+                n => len += n,
+            }
         }
-    }
-
-    fn send(from: &mut MemoryStream, to: &mut MemoryStream) {
-        to.extend_incoming(&from.take_outgoing());
-    }
+        assert_eq!(&buf[..len], b"hello");
+        stream.accept().unwrap();
+        assert_eq!(
+            stream.ssl().early_data_status(),
+            SslEarlyDataStatus::ACCEPTED
+        );
+        let exporter = stream
+            .ssl()
+            .keying_material_early(32, "EXPORTER-early-test", b"context")
+            .unwrap();
+        stream.write_all(&[0]).unwrap();
+        exporter
+    });
 
-    //
-    // Setup
-    //
+    let client_ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
 
-    let mut client_ctx = SslContext::builder(SslMethod::tls()).unwrap();
-    client_ctx.clear_options(SslOptions::ENABLE_MIDDLEBOX_COMPAT);
-    let mut client_stream =
-        SslStream::new(Ssl::new(&client_ctx.build()).unwrap(), MemoryStream::new()).unwrap();
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut stream = SslStream::new(Ssl::new(&client_ctx).unwrap(), stream).unwrap();
+    stream.connect().unwrap();
+    // TLS 1.3 session tickets arrive after the handshake.
+    stream.read_exact(&mut [0]).unwrap();
+    stream.write_all(&[0]).unwrap();
+    let session = stream.ssl().session().unwrap().to_owned();
+    assert_eq!(session.max_early_data(), 1024);
 
-    let mut server_ctx = SslContext::builder(SslMethod::tls()).unwrap();
-    server_ctx
-        .set_certificate_file(&Path::new("test/cert.pem"), SslFiletype::PEM)
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut ssl = Ssl::new(&client_ctx).unwrap();
+    unsafe { ssl.set_session(&session).unwrap() };
+    ssl.set_connect_state();
+    let mut stream = SslStream::new(ssl, stream).unwrap();
+    assert_eq!(stream.write_early_data(b"hello").unwrap(), 5);
+    stream.connect().unwrap();
+    assert!(stream.ssl().session_reused());
+    assert_eq!(
+        stream.ssl().early_data_status(),
+        SslEarlyDataStatus::ACCEPTED
+    );
+    let exporter: [u8; 32] = stream
+        .ssl()
+        .keying_material_early_into([0; 32], "EXPORTER-early-test", b"context")
         .unwrap();
-    server_ctx
-        .set_private_key_file(&Path::new("test/key.pem"), SslFiletype::PEM)
+    assert!(stream
+        .ssl()
+        .keying_material_early(0, "EXPORTER-early-test", b"context")
+        .is_err());
+    let mut main_exporter = [0; 32];
+    stream
+        .ssl()
+        .export_keying_material(&mut main_exporter, "EXPORTER-early-test", Some(b"context"))
         .unwrap();
-    const COOKIE: &[u8] = b"chocolate chip";
-    server_ctx.set_stateless_cookie_generate_cb(|_tls, buf| {
-        buf[0..COOKIE.len()].copy_from_slice(COOKIE);
-        Ok(COOKIE.len())
-    });
-    server_ctx.set_stateless_cookie_verify_cb(|_tls, buf| buf == COOKIE);
-    let mut server_stream =
-        SslStream::new(Ssl::new(&server_ctx.build()).unwrap(), MemoryStream::new()).unwrap();
-
-    //
-    // Handshake
-    //
+    assert_ne!(exporter, main_exporter);
+    stream.read_exact(&mut [0]).unwrap();
 
-    // Initial ClientHello
-    client_stream.connect().unwrap_err();
-    send(client_stream.get_mut(), server_stream.get_mut());
-    // HelloRetryRequest
-    assert!(!server_stream.stateless().unwrap());
-    send(server_stream.get_mut(), client_stream.get_mut());
-    // Second ClientHello
-    client_stream.do_handshake().unwrap_err();
-    send(client_stream.get_mut(), server_stream.get_mut());
-    // OldServerHello
-    assert!(server_stream.stateless().unwrap());
-    server_stream.accept().unwrap_err();
-    send(server_stream.get_mut(), client_stream.get_mut());
-    // Finished
-    client_stream.do_handshake().unwrap();
-    send(client_stream.get_mut(), server_stream.get_mut());
-    server_stream.do_handshake().unwrap();
+    assert_eq!(guard.join().unwrap(), exporter);
 }
 
-#[cfg(not(osslconf = "OPENSSL_NO_PSK"))]
 #[test]
-fn psk_ciphers() {
-    const CIPHER: &str = "PSK-AES256-CBC-SHA";
-    const PSK: &[u8] = b"thisisaverysecurekey";
-    const CLIENT_IDENT: &[u8] = b"thisisaclient";
-    static CLIENT_CALLED: AtomicBool = AtomicBool::new(false);
-    static SERVER_CALLED: AtomicBool = AtomicBool::new(false);
+#[cfg(ossl111)]
+fn tls13_external_psk() {
+    const IDENTITY: &[u8] = b"device-1";
+    const KEY: [u8; 32] = [0x42; 32];
+    const TLS_AES_128_GCM_SHA256: u16 = 0x1301;
 
     let mut server = Server::builder();
-    server.ctx().set_cipher_list(CIPHER).unwrap();
-    server.ctx().set_psk_server_callback(|_, identity, psk| {
-        eprintln!("hello???!");
-        assert!(identity.unwrap_or(&[]) == CLIENT_IDENT);
-        eprintln!("hello???!");
-        psk[..PSK.len()].copy_from_slice(PSK);
-        SERVER_CALLED.store(true, Ordering::SeqCst);
-        Ok(PSK.len())
+    server
+        .ctx()
+        .set_min_proto_version(Some(SslVersion::TLS1_3))
+        .unwrap();
+    server.ctx().set_psk_find_session_callback(|ssl, identity| {
+        if identity != IDENTITY {
+            return Ok(None);
+        }
+        let cipher = ssl.find_cipher(TLS_AES_128_GCM_SHA256).unwrap();
+        SslSession::from_psk(&KEY, cipher).map(Some)
     });
-
+    server.io_cb(|s| assert!(s.ssl().session_reused()));
     let server = server.build();
 
     let mut client = server.client();
-    // This test relies on TLS 1.2 suites
-    #[cfg(ossl111)]
-    client.ctx().set_options(super::SslOptions::NO_TLSV1_3);
-    client.ctx().set_cipher_list(CIPHER).unwrap();
+    // The server only accepts the PSK if it selects a cipher suite using the PSK's digest.
     client
         .ctx()
-        .set_psk_client_callback(move |_, _, identity, psk| {
-            eprintln!("hello???! 2");
-            identity[..CLIENT_IDENT.len()].copy_from_slice(CLIENT_IDENT);
-            identity[CLIENT_IDENT.len()] = 0;
-            psk[..PSK.len()].copy_from_slice(PSK);
-            CLIENT_CALLED.store(true, Ordering::SeqCst);
-            Ok(PSK.len())
-        });
-
-    client.connect();
+        .set_ciphersuites("TLS_AES_128_GCM_SHA256")
+        .unwrap();
+    client.ctx().set_psk_use_session_callback(|ssl, md| {
+        if let Some(md) = md {
+            assert_eq!(md.type_(), crate::nid::Nid::SHA256);
+        }
+        let cipher = ssl.find_cipher(TLS_AES_128_GCM_SHA256).unwrap();
+        let session = SslSession::from_psk(&KEY, cipher)?;
+        Ok(Some((IDENTITY.to_vec(), session)))
+    });
+    let s = client.connect();
 
-    assert!(SERVER_CALLED.load(Ordering::SeqCst));
-    assert!(CLIENT_CALLED.load(Ordering::SeqCst));
+    assert!(s.ssl().session_reused());
+    assert!(s.ssl().peer_certificate().is_none());
+    assert_eq!(
+        s.ssl().current_cipher().unwrap().standard_name(),
+        Some("TLS_AES_128_GCM_SHA256")
+    );
 }
 
 #[test]
-fn sni_callback_swapped_ctx() {
-    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+#[cfg(ossl111)]
+fn tls13_external_psk_without_dhe() {
+    const IDENTITY: &[u8] = b"device-1";
+    const KEY: [u8; 32] = [0x42; 32];
+    const TLS_AES_128_GCM_SHA256: u16 = 0x1301;
 
     let mut server = Server::builder();
+    server
+        .ctx()
+        .set_min_proto_version(Some(SslVersion::TLS1_3))
+        .unwrap();
+    server.ctx().set_options(SslOptions::ALLOW_NO_DHE_KEX);
+    server.ctx().set_psk_find_session_callback(|ssl, identity| {
+        assert_eq!(identity, IDENTITY);
+        let cipher = ssl.find_cipher(TLS_AES_128_GCM_SHA256).unwrap();
+        SslSession::from_psk(&KEY, cipher).map(Some)
+    });
+    server.io_cb(|s| assert!(s.ssl().session_reused()));
+    let server = server.build();
 
-    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
-    ctx.set_servername_callback(|_, _| {
-        CALLED_BACK.store(true, Ordering::SeqCst);
-        Ok(())
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_ciphersuites("TLS_AES_128_GCM_SHA256")
+        .unwrap();
+    client.ctx().set_options(SslOptions::ALLOW_NO_DHE_KEX);
+    client.ctx().set_psk_use_session_callback(|ssl, _| {
+        let cipher = ssl.find_cipher(TLS_AES_128_GCM_SHA256).unwrap();
+        let mut session = SslSession::from_psk(&KEY, cipher)?;
+        session.set_hostname("foobar.com")?;
+        session.set_alpn_selected(b"http/1.1")?;
+        session.set_max_early_data(1024)?;
+        assert_eq!(session.max_early_data(), 1024);
+        Ok(Some((IDENTITY.to_vec(), session)))
     });
+    let s = client.connect();
 
-    let keyed_ctx = mem::replace(server.ctx(), ctx).build();
-    server.ssl_cb(move |ssl| ssl.set_ssl_context(&keyed_ctx).unwrap());
+    assert!(s.ssl().session_reused());
+}
+
+#[test]
+#[cfg(boringssl)]
+fn encrypted_client_hello() {
+    use crate::hpke::{HpkeKem, HpkeKey};
+    use crate::ssl::{marshal_ech_config, SslEchKeys};
+
+    let key = HpkeKey::generate(HpkeKem::x25519_hkdf_sha256()).unwrap();
+    let config = marshal_ech_config(1, &key, "public.example", 0).unwrap();
+    let mut keys = SslEchKeys::builder().unwrap();
+    keys.add(true, &config, &key).unwrap();
+    let keys = keys.build();
+    assert!(!keys.has_duplicate_config_id());
+    let config_list = keys.marshal_retry_configs().unwrap();
 
+    let mut server = Server::builder();
+    server.ctx().set_ech_keys(&keys).unwrap();
+    server.io_cb(|s| assert!(s.ssl().ech_accepted()));
     let server = server.build();
 
-    server.client().connect();
+    let mut client = server.client().build().builder();
+    client.ssl().set_ech_config_list(&config_list).unwrap();
+    let s = client.connect();
+    assert!(s.ssl().ech_accepted());
+    assert!(s.ssl().ech_retry_configs().is_none());
+}
 
-    assert!(CALLED_BACK.load(Ordering::SeqCst));
+#[test]
+#[cfg(boringssl)]
+fn application_settings() {
+    let mut server = Server::builder();
+    server.ctx().set_alpn_select_callback(|_, client| {
+        ssl::select_next_proto(b"\x02h2", client).ok_or(ssl::AlpnError::NOACK)
+    });
+    server.ssl_cb(|s| {
+        s.add_application_settings(b"h2", b"server settings")
+            .unwrap()
+    });
+    server.io_cb(|s| {
+        assert!(s.ssl().has_application_settings());
+        assert_eq!(
+            s.ssl().peer_application_settings(),
+            Some(&b"client settings"[..])
+        );
+    });
+    let server = server.build();
+
+    let mut client = server.client().build().builder();
+    client.ssl().set_alpn_protos(b"\x02h2").unwrap();
+    client
+        .ssl()
+        .add_application_settings(b"h2", b"client settings")
+        .unwrap();
+    let s = client.connect();
+    assert_eq!(s.ssl().selected_alpn_protocol(), Some(&b"h2"[..]));
+    assert!(s.ssl().has_application_settings());
+    assert_eq!(
+        s.ssl().peer_application_settings(),
+        Some(&b"server settings"[..])
+    );
 }
 
 #[test]
-#[cfg(ossl111)]
-fn client_hello() {
-    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+#[cfg(boringssl)]
+fn certificate_compression_callback_types_are_unique() {
+    use crate::ssl::CertificateCompressionAlgorithm;
 
-    let mut server = Server::builder();
-    server.ctx().set_client_hello_callback(|ssl, _| {
-        assert!(!ssl.client_hello_isv2());
-        assert_eq!(ssl.client_hello_legacy_version(), Some(SslVersion::TLS1_2));
-        assert!(ssl.client_hello_random().is_some());
-        assert!(ssl.client_hello_session_id().is_some());
-        assert!(ssl.client_hello_ciphers().is_some());
-        assert!(ssl.client_hello_compression_methods().is_some());
+    fn identity(_: &mut SslRef, input: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        Ok(input.to_vec())
+    }
 
-        CALLED_BACK.store(true, Ordering::SeqCst);
-        Ok(ClientHelloResponse::SUCCESS)
-    });
+    fn identity_decompress(_: &mut SslRef, input: &[u8], _: usize) -> Result<Vec<u8>, ErrorStack> {
+        Ok(input.to_vec())
+    }
 
-    let server = server.build();
-    server.client().connect();
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.add_certificate_compression_algorithm(
+        CertificateCompressionAlgorithm::from_raw(0xff00),
+        identity,
+        identity_decompress,
+    )
+    .unwrap();
+    ctx.add_certificate_compression_algorithm(
+        CertificateCompressionAlgorithm::from_raw(0xff01),
+        identity,
+        identity_decompress,
+    )
+    .unwrap_err();
+    ctx.add_certificate_compression_algorithm(
+        CertificateCompressionAlgorithm::from_raw(0xff01),
+        |_: &mut SslRef, input: &[u8]| Ok(input.to_vec()),
+        |_: &mut SslRef, input: &[u8], _: usize| Ok(input.to_vec()),
+    )
+    .unwrap();
+}
+
+#[cfg(boringssl)]
+#[derive(Default)]
+struct QuicRecorder {
+    read_levels: Mutex<Vec<QuicEncryptionLevel>>,
+    write_levels: Mutex<Vec<QuicEncryptionLevel>>,
+    messages: Mutex<Vec<(QuicEncryptionLevel, Vec<u8>)>>,
+    flushes: AtomicUsize,
+    alerts: Mutex<Vec<SslAlert>>,
+}
+
+#[cfg(boringssl)]
+impl QuicMethod for Arc<QuicRecorder> {
+    fn set_read_secret(
+        &self,
+        _: &mut SslRef,
+        level: QuicEncryptionLevel,
+        _: &SslCipherRef,
+        secret: &[u8],
+    ) -> Result<(), ErrorStack> {
+        assert!(!secret.is_empty());
+        self.read_levels.lock().unwrap().push(level);
+        Ok(())
+    }
 
-    assert!(CALLED_BACK.load(Ordering::SeqCst));
+    fn set_write_secret(
+        &self,
+        _: &mut SslRef,
+        level: QuicEncryptionLevel,
+        _: &SslCipherRef,
+        secret: &[u8],
+    ) -> Result<(), ErrorStack> {
+        assert!(!secret.is_empty());
+        self.write_levels.lock().unwrap().push(level);
+        Ok(())
+    }
+
+    fn add_handshake_data(
+        &self,
+        _: &mut SslRef,
+        level: QuicEncryptionLevel,
+        data: &[u8],
+    ) -> Result<(), ErrorStack> {
+        self.messages.lock().unwrap().push((level, data.to_vec()));
+        Ok(())
+    }
+
+    fn flush_flight(&self, _: &mut SslRef) -> Result<(), ErrorStack> {
+        self.flushes.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn send_alert(
+        &self,
+        _: &mut SslRef,
+        _: QuicEncryptionLevel,
+        alert: SslAlert,
+    ) -> Result<(), ErrorStack> {
+        self.alerts.lock().unwrap().push(alert);
+        Ok(())
+    }
+}
+
+#[cfg(boringssl)]
+fn quic_deliver(from: &QuicRecorder, to: &mut SslMachine) {
+    for (level, data) in from.messages.lock().unwrap().drain(..) {
+        to.ssl_mut().provide_quic_data(level, &data).unwrap();
+    }
 }
 
 #[test]
-#[cfg(ossl111)]
-fn openssl_cipher_name() {
+#[cfg(boringssl)]
+fn quic_method() {
+    let mut server_ctx = machine_ctx();
+    server_ctx.set_alpn_select_callback(|_, client| {
+        ssl::select_next_proto(b"\x02h3", client).ok_or(ssl::AlpnError::NOACK)
+    });
+    let server_ctx = server_ctx.build();
+    let mut client_ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    client_ctx.set_alpn_protos(b"\x02h3").unwrap();
+    let client_ctx = client_ctx.build();
+
+    let client_quic = Arc::new(QuicRecorder::default());
+    let mut ssl = Ssl::new(&client_ctx).unwrap();
+    ssl.set_quic_method(client_quic.clone()).unwrap();
+    ssl.set_quic_transport_params(b"client params").unwrap();
+    ssl.set_connect_state();
+    let mut client = SslMachine::new(ssl).unwrap();
+
+    let server_quic = Arc::new(QuicRecorder::default());
+    let mut ssl = Ssl::new(&server_ctx).unwrap();
+    ssl.set_quic_method(server_quic.clone()).unwrap();
+    ssl.set_quic_transport_params(b"server params").unwrap();
+    ssl.set_accept_state();
+    let mut server = SslMachine::new(ssl).unwrap();
+
     assert_eq!(
-        super::cipher_name("TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384"),
-        "ECDHE-RSA-AES256-SHA384",
+        client.ssl().quic_write_level(),
+        QuicEncryptionLevel::INITIAL
+    );
+    assert!(
+        server
+            .ssl()
+            .quic_max_handshake_flight_len(QuicEncryptionLevel::INITIAL)
+            > 0
     );
 
-    assert_eq!(super::cipher_name("asdf"), "(NONE)");
+    let mut client_done = false;
+    let mut server_done = false;
+    while !client_done || !server_done {
+        if !client_done {
+            match client.do_handshake() {
+                Ok(()) => client_done = true,
+                Err(e) => assert_eq!(e.code(), ErrorCode::WANT_READ),
+            }
+            quic_deliver(&client_quic, &mut server);
+        }
+        if !server_done {
+            match server.do_handshake() {
+                Ok(()) => server_done = true,
+                Err(e) => assert_eq!(e.code(), ErrorCode::WANT_READ),
+            }
+            quic_deliver(&server_quic, &mut client);
+        }
+    }
+    server.ssl_mut().process_quic_post_handshake().unwrap();
+    client.ssl_mut().process_quic_post_handshake().unwrap();
+
+    // the handshake never goes through the record layer
+    assert!(!client.wants_write());
+    assert!(!server.wants_write());
+
+    for (machine, quic) in [(&client, &client_quic), (&server, &server_quic)].iter() {
+        let ssl = machine.ssl();
+        assert_eq!(ssl.quic_read_level(), QuicEncryptionLevel::APPLICATION);
+        assert_eq!(ssl.quic_write_level(), QuicEncryptionLevel::APPLICATION);
+        assert_eq!(ssl.selected_alpn_protocol(), Some(&b"h3"[..]));
+        for levels in [&quic.read_levels, &quic.write_levels].iter() {
+            let levels = levels.lock().unwrap();
+            assert!(levels.contains(&QuicEncryptionLevel::HANDSHAKE));
+            assert!(levels.contains(&QuicEncryptionLevel::APPLICATION));
+        }
+        assert!(quic.flushes.load(Ordering::SeqCst) > 0);
+        assert!(quic.alerts.lock().unwrap().is_empty());
+    }
+    assert_eq!(
+        client.ssl().peer_quic_transport_params(),
+        Some(&b"server params"[..])
+    );
+    assert_eq!(
+        server.ssl().peer_quic_transport_params(),
+        Some(&b"client params"[..])
+    );
 }
 
 #[test]
-fn session_cache_size() {
+#[cfg(all(ossl320, not(osslconf = "OPENSSL_NO_COMP_ALG")))]
+fn certificate_compression() {
+    let algorithms = [
+        CertificateCompressionAlgorithm::ZSTD,
+        CertificateCompressionAlgorithm::BROTLI,
+        CertificateCompressionAlgorithm::ZLIB,
+    ];
+
     let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
-    ctx.set_session_cache_size(1234);
+    ctx.set_certificate_chain_file("test/cert.pem").unwrap();
+    ctx.set_certificate_compression_preference(&algorithms)
+        .unwrap();
+    ctx.compress_certificates().unwrap();
     let ctx = ctx.build();
-    assert_eq!(ctx.session_cache_size(), 1234);
+    let compressed = algorithms
+        .iter()
+        .filter_map(|a| ctx.compressed_certificate(*a))
+        .collect::<Vec<_>>();
+    assert!(!compressed.is_empty());
+    for (data, len) in compressed {
+        assert!(data.len() < len);
+    }
+
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_certificate_compression_preference(&algorithms)
+        .unwrap();
+    server.ctx().compress_certificates().unwrap();
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_min_proto_version(Some(SslVersion::TLS1_3))
+        .unwrap();
+    client
+        .ctx()
+        .set_certificate_compression_preference(&algorithms)
+        .unwrap();
+    let s = client.connect();
+    assert!(s.ssl().peer_certificate().is_some());
+}
+
+#[test]
+#[cfg(all(ossl320, not(osslconf = "OPENSSL_NO_QUIC")))]
+fn quic_client_streams() {
+    let ctx = SslContext::builder(SslMethod::quic_client())
+        .unwrap()
+        .build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    assert!(ssl.is_connection());
+    assert_eq!(ssl.stream_id(), None);
+
+    ssl.set_default_stream_mode(QuicDefaultStreamMode::NONE)
+        .unwrap();
+    assert_eq!(ssl.stream_type(), QuicStreamType::NONE);
+    ssl.set_blocking_mode(false).unwrap();
+    assert!(!ssl.blocking_mode());
+    assert_eq!(ssl.accept_stream_queue_len(), 0);
+
+    // no network BIO has been configured, so the connection cannot be started
+    assert!(ssl.new_stream(QuicStreamFlags::NO_BLOCK).is_err());
+    assert!(ssl
+        .accept_stream(QuicAcceptStreamFlags::NO_BLOCK)
+        .unwrap()
+        .is_none());
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let ssl = Ssl::new(&ctx).unwrap();
+    assert!(ssl.is_connection());
+    assert_eq!(ssl.stream_type(), QuicStreamType::BIDI);
 }
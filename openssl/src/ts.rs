@@ -0,0 +1,779 @@
+//! The Time-Stamp Protocol.
+//!
+//! A time-stamp token ([RFC 3161]) is a statement, signed by a Time Stamping Authority (TSA),
+//! that a given message digest existed at a certain time. Tokens are commonly attached to code
+//! signatures and archived documents so that they can still be validated after the signing
+//! certificate expires.
+//!
+//! A client builds a [`TsRequest`] for the digest of its data and sends it to the TSA, which
+//! answers with a [`TsResponse`] created by a [`TsResponseContext`]. The client then checks the
+//! response against its request and a set of trusted certificates with a [`TsVerifyContext`].
+//!
+//! Requires OpenSSL 1.1.0 or newer.
+//!
+//! [RFC 3161]: https://www.rfc-editor.org/rfc/rfc3161
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::bn::{BigNum, MsbOption};
+//! use openssl::hash::MessageDigest;
+//! use openssl::ts::{TsMessageImprint, TsRequest};
+//!
+//! let imprint = TsMessageImprint::from_data(MessageDigest::sha256(), b"hello world").unwrap();
+//!
+//! let mut nonce = BigNum::new().unwrap();
+//! nonce.rand(64, MsbOption::MAYBE_ZERO, false).unwrap();
+//!
+//! let mut request = TsRequest::new().unwrap();
+//! request.set_message_imprint(&imprint).unwrap();
+//! request.set_nonce(&nonce.to_asn1_integer().unwrap()).unwrap();
+//! request.set_cert_req(true).unwrap();
+//! let der = request.to_der().unwrap();
+//! ```
+use bitflags::bitflags;
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::{c_int, c_long, c_void};
+use std::mem;
+use std::ptr;
+
+use crate::asn1::{
+    Asn1GeneralizedTimeRef, Asn1Integer, Asn1IntegerRef, Asn1ObjectRef, Asn1StringRef,
+};
+use crate::bio::MemBioSlice;
+use crate::bn::{BigNum, MsbOption};
+use crate::error::ErrorStack;
+use crate::hash::{hash, MessageDigest};
+use crate::pkcs7::Pkcs7Ref;
+use crate::pkey::{HasPrivate, PKeyRef};
+use crate::stack::{Stack, StackRef};
+use crate::util::ForeignTypeRefExt;
+use crate::x509::store::X509Store;
+use crate::x509::{X509Algorithm, X509AlgorithmRef, X509Ref, X509};
+use crate::{cvt, cvt_p};
+use openssl_macros::corresponds;
+
+bitflags! {
+    /// Options controlling the responses created by a `TsResponseContext`.
+    pub struct TsResponseFlags: c_int {
+        /// Include the name of the TSA in the token.
+        const TSA_NAME = ffi::TS_TSA_NAME;
+        /// Indicate that tokens from this TSA can be ordered by their time alone.
+        const ORDERING = ffi::TS_ORDERING;
+        /// Identify the whole signer certificate chain, rather than only the signer certificate,
+        /// in the token's signing certificate attribute.
+        const ESS_CERT_ID_CHAIN = ffi::TS_ESS_CERT_ID_CHAIN;
+    }
+}
+
+bitflags! {
+    /// The checks performed by a `TsVerifyContext`.
+    pub struct TsVerifyFlags: c_int {
+        /// Verify the token's signature and the signer's certificate chain.
+        const SIGNATURE = ffi::TS_VFY_SIGNATURE;
+        /// Verify the token's version.
+        const VERSION = ffi::TS_VFY_VERSION;
+        /// Verify that the token was issued under the expected policy.
+        const POLICY = ffi::TS_VFY_POLICY;
+        /// Verify that the token's message imprint matches the expected digest.
+        const IMPRINT = ffi::TS_VFY_IMPRINT;
+        /// Verify that the token's message imprint matches the digest of the expected data.
+        const DATA = ffi::TS_VFY_DATA;
+        /// Verify that the token's nonce matches the expected nonce.
+        const NONCE = ffi::TS_VFY_NONCE;
+        /// Verify that the TSA name in the token, if any, matches the signer certificate.
+        const SIGNER = ffi::TS_VFY_SIGNER;
+        /// Verify the TSA name in the token.
+        const TSA_NAME = ffi::TS_VFY_TSA_NAME;
+        const ALL_IMPRINT = ffi::TS_VFY_ALL_IMPRINT;
+        const ALL_DATA = ffi::TS_VFY_ALL_DATA;
+    }
+}
+
+/// The status of a time-stamp response.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TsStatus(c_int);
+
+impl TsStatus {
+    /// The request was granted, and the response contains a token.
+    pub const GRANTED: TsStatus = TsStatus(ffi::TS_STATUS_GRANTED);
+    /// The request was granted with modifications, and the response contains a token.
+    pub const GRANTED_WITH_MODS: TsStatus = TsStatus(ffi::TS_STATUS_GRANTED_WITH_MODS);
+    /// The request was rejected.
+    pub const REJECTION: TsStatus = TsStatus(ffi::TS_STATUS_REJECTION);
+    /// The request has not yet been processed.
+    pub const WAITING: TsStatus = TsStatus(ffi::TS_STATUS_WAITING);
+    /// A revocation of the TSA's certificate is imminent.
+    pub const REVOCATION_WARNING: TsStatus = TsStatus(ffi::TS_STATUS_REVOCATION_WARNING);
+    /// The TSA's certificate has been revoked.
+    pub const REVOCATION_NOTIFICATION: TsStatus = TsStatus(ffi::TS_STATUS_REVOCATION_NOTIFICATION);
+
+    /// Constructs a `TsStatus` from a raw value.
+    pub fn from_raw(raw: c_int) -> TsStatus {
+        TsStatus(raw)
+    }
+
+    /// Returns the raw value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::TS_MSG_IMPRINT;
+    fn drop = ffi::TS_MSG_IMPRINT_free;
+
+    /// The digest of the data being time-stamped, together with its algorithm.
+    pub struct TsMessageImprint;
+    /// Reference to `TsMessageImprint`.
+    pub struct TsMessageImprintRef;
+}
+
+impl TsMessageImprint {
+    /// Creates a message imprint from a digest computed with `md`.
+    #[corresponds(TS_MSG_IMPRINT_set_msg)]
+    pub fn new(md: MessageDigest, digest: &[u8]) -> Result<TsMessageImprint, ErrorStack> {
+        assert!(digest.len() <= c_int::max_value() as usize);
+        unsafe {
+            ffi::init();
+
+            let imprint = TsMessageImprint::from_ptr(cvt_p(ffi::TS_MSG_IMPRINT_new())?);
+            let algorithm = X509Algorithm::from_ptr(cvt_p(ffi::X509_ALGOR_new())?);
+            ffi::X509_ALGOR_set_md(algorithm.as_ptr(), md.as_ptr());
+            cvt(ffi::TS_MSG_IMPRINT_set_algo(
+                imprint.as_ptr(),
+                algorithm.as_ptr(),
+            ))?;
+            cvt(ffi::TS_MSG_IMPRINT_set_msg(
+                imprint.as_ptr(),
+                digest.as_ptr() as *mut _,
+                digest.len() as c_int,
+            ))?;
+            Ok(imprint)
+        }
+    }
+
+    /// Creates a message imprint by hashing `data` with `md`.
+    pub fn from_data(md: MessageDigest, data: &[u8]) -> Result<TsMessageImprint, ErrorStack> {
+        let digest = hash(md, data)?;
+        TsMessageImprint::new(md, &digest)
+    }
+}
+
+impl TsMessageImprintRef {
+    /// Returns the algorithm used to compute the digest.
+    #[corresponds(TS_MSG_IMPRINT_get_algo)]
+    pub fn algorithm(&self) -> &X509AlgorithmRef {
+        unsafe { X509AlgorithmRef::from_ptr(ffi::TS_MSG_IMPRINT_get_algo(self.as_ptr())) }
+    }
+
+    /// Returns the digest.
+    #[corresponds(TS_MSG_IMPRINT_get_msg)]
+    pub fn digest(&self) -> &[u8] {
+        unsafe {
+            let msg = ffi::TS_MSG_IMPRINT_get_msg(self.as_ptr());
+            Asn1StringRef::from_ptr(msg as *mut ffi::ASN1_STRING).as_slice()
+        }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::TS_REQ;
+    fn drop = ffi::TS_REQ_free;
+
+    /// A request for a time-stamp token.
+    pub struct TsRequest;
+    /// Reference to `TsRequest`.
+    pub struct TsRequestRef;
+}
+
+impl TsRequest {
+    /// Creates a new version 1 request.
+    ///
+    /// A message imprint must be set before the request is sent.
+    #[corresponds(TS_REQ_new)]
+    pub fn new() -> Result<TsRequest, ErrorStack> {
+        unsafe {
+            ffi::init();
+
+            let request = TsRequest::from_ptr(cvt_p(ffi::TS_REQ_new())?);
+            cvt(ffi::TS_REQ_set_version(request.as_ptr(), 1))?;
+            Ok(request)
+        }
+    }
+
+    from_der! {
+        /// Deserializes a DER-encoded time-stamp request.
+        #[corresponds(d2i_TS_REQ)]
+        from_der,
+        TsRequest,
+        ffi::d2i_TS_REQ
+    }
+}
+
+impl TsRequestRef {
+    to_der! {
+        /// Serializes the request to its standard DER encoding.
+        #[corresponds(i2d_TS_REQ)]
+        to_der,
+        ffi::i2d_TS_REQ
+    }
+
+    /// Sets the digest of the data to be time-stamped.
+    #[corresponds(TS_REQ_set_msg_imprint)]
+    pub fn set_message_imprint(&mut self, imprint: &TsMessageImprintRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::TS_REQ_set_msg_imprint(self.as_ptr(), imprint.as_ptr())).map(|_| ()) }
+    }
+
+    /// Returns the digest of the data to be time-stamped.
+    #[corresponds(TS_REQ_get_msg_imprint)]
+    pub fn message_imprint(&self) -> &TsMessageImprintRef {
+        unsafe { TsMessageImprintRef::from_ptr(ffi::TS_REQ_get_msg_imprint(self.as_ptr())) }
+    }
+
+    /// Sets the policy under which the TSA is asked to issue the token.
+    #[corresponds(TS_REQ_set_policy_id)]
+    pub fn set_policy_id(&mut self, policy: &Asn1ObjectRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::TS_REQ_set_policy_id(self.as_ptr(), policy.as_ptr())).map(|_| ()) }
+    }
+
+    /// Returns the policy under which the TSA is asked to issue the token, if one was set.
+    #[corresponds(TS_REQ_get_policy_id)]
+    pub fn policy_id(&self) -> Option<&Asn1ObjectRef> {
+        unsafe { Asn1ObjectRef::from_const_ptr_opt(ffi::TS_REQ_get_policy_id(self.as_ptr())) }
+    }
+
+    /// Sets the nonce which the TSA must copy into the token.
+    ///
+    /// A random nonce protects the client against replayed responses.
+    #[corresponds(TS_REQ_set_nonce)]
+    pub fn set_nonce(&mut self, nonce: &Asn1IntegerRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::TS_REQ_set_nonce(self.as_ptr(), nonce.as_ptr())).map(|_| ()) }
+    }
+
+    /// Returns the nonce, if one was set.
+    #[corresponds(TS_REQ_get_nonce)]
+    pub fn nonce(&self) -> Option<&Asn1IntegerRef> {
+        unsafe { Asn1IntegerRef::from_const_ptr_opt(ffi::TS_REQ_get_nonce(self.as_ptr())) }
+    }
+
+    /// Sets whether the TSA is asked to include its certificate in the token.
+    #[corresponds(TS_REQ_set_cert_req)]
+    pub fn set_cert_req(&mut self, cert_req: bool) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::TS_REQ_set_cert_req(self.as_ptr(), cert_req as c_int)).map(|_| ()) }
+    }
+
+    /// Returns whether the TSA is asked to include its certificate in the token.
+    #[corresponds(TS_REQ_get_cert_req)]
+    pub fn cert_req(&self) -> bool {
+        unsafe { ffi::TS_REQ_get_cert_req(self.as_ptr()) != 0 }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::TS_TST_INFO;
+    fn drop = ffi::TS_TST_INFO_free;
+
+    /// The signed contents of a time-stamp token.
+    pub struct TsTokenInfo;
+    /// Reference to `TsTokenInfo`.
+    pub struct TsTokenInfoRef;
+}
+
+impl TsTokenInfoRef {
+    /// Returns the policy under which the token was issued.
+    #[corresponds(TS_TST_INFO_get_policy_id)]
+    pub fn policy_id(&self) -> &Asn1ObjectRef {
+        unsafe { Asn1ObjectRef::from_ptr(ffi::TS_TST_INFO_get_policy_id(self.as_ptr())) }
+    }
+
+    /// Returns the digest which was time-stamped.
+    #[corresponds(TS_TST_INFO_get_msg_imprint)]
+    pub fn message_imprint(&self) -> &TsMessageImprintRef {
+        unsafe { TsMessageImprintRef::from_ptr(ffi::TS_TST_INFO_get_msg_imprint(self.as_ptr())) }
+    }
+
+    /// Returns the serial number assigned to the token by the TSA.
+    #[corresponds(TS_TST_INFO_get_serial)]
+    pub fn serial_number(&self) -> &Asn1IntegerRef {
+        unsafe { Asn1IntegerRef::from_const_ptr(ffi::TS_TST_INFO_get_serial(self.as_ptr())) }
+    }
+
+    /// Returns the time at which the token was issued.
+    #[corresponds(TS_TST_INFO_get_time)]
+    pub fn time(&self) -> &Asn1GeneralizedTimeRef {
+        unsafe { Asn1GeneralizedTimeRef::from_const_ptr(ffi::TS_TST_INFO_get_time(self.as_ptr())) }
+    }
+
+    /// Returns the nonce copied from the request, if any.
+    #[corresponds(TS_TST_INFO_get_nonce)]
+    pub fn nonce(&self) -> Option<&Asn1IntegerRef> {
+        unsafe { Asn1IntegerRef::from_const_ptr_opt(ffi::TS_TST_INFO_get_nonce(self.as_ptr())) }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::TS_RESP;
+    fn drop = ffi::TS_RESP_free;
+
+    /// A response from a TSA.
+    pub struct TsResponse;
+    /// Reference to `TsResponse`.
+    pub struct TsResponseRef;
+}
+
+impl TsResponse {
+    from_der! {
+        /// Deserializes a DER-encoded time-stamp response.
+        #[corresponds(d2i_TS_RESP)]
+        from_der,
+        TsResponse,
+        ffi::d2i_TS_RESP
+    }
+}
+
+impl TsResponseRef {
+    to_der! {
+        /// Serializes the response to its standard DER encoding.
+        #[corresponds(i2d_TS_RESP)]
+        to_der,
+        ffi::i2d_TS_RESP
+    }
+
+    /// Returns the status of the response.
+    #[corresponds(TS_STATUS_INFO_get0_status)]
+    pub fn status(&self) -> TsStatus {
+        unsafe {
+            let info = ffi::TS_RESP_get_status_info(self.as_ptr());
+            let status = ffi::TS_STATUS_INFO_get0_status(info);
+            TsStatus(ffi::ASN1_INTEGER_get(status) as c_int)
+        }
+    }
+
+    /// Returns the time-stamp token, a PKCS#7 `signedData` structure.
+    ///
+    /// The token is only present if the request was granted.
+    #[corresponds(TS_RESP_get_token)]
+    pub fn token(&self) -> Option<&Pkcs7Ref> {
+        unsafe { Pkcs7Ref::from_const_ptr_opt(ffi::TS_RESP_get_token(self.as_ptr())) }
+    }
+
+    /// Returns the contents of the time-stamp token.
+    ///
+    /// The token is only present if the request was granted. Its signature is not checked.
+    #[corresponds(TS_RESP_get_tst_info)]
+    pub fn token_info(&self) -> Option<&TsTokenInfoRef> {
+        unsafe { TsTokenInfoRef::from_const_ptr_opt(ffi::TS_RESP_get_tst_info(self.as_ptr())) }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::TS_RESP_CTX;
+    fn drop = ffi::TS_RESP_CTX_free;
+
+    /// The state of a TSA, used to answer time-stamp requests.
+    pub struct TsResponseContext;
+    /// Reference to `TsResponseContext`.
+    pub struct TsResponseContextRef;
+}
+
+impl TsResponseContext {
+    /// Creates a new context.
+    ///
+    /// Before responses can be created, the signer certificate and key, a default policy, and at
+    /// least one accepted digest must be configured. Tokens are assigned random serial numbers.
+    #[corresponds(TS_RESP_CTX_new)]
+    pub fn new() -> Result<TsResponseContext, ErrorStack> {
+        unsafe {
+            ffi::init();
+
+            let ctx = TsResponseContext::from_ptr(cvt_p(ffi::TS_RESP_CTX_new())?);
+            ffi::TS_RESP_CTX_set_serial_cb(ctx.as_ptr(), Some(random_serial), ptr::null_mut());
+            Ok(ctx)
+        }
+    }
+}
+
+impl TsResponseContextRef {
+    /// Sets the certificate of the TSA.
+    ///
+    /// The certificate must have a critical extended key usage extension allowing only time
+    /// stamping.
+    #[corresponds(TS_RESP_CTX_set_signer_cert)]
+    pub fn set_signer_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::TS_RESP_CTX_set_signer_cert(
+                self.as_ptr(),
+                cert.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the private key used to sign tokens.
+    #[corresponds(TS_RESP_CTX_set_signer_key)]
+    pub fn set_signer_key<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe { cvt(ffi::TS_RESP_CTX_set_signer_key(self.as_ptr(), key.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the digest used to sign tokens.
+    ///
+    /// Defaults to SHA-256.
+    #[corresponds(TS_RESP_CTX_set_signer_digest)]
+    pub fn set_signer_digest(&mut self, md: MessageDigest) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::TS_RESP_CTX_set_signer_digest(
+                self.as_ptr(),
+                md.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the policy used for requests which do not ask for a specific policy.
+    #[corresponds(TS_RESP_CTX_set_def_policy)]
+    pub fn set_default_policy(&mut self, policy: &Asn1ObjectRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::TS_RESP_CTX_set_def_policy(
+                self.as_ptr(),
+                policy.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds a policy, other than the default policy, which requests may ask for.
+    #[corresponds(TS_RESP_CTX_add_policy)]
+    pub fn add_policy(&mut self, policy: &Asn1ObjectRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::TS_RESP_CTX_add_policy(self.as_ptr(), policy.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets additional certificates, such as intermediates, to include in tokens when the
+    /// request asks for the TSA's certificate.
+    #[corresponds(TS_RESP_CTX_set_certs)]
+    pub fn set_certs(&mut self, certs: &StackRef<X509>) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::TS_RESP_CTX_set_certs(self.as_ptr(), certs.as_ptr())).map(|_| ()) }
+    }
+
+    /// Adds a digest algorithm which is accepted in message imprints.
+    #[corresponds(TS_RESP_CTX_add_md)]
+    pub fn add_md(&mut self, md: MessageDigest) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::TS_RESP_CTX_add_md(self.as_ptr(), md.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the accuracy of the TSA's clock, which is included in tokens.
+    #[corresponds(TS_RESP_CTX_set_accuracy)]
+    pub fn set_accuracy(&mut self, secs: u32, millis: u32, micros: u32) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::TS_RESP_CTX_set_accuracy(
+                self.as_ptr(),
+                secs as c_int,
+                millis as c_int,
+                micros as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds options controlling the contents of tokens.
+    #[corresponds(TS_RESP_CTX_add_flags)]
+    pub fn add_flags(&mut self, flags: TsResponseFlags) {
+        unsafe { ffi::TS_RESP_CTX_add_flags(self.as_ptr(), flags.bits()) }
+    }
+
+    /// Answers a DER-encoded time-stamp request.
+    ///
+    /// Requests which are malformed, or which ask for an unaccepted digest or policy, are
+    /// answered with a response rejecting the request rather than an error.
+    #[corresponds(TS_RESP_create_response)]
+    pub fn create_response(&mut self, request: &[u8]) -> Result<TsResponse, ErrorStack> {
+        let bio = MemBioSlice::new(request)?;
+        unsafe { cvt_p(ffi::TS_RESP_create_response(self.as_ptr(), bio.as_ptr())).map(TsResponse) }
+    }
+}
+
+unsafe extern "C" fn random_serial(
+    _: *mut ffi::TS_RESP_CTX,
+    _: *mut c_void,
+) -> *mut ffi::ASN1_INTEGER {
+    let serial = BigNum::new().and_then(|mut serial| {
+        serial.rand(128, MsbOption::MAYBE_ZERO, false)?;
+        serial.to_asn1_integer()
+    });
+
+    match serial {
+        Ok(serial) => into_ptr(serial),
+        // OpenSSL rejects the request when no serial number is returned
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+fn into_ptr(serial: Asn1Integer) -> *mut ffi::ASN1_INTEGER {
+    let ptr = serial.as_ptr();
+    mem::forget(serial);
+    ptr
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::TS_VERIFY_CTX;
+    fn drop = ffi::TS_VERIFY_CTX_free;
+
+    /// The expectations a time-stamp response or token is verified against.
+    pub struct TsVerifyContext;
+    /// Reference to `TsVerifyContext`.
+    pub struct TsVerifyContextRef;
+}
+
+impl TsVerifyContext {
+    /// Creates a new context which performs no checks.
+    #[corresponds(TS_VERIFY_CTX_new)]
+    pub fn new() -> Result<TsVerifyContext, ErrorStack> {
+        unsafe {
+            ffi::init();
+
+            cvt_p(ffi::TS_VERIFY_CTX_new()).map(TsVerifyContext)
+        }
+    }
+
+    /// Creates a context which checks that a response answers `request`.
+    ///
+    /// The version and message imprint of the token are checked, as are its policy and nonce
+    /// if the request contains them. Unlike OpenSSL's default, the token's signature is checked
+    /// as well, so a trusted certificate store must be set with `set_store`.
+    #[corresponds(TS_REQ_to_TS_VERIFY_CTX)]
+    pub fn from_request(request: &TsRequestRef) -> Result<TsVerifyContext, ErrorStack> {
+        unsafe {
+            let ctx = cvt_p(ffi::TS_REQ_to_TS_VERIFY_CTX(
+                request.as_ptr(),
+                ptr::null_mut(),
+            ))
+            .map(TsVerifyContext)?;
+            ffi::TS_VERIFY_CTX_add_flags(ctx.as_ptr(), ffi::TS_VFY_SIGNATURE);
+            Ok(ctx)
+        }
+    }
+}
+
+impl TsVerifyContextRef {
+    /// Sets the checks to perform, replacing any previously set.
+    #[corresponds(TS_VERIFY_CTX_set_flags)]
+    pub fn set_flags(&mut self, flags: TsVerifyFlags) {
+        unsafe {
+            ffi::TS_VERIFY_CTX_set_flags(self.as_ptr(), flags.bits());
+        }
+    }
+
+    /// Adds checks to perform.
+    #[corresponds(TS_VERIFY_CTX_add_flags)]
+    pub fn add_flags(&mut self, flags: TsVerifyFlags) {
+        unsafe {
+            ffi::TS_VERIFY_CTX_add_flags(self.as_ptr(), flags.bits());
+        }
+    }
+
+    /// Sets the digest which the token's message imprint is checked against when
+    /// `TsVerifyFlags::IMPRINT` is set.
+    #[corresponds(TS_VERIFY_CTX_set_imprint)]
+    pub fn set_imprint(&mut self, digest: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            let buf = cvt_p(ffi::OPENSSL_malloc(digest.len()))? as *mut u8;
+            ptr::copy_nonoverlapping(digest.as_ptr(), buf, digest.len());
+            ffi::TS_VERIFY_CTX_set_imprint(self.as_ptr(), buf, digest.len() as c_long);
+            Ok(())
+        }
+    }
+
+    /// Sets the trusted certificates used to verify the signer's certificate chain.
+    #[corresponds(TS_VERIFY_CTX_set_store)]
+    pub fn set_store(&mut self, store: X509Store) {
+        unsafe {
+            ffi::TS_VERIFY_CTX_set_store(self.as_ptr(), store.as_ptr());
+            mem::forget(store);
+        }
+    }
+
+    /// Sets untrusted certificates which may be used to find the signer's certificate and build
+    /// its chain.
+    #[corresponds(TS_VERIFY_CTX_set_certs)]
+    pub fn set_certs(&mut self, certs: Stack<X509>) {
+        unsafe {
+            ffi::TS_VERIFY_CTX_set_certs(self.as_ptr(), certs.as_ptr());
+            mem::forget(certs);
+        }
+    }
+
+    /// Verifies a time-stamp response.
+    ///
+    /// Responses which do not grant the request fail verification.
+    #[corresponds(TS_RESP_verify_response)]
+    pub fn verify_response(&self, response: &TsResponseRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::TS_RESP_verify_response(
+                self.as_ptr(),
+                response.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Verifies a time-stamp token.
+    #[corresponds(TS_RESP_verify_token)]
+    pub fn verify_token(&self, token: &Pkcs7Ref) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::TS_RESP_verify_token(self.as_ptr(), token.as_ptr())).map(|_| ()) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::asn1::{Asn1Object, Asn1Time};
+    use crate::nid::Nid;
+    use crate::pkey::{PKey, Private};
+    use crate::rsa::Rsa;
+    use crate::x509::extension::ExtendedKeyUsage;
+    use crate::x509::store::X509StoreBuilder;
+    use crate::x509::X509NameBuilder;
+
+    fn tsa() -> (X509, PKey<Private>) {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, "tsa").unwrap();
+        let name = name.build();
+
+        let serial = BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap();
+        let mut cert = X509::builder().unwrap();
+        cert.set_version(2).unwrap();
+        cert.set_serial_number(&serial).unwrap();
+        cert.set_subject_name(&name).unwrap();
+        cert.set_issuer_name(&name).unwrap();
+        cert.set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        cert.set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        cert.set_pubkey(&key).unwrap();
+        let eku = ExtendedKeyUsage::new()
+            .critical()
+            .time_stamping()
+            .build()
+            .unwrap();
+        cert.append_extension(eku).unwrap();
+        cert.sign(&key, MessageDigest::sha256()).unwrap();
+
+        (cert.build(), key)
+    }
+
+    fn responder(cert: &X509Ref, key: &PKeyRef<Private>) -> TsResponseContext {
+        let mut ctx = TsResponseContext::new().unwrap();
+        ctx.set_signer_cert(cert).unwrap();
+        ctx.set_signer_key(key).unwrap();
+        ctx.set_default_policy(&Asn1Object::from_str("1.2.3.4.1").unwrap())
+            .unwrap();
+        ctx.add_md(MessageDigest::sha256()).unwrap();
+        ctx
+    }
+
+    fn request(data: &[u8], nonce: u32) -> TsRequest {
+        let imprint = TsMessageImprint::from_data(MessageDigest::sha256(), data).unwrap();
+        let nonce = BigNum::from_u32(nonce).unwrap().to_asn1_integer().unwrap();
+
+        let mut request = TsRequest::new().unwrap();
+        request.set_message_imprint(&imprint).unwrap();
+        request.set_nonce(&nonce).unwrap();
+        request.set_cert_req(true).unwrap();
+        request
+    }
+
+    fn verifier(request: &TsRequestRef, cert: &X509Ref) -> TsVerifyContext {
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(cert.to_owned()).unwrap();
+
+        let mut ctx = TsVerifyContext::from_request(request).unwrap();
+        ctx.set_store(store.build());
+        ctx
+    }
+
+    #[test]
+    fn request_round_trip() {
+        let mut request = request(b"hello world", 42);
+        let policy = Asn1Object::from_str("1.2.3.4.1").unwrap();
+        request.set_policy_id(&policy).unwrap();
+
+        let request = TsRequest::from_der(&request.to_der().unwrap()).unwrap();
+        let digest = hash(MessageDigest::sha256(), b"hello world").unwrap();
+        assert_eq!(request.message_imprint().digest(), &*digest);
+        assert_eq!(
+            request.message_imprint().algorithm().object().nid(),
+            Nid::SHA256
+        );
+        assert_eq!(request.policy_id().unwrap().to_string(), "1.2.3.4.1");
+        assert_eq!(
+            request.nonce().unwrap().to_bn().unwrap(),
+            BigNum::from_u32(42).unwrap()
+        );
+        assert!(request.cert_req());
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let (cert, key) = tsa();
+        let request = request(b"hello world", 42);
+
+        let mut ctx = responder(&cert, &key);
+        let response = ctx.create_response(&request.to_der().unwrap()).unwrap();
+        let response = TsResponse::from_der(&response.to_der().unwrap()).unwrap();
+        assert_eq!(response.status(), TsStatus::GRANTED);
+
+        let info = response.token_info().unwrap();
+        assert_eq!(info.policy_id().to_string(), "1.2.3.4.1");
+        assert_eq!(
+            info.nonce().unwrap().to_bn().unwrap(),
+            BigNum::from_u32(42).unwrap()
+        );
+        assert_eq!(
+            info.message_imprint().digest(),
+            request.message_imprint().digest()
+        );
+
+        let verifier = verifier(&request, &cert);
+        verifier.verify_response(&response).unwrap();
+        verifier.verify_token(response.token().unwrap()).unwrap();
+
+        let other = self::request(b"hello world", 43);
+        self::verifier(&other, &cert)
+            .verify_response(&response)
+            .unwrap_err();
+        let other = self::request(b"goodbye world", 42);
+        self::verifier(&other, &cert)
+            .verify_response(&response)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn untrusted_signer() {
+        let (cert, key) = tsa();
+        let (other, _) = tsa();
+        let request = request(b"hello world", 42);
+
+        let response = responder(&cert, &key)
+            .create_response(&request.to_der().unwrap())
+            .unwrap();
+        verifier(&request, &other)
+            .verify_response(&response)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn reject_malformed_request() {
+        let (cert, key) = tsa();
+
+        let response = responder(&cert, &key).create_response(b"garbage").unwrap();
+        assert_eq!(response.status(), TsStatus::REJECTION);
+        assert!(response.token().is_none());
+        assert!(response.token_info().is_none());
+    }
+}
@@ -14,6 +14,11 @@
 use cfg_if::cfg_if;
 use openssl_macros::corresponds;
 use std::ffi::CStr;
+#[cfg(ossl300)]
+use std::ffi::CString;
+use std::fmt;
+#[cfg(ossl300)]
+use std::ptr;
 
 cfg_if! {
     if #[cfg(any(ossl110, libressl271))] {
@@ -98,6 +103,137 @@ pub fn dir() -> &'static str {
     }
 }
 
+/// A TLS library this crate can be linked against.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Library {
+    /// OpenSSL.
+    OpenSsl,
+    /// LibreSSL.
+    LibreSsl,
+    /// BoringSSL.
+    BoringSsl,
+}
+
+/// Returns the TLS library this crate is linked against.
+pub fn library() -> Library {
+    if cfg!(boringssl) {
+        Library::BoringSsl
+    } else if cfg!(libressl) {
+        Library::LibreSsl
+    } else {
+        Library::OpenSsl
+    }
+}
+
+/// A release version of a TLS library.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl Version {
+    /// Creates a version from its components.
+    pub fn new(major: u32, minor: u32, patch: u32) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Returns the major version.
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    /// Returns the minor version.
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
+
+    /// Returns the patch version.
+    ///
+    /// For OpenSSL releases before 3.0.0 this is the fix number, so 1.1.1w is 1.1.1.
+    pub fn patch(&self) -> u32 {
+        self.patch
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Returns the release version of the library loaded at runtime.
+///
+/// Unlike `number`, this reports the LibreSSL version for LibreSSL rather than the OpenSSL
+/// version it claims compatibility with. BoringSSL does not make releases, so `None` is returned
+/// for it.
+pub fn library_version() -> Option<Version> {
+    match library() {
+        Library::OpenSsl => Some(parse_number(number())),
+        Library::LibreSsl => parse_libressl_version(version()),
+        Library::BoringSsl => None,
+    }
+}
+
+fn parse_number(number: i64) -> Version {
+    let major = (number >> 28) as u32 & 0xf;
+    let minor = (number >> 20) as u32 & 0xff;
+    // OpenSSL 3 moved the patch number to the byte used for the patch letter by earlier versions
+    let patch = if major >= 3 {
+        (number >> 4) as u32 & 0xff
+    } else {
+        (number >> 12) as u32 & 0xff
+    };
+    Version::new(major, minor, patch)
+}
+
+fn parse_libressl_version(version: &str) -> Option<Version> {
+    let mut parts = version.split_whitespace().nth(1)?.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    let patch = parts.next()?.parse().ok()?;
+    Some(Version::new(major, minor, patch))
+}
+
+/// Determines if the library supports providers, which were introduced in OpenSSL 3.0.0.
+pub fn supports_providers() -> bool {
+    cfg!(ossl300)
+}
+
+/// Determines if the provider `name` is loaded in the default library context, or can be
+/// loaded automatically.
+///
+/// Always returns `false` if the library does not support providers.
+#[corresponds(OSSL_PROVIDER_available)]
+#[allow(unused_variables)]
+pub fn provider_available(name: &str) -> bool {
+    #[cfg(ossl300)]
+    unsafe {
+        ffi::init();
+        let name = CString::new(name).unwrap();
+        ffi::OSSL_PROVIDER_available(ptr::null_mut(), name.as_ptr()) == 1
+    }
+    #[cfg(not(ossl300))]
+    false
+}
+
+/// Determines if the library supports TLS 1.3.
+pub fn supports_tls13() -> bool {
+    cfg!(any(ossl111, libressl340, boringssl))
+}
+
+/// Determines if the QUIC APIs of this crate, such as `SslRef::set_quic_method`, are available.
+///
+/// They require BoringSSL.
+pub fn supports_quic() -> bool {
+    cfg!(boringssl)
+}
+
 /// This test ensures that we do not segfault when calling the functions of this module
 /// and that the strings respect a reasonable format.
 #[test]
@@ -131,3 +267,30 @@ fn test_versions() {
     }
     assert!(dir().starts_with("OPENSSLDIR:"));
 }
+
+#[test]
+fn test_library_version() {
+    assert_eq!(parse_number(0x1010107f), Version::new(1, 1, 1));
+    assert_eq!(parse_number(0x1000214f), Version::new(1, 0, 2));
+    assert_eq!(parse_number(0x30000020), Version::new(3, 0, 2));
+    assert_eq!(parse_number(0x30100000), Version::new(3, 1, 0));
+    assert_eq!(
+        parse_libressl_version("LibreSSL 3.8.2"),
+        Some(Version::new(3, 8, 2))
+    );
+    assert_eq!(parse_libressl_version("LibreSSL"), None);
+    assert_eq!(Version::new(3, 0, 2).to_string(), "3.0.2");
+    assert!(Version::new(1, 1, 1) < Version::new(3, 0, 0));
+
+    match library() {
+        Library::BoringSsl => assert_eq!(library_version(), None),
+        _ => assert!(library_version().unwrap().major() > 0),
+    }
+
+    if supports_providers() {
+        assert!(provider_available("default"));
+        assert!(!provider_available("nonexistent"));
+    } else {
+        assert!(!provider_available("default"));
+    }
+}
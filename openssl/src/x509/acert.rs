@@ -0,0 +1,396 @@
+//! Attribute certificates.
+//!
+//! An attribute certificate binds a set of attributes, such as roles, group memberships or
+//! clearances, to the holder of a public key certificate. It is issued by an attribute authority
+//! and carries no public key of its own. The format is defined in [RFC 5755 section 4.1]:
+//!
+//! ```text
+//! AttributeCertificateInfo ::= SEQUENCE {
+//!     version                 AttCertVersion, -- version is v2
+//!     holder                  Holder,
+//!     issuer                  AttCertIssuer,
+//!     signature               AlgorithmIdentifier,
+//!     serialNumber            CertificateSerialNumber,
+//!     attrCertValidityPeriod  AttCertValidityPeriod,
+//!     attributes              SEQUENCE OF Attribute,
+//!     issuerUniqueID          UniqueIdentifier OPTIONAL,
+//!     extensions              Extensions OPTIONAL }
+//! ```
+//!
+//! Requires OpenSSL 3.4.0 or newer.
+//!
+//! [RFC 5755 section 4.1]: https://www.rfc-editor.org/rfc/rfc5755#section-4.1
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::c_int;
+use openssl_macros::corresponds;
+use std::mem;
+use std::ptr;
+
+use crate::asn1::{Asn1BitStringRef, Asn1IntegerRef, Asn1String, Asn1TimeRef};
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
+use crate::stack::{Stack, StackRef};
+use crate::util::ForeignTypeRefExt;
+use crate::x509::{
+    GeneralName, X509AlgorithmRef, X509AttributeRef, X509Extension, X509NameRef, X509Ref,
+};
+use crate::{cvt, cvt_n, cvt_p};
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::X509_ACERT;
+    fn drop = ffi::X509_ACERT_free;
+
+    /// An X.509 attribute certificate, backed by OpenSSL's `X509_ACERT`.
+    ///
+    /// Requires OpenSSL 3.4.0 or newer.
+    pub struct X509Acert;
+    /// Reference to `X509Acert`.
+    pub struct X509AcertRef;
+}
+
+impl X509Acert {
+    /// Returns a new builder.
+    pub fn builder() -> Result<X509AcertBuilder, ErrorStack> {
+        X509AcertBuilder::new()
+    }
+
+    from_pem! {
+        /// Deserializes a PEM-encoded `AttributeCertificate` structure.
+        ///
+        /// The input should have a header of `-----BEGIN ATTRIBUTE CERTIFICATE-----`.
+        #[corresponds(PEM_read_bio_X509_ACERT)]
+        from_pem,
+        X509Acert,
+        ffi::PEM_read_bio_X509_ACERT
+    }
+
+    from_der! {
+        /// Deserializes a DER-encoded `AttributeCertificate` structure.
+        #[corresponds(d2i_X509_ACERT)]
+        from_der,
+        X509Acert,
+        ffi::d2i_X509_ACERT
+    }
+}
+
+impl X509AcertRef {
+    to_pem! {
+        /// Serializes the certificate into a PEM-encoded `AttributeCertificate` structure.
+        ///
+        /// The output will have a header of `-----BEGIN ATTRIBUTE CERTIFICATE-----`.
+        #[corresponds(PEM_write_bio_X509_ACERT)]
+        to_pem,
+        ffi::PEM_write_bio_X509_ACERT
+    }
+
+    to_der! {
+        /// Serializes the certificate into a DER-encoded `AttributeCertificate` structure.
+        #[corresponds(i2d_X509_ACERT)]
+        to_der,
+        ffi::i2d_X509_ACERT
+    }
+
+    /// Returns the numerical value of the version field.
+    ///
+    /// Certificates following RFC 5755 always have version 1, corresponding to v2.
+    #[corresponds(X509_ACERT_get_version)]
+    pub fn version(&self) -> i32 {
+        unsafe { ffi::X509_ACERT_get_version(self.as_ptr()) as i32 }
+    }
+
+    /// Returns the issuer name and serial number of the public key certificate identifying the
+    /// holder, if present.
+    #[corresponds(X509_ACERT_get0_holder_baseCertId)]
+    pub fn holder_base_certificate_id(&self) -> Option<(&X509NameRef, &Asn1IntegerRef)> {
+        unsafe {
+            let id = ffi::X509_ACERT_get0_holder_baseCertId(self.as_ptr());
+            if id.is_null() {
+                return None;
+            }
+            let issuer = X509NameRef::from_const_ptr_opt(ffi::OSSL_ISSUER_SERIAL_get0_issuer(id))?;
+            let serial = Asn1IntegerRef::from_const_ptr(ffi::OSSL_ISSUER_SERIAL_get0_serial(id));
+            Some((issuer, serial))
+        }
+    }
+
+    /// Determines if `cert` is the public key certificate identifying the holder.
+    ///
+    /// This compares the issuer name and serial number of `cert` to the holder's
+    /// `baseCertificateID`.
+    pub fn is_held_by(&self, cert: &X509Ref) -> Result<bool, ErrorStack> {
+        let (issuer, serial) = match self.holder_base_certificate_id() {
+            Some(id) => id,
+            None => return Ok(false),
+        };
+        Ok(issuer.to_der()? == cert.issuer_name().to_der()?
+            && *serial.to_bn()? == *cert.serial_number().to_bn()?)
+    }
+
+    /// Returns the names identifying the holder, if present.
+    #[corresponds(X509_ACERT_get0_holder_entityName)]
+    pub fn holder_entity_name(&self) -> Option<&StackRef<GeneralName>> {
+        unsafe {
+            StackRef::from_const_ptr_opt(ffi::X509_ACERT_get0_holder_entityName(self.as_ptr()))
+        }
+    }
+
+    /// Returns the directory name of the attribute authority which issued the certificate, if
+    /// present.
+    #[corresponds(X509_ACERT_get0_issuerName)]
+    pub fn issuer_name(&self) -> Option<&X509NameRef> {
+        unsafe { X509NameRef::from_const_ptr_opt(ffi::X509_ACERT_get0_issuerName(self.as_ptr())) }
+    }
+
+    /// Returns the certificate's serial number.
+    #[corresponds(X509_ACERT_get0_serialNumber)]
+    pub fn serial_number(&self) -> &Asn1IntegerRef {
+        unsafe {
+            Asn1IntegerRef::from_const_ptr_opt(ffi::X509_ACERT_get0_serialNumber(self.as_ptr()))
+                .expect("serial number must not be null")
+        }
+    }
+
+    /// Returns the time at which the certificate becomes valid.
+    #[corresponds(X509_ACERT_get0_notBefore)]
+    pub fn not_before(&self) -> &Asn1TimeRef {
+        unsafe {
+            Asn1TimeRef::from_const_ptr_opt(ffi::X509_ACERT_get0_notBefore(self.as_ptr()))
+                .expect("not before must not be null")
+        }
+    }
+
+    /// Returns the time after which the certificate is no longer valid.
+    #[corresponds(X509_ACERT_get0_notAfter)]
+    pub fn not_after(&self) -> &Asn1TimeRef {
+        unsafe {
+            Asn1TimeRef::from_const_ptr_opt(ffi::X509_ACERT_get0_notAfter(self.as_ptr()))
+                .expect("not after must not be null")
+        }
+    }
+
+    /// Returns the number of attributes the certificate grants to its holder.
+    #[corresponds(X509_ACERT_get_attr_count)]
+    pub fn attribute_count(&self) -> usize {
+        unsafe { ffi::X509_ACERT_get_attr_count(self.as_ptr()) as usize }
+    }
+
+    /// Returns the attribute at the specified index, if it exists.
+    #[corresponds(X509_ACERT_get_attr)]
+    pub fn attribute(&self, idx: usize) -> Option<&X509AttributeRef> {
+        if idx >= self.attribute_count() {
+            return None;
+        }
+
+        unsafe {
+            X509AttributeRef::from_const_ptr_opt(ffi::X509_ACERT_get_attr(
+                self.as_ptr(),
+                idx as c_int,
+            ))
+        }
+    }
+
+    /// Returns the certificate's extensions, if present.
+    #[corresponds(X509_ACERT_get0_extensions)]
+    pub fn extensions(&self) -> Option<&StackRef<X509Extension>> {
+        unsafe { StackRef::from_const_ptr_opt(ffi::X509_ACERT_get0_extensions(self.as_ptr())) }
+    }
+
+    /// Returns the certificate's signature.
+    #[corresponds(X509_ACERT_get0_signature)]
+    pub fn signature(&self) -> &Asn1BitStringRef {
+        unsafe {
+            let mut signature = ptr::null();
+            ffi::X509_ACERT_get0_signature(self.as_ptr(), &mut signature, ptr::null_mut());
+            Asn1BitStringRef::from_const_ptr_opt(signature).expect("signature must not be null")
+        }
+    }
+
+    /// Returns the certificate's signature algorithm, including any parameters.
+    #[corresponds(X509_ACERT_get0_signature)]
+    pub fn signature_algorithm(&self) -> &X509AlgorithmRef {
+        unsafe {
+            let mut algor = ptr::null();
+            ffi::X509_ACERT_get0_signature(self.as_ptr(), ptr::null_mut(), &mut algor);
+            X509AlgorithmRef::from_const_ptr_opt(algor)
+                .expect("signature algorithm must not be null")
+        }
+    }
+
+    /// Checks that the certificate is signed by the private key corresponding to `key`.
+    ///
+    /// Returns `true` if verification succeeds.
+    #[corresponds(X509_ACERT_verify)]
+    pub fn verify<T>(&self, key: &PKeyRef<T>) -> Result<bool, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe { cvt_n(ffi::X509_ACERT_verify(self.as_ptr(), key.as_ptr())).map(|n| n != 0) }
+    }
+}
+
+/// A builder used to construct an `X509Acert`.
+///
+/// Requires OpenSSL 3.4.0 or newer.
+pub struct X509AcertBuilder(X509Acert);
+
+impl X509AcertBuilder {
+    /// Creates a new builder for a v2 attribute certificate.
+    #[corresponds(X509_ACERT_new)]
+    pub fn new() -> Result<X509AcertBuilder, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let acert = X509Acert::from_ptr(cvt_p(ffi::X509_ACERT_new())?);
+            cvt(ffi::X509_ACERT_set_version(
+                acert.as_ptr(),
+                ffi::X509_ACERT_VERSION_2,
+            ))?;
+            Ok(X509AcertBuilder(acert))
+        }
+    }
+
+    /// Identifies the holder by its public key certificate.
+    ///
+    /// The issuer name and serial number of `cert` are stored in the holder's
+    /// `baseCertificateID`.
+    #[corresponds(X509_ACERT_set0_holder_baseCertId)]
+    pub fn set_holder_certificate(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe {
+            let id = cvt_p(ffi::OSSL_ISSUER_SERIAL_new())?;
+            let r = cvt(ffi::OSSL_ISSUER_SERIAL_set1_issuer(
+                id,
+                cert.issuer_name().as_ptr(),
+            ))
+            .and_then(|_| {
+                cvt(ffi::OSSL_ISSUER_SERIAL_set1_serial(
+                    id,
+                    cert.serial_number().as_ptr(),
+                ))
+            });
+            if let Err(e) = r {
+                ffi::OSSL_ISSUER_SERIAL_free(id);
+                return Err(e);
+            }
+            ffi::X509_ACERT_set0_holder_baseCertId(self.0.as_ptr(), id);
+            Ok(())
+        }
+    }
+
+    /// Identifies the holder by a directory name.
+    #[corresponds(X509_ACERT_set0_holder_entityName)]
+    pub fn set_holder_name(&mut self, name: &X509NameRef) -> Result<(), ErrorStack> {
+        unsafe {
+            let name = name.to_owned()?;
+            let general_name = GeneralName::from_ptr(cvt_p(ffi::GENERAL_NAME_new())?);
+            ffi::GENERAL_NAME_set0_value(
+                general_name.as_ptr(),
+                ffi::GEN_DIRNAME,
+                name.as_ptr() as *mut _,
+            );
+            mem::forget(name);
+            let mut names = Stack::new()?;
+            names.push(general_name)?;
+            ffi::X509_ACERT_set0_holder_entityName(self.0.as_ptr(), names.as_ptr());
+            mem::forget(names);
+            Ok(())
+        }
+    }
+
+    /// Sets the directory name of the attribute authority issuing the certificate.
+    #[corresponds(X509_ACERT_set1_issuerName)]
+    pub fn set_issuer_name(&mut self, issuer_name: &X509NameRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_ACERT_set1_issuerName(
+                self.0.as_ptr(),
+                issuer_name.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the serial number of the certificate.
+    #[corresponds(X509_ACERT_set1_serialNumber)]
+    pub fn set_serial_number(&mut self, serial_number: &Asn1IntegerRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_ACERT_set1_serialNumber(
+                self.0.as_ptr(),
+                serial_number.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the time at which the certificate becomes valid.
+    ///
+    /// The time is stored as a GeneralizedTime, as RFC 5755 requires.
+    #[corresponds(X509_ACERT_set1_notBefore)]
+    pub fn set_not_before(&mut self, not_before: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            let time = to_generalized_time(not_before)?;
+            cvt(ffi::X509_ACERT_set1_notBefore(
+                self.0.as_ptr(),
+                time.as_ptr() as *const _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the time after which the certificate is no longer valid.
+    ///
+    /// The time is stored as a GeneralizedTime, as RFC 5755 requires.
+    #[corresponds(X509_ACERT_set1_notAfter)]
+    pub fn set_not_after(&mut self, not_after: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            let time = to_generalized_time(not_after)?;
+            cvt(ffi::X509_ACERT_set1_notAfter(
+                self.0.as_ptr(),
+                time.as_ptr() as *const _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds an attribute granted to the holder.
+    #[corresponds(X509_ACERT_add1_attr)]
+    pub fn append_attribute(&mut self, attribute: &X509AttributeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_ACERT_add1_attr(
+                self.0.as_ptr(),
+                attribute.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Signs the certificate with the attribute authority's private key.
+    #[corresponds(X509_ACERT_sign)]
+    pub fn sign<T>(&mut self, key: &PKeyRef<T>, hash: MessageDigest) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            cvt(ffi::X509_ACERT_sign(
+                self.0.as_ptr(),
+                key.as_ptr(),
+                hash.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Consumes the builder, returning the certificate.
+    pub fn build(self) -> X509Acert {
+        self.0
+    }
+}
+
+/// Converts `time` to a GeneralizedTime.
+fn to_generalized_time(time: &Asn1TimeRef) -> Result<Asn1String, ErrorStack> {
+    unsafe {
+        let time = cvt_p(ffi::ASN1_TIME_to_generalizedtime(
+            time.as_ptr(),
+            ptr::null_mut(),
+        ))?;
+        Ok(Asn1String::from_ptr(time as *mut ffi::ASN1_STRING))
+    }
+}
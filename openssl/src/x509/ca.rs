@@ -0,0 +1,302 @@
+//! A minimal certificate authority.
+//!
+//! [`CertificateAuthority`] issues certificates according to an [`IssuanceProfile`], which
+//! selects the extensions appropriate to the certificate's purpose. Serial numbers are random,
+//! key identifiers chain each certificate to its issuer, and validity periods are clamped so that
+//! they do not outlive the issuer.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::nid::Nid;
+//! use openssl::pkey::PKey;
+//! use openssl::rsa::Rsa;
+//! use openssl::x509::ca::{CertificateAuthority, IssuanceProfile};
+//! use openssl::x509::X509Name;
+//!
+//! let mut name = X509Name::builder().unwrap();
+//! name.append_entry_by_nid(Nid::COMMONNAME, "Example Root CA").unwrap();
+//! let name = name.build();
+//! let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+//! let ca = CertificateAuthority::self_signed(&name, key, 3650).unwrap();
+//!
+//! let mut name = X509Name::builder().unwrap();
+//! name.append_entry_by_nid(Nid::COMMONNAME, "example.com").unwrap();
+//! let name = name.build();
+//! let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+//! let cert = ca
+//!     .issue(IssuanceProfile::tls_server().dns("example.com"), &name, &key)
+//!     .unwrap();
+//! ```
+use crate::asn1::Asn1Time;
+use crate::bn::{BigNum, MsbOption};
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::pkey::{HasPublic, Id, PKey, PKeyRef, Private};
+use crate::x509::extension::{
+    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
+    SubjectKeyIdentifier,
+};
+use crate::x509::{X509Builder, X509NameRef, X509Ref, X509};
+
+/// The number of bits in generated serial numbers.
+///
+/// RFC 5280 limits serial numbers to 20 octets, and they must be positive.
+const SERIAL_BITS: i32 = 159;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    TlsServer,
+    TlsClient,
+    IntermediateCa,
+}
+
+/// The purpose and contents of a certificate to be issued by a [`CertificateAuthority`].
+#[derive(Debug, Clone)]
+pub struct IssuanceProfile {
+    kind: Kind,
+    validity_days: u32,
+    path_length: Option<u32>,
+    dns_names: Vec<String>,
+    ip_addresses: Vec<String>,
+}
+
+impl IssuanceProfile {
+    /// Returns a profile for TLS server certificates.
+    ///
+    /// The certificates have the `serverAuth` extended key usage and are valid for 397 days by
+    /// default, the longest period accepted by web browsers. The names of the server should be
+    /// added with [`dns`] and [`ip`].
+    ///
+    /// [`dns`]: IssuanceProfile::dns
+    /// [`ip`]: IssuanceProfile::ip
+    pub fn tls_server() -> IssuanceProfile {
+        IssuanceProfile::new(Kind::TlsServer, 397)
+    }
+
+    /// Returns a profile for TLS client certificates.
+    ///
+    /// The certificates have the `clientAuth` extended key usage and are valid for 397 days by
+    /// default.
+    pub fn tls_client() -> IssuanceProfile {
+        IssuanceProfile::new(Kind::TlsClient, 397)
+    }
+
+    /// Returns a profile for intermediate CA certificates.
+    ///
+    /// The certificates may sign certificates and CRLs, and are valid for 5 years by default.
+    pub fn intermediate_ca() -> IssuanceProfile {
+        IssuanceProfile::new(Kind::IntermediateCa, 5 * 365)
+    }
+
+    fn new(kind: Kind, validity_days: u32) -> IssuanceProfile {
+        IssuanceProfile {
+            kind,
+            validity_days,
+            path_length: None,
+            dns_names: vec![],
+            ip_addresses: vec![],
+        }
+    }
+
+    /// Sets the number of days the certificates are valid for, starting from their issuance.
+    pub fn validity_days(&mut self, days: u32) -> &mut IssuanceProfile {
+        self.validity_days = days;
+        self
+    }
+
+    /// Sets the maximum number of intermediate CA certificates which may follow the certificates
+    /// in a path.
+    ///
+    /// This only applies to intermediate CA certificates.
+    pub fn path_length(&mut self, path_length: u32) -> &mut IssuanceProfile {
+        self.path_length = Some(path_length);
+        self
+    }
+
+    /// Adds a DNS name to the subject alternative names of the certificates.
+    pub fn dns(&mut self, name: &str) -> &mut IssuanceProfile {
+        self.dns_names.push(name.to_string());
+        self
+    }
+
+    /// Adds an IP address to the subject alternative names of the certificates.
+    pub fn ip(&mut self, address: &str) -> &mut IssuanceProfile {
+        self.ip_addresses.push(address.to_string());
+        self
+    }
+}
+
+/// A certificate authority, consisting of a CA certificate and its private key.
+pub struct CertificateAuthority {
+    cert: X509,
+    key: PKey<Private>,
+    digest: MessageDigest,
+}
+
+impl CertificateAuthority {
+    /// Creates a certificate authority from its certificate and the corresponding private key.
+    ///
+    /// Certificates are signed with SHA-256, or without a separate digest for Ed25519 and Ed448
+    /// keys.
+    pub fn new(cert: X509, key: PKey<Private>) -> CertificateAuthority {
+        let digest = default_digest(&key);
+        CertificateAuthority { cert, key, digest }
+    }
+
+    /// Creates a certificate authority with a new self-signed root certificate.
+    pub fn self_signed(
+        subject: &X509NameRef,
+        key: PKey<Private>,
+        validity_days: u32,
+    ) -> Result<CertificateAuthority, ErrorStack> {
+        let mut builder = new_builder(subject, &key)?;
+        builder.set_issuer_name(subject)?;
+        let not_after = Asn1Time::days_from_now(validity_days)?;
+        builder.set_not_after(&not_after)?;
+        builder.append_extension(BasicConstraints::new().critical().ca().build()?)?;
+        builder.append_extension(
+            KeyUsage::new()
+                .critical()
+                .key_cert_sign()
+                .crl_sign()
+                .build()?,
+        )?;
+        let subject_key_identifier =
+            SubjectKeyIdentifier::new().build(&builder.x509v3_context(None, None))?;
+        builder.append_extension(subject_key_identifier)?;
+        let authority_key_identifier = AuthorityKeyIdentifier::new()
+            .keyid(false)
+            .build(&builder.x509v3_context(None, None))?;
+        builder.append_extension(authority_key_identifier)?;
+
+        let digest = default_digest(&key);
+        builder.sign(&key, digest)?;
+        Ok(CertificateAuthority {
+            cert: builder.build(),
+            key,
+            digest,
+        })
+    }
+
+    /// Sets the digest used to sign certificates.
+    pub fn set_digest(&mut self, digest: MessageDigest) {
+        self.digest = digest;
+    }
+
+    /// Returns the certificate of the certificate authority.
+    pub fn certificate(&self) -> &X509Ref {
+        &self.cert
+    }
+
+    /// Issues a certificate for `public_key` according to `profile`.
+    ///
+    /// The certificate is valid from the current time. If the validity period in `profile` would
+    /// extend past the expiry of the certificate authority, the certificate expires along with it.
+    pub fn issue<T>(
+        &self,
+        profile: &IssuanceProfile,
+        subject: &X509NameRef,
+        public_key: &PKeyRef<T>,
+    ) -> Result<X509, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        let mut builder = new_builder(subject, public_key)?;
+        builder.set_issuer_name(self.cert.subject_name())?;
+        let not_after = Asn1Time::days_from_now(profile.validity_days)?;
+        if self.cert.not_after() < not_after {
+            builder.set_not_after(self.cert.not_after())?;
+        } else {
+            builder.set_not_after(&not_after)?;
+        }
+
+        match profile.kind {
+            Kind::TlsServer | Kind::TlsClient => {
+                builder.append_extension(BasicConstraints::new().critical().build()?)?;
+                let mut key_usage = KeyUsage::new();
+                key_usage.critical().digital_signature();
+                // RSA key exchange encrypts the premaster secret to the server's key
+                if profile.kind == Kind::TlsServer && public_key.id() == Id::RSA {
+                    key_usage.key_encipherment();
+                }
+                builder.append_extension(key_usage.build()?)?;
+                let mut extended_key_usage = ExtendedKeyUsage::new();
+                if profile.kind == Kind::TlsServer {
+                    extended_key_usage.server_auth();
+                } else {
+                    extended_key_usage.client_auth();
+                }
+                builder.append_extension(extended_key_usage.build()?)?;
+            }
+            Kind::IntermediateCa => {
+                let mut basic_constraints = BasicConstraints::new();
+                basic_constraints.critical().ca();
+                if let Some(path_length) = profile.path_length {
+                    basic_constraints.pathlen(path_length);
+                }
+                builder.append_extension(basic_constraints.build()?)?;
+                builder.append_extension(
+                    KeyUsage::new()
+                        .critical()
+                        .key_cert_sign()
+                        .crl_sign()
+                        .build()?,
+                )?;
+            }
+        }
+
+        if !profile.dns_names.is_empty() || !profile.ip_addresses.is_empty() {
+            let mut subject_alt_name = SubjectAlternativeName::new();
+            for name in &profile.dns_names {
+                subject_alt_name.dns(name);
+            }
+            for address in &profile.ip_addresses {
+                subject_alt_name.ip(address);
+            }
+            let subject_alt_name =
+                subject_alt_name.build(&builder.x509v3_context(Some(&self.cert), None))?;
+            builder.append_extension(subject_alt_name)?;
+        }
+
+        let subject_key_identifier =
+            SubjectKeyIdentifier::new().build(&builder.x509v3_context(Some(&self.cert), None))?;
+        builder.append_extension(subject_key_identifier)?;
+        let authority_key_identifier = AuthorityKeyIdentifier::new()
+            .keyid(false)
+            .build(&builder.x509v3_context(Some(&self.cert), None))?;
+        builder.append_extension(authority_key_identifier)?;
+
+        builder.sign(&self.key, self.digest)?;
+        Ok(builder.build())
+    }
+}
+
+/// Returns a builder with the fields common to every issued certificate set.
+fn new_builder<T>(subject: &X509NameRef, public_key: &PKeyRef<T>) -> Result<X509Builder, ErrorStack>
+where
+    T: HasPublic,
+{
+    let mut builder = X509Builder::new()?;
+    builder.set_version(2)?;
+    let mut serial = BigNum::new()?;
+    serial.rand(SERIAL_BITS, MsbOption::MAYBE_ZERO, false)?;
+    let serial = serial.to_asn1_integer()?;
+    builder.set_serial_number(&serial)?;
+    builder.set_subject_name(subject)?;
+    builder.set_pubkey(public_key)?;
+    let not_before = Asn1Time::days_from_now(0)?;
+    builder.set_not_before(&not_before)?;
+    Ok(builder)
+}
+
+#[allow(unused_variables)]
+fn default_digest(key: &PKeyRef<Private>) -> MessageDigest {
+    #[cfg(ossl111)]
+    {
+        if key.id() == Id::ED25519 || key.id() == Id::ED448 {
+            return MessageDigest::null();
+        }
+    }
+    MessageDigest::sha256()
+}
@@ -0,0 +1,466 @@
+//! Compact revocation lookups for large certificate revocation lists.
+//!
+//! Parsing a CRL into an [`X509Crl`] allocates an `X509_REVOKED` object, with its serial number,
+//! revocation date and entry extensions, for every entry in the list. For CRLs with millions of
+//! entries this costs far more memory than is needed to answer whether a given certificate has
+//! been revoked, and [`X509CrlRef::revoked`] can only be searched linearly.
+//!
+//! An [`X509CrlIndex`] keeps only the serial numbers, packed into a single sorted buffer, along
+//! with the issuer name and update times of the CRL. [`X509CrlIndex::from_der_reader`] builds one
+//! while streaming the CRL from a reader, verifying its signature as it goes, so the encoded CRL
+//! never needs to be held in memory either.
+//!
+//! Indirect CRLs, whose entries may name a different certificate issuer, and entries with the
+//! `removeFromCRL` reason code used by delta CRLs are not treated specially: every listed serial
+//! number is considered revoked.
+//!
+//! [`X509Crl`]: crate::x509::X509Crl
+use foreign_types::{ForeignType, ForeignTypeRef};
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, Read};
+use std::str;
+
+use crate::asn1::{get_object, unknown_signature_algorithm, Asn1Object, Asn1Time, Asn1TimeRef};
+use crate::bn::BigNumRef;
+use crate::cvt_p;
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::nid::Nid;
+use crate::pkey::{HasPublic, PKeyRef};
+use crate::sign::Verifier;
+use crate::x509::{read_der_contents, read_der_header, X509CrlRef, X509Name, X509NameRef};
+
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+const UTC_TIME: u8 = 0x17;
+const GENERALIZED_TIME: u8 = 0x18;
+const SEQUENCE: u8 = 0x30;
+const CRL_EXTENSIONS: u8 = 0xa0;
+
+/// An index of the serial numbers revoked by a CRL.
+pub struct X509CrlIndex {
+    issuer_name: X509Name,
+    last_update: Asn1Time,
+    next_update: Option<Asn1Time>,
+    serials: Serials,
+}
+
+impl X509CrlIndex {
+    /// Builds an index of an already parsed CRL.
+    ///
+    /// The CRL's signature is not checked; use [`X509CrlRef::verify`] for that.
+    pub fn new(crl: &X509CrlRef) -> Result<X509CrlIndex, ErrorStack> {
+        let mut serials = SerialsBuilder::new();
+        if let Some(revoked) = crl.revoked() {
+            for entry in revoked {
+                serials.push_bn(&*entry.serial_number().to_bn()?);
+            }
+        }
+
+        Ok(X509CrlIndex {
+            issuer_name: X509Name::from_der(&crl.issuer_name().to_der()?)?,
+            last_update: copy_time(crl.last_update())?,
+            next_update: crl.next_update().map(copy_time).transpose()?,
+            serials: serials.build(),
+        })
+    }
+
+    /// Builds an index of a DER-encoded CRL read from `reader`.
+    ///
+    /// The CRL is parsed one revoked entry at a time and its signature is checked against
+    /// `issuer_key` as it is read. An error of kind `InvalidData` is returned if the CRL is
+    /// malformed or its signature does not verify.
+    ///
+    /// Only signature algorithms with a separate digest are supported, which excludes Ed25519,
+    /// Ed448 and RSA-PSS signatures.
+    pub fn from_der_reader<R, T>(reader: R, issuer_key: &PKeyRef<T>) -> io::Result<X509CrlIndex>
+    where
+        R: Read,
+        T: HasPublic,
+    {
+        let mut parser = Parser {
+            reader,
+            signature: Signature::Pending(vec![]),
+            key: issuer_key,
+        };
+
+        let mut crl = match read_der_header(&mut parser.reader)? {
+            Some((header, len)) if header[0] == SEQUENCE => len,
+            Some(_) => return Err(invalid_data("expected a CRL")),
+            None => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+        };
+
+        let (tbs_header, tbs_len) = parser.element(&mut crl, false)?;
+        if tbs_header[0] != SEQUENCE {
+            return Err(invalid_data("expected a TBSCertList"));
+        }
+        parser.signature.update(&tbs_header)?;
+        let mut tbs = tbs_len;
+
+        let (mut header, mut len) = parser.element(&mut tbs, true)?;
+        if header[0] == INTEGER {
+            parser.contents(len, &mut tbs, true)?;
+            let next = parser.element(&mut tbs, true)?;
+            header = next.0;
+            len = next.1;
+        }
+        if header[0] != SEQUENCE {
+            return Err(invalid_data("expected a signature algorithm"));
+        }
+        let mut algorithm = header;
+        algorithm.extend(parser.contents(len, &mut tbs, true)?);
+        parser.start_verifier(&algorithm)?;
+
+        let (mut issuer, len) = parser.element(&mut tbs, true)?;
+        if issuer[0] != SEQUENCE {
+            return Err(invalid_data("expected an issuer name"));
+        }
+        issuer.extend(parser.contents(len, &mut tbs, true)?);
+        let issuer_name = X509Name::from_der(&issuer)?;
+
+        let (header, len) = parser.element(&mut tbs, true)?;
+        let last_update = parser.time(&header, len, &mut tbs)?;
+
+        let mut next = parser.next_element(&mut tbs)?;
+        let mut next_update = None;
+        if let Some((header, len)) = take_tag(&mut next, is_time) {
+            next_update = Some(parser.time(&header, len, &mut tbs)?);
+            next = parser.next_element(&mut tbs)?;
+        }
+
+        let mut serials = SerialsBuilder::new();
+        if let Some((_, len)) = take_tag(&mut next, |tag| tag == SEQUENCE) {
+            let mut list = len;
+            while list > 0 {
+                let (header, len) = parser.element(&mut list, true)?;
+                if header[0] != SEQUENCE {
+                    return Err(invalid_data("expected a revoked certificate"));
+                }
+                let entry = parser.contents(len, &mut list, true)?;
+                let mut entry = &entry[..];
+                match get_object(&mut entry)? {
+                    (ffi::V_ASN1_INTEGER, ffi::V_ASN1_UNIVERSAL, serial) if !serial.is_empty() => {
+                        serials.push_der(serial)
+                    }
+                    _ => return Err(invalid_data("expected a serial number")),
+                }
+            }
+            tbs -= len;
+            next = parser.next_element(&mut tbs)?;
+        }
+
+        if let Some((_, len)) = take_tag(&mut next, |tag| tag == CRL_EXTENSIONS) {
+            parser.contents(len, &mut tbs, true)?;
+            next = parser.next_element(&mut tbs)?;
+        }
+        if next.is_some() {
+            return Err(invalid_data("unexpected data in TBSCertList"));
+        }
+        crl -= tbs_len;
+
+        let (mut outer_algorithm, len) = parser.element(&mut crl, false)?;
+        outer_algorithm.extend(parser.contents(len, &mut crl, false)?);
+        if outer_algorithm != algorithm {
+            return Err(invalid_data("mismatched signature algorithms"));
+        }
+
+        let (header, len) = parser.element(&mut crl, false)?;
+        if header[0] != BIT_STRING || crl != len {
+            return Err(invalid_data("expected a signature"));
+        }
+        let signature = parser.contents(len, &mut crl, false)?;
+        let signature = match signature.split_first() {
+            Some((0, signature)) => signature,
+            _ => return Err(invalid_data("expected a signature")),
+        };
+
+        match parser.signature {
+            Signature::Verifying(verifier) if verifier.verify(signature)? => {}
+            _ => return Err(invalid_data("CRL signature verification failed")),
+        }
+
+        Ok(X509CrlIndex {
+            issuer_name,
+            last_update,
+            next_update,
+            serials: serials.build(),
+        })
+    }
+
+    /// Determines if a certificate with the specified serial number is revoked by the CRL.
+    pub fn is_revoked(&self, serial_number: &BigNumRef) -> bool {
+        let mut key = vec![serial_number.is_negative() as u8];
+        key.extend(serial_number.to_vec());
+        self.serials.contains(&key)
+    }
+
+    /// Returns the number of distinct revoked serial numbers.
+    pub fn len(&self) -> usize {
+        self.serials.len()
+    }
+
+    /// Determines if the CRL revokes no certificates.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the name of the CRL's issuer.
+    pub fn issuer_name(&self) -> &X509NameRef {
+        &self.issuer_name
+    }
+
+    /// Returns the CRL's `lastUpdate` time.
+    pub fn last_update(&self) -> &Asn1TimeRef {
+        &self.last_update
+    }
+
+    /// Returns the CRL's `nextUpdate` time, if present.
+    pub fn next_update(&self) -> Option<&Asn1TimeRef> {
+        self.next_update.as_deref()
+    }
+}
+
+impl fmt::Debug for X509CrlIndex {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("X509CrlIndex")
+            .field("issuer_name", &self.issuer_name())
+            .field("last_update", &self.last_update())
+            .field("next_update", &self.next_update())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+enum Signature<'a> {
+    /// The digest is not known yet, so the signed data read so far is buffered.
+    Pending(Vec<u8>),
+    Verifying(Verifier<'a>),
+}
+
+impl<'a> Signature<'a> {
+    fn update(&mut self, data: &[u8]) -> io::Result<()> {
+        match self {
+            Signature::Pending(buf) => buf.extend_from_slice(data),
+            Signature::Verifying(verifier) => verifier.update(data)?,
+        }
+        Ok(())
+    }
+}
+
+struct Parser<'a, R, T> {
+    reader: R,
+    signature: Signature<'a>,
+    key: &'a PKeyRef<T>,
+}
+
+impl<'a, R, T> Parser<'a, R, T>
+where
+    R: Read,
+    T: HasPublic,
+{
+    /// Reads the header of an element which must fit in the `remaining` bytes of its parent,
+    /// returning the encoded header and the length of the element's contents.
+    fn element(&mut self, remaining: &mut usize, signed: bool) -> io::Result<(Vec<u8>, usize)> {
+        match self.next_element_inner(remaining, signed)? {
+            Some(element) => Ok(element),
+            None => Err(invalid_data("missing CRL field")),
+        }
+    }
+
+    /// Like `element`, but returns `None` if the parent has no bytes remaining.
+    fn next_element(&mut self, remaining: &mut usize) -> io::Result<Option<(Vec<u8>, usize)>> {
+        self.next_element_inner(remaining, true)
+    }
+
+    fn next_element_inner(
+        &mut self,
+        remaining: &mut usize,
+        signed: bool,
+    ) -> io::Result<Option<(Vec<u8>, usize)>> {
+        if *remaining == 0 {
+            return Ok(None);
+        }
+        let (header, len) = read_der_header(&mut self.reader)?
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        match header.len().checked_add(len) {
+            Some(total) if total <= *remaining => *remaining -= header.len(),
+            _ => return Err(invalid_data("CRL field overruns its parent")),
+        }
+        if signed {
+            self.signature.update(&header)?;
+        }
+        Ok(Some((header, len)))
+    }
+
+    /// Reads `len` bytes of contents of an element whose header was returned by `element`.
+    fn contents(&mut self, len: usize, remaining: &mut usize, signed: bool) -> io::Result<Vec<u8>> {
+        let mut buf = vec![];
+        read_der_contents(&mut self.reader, len, &mut buf)?;
+        *remaining -= len;
+        if signed {
+            self.signature.update(&buf)?;
+        }
+        Ok(buf)
+    }
+
+    fn time(&mut self, header: &[u8], len: usize, remaining: &mut usize) -> io::Result<Asn1Time> {
+        if !is_time(header[0]) {
+            return Err(invalid_data("expected a time"));
+        }
+        let contents = self.contents(len, remaining, true)?;
+        let time = str::from_utf8(&contents).map_err(|_| invalid_data("invalid time"))?;
+        Ok(Asn1Time::from_str(time)?)
+    }
+
+    /// Switches from buffering the signed data to feeding it to a verifier for the encoded
+    /// `AlgorithmIdentifier` in `algorithm`.
+    fn start_verifier(&mut self, algorithm: &[u8]) -> io::Result<()> {
+        let mut der = algorithm;
+        let (_, _, mut contents) = get_object(&mut der)?;
+        let start = contents;
+        get_object(&mut contents)?;
+        let oid = Asn1Object::from_der(&start[..start.len() - contents.len()])?;
+
+        let algorithms = oid
+            .nid()
+            .signature_algorithms()
+            .ok_or_else(unknown_signature_algorithm)?;
+        if algorithms.pkey.as_raw() != self.key.id().as_raw() {
+            return Err(invalid_data("CRL signature verification failed"));
+        }
+        if algorithms.digest == Nid::UNDEF {
+            return Err(unknown_signature_algorithm().into());
+        }
+        let digest =
+            MessageDigest::from_nid(algorithms.digest).ok_or_else(unknown_signature_algorithm)?;
+
+        let mut verifier = Verifier::new(digest, self.key)?;
+        if let Signature::Pending(buf) = &self.signature {
+            verifier.update(buf)?;
+        }
+        self.signature = Signature::Verifying(verifier);
+        Ok(())
+    }
+}
+
+/// Serial numbers packed end to end in a single sorted buffer.
+///
+/// Each serial number is encoded as a sign byte followed by its big-endian magnitude without
+/// leading zeros, so that equal numbers have equal encodings however they appeared in the CRL.
+struct Serials {
+    data: Vec<u8>,
+    /// `ends[i]` is the end offset of the `i`th serial number in `data`.
+    ends: Vec<usize>,
+}
+
+impl Serials {
+    fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    fn get(&self, i: usize) -> &[u8] {
+        let start = if i == 0 { 0 } else { self.ends[i - 1] };
+        &self.data[start..self.ends[i]]
+    }
+
+    fn contains(&self, key: &[u8]) -> bool {
+        let mut lo = 0;
+        let mut hi = self.len();
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match self.get(mid).cmp(key) {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+}
+
+struct SerialsBuilder(Serials);
+
+impl SerialsBuilder {
+    fn new() -> SerialsBuilder {
+        SerialsBuilder(Serials {
+            data: vec![],
+            ends: vec![],
+        })
+    }
+
+    fn push_bn(&mut self, serial: &BigNumRef) {
+        self.0.data.push(serial.is_negative() as u8);
+        self.0.data.extend(serial.to_vec());
+        self.0.ends.push(self.0.data.len());
+    }
+
+    /// Adds a serial number from the two's complement contents of a DER INTEGER.
+    fn push_der(&mut self, contents: &[u8]) {
+        let negative = contents[0] & 0x80 != 0;
+        let start = self.0.data.len();
+        self.0.data.push(negative as u8);
+        self.0.data.extend_from_slice(contents);
+        let magnitude = &mut self.0.data[start + 1..];
+        if negative {
+            for b in magnitude.iter_mut() {
+                *b = !*b;
+            }
+            for b in magnitude.iter_mut().rev() {
+                *b = b.wrapping_add(1);
+                if *b != 0 {
+                    break;
+                }
+            }
+        }
+        let zeros = magnitude.iter().take_while(|b| **b == 0).count();
+        self.0.data.drain(start + 1..start + 1 + zeros);
+        self.0.ends.push(self.0.data.len());
+    }
+
+    fn build(self) -> Serials {
+        let unsorted = self.0;
+        let mut order = (0..unsorted.len()).collect::<Vec<_>>();
+        order.sort_unstable_by(|a, b| unsorted.get(*a).cmp(unsorted.get(*b)));
+        order.dedup_by(|a, b| unsorted.get(*a) == unsorted.get(*b));
+
+        let mut serials = Serials {
+            data: Vec::with_capacity(unsorted.data.len()),
+            ends: Vec::with_capacity(order.len()),
+        };
+        for i in order {
+            serials.data.extend_from_slice(unsorted.get(i));
+            serials.ends.push(serials.data.len());
+        }
+        serials.data.shrink_to_fit();
+        serials
+    }
+}
+
+/// Removes the element in `next` if its tag satisfies `f`, returning it.
+fn take_tag<F>(next: &mut Option<(Vec<u8>, usize)>, f: F) -> Option<(Vec<u8>, usize)>
+where
+    F: Fn(u8) -> bool,
+{
+    match next {
+        Some((header, _)) if f(header[0]) => next.take(),
+        _ => None,
+    }
+}
+
+fn is_time(tag: u8) -> bool {
+    tag == UTC_TIME || tag == GENERALIZED_TIME
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn copy_time(time: &Asn1TimeRef) -> Result<Asn1Time, ErrorStack> {
+    unsafe {
+        cvt_p(ffi::ASN1_STRING_dup(
+            time.as_ptr() as *const ffi::ASN1_STRING
+        ))
+        .map(|p| Asn1Time::from_ptr(p as *mut ffi::ASN1_TIME))
+    }
+}
@@ -0,0 +1,130 @@
+//! Cross-certificate pairs.
+//!
+//! Bridge and federated PKIs link certificate authorities by having each CA
+//! issue a certificate for the other's key. The two certificates are
+//! published together as a `crossCertificatePair`, as defined in
+//! [RFC 4523 section 2.18]:
+//!
+//! ```text
+//! CertificatePair ::= SEQUENCE {
+//!     forward  [0] Certificate OPTIONAL,
+//!     reverse  [1] Certificate OPTIONAL
+//!     -- at least one of the pair shall be present -- }
+//! ```
+//!
+//! [RFC 4523 section 2.18]: https://www.rfc-editor.org/rfc/rfc4523#section-2.18
+use libc::c_int;
+
+use crate::asn1::{get_object, put_object, wrong_tag};
+use crate::error::ErrorStack;
+use crate::x509::{X509Ref, X509};
+
+const FORWARD_TAG: c_int = 0;
+const REVERSE_TAG: c_int = 1;
+
+/// A pair of certificates issued between two certificate authorities.
+///
+/// The forward certificate is the one issued to this CA by the other CA, and
+/// the reverse certificate is the one issued by this CA to the other CA.
+#[derive(Clone)]
+pub struct X509CrossCertificatePair {
+    forward: Option<X509>,
+    reverse: Option<X509>,
+}
+
+impl X509CrossCertificatePair {
+    /// Creates a new pair from its forward and reverse certificates.
+    ///
+    /// At least one of the certificates should be present.
+    pub fn new(forward: Option<X509>, reverse: Option<X509>) -> X509CrossCertificatePair {
+        X509CrossCertificatePair { forward, reverse }
+    }
+
+    /// Returns the certificate issued to this CA by the other CA.
+    pub fn forward(&self) -> Option<&X509Ref> {
+        self.forward.as_deref()
+    }
+
+    /// Returns the certificate issued by this CA to the other CA.
+    pub fn reverse(&self) -> Option<&X509Ref> {
+        self.reverse.as_deref()
+    }
+
+    /// Checks that both certificates of the pair are present and cross-certify each other.
+    ///
+    /// See [`X509Ref::cross_certifies`] for the checks performed.
+    ///
+    /// [`X509Ref::cross_certifies`]: ../struct.X509Ref.html#method.cross_certifies
+    pub fn verify(&self) -> Result<bool, ErrorStack> {
+        match (&self.forward, &self.reverse) {
+            (Some(forward), Some(reverse)) => forward.cross_certifies(reverse),
+            _ => Ok(false),
+        }
+    }
+
+    /// Deserializes a DER-encoded `CertificatePair` structure.
+    pub fn from_der(der: &[u8]) -> Result<X509CrossCertificatePair, ErrorStack> {
+        let mut der = der;
+        let mut body = match get_object(&mut der)? {
+            (ffi::V_ASN1_SEQUENCE, ffi::V_ASN1_UNIVERSAL, body) => body,
+            _ => return Err(wrong_tag()),
+        };
+
+        let forward = get_certificate(&mut body, FORWARD_TAG)?;
+        let reverse = get_certificate(&mut body, REVERSE_TAG)?;
+        if !body.is_empty() {
+            return Err(wrong_tag());
+        }
+
+        Ok(X509CrossCertificatePair { forward, reverse })
+    }
+
+    /// Serializes the pair into a DER-encoded `CertificatePair` structure.
+    pub fn to_der(&self) -> Result<Vec<u8>, ErrorStack> {
+        let mut body = vec![];
+        if let Some(forward) = &self.forward {
+            put_object(
+                &mut body,
+                FORWARD_TAG,
+                ffi::V_ASN1_CONTEXT_SPECIFIC,
+                true,
+                &forward.to_der()?,
+            );
+        }
+        if let Some(reverse) = &self.reverse {
+            put_object(
+                &mut body,
+                REVERSE_TAG,
+                ffi::V_ASN1_CONTEXT_SPECIFIC,
+                true,
+                &reverse.to_der()?,
+            );
+        }
+
+        let mut der = vec![];
+        put_object(
+            &mut der,
+            ffi::V_ASN1_SEQUENCE,
+            ffi::V_ASN1_UNIVERSAL,
+            true,
+            &body,
+        );
+        Ok(der)
+    }
+}
+
+/// Parses an optional explicitly tagged certificate off the front of `body`.
+fn get_certificate(body: &mut &[u8], tag: c_int) -> Result<Option<X509>, ErrorStack> {
+    if body.is_empty() {
+        return Ok(None);
+    }
+
+    let mut rest = *body;
+    match get_object(&mut rest)? {
+        (t, ffi::V_ASN1_CONTEXT_SPECIFIC, cert) if t == tag => {
+            *body = rest;
+            X509::from_der(cert).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
@@ -16,11 +16,14 @@
 //!
 //! let extension: X509Extension = bc.build().unwrap();
 //! ```
+use foreign_types::{ForeignType, ForeignTypeRef};
 use std::fmt::Write;
+use std::net::IpAddr;
 
+use crate::asn1::{put_object, Asn1Object, Asn1StringRef};
 use crate::error::ErrorStack;
 use crate::nid::Nid;
-use crate::x509::{X509Extension, X509v3Context};
+use crate::x509::{X509Extension, X509Name, X509v3Context};
 
 /// An extension which indicates whether a certificate is a CA certificate.
 pub struct BasicConstraints {
@@ -468,6 +471,42 @@ impl AuthorityKeyIdentifier {
 pub struct SubjectAlternativeName {
     critical: bool,
     names: Vec<String>,
+    encoded_names: Vec<EncodedName>,
+}
+
+/// A name which can't be expressed in OpenSSL's configuration syntax, and is encoded directly.
+enum EncodedName {
+    OtherName(Asn1Object, Vec<u8>),
+    DirName(X509Name),
+    Rid(Asn1Object),
+}
+
+impl EncodedName {
+    fn encode(&self, out: &mut Vec<u8>) -> Result<(), ErrorStack> {
+        match self {
+            EncodedName::OtherName(oid, value) => {
+                // AnotherName ::= SEQUENCE { type-id OBJECT IDENTIFIER, value [0] EXPLICIT ANY }
+                let mut name = oid.to_der()?;
+                put_object(&mut name, 0, ffi::V_ASN1_CONTEXT_SPECIFIC, true, value);
+                put_object(out, 0, ffi::V_ASN1_CONTEXT_SPECIFIC, true, &name);
+            }
+            EncodedName::DirName(dir_name) => put_object(
+                out,
+                4,
+                ffi::V_ASN1_CONTEXT_SPECIFIC,
+                true,
+                &dir_name.to_der()?,
+            ),
+            EncodedName::Rid(oid) => put_object(
+                out,
+                8,
+                ffi::V_ASN1_CONTEXT_SPECIFIC,
+                false,
+                der_contents(&oid.to_der()?),
+            ),
+        }
+        Ok(())
+    }
 }
 
 impl Default for SubjectAlternativeName {
@@ -482,6 +521,7 @@ impl SubjectAlternativeName {
         SubjectAlternativeName {
             critical: false,
             names: vec![],
+            encoded_names: vec![],
         }
     }
 
@@ -533,15 +573,577 @@ impl SubjectAlternativeName {
         self
     }
 
+    /// Adds an `otherName` of type `oid`, with the DER-encoded value `value`.
+    ///
+    /// For example, the user principal names used for smartcard logon have the type
+    /// `1.3.6.1.4.1.311.20.2.3` and a `UTF8String` value.
+    pub fn other_name_der(&mut self, oid: Asn1Object, value: &[u8]) -> &mut SubjectAlternativeName {
+        self.encoded_names
+            .push(EncodedName::OtherName(oid, value.to_vec()));
+        self
+    }
+
+    /// Adds a `directoryName`.
+    ///
+    /// Unlike [`dir_name`], which refers to a section of an OpenSSL configuration, this takes
+    /// the name itself.
+    ///
+    /// [`dir_name`]: SubjectAlternativeName::dir_name
+    pub fn directory_name(&mut self, dir_name: X509Name) -> &mut SubjectAlternativeName {
+        self.encoded_names.push(EncodedName::DirName(dir_name));
+        self
+    }
+
+    /// Adds a `registeredID`.
+    pub fn registered_id(&mut self, oid: Asn1Object) -> &mut SubjectAlternativeName {
+        self.encoded_names.push(EncodedName::Rid(oid));
+        self
+    }
+
     /// Return a `SubjectAlternativeName` extension as an `X509Extension`.
     pub fn build(&self, ctx: &X509v3Context<'_>) -> Result<X509Extension, ErrorStack> {
         let mut value = String::new();
         let mut first = true;
-        append(&mut value, &mut first, self.critical, "critical");
+        append(
+            &mut value,
+            &mut first,
+            self.critical && self.encoded_names.is_empty(),
+            "critical",
+        );
         for name in &self.names {
             append(&mut value, &mut first, true, name);
         }
-        X509Extension::new_nid(None, Some(ctx), Nid::SUBJECT_ALT_NAME, &value)
+        if self.encoded_names.is_empty() {
+            return X509Extension::new_nid(None, Some(ctx), Nid::SUBJECT_ALT_NAME, &value);
+        }
+
+        // GeneralNames ::= SEQUENCE OF GeneralName, starting with the names OpenSSL encodes
+        let mut names = vec![];
+        if !self.names.is_empty() {
+            let extension = X509Extension::new_nid(None, Some(ctx), Nid::SUBJECT_ALT_NAME, &value)?;
+            let der = unsafe {
+                let data = ffi::X509_EXTENSION_get_data(extension.as_ptr());
+                Asn1StringRef::from_ptr(data as *mut _).as_slice()
+            };
+            names.extend_from_slice(der_contents(der));
+        }
+        for name in &self.encoded_names {
+            name.encode(&mut names)?;
+        }
+        let mut der = vec![];
+        put_object(
+            &mut der,
+            ffi::V_ASN1_SEQUENCE,
+            ffi::V_ASN1_UNIVERSAL,
+            true,
+            &names,
+        );
+
+        let oid = Asn1Object::from_str("2.5.29.17")?;
+        X509Extension::new_from_der(&oid, self.critical, &der)
+    }
+}
+
+/// An extension that indicates how to access information and services for the subject of
+/// the certificate, such as the repository of certificates issued by a CA.
+pub struct SubjectInformationAccess {
+    critical: bool,
+    descriptions: Vec<String>,
+}
+
+impl Default for SubjectInformationAccess {
+    fn default() -> SubjectInformationAccess {
+        SubjectInformationAccess::new()
+    }
+}
+
+impl SubjectInformationAccess {
+    /// Construct a new `SubjectInformationAccess` extension.
+    pub fn new() -> SubjectInformationAccess {
+        SubjectInformationAccess {
+            critical: false,
+            descriptions: vec![],
+        }
+    }
+
+    /// Sets the `critical` flag to `true`. The extension will be critical.
+    pub fn critical(&mut self) -> &mut SubjectInformationAccess {
+        self.critical = true;
+        self
+    }
+
+    /// Adds a `caRepository` access description with the given URI.
+    pub fn ca_repository(&mut self, uri: &str) -> &mut SubjectInformationAccess {
+        self.descriptions.push(format!("caRepository;URI:{}", uri));
+        self
+    }
+
+    /// Adds a `timeStamping` access description with the given URI.
+    pub fn time_stamping(&mut self, uri: &str) -> &mut SubjectInformationAccess {
+        self.descriptions
+            .push(format!("ad_timestamping;URI:{}", uri));
+        self
+    }
+
+    /// Return the `SubjectInformationAccess` extension as an `X509Extension`.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        let mut value = String::new();
+        let mut first = true;
+        append(&mut value, &mut first, self.critical, "critical");
+        for description in &self.descriptions {
+            append(&mut value, &mut first, true, description);
+        }
+        X509Extension::new_nid(None, None, Nid::SINFO_ACCESS, &value)
+    }
+}
+
+/// An extension that identifies how delta CRL information for the certificate's issuer is
+/// obtained.
+pub struct FreshestCrl {
+    uris: Vec<String>,
+}
+
+impl Default for FreshestCrl {
+    fn default() -> FreshestCrl {
+        FreshestCrl::new()
+    }
+}
+
+impl FreshestCrl {
+    /// Construct a new `FreshestCrl` extension.
+    ///
+    /// RFC 5280 requires this extension to be non-critical.
+    pub fn new() -> FreshestCrl {
+        FreshestCrl { uris: vec![] }
+    }
+
+    /// Adds a distribution point identified by the given URI.
+    pub fn uri(&mut self, uri: &str) -> &mut FreshestCrl {
+        self.uris.push(format!("URI:{}", uri));
+        self
+    }
+
+    /// Return the `FreshestCrl` extension as an `X509Extension`.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        let mut value = String::new();
+        let mut first = true;
+        for uri in &self.uris {
+            append(&mut value, &mut first, true, uri);
+        }
+        X509Extension::new_nid(None, None, Nid::FRESHEST_CRL, &value)
+    }
+}
+
+/// A CRL extension which identifies the distribution point and scope of the CRL.
+pub struct IssuingDistributionPoint {
+    critical: bool,
+    uri: Option<String>,
+    only_user_certs: bool,
+    only_ca_certs: bool,
+    only_attribute_certs: bool,
+    indirect_crl: bool,
+}
+
+impl Default for IssuingDistributionPoint {
+    fn default() -> IssuingDistributionPoint {
+        IssuingDistributionPoint::new()
+    }
+}
+
+impl IssuingDistributionPoint {
+    /// Construct a new `IssuingDistributionPoint` extension.
+    pub fn new() -> IssuingDistributionPoint {
+        IssuingDistributionPoint {
+            critical: false,
+            uri: None,
+            only_user_certs: false,
+            only_ca_certs: false,
+            only_attribute_certs: false,
+            indirect_crl: false,
+        }
+    }
+
+    /// Sets the `critical` flag to `true`. The extension will be critical.
+    ///
+    /// RFC 5280 requires conforming CRL issuers to mark this extension as critical.
+    pub fn critical(&mut self) -> &mut IssuingDistributionPoint {
+        self.critical = true;
+        self
+    }
+
+    /// Sets the full name of the distribution point to the given URI.
+    pub fn uri(&mut self, uri: &str) -> &mut IssuingDistributionPoint {
+        self.uri = Some(format!("fullname:URI:{}", uri));
+        self
+    }
+
+    /// Sets the `onlyContainsUserCerts` flag to `true`.
+    pub fn only_user_certs(&mut self) -> &mut IssuingDistributionPoint {
+        self.only_user_certs = true;
+        self
+    }
+
+    /// Sets the `onlyContainsCACerts` flag to `true`.
+    pub fn only_ca_certs(&mut self) -> &mut IssuingDistributionPoint {
+        self.only_ca_certs = true;
+        self
+    }
+
+    /// Sets the `onlyContainsAttributeCerts` flag to `true`.
+    pub fn only_attribute_certs(&mut self) -> &mut IssuingDistributionPoint {
+        self.only_attribute_certs = true;
+        self
+    }
+
+    /// Sets the `indirectCRL` flag to `true`.
+    pub fn indirect_crl(&mut self) -> &mut IssuingDistributionPoint {
+        self.indirect_crl = true;
+        self
+    }
+
+    /// Return the `IssuingDistributionPoint` extension as an `X509Extension`.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        let mut value = String::new();
+        let mut first = true;
+        append(&mut value, &mut first, self.critical, "critical");
+        if let Some(uri) = &self.uri {
+            append(&mut value, &mut first, true, uri);
+        }
+        append(
+            &mut value,
+            &mut first,
+            self.only_user_certs,
+            "onlyuser:TRUE",
+        );
+        append(&mut value, &mut first, self.only_ca_certs, "onlyCA:TRUE");
+        append(
+            &mut value,
+            &mut first,
+            self.only_attribute_certs,
+            "onlyAA:TRUE",
+        );
+        append(
+            &mut value,
+            &mut first,
+            self.indirect_crl,
+            "indirectCRL:TRUE",
+        );
+        X509Extension::new_nid(None, None, Nid::ISSUING_DISTRIBUTION_POINT, &value)
+    }
+}
+
+/// An extension which indicates that the special `anyPolicy` OID is not considered a match
+/// for other certificate policies.
+pub struct InhibitAnyPolicy {
+    critical: bool,
+    skip_certs: u32,
+}
+
+impl InhibitAnyPolicy {
+    /// Construct a new `InhibitAnyPolicy` extension.
+    ///
+    /// `skip_certs` is the number of additional non-self-issued certificates which may appear in
+    /// the path before `anyPolicy` is no longer permitted.
+    pub fn new(skip_certs: u32) -> InhibitAnyPolicy {
+        InhibitAnyPolicy {
+            critical: false,
+            skip_certs,
+        }
+    }
+
+    /// Sets the `critical` flag to `true`. The extension will be critical.
+    ///
+    /// RFC 5280 requires conforming CAs to mark this extension as critical.
+    pub fn critical(&mut self) -> &mut InhibitAnyPolicy {
+        self.critical = true;
+        self
+    }
+
+    /// Return the `InhibitAnyPolicy` extension as an `X509Extension`.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        let mut value = String::new();
+        if self.critical {
+            value.push_str("critical,");
+        }
+        write!(value, "{}", self.skip_certs).unwrap();
+        X509Extension::new_nid(None, None, Nid::INHIBIT_ANY_POLICY, &value)
+    }
+}
+
+/// An extension which constrains path validation of certificate policies.
+///
+/// At least one of `require_explicit_policy` and `inhibit_policy_mapping` must be set.
+pub struct PolicyConstraints {
+    critical: bool,
+    require_explicit_policy: Option<u32>,
+    inhibit_policy_mapping: Option<u32>,
+}
+
+impl Default for PolicyConstraints {
+    fn default() -> PolicyConstraints {
+        PolicyConstraints::new()
+    }
+}
+
+impl PolicyConstraints {
+    /// Construct a new `PolicyConstraints` extension.
+    pub fn new() -> PolicyConstraints {
+        PolicyConstraints {
+            critical: false,
+            require_explicit_policy: None,
+            inhibit_policy_mapping: None,
+        }
+    }
+
+    /// Sets the `critical` flag to `true`. The extension will be critical.
+    ///
+    /// RFC 5280 requires conforming CAs to mark this extension as critical.
+    pub fn critical(&mut self) -> &mut PolicyConstraints {
+        self.critical = true;
+        self
+    }
+
+    /// Sets the number of additional certificates which may appear in the path before an
+    /// explicit policy is required.
+    pub fn require_explicit_policy(&mut self, skip_certs: u32) -> &mut PolicyConstraints {
+        self.require_explicit_policy = Some(skip_certs);
+        self
+    }
+
+    /// Sets the number of additional certificates which may appear in the path before policy
+    /// mapping is no longer permitted.
+    pub fn inhibit_policy_mapping(&mut self, skip_certs: u32) -> &mut PolicyConstraints {
+        self.inhibit_policy_mapping = Some(skip_certs);
+        self
+    }
+
+    /// Return the `PolicyConstraints` extension as an `X509Extension`.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        let mut value = String::new();
+        let mut first = true;
+        append(&mut value, &mut first, self.critical, "critical");
+        if let Some(skip_certs) = self.require_explicit_policy {
+            append(
+                &mut value,
+                &mut first,
+                true,
+                &format!("requireExplicitPolicy:{}", skip_certs),
+            );
+        }
+        if let Some(skip_certs) = self.inhibit_policy_mapping {
+            append(
+                &mut value,
+                &mut first,
+                true,
+                &format!("inhibitPolicyMapping:{}", skip_certs),
+            );
+        }
+        X509Extension::new_nid(None, None, Nid::POLICY_CONSTRAINTS, &value)
+    }
+}
+
+/// An extension which restricts the names that may appear in certificates issued by a CA.
+///
+/// Names in the subjects and subject alternative names of subsequent certificates in the path
+/// must fall within one of the permitted subtrees of each type present, and must not fall within
+/// any excluded subtree.
+pub struct NameConstraints {
+    critical: bool,
+    permitted: Vec<GeneralSubtree>,
+    excluded: Vec<GeneralSubtree>,
+}
+
+enum GeneralSubtree {
+    Dns(String),
+    Email(String),
+    Ip(Vec<u8>),
+    DirName(X509Name),
+}
+
+impl GeneralSubtree {
+    fn ip(addr: IpAddr, prefix_len: u8) -> GeneralSubtree {
+        let mut value = match addr {
+            IpAddr::V4(addr) => addr.octets().to_vec(),
+            IpAddr::V6(addr) => addr.octets().to_vec(),
+        };
+        let bits = value.len() * 8;
+        assert!(
+            usize::from(prefix_len) <= bits,
+            "prefix length {} is longer than the {} bit address",
+            prefix_len,
+            bits,
+        );
+        // the iPAddress of a subtree is the address followed by its netmask
+        let mut remaining = u32::from(prefix_len);
+        for _ in 0..bits / 8 {
+            let ones = remaining.min(8);
+            value.push((0xff00u16 >> ones) as u8);
+            remaining -= ones;
+        }
+        GeneralSubtree::Ip(value)
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) -> Result<(), ErrorStack> {
+        // GeneralSubtree ::= SEQUENCE { base GeneralName, ... }, leaving the minimum and maximum
+        // distances at their defaults
+        let dir_name_der;
+        let (tag, constructed, value): (_, _, &[u8]) = match self {
+            GeneralSubtree::Email(email) => (1, false, email.as_bytes()),
+            GeneralSubtree::Dns(dns) => (2, false, dns.as_bytes()),
+            GeneralSubtree::DirName(dir_name) => {
+                dir_name_der = dir_name.to_der()?;
+                (4, true, &dir_name_der)
+            }
+            GeneralSubtree::Ip(ip) => (7, false, ip),
+        };
+        let mut name = vec![];
+        put_object(
+            &mut name,
+            tag,
+            ffi::V_ASN1_CONTEXT_SPECIFIC,
+            constructed,
+            value,
+        );
+        put_object(
+            out,
+            ffi::V_ASN1_SEQUENCE,
+            ffi::V_ASN1_UNIVERSAL,
+            true,
+            &name,
+        );
+        Ok(())
+    }
+}
+
+impl Default for NameConstraints {
+    fn default() -> NameConstraints {
+        NameConstraints::new()
+    }
+}
+
+impl NameConstraints {
+    /// Construct a new `NameConstraints` extension.
+    pub fn new() -> NameConstraints {
+        NameConstraints {
+            critical: false,
+            permitted: vec![],
+            excluded: vec![],
+        }
+    }
+
+    /// Sets the `critical` flag to `true`. The extension will be critical.
+    ///
+    /// RFC 5280 requires conforming CAs to mark this extension as critical.
+    pub fn critical(&mut self) -> &mut NameConstraints {
+        self.critical = true;
+        self
+    }
+
+    /// Permits DNS names within the given domain, such as `example.com`.
+    pub fn permitted_dns(&mut self, dns: &str) -> &mut NameConstraints {
+        self.permitted.push(GeneralSubtree::Dns(dns.to_string()));
+        self
+    }
+
+    /// Excludes DNS names within the given domain.
+    pub fn excluded_dns(&mut self, dns: &str) -> &mut NameConstraints {
+        self.excluded.push(GeneralSubtree::Dns(dns.to_string()));
+        self
+    }
+
+    /// Permits email addresses matching `email`.
+    ///
+    /// This is either a full address, a host such as `example.com` to permit all addresses at
+    /// that host, or a domain such as `.example.com` to permit all addresses at its subdomains.
+    pub fn permitted_email(&mut self, email: &str) -> &mut NameConstraints {
+        self.permitted
+            .push(GeneralSubtree::Email(email.to_string()));
+        self
+    }
+
+    /// Excludes email addresses matching `email`.
+    pub fn excluded_email(&mut self, email: &str) -> &mut NameConstraints {
+        self.excluded.push(GeneralSubtree::Email(email.to_string()));
+        self
+    }
+
+    /// Permits IP addresses within the network with the given address and prefix length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` is longer than the address.
+    pub fn permitted_ip(&mut self, addr: IpAddr, prefix_len: u8) -> &mut NameConstraints {
+        self.permitted.push(GeneralSubtree::ip(addr, prefix_len));
+        self
+    }
+
+    /// Excludes IP addresses within the network with the given address and prefix length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` is longer than the address.
+    pub fn excluded_ip(&mut self, addr: IpAddr, prefix_len: u8) -> &mut NameConstraints {
+        self.excluded.push(GeneralSubtree::ip(addr, prefix_len));
+        self
+    }
+
+    /// Permits directory names beneath `dir_name`.
+    pub fn permitted_dir_name(&mut self, dir_name: X509Name) -> &mut NameConstraints {
+        self.permitted.push(GeneralSubtree::DirName(dir_name));
+        self
+    }
+
+    /// Excludes directory names beneath `dir_name`.
+    pub fn excluded_dir_name(&mut self, dir_name: X509Name) -> &mut NameConstraints {
+        self.excluded.push(GeneralSubtree::DirName(dir_name));
+        self
+    }
+
+    /// Return the `NameConstraints` extension as an `X509Extension`.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        // NameConstraints ::= SEQUENCE {
+        //      permittedSubtrees       [0]     GeneralSubtrees OPTIONAL,
+        //      excludedSubtrees        [1]     GeneralSubtrees OPTIONAL }
+        let mut constraints = vec![];
+        for (tag, subtrees) in [(0, &self.permitted), (1, &self.excluded)].iter() {
+            if subtrees.is_empty() {
+                continue;
+            }
+            let mut value = vec![];
+            for subtree in subtrees.iter() {
+                subtree.encode(&mut value)?;
+            }
+            put_object(
+                &mut constraints,
+                *tag,
+                ffi::V_ASN1_CONTEXT_SPECIFIC,
+                true,
+                &value,
+            );
+        }
+        let mut der = vec![];
+        put_object(
+            &mut der,
+            ffi::V_ASN1_SEQUENCE,
+            ffi::V_ASN1_UNIVERSAL,
+            true,
+            &constraints,
+        );
+
+        let oid = Asn1Object::from_str("2.5.29.30")?;
+        X509Extension::new_from_der(&oid, self.critical, &der)
+    }
+}
+
+/// Returns the contents of the DER TLV `der`.
+fn der_contents(der: &[u8]) -> &[u8] {
+    let len = der[1];
+    if len & 0x80 == 0 {
+        &der[2..]
+    } else {
+        &der[2 + usize::from(len & 0x7f)..]
     }
 }
 
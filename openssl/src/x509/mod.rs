@@ -7,23 +7,34 @@
 //! Internet protocols, including SSL/TLS, which is the basis for HTTPS,
 //! the secure protocol for browsing the web.
 
+use bitflags::bitflags;
 use cfg_if::cfg_if;
 use foreign_types::{ForeignType, ForeignTypeRef};
-use libc::{c_int, c_long, c_uint};
+use libc::{c_int, c_long, c_uint, c_ulong, c_void};
+use std::cmp;
+use std::convert::TryInto;
 use std::error::Error;
 use std::ffi::{CStr, CString};
 use std::fmt;
+use std::io::{self, Read};
 use std::marker::PhantomData;
 use std::mem;
+#[cfg(any(ossl102, libressl261))]
+use std::net::IpAddr;
 use std::path::Path;
 use std::ptr;
 use std::slice;
 use std::str;
 
+#[cfg(ossl111)]
+use crate::asn1::put_object;
+#[cfg(not(boringssl))]
+use crate::asn1::{get_object, illegal_characters};
 use crate::asn1::{
-    Asn1BitStringRef, Asn1IntegerRef, Asn1ObjectRef, Asn1StringRef, Asn1TimeRef, Asn1Type,
+    Asn1BitStringRef, Asn1Integer, Asn1IntegerRef, Asn1Object, Asn1ObjectRef, Asn1String,
+    Asn1StringRef, Asn1Time, Asn1TimeRef, Asn1Type,
 };
-use crate::bio::MemBioSlice;
+use crate::bio::{MemBio, MemBioSlice};
 use crate::conf::ConfRef;
 use crate::error::ErrorStack;
 use crate::ex_data::Index;
@@ -34,13 +45,27 @@ use crate::ssl::SslRef;
 use crate::stack::{Stack, StackRef, Stackable};
 use crate::string::OpensslString;
 use crate::util::{ForeignTypeExt, ForeignTypeRefExt};
+#[cfg(ossl110)]
+use crate::x509::policy::X509PolicyTreeRef;
+#[cfg(any(ossl102, libressl261))]
+use crate::x509::verify::{X509CheckFlags, X509VerifyParamRef};
 use crate::{cvt, cvt_n, cvt_p};
 use openssl_macros::corresponds;
 
 #[cfg(any(ossl102, libressl261))]
 pub mod verify;
 
+#[cfg(ossl340)]
+pub mod acert;
+#[cfg(ossl102)]
+pub mod ca;
+#[cfg(not(boringssl))]
+pub mod crl_index;
+#[cfg(not(boringssl))]
+pub mod cross;
 pub mod extension;
+#[cfg(ossl110)]
+pub mod policy;
 pub mod store;
 
 #[cfg(test)]
@@ -89,6 +114,22 @@ impl X509StoreContextRef {
         }
     }
 
+    /// Returns a mutable reference to the application data pertaining to an `X509` store context.
+    ///
+    /// During a TLS handshake, this can be used with [`X509StoreContext::ssl_idx`] to configure
+    /// the connection from a verification callback.
+    #[corresponds(X509_STORE_CTX_get_ex_data)]
+    pub fn ex_data_mut<T>(&mut self, index: Index<X509StoreContext, T>) -> Option<&mut T> {
+        unsafe {
+            let data = ffi::X509_STORE_CTX_get_ex_data(self.as_ptr(), index.as_raw());
+            if data.is_null() {
+                None
+            } else {
+                Some(&mut *(data as *mut T))
+            }
+        }
+    }
+
     /// Returns the error code of the context.
     #[corresponds(X509_STORE_CTX_get_error)]
     pub fn error(&self) -> X509VerifyResult {
@@ -154,6 +195,55 @@ impl X509StoreContextRef {
         unsafe { cvt_n(ffi::X509_verify_cert(self.as_ptr())).map(|n| n != 0) }
     }
 
+    /// Verifies the stored certificate, using `untrusted` as the set of untrusted certificates
+    /// from which the chain may be built.
+    ///
+    /// This replaces the certificates the context was initialized with, such as the chain sent by
+    /// a TLS peer, for the duration of the call. To supplement rather than replace them, include
+    /// the certificates returned by `untrusted` in `untrusted`.
+    ///
+    /// Like `verify_cert`, this can only be called once on an initialized context.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(X509_STORE_CTX_set0_untrusted)]
+    #[cfg(ossl110)]
+    pub fn verify_cert_with_untrusted(
+        &mut self,
+        untrusted: &StackRef<X509>,
+    ) -> Result<bool, ErrorStack> {
+        unsafe {
+            let original = ffi::X509_STORE_CTX_get0_untrusted(self.as_ptr());
+            ffi::X509_STORE_CTX_set0_untrusted(self.as_ptr(), untrusted.as_ptr());
+            let r = cvt_n(ffi::X509_verify_cert(self.as_ptr())).map(|n| n != 0);
+            ffi::X509_STORE_CTX_set0_untrusted(self.as_ptr(), original);
+            r
+        }
+    }
+
+    /// Returns the certificate being verified.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(X509_STORE_CTX_get0_cert)]
+    #[cfg(ossl110)]
+    pub fn cert(&self) -> Option<&X509Ref> {
+        unsafe {
+            let ptr = ffi::X509_STORE_CTX_get0_cert(self.as_ptr());
+            X509Ref::from_const_ptr_opt(ptr)
+        }
+    }
+
+    /// Returns the untrusted certificates available for building the chain.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(X509_STORE_CTX_get0_untrusted)]
+    #[cfg(ossl110)]
+    pub fn untrusted(&self) -> Option<&StackRef<X509>> {
+        unsafe {
+            let ptr = ffi::X509_STORE_CTX_get0_untrusted(self.as_ptr());
+            StackRef::from_const_ptr_opt(ptr)
+        }
+    }
+
     /// Set the error code of the context.
     #[corresponds(X509_STORE_CTX_set_error)]
     pub fn set_error(&mut self, result: X509VerifyResult) {
@@ -181,6 +271,51 @@ impl X509StoreContextRef {
         unsafe { ffi::X509_STORE_CTX_get_error_depth(self.as_ptr()) as u32 }
     }
 
+    /// Returns the policy tree built during verification.
+    ///
+    /// This is only available if policy checking was enabled, for example with
+    /// `X509VerifyFlags::POLICY_CHECK`, and the chain was verified successfully.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(X509_STORE_CTX_get0_policy_tree)]
+    #[cfg(ossl110)]
+    pub fn policy_tree(&self) -> Option<&X509PolicyTreeRef> {
+        unsafe {
+            let tree = ffi::X509_STORE_CTX_get0_policy_tree(self.as_ptr());
+            X509PolicyTreeRef::from_const_ptr_opt(tree)
+        }
+    }
+
+    /// Returns `true` if an explicit policy was required for the verified chain.
+    ///
+    /// This is the case if `X509VerifyFlags::EXPLICIT_POLICY` was set or a certificate in the
+    /// chain contained a policy constraints extension requiring one.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(X509_STORE_CTX_get_explicit_policy)]
+    #[cfg(ossl110)]
+    pub fn explicit_policy(&self) -> bool {
+        unsafe { ffi::X509_STORE_CTX_get_explicit_policy(self.as_ptr()) != 0 }
+    }
+
+    /// Returns a shared reference to the verification parameters of the context.
+    #[corresponds(X509_STORE_CTX_get0_param)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn verify_param(&self) -> &X509VerifyParamRef {
+        unsafe { X509VerifyParamRef::from_ptr(ffi::X509_STORE_CTX_get0_param(self.as_ptr())) }
+    }
+
+    /// Returns a mutable reference to the verification parameters of the context.
+    ///
+    /// These are initialized from the `X509Store` by `X509StoreContext::init`, and can be adjusted
+    /// before calling `verify_cert` without affecting the store, for example to check the chain
+    /// at a different time or to require a specific set of certificate policies.
+    #[corresponds(X509_STORE_CTX_get0_param)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn verify_param_mut(&mut self) -> &mut X509VerifyParamRef {
+        unsafe { X509VerifyParamRef::from_ptr_mut(ffi::X509_STORE_CTX_get0_param(self.as_ptr())) }
+    }
+
     /// Returns a reference to a complete valid `X509` certificate chain.
     #[corresponds(X509_STORE_CTX_get0_chain)]
     pub fn chain(&self) -> Option<&StackRef<X509>> {
@@ -352,6 +487,65 @@ impl X509Builder {
         unsafe { cvt(ffi::X509_sign(self.0.as_ptr(), key.as_ptr(), hash.as_ptr())).map(|_| ()) }
     }
 
+    /// Sets the signature algorithm of the certificate.
+    ///
+    /// This is only needed when the certificate will be signed externally, as `sign` sets the
+    /// algorithm itself. It must be called before [`tbs_certificate`], as the algorithm is part
+    /// of the signed data.
+    ///
+    /// [`tbs_certificate`]: X509Builder::tbs_certificate
+    #[corresponds(X509_ALGOR_copy)]
+    #[cfg(ossl111)]
+    pub fn set_signature_algorithm(
+        &mut self,
+        algorithm: &X509AlgorithmRef,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            let tbs_algorithm = ffi::X509_get0_tbs_sigalg(self.0.as_ptr());
+            cvt(ffi::X509_ALGOR_copy(
+                tbs_algorithm as *mut _,
+                algorithm.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the DER-encoded `TBSCertificate` structure, which is the data covered by the
+    /// certificate's signature.
+    ///
+    /// Together with [`set_signature_algorithm`] and [`build_with_signature`], this allows the
+    /// certificate to be signed by a key held outside of the process, such as in an HSM.
+    ///
+    /// [`set_signature_algorithm`]: X509Builder::set_signature_algorithm
+    /// [`build_with_signature`]: X509Builder::build_with_signature
+    #[corresponds(i2d_re_X509_tbs)]
+    #[cfg(ossl111)]
+    pub fn tbs_certificate(&mut self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let len = cvt(ffi::i2d_re_X509_tbs(self.0.as_ptr(), ptr::null_mut()))?;
+            let mut buf = vec![0; len as usize];
+            cvt(ffi::i2d_re_X509_tbs(self.0.as_ptr(), &mut buf.as_mut_ptr()))?;
+            Ok(buf)
+        }
+    }
+
+    /// Consumes the builder, returning the certificate with a signature produced externally over
+    /// the output of [`tbs_certificate`].
+    ///
+    /// The signature must have been made with the algorithm passed to
+    /// [`set_signature_algorithm`]. It is not checked.
+    ///
+    /// [`tbs_certificate`]: X509Builder::tbs_certificate
+    /// [`set_signature_algorithm`]: X509Builder::set_signature_algorithm
+    #[cfg(ossl111)]
+    pub fn build_with_signature(mut self, signature: &[u8]) -> Result<X509, ErrorStack> {
+        let tbs = self.tbs_certificate()?;
+        let algorithm =
+            unsafe { X509AlgorithmRef::from_const_ptr(ffi::X509_get0_tbs_sigalg(self.0.as_ptr())) };
+        let der = signed_der(&tbs, algorithm, signature)?;
+        X509::from_der(&der)
+    }
+
     /// Consumes the builder, returning the certificate.
     pub fn build(self) -> X509 {
         self.0
@@ -442,6 +636,155 @@ impl X509Ref {
         }
     }
 
+    /// Returns the URIs from which this certificate's issuer can be retrieved.
+    ///
+    /// These are the `caIssuers` entries of the certificate's authority information access
+    /// extension.
+    pub fn ca_issuers(&self) -> Vec<String> {
+        let info = match self.authority_info() {
+            Some(info) => info,
+            None => return vec![],
+        };
+
+        info.iter()
+            .filter(|desc| desc.method().nid() == Nid::AD_CA_ISSUERS)
+            .filter_map(|desc| desc.location().uri())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Returns this certificate's [`subject information access`] entries, if they exist.
+    ///
+    /// [`subject information access`]: https://tools.ietf.org/html/rfc5280#section-4.2.2.2
+    #[corresponds(X509_get_ext_d2i)]
+    pub fn subject_info_access(&self) -> Option<Stack<AccessDescription>> {
+        unsafe {
+            let stack = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_sinfo_access,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Stack::from_ptr_opt(stack as *mut _)
+        }
+    }
+
+    /// Returns this certificate's [`freshest CRL`] distribution points, if they exist.
+    ///
+    /// These identify where delta CRLs for the certificate's issuer can be obtained.
+    ///
+    /// [`freshest CRL`]: https://tools.ietf.org/html/rfc5280#section-4.2.1.15
+    #[corresponds(X509_get_ext_d2i)]
+    pub fn freshest_crl(&self) -> Option<Stack<DistPoint>> {
+        unsafe {
+            let stack = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_freshest_crl,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Stack::from_ptr_opt(stack as *mut _)
+        }
+    }
+
+    /// Returns the `SkipCerts` value of this certificate's [`inhibit anyPolicy`] extension, if
+    /// it exists.
+    ///
+    /// [`inhibit anyPolicy`]: https://tools.ietf.org/html/rfc5280#section-4.2.1.14
+    #[corresponds(X509_get_ext_d2i)]
+    pub fn inhibit_any_policy(&self) -> Option<Asn1Integer> {
+        unsafe {
+            let skip_certs = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_inhibit_any_policy,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Asn1Integer::from_ptr_opt(skip_certs as *mut _)
+        }
+    }
+
+    /// Returns this certificate's [`policy constraints`], if they exist.
+    ///
+    /// [`policy constraints`]: https://tools.ietf.org/html/rfc5280#section-4.2.1.11
+    #[corresponds(X509_get_ext_d2i)]
+    pub fn policy_constraints(&self) -> Option<X509PolicyConstraints> {
+        unsafe {
+            let constraints = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_policy_constraints,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            X509PolicyConstraints::from_ptr_opt(constraints as *mut _)
+        }
+    }
+
+    /// Returns this certificate's [`CRL distribution points`], if they exist.
+    ///
+    /// [`CRL distribution points`]: https://tools.ietf.org/html/rfc5280#section-4.2.1.13
+    #[corresponds(X509_get_ext_d2i)]
+    pub fn crl_distribution_points(&self) -> Option<Stack<DistPoint>> {
+        unsafe {
+            let stack = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_crl_distribution_points,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Stack::from_ptr_opt(stack as *mut _)
+        }
+    }
+
+    /// Returns this certificate's [`certificate policies`], if they exist.
+    ///
+    /// [`certificate policies`]: https://tools.ietf.org/html/rfc5280#section-4.2.1.4
+    #[corresponds(X509_get_ext_d2i)]
+    pub fn certificate_policies(&self) -> Option<Stack<PolicyInfo>> {
+        unsafe {
+            let stack = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_certificate_policies,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Stack::from_ptr_opt(stack as *mut _)
+        }
+    }
+
+    /// Returns the key purposes listed in this certificate's [`extended key usage`] extension,
+    /// if it exists.
+    ///
+    /// [`extended key usage`]: https://tools.ietf.org/html/rfc5280#section-4.2.1.12
+    #[corresponds(X509_get_ext_d2i)]
+    pub fn extended_key_usage(&self) -> Option<Stack<Asn1Object>> {
+        unsafe {
+            let stack = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_ext_key_usage,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Stack::from_ptr_opt(stack as *mut _)
+        }
+    }
+
+    /// Returns this certificate's [`basic constraints`], if they exist.
+    ///
+    /// [`basic constraints`]: https://tools.ietf.org/html/rfc5280#section-4.2.1.9
+    #[corresponds(X509_get_ext_d2i)]
+    pub fn basic_constraints(&self) -> Option<X509BasicConstraints> {
+        unsafe {
+            let constraints = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_basic_constraints,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            X509BasicConstraints::from_ptr_opt(constraints as *mut _)
+        }
+    }
+
     #[corresponds(X509_get_pubkey)]
     pub fn public_key(&self) -> Result<PKey<Public>, ErrorStack> {
         unsafe {
@@ -531,6 +874,26 @@ impl X509Ref {
         }
     }
 
+    /// Checks that this certificate and `other` cross-certify each other.
+    ///
+    /// This is the case when each certificate was issued by the subject of the other, as checked
+    /// by [`issued`], and is signed by the other's public key. Two CAs which have cross-certified
+    /// each other publish such a pair of certificates, for example as an
+    /// [`X509CrossCertificatePair`].
+    ///
+    /// [`issued`]: #method.issued
+    /// [`X509CrossCertificatePair`]: cross/struct.X509CrossCertificatePair.html
+    pub fn cross_certifies(&self, other: &X509Ref) -> Result<bool, ErrorStack> {
+        if self.issued(other) != X509VerifyResult::OK || other.issued(self) != X509VerifyResult::OK
+        {
+            return Ok(false);
+        }
+
+        let key = self.public_key()?;
+        let other_key = other.public_key()?;
+        Ok(other.verify(&key)? && self.verify(&other_key)?)
+    }
+
     /// Returns certificate version. If this certificate has no explicit version set, it defaults to
     /// version 1.
     ///
@@ -555,6 +918,161 @@ impl X509Ref {
         unsafe { cvt_n(ffi::X509_verify(self.as_ptr(), key.as_ptr())).map(|n| n != 0) }
     }
 
+    /// Checks that `key` is the private key corresponding to this certificate's public key.
+    ///
+    /// Returns `false` if the keys do not match or are of different types.
+    #[corresponds(X509_check_private_key)]
+    pub fn matches_private_key<T>(&self, key: &PKeyRef<T>) -> Result<bool, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        if unsafe { ffi::X509_check_private_key(self.as_ptr(), key.as_ptr()) } == 1 {
+            return Ok(true);
+        }
+
+        // mismatches are reported on the error stack like genuine failures, possibly along with
+        // the reason the keys could not be compared
+        let errors = ErrorStack::get();
+        let mismatch = errors.errors().iter().any(|e| {
+            ffi::ERR_GET_LIB(e.code()) == ffi::ERR_LIB_X509
+                && (ffi::ERR_GET_REASON(e.code()) == ffi::X509_R_KEY_TYPE_MISMATCH
+                    || ffi::ERR_GET_REASON(e.code()) == ffi::X509_R_KEY_VALUES_MISMATCH)
+        });
+        if mismatch {
+            Ok(false)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Checks that `chain` continues the certificate chain from this certificate, with each
+    /// certificate issued by the one following it.
+    ///
+    /// This is the order TLS requires of the chain sent to the peer, as configured with
+    /// [`SslContextBuilder::add_extra_chain_cert`] for example. The first failure is returned,
+    /// as by [`issued`].
+    ///
+    /// [`SslContextBuilder::add_extra_chain_cert`]: crate::ssl::SslContextBuilder::add_extra_chain_cert
+    /// [`issued`]: X509Ref::issued
+    pub fn check_chain_order(&self, chain: &StackRef<X509>) -> X509VerifyResult {
+        let mut subject = self;
+        for issuer in chain {
+            let r = issuer.issued(subject);
+            if r != X509VerifyResult::OK {
+                return r;
+            }
+            subject = issuer;
+        }
+        X509VerifyResult::OK
+    }
+
+    /// Checks whether the certificate is valid for the DNS name `host`, returning the name which
+    /// matched it.
+    ///
+    /// The subject alternative names of the certificate are checked, along with the subject's
+    /// common name if there are no DNS names among them and `flags` allows it. The returned name
+    /// is the one from the certificate, so it may be a wildcard pattern such as `*.example.com`.
+    ///
+    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    #[corresponds(X509_check_host)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn check_host(
+        &self,
+        host: &str,
+        flags: X509CheckFlags,
+    ) -> Result<Option<OpensslString>, ErrorStack> {
+        // a zero length tells OpenSSL to call strlen on the name
+        if host.is_empty() {
+            return Ok(None);
+        }
+
+        unsafe {
+            let mut peername = ptr::null_mut();
+            let r = cvt_n(ffi::X509_check_host(
+                self.as_ptr(),
+                host.as_ptr() as *const _,
+                host.len(),
+                flags.bits(),
+                &mut peername,
+            ))?;
+            if r == 0 {
+                return Ok(None);
+            }
+            Ok(OpensslString::from_ptr_opt(peername))
+        }
+    }
+
+    /// Checks whether the certificate is valid for the email address `email`.
+    ///
+    /// The `rfc822Name` subject alternative names of the certificate are checked, along with the
+    /// subject's email address attributes if `flags` allows it.
+    ///
+    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    #[corresponds(X509_check_email)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn check_email(&self, email: &str, flags: X509CheckFlags) -> Result<bool, ErrorStack> {
+        // a zero length tells OpenSSL to call strlen on the address
+        if email.is_empty() {
+            return Ok(false);
+        }
+
+        unsafe {
+            cvt_n(ffi::X509_check_email(
+                self.as_ptr(),
+                email.as_ptr() as *const _,
+                email.len(),
+                flags.bits(),
+            ))
+            .map(|r| r == 1)
+        }
+    }
+
+    /// Checks whether the certificate is valid for the IP address `ip`.
+    ///
+    /// Only the `iPAddress` subject alternative names of the certificate are checked.
+    ///
+    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    #[corresponds(X509_check_ip)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn check_ip(&self, ip: IpAddr, flags: X509CheckFlags) -> Result<bool, ErrorStack> {
+        let octets = match ip {
+            IpAddr::V4(addr) => addr.octets().to_vec(),
+            IpAddr::V6(addr) => addr.octets().to_vec(),
+        };
+
+        unsafe {
+            cvt_n(ffi::X509_check_ip(
+                self.as_ptr(),
+                octets.as_ptr(),
+                octets.len(),
+                flags.bits(),
+            ))
+            .map(|r| r == 1)
+        }
+    }
+
+    /// Like [`check_ip`], but takes the address in its textual form, such as `192.0.2.1` or
+    /// `2001:db8::1`.
+    ///
+    /// Returns an error if `address` is not a valid IPv4 or IPv6 address.
+    ///
+    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    ///
+    /// [`check_ip`]: X509Ref::check_ip
+    #[corresponds(X509_check_ip_asc)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn check_ip_asc(&self, address: &str, flags: X509CheckFlags) -> Result<bool, ErrorStack> {
+        let address = CString::new(address).map_err(|_| illegal_characters())?;
+        unsafe {
+            cvt_n(ffi::X509_check_ip_asc(
+                self.as_ptr(),
+                address.as_ptr(),
+                flags.bits(),
+            ))
+            .map(|r| r == 1)
+        }
+    }
+
     /// Returns this certificate's serial number.
     #[corresponds(X509_get_serialNumber)]
     pub fn serial_number(&self) -> &Asn1IntegerRef {
@@ -645,6 +1163,50 @@ impl X509 {
             Ok(certs)
         }
     }
+
+    /// Deserializes a list of concatenated DER-encoded certificates.
+    #[corresponds(d2i_X509)]
+    pub fn stack_from_der(mut der: &[u8]) -> Result<Vec<X509>, ErrorStack> {
+        let mut certs = vec![];
+        while !der.is_empty() {
+            unsafe {
+                ffi::init();
+                let mut ptr = der.as_ptr();
+                let len = cmp::min(der.len(), c_long::MAX as usize) as c_long;
+                let cert = cvt_p(ffi::d2i_X509(ptr::null_mut(), &mut ptr, len)).map(X509)?;
+                der = &der[ptr as usize - der.as_ptr() as usize..];
+                certs.push(cert);
+            }
+        }
+
+        Ok(certs)
+    }
+
+    /// Deserializes a DER-encoded X509 structure read from `reader`.
+    ///
+    /// Only the bytes of the certificate are consumed from the reader. The encoded certificate is
+    /// buffered in full before it is parsed.
+    #[corresponds(d2i_X509)]
+    pub fn from_der_reader<R>(mut reader: R) -> io::Result<X509>
+    where
+        R: Read,
+    {
+        let der =
+            read_der(&mut reader)?.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        X509::from_der(&der).map_err(io::Error::from)
+    }
+
+    /// Returns an iterator over concatenated DER-encoded certificates read from `reader`.
+    ///
+    /// Certificates are read one at a time, so only a single certificate is held in memory
+    /// regardless of the size of the stream.
+    #[corresponds(d2i_X509)]
+    pub fn iter_from_der_reader<R>(reader: R) -> DerIter<R, X509>
+    where
+        R: Read,
+    {
+        DerIter::new(reader, X509::from_der)
+    }
 }
 
 impl Clone for X509 {
@@ -766,16 +1328,62 @@ impl X509Extension {
             cvt_p(ffi::X509V3_EXT_nconf_nid(conf, context, name, value)).map(X509Extension)
         }
     }
-}
-
-/// A builder used to construct an `X509Name`.
-pub struct X509NameBuilder(X509Name);
 
-impl X509NameBuilder {
-    /// Creates a new builder.
-    pub fn new() -> Result<X509NameBuilder, ErrorStack> {
+    /// Constructs an X509 extension from its OID, criticality and DER-encoded value.
+    ///
+    /// Unlike [`X509Extension::new`], the value is not interpreted by OpenSSL, so this can be used
+    /// for extensions OpenSSL does not know about. `der_value` is the DER encoding of the
+    /// extension's ASN.1 value, which is wrapped in the `extnValue` OCTET STRING.
+    #[corresponds(X509_EXTENSION_create_by_OBJ)]
+    pub fn new_from_der(
+        oid: &Asn1ObjectRef,
+        critical: bool,
+        der_value: &[u8],
+    ) -> Result<X509Extension, ErrorStack> {
         unsafe {
-            ffi::init();
+            let value = cvt_p(ffi::ASN1_STRING_type_new(ffi::V_ASN1_OCTET_STRING))
+                .map(|p| Asn1String::from_ptr(p))?;
+            cvt(ffi::ASN1_STRING_set(
+                value.as_ptr(),
+                der_value.as_ptr() as *const c_void,
+                der_value.len().try_into().unwrap(),
+            ))?;
+            cvt_p(ffi::X509_EXTENSION_create_by_OBJ(
+                ptr::null_mut(),
+                oid.as_ptr(),
+                critical as c_int,
+                value.as_ptr() as *mut ffi::ASN1_OCTET_STRING,
+            ))
+            .map(X509Extension)
+        }
+    }
+
+    from_der! {
+        /// Deserializes a DER-encoded `Extension` structure.
+        #[corresponds(d2i_X509_EXTENSION)]
+        from_der,
+        X509Extension,
+        ffi::d2i_X509_EXTENSION
+    }
+}
+
+impl X509ExtensionRef {
+    to_der! {
+        /// Serializes the extension into a DER-encoded `Extension` structure.
+        #[corresponds(i2d_X509_EXTENSION)]
+        to_der,
+        ffi::i2d_X509_EXTENSION
+    }
+}
+
+/// A builder used to construct an `X509Name`.
+pub struct X509NameBuilder(X509Name);
+
+impl X509NameBuilder {
+    /// Creates a new builder.
+    pub fn new() -> Result<X509NameBuilder, ErrorStack> {
+        unsafe {
+            ffi::init();
             cvt_p(ffi::X509_NAME_new()).map(|p| X509NameBuilder(X509Name(p)))
         }
     }
@@ -876,6 +1484,199 @@ impl X509NameBuilder {
         }
     }
 
+    /// Removes the entry at index `loc`, returning it.
+    ///
+    /// Returns `None` if `loc` is out of bounds.
+    #[corresponds(X509_NAME_delete_entry)]
+    pub fn remove_entry(&mut self, loc: usize) -> Option<X509NameEntry> {
+        let loc: c_int = loc.try_into().ok()?;
+        unsafe { X509NameEntry::from_ptr_opt(ffi::X509_NAME_delete_entry(self.0.as_ptr(), loc)) }
+    }
+
+    /// Removes all entries with the field `nid`, returning the number of entries removed.
+    #[corresponds(X509_NAME_delete_entry)]
+    pub fn remove_entries_by_nid(&mut self, nid: Nid) -> usize {
+        let mut removed = 0;
+        unsafe {
+            loop {
+                let loc = ffi::X509_NAME_get_index_by_NID(self.0.as_ptr(), nid.as_raw(), -1);
+                if loc < 0 {
+                    break;
+                }
+                ffi::X509_NAME_ENTRY_free(ffi::X509_NAME_delete_entry(self.0.as_ptr(), loc));
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    /// Replaces the value of every entry with the field `nid`, returning the number of entries
+    /// replaced.
+    ///
+    /// Each replacement keeps the position of the original entry, and remains in the same
+    /// relative distinguished name as any entries that shared it. If an error is returned, entries
+    /// before the one which could not be replaced will have been updated.
+    #[corresponds(X509_NAME_add_entry_by_NID)]
+    #[cfg(any(ossl110, libressl270, boringssl))]
+    pub fn replace_entries_by_nid(&mut self, nid: Nid, value: &str) -> Result<usize, ErrorStack> {
+        assert!(value.len() <= c_int::MAX as usize);
+        let mut replaced = 0;
+        let mut loc = -1;
+        unsafe {
+            loop {
+                loc = ffi::X509_NAME_get_index_by_NID(self.0.as_ptr(), nid.as_raw(), loc);
+                if loc < 0 {
+                    break;
+                }
+
+                // -1 joins the previous RDN, 1 the following one, and 0 starts a new one.
+                let rdn =
+                    |loc| ffi::X509_NAME_ENTRY_set(ffi::X509_NAME_get_entry(self.0.as_ptr(), loc));
+                let count = ffi::X509_NAME_entry_count(self.0.as_ptr());
+                let set = if loc > 0 && rdn(loc - 1) == rdn(loc) {
+                    -1
+                } else if loc + 1 < count && rdn(loc + 1) == rdn(loc) {
+                    1
+                } else {
+                    0
+                };
+
+                cvt(ffi::X509_NAME_add_entry_by_NID(
+                    self.0.as_ptr(),
+                    nid.as_raw(),
+                    ffi::MBSTRING_UTF8,
+                    value.as_ptr() as *mut _,
+                    value.len() as c_int,
+                    loc,
+                    set,
+                ))?;
+                ffi::X509_NAME_ENTRY_free(ffi::X509_NAME_delete_entry(self.0.as_ptr(), loc + 1));
+                replaced += 1;
+            }
+        }
+        Ok(replaced)
+    }
+
+    /// Adds a field entry by str to the last relative distinguished name, rather than starting a
+    /// new one.
+    ///
+    /// This builds multi-valued relative distinguished names such as `CN=Alice+UID=alice`. If the
+    /// name is empty, the entry starts the first relative distinguished name.
+    #[corresponds(X509_NAME_add_entry_by_txt)]
+    pub fn append_entry_to_rdn_by_text(
+        &mut self,
+        field: &str,
+        value: &str,
+    ) -> Result<(), ErrorStack> {
+        self.add_entry_by_text(field, value.as_bytes(), ffi::MBSTRING_UTF8, -1, -1)
+    }
+
+    /// Adds a field entry by NID to the last relative distinguished name, rather than starting a
+    /// new one.
+    ///
+    /// If the name is empty, the entry starts the first relative distinguished name.
+    #[corresponds(X509_NAME_add_entry_by_NID)]
+    pub fn append_entry_to_rdn_by_nid(
+        &mut self,
+        field: Nid,
+        value: &str,
+    ) -> Result<(), ErrorStack> {
+        self.add_entry_by_nid(field, value, -1, -1)
+    }
+
+    /// Inserts a field entry by str at index `loc`, in a new relative distinguished name.
+    ///
+    /// Entries at or after `loc` move to the next index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `loc` is greater than the number of entries in the name.
+    #[corresponds(X509_NAME_add_entry_by_txt)]
+    pub fn insert_entry_by_text(
+        &mut self,
+        loc: usize,
+        field: &str,
+        value: &str,
+    ) -> Result<(), ErrorStack> {
+        let loc = self.insert_loc(loc);
+        self.add_entry_by_text(field, value.as_bytes(), ffi::MBSTRING_UTF8, loc, 0)
+    }
+
+    /// Inserts a field entry by NID at index `loc`, in a new relative distinguished name.
+    ///
+    /// Entries at or after `loc` move to the next index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `loc` is greater than the number of entries in the name.
+    #[corresponds(X509_NAME_add_entry_by_NID)]
+    pub fn insert_entry_by_nid(
+        &mut self,
+        loc: usize,
+        field: Nid,
+        value: &str,
+    ) -> Result<(), ErrorStack> {
+        let loc = self.insert_loc(loc);
+        self.add_entry_by_nid(field, value, loc, 0)
+    }
+
+    fn insert_loc(&self, loc: usize) -> c_int {
+        let count = self.0.entry_count();
+        assert!(
+            loc <= count,
+            "insertion index {} is out of bounds for a name with {} entries",
+            loc,
+            count
+        );
+        loc as c_int
+    }
+
+    fn add_entry_by_text(
+        &mut self,
+        field: &str,
+        value: &[u8],
+        ty: c_int,
+        loc: c_int,
+        set: c_int,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            let field = CString::new(field).unwrap();
+            assert!(value.len() <= c_int::max_value() as usize);
+            cvt(ffi::X509_NAME_add_entry_by_txt(
+                self.0.as_ptr(),
+                field.as_ptr() as *mut _,
+                ty,
+                value.as_ptr(),
+                value.len() as c_int,
+                loc,
+                set,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    fn add_entry_by_nid(
+        &mut self,
+        field: Nid,
+        value: &str,
+        loc: c_int,
+        set: c_int,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(value.len() <= c_int::max_value() as usize);
+            cvt(ffi::X509_NAME_add_entry_by_NID(
+                self.0.as_ptr(),
+                field.as_raw(),
+                ffi::MBSTRING_UTF8,
+                value.as_ptr() as *mut _,
+                value.len() as c_int,
+                loc,
+                set,
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Return an `X509Name`.
     pub fn build(self) -> X509Name {
         self.0
@@ -898,6 +1699,13 @@ impl X509Name {
         X509NameBuilder::new()
     }
 
+    /// Returns a builder initialized with a copy of the entries of `name`.
+    ///
+    /// This can be used to produce a modified version of an existing name.
+    pub fn builder_from(name: &X509NameRef) -> Result<X509NameBuilder, ErrorStack> {
+        name.to_owned().map(X509NameBuilder)
+    }
+
     /// Loads subject names from a file containing PEM-formatted certificates.
     ///
     /// This is commonly used in conjunction with `SslContextBuilder::set_client_ca_list`.
@@ -906,6 +1714,36 @@ impl X509Name {
         unsafe { cvt_p(ffi::SSL_load_client_CA_file(file.as_ptr())).map(|p| Stack::from_ptr(p)) }
     }
 
+    /// Parses a name written in the string format of RFC 2253, such as
+    /// `CN=Alice+UID=alice,O=Example,C=US`.
+    ///
+    /// Relative distinguished names are separated by `,` and listed from the most specific, so the
+    /// first one written is the last one in the name. The attributes of a multi-valued relative
+    /// distinguished name are separated by `+`. Values may use `\` escapes, be quoted, or be
+    /// written as `#` followed by the hex encoding of a DER string, which is how
+    /// [`X509NameRef::to_rfc2253`] writes values it cannot represent as text.
+    ///
+    /// Attribute types are the short names or dotted OIDs accepted by
+    /// [`X509NameBuilder::append_entry_by_text`].
+    #[cfg(not(boringssl))]
+    pub fn from_rfc2253(name: &str) -> Result<X509Name, ErrorStack> {
+        let rdns = parse_rfc2253(name)?;
+        let mut builder = X509Name::builder()?;
+        for rdn in rdns.iter().rev() {
+            for (i, attribute) in rdn.iter().enumerate() {
+                let set = if i == 0 { 0 } else { -1 };
+                builder.add_entry_by_text(
+                    &attribute.field,
+                    &attribute.value,
+                    attribute.ty,
+                    -1,
+                    set,
+                )?;
+            }
+        }
+        Ok(builder.build())
+    }
+
     from_der! {
         /// Deserializes a DER-encoded X509 name structure.
         ///
@@ -941,6 +1779,57 @@ impl X509NameRef {
         }
     }
 
+    /// Returns the number of entries in the name.
+    #[corresponds(X509_NAME_entry_count)]
+    pub fn entry_count(&self) -> usize {
+        unsafe { ffi::X509_NAME_entry_count(self.as_ptr()) as usize }
+    }
+
+    /// Returns a copy of the name.
+    #[corresponds(X509_NAME_dup)]
+    pub fn to_owned(&self) -> Result<X509Name, ErrorStack> {
+        unsafe { cvt_p(ffi::X509_NAME_dup(self.as_ptr())).map(|p| X509Name::from_ptr(p)) }
+    }
+
+    /// Formats the name as a string according to `flags`.
+    #[corresponds(X509_NAME_print_ex)]
+    pub fn to_string_with_flags(&self, flags: X509NamePrintFlags) -> Result<String, ErrorStack> {
+        let bio = MemBio::new()?;
+        unsafe {
+            cvt_n(ffi::X509_NAME_print_ex(
+                bio.as_ptr(),
+                self.as_ptr(),
+                0,
+                flags.bits(),
+            ))?;
+        }
+        Ok(String::from_utf8_lossy(bio.get_buf()).into_owned())
+    }
+
+    /// Formats the name in the string format of RFC 2253, such as
+    /// `CN=Alice+UID=alice,O=Example,C=US`.
+    ///
+    /// The result can be parsed back with [`X509Name::from_rfc2253`].
+    #[corresponds(X509_NAME_print_ex)]
+    pub fn to_rfc2253(&self) -> Result<String, ErrorStack> {
+        self.to_string_with_flags(X509NamePrintFlags::RFC2253)
+    }
+
+    /// Formats the name in OpenSSL's legacy one-line format, such as
+    /// `/C=US/O=Example/CN=Alice+UID=alice`.
+    ///
+    /// The format does not escape separators within values, so it cannot always be parsed back
+    /// unambiguously. [`to_rfc2253`] should be preferred.
+    ///
+    /// [`to_rfc2253`]: X509NameRef::to_rfc2253
+    #[corresponds(X509_NAME_oneline)]
+    pub fn oneline(&self) -> Result<String, ErrorStack> {
+        unsafe {
+            let s = cvt_p(ffi::X509_NAME_oneline(self.as_ptr(), ptr::null_mut(), 0))?;
+            Ok(OpensslString::from_ptr(s).to_string())
+        }
+    }
+
     to_der! {
         /// Serializes the certificate into a DER-encoded X509 name structure.
         ///
@@ -958,6 +1847,193 @@ impl fmt::Debug for X509NameRef {
     }
 }
 
+bitflags! {
+    /// Flags controlling how [`X509NameRef::to_string_with_flags`] formats a name.
+    ///
+    /// `RFC2253`, `ONELINE` and `MULTILINE` are complete formats. The other flags select
+    /// individual aspects of the output, and can be combined to build other formats.
+    pub struct X509NamePrintFlags: c_ulong {
+        /// OpenSSL's original format, equivalent to `X509_NAME_print`.
+        const COMPAT = ffi::XN_FLAG_COMPAT;
+        /// Separates relative distinguished names with `,` and attributes with `+`.
+        const SEP_COMMA_PLUS = ffi::XN_FLAG_SEP_COMMA_PLUS;
+        /// Separates relative distinguished names with `, ` and attributes with ` + `.
+        const SEP_CPLUS_SPC = ffi::XN_FLAG_SEP_CPLUS_SPC;
+        /// Separates relative distinguished names with `; ` and attributes with ` + `.
+        const SEP_SPLUS_SPC = ffi::XN_FLAG_SEP_SPLUS_SPC;
+        /// Writes each relative distinguished name on its own line.
+        const SEP_MULTILINE = ffi::XN_FLAG_SEP_MULTILINE;
+        /// Writes the relative distinguished names from the last to the first.
+        const DN_REV = ffi::XN_FLAG_DN_REV;
+        /// Uses the long names of attribute types, such as `commonName`.
+        const FN_LN = ffi::XN_FLAG_FN_LN;
+        /// Uses the dotted OIDs of attribute types.
+        const FN_OID = ffi::XN_FLAG_FN_OID;
+        /// Omits attribute types.
+        const FN_NONE = ffi::XN_FLAG_FN_NONE;
+        /// Puts spaces around the `=` between attribute types and values.
+        const SPC_EQ = ffi::XN_FLAG_SPC_EQ;
+        /// Writes the values of attributes with unknown types as hex-encoded DER.
+        const DUMP_UNKNOWN_FIELDS = ffi::XN_FLAG_DUMP_UNKNOWN_FIELDS;
+        /// Aligns attribute types in the multi-line format.
+        const FN_ALIGN = ffi::XN_FLAG_FN_ALIGN;
+        /// The format of RFC 2253.
+        const RFC2253 = ffi::XN_FLAG_RFC2253;
+        /// A more readable single line format, such as `C = US, O = Example, CN = Alice`.
+        const ONELINE = ffi::XN_FLAG_ONELINE;
+        /// A multi-line format using the long names of attribute types.
+        const MULTILINE = ffi::XN_FLAG_MULTILINE;
+    }
+}
+
+/// An attribute of a relative distinguished name parsed from a string.
+#[cfg(not(boringssl))]
+struct Rfc2253Attribute {
+    field: String,
+    value: Vec<u8>,
+    ty: c_int,
+}
+
+/// Splits a name in the format of RFC 2253 into its relative distinguished names, in the order
+/// they are written.
+#[cfg(not(boringssl))]
+fn parse_rfc2253(name: &str) -> Result<Vec<Vec<Rfc2253Attribute>>, ErrorStack> {
+    let mut rdns = vec![];
+    let mut rdn = vec![];
+    let mut rest = name.trim_start().as_bytes();
+    if rest.is_empty() {
+        return Ok(rdns);
+    }
+
+    loop {
+        let eq = rest
+            .iter()
+            .position(|&b| b == b'=')
+            .ok_or_else(illegal_characters)?;
+        let field = str::from_utf8(&rest[..eq]).unwrap().trim();
+        if field.is_empty() {
+            return Err(illegal_characters());
+        }
+        let (value, ty, tail) = parse_rfc2253_value(&rest[eq + 1..])?;
+        rdn.push(Rfc2253Attribute {
+            field: field.to_string(),
+            value,
+            ty,
+        });
+
+        match tail.split_first() {
+            None => {
+                rdns.push(rdn);
+                return Ok(rdns);
+            }
+            Some((b'+', tail)) => rest = tail,
+            Some((b',', tail)) | Some((b';', tail)) => {
+                rdns.push(mem::take(&mut rdn));
+                rest = tail;
+            }
+            Some(_) => return Err(illegal_characters()),
+        }
+    }
+}
+
+/// Parses an attribute value, returning it along with the type to add it with and the remainder
+/// of the input, which is either empty or starts with a separator.
+#[cfg(not(boringssl))]
+fn parse_rfc2253_value(s: &[u8]) -> Result<(Vec<u8>, c_int, &[u8]), ErrorStack> {
+    let s = trim_spaces_start(s);
+
+    if let Some((b'#', s)) = s.split_first() {
+        let end = s
+            .iter()
+            .position(|&b| matches!(b, b',' | b'+' | b';' | b' '))
+            .unwrap_or(s.len());
+        let der = decode_hex(&s[..end]).ok_or_else(illegal_characters)?;
+        // only primitive universal types are strings, and their tags match their V_ASN1 types
+        if der.first().map_or(true, |&b| b & 0x20 != 0) {
+            return Err(illegal_characters());
+        }
+        let mut input = &der[..];
+        let (tag, class, contents) = get_object(&mut input)?;
+        if class != ffi::V_ASN1_UNIVERSAL || !input.is_empty() {
+            return Err(illegal_characters());
+        }
+        return Ok((contents.to_vec(), tag, trim_spaces_start(&s[end..])));
+    }
+
+    let (quoted, mut s) = match s.split_first() {
+        Some((b'"', s)) => (true, s),
+        _ => (false, s),
+    };
+    let mut value = vec![];
+    // unescaped trailing spaces are not part of the value
+    let mut len = 0;
+    loop {
+        match s.split_first() {
+            None if quoted => return Err(illegal_characters()),
+            None => break,
+            Some((b'"', rest)) if quoted => {
+                s = trim_spaces_start(rest);
+                break;
+            }
+            Some((b',', _)) | Some((b'+', _)) | Some((b';', _)) if !quoted => break,
+            Some((b'\\', rest)) => {
+                let (b, rest) = parse_rfc2253_escape(rest).ok_or_else(illegal_characters)?;
+                value.push(b);
+                len = value.len();
+                s = rest;
+            }
+            Some((&b, rest)) => {
+                value.push(b);
+                if quoted || b != b' ' {
+                    len = value.len();
+                }
+                s = rest;
+            }
+        }
+    }
+    value.truncate(len);
+
+    Ok((value, ffi::MBSTRING_UTF8, s))
+}
+
+/// Parses the character or pair of hex digits following a `\`.
+#[cfg(not(boringssl))]
+fn parse_rfc2253_escape(s: &[u8]) -> Option<(u8, &[u8])> {
+    let (&first, rest) = s.split_first()?;
+    if let Some(high) = hex_digit(first) {
+        let (&second, rest) = rest.split_first()?;
+        return Some((high << 4 | hex_digit(second)?, rest));
+    }
+    if b",=+<>#;\\\" ".contains(&first) {
+        Some((first, rest))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(boringssl))]
+fn decode_hex(s: &[u8]) -> Option<Vec<u8>> {
+    if s.is_empty() || s.len() % 2 != 0 {
+        return None;
+    }
+    s.chunks(2)
+        .map(|pair| Some(hex_digit(pair[0])? << 4 | hex_digit(pair[1])?))
+        .collect()
+}
+
+#[cfg(not(boringssl))]
+fn hex_digit(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
+
+#[cfg(not(boringssl))]
+fn trim_spaces_start(mut s: &[u8]) -> &[u8] {
+    while let Some((b' ', rest)) = s.split_first() {
+        s = rest;
+    }
+    s
+}
+
 /// A type to destructure and examine an `X509Name`.
 pub struct X509NameEntries<'a> {
     name: &'a X509NameRef,
@@ -1030,6 +2106,16 @@ impl X509NameEntryRef {
             Asn1ObjectRef::from_ptr(object)
         }
     }
+
+    /// Returns the index of the relative distinguished name containing the entry within its
+    /// `X509Name`.
+    ///
+    /// The entries of a multi-valued relative distinguished name share the same index.
+    #[corresponds(X509_NAME_ENTRY_set)]
+    #[cfg(any(ossl110, libressl270, boringssl))]
+    pub fn rdn_index(&self) -> usize {
+        unsafe { ffi::X509_NAME_ENTRY_set(self.as_ptr()) as usize }
+    }
 }
 
 impl fmt::Debug for X509NameEntryRef {
@@ -1128,8 +2214,75 @@ impl X509ReqBuilder {
         }
     }
 
+    /// Adds an extension to the request's `extensionRequest` attribute.
+    ///
+    /// Unlike `add_extensions`, this may be called any number of times, and alongside
+    /// `add_extensions`; the extensions are merged into a single attribute.
+    pub fn append_extension(&mut self, extension: X509Extension) -> Result<(), ErrorStack> {
+        self.append_extension2(&extension)
+    }
+
+    /// Adds an extension to the request's `extensionRequest` attribute.
+    #[corresponds(X509v3_add_ext)]
+    pub fn append_extension2(&mut self, extension: &X509ExtensionRef) -> Result<(), ErrorStack> {
+        unsafe {
+            let extensions = ffi::X509_REQ_get_extensions(self.0.as_ptr());
+            let extensions = match Stack::<X509Extension>::from_ptr_opt(extensions) {
+                Some(extensions) => extensions,
+                None => Stack::new()?,
+            };
+            let mut ptr = extensions.as_ptr();
+            cvt_p(ffi::X509v3_add_ext(&mut ptr, extension.as_ptr(), -1))?;
+
+            for &nid in &[ffi::NID_ext_req, ffi::NID_ms_ext_req] {
+                loop {
+                    let idx = ffi::X509_REQ_get_attr_by_NID(self.0.as_ptr(), nid, -1);
+                    if idx < 0 {
+                        break;
+                    }
+                    ffi::X509_ATTRIBUTE_free(ffi::X509_REQ_delete_attr(self.0.as_ptr(), idx));
+                }
+            }
+
+            cvt(ffi::X509_REQ_add_extensions(
+                self.0.as_ptr(),
+                extensions.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds a `challengePassword` attribute to the request.
+    ///
+    /// The password is encoded as a `PrintableString` if possible. This should only be called once
+    /// per request.
+    #[corresponds(X509_REQ_add1_attr_by_NID)]
+    pub fn set_challenge_password(&mut self, password: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_REQ_add1_attr_by_NID(
+                self.0.as_ptr(),
+                ffi::NID_pkcs9_challengePassword,
+                ffi::MBSTRING_ASC,
+                password.as_ptr(),
+                password.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds an attribute to the request.
+    ///
+    /// The `extensionRequest` attribute should be populated with `add_extensions` instead.
+    #[corresponds(X509_REQ_add1_attr)]
+    pub fn add_attribute(&mut self, attribute: &X509AttributeRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_REQ_add1_attr(self.0.as_ptr(), attribute.as_ptr())).map(|_| ()) }
+    }
+
     /// Sign the request using a private key.
     ///
+    /// Keys which do not use a separate digest, such as Ed25519 keys, must be used with
+    /// `MessageDigest::null()`.
+    ///
     /// This corresponds to [`X509_REQ_sign`].
     ///
     /// [`X509_REQ_sign`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REQ_sign.html
@@ -1147,6 +2300,43 @@ impl X509ReqBuilder {
         }
     }
 
+    /// Returns the DER-encoded `CertificationRequestInfo` structure, which is the data covered by
+    /// the request's signature.
+    ///
+    /// Together with [`build_with_signature`], this allows the request to be signed by a key held
+    /// outside of the process, such as in an HSM.
+    ///
+    /// [`build_with_signature`]: X509ReqBuilder::build_with_signature
+    #[corresponds(i2d_re_X509_REQ_tbs)]
+    #[cfg(ossl111)]
+    pub fn tbs_request(&mut self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let len = cvt(ffi::i2d_re_X509_REQ_tbs(self.0.as_ptr(), ptr::null_mut()))?;
+            let mut buf = vec![0; len as usize];
+            cvt(ffi::i2d_re_X509_REQ_tbs(
+                self.0.as_ptr(),
+                &mut buf.as_mut_ptr(),
+            ))?;
+            Ok(buf)
+        }
+    }
+
+    /// Consumes the builder, returning the request with a signature produced externally over the
+    /// output of [`tbs_request`].
+    ///
+    /// The signature is not checked against `algorithm`.
+    ///
+    /// [`tbs_request`]: X509ReqBuilder::tbs_request
+    #[cfg(ossl111)]
+    pub fn build_with_signature(
+        mut self,
+        algorithm: &X509AlgorithmRef,
+        signature: &[u8],
+    ) -> Result<X509Req, ErrorStack> {
+        let der = signed_der(&self.tbs_request()?, algorithm, signature)?;
+        X509Req::from_der(&der)
+    }
+
     /// Returns the `X509Req`.
     pub fn build(self) -> X509Req {
         self.0
@@ -1273,26 +2463,942 @@ impl X509ReqRef {
             Ok(Stack::from_ptr(extensions))
         }
     }
-}
-
-/// The result of peer certificate verification.
-#[derive(Copy, Clone, PartialEq, Eq)]
-pub struct X509VerifyResult(c_int);
 
-impl fmt::Debug for X509VerifyResult {
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt.debug_struct("X509VerifyResult")
-            .field("code", &self.0)
-            .field("error", &self.error_string())
-            .finish()
+    /// Returns the subject alternative name entries requested, if they exist.
+    #[corresponds(X509V3_get_d2i)]
+    pub fn subject_alt_names(&self) -> Option<Stack<GeneralName>> {
+        let extensions = self.extensions().ok()?;
+        unsafe {
+            let stack = ffi::X509V3_get_d2i(
+                extensions.as_ptr(),
+                ffi::NID_subject_alt_name,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Stack::from_ptr_opt(stack as *mut _)
+        }
     }
-}
 
-impl fmt::Display for X509VerifyResult {
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt.write_str(self.error_string())
-    }
-}
+    /// Returns the certificate request's signature.
+    #[corresponds(X509_REQ_get0_signature)]
+    #[cfg(ossl110)]
+    pub fn signature(&self) -> &Asn1BitStringRef {
+        unsafe {
+            let mut signature = ptr::null();
+            ffi::X509_REQ_get0_signature(self.as_ptr(), &mut signature, ptr::null_mut());
+            Asn1BitStringRef::from_const_ptr_opt(signature).expect("signature must not be null")
+        }
+    }
+
+    /// Returns the certificate request's signature algorithm.
+    #[corresponds(X509_REQ_get0_signature)]
+    #[cfg(ossl110)]
+    pub fn signature_algorithm(&self) -> &X509AlgorithmRef {
+        unsafe {
+            let mut algor = ptr::null();
+            ffi::X509_REQ_get0_signature(self.as_ptr(), ptr::null_mut(), &mut algor);
+            X509AlgorithmRef::from_const_ptr_opt(algor)
+                .expect("signature algorithm must not be null")
+        }
+    }
+
+    /// Returns the number of attributes of the certificate request.
+    #[corresponds(X509_REQ_get_attr_count)]
+    pub fn attribute_count(&self) -> usize {
+        unsafe { ffi::X509_REQ_get_attr_count(self.as_ptr()) as usize }
+    }
+
+    /// Returns the attribute at the specified index, if it exists.
+    #[corresponds(X509_REQ_get_attr)]
+    pub fn attribute(&self, idx: usize) -> Option<&X509AttributeRef> {
+        if idx >= self.attribute_count() {
+            return None;
+        }
+
+        unsafe {
+            X509AttributeRef::from_const_ptr_opt(ffi::X509_REQ_get_attr(
+                self.as_ptr(),
+                idx as c_int,
+            ))
+        }
+    }
+
+    /// Returns the first attribute of the specified type, if it exists.
+    #[corresponds(X509_REQ_get_attr_by_NID)]
+    pub fn attribute_by_nid(&self, nid: Nid) -> Option<&X509AttributeRef> {
+        unsafe {
+            let idx = ffi::X509_REQ_get_attr_by_NID(self.as_ptr(), nid.as_raw(), -1);
+            if idx < 0 {
+                return None;
+            }
+            X509AttributeRef::from_const_ptr_opt(ffi::X509_REQ_get_attr(self.as_ptr(), idx))
+        }
+    }
+
+    /// Returns the value of the `challengePassword` attribute, if it exists.
+    pub fn challenge_password(&self) -> Option<&Asn1StringRef> {
+        self.attribute_by_nid(Nid::PKCS9_CHALLENGEPASSWORD)?
+            .value_string(0)
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::X509_REVOKED;
+    fn drop = ffi::X509_REVOKED_free;
+
+    /// An entry in a certificate revocation list.
+    pub struct X509Revoked;
+    /// Reference to `X509Revoked`.
+    pub struct X509RevokedRef;
+}
+
+impl Stackable for X509Revoked {
+    type StackType = ffi::stack_st_X509_REVOKED;
+}
+
+impl X509Revoked {
+    /// Creates a new entry for the certificate with serial number `serial_number`, revoked at
+    /// `revocation_date`.
+    #[corresponds(X509_REVOKED_new)]
+    pub fn new(
+        serial_number: &Asn1IntegerRef,
+        revocation_date: &Asn1TimeRef,
+    ) -> Result<X509Revoked, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let revoked = X509Revoked::from_ptr(cvt_p(ffi::X509_REVOKED_new())?);
+            cvt(ffi::X509_REVOKED_set_serialNumber(
+                revoked.as_ptr(),
+                serial_number.as_ptr(),
+            ))?;
+            cvt(ffi::X509_REVOKED_set_revocationDate(
+                revoked.as_ptr(),
+                revocation_date.as_ptr(),
+            ))?;
+            Ok(revoked)
+        }
+    }
+
+    from_der! {
+        /// Deserializes a DER-encoded `RevokedCertificate` structure.
+        #[corresponds(d2i_X509_REVOKED)]
+        from_der,
+        X509Revoked,
+        ffi::d2i_X509_REVOKED
+    }
+}
+
+impl X509RevokedRef {
+    to_der! {
+        /// Serializes the entry into a DER-encoded `RevokedCertificate` structure.
+        #[corresponds(i2d_X509_REVOKED)]
+        to_der,
+        ffi::i2d_X509_REVOKED
+    }
+
+    /// Returns the serial number of the revoked certificate.
+    #[corresponds(X509_REVOKED_get0_serialNumber)]
+    pub fn serial_number(&self) -> &Asn1IntegerRef {
+        unsafe { Asn1IntegerRef::from_const_ptr(X509_REVOKED_get0_serialNumber(self.as_ptr())) }
+    }
+
+    /// Returns the time at which the certificate was revoked.
+    #[corresponds(X509_REVOKED_get0_revocationDate)]
+    pub fn revocation_date(&self) -> &Asn1TimeRef {
+        unsafe { Asn1TimeRef::from_const_ptr(X509_REVOKED_get0_revocationDate(self.as_ptr())) }
+    }
+
+    /// Returns the reason code of the entry, if it has one.
+    #[corresponds(X509_REVOKED_get_ext_d2i)]
+    pub fn reason(&self) -> Option<CrlReason> {
+        unsafe {
+            let reason = ffi::X509_REVOKED_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_crl_reason,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            ) as *mut ffi::ASN1_ENUMERATED;
+            if reason.is_null() {
+                return None;
+            }
+            let raw = ffi::ASN1_ENUMERATED_get(reason);
+            ffi::ASN1_ENUMERATED_free(reason);
+            Some(CrlReason::from_raw(raw as c_int))
+        }
+    }
+
+    /// Sets the reason code of the entry, replacing any existing one.
+    #[corresponds(X509_REVOKED_add1_ext_i2d)]
+    pub fn set_reason(&mut self, reason: CrlReason) -> Result<(), ErrorStack> {
+        unsafe {
+            let value = cvt_p(ffi::ASN1_ENUMERATED_new())?;
+            let r =
+                cvt(ffi::ASN1_ENUMERATED_set(value, reason.as_raw() as c_long)).and_then(|_| {
+                    cvt(ffi::X509_REVOKED_add1_ext_i2d(
+                        self.as_ptr(),
+                        ffi::NID_crl_reason,
+                        value as *mut c_void,
+                        0,
+                        ffi::X509V3_ADD_REPLACE,
+                    ))
+                });
+            ffi::ASN1_ENUMERATED_free(value);
+            r.map(|_| ())
+        }
+    }
+
+    /// Returns the date on which the private key was known or suspected to have been compromised,
+    /// if the entry specifies one.
+    #[corresponds(X509_REVOKED_get_ext_d2i)]
+    pub fn invalidity_date(&self) -> Option<Asn1Time> {
+        unsafe {
+            let date = ffi::X509_REVOKED_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_invalidity_date,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Asn1Time::from_ptr_opt(date as *mut _)
+        }
+    }
+
+    /// Sets the date on which the private key was known or suspected to have been compromised,
+    /// replacing any existing one.
+    #[corresponds(X509_REVOKED_add1_ext_i2d)]
+    pub fn set_invalidity_date(&mut self, date: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            // the extension is always encoded as a GeneralizedTime
+            let value = cvt_p(ffi::ASN1_TIME_to_generalizedtime(
+                date.as_ptr(),
+                ptr::null_mut(),
+            ))?;
+            let r = cvt(ffi::X509_REVOKED_add1_ext_i2d(
+                self.as_ptr(),
+                ffi::NID_invalidity_date,
+                value as *mut c_void,
+                0,
+                ffi::X509V3_ADD_REPLACE,
+            ));
+            ffi::ASN1_GENERALIZEDTIME_free(value);
+            r.map(|_| ())
+        }
+    }
+}
+
+impl fmt::Debug for X509RevokedRef {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("X509Revoked")
+            .field("serial_number", &self.serial_number().to_bn().ok())
+            .field("revocation_date", &self.revocation_date())
+            .finish()
+    }
+}
+
+/// The reason a certificate was revoked.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CrlReason(c_int);
+
+impl CrlReason {
+    pub const UNSPECIFIED: CrlReason = CrlReason(ffi::CRL_REASON_UNSPECIFIED);
+    pub const KEY_COMPROMISE: CrlReason = CrlReason(ffi::CRL_REASON_KEY_COMPROMISE);
+    pub const CA_COMPROMISE: CrlReason = CrlReason(ffi::CRL_REASON_CA_COMPROMISE);
+    pub const AFFILIATION_CHANGED: CrlReason = CrlReason(ffi::CRL_REASON_AFFILIATION_CHANGED);
+    pub const SUPERSEDED: CrlReason = CrlReason(ffi::CRL_REASON_SUPERSEDED);
+    pub const CESSATION_OF_OPERATION: CrlReason = CrlReason(ffi::CRL_REASON_CESSATION_OF_OPERATION);
+    pub const CERTIFICATE_HOLD: CrlReason = CrlReason(ffi::CRL_REASON_CERTIFICATE_HOLD);
+    pub const REMOVE_FROM_CRL: CrlReason = CrlReason(ffi::CRL_REASON_REMOVE_FROM_CRL);
+    pub const PRIVILEGE_WITHDRAWN: CrlReason = CrlReason(ffi::CRL_REASON_PRIVILEGE_WITHDRAWN);
+    pub const AA_COMPROMISE: CrlReason = CrlReason(ffi::CRL_REASON_AA_COMPROMISE);
+
+    /// Constructs a `CrlReason` from a raw `CRLReason` value.
+    pub fn from_raw(raw: c_int) -> CrlReason {
+        CrlReason(raw)
+    }
+
+    /// Returns the raw `CRLReason` value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// A builder used to construct an `X509Crl`.
+pub struct X509CrlBuilder(X509Crl);
+
+impl X509CrlBuilder {
+    /// Creates a new builder.
+    #[corresponds(X509_CRL_new)]
+    pub fn new() -> Result<X509CrlBuilder, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::X509_CRL_new()).map(|p| X509CrlBuilder(X509Crl(p)))
+        }
+    }
+
+    /// Sets the version of the CRL.
+    ///
+    /// Note that the version is zero-indexed; CRLs with extensions must be version 2, and should
+    /// pass `1` to this method.
+    #[corresponds(X509_CRL_set_version)]
+    pub fn set_version(&mut self, version: i32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_CRL_set_version(self.0.as_ptr(), version.into())).map(|_| ()) }
+    }
+
+    /// Sets the issuer name of the CRL.
+    #[corresponds(X509_CRL_set_issuer_name)]
+    pub fn set_issuer_name(&mut self, issuer_name: &X509NameRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_set_issuer_name(
+                self.0.as_ptr(),
+                issuer_name.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the time at which the CRL was issued.
+    #[corresponds(X509_CRL_set1_lastUpdate)]
+    pub fn set_last_update(&mut self, last_update: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(X509_CRL_set1_lastUpdate(
+                self.0.as_ptr(),
+                last_update.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the time by which the next CRL will be issued.
+    #[corresponds(X509_CRL_set1_nextUpdate)]
+    pub fn set_next_update(&mut self, next_update: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(X509_CRL_set1_nextUpdate(
+                self.0.as_ptr(),
+                next_update.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds an entry to the list of revoked certificates.
+    #[corresponds(X509_CRL_add0_revoked)]
+    pub fn add_revoked(&mut self, revoked: X509Revoked) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_add0_revoked(
+                self.0.as_ptr(),
+                revoked.as_ptr(),
+            ))?;
+            mem::forget(revoked);
+            Ok(())
+        }
+    }
+
+    /// Sets the CRL number extension, which identifies the sequence of CRLs issued by the CA.
+    #[corresponds(X509_CRL_add1_ext_i2d)]
+    pub fn set_crl_number(&mut self, crl_number: &Asn1IntegerRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_add1_ext_i2d(
+                self.0.as_ptr(),
+                ffi::NID_crl_number,
+                crl_number.as_ptr() as *mut c_void,
+                0,
+                ffi::X509V3_ADD_REPLACE,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the delta CRL indicator extension, marking this as a delta CRL which updates the base
+    /// CRL numbered `base_crl_number`.
+    #[corresponds(X509_CRL_add1_ext_i2d)]
+    pub fn set_delta_crl_indicator(
+        &mut self,
+        base_crl_number: &Asn1IntegerRef,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_add1_ext_i2d(
+                self.0.as_ptr(),
+                ffi::NID_delta_crl,
+                base_crl_number.as_ptr() as *mut c_void,
+                1,
+                ffi::X509V3_ADD_REPLACE,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns a context object which is needed to create certain X509 extension values, such as
+    /// the authority key identifier.
+    ///
+    /// `issuer` is the certificate of the CA issuing the CRL.
+    #[corresponds(X509V3_set_ctx)]
+    pub fn x509v3_context<'a>(
+        &'a self,
+        issuer: &'a X509Ref,
+        conf: Option<&'a ConfRef>,
+    ) -> X509v3Context<'a> {
+        unsafe {
+            let mut ctx = mem::zeroed();
+
+            ffi::X509V3_set_ctx(
+                &mut ctx,
+                issuer.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                self.0.as_ptr(),
+                0,
+            );
+
+            // nodb case taken care of since we zeroed ctx above
+            if let Some(conf) = conf {
+                ffi::X509V3_set_nconf(&mut ctx, conf.as_ptr());
+            }
+
+            X509v3Context(ctx, PhantomData)
+        }
+    }
+
+    /// Adds an X509 extension value to the CRL.
+    ///
+    /// This works just as `append_extension2` except it takes ownership of the `X509Extension`.
+    pub fn append_extension(&mut self, extension: X509Extension) -> Result<(), ErrorStack> {
+        self.append_extension2(&extension)
+    }
+
+    /// Adds an X509 extension value to the CRL.
+    #[corresponds(X509_CRL_add_ext)]
+    pub fn append_extension2(&mut self, extension: &X509ExtensionRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_add_ext(
+                self.0.as_ptr(),
+                extension.as_ptr(),
+                -1,
+            ))?;
+            Ok(())
+        }
+    }
+
+    /// Sorts the revoked entries by serial number and signs the CRL with a private key.
+    ///
+    /// OpenSSL only derives the information it uses during verification, such as the CRL number
+    /// and delta CRL indicator, when a CRL is decoded, so the signed CRL is re-encoded and decoded
+    /// to allow it to be used in an `X509Store`.
+    #[corresponds(X509_CRL_sign)]
+    pub fn sign<T>(&mut self, key: &PKeyRef<T>, hash: MessageDigest) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            cvt(ffi::X509_CRL_sort(self.0.as_ptr()))?;
+            cvt(ffi::X509_CRL_sign(
+                self.0.as_ptr(),
+                key.as_ptr(),
+                hash.as_ptr(),
+            ))?;
+        }
+        self.0 = X509Crl::from_der(&self.0.to_der()?)?;
+        Ok(())
+    }
+
+    /// Consumes the builder, returning the CRL.
+    pub fn build(self) -> X509Crl {
+        self.0
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::X509_CRL;
+    fn drop = ffi::X509_CRL_free;
+
+    /// A certificate revocation list.
+    pub struct X509Crl;
+    /// Reference to `X509Crl`.
+    pub struct X509CrlRef;
+}
+
+impl Stackable for X509Crl {
+    type StackType = ffi::stack_st_X509_CRL;
+}
+
+impl X509Crl {
+    /// Returns a new builder.
+    pub fn builder() -> Result<X509CrlBuilder, ErrorStack> {
+        X509CrlBuilder::new()
+    }
+
+    from_pem! {
+        /// Deserializes a PEM-encoded `CertificateList` structure.
+        ///
+        /// The input should have a header of `-----BEGIN X509 CRL-----`.
+        #[corresponds(PEM_read_bio_X509_CRL)]
+        from_pem,
+        X509Crl,
+        ffi::PEM_read_bio_X509_CRL
+    }
+
+    from_der! {
+        /// Deserializes a DER-encoded `CertificateList` structure.
+        #[corresponds(d2i_X509_CRL)]
+        from_der,
+        X509Crl,
+        ffi::d2i_X509_CRL
+    }
+
+    /// Deserializes a DER-encoded `CertificateList` structure read from `reader`.
+    ///
+    /// Only the bytes of the CRL are consumed from the reader. The encoded CRL is buffered in full
+    /// before it is parsed, and the parsed CRL holds every revoked entry, so memory use grows with
+    /// the size of the CRL. [`X509CrlIndex::from_der_reader`] checks revocation against very large
+    /// CRLs without holding them in memory.
+    ///
+    /// [`X509CrlIndex::from_der_reader`]: crl_index::X509CrlIndex::from_der_reader
+    #[corresponds(d2i_X509_CRL)]
+    pub fn from_der_reader<R>(mut reader: R) -> io::Result<X509Crl>
+    where
+        R: Read,
+    {
+        let der =
+            read_der(&mut reader)?.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        X509Crl::from_der(&der).map_err(io::Error::from)
+    }
+
+    /// Returns an iterator over concatenated DER-encoded CRLs read from `reader`.
+    ///
+    /// CRLs are read one at a time, each being buffered in full as with [`from_der_reader`].
+    ///
+    /// [`from_der_reader`]: X509Crl::from_der_reader
+    #[corresponds(d2i_X509_CRL)]
+    pub fn iter_from_der_reader<R>(reader: R) -> DerIter<R, X509Crl>
+    where
+        R: Read,
+    {
+        DerIter::new(reader, X509Crl::from_der)
+    }
+}
+
+impl X509CrlRef {
+    to_pem! {
+        /// Serializes the CRL into a PEM-encoded `CertificateList` structure.
+        ///
+        /// The output will have a header of `-----BEGIN X509 CRL-----`.
+        #[corresponds(PEM_write_bio_X509_CRL)]
+        to_pem,
+        ffi::PEM_write_bio_X509_CRL
+    }
+
+    to_der! {
+        /// Serializes the CRL into a DER-encoded `CertificateList` structure.
+        #[corresponds(i2d_X509_CRL)]
+        to_der,
+        ffi::i2d_X509_CRL
+    }
+
+    /// Returns the name of the CRL's issuer.
+    #[corresponds(X509_CRL_get_issuer)]
+    pub fn issuer_name(&self) -> &X509NameRef {
+        unsafe {
+            let name = X509_CRL_get_issuer(self.as_ptr());
+            X509NameRef::from_const_ptr_opt(name).expect("issuer name must not be null")
+        }
+    }
+
+    /// Returns the time at which the CRL was issued.
+    #[corresponds(X509_CRL_get0_lastUpdate)]
+    pub fn last_update(&self) -> &Asn1TimeRef {
+        unsafe {
+            let time = X509_CRL_get0_lastUpdate(self.as_ptr());
+            Asn1TimeRef::from_const_ptr_opt(time).expect("last update must not be null")
+        }
+    }
+
+    /// Returns the time by which the next CRL will be issued, if specified.
+    #[corresponds(X509_CRL_get0_nextUpdate)]
+    pub fn next_update(&self) -> Option<&Asn1TimeRef> {
+        unsafe { Asn1TimeRef::from_const_ptr_opt(X509_CRL_get0_nextUpdate(self.as_ptr())) }
+    }
+
+    /// Returns the CRL number, if present.
+    #[corresponds(X509_CRL_get_ext_d2i)]
+    pub fn crl_number(&self) -> Option<Asn1Integer> {
+        unsafe {
+            let number = ffi::X509_CRL_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_crl_number,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Asn1Integer::from_ptr_opt(number as *mut _)
+        }
+    }
+
+    /// Returns the number of the base CRL this delta CRL updates, if this is a delta CRL.
+    #[corresponds(X509_CRL_get_ext_d2i)]
+    pub fn delta_crl_indicator(&self) -> Option<Asn1Integer> {
+        unsafe {
+            let number = ffi::X509_CRL_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_delta_crl,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Asn1Integer::from_ptr_opt(number as *mut _)
+        }
+    }
+
+    /// Returns the CRL's issuing distribution point, if present.
+    #[corresponds(X509_CRL_get_ext_d2i)]
+    pub fn issuing_distribution_point(&self) -> Option<IssuingDistPoint> {
+        unsafe {
+            let idp = ffi::X509_CRL_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_issuing_distribution_point,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            IssuingDistPoint::from_ptr_opt(idp as *mut _)
+        }
+    }
+
+    /// Returns the distribution points of delta CRLs for this CRL, if present.
+    #[corresponds(X509_CRL_get_ext_d2i)]
+    pub fn freshest_crl(&self) -> Option<Stack<DistPoint>> {
+        unsafe {
+            let stack = ffi::X509_CRL_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_freshest_crl,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Stack::from_ptr_opt(stack as *mut _)
+        }
+    }
+
+    /// Returns the revoked certificates listed in the CRL, if any.
+    #[corresponds(X509_CRL_get_REVOKED)]
+    pub fn revoked(&self) -> Option<&StackRef<X509Revoked>> {
+        unsafe { StackRef::from_const_ptr_opt(X509_CRL_get_REVOKED(self.as_ptr())) }
+    }
+
+    /// Checks that the CRL is signed by the private key corresponding to `key`.
+    ///
+    /// Returns `true` if verification succeeds.
+    #[corresponds(X509_CRL_verify)]
+    pub fn verify<T>(&self, key: &PKeyRef<T>) -> Result<bool, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe { cvt_n(ffi::X509_CRL_verify(self.as_ptr(), key.as_ptr())).map(|n| n != 0) }
+    }
+}
+
+impl fmt::Debug for X509CrlRef {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("X509Crl")
+            .field("issuer_name", &self.issuer_name())
+            .field("last_update", &self.last_update())
+            .field("next_update", &self.next_update())
+            .finish()
+    }
+}
+
+/// An iterator over concatenated DER-encoded objects read from a stream.
+///
+/// Objects are read one at a time, and each is buffered in full while it is parsed. Iteration
+/// stops at the end of the stream or after the first I/O error.
+pub struct DerIter<R, T> {
+    reader: R,
+    parse: fn(&[u8]) -> Result<T, ErrorStack>,
+    done: bool,
+}
+
+impl<R, T> DerIter<R, T> {
+    fn new(reader: R, parse: fn(&[u8]) -> Result<T, ErrorStack>) -> DerIter<R, T> {
+        DerIter {
+            reader,
+            parse,
+            done: false,
+        }
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R, T> Iterator for DerIter<R, T>
+where
+    R: Read,
+{
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<io::Result<T>> {
+        if self.done {
+            return None;
+        }
+
+        match read_der(&mut self.reader) {
+            Ok(Some(der)) => Some((self.parse)(&der).map_err(io::Error::from)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Reads one DER-encoded object, including its tag and length, from `reader`.
+///
+/// Returns `None` if the reader is at the end of the stream.
+fn read_der<R>(reader: &mut R) -> io::Result<Option<Vec<u8>>>
+where
+    R: Read,
+{
+    let (mut der, len) = match read_der_header(reader)? {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+    read_der_contents(reader, len, &mut der)?;
+    Ok(Some(der))
+}
+
+/// Reads the tag and length of a DER-encoded object from `reader`, returning them in their
+/// encoded form along with the length of the object's contents.
+///
+/// Returns `None` if the reader is at the end of the stream.
+fn read_der_header<R>(reader: &mut R) -> io::Result<Option<(Vec<u8>, usize)>>
+where
+    R: Read,
+{
+    let mut tag = [0];
+    loop {
+        match reader.read(&mut tag) {
+            Ok(0) => return Ok(None),
+            Ok(_) => break,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    let mut header = vec![tag[0]];
+    // high tag numbers are continued in base 128
+    if tag[0] & 0x1f == 0x1f {
+        loop {
+            let b = read_u8(reader)?;
+            header.push(b);
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+    }
+
+    let b = read_u8(reader)?;
+    header.push(b);
+    let len = if b & 0x80 == 0 {
+        usize::from(b)
+    } else {
+        let n = usize::from(b & 0x7f);
+        // indefinite lengths are not permitted in DER
+        if n == 0 || n > mem::size_of::<usize>() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid DER length",
+            ));
+        }
+        let mut len = 0;
+        for _ in 0..n {
+            let b = read_u8(reader)?;
+            header.push(b);
+            len = len << 8 | usize::from(b);
+        }
+        len
+    };
+
+    Ok(Some((header, len)))
+}
+
+/// Reads `len` bytes of an object's contents from `reader`, appending them to `buf`.
+fn read_der_contents<R>(reader: &mut R, len: usize, buf: &mut Vec<u8>) -> io::Result<()>
+where
+    R: Read,
+{
+    // the buffer grows as data arrives, so a bogus length can't force a huge allocation
+    let start = buf.len();
+    reader.take(len as u64).read_to_end(buf)?;
+    if buf.len() - start != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+    Ok(())
+}
+
+fn read_u8<R>(reader: &mut R) -> io::Result<u8>
+where
+    R: Read,
+{
+    let mut b = [0];
+    reader.read_exact(&mut b)?;
+    Ok(b[0])
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::X509_ATTRIBUTE;
+    fn drop = ffi::X509_ATTRIBUTE_free;
+
+    /// An attribute of a certificate request or PKCS#7 signer, consisting of a type and a set of
+    /// values.
+    pub struct X509Attribute;
+    /// Reference to `X509Attribute`.
+    pub struct X509AttributeRef;
+}
+
+impl Stackable for X509Attribute {
+    type StackType = ffi::stack_st_X509_ATTRIBUTE;
+}
+
+impl X509Attribute {
+    /// Creates an attribute of the specified type with a single value.
+    ///
+    /// For primitive types such as `Asn1Type::UTF8STRING`, `data` is the contents of the value.
+    /// For constructed types such as `Asn1Type::SEQUENCE`, it is the complete DER encoding of the
+    /// value.
+    #[corresponds(X509_ATTRIBUTE_create_by_OBJ)]
+    pub fn new(
+        object: &Asn1ObjectRef,
+        ty: Asn1Type,
+        data: &[u8],
+    ) -> Result<X509Attribute, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::X509_ATTRIBUTE_create_by_OBJ(
+                ptr::null_mut(),
+                object.as_ptr(),
+                ty.as_raw(),
+                data.as_ptr() as *const c_void,
+                data.len() as c_int,
+            ))
+            .map(X509Attribute)
+        }
+    }
+
+    from_der! {
+        /// Deserializes a DER-encoded `Attribute` structure.
+        #[corresponds(d2i_X509_ATTRIBUTE)]
+        from_der,
+        X509Attribute,
+        ffi::d2i_X509_ATTRIBUTE
+    }
+}
+
+impl X509AttributeRef {
+    to_der! {
+        /// Serializes the attribute into a DER-encoded `Attribute` structure.
+        #[corresponds(i2d_X509_ATTRIBUTE)]
+        to_der,
+        ffi::i2d_X509_ATTRIBUTE
+    }
+
+    /// Returns the type of the attribute.
+    #[corresponds(X509_ATTRIBUTE_get0_object)]
+    pub fn object(&self) -> &Asn1ObjectRef {
+        unsafe { Asn1ObjectRef::from_ptr(ffi::X509_ATTRIBUTE_get0_object(self.as_ptr())) }
+    }
+
+    /// Returns the number of values of the attribute.
+    #[corresponds(X509_ATTRIBUTE_count)]
+    pub fn value_count(&self) -> usize {
+        unsafe { ffi::X509_ATTRIBUTE_count(self.as_ptr()) as usize }
+    }
+
+    /// Returns the ASN.1 type of the value at the specified index, if it exists.
+    #[corresponds(X509_ATTRIBUTE_get0_type)]
+    pub fn value_type(&self, idx: usize) -> Option<Asn1Type> {
+        if idx >= self.value_count() {
+            return None;
+        }
+
+        unsafe {
+            let value = ffi::X509_ATTRIBUTE_get0_type(self.as_ptr(), idx as c_int);
+            if value.is_null() {
+                None
+            } else {
+                Some(Asn1Type::from_raw(ffi::ASN1_TYPE_get(value)))
+            }
+        }
+    }
+
+    /// Returns the value at the specified index if it is represented as a string.
+    ///
+    /// This is the case for all values other than booleans, nulls and object identifiers. The
+    /// contents of `SEQUENCE` and `SET` values are returned in their DER encoding.
+    #[corresponds(X509_ATTRIBUTE_get0_data)]
+    pub fn value_string(&self, idx: usize) -> Option<&Asn1StringRef> {
+        match self.value_type(idx)? {
+            Asn1Type::BOOLEAN | Asn1Type::NULL | Asn1Type::OBJECT => None,
+            ty => unsafe {
+                self.value_data(idx, ty)
+                    .map(|p| Asn1StringRef::from_ptr(p as *mut _))
+            },
+        }
+    }
+
+    /// Returns the value at the specified index if it is an object identifier.
+    #[corresponds(X509_ATTRIBUTE_get0_data)]
+    pub fn value_object(&self, idx: usize) -> Option<&Asn1ObjectRef> {
+        match self.value_type(idx)? {
+            Asn1Type::OBJECT => unsafe {
+                self.value_data(idx, Asn1Type::OBJECT)
+                    .map(|p| Asn1ObjectRef::from_ptr(p as *mut _))
+            },
+            _ => None,
+        }
+    }
+
+    unsafe fn value_data(&self, idx: usize, ty: Asn1Type) -> Option<*mut c_void> {
+        let data = ffi::X509_ATTRIBUTE_get0_data(
+            self.as_ptr(),
+            idx as c_int,
+            ty.as_raw(),
+            ptr::null_mut(),
+        );
+        if data.is_null() {
+            None
+        } else {
+            Some(data)
+        }
+    }
+}
+
+impl fmt::Debug for X509AttributeRef {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("X509Attribute")
+            .field("object", &self.object())
+            .field("value_count", &self.value_count())
+            .finish()
+    }
+}
+
+/// The result of peer certificate verification.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct X509VerifyResult(c_int);
+
+impl fmt::Debug for X509VerifyResult {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("X509VerifyResult")
+            .field("code", &self.0)
+            .field("error", &self.error_string())
+            .finish()
+    }
+}
+
+impl fmt::Display for X509VerifyResult {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(self.error_string())
+    }
+}
 
 impl Error for X509VerifyResult {}
 
@@ -1400,6 +3506,21 @@ impl GeneralNameRef {
             Some(slice::from_raw_parts(ptr as *const u8, len as usize))
         }
     }
+
+    /// Returns the contents of this `GeneralName` if it is a `directoryName`.
+    pub fn directory_name(&self) -> Option<&X509NameRef> {
+        unsafe {
+            if (*self.as_ptr()).type_ != ffi::GEN_DIRNAME {
+                return None;
+            }
+            #[cfg(boringssl)]
+            let d: *const ffi::X509_NAME = std::mem::transmute((*self.as_ptr()).d);
+            #[cfg(not(boringssl))]
+            let d = (*self.as_ptr()).d;
+
+            Some(X509NameRef::from_const_ptr(d as *const _))
+        }
+    }
 }
 
 impl fmt::Debug for GeneralNameRef {
@@ -1449,6 +3570,236 @@ impl Stackable for AccessDescription {
     type StackType = ffi::stack_st_ACCESS_DESCRIPTION;
 }
 
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::DIST_POINT;
+    fn drop = ffi::DIST_POINT_free;
+
+    /// A CRL distribution point.
+    pub struct DistPoint;
+    /// Reference to `DistPoint`.
+    pub struct DistPointRef;
+}
+
+impl DistPointRef {
+    /// Returns the name of this distribution point, if present.
+    pub fn distpoint(&self) -> Option<&DistPointNameRef> {
+        unsafe { DistPointNameRef::from_const_ptr_opt((*self.as_ptr()).distpoint) }
+    }
+
+    /// Returns the names of the entity which signs the CRLs at this distribution point, if
+    /// present.
+    ///
+    /// If this is absent, the CRLs are signed by the certificate's issuer.
+    pub fn crl_issuer(&self) -> Option<&StackRef<GeneralName>> {
+        unsafe { StackRef::from_const_ptr_opt((*self.as_ptr()).CRLissuer) }
+    }
+}
+
+impl Stackable for DistPoint {
+    type StackType = ffi::stack_st_DIST_POINT;
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::DIST_POINT_NAME;
+    fn drop = ffi::DIST_POINT_NAME_free;
+
+    /// The name of a CRL distribution point.
+    pub struct DistPointName;
+    /// Reference to `DistPointName`.
+    pub struct DistPointNameRef;
+}
+
+impl DistPointNameRef {
+    /// Returns the general names of this distribution point, if it is identified by its full
+    /// name.
+    pub fn fullname(&self) -> Option<&StackRef<GeneralName>> {
+        unsafe {
+            if (*self.as_ptr()).type_ != 0 {
+                return None;
+            }
+            StackRef::from_const_ptr_opt((*self.as_ptr()).name.fullname)
+        }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::ISSUING_DIST_POINT;
+    fn drop = ffi::ISSUING_DIST_POINT_free;
+
+    /// The contents of a CRL's issuing distribution point extension.
+    pub struct IssuingDistPoint;
+    /// Reference to `IssuingDistPoint`.
+    pub struct IssuingDistPointRef;
+}
+
+impl IssuingDistPointRef {
+    /// Returns the name of the distribution point, if present.
+    pub fn distpoint(&self) -> Option<&DistPointNameRef> {
+        unsafe { DistPointNameRef::from_const_ptr_opt((*self.as_ptr()).distpoint) }
+    }
+
+    /// Determines if the CRL only covers end-entity certificates.
+    pub fn only_contains_user_certs(&self) -> bool {
+        unsafe { (*self.as_ptr()).onlyuser > 0 }
+    }
+
+    /// Determines if the CRL only covers CA certificates.
+    pub fn only_contains_ca_certs(&self) -> bool {
+        unsafe { (*self.as_ptr()).onlyCA > 0 }
+    }
+
+    /// Determines if the CRL only covers attribute certificates.
+    pub fn only_contains_attribute_certs(&self) -> bool {
+        unsafe { (*self.as_ptr()).onlyattr > 0 }
+    }
+
+    /// Determines if the CRL may list certificates issued by entities other than the CRL issuer.
+    pub fn indirect_crl(&self) -> bool {
+        unsafe { (*self.as_ptr()).indirectCRL > 0 }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::POLICY_CONSTRAINTS;
+    fn drop = ffi::POLICY_CONSTRAINTS_free;
+
+    /// The contents of a policy constraints extension.
+    pub struct X509PolicyConstraints;
+    /// Reference to `X509PolicyConstraints`.
+    pub struct X509PolicyConstraintsRef;
+}
+
+impl X509PolicyConstraintsRef {
+    /// Returns the number of additional certificates which may appear in a path before an
+    /// explicit policy is required, if present.
+    pub fn require_explicit_policy(&self) -> Option<&Asn1IntegerRef> {
+        unsafe { Asn1IntegerRef::from_const_ptr_opt((*self.as_ptr()).requireExplicitPolicy) }
+    }
+
+    /// Returns the number of additional certificates which may appear in a path before policy
+    /// mapping is no longer permitted, if present.
+    pub fn inhibit_policy_mapping(&self) -> Option<&Asn1IntegerRef> {
+        unsafe { Asn1IntegerRef::from_const_ptr_opt((*self.as_ptr()).inhibitPolicyMapping) }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::BASIC_CONSTRAINTS;
+    fn drop = ffi::BASIC_CONSTRAINTS_free;
+
+    /// The contents of a basic constraints extension.
+    pub struct X509BasicConstraints;
+    /// Reference to `X509BasicConstraints`.
+    pub struct X509BasicConstraintsRef;
+}
+
+impl X509BasicConstraintsRef {
+    /// Determines if the subject of the certificate is a CA.
+    pub fn ca(&self) -> bool {
+        unsafe { (*self.as_ptr()).ca != 0 }
+    }
+
+    /// Returns the maximum number of non-self-issued intermediate certificates which may follow
+    /// this one in a path, if present.
+    pub fn pathlen(&self) -> Option<&Asn1IntegerRef> {
+        unsafe { Asn1IntegerRef::from_const_ptr_opt((*self.as_ptr()).pathlen) }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::POLICYINFO;
+    fn drop = ffi::POLICYINFO_free;
+
+    /// A policy information entry of a certificate policies extension.
+    pub struct PolicyInfo;
+    /// Reference to `PolicyInfo`.
+    pub struct PolicyInfoRef;
+}
+
+impl Stackable for PolicyInfo {
+    type StackType = ffi::stack_st_POLICYINFO;
+}
+
+impl PolicyInfoRef {
+    /// Returns the identifier of the policy.
+    pub fn policy(&self) -> &Asn1ObjectRef {
+        unsafe { Asn1ObjectRef::from_const_ptr((*self.as_ptr()).policyid) }
+    }
+
+    /// Returns the qualifiers of the policy, if present.
+    pub fn qualifiers(&self) -> Option<&StackRef<PolicyQualifierInfo>> {
+        unsafe { StackRef::from_const_ptr_opt((*self.as_ptr()).qualifiers) }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::POLICYQUALINFO;
+    fn drop = ffi::POLICYQUALINFO_free;
+
+    /// A qualifier of a certificate policy.
+    pub struct PolicyQualifierInfo;
+    /// Reference to `PolicyQualifierInfo`.
+    pub struct PolicyQualifierInfoRef;
+}
+
+impl Stackable for PolicyQualifierInfo {
+    type StackType = ffi::stack_st_POLICYQUALINFO;
+}
+
+impl PolicyQualifierInfoRef {
+    /// Returns the identifier of the qualifier.
+    pub fn id(&self) -> &Asn1ObjectRef {
+        unsafe { Asn1ObjectRef::from_const_ptr((*self.as_ptr()).pqualid) }
+    }
+
+    /// Returns the contents of the qualifier if it is a CPS pointer.
+    pub fn cps_uri(&self) -> Option<&str> {
+        if self.id().nid() != Nid::ID_QT_CPS {
+            return None;
+        }
+
+        unsafe {
+            let d = (*self.as_ptr()).d.cpsuri;
+            let ptr = ASN1_STRING_get0_data(d);
+            let len = ffi::ASN1_STRING_length(d);
+
+            let slice = slice::from_raw_parts(ptr as *const u8, len as usize);
+            str::from_utf8(slice).ok()
+        }
+    }
+}
+
+/// Encodes a signed structure from its DER-encoded contents, signature algorithm and signature.
+#[cfg(ossl111)]
+fn signed_der(
+    tbs: &[u8],
+    algorithm: &X509AlgorithmRef,
+    signature: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut contents = tbs.to_vec();
+    contents.extend_from_slice(&algorithm.to_der()?);
+    // the leading byte is the number of unused bits
+    let mut bits = vec![0];
+    bits.extend_from_slice(signature);
+    put_object(
+        &mut contents,
+        ffi::V_ASN1_BIT_STRING,
+        ffi::V_ASN1_UNIVERSAL,
+        false,
+        &bits,
+    );
+
+    let mut der = vec![];
+    put_object(
+        &mut der,
+        ffi::V_ASN1_SEQUENCE,
+        ffi::V_ASN1_UNIVERSAL,
+        true,
+        &contents,
+    );
+    Ok(der)
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::X509_ALGOR;
     fn drop = ffi::X509_ALGOR_free;
@@ -1459,7 +3810,28 @@ foreign_type_and_impl_send_sync! {
     pub struct X509AlgorithmRef;
 }
 
+impl Stackable for X509Algorithm {
+    type StackType = ffi::stack_st_X509_ALGOR;
+}
+
+impl X509Algorithm {
+    from_der! {
+        /// Deserializes a DER-encoded `AlgorithmIdentifier` structure.
+        #[corresponds(d2i_X509_ALGOR)]
+        from_der,
+        X509Algorithm,
+        ffi::d2i_X509_ALGOR
+    }
+}
+
 impl X509AlgorithmRef {
+    to_der! {
+        /// Serializes the algorithm into a DER-encoded `AlgorithmIdentifier` structure.
+        #[corresponds(i2d_X509_ALGOR)]
+        to_der,
+        ffi::i2d_X509_ALGOR
+    }
+
     /// Returns the ASN.1 OID of this algorithm.
     pub fn object(&self) -> &Asn1ObjectRef {
         unsafe {
@@ -1574,6 +3946,62 @@ cfg_if! {
     }
 }
 
+cfg_if! {
+    if #[cfg(any(boringssl, ossl110, libressl281))] {
+        use ffi::{
+            X509_CRL_get0_lastUpdate, X509_CRL_get0_nextUpdate, X509_CRL_get_REVOKED,
+            X509_CRL_get_issuer,
+        };
+    } else {
+        #[allow(bad_style)]
+        unsafe fn X509_CRL_get_issuer(x: *mut ffi::X509_CRL) -> *mut ffi::X509_NAME {
+            (*(*x).crl).issuer
+        }
+
+        #[allow(bad_style)]
+        unsafe fn X509_CRL_get0_lastUpdate(x: *mut ffi::X509_CRL) -> *const ffi::ASN1_TIME {
+            (*(*x).crl).lastUpdate
+        }
+
+        #[allow(bad_style)]
+        unsafe fn X509_CRL_get0_nextUpdate(x: *mut ffi::X509_CRL) -> *const ffi::ASN1_TIME {
+            (*(*x).crl).nextUpdate
+        }
+
+        #[allow(bad_style)]
+        unsafe fn X509_CRL_get_REVOKED(x: *mut ffi::X509_CRL) -> *mut ffi::stack_st_X509_REVOKED {
+            (*(*x).crl).revoked
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(any(boringssl, ossl110, libressl270))] {
+        use ffi::{X509_CRL_set1_lastUpdate, X509_CRL_set1_nextUpdate};
+    } else {
+        use ffi::{
+            X509_CRL_set_lastUpdate as X509_CRL_set1_lastUpdate,
+            X509_CRL_set_nextUpdate as X509_CRL_set1_nextUpdate,
+        };
+    }
+}
+
+cfg_if! {
+    if #[cfg(any(boringssl, ossl110, libressl270))] {
+        use ffi::{X509_REVOKED_get0_revocationDate, X509_REVOKED_get0_serialNumber};
+    } else {
+        #[allow(bad_style)]
+        unsafe fn X509_REVOKED_get0_serialNumber(x: *mut ffi::X509_REVOKED) -> *const ffi::ASN1_INTEGER {
+            (*x).serialNumber
+        }
+
+        #[allow(bad_style)]
+        unsafe fn X509_REVOKED_get0_revocationDate(x: *mut ffi::X509_REVOKED) -> *const ffi::ASN1_TIME {
+            (*x).revocationDate
+        }
+    }
+}
+
 cfg_if! {
     if #[cfg(any(ossl110, boringssl, libressl270))] {
         use ffi::X509_OBJECT_get0_X509;
@@ -0,0 +1,147 @@
+//! The certificate policy tree produced by chain verification.
+//!
+//! When policy checking is enabled with `X509VerifyFlags::POLICY_CHECK` or
+//! `X509VerifyFlags::EXPLICIT_POLICY`, OpenSSL builds a tree of the policies
+//! which are valid at each depth of the verified chain, as described in
+//! [RFC 5280 section 6.1]. The tree can be retrieved from an
+//! `X509StoreContextRef` once verification has finished.
+//!
+//! [RFC 5280 section 6.1]: https://www.rfc-editor.org/rfc/rfc5280#section-6.1
+use foreign_types::{ForeignTypeRef, Opaque};
+use libc::c_int;
+use std::fmt;
+
+use crate::asn1::Asn1ObjectRef;
+use crate::util::ForeignTypeRefExt;
+use openssl_macros::corresponds;
+
+/// The policy tree of a verified certificate chain.
+///
+/// This is owned by the `X509StoreContext` which built it.
+pub struct X509PolicyTreeRef(Opaque);
+
+impl ForeignTypeRef for X509PolicyTreeRef {
+    type CType = ffi::X509_POLICY_TREE;
+}
+
+impl X509PolicyTreeRef {
+    /// Returns the number of levels in the tree.
+    ///
+    /// Level 0 corresponds to the trust anchor, and each following level to the next certificate
+    /// down the chain towards the leaf.
+    #[corresponds(X509_policy_tree_level_count)]
+    pub fn level_count(&self) -> usize {
+        unsafe { ffi::X509_policy_tree_level_count(self.as_ptr()) as usize }
+    }
+
+    /// Returns the level at the specified index, if it exists.
+    #[corresponds(X509_policy_tree_get0_level)]
+    pub fn level(&self, idx: usize) -> Option<&X509PolicyLevelRef> {
+        if idx >= self.level_count() {
+            return None;
+        }
+
+        unsafe {
+            let level = ffi::X509_policy_tree_get0_level(self.as_ptr(), idx as c_int);
+            X509PolicyLevelRef::from_const_ptr_opt(level)
+        }
+    }
+
+    /// Returns the nodes of the authority-constrained policy set.
+    ///
+    /// These are the policies which are valid for the whole chain, regardless of the policies
+    /// requested by the verifier.
+    #[corresponds(X509_policy_tree_get0_policies)]
+    pub fn authority_policies(&self) -> Vec<&X509PolicyNodeRef> {
+        unsafe { nodes(ffi::X509_policy_tree_get0_policies(self.as_ptr())) }
+    }
+
+    /// Returns the nodes of the user-constrained policy set.
+    ///
+    /// These are the intersection of the authority-constrained policy set with the policies set
+    /// on the verification parameters. It is empty if the authority-constrained policy set
+    /// contains `anyPolicy`.
+    #[corresponds(X509_policy_tree_get0_user_policies)]
+    pub fn user_policies(&self) -> Vec<&X509PolicyNodeRef> {
+        unsafe { nodes(ffi::X509_policy_tree_get0_user_policies(self.as_ptr())) }
+    }
+}
+
+/// A single level of an `X509PolicyTreeRef`, corresponding to one certificate in the chain.
+pub struct X509PolicyLevelRef(Opaque);
+
+impl ForeignTypeRef for X509PolicyLevelRef {
+    type CType = ffi::X509_POLICY_LEVEL;
+}
+
+impl X509PolicyLevelRef {
+    /// Returns the number of nodes in this level.
+    #[corresponds(X509_policy_level_node_count)]
+    pub fn node_count(&self) -> usize {
+        unsafe { ffi::X509_policy_level_node_count(self.as_ptr()) as usize }
+    }
+
+    /// Returns the node at the specified index, if it exists.
+    #[corresponds(X509_policy_level_get0_node)]
+    pub fn node(&self, idx: usize) -> Option<&X509PolicyNodeRef> {
+        if idx >= self.node_count() {
+            return None;
+        }
+
+        unsafe {
+            let node = ffi::X509_policy_level_get0_node(self.as_ptr(), idx as c_int);
+            X509PolicyNodeRef::from_const_ptr_opt(node)
+        }
+    }
+
+    /// Returns the nodes in this level.
+    pub fn nodes(&self) -> Vec<&X509PolicyNodeRef> {
+        (0..self.node_count())
+            .filter_map(|i| self.node(i))
+            .collect()
+    }
+}
+
+/// A valid policy at some level of an `X509PolicyTreeRef`.
+pub struct X509PolicyNodeRef(Opaque);
+
+impl ForeignTypeRef for X509PolicyNodeRef {
+    type CType = ffi::X509_POLICY_NODE;
+}
+
+impl X509PolicyNodeRef {
+    /// Returns the policy OID of this node.
+    #[corresponds(X509_policy_node_get0_policy)]
+    pub fn policy(&self) -> &Asn1ObjectRef {
+        unsafe { Asn1ObjectRef::from_const_ptr(ffi::X509_policy_node_get0_policy(self.as_ptr())) }
+    }
+
+    /// Returns the node in the previous level from which this node was derived.
+    ///
+    /// Returns `None` for nodes in level 0.
+    #[corresponds(X509_policy_node_get0_parent)]
+    pub fn parent(&self) -> Option<&X509PolicyNodeRef> {
+        unsafe {
+            X509PolicyNodeRef::from_const_ptr_opt(ffi::X509_policy_node_get0_parent(self.as_ptr()))
+        }
+    }
+}
+
+impl fmt::Debug for X509PolicyNodeRef {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("X509PolicyNode")
+            .field("policy", &self.policy())
+            .finish()
+    }
+}
+
+unsafe fn nodes<'a>(stack: *mut ffi::stack_st_X509_POLICY_NODE) -> Vec<&'a X509PolicyNodeRef> {
+    if stack.is_null() {
+        return vec![];
+    }
+
+    let stack = stack as *mut ffi::OPENSSL_STACK;
+    (0..ffi::OPENSSL_sk_num(stack))
+        .map(|i| X509PolicyNodeRef::from_ptr(ffi::OPENSSL_sk_value(stack, i) as *mut _))
+        .collect()
+}
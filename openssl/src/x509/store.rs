@@ -44,7 +44,7 @@ use crate::ssl::SslFiletype;
 use crate::stack::StackRef;
 #[cfg(any(ossl102, libressl261))]
 use crate::x509::verify::X509VerifyFlags;
-use crate::x509::{X509Object, X509};
+use crate::x509::{X509CrlRef, X509Object, X509};
 use crate::{cvt, cvt_p};
 use openssl_macros::corresponds;
 use std::ffi::CString;
@@ -88,6 +88,20 @@ impl X509StoreBuilderRef {
         unsafe { cvt(ffi::X509_STORE_add_cert(self.as_ptr(), cert.as_ptr())).map(|_| ()) }
     }
 
+    /// Adds a certificate revocation list to the certificate store.
+    ///
+    /// CRLs in the store are only consulted if CRL checking is enabled with
+    /// [`X509VerifyFlags::CRL_CHECK`] or [`X509VerifyFlags::CRL_CHECK_ALL`], and delta CRLs only if
+    /// [`X509VerifyFlags::USE_DELTAS`] is also set.
+    ///
+    /// [`X509VerifyFlags::CRL_CHECK`]: crate::x509::verify::X509VerifyFlags::CRL_CHECK
+    /// [`X509VerifyFlags::CRL_CHECK_ALL`]: crate::x509::verify::X509VerifyFlags::CRL_CHECK_ALL
+    /// [`X509VerifyFlags::USE_DELTAS`]: crate::x509::verify::X509VerifyFlags::USE_DELTAS
+    #[corresponds(X509_STORE_add_crl)]
+    pub fn add_crl(&mut self, crl: &X509CrlRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_STORE_add_crl(self.as_ptr(), crl.as_ptr())).map(|_| ()) }
+    }
+
     /// Load certificates from their default locations.
     ///
     /// These locations are read from the `SSL_CERT_FILE` and `SSL_CERT_DIR`
@@ -161,6 +175,72 @@ impl X509LookupRef<HashDir> {
     }
 }
 
+/// Marker type corresponding to the [`X509_LOOKUP_file`] lookup method.
+///
+/// [`X509_LOOKUP_file`]: https://www.openssl.org/docs/man1.1.1/man3/X509_LOOKUP_file.html
+pub struct File;
+
+impl X509Lookup<File> {
+    /// Lookup method that loads all the certificates or CRLs present in a file
+    /// into memory at the time the file is added.
+    #[corresponds(X509_LOOKUP_file)]
+    pub fn file() -> &'static X509LookupMethodRef<File> {
+        unsafe { X509LookupMethodRef::from_ptr(ffi::X509_LOOKUP_file()) }
+    }
+}
+
+impl X509LookupRef<File> {
+    /// Loads the certificates, and in the case of PEM files also the CRLs,
+    /// contained in a file. Must be used with `X509Lookup::file`.
+    #[corresponds(X509_LOOKUP_load_file)]
+    #[cfg(not(boringssl))]
+    pub fn load_file(&mut self, file: &str, file_type: SslFiletype) -> Result<(), ErrorStack> {
+        let file = CString::new(file).unwrap();
+        unsafe {
+            cvt(ffi::X509_LOOKUP_load_file(
+                self.as_ptr(),
+                file.as_ptr(),
+                file_type.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Loads the certificates contained in a file, returning the number
+    /// loaded. Must be used with `X509Lookup::file`.
+    #[corresponds(X509_load_cert_file)]
+    pub fn load_cert_file(
+        &mut self,
+        file: &str,
+        file_type: SslFiletype,
+    ) -> Result<u32, ErrorStack> {
+        let file = CString::new(file).unwrap();
+        unsafe {
+            cvt(ffi::X509_load_cert_file(
+                self.as_ptr(),
+                file.as_ptr(),
+                file_type.as_raw(),
+            ))
+            .map(|n| n as u32)
+        }
+    }
+
+    /// Loads the CRLs contained in a file, returning the number loaded. Must
+    /// be used with `X509Lookup::file`.
+    #[corresponds(X509_load_crl_file)]
+    pub fn load_crl_file(&mut self, file: &str, file_type: SslFiletype) -> Result<u32, ErrorStack> {
+        let file = CString::new(file).unwrap();
+        unsafe {
+            cvt(ffi::X509_load_crl_file(
+                self.as_ptr(),
+                file.as_ptr(),
+                file_type.as_raw(),
+            ))
+            .map(|n| n as u32)
+        }
+    }
+}
+
 generic_foreign_type_and_impl_send_sync! {
     type CType = ffi::X509_LOOKUP_METHOD;
     fn drop = X509_LOOKUP_meth_free;
@@ -1,20 +1,51 @@
 use crate::asn1::Asn1Time;
+#[cfg(not(boringssl))]
+use crate::asn1::{Asn1Object, Asn1Type};
 use crate::bn::{BigNum, MsbOption};
+#[cfg(ossl110)]
+use crate::conf::{Conf, ConfMethod};
 use crate::hash::MessageDigest;
 use crate::nid::Nid;
+#[cfg(ossl110)]
+use crate::pkey::PKeyRef;
 use crate::pkey::{PKey, Private};
 use crate::rsa::Rsa;
+#[cfg(ossl111)]
+use crate::sign::Signer;
+use crate::ssl::SslFiletype;
 use crate::stack::Stack;
+#[cfg(ossl340)]
+use crate::x509::acert::X509Acert;
+#[cfg(ossl110)]
+use crate::x509::ca::{CertificateAuthority, IssuanceProfile};
+#[cfg(not(boringssl))]
+use crate::x509::crl_index::X509CrlIndex;
+#[cfg(all(ossl110, not(boringssl)))]
+use crate::x509::cross::X509CrossCertificatePair;
 use crate::x509::extension::{
-    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
-    SubjectKeyIdentifier,
+    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, FreshestCrl, InhibitAnyPolicy,
+    IssuingDistributionPoint, KeyUsage, NameConstraints, PolicyConstraints, SubjectAlternativeName,
+    SubjectInformationAccess, SubjectKeyIdentifier,
 };
-use crate::x509::store::X509StoreBuilder;
+use crate::x509::store::{X509Lookup, X509StoreBuilder};
 #[cfg(any(ossl102, libressl261))]
-use crate::x509::verify::X509VerifyFlags;
+use crate::x509::verify::{
+    hostname_matches, HostnameMatcher, X509CheckFlags, X509PurposeId, X509VerifyFlags,
+};
+#[cfg(ossl111)]
+use crate::x509::X509Algorithm;
+#[cfg(not(boringssl))]
+use crate::x509::X509Attribute;
 #[cfg(ossl110)]
 use crate::x509::X509Builder;
-use crate::x509::{X509Name, X509Req, X509StoreContext, X509VerifyResult, X509};
+#[cfg(any(ossl110, not(boringssl)))]
+use crate::x509::X509Extension;
+#[cfg(ossl110)]
+use crate::x509::X509Ref;
+use crate::x509::{
+    CrlReason, X509Crl, X509Name, X509NamePrintFlags, X509Req, X509Revoked, X509StoreContext,
+    X509VerifyResult, X509,
+};
 use hex::{self, FromHex};
 
 fn pkey() -> PKey<Private> {
@@ -22,6 +53,67 @@ fn pkey() -> PKey<Private> {
     PKey::from_rsa(rsa).unwrap()
 }
 
+/// Issues a certificate for `key` named `cn`, self-signed if `issuer` is `None`.
+#[cfg(ossl110)]
+fn issue_cert(
+    cn: &str,
+    ca: bool,
+    key: &PKeyRef<Private>,
+    issuer: Option<(&X509Ref, &PKeyRef<Private>)>,
+) -> X509 {
+    issue_cert_with_extensions(cn, ca, key, issuer, vec![])
+}
+
+#[cfg(ossl110)]
+fn issue_cert_with_extensions(
+    cn: &str,
+    ca: bool,
+    key: &PKeyRef<Private>,
+    issuer: Option<(&X509Ref, &PKeyRef<Private>)>,
+    extensions: Vec<X509Extension>,
+) -> X509 {
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, cn).unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    match issuer {
+        Some((cert, _)) => builder.set_issuer_name(cert.subject_name()).unwrap(),
+        None => builder.set_issuer_name(&name).unwrap(),
+    }
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+        .unwrap();
+    builder.set_pubkey(key).unwrap();
+
+    let mut serial = BigNum::new().unwrap();
+    serial.rand(64, MsbOption::MAYBE_ZERO, false).unwrap();
+    builder
+        .set_serial_number(&serial.to_asn1_integer().unwrap())
+        .unwrap();
+
+    let mut basic_constraints = BasicConstraints::new();
+    basic_constraints.critical();
+    if ca {
+        basic_constraints.ca();
+    }
+    builder
+        .append_extension(basic_constraints.build().unwrap())
+        .unwrap();
+    for extension in extensions {
+        builder.append_extension(extension).unwrap();
+    }
+
+    let signer = issuer.map_or(key, |(_, key)| key);
+    builder.sign(signer, MessageDigest::sha256()).unwrap();
+    builder.build()
+}
+
 #[test]
 fn test_cert_loading() {
     let cert = include_bytes!("../../test/cert.pem");
@@ -152,6 +244,134 @@ fn test_subject_alt_name() {
     assert_eq!(Some("http://www.example.com"), subject_alt_names[4].uri());
 }
 
+#[test]
+fn subject_alt_name_encoded_names() {
+    let pkey = pkey();
+
+    let mut dir_name = X509Name::builder().unwrap();
+    dir_name
+        .append_entry_by_nid(Nid::COMMONNAME, "Alice")
+        .unwrap();
+    let dir_name = dir_name.build();
+    let dir_name_der = dir_name.to_der().unwrap();
+    // a user principal name, as a UTF8String
+    let mut upn = vec![0x0c, 0x11];
+    upn.extend_from_slice(b"alice@example.com");
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+        .unwrap();
+    let san = SubjectAlternativeName::new()
+        .critical()
+        .dns("example.com")
+        .uri("sip:alice@example.com")
+        .other_name_der(
+            Asn1Object::from_str("1.3.6.1.4.1.311.20.2.3").unwrap(),
+            &upn,
+        )
+        .directory_name(dir_name)
+        .registered_id(Asn1Object::from_str("1.2.3.4").unwrap())
+        .build(&builder.x509v3_context(None, None))
+        .unwrap();
+    builder.append_extension(san).unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let der = builder.build().to_der().unwrap();
+    let cert = X509::from_der(&der).unwrap();
+
+    let names = cert.subject_alt_names().unwrap();
+    assert_eq!(names.len(), 5);
+    assert_eq!(names[0].dnsname(), Some("example.com"));
+    assert_eq!(names[1].uri(), Some("sip:alice@example.com"));
+
+    let contains = |needle: &[u8]| der.windows(needle.len()).any(|w| w == needle);
+    // critical subjectAltName
+    assert!(contains(&[0x06, 0x03, 0x55, 0x1d, 0x11, 0x01, 0x01, 0xff]));
+    let mut other_name = vec![
+        0xa0, 0x21, 0x06, 0x0a, 0x2b, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x14, 0x02, 0x03, 0xa0,
+        0x13,
+    ];
+    other_name.extend_from_slice(&upn);
+    assert!(contains(&other_name));
+    let mut directory_name = vec![0xa4, dir_name_der.len() as u8];
+    directory_name.extend_from_slice(&dir_name_der);
+    assert!(contains(&directory_name));
+    assert!(contains(&[0x88, 0x03, 0x2a, 0x03, 0x04]));
+}
+
+#[test]
+#[cfg(any(ossl102, libressl261))]
+fn check_host_email_ip() {
+    let cert = include_bytes!("../../test/alt_name_cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let flags = X509CheckFlags::empty();
+
+    let matched = cert.check_host("EXAMPLE.com", flags).unwrap().unwrap();
+    assert_eq!(&**matched, "example.com");
+    assert!(cert.check_host("www.example.com", flags).unwrap().is_none());
+    assert!(cert.check_host("", flags).unwrap().is_none());
+
+    assert!(cert.check_email("test@example.com", flags).unwrap());
+    assert!(!cert.check_email("other@example.com", flags).unwrap());
+
+    assert!(cert.check_ip("127.0.0.1".parse().unwrap(), flags).unwrap());
+    assert!(cert.check_ip("::1".parse().unwrap(), flags).unwrap());
+    assert!(!cert.check_ip("127.0.0.2".parse().unwrap(), flags).unwrap());
+    assert!(cert.check_ip_asc("127.0.0.1", flags).unwrap());
+    assert!(cert.check_ip_asc("0:0::1", flags).unwrap());
+    assert!(!cert.check_ip_asc("127.0.0.2", flags).unwrap());
+    assert!(cert.check_ip_asc("not an address", flags).is_err());
+    assert!(cert.check_ip_asc("127.0.0.1\0", flags).is_err());
+
+    let pkey = pkey();
+    let mut builder = X509::builder().unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    let san = SubjectAlternativeName::new()
+        .dns("*.example.com")
+        .build(&builder.x509v3_context(None, None))
+        .unwrap();
+    builder.append_extension(san).unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let cert = builder.build();
+
+    let matched = cert.check_host("www.example.com", flags).unwrap().unwrap();
+    assert_eq!(&**matched, "*.example.com");
+    assert!(cert
+        .check_host("www.example.com", X509CheckFlags::NO_WILDCARDS)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+#[cfg(any(ossl102, libressl261))]
+fn hostname_matcher() {
+    let flags = X509CheckFlags::empty();
+    let matcher = HostnameMatcher::new(["example.com", "*.example.org"]).unwrap();
+
+    let matched = matcher.matches("www.example.org", flags).unwrap().unwrap();
+    assert_eq!(&**matched, "*.example.org");
+    let matched = matcher.matches("EXAMPLE.com", flags).unwrap().unwrap();
+    assert_eq!(&**matched, "example.com");
+    assert!(matcher.matches("a.b.example.org", flags).unwrap().is_none());
+    assert!(matcher
+        .matches("www.example.org", X509CheckFlags::NO_WILDCARDS)
+        .unwrap()
+        .is_none());
+
+    assert!(hostname_matches("f*.example.com", "foo.example.com", flags).unwrap());
+    assert!(!hostname_matches(
+        "f*.example.com",
+        "foo.example.com",
+        X509CheckFlags::NO_PARTIAL_WILDCARDS
+    )
+    .unwrap());
+    assert!(!hostname_matches("*.example.com", "example.com", flags).unwrap());
+}
+
 #[test]
 fn test_subject_alt_name_iter() {
     let cert = include_bytes!("../../test/alt_name_cert.pem");
@@ -273,6 +493,361 @@ fn x509_builder() {
     assert_eq!(serial, x509.serial_number().to_bn().unwrap());
 }
 
+#[test]
+fn x509_policy_and_access_extensions() {
+    let pkey = pkey();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "ca.example.com")
+        .unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+        .unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+
+    let sia = SubjectInformationAccess::new()
+        .ca_repository("http://example.com/repo")
+        .time_stamping("http://example.com/tsa")
+        .build()
+        .unwrap();
+    builder.append_extension(sia).unwrap();
+    let freshest_crl = FreshestCrl::new()
+        .uri("http://example.com/delta.crl")
+        .build()
+        .unwrap();
+    builder.append_extension(freshest_crl).unwrap();
+    let inhibit_any_policy = InhibitAnyPolicy::new(2).critical().build().unwrap();
+    builder.append_extension(inhibit_any_policy).unwrap();
+    let policy_constraints = PolicyConstraints::new()
+        .critical()
+        .require_explicit_policy(0)
+        .inhibit_policy_mapping(3)
+        .build()
+        .unwrap();
+    builder.append_extension(policy_constraints).unwrap();
+
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let x509 = builder.build();
+
+    let sia = x509.subject_info_access().unwrap();
+    assert_eq!(sia.len(), 2);
+    assert_eq!(sia[0].method().nid(), Nid::CAREPOSITORY);
+    assert_eq!(sia[0].location().uri(), Some("http://example.com/repo"));
+    assert_eq!(sia[1].method().nid(), Nid::AD_TIMESTAMPING);
+    assert_eq!(sia[1].location().uri(), Some("http://example.com/tsa"));
+
+    let freshest_crl = x509.freshest_crl().unwrap();
+    assert_eq!(freshest_crl.len(), 1);
+    let dp = &freshest_crl[0];
+    assert!(dp.crl_issuer().is_none());
+    let fullname = dp.distpoint().unwrap().fullname().unwrap();
+    assert_eq!(fullname[0].uri(), Some("http://example.com/delta.crl"));
+
+    let skip_certs = x509.inhibit_any_policy().unwrap();
+    assert_eq!(skip_certs.to_bn().unwrap(), BigNum::from_u32(2).unwrap());
+
+    let constraints = x509.policy_constraints().unwrap();
+    let require = constraints.require_explicit_policy().unwrap();
+    assert_eq!(require.to_bn().unwrap(), BigNum::from_u32(0).unwrap());
+    let inhibit = constraints.inhibit_policy_mapping().unwrap();
+    assert_eq!(inhibit.to_bn().unwrap(), BigNum::from_u32(3).unwrap());
+
+    let x509 = X509::from_pem(&x509.to_pem().unwrap()).unwrap();
+    assert!(x509.authority_info().is_none());
+    assert!(x509.subject_info_access().is_some());
+}
+
+#[test]
+#[cfg(not(boringssl))]
+fn x509_standard_extension_accessors() {
+    let pkey = pkey();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "ca.example.com")
+        .unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+        .unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+
+    let basic_constraints = BasicConstraints::new()
+        .critical()
+        .ca()
+        .pathlen(1)
+        .build()
+        .unwrap();
+    builder.append_extension(basic_constraints).unwrap();
+    let ext_key_usage = ExtendedKeyUsage::new()
+        .server_auth()
+        .client_auth()
+        .build()
+        .unwrap();
+    builder.append_extension(ext_key_usage).unwrap();
+    let crl_dp = X509Extension::new_nid(
+        None,
+        None,
+        Nid::CRL_DISTRIBUTION_POINTS,
+        "URI:http://example.com/ca.crl",
+    )
+    .unwrap();
+    builder.append_extension(crl_dp).unwrap();
+    // policy 1.2.3.4 with a CPS pointer to http://example.com/cps
+    let oid = Asn1Object::from_str("2.5.29.32").unwrap();
+    let policies = X509Extension::new_from_der(
+        &oid,
+        false,
+        b"\x30\x2d\x30\x2b\x06\x03\x2a\x03\x04\x30\x24\x30\x22\x06\x08\x2b\x06\x01\x05\x05\x07\x02\x01\x16\x16http://example.com/cps",
+    )
+    .unwrap();
+    builder.append_extension(policies).unwrap();
+
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let x509 = X509::from_pem(&builder.build().to_pem().unwrap()).unwrap();
+
+    let constraints = x509.basic_constraints().unwrap();
+    assert!(constraints.ca());
+    let pathlen = constraints.pathlen().unwrap();
+    assert_eq!(pathlen.to_bn().unwrap(), BigNum::from_u32(1).unwrap());
+
+    let ext_key_usage = x509.extended_key_usage().unwrap();
+    let purposes = ext_key_usage.iter().map(|o| o.nid()).collect::<Vec<_>>();
+    assert_eq!(purposes, vec![Nid::SERVER_AUTH, Nid::CLIENT_AUTH]);
+
+    let dps = x509.crl_distribution_points().unwrap();
+    assert_eq!(dps.len(), 1);
+    let fullname = dps[0].distpoint().unwrap().fullname().unwrap();
+    assert_eq!(fullname[0].uri(), Some("http://example.com/ca.crl"));
+
+    let policies = x509.certificate_policies().unwrap();
+    assert_eq!(policies.len(), 1);
+    assert_eq!(
+        policies[0].policy().to_der().unwrap(),
+        b"\x06\x03\x2a\x03\x04"
+    );
+    let qualifiers = policies[0].qualifiers().unwrap();
+    assert_eq!(qualifiers.len(), 1);
+    assert_eq!(qualifiers[0].id().nid(), Nid::ID_QT_CPS);
+    assert_eq!(qualifiers[0].cps_uri(), Some("http://example.com/cps"));
+
+    let x509 = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    assert!(x509.basic_constraints().is_none());
+    assert!(x509.certificate_policies().is_none());
+}
+
+#[test]
+fn x509_name_modification() {
+    let mut builder = X509Name::builder().unwrap();
+    builder.append_entry_by_nid(Nid::COUNTRYNAME, "US").unwrap();
+    builder
+        .append_entry_by_nid(Nid::ORGANIZATIONNAME, "Example")
+        .unwrap();
+    builder
+        .append_entry_by_nid(Nid::PKCS9_EMAILADDRESS, "a@example.com")
+        .unwrap();
+    builder.append_entry_by_nid(Nid::COMMONNAME, "foo").unwrap();
+    builder
+        .append_entry_by_nid(Nid::PKCS9_EMAILADDRESS, "b@example.com")
+        .unwrap();
+    let name = builder.build();
+
+    let copy = name.to_owned().unwrap();
+    assert_eq!(copy.to_der().unwrap(), name.to_der().unwrap());
+
+    let mut builder = X509Name::builder_from(&name).unwrap();
+    assert_eq!(builder.remove_entries_by_nid(Nid::PKCS9_EMAILADDRESS), 2);
+    assert_eq!(builder.remove_entries_by_nid(Nid::PKCS9_EMAILADDRESS), 0);
+    let removed = builder.remove_entry(0).unwrap();
+    assert_eq!(removed.object().nid(), Nid::COUNTRYNAME);
+    assert!(builder.remove_entry(2).is_none());
+    let modified = builder.build();
+    assert_eq!(modified.entry_count(), 2);
+    assert_eq!(name.entry_count(), 5);
+
+    let mut expected = X509Name::builder().unwrap();
+    expected
+        .append_entry_by_nid(Nid::ORGANIZATIONNAME, "Example")
+        .unwrap();
+    expected
+        .append_entry_by_nid(Nid::COMMONNAME, "foo")
+        .unwrap();
+    let expected = expected.build();
+    assert_eq!(modified.to_der().unwrap(), expected.to_der().unwrap());
+}
+
+#[test]
+#[cfg(any(ossl110, libressl270, boringssl))]
+fn x509_name_replace_entries() {
+    let mut builder = X509Name::builder().unwrap();
+    builder.append_entry_by_nid(Nid::COUNTRYNAME, "US").unwrap();
+    builder
+        .append_entry_by_nid(Nid::ORGANIZATIONNAME, "Example")
+        .unwrap();
+    builder.append_entry_by_nid(Nid::COMMONNAME, "foo").unwrap();
+    let name = builder.build();
+
+    let mut builder = X509Name::builder_from(&name).unwrap();
+    assert_eq!(
+        builder
+            .replace_entries_by_nid(Nid::ORGANIZATIONNAME, "Other")
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        builder
+            .replace_entries_by_nid(Nid::ORGANIZATIONALUNITNAME, "Unit")
+            .unwrap(),
+        0
+    );
+    assert!(builder
+        .replace_entries_by_nid(Nid::COUNTRYNAME, "too long")
+        .is_err());
+    let modified = builder.build();
+
+    let mut expected = X509Name::builder().unwrap();
+    expected
+        .append_entry_by_nid(Nid::COUNTRYNAME, "US")
+        .unwrap();
+    expected
+        .append_entry_by_nid(Nid::ORGANIZATIONNAME, "Other")
+        .unwrap();
+    expected
+        .append_entry_by_nid(Nid::COMMONNAME, "foo")
+        .unwrap();
+    let expected = expected.build();
+    assert_eq!(modified.to_der().unwrap(), expected.to_der().unwrap());
+
+    // C=US, CN=foo+O=Org
+    let name = X509Name::from_der(
+        &Vec::from_hex(
+            "3027310b30090603550406130255533118300a06035504030c03666f6f300a060355040a0c034f7267",
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let mut builder = X509Name::builder_from(&name).unwrap();
+    builder
+        .replace_entries_by_nid(Nid::COMMONNAME, "bar")
+        .unwrap();
+    let modified = builder.build();
+    assert_eq!(
+        hex::encode(modified.to_der().unwrap()),
+        "3027310b30090603550406130255533118300a06035504030c03626172300a060355040a0c034f7267"
+    );
+}
+
+#[test]
+fn x509_name_multi_valued_rdns() {
+    let mut builder = X509Name::builder().unwrap();
+    builder
+        .append_entry_to_rdn_by_nid(Nid::COUNTRYNAME, "US")
+        .unwrap();
+    builder.append_entry_by_nid(Nid::COMMONNAME, "foo").unwrap();
+    builder.append_entry_to_rdn_by_text("O", "Org").unwrap();
+    let name = builder.build();
+
+    // C=US, CN=foo+O=Org
+    assert_eq!(
+        hex::encode(name.to_der().unwrap()),
+        "3027310b30090603550406130255533118300a06035504030c03666f6f300a060355040a0c034f7267"
+    );
+    #[cfg(any(ossl110, libressl270, boringssl))]
+    assert_eq!(
+        name.entries().map(|e| e.rdn_index()).collect::<Vec<_>>(),
+        [0, 1, 1]
+    );
+
+    assert_eq!(name.to_rfc2253().unwrap(), "O=Org+CN=foo,C=US");
+    assert_eq!(
+        name.to_string_with_flags(X509NamePrintFlags::ONELINE)
+            .unwrap(),
+        "C = US, CN = foo + O = Org"
+    );
+    assert_eq!(name.oneline().unwrap(), "/C=US/CN=foo+O=Org");
+}
+
+#[test]
+fn x509_name_insert_entries() {
+    let mut builder = X509Name::builder().unwrap();
+    builder.append_entry_by_nid(Nid::COUNTRYNAME, "US").unwrap();
+    builder.append_entry_by_nid(Nid::COMMONNAME, "foo").unwrap();
+    builder
+        .insert_entry_by_nid(1, Nid::ORGANIZATIONNAME, "Org")
+        .unwrap();
+    builder.insert_entry_by_text(0, "DC", "com").unwrap();
+    builder.insert_entry_by_text(4, "OU", "Unit").unwrap();
+    let name = builder.build();
+
+    assert_eq!(
+        name.to_rfc2253().unwrap(),
+        "OU=Unit,CN=foo,O=Org,C=US,DC=com"
+    );
+    #[cfg(any(ossl110, libressl270, boringssl))]
+    assert_eq!(
+        name.entries().map(|e| e.rdn_index()).collect::<Vec<_>>(),
+        [0, 1, 2, 3, 4]
+    );
+}
+
+#[test]
+#[cfg(not(boringssl))]
+fn x509_name_from_rfc2253() {
+    let name = X509Name::from_rfc2253("O=Org+CN=foo,C=US").unwrap();
+    assert_eq!(
+        hex::encode(name.to_der().unwrap()),
+        "3027310b30090603550406130255533118300a06035504030c03666f6f300a060355040a0c034f7267"
+    );
+
+    let name =
+        X509Name::from_rfc2253(r#"CN=Doe\, John \2B Jos\C3\A9 ,O="Org; Inc", C = US"#).unwrap();
+    let cn = name.entries_by_nid(Nid::COMMONNAME).next().unwrap();
+    assert_eq!(&**cn.data().as_utf8().unwrap(), "Doe, John + Jos\u{e9}");
+    let o = name.entries_by_nid(Nid::ORGANIZATIONNAME).next().unwrap();
+    assert_eq!(&**o.data().as_utf8().unwrap(), "Org; Inc");
+    let c = name.entries_by_nid(Nid::COUNTRYNAME).next().unwrap();
+    assert_eq!(&**c.data().as_utf8().unwrap(), "US");
+
+    let parsed = X509Name::from_rfc2253(&name.to_rfc2253().unwrap()).unwrap();
+    assert_eq!(parsed.to_der().unwrap(), name.to_der().unwrap());
+
+    // a hex-encoded IA5String
+    let name = X509Name::from_rfc2253("CN=#1603666f6f").unwrap();
+    let cn = name.entries_by_nid(Nid::COMMONNAME).next().unwrap();
+    assert_eq!(cn.data().as_slice(), b"foo");
+
+    assert_eq!(X509Name::from_rfc2253("").unwrap().entry_count(), 0);
+    for invalid in &[
+        "CN",
+        "CN=foo,",
+        "=foo",
+        "CN=\"foo",
+        "CN=\"foo\"bar",
+        "CN=foo\\q",
+        "CN=#16",
+        "CN=#3003020100",
+        "XX=foo",
+    ] {
+        assert!(X509Name::from_rfc2253(invalid).is_err(), "{}", invalid);
+    }
+}
+
 #[test]
 fn x509_req_builder() {
     let pkey = pkey();
@@ -310,34 +885,217 @@ fn x509_req_builder() {
 }
 
 #[test]
-fn test_stack_from_pem() {
-    let certs = include_bytes!("../../test/certs.pem");
-    let certs = X509::stack_from_pem(certs).unwrap();
-
-    assert_eq!(certs.len(), 2);
+#[cfg(not(boringssl))]
+fn x509_extension_new_from_der() {
+    let oid = Asn1Object::from_str("1.3.6.1.4.1.99999.1").unwrap();
+    let extension = X509Extension::new_from_der(&oid, true, b"\x0c\x05hello").unwrap();
     assert_eq!(
-        hex::encode(certs[0].digest(MessageDigest::sha1()).unwrap()),
-        "59172d9313e84459bcff27f967e79e6e9217e584"
+        extension.to_der().unwrap(),
+        b"\x30\x17\x06\x09\x2b\x06\x01\x04\x01\x86\x8d\x1f\x01\x01\x01\xff\x04\x07\x0c\x05hello"
     );
+
+    let extension = X509Extension::new_from_der(&oid, false, b"\x05\x00").unwrap();
+    let pkey = pkey();
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    let mut extensions = Stack::new().unwrap();
+    extensions.push(extension).unwrap();
+    builder.add_extensions(&extensions).unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+
+    let req = X509Req::from_der(&builder.build().to_der().unwrap()).unwrap();
+    let extensions = req.extensions().unwrap();
+    assert_eq!(extensions.len(), 1);
     assert_eq!(
-        hex::encode(certs[1].digest(MessageDigest::sha1()).unwrap()),
-        "c0cbdf7cdd03c9773e5468e1f6d2da7d5cbb1875"
+        extensions[0].to_der().unwrap(),
+        b"\x30\x0f\x06\x09\x2b\x06\x01\x04\x01\x86\x8d\x1f\x01\x04\x02\x05\x00"
     );
 }
 
 #[test]
-fn issued() {
-    let cert = include_bytes!("../../test/cert.pem");
-    let cert = X509::from_pem(cert).unwrap();
-    let ca = include_bytes!("../../test/root-ca.pem");
-    let ca = X509::from_pem(ca).unwrap();
+fn x509_req_append_extension() {
+    let pkey = pkey();
 
-    assert_eq!(ca.issued(&cert), X509VerifyResult::OK);
-    assert_ne!(cert.issued(&cert), X509VerifyResult::OK);
-}
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_version(0).unwrap();
+    builder.set_pubkey(&pkey).unwrap();
 
-#[test]
-fn signature() {
+    let mut extensions = Stack::new().unwrap();
+    extensions
+        .push(KeyUsage::new().digital_signature().build().unwrap())
+        .unwrap();
+    builder.add_extensions(&extensions).unwrap();
+    let subject_alternative_name = SubjectAlternativeName::new()
+        .dns("example.com")
+        .ip("127.0.0.1")
+        .build(&builder.x509v3_context(None))
+        .unwrap();
+    builder.append_extension(subject_alternative_name).unwrap();
+    builder
+        .append_extension(BasicConstraints::new().build().unwrap())
+        .unwrap();
+    builder.set_challenge_password("password").unwrap();
+    builder.sign(&pkey, MessageDigest::sha384()).unwrap();
+
+    let req = X509Req::from_der(&builder.build().to_der().unwrap()).unwrap();
+    assert_eq!(req.version(), 0);
+    assert_eq!(req.extensions().unwrap().len(), 3);
+    assert_eq!(req.attribute_count(), 2);
+    assert!(req.attribute_by_nid(Nid::EXT_REQ).is_some());
+    assert_eq!(req.challenge_password().unwrap().as_slice(), b"password");
+
+    let subject_alt_names = req.subject_alt_names().unwrap();
+    assert_eq!(subject_alt_names.len(), 2);
+    assert_eq!(subject_alt_names[0].dnsname(), Some("example.com"));
+    assert_eq!(subject_alt_names[1].ipaddress(), Some(&[127, 0, 0, 1][..]));
+
+    #[cfg(ossl110)]
+    assert_eq!(
+        req.signature_algorithm().object().nid(),
+        Nid::SHA384WITHRSAENCRYPTION
+    );
+    assert!(req.verify(&pkey).unwrap());
+}
+
+#[test]
+#[cfg(ossl111)]
+fn x509_external_signature() {
+    // sha256WithRSAEncryption
+    let algorithm = X509Algorithm::from_der(&[
+        0x30, 0x0d, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b, 0x05, 0x00,
+    ])
+    .unwrap();
+    let pkey = pkey();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "foobar.com")
+        .unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+        .unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder.set_signature_algorithm(&algorithm).unwrap();
+    let tbs = builder.tbs_certificate().unwrap();
+    let signature = Signer::new(MessageDigest::sha256(), &pkey)
+        .unwrap()
+        .sign_oneshot_to_vec(&tbs)
+        .unwrap();
+    let x509 = builder.build_with_signature(&signature).unwrap();
+
+    assert_eq!(
+        x509.signature_algorithm().object().nid(),
+        Nid::SHA256WITHRSAENCRYPTION
+    );
+    assert_eq!(x509.signature().as_slice(), &signature[..]);
+    assert!(x509.verify(&pkey).unwrap());
+
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    let tbs = builder.tbs_request().unwrap();
+    let signature = Signer::new(MessageDigest::sha256(), &pkey)
+        .unwrap()
+        .sign_oneshot_to_vec(&tbs)
+        .unwrap();
+    let req = builder
+        .build_with_signature(&algorithm, &signature)
+        .unwrap();
+
+    assert_eq!(
+        req.signature_algorithm().object().nid(),
+        Nid::SHA256WITHRSAENCRYPTION
+    );
+    assert!(req.verify(&pkey).unwrap());
+
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    let req = builder
+        .build_with_signature(&algorithm, &signature)
+        .unwrap();
+    assert!(!req.verify(&pkey).unwrap());
+}
+
+#[test]
+#[cfg(not(boringssl))]
+fn x509_req_attributes() {
+    let pkey = pkey();
+
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    let object = Asn1Object::from_str("1.2.3.4").unwrap();
+    let attribute = X509Attribute::new(&object, Asn1Type::UTF8STRING, b"value").unwrap();
+    builder.add_attribute(&attribute).unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+
+    let req = X509Req::from_der(&builder.build().to_der().unwrap()).unwrap();
+    assert_eq!(req.attribute_count(), 1);
+    assert!(req.challenge_password().is_none());
+    let attribute = req.attribute(0).unwrap();
+    assert_eq!(
+        attribute.object().to_der().unwrap(),
+        object.to_der().unwrap()
+    );
+    assert_eq!(attribute.value_type(0), Some(Asn1Type::UTF8STRING));
+    assert_eq!(attribute.value_string(0).unwrap().as_slice(), b"value");
+}
+
+#[test]
+#[cfg(ossl111)]
+fn x509_req_sign_ed25519() {
+    let pkey = PKey::generate_ed25519().unwrap();
+
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_version(0).unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder.sign(&pkey, MessageDigest::null()).unwrap();
+
+    let req = builder.build();
+    assert!(req.subject_alt_names().is_none());
+    assert_eq!(
+        req.signature_algorithm().object().nid(),
+        Nid::from_raw(ffi::NID_ED25519)
+    );
+    assert!(req.verify(&pkey).unwrap());
+}
+
+#[test]
+fn test_stack_from_pem() {
+    let certs = include_bytes!("../../test/certs.pem");
+    let certs = X509::stack_from_pem(certs).unwrap();
+
+    assert_eq!(certs.len(), 2);
+    assert_eq!(
+        hex::encode(certs[0].digest(MessageDigest::sha1()).unwrap()),
+        "59172d9313e84459bcff27f967e79e6e9217e584"
+    );
+    assert_eq!(
+        hex::encode(certs[1].digest(MessageDigest::sha1()).unwrap()),
+        "c0cbdf7cdd03c9773e5468e1f6d2da7d5cbb1875"
+    );
+}
+
+#[test]
+fn issued() {
+    let cert = include_bytes!("../../test/cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let ca = include_bytes!("../../test/root-ca.pem");
+    let ca = X509::from_pem(ca).unwrap();
+
+    assert_eq!(ca.issued(&cert), X509VerifyResult::OK);
+    assert_ne!(cert.issued(&cert), X509VerifyResult::OK);
+}
+
+#[test]
+fn signature() {
     let cert = include_bytes!("../../test/cert.pem");
     let cert = X509::from_pem(cert).unwrap();
     let signature = cert.signature();
@@ -385,6 +1143,152 @@ fn test_verify_cert() {
         .unwrap());
 }
 
+#[test]
+#[cfg(any(ossl102, libressl261))]
+fn test_verify_cert_hosts() {
+    let cert = include_bytes!("../../test/cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let ca = include_bytes!("../../test/root-ca.pem");
+    let ca = X509::from_pem(ca).unwrap();
+    let chain = Stack::new().unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(ca).unwrap();
+    let store = store_bldr.build();
+
+    let mut context = X509StoreContext::new().unwrap();
+    let verified = context
+        .init(&store, &cert, &chain, |c| {
+            let param = c.verify_param_mut();
+            // 2017-07-14, while the certificates are valid
+            param.set_time(1_500_000_000);
+            param.set_host("example.com")?;
+            let verified = c.verify_cert()?;
+            assert_eq!(c.error().as_raw(), ffi::X509_V_ERR_HOSTNAME_MISMATCH);
+            Ok(verified)
+        })
+        .unwrap();
+    assert!(!verified);
+
+    let verified = context
+        .init(&store, &cert, &chain, |c| {
+            let param = c.verify_param_mut();
+            param.set_time(1_500_000_000);
+            param.set_host("example.com")?;
+            param.add_host("")?;
+            param.add_host("foobar.com")?;
+            c.verify_cert()
+        })
+        .unwrap();
+    assert!(verified);
+
+    let verified = context
+        .init(&store, &cert, &chain, |c| {
+            let param = c.verify_param_mut();
+            param.set_time(1_500_000_000);
+            param.set_email("test@example.com")?;
+            c.verify_cert()
+        })
+        .unwrap();
+    assert!(!verified);
+}
+
+#[test]
+#[cfg(any(ossl102, libressl261))]
+fn test_verify_cert_at_time() {
+    let cert = include_bytes!("../../test/cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let ca = include_bytes!("../../test/root-ca.pem");
+    let ca = X509::from_pem(ca).unwrap();
+    let chain = Stack::new().unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(ca).unwrap();
+    let store = store_bldr.build();
+
+    let mut context = X509StoreContext::new().unwrap();
+    let verified = context
+        .init(&store, &cert, &chain, |c| {
+            // 2014-05-13, before the certificates were issued
+            c.verify_param_mut().set_time(1_400_000_000);
+            let verified = c.verify_cert()?;
+            assert_eq!(c.error().as_raw(), ffi::X509_V_ERR_CERT_NOT_YET_VALID);
+            Ok(verified)
+        })
+        .unwrap();
+    assert!(!verified);
+
+    let verified = context
+        .init(&store, &cert, &chain, |c| {
+            // 2017-07-14
+            let param = c.verify_param_mut();
+            param.set_time(1_500_000_000);
+            param.set_purpose(X509PurposeId::SSL_SERVER).unwrap();
+            c.verify_cert()
+        })
+        .unwrap();
+    assert!(verified);
+}
+
+#[test]
+#[cfg(any(ossl102, libressl261))]
+fn test_verify_cert_with_file_lookup() {
+    let cert = include_bytes!("../../test/cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let chain = Stack::new().unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    let lookup = store_bldr.add_lookup(X509Lookup::file()).unwrap();
+    assert_eq!(
+        lookup
+            .load_cert_file("test/root-ca.pem", SslFiletype::PEM)
+            .unwrap(),
+        1
+    );
+    let store = store_bldr.build();
+
+    let mut context = X509StoreContext::new().unwrap();
+    assert!(context
+        .init(&store, &cert, &chain, |c| {
+            // 2017-07-14, while the certificates are valid
+            c.verify_param_mut().set_time(1_500_000_000);
+            c.verify_cert()
+        })
+        .unwrap());
+}
+
+#[test]
+#[cfg(any(ossl102, libressl261))]
+fn test_verify_cert_with_hash_dir_lookup() {
+    let cert = include_bytes!("../../test/cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let ca = include_bytes!("../../test/root-ca.pem");
+    let ca = X509::from_pem(ca).unwrap();
+    let chain = Stack::new().unwrap();
+
+    let dir = tempdir::TempDir::new("hash_dir").unwrap();
+    let name = format!("{:08x}.0", ca.subject_name_hash());
+    std::fs::write(dir.path().join(name), ca.to_pem().unwrap()).unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr
+        .add_lookup(X509Lookup::hash_dir())
+        .unwrap()
+        .add_dir(dir.path().to_str().unwrap(), SslFiletype::PEM)
+        .unwrap();
+    let store = store_bldr.build();
+    assert!(store.objects().is_empty());
+
+    let mut context = X509StoreContext::new().unwrap();
+    assert!(context
+        .init(&store, &cert, &chain, |c| {
+            // 2017-07-14, while the certificates are valid
+            c.verify_param_mut().set_time(1_500_000_000);
+            c.verify_cert()
+        })
+        .unwrap());
+}
+
 #[test]
 fn test_verify_fails() {
     let cert = include_bytes!("../../test/cert.pem");
@@ -479,3 +1383,786 @@ fn test_load_subject_der() {
     ];
     X509Name::from_der(SUBJECT_DER).unwrap();
 }
+
+#[test]
+fn matches_private_key() {
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    let key = PKey::private_key_from_pem(include_bytes!("../../test/key.pem")).unwrap();
+    assert!(cert.matches_private_key(&key).unwrap());
+
+    let root = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    assert!(!root.matches_private_key(&key).unwrap());
+    assert!(!root.matches_private_key(&pkey()).unwrap());
+    #[cfg(ossl111)]
+    assert!(!root
+        .matches_private_key(&PKey::generate_ed25519().unwrap())
+        .unwrap());
+}
+
+#[test]
+fn check_chain_order() {
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    let root = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+
+    let mut chain = Stack::new().unwrap();
+    assert_eq!(cert.check_chain_order(&chain), X509VerifyResult::OK);
+    chain.push(root.clone()).unwrap();
+    assert_eq!(cert.check_chain_order(&chain), X509VerifyResult::OK);
+    chain.push(cert.clone()).unwrap();
+    assert_ne!(cert.check_chain_order(&chain), X509VerifyResult::OK);
+
+    let mut chain = Stack::new().unwrap();
+    chain.push(cert.clone()).unwrap();
+    assert_ne!(root.check_chain_order(&chain), X509VerifyResult::OK);
+}
+
+#[test]
+fn test_stack_from_der() {
+    let certs = include_bytes!("../../test/certs.pem");
+    let certs = X509::stack_from_pem(certs).unwrap();
+    let mut der = vec![];
+    for cert in &certs {
+        der.extend_from_slice(&cert.to_der().unwrap());
+    }
+
+    let parsed = X509::stack_from_der(&der).unwrap();
+    assert_eq!(parsed.len(), certs.len());
+    for (a, b) in parsed.iter().zip(&certs) {
+        assert_eq!(a.to_der().unwrap(), b.to_der().unwrap());
+    }
+
+    let parsed = X509::iter_from_der_reader(&der[..])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(parsed.len(), certs.len());
+
+    let mut reader = &der[..];
+    let first = X509::from_der_reader(&mut reader).unwrap();
+    assert_eq!(first.to_der().unwrap(), certs[0].to_der().unwrap());
+    assert_eq!(reader.len(), der.len() - first.to_der().unwrap().len());
+
+    let truncated = &der[..der.len() - 1];
+    assert!(X509::stack_from_der(truncated).is_err());
+    let mut iter = X509::iter_from_der_reader(truncated);
+    assert!(iter.next().unwrap().is_ok());
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_crl() {
+    let der = include_bytes!("../../test/crl.der");
+    let crl = X509Crl::from_der_reader(&der[..]).unwrap();
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+
+    assert_eq!(
+        crl.issuer_name().to_der().unwrap(),
+        ca.subject_name().to_der().unwrap()
+    );
+    assert!(crl.next_update().unwrap() > crl.last_update());
+    let revoked = crl.revoked().unwrap();
+    assert_eq!(revoked.len(), 1);
+    assert_eq!(
+        revoked[0].serial_number().to_bn().unwrap(),
+        cert.serial_number().to_bn().unwrap()
+    );
+    assert!(crl.verify(&ca.public_key().unwrap()).unwrap());
+    assert!(!crl.verify(&cert.public_key().unwrap()).unwrap());
+
+    assert_eq!(&crl.to_der().unwrap()[..], &der[..]);
+    let crl = X509Crl::from_pem(&crl.to_pem().unwrap()).unwrap();
+    assert_eq!(&crl.to_der().unwrap()[..], &der[..]);
+
+    let mut stream = der.to_vec();
+    stream.extend_from_slice(der);
+    let crls = X509Crl::iter_from_der_reader(&stream[..])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(crls.len(), 2);
+}
+
+#[test]
+fn test_crl_builder() {
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    let ca_key = include_bytes!("../../test/root-ca.key");
+    let ca_key = PKey::private_key_from_pem(ca_key).unwrap();
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+
+    let mut builder = X509Crl::builder().unwrap();
+    builder.set_version(1).unwrap();
+    builder.set_issuer_name(ca.subject_name()).unwrap();
+    let last_update = Asn1Time::from_str("20240101000000Z").unwrap();
+    builder.set_last_update(&last_update).unwrap();
+    let next_update = Asn1Time::from_str("20240108000000Z").unwrap();
+    builder.set_next_update(&next_update).unwrap();
+
+    let serial = BigNum::from_u32(0xffff).unwrap().to_asn1_integer().unwrap();
+    let revoked = X509Revoked::new(&serial, &last_update).unwrap();
+    assert_eq!(revoked.reason(), None);
+    builder.add_revoked(revoked).unwrap();
+
+    let mut revoked = X509Revoked::new(cert.serial_number(), &last_update).unwrap();
+    revoked.set_reason(CrlReason::SUPERSEDED).unwrap();
+    revoked.set_reason(CrlReason::KEY_COMPROMISE).unwrap();
+    let invalidity_date = Asn1Time::from_str("20231225000000Z").unwrap();
+    revoked.set_invalidity_date(&invalidity_date).unwrap();
+    builder.add_revoked(revoked).unwrap();
+
+    let crl_number = BigNum::from_u32(42).unwrap().to_asn1_integer().unwrap();
+    builder.set_crl_number(&crl_number).unwrap();
+    let authority_key_identifier = AuthorityKeyIdentifier::new()
+        .keyid(true)
+        .build(&builder.x509v3_context(&ca, None))
+        .unwrap();
+    builder.append_extension(authority_key_identifier).unwrap();
+    builder.sign(&ca_key, MessageDigest::sha256()).unwrap();
+    let crl = builder.build();
+
+    let crl = X509Crl::from_der(&crl.to_der().unwrap()).unwrap();
+    assert!(crl.verify(&ca.public_key().unwrap()).unwrap());
+    assert_eq!(
+        crl.issuer_name().to_der().unwrap(),
+        ca.subject_name().to_der().unwrap()
+    );
+    assert_eq!(crl.last_update().to_string(), last_update.to_string());
+    assert_eq!(
+        crl.next_update().unwrap().to_string(),
+        next_update.to_string()
+    );
+
+    let revoked = crl.revoked().unwrap();
+    assert_eq!(revoked.len(), 2);
+    // entries are sorted by serial number when signing
+    let entry = revoked
+        .iter()
+        .find(|r| r.serial_number().to_bn().unwrap() == cert.serial_number().to_bn().unwrap())
+        .unwrap();
+    assert_eq!(entry.reason(), Some(CrlReason::KEY_COMPROMISE));
+    assert_eq!(
+        entry.invalidity_date().unwrap().to_string(),
+        invalidity_date.to_string()
+    );
+    let entry = revoked
+        .iter()
+        .find(|r| r.serial_number().to_bn().unwrap() == BigNum::from_u32(0xffff).unwrap())
+        .unwrap();
+    assert_eq!(entry.reason(), None);
+    assert!(entry.invalidity_date().is_none());
+
+    let der = crl.to_der().unwrap();
+    // crlNumber
+    assert!(der
+        .windows(8)
+        .any(|w| w == [0x06, 0x03, 0x55, 0x1d, 0x14, 0x04, 0x03, 0x02]));
+    // authorityKeyIdentifier
+    assert!(der.windows(5).any(|w| w == [0x06, 0x03, 0x55, 0x1d, 0x23]));
+}
+
+#[test]
+#[cfg(ossl110)]
+fn test_crl_issuing_distribution_point() {
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    let ca_key = include_bytes!("../../test/root-ca.key");
+    let ca_key = PKey::private_key_from_pem(ca_key).unwrap();
+
+    let mut builder = X509Crl::builder().unwrap();
+    builder.set_version(1).unwrap();
+    builder.set_issuer_name(ca.subject_name()).unwrap();
+    builder
+        .set_last_update(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    let idp = IssuingDistributionPoint::new()
+        .critical()
+        .uri("http://example.com/ca.crl")
+        .only_user_certs()
+        .build()
+        .unwrap();
+    builder.append_extension(idp).unwrap();
+    let freshest_crl = FreshestCrl::new()
+        .uri("http://example.com/delta.crl")
+        .build()
+        .unwrap();
+    builder.append_extension(freshest_crl).unwrap();
+    builder.sign(&ca_key, MessageDigest::sha256()).unwrap();
+    let crl = builder.build();
+
+    let idp = crl.issuing_distribution_point().unwrap();
+    assert!(idp.only_contains_user_certs());
+    assert!(!idp.only_contains_ca_certs());
+    assert!(!idp.only_contains_attribute_certs());
+    assert!(!idp.indirect_crl());
+    let fullname = idp.distpoint().unwrap().fullname().unwrap();
+    assert_eq!(fullname[0].uri(), Some("http://example.com/ca.crl"));
+
+    let freshest_crl = crl.freshest_crl().unwrap();
+    let fullname = freshest_crl[0].distpoint().unwrap().fullname().unwrap();
+    assert_eq!(fullname[0].uri(), Some("http://example.com/delta.crl"));
+    assert!(crl.crl_number().is_none());
+    assert!(crl.delta_crl_indicator().is_none());
+}
+
+#[test]
+#[cfg(ossl110)]
+fn test_delta_crl() {
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    let ca_key = include_bytes!("../../test/root-ca.key");
+    let ca_key = PKey::private_key_from_pem(ca_key).unwrap();
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+
+    let build_crl = |number: u32, base: Option<u32>, revoked: Option<&X509Ref>| {
+        let mut builder = X509Crl::builder().unwrap();
+        builder.set_version(1).unwrap();
+        builder.set_issuer_name(ca.subject_name()).unwrap();
+        let now = Asn1Time::days_from_now(0).unwrap();
+        builder.set_last_update(&now).unwrap();
+        builder
+            .set_next_update(&Asn1Time::days_from_now(7).unwrap())
+            .unwrap();
+        let number = BigNum::from_u32(number).unwrap().to_asn1_integer().unwrap();
+        builder.set_crl_number(&number).unwrap();
+        match base {
+            Some(base) => {
+                let base = BigNum::from_u32(base).unwrap().to_asn1_integer().unwrap();
+                builder.set_delta_crl_indicator(&base).unwrap();
+            }
+            None => {
+                let freshest_crl = FreshestCrl::new()
+                    .uri("http://example.com/delta.crl")
+                    .build()
+                    .unwrap();
+                builder.append_extension(freshest_crl).unwrap();
+            }
+        }
+        if let Some(revoked) = revoked {
+            let revoked = X509Revoked::new(revoked.serial_number(), &now).unwrap();
+            builder.add_revoked(revoked).unwrap();
+        }
+        builder.sign(&ca_key, MessageDigest::sha256()).unwrap();
+        builder.build()
+    };
+    let base = build_crl(1, None, None);
+    let delta = build_crl(2, Some(1), Some(&cert));
+    assert!(base.delta_crl_indicator().is_none());
+    assert_eq!(
+        delta.delta_crl_indicator().unwrap().to_bn().unwrap(),
+        BigNum::from_u32(1).unwrap()
+    );
+    assert_eq!(
+        delta.crl_number().unwrap().to_bn().unwrap(),
+        BigNum::from_u32(2).unwrap()
+    );
+
+    let verify = |flags: X509VerifyFlags| {
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(ca.clone()).unwrap();
+        store.add_crl(&base).unwrap();
+        store.add_crl(&delta).unwrap();
+        store
+            .set_flags(X509VerifyFlags::CRL_CHECK | X509VerifyFlags::NO_CHECK_TIME | flags)
+            .unwrap();
+        let store = store.build();
+
+        let chain = Stack::new().unwrap();
+        let mut context = X509StoreContext::new().unwrap();
+        context
+            .init(&store, &cert, &chain, |c| {
+                c.verify_cert()?;
+                Ok(c.error())
+            })
+            .unwrap()
+    };
+
+    assert_eq!(
+        verify(X509VerifyFlags::EXTENDED_CRL_SUPPORT),
+        X509VerifyResult::OK
+    );
+    assert_eq!(
+        verify(X509VerifyFlags::EXTENDED_CRL_SUPPORT | X509VerifyFlags::USE_DELTAS).as_raw(),
+        ffi::X509_V_ERR_CERT_REVOKED
+    );
+}
+
+#[test]
+#[cfg(not(boringssl))]
+fn test_crl_index() {
+    let der = include_bytes!("../../test/crl.der");
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    let revoked = cert.serial_number().to_bn().unwrap();
+    let not_revoked = BigNum::from_u32(1).unwrap();
+
+    let index = X509CrlIndex::from_der_reader(&der[..], &ca.public_key().unwrap()).unwrap();
+    assert_eq!(index.len(), 1);
+    assert!(index.is_revoked(&revoked));
+    assert!(!index.is_revoked(&not_revoked));
+    assert_eq!(
+        index.issuer_name().to_der().unwrap(),
+        ca.subject_name().to_der().unwrap()
+    );
+    assert!(index.next_update().unwrap() > index.last_update());
+
+    let err = X509CrlIndex::from_der_reader(&der[..], &cert.public_key().unwrap()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    let err = X509CrlIndex::from_der_reader(&der[..der.len() - 1], &ca.public_key().unwrap())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    let crl = X509Crl::from_der(der).unwrap();
+    let index = X509CrlIndex::new(&crl).unwrap();
+    assert_eq!(index.len(), 1);
+    assert!(index.is_revoked(&revoked));
+    assert!(!index.is_revoked(&not_revoked));
+    assert!(index.last_update() == crl.last_update());
+}
+
+#[test]
+fn test_ca_issuers() {
+    let cert = X509::from_pem(include_bytes!("../../test/aia_test_cert.pem")).unwrap();
+    assert_eq!(cert.ca_issuers(), vec!["http://www.example.com/cert.pem"]);
+
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    assert!(cert.ca_issuers().is_empty());
+}
+
+#[test]
+#[cfg(all(ossl110, not(boringssl)))]
+fn test_cross_certificate_pair() {
+    let key_a = pkey();
+    let key_b = pkey();
+    let root_a = issue_cert("CA A", true, &key_a, None);
+    let root_b = issue_cert("CA B", true, &key_b, None);
+    let forward = issue_cert("CA A", true, &key_a, Some((&root_b, &key_b)));
+    let reverse = issue_cert("CA B", true, &key_b, Some((&root_a, &key_a)));
+
+    assert!(forward.cross_certifies(&reverse).unwrap());
+    assert!(reverse.cross_certifies(&forward).unwrap());
+    assert!(!forward.cross_certifies(&root_b).unwrap());
+    assert!(!root_a.cross_certifies(&root_b).unwrap());
+
+    let pair = X509CrossCertificatePair::new(Some(forward.clone()), Some(reverse.clone()));
+    let der = pair.to_der().unwrap();
+    let pair = X509CrossCertificatePair::from_der(&der).unwrap();
+    assert_eq!(
+        pair.forward().unwrap().to_der().unwrap(),
+        forward.to_der().unwrap()
+    );
+    assert_eq!(
+        pair.reverse().unwrap().to_der().unwrap(),
+        reverse.to_der().unwrap()
+    );
+    assert!(pair.verify().unwrap());
+
+    let pair = X509CrossCertificatePair::new(None, Some(reverse));
+    let pair = X509CrossCertificatePair::from_der(&pair.to_der().unwrap()).unwrap();
+    assert!(pair.forward().is_none());
+    assert!(pair.reverse().is_some());
+    assert!(!pair.verify().unwrap());
+
+    assert!(X509CrossCertificatePair::from_der(&forward.to_der().unwrap()).is_err());
+}
+
+#[test]
+#[cfg(ossl340)]
+fn test_acert() {
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    let ca_key = include_bytes!("../../test/root-ca.key");
+    let ca_key = PKey::private_key_from_pem(ca_key).unwrap();
+    let holder = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+
+    let acert = X509Acert::from_der(include_bytes!("../../test/acert.der")).unwrap();
+    let acert = X509Acert::from_pem(&acert.to_pem().unwrap()).unwrap();
+    assert_eq!(
+        acert.to_der().unwrap(),
+        include_bytes!("../../test/acert.der")
+    );
+    assert_eq!(acert.version(), 1);
+    let (issuer, serial_number) = acert.holder_base_certificate_id().unwrap();
+    assert_eq!(
+        issuer.to_der().unwrap(),
+        holder.issuer_name().to_der().unwrap()
+    );
+    assert_eq!(
+        *serial_number.to_bn().unwrap(),
+        *holder.serial_number().to_bn().unwrap()
+    );
+    assert!(acert.is_held_by(&holder).unwrap());
+    assert!(!acert.is_held_by(&ca).unwrap());
+    assert!(acert.holder_entity_name().is_none());
+    assert_eq!(
+        acert.issuer_name().unwrap().to_der().unwrap(),
+        ca.subject_name().to_der().unwrap()
+    );
+    assert_eq!(
+        *acert.serial_number().to_bn().unwrap(),
+        *BigNum::from_u32(0x1234).unwrap()
+    );
+    assert!(acert.not_before() == Asn1Time::from_str("20240101000000Z").unwrap());
+    assert!(acert.not_after() == Asn1Time::from_str("20340101000000Z").unwrap());
+    assert_eq!(acert.attribute_count(), 1);
+    let attribute = acert.attribute(0).unwrap();
+    assert_eq!(attribute.object().nid(), Nid::ROLE);
+    assert_eq!(attribute.value_string(0).unwrap().as_slice(), b"operator");
+    assert!(acert.attribute(1).is_none());
+    assert_eq!(acert.extensions().unwrap().len(), 1);
+    assert_eq!(
+        acert.signature_algorithm().object().nid(),
+        Nid::SHA256WITHRSAENCRYPTION
+    );
+    assert!(acert.verify(&ca_key).unwrap());
+    assert!(!acert.verify(&pkey()).unwrap());
+
+    let acert = X509Acert::from_der(include_bytes!("../../test/acert-pss.der")).unwrap();
+    assert!(acert.verify(&ca_key).unwrap());
+
+    let mut builder = X509Acert::builder().unwrap();
+    builder.set_holder_certificate(&holder).unwrap();
+    builder.set_holder_name(holder.subject_name()).unwrap();
+    builder.set_issuer_name(ca.subject_name()).unwrap();
+    let serial = BigNum::from_u32(0x8002).unwrap().to_asn1_integer().unwrap();
+    builder.set_serial_number(&serial).unwrap();
+    builder
+        .set_not_before(&Asn1Time::from_str("20240101000000Z").unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(30).unwrap())
+        .unwrap();
+    let role_type = Asn1Object::from_str("2.5.4.72").unwrap();
+    let role = X509Attribute::new(&role_type, Asn1Type::UTF8STRING, b"auditor").unwrap();
+    builder.append_attribute(&role).unwrap();
+    builder.sign(&ca_key, MessageDigest::sha256()).unwrap();
+    let acert = builder.build();
+
+    let names = acert.holder_entity_name().unwrap();
+    assert_eq!(names.len(), 1);
+    assert_eq!(
+        names[0].directory_name().unwrap().to_der().unwrap(),
+        holder.subject_name().to_der().unwrap()
+    );
+    assert!(acert.is_held_by(&holder).unwrap());
+    assert!(acert.verify(&ca_key).unwrap());
+
+    let acert = X509Acert::from_der(&acert.to_der().unwrap()).unwrap();
+    assert_eq!(
+        *acert.serial_number().to_bn().unwrap(),
+        *BigNum::from_u32(0x8002).unwrap()
+    );
+    assert!(acert.verify(&ca_key).unwrap());
+
+    assert!(X509Acert::from_der(&holder.to_der().unwrap()).is_err());
+}
+
+#[test]
+#[cfg(ossl110)]
+fn test_certificate_authority() {
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "root").unwrap();
+    let root = CertificateAuthority::self_signed(&name.build(), pkey(), 30).unwrap();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "intermediate")
+        .unwrap();
+    let intermediate_key = pkey();
+    let intermediate = root
+        .issue(
+            IssuanceProfile::intermediate_ca().path_length(0),
+            &name.build(),
+            &intermediate_key,
+        )
+        .unwrap();
+    assert!(intermediate.not_after() == root.certificate().not_after());
+    let intermediate = CertificateAuthority::new(intermediate, intermediate_key);
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "example.com")
+        .unwrap();
+    let leaf = intermediate
+        .issue(
+            IssuanceProfile::tls_server()
+                .validity_days(7)
+                .dns("example.com")
+                .ip("127.0.0.1"),
+            &name.build(),
+            &pkey(),
+        )
+        .unwrap();
+    assert!(leaf.not_after() < root.certificate().not_after());
+    assert_ne!(
+        leaf.serial_number().to_bn().unwrap(),
+        intermediate.certificate().serial_number().to_bn().unwrap()
+    );
+    let subject_alt_names = leaf.subject_alt_names().unwrap();
+    assert_eq!(subject_alt_names.len(), 2);
+    assert_eq!(subject_alt_names[0].dnsname(), Some("example.com"));
+    let extended_key_usage = leaf.extended_key_usage().unwrap();
+    assert_eq!(extended_key_usage.len(), 1);
+    assert_eq!(extended_key_usage[0].nid(), Nid::SERVER_AUTH);
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(root.certificate().to_owned()).unwrap();
+    let store = store_bldr.build();
+    let mut untrusted = Stack::new().unwrap();
+    untrusted
+        .push(intermediate.certificate().to_owned())
+        .unwrap();
+    let mut context = X509StoreContext::new().unwrap();
+    assert!(context
+        .init(&store, &leaf, &untrusted, |c| c.verify_cert())
+        .unwrap());
+}
+
+#[test]
+#[cfg(ossl110)]
+fn test_verify_cert_with_untrusted() {
+    let root_key = pkey();
+    let root = issue_cert("root", true, &root_key, None);
+    let intermediate_key = pkey();
+    let intermediate = issue_cert(
+        "intermediate",
+        true,
+        &intermediate_key,
+        Some((&root, &root_key)),
+    );
+    let leaf_key = pkey();
+    let leaf = issue_cert(
+        "leaf",
+        false,
+        &leaf_key,
+        Some((&intermediate, &intermediate_key)),
+    );
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(root).unwrap();
+    let store = store_bldr.build();
+
+    let chain = Stack::new().unwrap();
+    let mut context = X509StoreContext::new().unwrap();
+    assert!(!context
+        .init(&store, &leaf, &chain, |c| c.verify_cert())
+        .unwrap());
+
+    let mut untrusted = Stack::new().unwrap();
+    untrusted.push(intermediate).unwrap();
+    let verified = context
+        .init(&store, &leaf, &chain, |c| {
+            assert_eq!(c.cert().unwrap().to_der()?, leaf.to_der()?);
+            assert_eq!(c.untrusted().unwrap().len(), 0);
+            let ok = c.verify_cert_with_untrusted(&untrusted)?;
+            assert_eq!(c.chain().unwrap().len(), 3);
+            assert_eq!(c.untrusted().unwrap().len(), 0);
+            Ok(ok)
+        })
+        .unwrap();
+    assert!(verified);
+}
+
+#[test]
+#[cfg(ossl110)]
+fn test_policy_tree() {
+    let conf = Conf::new(ConfMethod::default()).unwrap();
+    let policies = || {
+        let builder = X509Builder::new().unwrap();
+        let context = builder.x509v3_context(None, Some(&conf));
+        X509Extension::new_nid(
+            Some(&conf),
+            Some(&context),
+            Nid::CERTIFICATE_POLICIES,
+            "1.2.3.4",
+        )
+        .unwrap()
+    };
+
+    let root_key = pkey();
+    let root = issue_cert("root", true, &root_key, None);
+    let intermediate_key = pkey();
+    let intermediate = issue_cert_with_extensions(
+        "intermediate",
+        true,
+        &intermediate_key,
+        Some((&root, &root_key)),
+        vec![policies()],
+    );
+    let leaf_key = pkey();
+    let leaf = issue_cert_with_extensions(
+        "leaf",
+        false,
+        &leaf_key,
+        Some((&intermediate, &intermediate_key)),
+        vec![policies()],
+    );
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(root).unwrap();
+    store_bldr.set_flags(X509VerifyFlags::POLICY_CHECK).unwrap();
+    let store = store_bldr.build();
+
+    let mut chain = Stack::new().unwrap();
+    chain.push(intermediate).unwrap();
+
+    // compare by encoding since another test may give 1.2.3.4 a name
+    let policy = Asn1Object::from_str("1.2.3.4").unwrap().to_der().unwrap();
+    let mut context = X509StoreContext::new().unwrap();
+    context
+        .init(&store, &leaf, &chain, |c| {
+            assert!(c.verify_cert()?);
+            assert!(!c.explicit_policy());
+
+            let tree = c.policy_tree().unwrap();
+            assert_eq!(tree.level_count(), 3);
+            assert!(tree.level(3).is_none());
+
+            let authority = tree.authority_policies();
+            assert_eq!(authority.len(), 1);
+            assert_eq!(authority[0].policy().to_der()?, policy);
+            assert!(tree.user_policies().is_empty());
+
+            let level = tree.level(2).unwrap();
+            assert_eq!(level.node_count(), 1);
+            let node = level.node(0).unwrap();
+            assert_eq!(node.policy().to_der()?, policy);
+            let parent = node.parent().unwrap();
+            assert_eq!(parent.policy().to_der()?, policy);
+            assert_eq!(parent.parent().unwrap().policy().nid(), Nid::ANY_POLICY);
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+#[cfg(all(ossl110, not(boringssl)))]
+fn test_policy_validation() {
+    let conf = Conf::new(ConfMethod::default()).unwrap();
+    let policies = || {
+        let builder = X509Builder::new().unwrap();
+        let context = builder.x509v3_context(None, Some(&conf));
+        X509Extension::new_nid(
+            Some(&conf),
+            Some(&context),
+            Nid::CERTIFICATE_POLICIES,
+            "1.2.3.4",
+        )
+        .unwrap()
+    };
+
+    let root_key = pkey();
+    let root = issue_cert("root", true, &root_key, None);
+    let leaf_key = pkey();
+    let leaf = issue_cert_with_extensions(
+        "leaf",
+        false,
+        &leaf_key,
+        Some((&root, &root_key)),
+        vec![policies()],
+    );
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(root).unwrap();
+    let store = store_bldr.build();
+
+    let chain = Stack::new().unwrap();
+    let mut context = X509StoreContext::new().unwrap();
+    let verified = context
+        .init(&store, &leaf, &chain, |c| {
+            let param = c.verify_param_mut();
+            param.set_flags(X509VerifyFlags::EXPLICIT_POLICY)?;
+            param.add_policy(Asn1Object::from_str("1.2.3.5")?)?;
+            c.verify_cert()
+        })
+        .unwrap();
+    assert!(!verified);
+
+    let mut acceptable = Stack::new().unwrap();
+    acceptable
+        .push(Asn1Object::from_str("1.2.3.5").unwrap())
+        .unwrap();
+    acceptable
+        .push(Asn1Object::from_str("1.2.3.4").unwrap())
+        .unwrap();
+    context
+        .init(&store, &leaf, &chain, |c| {
+            let param = c.verify_param_mut();
+            param.set_flags(X509VerifyFlags::EXPLICIT_POLICY)?;
+            param.set_policies(&acceptable)?;
+            assert!(c.verify_cert()?);
+            assert!(c.explicit_policy());
+
+            let user = c.policy_tree().unwrap().user_policies();
+            assert_eq!(user.len(), 1);
+            assert_eq!(user[0].policy().to_der()?, acceptable[1].to_der()?);
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+#[cfg(ossl110)]
+fn name_constraints() {
+    let mut dir_name = X509Name::builder().unwrap();
+    dir_name
+        .append_entry_by_nid(Nid::ORGANIZATIONNAME, "Example")
+        .unwrap();
+    let constraints = NameConstraints::new()
+        .critical()
+        .permitted_dns("example.com")
+        .excluded_dns("internal.example.com")
+        .permitted_email(".example.com")
+        .permitted_ip("10.0.0.0".parse().unwrap(), 8)
+        .permitted_ip("2001:db8::".parse().unwrap(), 32)
+        .excluded_ip("2001:db8:1::".parse().unwrap(), 48)
+        .excluded_dir_name(dir_name.build())
+        .build()
+        .unwrap();
+
+    let root_key = pkey();
+    let root = issue_cert("root", true, &root_key, None);
+    let intermediate_key = pkey();
+    let intermediate = issue_cert_with_extensions(
+        "intermediate",
+        true,
+        &intermediate_key,
+        Some((&root, &root_key)),
+        vec![constraints],
+    );
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(root).unwrap();
+    let store = store_bldr.build();
+    let mut chain = Stack::new().unwrap();
+    chain.push(intermediate.clone()).unwrap();
+
+    let verify = |san: &str| {
+        let leaf_key = pkey();
+        let san = X509Extension::new_nid(None, None, Nid::SUBJECT_ALT_NAME, san).unwrap();
+        let leaf = issue_cert_with_extensions(
+            "leaf",
+            false,
+            &leaf_key,
+            Some((&intermediate, &intermediate_key)),
+            vec![san],
+        );
+        let mut context = X509StoreContext::new().unwrap();
+        context
+            .init(&store, &leaf, &chain, |c| {
+                c.verify_cert()?;
+                Ok(c.error().as_raw())
+            })
+            .unwrap()
+    };
+
+    assert_eq!(verify("DNS:www.example.com"), ffi::X509_V_OK);
+    assert_eq!(verify("IP:10.1.2.3"), ffi::X509_V_OK);
+    assert_eq!(
+        verify("DNS:www.example.org"),
+        ffi::X509_V_ERR_PERMITTED_VIOLATION
+    );
+    assert_eq!(
+        verify("DNS:www.internal.example.com"),
+        ffi::X509_V_ERR_EXCLUDED_VIOLATION
+    );
+    assert_eq!(verify("IP:2001:db8:2::1"), ffi::X509_V_OK);
+    assert_eq!(verify("IP:::1"), ffi::X509_V_ERR_PERMITTED_VIOLATION);
+    assert_eq!(
+        verify("IP:2001:db8:1::1"),
+        ffi::X509_V_ERR_EXCLUDED_VIOLATION
+    );
+}
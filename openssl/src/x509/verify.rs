@@ -1,10 +1,21 @@
 use bitflags::bitflags;
-use foreign_types::ForeignTypeRef;
-use libc::{c_uint, c_ulong};
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::{c_int, c_uint, c_ulong, time_t};
+#[cfg(any(ossl102, libressl261))]
+use std::fmt;
+use std::mem;
 use std::net::IpAddr;
 
+#[cfg(any(ossl102, libressl261))]
+use crate::asn1::put_object;
+use crate::asn1::Asn1Object;
 use crate::cvt;
 use crate::error::ErrorStack;
+use crate::stack::StackRef;
+#[cfg(any(ossl102, libressl261))]
+use crate::string::OpensslString;
+#[cfg(any(ossl102, libressl261))]
+use crate::x509::{X509Extension, X509};
 use openssl_macros::corresponds;
 
 bitflags! {
@@ -59,6 +70,32 @@ bitflags! {
     }
 }
 
+/// The purpose for which a certificate is checked during verification.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct X509PurposeId(c_int);
+
+impl X509PurposeId {
+    pub const SSL_CLIENT: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_SSL_CLIENT);
+    pub const SSL_SERVER: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_SSL_SERVER);
+    pub const NS_SSL_SERVER: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_NS_SSL_SERVER);
+    pub const SMIME_SIGN: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_SMIME_SIGN);
+    pub const SMIME_ENCRYPT: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_SMIME_ENCRYPT);
+    pub const CRL_SIGN: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_CRL_SIGN);
+    pub const ANY: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_ANY);
+    pub const OCSP_HELPER: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_OCSP_HELPER);
+    pub const TIMESTAMP_SIGN: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_TIMESTAMP_SIGN);
+
+    /// Constructs an `X509PurposeId` from a raw OpenSSL value.
+    pub fn from_raw(id: c_int) -> X509PurposeId {
+        X509PurposeId(id)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::X509_VERIFY_PARAM;
     fn drop = ffi::X509_VERIFY_PARAM_free;
@@ -107,15 +144,52 @@ impl X509VerifyParamRef {
     #[corresponds(X509_VERIFY_PARAM_set1_host)]
     pub fn set_host(&mut self, host: &str) -> Result<(), ErrorStack> {
         unsafe {
+            // len == 0 means "run strlen" :(
+            let raw_host = if host.is_empty() { "\0" } else { host };
             cvt(ffi::X509_VERIFY_PARAM_set1_host(
                 self.as_ptr(),
-                host.as_ptr() as *const _,
+                raw_host.as_ptr() as *const _,
+                host.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds a DNS hostname to the set of expected names.
+    ///
+    /// The certificate is accepted if it is valid for any of the names passed to this method or
+    /// [`set_host`], which replaces the set with a single name.
+    ///
+    /// [`set_host`]: X509VerifyParamRef::set_host
+    #[corresponds(X509_VERIFY_PARAM_add1_host)]
+    pub fn add_host(&mut self, host: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            // len == 0 means "run strlen" :(
+            let raw_host = if host.is_empty() { "\0" } else { host };
+            cvt(ffi::X509_VERIFY_PARAM_add1_host(
+                self.as_ptr(),
+                raw_host.as_ptr() as *const _,
                 host.len(),
             ))
             .map(|_| ())
         }
     }
 
+    /// Set the expected email address.
+    #[corresponds(X509_VERIFY_PARAM_set1_email)]
+    pub fn set_email(&mut self, email: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            // len == 0 means "run strlen" :(
+            let raw_email = if email.is_empty() { "\0" } else { email };
+            cvt(ffi::X509_VERIFY_PARAM_set1_email(
+                self.as_ptr(),
+                raw_email.as_ptr() as *const _,
+                email.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Set the expected IPv4 or IPv6 address.
     #[corresponds(X509_VERIFY_PARAM_set1_ip)]
     pub fn set_ip(&mut self, ip: IpAddr) -> Result<(), ErrorStack> {
@@ -139,4 +213,154 @@ impl X509VerifyParamRef {
             .map(|_| ())
         }
     }
+
+    /// Sets the time at which the certificate chain is checked, instead of the current time.
+    ///
+    /// This also sets `X509VerifyFlags::USE_CHECK_TIME`.
+    #[corresponds(X509_VERIFY_PARAM_set_time)]
+    pub fn set_time(&mut self, time: time_t) {
+        unsafe { ffi::X509_VERIFY_PARAM_set_time(self.as_ptr(), time) }
+    }
+
+    /// Sets the maximum number of intermediate certificates which may appear in the chain.
+    #[corresponds(X509_VERIFY_PARAM_set_depth)]
+    pub fn set_depth(&mut self, depth: c_int) {
+        unsafe { ffi::X509_VERIFY_PARAM_set_depth(self.as_ptr(), depth) }
+    }
+
+    /// Sets the purpose for which the leaf certificate must be acceptable.
+    #[corresponds(X509_VERIFY_PARAM_set_purpose)]
+    pub fn set_purpose(&mut self, purpose: X509PurposeId) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set_purpose(
+                self.as_ptr(),
+                purpose.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds a policy to the set of acceptable policies.
+    ///
+    /// Policies are only checked if policy checking is enabled, for example with
+    /// `X509VerifyFlags::POLICY_CHECK` or `X509VerifyFlags::EXPLICIT_POLICY`.
+    #[corresponds(X509_VERIFY_PARAM_add0_policy)]
+    pub fn add_policy(&mut self, policy: Asn1Object) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_add0_policy(
+                self.as_ptr(),
+                policy.as_ptr(),
+            ))?;
+            mem::forget(policy);
+            Ok(())
+        }
+    }
+
+    /// Sets the set of acceptable policies, replacing any previously configured.
+    ///
+    /// Policies are only checked if policy checking is enabled, for example with
+    /// `X509VerifyFlags::POLICY_CHECK` or `X509VerifyFlags::EXPLICIT_POLICY`.
+    #[corresponds(X509_VERIFY_PARAM_set1_policies)]
+    pub fn set_policies(&mut self, policies: &StackRef<Asn1Object>) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set1_policies(
+                self.as_ptr(),
+                policies.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+}
+
+/// Matches hostnames against a set of DNS name patterns.
+///
+/// Patterns are matched with the same rules OpenSSL applies to the `dNSName` subject alternative
+/// names of a certificate during verification, including its handling of wildcards, so this can
+/// be used to check ahead of time whether a certificate with the given names would be accepted
+/// for a hostname.
+///
+/// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+#[cfg(any(ossl102, libressl261))]
+pub struct HostnameMatcher {
+    cert: X509,
+}
+
+#[cfg(any(ossl102, libressl261))]
+impl HostnameMatcher {
+    /// Creates a matcher for the DNS name patterns `patterns`, such as `example.com` or
+    /// `*.example.com`.
+    pub fn new<I, S>(patterns: I) -> Result<HostnameMatcher, ErrorStack>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        // GeneralNames ::= SEQUENCE OF GeneralName, with each name as a [2] IMPLICIT IA5String
+        let mut names = vec![];
+        for pattern in patterns {
+            put_object(
+                &mut names,
+                2,
+                ffi::V_ASN1_CONTEXT_SPECIFIC,
+                false,
+                pattern.as_ref().as_bytes(),
+            );
+        }
+        let mut general_names = vec![];
+        put_object(
+            &mut general_names,
+            ffi::V_ASN1_SEQUENCE,
+            ffi::V_ASN1_UNIVERSAL,
+            true,
+            &names,
+        );
+
+        let oid = Asn1Object::from_str("2.5.29.17")?;
+        let extension = X509Extension::new_from_der(&oid, false, &general_names)?;
+        let mut builder = X509::builder()?;
+        builder.append_extension(extension)?;
+        Ok(HostnameMatcher {
+            cert: builder.build(),
+        })
+    }
+
+    /// Returns the pattern which matches `hostname`, if any.
+    #[corresponds(X509_check_host)]
+    pub fn matches(
+        &self,
+        hostname: &str,
+        flags: X509CheckFlags,
+    ) -> Result<Option<OpensslString>, ErrorStack> {
+        self.cert.check_host(hostname, flags)
+    }
+}
+
+#[cfg(any(ossl102, libressl261))]
+impl fmt::Debug for HostnameMatcher {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = self.cert.subject_alt_names();
+        let patterns = names
+            .iter()
+            .flat_map(|names| names.iter())
+            .filter_map(|name| name.dnsname())
+            .collect::<Vec<_>>();
+        fmt.debug_struct("HostnameMatcher")
+            .field("patterns", &patterns)
+            .finish()
+    }
+}
+
+/// Determines if the DNS name pattern `pattern` matches `hostname`.
+///
+/// See [`HostnameMatcher`] for details.
+///
+/// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+#[cfg(any(ossl102, libressl261))]
+pub fn hostname_matches(
+    pattern: &str,
+    hostname: &str,
+    flags: X509CheckFlags,
+) -> Result<bool, ErrorStack> {
+    HostnameMatcher::new(&[pattern])?
+        .matches(hostname, flags)
+        .map(|m| m.is_some())
 }
@@ -67,10 +67,23 @@ fn main() {
         cfg.header("openssl/cms.h");
         if version >= 0x10100000 {
             cfg.header("openssl/kdf.h");
+            cfg.header("openssl/srp.h");
+            cfg.header("openssl/ts.h");
         }
 
         if version >= 0x30000000 {
             cfg.header("openssl/provider.h");
+            cfg.header("openssl/cmp.h");
+            cfg.header("openssl/crmf.h");
+            cfg.header("openssl/http.h");
+        }
+
+        if version >= 0x30200000 {
+            cfg.header("openssl/quic.h");
+        }
+
+        if version >= 0x30400000 {
+            cfg.header("openssl/x509_acert.h");
         }
     }
 
@@ -84,6 +97,8 @@ fn main() {
             "bio_info_cb*".to_string()
         } else if s == "_STACK" {
             "struct stack_st".to_string()
+        } else if s == "timeval" {
+            "struct timeval".to_string()
         // This logic should really be cleaned up
         } else if is_struct
             && s != "point_conversion_form_t"
@@ -105,7 +120,11 @@ fn main() {
             || s.starts_with("CRYPTO_EX_")
     });
     cfg.skip_struct(|s| {
-        s == "ProbeResult" || s == "X509_OBJECT_data" // inline union
+        s == "ProbeResult" ||
+            s == "X509_OBJECT_data" || // inline union
+            s == "PKCS7_data" || // inline union
+            s == "DIST_POINT_NAME_st_anon_union" || // inline union
+            s == "POLICYQUALINFO_st_anon_union" // inline union
     });
     cfg.skip_fn(move |s| {
         s == "CRYPTO_memcmp" ||                 // uses volatile
@@ -125,7 +144,10 @@ fn main() {
     cfg.skip_field_type(|s, field| {
         (s == "EVP_PKEY" && field == "pkey") ||      // union
             (s == "GENERAL_NAME" && field == "d") || // union
-            (s == "X509_OBJECT" && field == "data") // union
+            (s == "X509_OBJECT" && field == "data") || // union
+            (s == "PKCS7" && field == "d") || // union
+            (s == "DIST_POINT_NAME" && field == "name") || // union
+            (s == "POLICYQUALINFO" && field == "d") // union
     });
     cfg.skip_signededness(|s| {
         s.ends_with("_cb")